@@ -65,6 +65,7 @@ impl From<tari_core::blocks::Block> for grpc::Block {
                         features: Some(grpc::OutputFeatures {
                             flags: input.features.flags.bits() as u32,
                             maturity: input.features.maturity,
+                            version: input.features.version as u32,
                         }),
                         commitment: Vec::from(input.commitment.as_bytes()),
                     })
@@ -77,6 +78,7 @@ impl From<tari_core::blocks::Block> for grpc::Block {
                         features: Some(grpc::OutputFeatures {
                             flags: output.features.flags.bits() as u32,
                             maturity: output.features.maturity,
+                            version: output.features.version as u32,
                         }),
                         commitment: Vec::from(output.commitment.as_bytes()),
                         range_proof: Vec::from(output.proof.as_bytes()),
@@ -160,6 +162,7 @@ impl From<NewBlockTemplate> for grpc::NewBlockTemplate {
                         features: Some(grpc::OutputFeatures {
                             flags: input.features.flags.bits() as u32,
                             maturity: input.features.maturity,
+                            version: input.features.version as u32,
                         }),
                         commitment: Vec::from(input.commitment.as_bytes()),
                     })
@@ -172,6 +175,7 @@ impl From<NewBlockTemplate> for grpc::NewBlockTemplate {
                         features: Some(grpc::OutputFeatures {
                             flags: output.features.flags.bits() as u32,
                             maturity: output.features.maturity,
+                            version: output.features.version as u32,
                         }),
                         commitment: Vec::from(output.commitment.as_bytes()),
                         range_proof: Vec::from(output.proof.as_bytes()),
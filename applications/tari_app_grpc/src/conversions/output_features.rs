@@ -29,6 +29,7 @@ impl TryFrom<grpc::OutputFeatures> for OutputFeatures {
 
     fn try_from(features: grpc::OutputFeatures) -> Result<Self, Self::Error> {
         Ok(Self {
+            version: u8::try_from(features.version).map_err(|_| "Invalid output features version".to_string())?,
             flags: OutputFlags::from_bits(features.flags as u8)
                 .ok_or_else(|| "Invalid or unrecognised output flags".to_string())?,
             maturity: features.maturity,
@@ -48,6 +48,7 @@ impl From<TransactionInput> for grpc::TransactionInput {
             features: Some(grpc::OutputFeatures {
                 flags: input.features.flags.bits() as u32,
                 maturity: input.features.maturity,
+                version: input.features.version as u32,
             }),
             commitment: Vec::from(input.commitment.as_bytes()),
             hash,
@@ -56,6 +56,7 @@ impl From<TransactionOutput> for grpc::TransactionOutput {
             features: Some(grpc::OutputFeatures {
                 flags: output.features.flags.bits() as u32,
                 maturity: output.features.maturity,
+                version: output.features.version as u32,
             }),
             commitment: Vec::from(output.commitment.as_bytes()),
             range_proof: Vec::from(output.proof.as_bytes()),
@@ -198,6 +198,8 @@ pub fn setup_wallet_transport_type(config: &GlobalConfig) -> TransportType {
                 port_mapping,
                 socks_address_override,
                 socks_auth: socks::Authentication::None,
+                additional_services: Vec::new(),
+                key_rotation_interval: None,
             })
         },
         CommsTransport::Socks5 {
@@ -211,6 +213,7 @@ pub fn setup_wallet_transport_type(config: &GlobalConfig) -> TransportType {
             },
             listener_address,
         },
+        CommsTransport::WebSocket { listener_address } => TransportType::WebSocket { listener_address },
     }
 }
 
@@ -22,9 +22,16 @@
 
 use anyhow::anyhow;
 use log::*;
-use std::{cmp, fs, str::FromStr, sync::Arc, time::Duration};
+use std::{
+    cmp,
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+    time::Duration,
+};
 use tari_app_utilities::{identity_management, utilities};
-use tari_common::{CommsTransport, GlobalConfig, TorControlAuthentication};
+use tari_common::{CommsTransport, ConfigurationError, GlobalConfig, TorControlAuthentication};
 use tari_comms::{
     peer_manager::Peer,
     protocol::rpc::RpcServer,
@@ -69,7 +76,7 @@ use tari_p2p::{
 };
 use tari_service_framework::{ServiceHandles, StackBuilder};
 use tari_shutdown::ShutdownSignal;
-use tokio::runtime;
+use tokio::{runtime, sync::broadcast, task};
 
 const LOG_TARGET: &str = "c::bn::initialization";
 /// The minimum buffer size for the base node pubsub_connector channel
@@ -101,7 +108,7 @@ where B: BlockchainBackend + 'static
         let node_config = BaseNodeServiceConfig::default(); // TODO - make this configurable
         let mempool_config = MempoolServiceConfig::default(); // TODO - make this configurable
 
-        let comms_config = self.create_comms_config();
+        let comms_config = self.create_comms_config()?;
         let transport_type = comms_config.transport_type.clone();
 
         let sync_peers = config
@@ -178,8 +185,19 @@ where B: BlockchainBackend + 'static
         identity_management::save_as_json(&config.base_node_identity_file, &*comms.node_identity())
             .map_err(|e| anyhow!("Failed to save node identity: {:?}", e))?;
         if let Some(hs) = comms.hidden_service() {
-            identity_management::save_as_json(&config.base_node_tor_identity_file, hs.tor_identity())
+            identity_management::save_as_json(&config.base_node_tor_identity_file, &hs.tor_identity())
                 .map_err(|e| anyhow!("Failed to save tor identity: {:?}", e))?;
+            for (name, identity) in hs.additional_services() {
+                let path = additional_tor_identity_file(&config.base_node_tor_identity_file, name);
+                identity_management::save_as_json(&path, identity)
+                    .map_err(|e| anyhow!("Failed to save tor identity for '{}' onion service: {:?}", name, e))?;
+            }
+            Self::spawn_onion_key_rotation_handler(
+                hs,
+                comms.node_identity(),
+                handles.expect_handle::<Dht>(),
+                config.base_node_tor_identity_file.clone(),
+            );
         }
 
         handles.register(comms);
@@ -187,6 +205,46 @@ where B: BlockchainBackend + 'static
         Ok(handles)
     }
 
+    /// Persists the rotated tor identity and announces the node's new onion address to the network
+    fn spawn_onion_key_rotation_handler(
+        hidden_service: &tor::HiddenService,
+        node_identity: Arc<NodeIdentity>,
+        dht: Dht,
+        tor_identity_file: PathBuf,
+    )
+    {
+        let mut events = hidden_service.subscribe_events();
+        let mut dht_requester = dht.dht_requester();
+        task::spawn(async move {
+            loop {
+                match events.recv().await {
+                    Ok(tor::HiddenServiceEvent::IdentityRotated(identity)) => {
+                        if let Err(err) = identity_management::save_as_json(&tor_identity_file, &identity) {
+                            warn!(target: LOG_TARGET, "Failed to save rotated tor identity: {}", err);
+                        }
+                        match identity.try_get_onion_address() {
+                            // Comms also updates the public address, but it must be set before the join is sent
+                            Ok(address) => node_identity.set_public_address(address),
+                            Err(err) => {
+                                warn!(target: LOG_TARGET, "Rotated tor identity is invalid: {:?}", err);
+                                continue;
+                            },
+                        }
+                        if let Err(err) = dht_requester.send_join().await {
+                            warn!(
+                                target: LOG_TARGET,
+                                "Failed to announce new onion address to the network: {:?}", err
+                            );
+                        }
+                    },
+                    Ok(_) => {},
+                    Err(broadcast::RecvError::Lagged(_)) => {},
+                    Err(broadcast::RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+
     fn setup_rpc_services(
         comms: UnspawnedCommsNode,
         handles: &ServiceHandles,
@@ -211,10 +269,10 @@ where B: BlockchainBackend + 'static
         comms.add_protocol_extension(rpc_server)
     }
 
-    fn create_comms_config(&self) -> CommsConfig {
-        CommsConfig {
+    fn create_comms_config(&self) -> Result<CommsConfig, ConfigurationError> {
+        Ok(CommsConfig {
             node_identity: self.node_identity.clone(),
-            transport_type: self.create_transport_type(),
+            transport_type: self.create_transport_type()?,
             datastore_path: self.config.peer_db_path.clone(),
             peer_database_name: "peers".to_string(),
             max_concurrent_inbound_tasks: 100,
@@ -242,7 +300,9 @@ where B: BlockchainBackend + 'static
             dns_seeds: self.config.dns_seeds.clone(),
             dns_seeds_name_server: self.config.dns_seeds_name_server,
             dns_seeds_use_dnssec: self.config.dns_seeds_use_dnssec,
-        }
+            max_bandwidth_bytes_per_sec: self.config.max_bandwidth_bytes_per_sec,
+            max_peer_bandwidth_bytes_per_sec: self.config.max_peer_bandwidth_bytes_per_sec,
+        })
     }
 
     /// Creates a transport type from the given configuration
@@ -251,12 +311,12 @@ where B: BlockchainBackend + 'static
     /// `config` - The reference to the configuration in which to set up the comms stack, see [GlobalConfig]
     ///
     /// ##Returns
-    /// TransportType based on the configuration
-    fn create_transport_type(&self) -> TransportType {
+    /// TransportType based on the configuration, or an error if a tor forward address is not a socket address
+    fn create_transport_type(&self) -> Result<TransportType, ConfigurationError> {
         let config = self.config;
         debug!(target: LOG_TARGET, "Transport is set to '{:?}'", config.comms_transport);
 
-        match config.comms_transport.clone() {
+        let transport_type = match config.comms_transport.clone() {
             CommsTransport::Tcp {
                 listener_address,
                 tor_socks_address,
@@ -276,6 +336,8 @@ where B: BlockchainBackend + 'static
                 forward_address,
                 auth,
                 onion_port,
+                additional_onion_services,
+                key_rotation_interval,
             } => {
                 let identity = Some(&config.base_node_tor_identity_file)
                     .filter(|p| p.exists())
@@ -294,7 +356,31 @@ where B: BlockchainBackend + 'static
                         .unwrap()
                 );
 
-                let forward_addr = multiaddr_to_socketaddr(&forward_address).expect("Invalid tor forward address");
+                let forward_addr = multiaddr_to_socketaddr(&forward_address).map_err(|err| {
+                    ConfigurationError::new(
+                        &format!("base_node.{}.tor_forward_address", config.network),
+                        &err.to_string(),
+                    )
+                })?;
+                let additional_services = additional_onion_services
+                    .into_iter()
+                    .map(|service| {
+                        let path = additional_tor_identity_file(&config.base_node_tor_identity_file, &service.name);
+                        let forward_addr = multiaddr_to_socketaddr(&service.forward_address).map_err(|err| {
+                            ConfigurationError::new(
+                                &format!("base_node.{}.tor_additional_onion_services", config.network),
+                                &format!("Invalid forward address for onion service '{}': {}", service.name, err),
+                            )
+                        })?;
+                        Ok(tor::OnionServiceConfig {
+                            identity: Some(&path)
+                                .filter(|p| p.exists())
+                                .and_then(|p| identity_management::load_from_json::<_, TorIdentity>(p).ok()),
+                            port_mapping: (service.onion_port, forward_addr).into(),
+                            name: service.name,
+                        })
+                    })
+                    .collect::<Result<_, ConfigurationError>>()?;
                 TransportType::Tor(TorConfig {
                     control_server_addr: control_server_address,
                     control_server_auth: {
@@ -310,6 +396,8 @@ where B: BlockchainBackend + 'static
                     // TODO: make configurable
                     socks_address_override,
                     socks_auth: socks::Authentication::None,
+                    additional_services,
+                    key_rotation_interval,
                 })
             },
             CommsTransport::Socks5 {
@@ -323,6 +411,18 @@ where B: BlockchainBackend + 'static
                 },
                 listener_address,
             },
-        }
+            CommsTransport::WebSocket { listener_address } => TransportType::WebSocket { listener_address },
+        };
+        Ok(transport_type)
     }
 }
+
+/// The identity file for an additional onion service is stored alongside the node's tor identity file as
+/// `<tor identity file stem>_<service name>.json`
+fn additional_tor_identity_file(tor_identity_file: &Path, name: &str) -> PathBuf {
+    let stem = tor_identity_file
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    tor_identity_file.with_file_name(format!("{}_{}.json", stem, name))
+}
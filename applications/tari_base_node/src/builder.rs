@@ -20,7 +20,7 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use crate::bootstrap::BaseNodeBootstrapper;
+use crate::{bootstrap::BaseNodeBootstrapper, regtest::BlockGenerator};
 use log::*;
 use std::sync::Arc;
 use tari_common::{DatabaseType, GlobalConfig};
@@ -28,8 +28,15 @@ use tari_comms::{peer_manager::NodeIdentity, CommsNode};
 use tari_comms_dht::Dht;
 use tari_core::{
     base_node::{state_machine_service::states::StatusInfo, LocalNodeCommsInterface, StateMachineHandle},
-    chain_storage::{create_lmdb_database, BlockchainDatabase, BlockchainDatabaseConfig, LMDBDatabase, Validators},
-    consensus::ConsensusManagerBuilder,
+    chain_storage::{
+        create_lmdb_database,
+        create_sqlite_database,
+        BlockchainBackend,
+        BlockchainDatabase,
+        BlockchainDatabaseConfig,
+        Validators,
+    },
+    consensus::{custom_network, ConsensusFile, ConsensusManager, ConsensusManagerBuilder},
     mempool::{service::LocalMempoolService, Mempool, MempoolConfig},
     proof_of_work::randomx_factory::{RandomXConfig, RandomXFactory},
     transactions::types::CryptoFactories,
@@ -49,10 +56,12 @@ const LOG_TARGET: &str = "c::bn::initialization";
 /// communications stack, the node state machine and handles to the various services that are registered
 /// on the comms stack.
 pub struct BaseNodeContext {
-    blockchain_db: BlockchainDatabase<LMDBDatabase>,
+    blockchain_db: BlockchainDatabase<Box<dyn BlockchainBackend>>,
     base_node_comms: CommsNode,
     base_node_dht: Dht,
     base_node_handles: ServiceHandles,
+    consensus_rules: ConsensusManager,
+    factories: CryptoFactories,
 }
 
 impl BaseNodeContext {
@@ -101,7 +110,7 @@ impl BaseNodeContext {
     }
 
     /// Returns a BlockchainDatabase handle
-    pub fn blockchain_db(&self) -> BlockchainDatabase<LMDBDatabase> {
+    pub fn blockchain_db(&self) -> BlockchainDatabase<Box<dyn BlockchainBackend>> {
         self.blockchain_db.clone()
     }
 
@@ -111,6 +120,21 @@ impl BaseNodeContext {
             .expect_handle::<StateMachineHandle>()
             .get_status_info_watch()
     }
+
+    /// Returns the consensus rules of the network the node is running on
+    pub fn consensus_rules(&self) -> ConsensusManager {
+        self.consensus_rules.clone()
+    }
+
+    /// Returns a generator that produces blocks on demand, if the node is running as a regtest (LocalNet) chain
+    pub fn block_generator(&self) -> Option<BlockGenerator> {
+        let generator = BlockGenerator::new(self.local_node(), self.consensus_rules.clone(), self.factories.clone());
+        if generator.is_enabled() {
+            Some(generator)
+        } else {
+            None
+        }
+    }
 }
 
 /// Sets up and initializes the base node, creating the context and database
@@ -145,7 +169,18 @@ pub async fn configure_and_initialize_node(
         DatabaseType::LMDB(p) => {
             let backend = create_lmdb_database(&p, config.db_config.clone())?;
             build_node_context(
-                backend,
+                Box::new(backend),
+                node_identity,
+                config,
+                interrupt_signal,
+                cleanup_orphans_at_startup,
+            )
+            .await?
+        },
+        DatabaseType::Sqlite(p) => {
+            let backend = create_sqlite_database(&p)?;
+            build_node_context(
+                Box::new(backend),
                 node_identity,
                 config,
                 interrupt_signal,
@@ -157,6 +192,27 @@ pub async fn configure_and_initialize_node(
     Ok(result)
 }
 
+/// Builds the consensus rules for the configured network. On localnet, the consensus constants and genesis block of a
+/// custom network are loaded from the configured consensus and genesis block files, if any.
+pub fn build_consensus_rules(config: &GlobalConfig) -> Result<ConsensusManager, anyhow::Error> {
+    let mut builder = ConsensusManagerBuilder::new(config.network.into());
+    if let Some(path) = &config.consensus_file {
+        info!(
+            target: LOG_TARGET,
+            "Loading consensus constants from {}",
+            path.display()
+        );
+        for constants in ConsensusFile::load(path)?.into_consensus_constants()? {
+            builder = builder.with_consensus_constants(constants);
+        }
+    }
+    if let Some(path) = &config.genesis_block_file {
+        info!(target: LOG_TARGET, "Loading genesis block from {}", path.display());
+        builder = builder.with_block(custom_network::load_genesis_block(path)?);
+    }
+    Ok(builder.build())
+}
+
 /// Constructs the base node context, this includes setting up the consensus manager, mempool, base node
 /// and state machine
 /// ## Parameters
@@ -169,7 +225,7 @@ pub async fn configure_and_initialize_node(
 /// ## Returns
 /// Result containing the BaseNodeContext, String will contain the reason on error
 async fn build_node_context(
-    backend: LMDBDatabase,
+    backend: Box<dyn BlockchainBackend>,
     base_node_identity: Arc<NodeIdentity>,
     config: &GlobalConfig,
     interrupt_signal: ShutdownSignal,
@@ -178,7 +234,7 @@ async fn build_node_context(
 {
     //---------------------------------- Blockchain --------------------------------------------//
 
-    let rules = ConsensusManagerBuilder::new(config.network.into()).build();
+    let rules = build_consensus_rules(config)?;
     let factories = CryptoFactories::default();
     let randomx_factory = RandomXFactory::new(RandomXConfig::default(), config.max_randomx_vms);
     let validators = Validators::new(
@@ -193,7 +249,11 @@ async fn build_node_context(
     };
     let blockchain_db = BlockchainDatabase::new(backend, &rules, validators, db_config, cleanup_orphans_at_startup)?;
     let mempool_validator = MempoolValidator::new(vec![
-        Box::new(TxInternalConsistencyValidator::new(factories.clone())),
+        Box::new(TxInternalConsistencyValidator::new(
+            blockchain_db.clone(),
+            rules.clone(),
+            factories.clone(),
+        )),
         Box::new(TxInputAndMaturityValidator::new(blockchain_db.clone())),
     ]);
     let mempool = Mempool::new(MempoolConfig::default(), Arc::new(mempool_validator));
@@ -221,5 +281,7 @@ async fn build_node_context(
         base_node_comms,
         base_node_dht,
         base_node_handles,
+        consensus_rules: rules,
+        factories,
     })
 }
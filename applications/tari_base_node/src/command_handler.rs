@@ -21,7 +21,7 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use super::LOG_TARGET;
-use crate::{builder::BaseNodeContext, table::Table, utils::format_duration_basic};
+use crate::{builder::BaseNodeContext, regtest::BlockGenerator, table::Table, utils::format_duration_basic};
 use chrono::{DateTime, Utc};
 use log::*;
 use regex::Regex;
@@ -34,20 +34,22 @@ use std::{
 use tari_comms::{
     connectivity::ConnectivityRequester,
     peer_manager::{NodeId, Peer, PeerFeatures, PeerManager, PeerManagerError, PeerQuery},
+    BandwidthStats,
     NodeIdentity,
 };
 use tari_comms_dht::{envelope::NodeDestination, DhtDiscoveryRequester, MetricsCollectorHandle};
 use tari_core::{
     base_node::{
+        comms_interface::BlockEvent,
         state_machine_service::states::{PeerMetadata, StatusInfo},
         LocalNodeCommsInterface,
     },
     blocks::BlockHeader,
-    chain_storage::{async_db::AsyncBlockchainDb, ChainHeader, LMDBDatabase},
+    chain_storage::{async_db::AsyncBlockchainDb, BlockchainBackend, ChainHeader},
     mempool::service::LocalMempoolService,
     proof_of_work::PowAlgorithm,
     tari_utilities::{hex::Hex, message_format::MessageFormat},
-    transactions::types::{Commitment, HashOutput, Signature},
+    transactions::types::{Commitment, HashOutput, PublicKey, Signature},
 };
 use tari_crypto::ristretto::RistrettoPublicKey;
 use tari_wallet::util::emoji::EmojiId;
@@ -55,9 +57,13 @@ use tokio::{runtime, sync::watch};
 // Import the auto-generated const values from the Manifest and Git
 
 include!(concat!(env!("OUT_DIR"), "/consts.rs"));
+
+/// The default number of blocks between chain balance checks when verifying the blockchain database
+pub const DEFAULT_BALANCE_CHECK_INTERVAL: u64 = 1000;
+
 pub struct CommandHandler {
     executor: runtime::Handle,
-    blockchain_db: AsyncBlockchainDb<LMDBDatabase>,
+    blockchain_db: AsyncBlockchainDb<Box<dyn BlockchainBackend>>,
     discovery_service: DhtDiscoveryRequester,
     dht_metrics_collector: MetricsCollectorHandle,
     base_node_identity: Arc<NodeIdentity>,
@@ -66,6 +72,7 @@ pub struct CommandHandler {
     node_service: LocalNodeCommsInterface,
     mempool_service: LocalMempoolService,
     state_machine_info: watch::Receiver<StatusInfo>,
+    block_generator: Option<BlockGenerator>,
 }
 
 impl CommandHandler {
@@ -81,6 +88,7 @@ impl CommandHandler {
             node_service: ctx.local_node(),
             mempool_service: ctx.local_mempool(),
             state_machine_info: ctx.get_state_machine_info_channel(),
+            block_generator: ctx.block_generator(),
         }
     }
 
@@ -350,6 +358,7 @@ impl CommandHandler {
                     });
                     println!("User agent: {}", peer.user_agent);
                     println!("Features: {:?}", peer.features);
+                    println!("Reputation: {}", peer.reputation());
                     println!("Supported protocols:");
                     peer.supported_protocols.iter().for_each(|p| {
                         println!("- {}", String::from_utf8_lossy(p));
@@ -591,7 +600,10 @@ impl CommandHandler {
                         "Role",
                         "User Agent",
                         "Chain Height",
+                        "Bytes In",
+                        "Bytes Out",
                     ]);
+                    let mut total_bandwidth = BandwidthStats::default();
                     for conn in conns {
                         let peer = peer_manager
                             .find_by_node_id(conn.peer_node_id())
@@ -607,6 +619,9 @@ impl CommandHandler {
                             None
                         };
 
+                        let bandwidth = conn.bandwidth_stats();
+                        total_bandwidth = total_bandwidth + bandwidth;
+
                         table.add_row(row![
                             peer.node_id,
                             peer.public_key,
@@ -624,12 +639,14 @@ impl CommandHandler {
                                 .map(|ua| if ua.is_empty() { "<unknown>".to_string() } else { ua })
                                 .unwrap(),
                             chain_height.unwrap_or_default(),
+                            bandwidth.bytes_in,
+                            bandwidth.bytes_out,
                         ]);
                     }
 
                     table.print_std();
 
-                    println!("{} active connection(s)", num_connections);
+                    println!("{} active connection(s) ({})", num_connections, total_bandwidth);
                 },
                 Err(err) => {
                     println!("Failed to list connections: {:?}", err);
@@ -692,7 +709,7 @@ impl CommandHandler {
 
     /// Function to process the get-headers command
     async fn get_headers(
-        blockchain_db: &AsyncBlockchainDb<LMDBDatabase>,
+        blockchain_db: &AsyncBlockchainDb<Box<dyn BlockchainBackend>>,
         start: u64,
         end: Option<u64>,
     ) -> Result<Vec<BlockHeader>, anyhow::Error>
@@ -711,7 +728,7 @@ impl CommandHandler {
 
     /// Function to process the get-headers command
     async fn get_chain_headers(
-        blockchain_db: &AsyncBlockchainDb<LMDBDatabase>,
+        blockchain_db: &AsyncBlockchainDb<Box<dyn BlockchainBackend>>,
         start: u64,
         end: Option<u64>,
     ) -> Result<Vec<ChainHeader>, anyhow::Error>
@@ -803,6 +820,35 @@ impl CommandHandler {
         });
     }
 
+    pub fn verify_db(&self, balance_check_interval: u64, repair: bool) {
+        let db = self.blockchain_db.clone();
+        self.executor.spawn(async move {
+            verify_blockchain_db(db, balance_check_interval, repair).await;
+        });
+    }
+
+    pub fn rewind_blockchain(&self, new_height: u64) {
+        let db = self.blockchain_db.clone();
+        let node = self.node_service.clone();
+        self.executor.spawn(async move {
+            match db.rewind_to_block_height(new_height, true).await {
+                Ok((previous_height, removed_blocks)) => {
+                    println!(
+                        "Rewound the blockchain from height {} to {}, removing {} block(s)",
+                        previous_height,
+                        new_height,
+                        removed_blocks.len()
+                    );
+                    // Let the mempool take back the transactions of the removed blocks
+                    node.publish_block_event(BlockEvent::BlockSyncRewind(removed_blocks));
+                },
+                Err(err) => {
+                    println!("Could not rewind the blockchain to height {}: {}", new_height, err);
+                },
+            }
+        });
+    }
+
     #[allow(deprecated)]
     pub fn period_stats(&self, period_end: u64, mut period_ticker_end: u64, period: u64) {
         let mut node = self.node_service.clone();
@@ -966,6 +1012,100 @@ impl CommandHandler {
     pub fn whoami(&self) {
         println!("{}", self.base_node_identity);
     }
+
+    /// Function to process the generate-blocks command
+    pub fn generate_blocks(&self, num_blocks: u64, wallet_public_key: Option<PublicKey>) {
+        let mut generator = match self.block_generator.clone() {
+            Some(generator) => generator,
+            None => {
+                println!("Blocks can only be generated when the base node is running in regtest mode (--regtest)");
+                return;
+            },
+        };
+        self.executor.spawn(async move {
+            match generator.generate_blocks(num_blocks, wallet_public_key).await {
+                Ok(block_hashes) => {
+                    println!("Generated {} block(s):", block_hashes.len());
+                    for hash in block_hashes {
+                        println!("{}", hash.to_hex());
+                    }
+                },
+                Err(err) => {
+                    println!("Failed to generate blocks: {}", err);
+                    warn!(target: LOG_TARGET, "Error generating blocks: {}", err);
+                },
+            }
+        });
+    }
+}
+
+/// Verifies the integrity of the blockchain database and prints the result. If `repair` is set and an inconsistency is
+/// found, the blockchain is rewound to the last consistent height. Returns true if the database is consistent after the
+/// verification (and repair, if requested).
+pub async fn verify_blockchain_db(
+    db: AsyncBlockchainDb<Box<dyn BlockchainBackend>>,
+    balance_check_interval: u64,
+    repair: bool,
+) -> bool
+{
+    println!("Verifying the blockchain database, this may take a while...");
+    let report = match db.verify_integrity(balance_check_interval).await {
+        Ok(report) => report,
+        Err(err) => {
+            println!("Could not verify the blockchain database: {}", err);
+            return false;
+        },
+    };
+
+    if let Some(last_verified_height) = report.last_verified_height {
+        println!(
+            "Verified blocks {} to {}, chain balance checked at {} height(s)",
+            report.start_height,
+            last_verified_height,
+            report.balance_checked_heights.len()
+        );
+    }
+    let inconsistency = match report.inconsistency {
+        Some(inconsistency) => inconsistency,
+        None => {
+            println!(
+                "The blockchain database is consistent up to the tip at height {}",
+                report.tip_height
+            );
+            return true;
+        },
+    };
+    println!(
+        "The blockchain database is inconsistent at height {}: {}",
+        inconsistency.height, inconsistency.error
+    );
+    if !repair {
+        println!(
+            "Run `verify-db {} repair` to rewind the blockchain to the last consistent height",
+            balance_check_interval
+        );
+        return false;
+    }
+
+    if inconsistency.height == 0 {
+        println!("Cannot repair the blockchain database: the genesis block is inconsistent");
+        return false;
+    }
+    let rewind_height = inconsistency.height - 1;
+    match db.rewind_to_block_height(rewind_height, true).await {
+        Ok((_, removed_blocks)) => {
+            println!(
+                "Rewound the blockchain to height {}, removing {} block(s)",
+                rewind_height,
+                removed_blocks.len()
+            );
+            true
+        },
+        Err(err) => {
+            println!("Could not rewind the blockchain to height {}: {}", rewind_height, err);
+            false
+        },
+    }
 }
 
 async fn banned_peers(pm: &PeerManager) -> Result<Vec<Peer>, PeerManagerError> {
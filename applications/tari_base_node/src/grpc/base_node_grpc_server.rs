@@ -19,9 +19,12 @@
 // SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
-use crate::grpc::{
-    blocks::{block_fees, block_heights, block_size, GET_BLOCKS_MAX_HEIGHTS, GET_BLOCKS_PAGE_SIZE},
-    helpers::{mean, median},
+use crate::{
+    grpc::{
+        blocks::{block_fees, block_heights, block_size, GET_BLOCKS_MAX_HEIGHTS, GET_BLOCKS_PAGE_SIZE},
+        helpers::{mean, median},
+    },
+    regtest::{BlockGenerator, GENERATE_BLOCKS_MAX_BLOCKS},
 };
 use log::*;
 use std::{
@@ -34,7 +37,6 @@ use tari_app_grpc::{
     tari_rpc,
     tari_rpc::{CalcType, Sorting},
 };
-use tari_common::GlobalConfig;
 use tari_comms::PeerManager;
 use tari_core::{
     base_node::{
@@ -44,13 +46,16 @@ use tari_core::{
         StateMachineHandle,
     },
     blocks::{Block, BlockHeader, NewBlockTemplate},
-    consensus::{ConsensusManager, ConsensusManagerBuilder, Network},
+    consensus::ConsensusManager,
     crypto::tari_utilities::hex::Hex,
     mempool::{service::LocalMempoolService, TxStorageResponse},
     proof_of_work::PowAlgorithm,
-    transactions::{transaction::Transaction, types::Signature},
+    transactions::{
+        transaction::Transaction,
+        types::{PublicKey, Signature},
+    },
 };
-use tari_crypto::tari_utilities::{message_format::MessageFormat, Hashable};
+use tari_crypto::tari_utilities::{message_format::MessageFormat, ByteArray, Hashable};
 use tokio::{runtime, sync::mpsc};
 use tonic::{Request, Response, Status};
 
@@ -77,10 +82,10 @@ pub struct BaseNodeGrpcServer {
     executor: runtime::Handle,
     node_service: LocalNodeCommsInterface,
     mempool_service: LocalMempoolService,
-    node_config: GlobalConfig,
     state_machine_handle: StateMachineHandle,
     peer_manager: Arc<PeerManager>,
     consensus_rules: ConsensusManager,
+    block_generator: Option<BlockGenerator>,
 }
 
 impl BaseNodeGrpcServer {
@@ -88,19 +93,20 @@ impl BaseNodeGrpcServer {
         executor: runtime::Handle,
         local_node: LocalNodeCommsInterface,
         local_mempool: LocalMempoolService,
-        node_config: GlobalConfig,
         state_machine_handle: StateMachineHandle,
         peer_manager: Arc<PeerManager>,
+        consensus_rules: ConsensusManager,
+        block_generator: Option<BlockGenerator>,
     ) -> Self
     {
         Self {
             executor,
             node_service: local_node,
             mempool_service: local_mempool,
-            consensus_rules: ConsensusManager::builder(node_config.network.into()).build(),
-            node_config,
+            consensus_rules,
             state_machine_handle,
             peer_manager,
+            block_generator,
         }
     }
 }
@@ -500,6 +506,44 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
         Ok(Response::new(tari_rpc::SubmitBlockResponse { block_hash }))
     }
 
+    async fn generate_blocks(
+        &self,
+        request: Request<tari_rpc::GenerateBlocksRequest>,
+    ) -> Result<Response<tari_rpc::GenerateBlocksResponse>, Status>
+    {
+        let request = request.into_inner();
+        debug!(
+            target: LOG_TARGET,
+            "Incoming GRPC request to generate {} block(s)", request.num_blocks
+        );
+        if request.num_blocks == 0 || request.num_blocks > GENERATE_BLOCKS_MAX_BLOCKS {
+            return Err(Status::invalid_argument(format!(
+                "Between 1 and {} blocks can be generated at a time",
+                GENERATE_BLOCKS_MAX_BLOCKS
+            )));
+        }
+        let mut generator = self
+            .block_generator
+            .clone()
+            .ok_or_else(|| Status::failed_precondition("Blocks can only be generated in regtest mode"))?;
+        let wallet_public_key = if request.wallet_public_key.is_empty() {
+            None
+        } else {
+            Some(
+                PublicKey::from_bytes(&request.wallet_public_key)
+                    .map_err(|e| Status::invalid_argument(format!("Invalid wallet public key: {}", e)))?,
+            )
+        };
+
+        let block_hashes = generator
+            .generate_blocks(request.num_blocks, wallet_public_key)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+
+        debug!(target: LOG_TARGET, "Sending GenerateBlocks response to client");
+        Ok(Response::new(tari_rpc::GenerateBlocksResponse { block_hashes }))
+    }
+
     async fn submit_transaction(
         &self,
         request: Request<tari_rpc::SubmitTransactionRequest>,
@@ -534,6 +578,7 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
                 }
             },
             TxStorageResponse::NotStored |
+            TxStorageResponse::NotStoredInvalid |
             TxStorageResponse::NotStoredOrphan |
             TxStorageResponse::NotStoredTimeLocked => tari_rpc::SubmitTransactionResponse {
                 result: tari_rpc::SubmitTransactionResult::Rejected.into(),
@@ -601,6 +646,7 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
                 }
             },
             TxStorageResponse::NotStored |
+            TxStorageResponse::NotStoredInvalid |
             TxStorageResponse::NotStoredOrphan |
             TxStorageResponse::NotStoredTimeLocked => tari_rpc::TransactionStateResponse {
                 result: tari_rpc::TransactionLocation::NotStored.into(),
@@ -884,11 +930,10 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
     ) -> Result<Response<tari_rpc::ConsensusConstants>, Status>
     {
         debug!(target: LOG_TARGET, "Incoming GRPC request for GetConstants",);
-        let network: Network = self.node_config.network.into();
         debug!(target: LOG_TARGET, "Sending GetConstants response to client");
         // TODO: Switch to request height
         Ok(Response::new(
-            network.create_consensus_constants().pop().unwrap().into(),
+            self.consensus_rules.consensus_constants(u64::MAX).clone().into(),
         ))
     }
 
@@ -923,8 +968,7 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
         heights = heights
             .drain(..cmp::min(heights.len(), GET_TOKENS_IN_CIRCULATION_MAX_HEIGHTS))
             .collect();
-        let network: Network = self.node_config.network.into();
-        let consensus_manager = ConsensusManagerBuilder::new(network).build();
+        let consensus_manager = self.consensus_rules.clone();
         // let constants = network.create_consensus_constants().pop().unwrap();
         let (mut tx, rx) = mpsc::channel(GET_TOKENS_IN_CIRCULATION_PAGE_SIZE);
         self.executor.spawn(async move {
@@ -71,11 +71,15 @@
 /// `list-connections` - Lists active connections to this Base Node
 /// `list-headers` - Lists header information. Either the first header height and the last header height needs to be
 /// specified, or the amount of headers from the top `check-db` - Checks the blockchain database for missing blocks and
-/// headers `calc-timing` - Calculates the time average time taken to mine a given range of blocks
+/// headers `verify-db` - Replays the blockchain database, recomputing MMR roots, accumulated difficulty and kernel
+/// offsets and checking the chain balance, and reports the first inconsistent height
+/// `rewind-blockchain` - Rewinds the blockchain to the given height, removing all blocks and headers above it
+/// `calc-timing` - Calculates the time average time taken to mine a given range of blocks
 /// `discover-peer` - Attempts to discover a peer on the network, a public key or emoji id needs to be specified
 /// `get-block` - Retrieves a block, the height of the block needs to be specified
 /// `get-mempool-stats` - Displays information about the mempool
 /// `get-mempool-state` - Displays state information for the mempool
+/// `generate-blocks` - Generates blocks on demand in regtest mode, optionally paying the rewards to a wallet
 /// `whoami` - Displays identity information about this Base Node and it's wallet
 /// `quit` - Exits the Base Node
 /// `exit` - Same as quit
@@ -91,6 +95,7 @@ mod command_handler;
 mod grpc;
 mod parser;
 mod recovery;
+mod regtest;
 mod utils;
 
 use crate::command_handler::CommandHandler;
@@ -126,7 +131,15 @@ fn main() {
 
 /// Sets up the base node and runs the cli_loop
 fn main_inner() -> Result<(), ExitCodes> {
-    let (bootstrap, node_config, _) = init_configuration(ApplicationType::BaseNode)?;
+    let (bootstrap, mut node_config, _) = init_configuration(ApplicationType::BaseNode)?;
+
+    if bootstrap.regtest {
+        // A regtest chain is self-contained, so we never try to reach any seed peers
+        info!(target: LOG_TARGET, "Running in regtest mode, seed peers are disabled");
+        node_config.peer_seeds.clear();
+        node_config.dns_seeds.clear();
+        node_config.force_sync_peers.clear();
+    }
 
     debug!(target: LOG_TARGET, "Using configuration: {:?}", node_config);
 
@@ -187,14 +200,29 @@ fn main_inner() -> Result<(), ExitCodes> {
             rt.handle().clone(),
             ctx.local_node(),
             ctx.local_mempool(),
-            node_config.clone(),
             ctx.state_machine(),
             ctx.base_node_comms().peer_manager(),
+            ctx.consensus_rules(),
+            ctx.block_generator(),
         );
 
         rt.spawn(run_grpc(grpc, node_config.grpc_base_node_address, shutdown.to_signal()));
     }
 
+    if bootstrap.verify_db {
+        info!(target: LOG_TARGET, "Verifying the blockchain database");
+        let is_consistent = rt.block_on(command_handler::verify_blockchain_db(
+            ctx.blockchain_db().into(),
+            command_handler::DEFAULT_BALANCE_CHECK_INTERVAL,
+            bootstrap.repair_db,
+        ));
+        if !is_consistent {
+            return Err(ExitCodes::CommandError(
+                "The blockchain database is inconsistent. Run with `--verify-db --repair-db` to repair it".to_string(),
+            ));
+        }
+    }
+
     // Run, node, run!
     let base_node_handle;
     let command_handler = Arc::new(CommandHandler::new(rt.handle().clone(), &ctx));
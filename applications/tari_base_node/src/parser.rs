@@ -21,7 +21,10 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use super::LOG_TARGET;
-use crate::command_handler::{delimit_command_string, CommandHandler, Format};
+use crate::{
+    command_handler::{delimit_command_string, CommandHandler, Format, DEFAULT_BALANCE_CHECK_INTERVAL},
+    regtest::GENERATE_BLOCKS_MAX_BLOCKS,
+};
 use futures::future::Either;
 use log::*;
 use rustyline::{
@@ -66,6 +69,8 @@ pub enum BaseNodeCommand {
     ListConnections,
     ListHeaders,
     CheckDb,
+    VerifyDb,
+    RewindBlockchain,
     PeriodStats,
     HeaderStats,
     CalcTiming,
@@ -76,6 +81,7 @@ pub enum BaseNodeCommand {
     SearchStxo,
     GetMempoolStats,
     GetMempoolState,
+    GenerateBlocks,
     Whoami,
     GetStateInfo,
     Quit,
@@ -198,6 +204,12 @@ impl Parser {
             CheckDb => {
                 self.command_handler.check_db();
             },
+            VerifyDb => {
+                self.process_verify_db(args);
+            },
+            RewindBlockchain => {
+                self.process_rewind_blockchain(args);
+            },
             PeriodStats => {
                 self.process_period_stats(args);
             },
@@ -243,6 +255,9 @@ impl Parser {
             GetMempoolState => {
                 self.command_handler.get_mempool_state();
             },
+            GenerateBlocks => {
+                self.process_generate_blocks(args);
+            },
             Whoami => {
                 self.command_handler.whoami();
             },
@@ -308,6 +323,25 @@ impl Parser {
             CheckDb => {
                 println!("Checks the blockchain database for missing blocks and headers");
             },
+            VerifyDb => {
+                println!(
+                    "Replays the stored blockchain, recomputing the MMR roots, accumulated difficulty and total \
+                     kernel offset of every block and checking the chain balance at selected heights. Reports the \
+                     first inconsistent height."
+                );
+                println!("verify-db [chain balance check interval] [repair]");
+                println!(
+                    "If `repair` is given, the blockchain is rewound to the last consistent height when an \
+                     inconsistency is found"
+                );
+            },
+            RewindBlockchain => {
+                println!(
+                    "Rewinds the blockchain to the given height, removing all blocks and headers above it. The \
+                     outputs spent by the removed blocks are restored and the node will sync from the new tip."
+                );
+                println!("rewind-blockchain [height]");
+            },
             HeaderStats => {
                 println!(
                     "Prints out certain stats to of the block chain in csv format for easy copy, use as follows: "
@@ -325,7 +359,10 @@ impl Parser {
                 );
             },
             ListConnections => {
-                println!("Lists the peer connections currently held by this node");
+                println!(
+                    "Lists the peer connections currently held by this node, including the bytes sent and received on \
+                     each"
+                );
             },
             ListHeaders => {
                 println!("List the amount of headers, can be called in the following two ways: ");
@@ -374,6 +411,13 @@ impl Parser {
             GetMempoolState => {
                 println!("Retrieves your mempools state");
             },
+            GenerateBlocks => {
+                println!(
+                    "Generates blocks on demand when running in regtest mode. If a wallet public key or emoji id is \
+                     given, the block rewards are paid to that wallet as one-sided payments."
+                );
+                println!("generate-blocks [number of blocks] [wallet public key or emoji id]");
+            },
             Whoami => {
                 println!(
                     "Display identity information about this node, including: public key, node ID and the public \
@@ -577,6 +621,34 @@ impl Parser {
         self.command_handler.ban_peer(node_id, duration, must_ban)
     }
 
+    /// Function to process the generate-blocks command
+    fn process_generate_blocks<'a, I: Iterator<Item = &'a str>>(&self, mut args: I) {
+        let num_blocks = match args.next().map(u64::from_str).map(Result::ok).flatten() {
+            Some(n) if n > 0 && n <= GENERATE_BLOCKS_MAX_BLOCKS => n,
+            _ => {
+                println!(
+                    "Please enter a valid number of blocks to generate, up to {}",
+                    GENERATE_BLOCKS_MAX_BLOCKS
+                );
+                println!("generate-blocks [number of blocks] [wallet public key or emoji id]");
+                return;
+            },
+        };
+        let wallet_public_key = match args.next() {
+            Some(s) => match parse_emoji_id_or_public_key(s) {
+                Some(public_key) => Some(public_key),
+                None => {
+                    println!("Please enter a valid wallet public key or emoji id");
+                    println!("generate-blocks [number of blocks] [wallet public key or emoji id]");
+                    return;
+                },
+            },
+            None => None,
+        };
+
+        self.command_handler.generate_blocks(num_blocks, wallet_public_key)
+    }
+
     /// Function to process the list-headers command
     fn process_list_headers<'a, I: Iterator<Item = &'a str>>(&self, mut args: I) {
         let start = args.next().map(u64::from_str).map(Result::ok).flatten();
@@ -591,6 +663,40 @@ impl Parser {
         self.command_handler.list_headers(start, end)
     }
 
+    /// Function to process the verify-db command
+    fn process_verify_db<'a, I: Iterator<Item = &'a str>>(&self, args: I) {
+        let mut balance_check_interval = DEFAULT_BALANCE_CHECK_INTERVAL;
+        let mut repair = false;
+        for arg in args {
+            if arg == "repair" {
+                repair = true;
+                continue;
+            }
+            match u64::from_str(arg) {
+                Ok(interval) => balance_check_interval = interval,
+                Err(_) => {
+                    println!("Command entered incorrectly, please use the following format: ");
+                    println!("verify-db [chain balance check interval] [repair]");
+                    return;
+                },
+            }
+        }
+        self.command_handler.verify_db(balance_check_interval, repair)
+    }
+
+    /// Function to process the rewind-blockchain command
+    fn process_rewind_blockchain<'a, I: Iterator<Item = &'a str>>(&self, mut args: I) {
+        let new_height = match args.next().map(u64::from_str).map(Result::ok).flatten() {
+            Some(height) => height,
+            None => {
+                println!("Please enter a valid height to rewind to");
+                println!("rewind-blockchain [height]");
+                return;
+            },
+        };
+        self.command_handler.rewind_blockchain(new_height)
+    }
+
     /// Function to process the calc-timing command
     fn process_calc_timing<'a, I: Iterator<Item = &'a str>>(&self, mut args: I) {
         let start = args.next().map(u64::from_str).map(Result::ok).flatten();
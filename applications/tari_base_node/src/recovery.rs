@@ -21,6 +21,7 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 //
 
+use crate::builder::build_consensus_rules;
 use anyhow::anyhow;
 use log::*;
 use std::{
@@ -91,7 +92,7 @@ pub async fn run_recovery(node_config: &GlobalConfig) -> Result<(), anyhow::Erro
             return Err(anyhow!("Recovery mode is only available for LMDB"));
         },
     };
-    let rules = ConsensusManagerBuilder::new(node_config.network.into()).build();
+    let rules = build_consensus_rules(node_config)?;
     let factories = CryptoFactories::default();
     let randomx_factory = RandomXFactory::new(RandomXConfig::default(), node_config.max_randomx_vms);
     let validators = Validators::new(
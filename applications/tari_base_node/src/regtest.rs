@@ -0,0 +1,266 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Block generation for the regtest developer chain.
+//!
+//! When the base node runs on LocalNet (`--regtest`), the difficulty is trivial and no peers are required, so blocks
+//! can be produced on demand instead of relying on an external miner. Each block is built from a new block template
+//! with a coinbase that either pays a wallet, as a one-sided payment its UTXO scanner can detect, or is burnt to a
+//! random key. The nonce of a one-sided coinbase is discarded once the block is built, so the node does not keep the
+//! coinbase's commitment key; the wallet claims the coinbase into a key held by its signer once the coinbase matures.
+
+use log::*;
+use rand::rngs::OsRng;
+use tari_core::{
+    base_node::{
+        comms_interface::{Broadcast, CommsInterfaceError},
+        LocalNodeCommsInterface,
+    },
+    consensus::{ConsensusManager, Network},
+    proof_of_work::{sha3_difficulty, PowAlgorithm},
+    transactions::{
+        transaction_protocol::one_sided::OneSidedPaymentKeys,
+        types::{CryptoFactories, HashOutput, PrivateKey, PublicKey},
+        CoinbaseBuildError,
+        CoinbaseBuilder,
+    },
+};
+use tari_crypto::keys::SecretKey;
+use thiserror::Error;
+
+const LOG_TARGET: &str = "base_node::app::regtest";
+/// The maximum number of blocks that can be generated by a single request
+pub const GENERATE_BLOCKS_MAX_BLOCKS: u64 = 1_000;
+
+#[derive(Debug, Error)]
+pub enum BlockGeneratorError {
+    #[error("Blocks can only be generated on demand on the localnet (regtest) network, not on {0:?}")]
+    NotRegtest(Network),
+    #[error("Between 1 and {max} blocks can be generated at a time, {requested} were requested")]
+    InvalidNumBlocks { requested: u64, max: u64 },
+    #[error("Base node request failed: {0}")]
+    CommsInterfaceError(#[from] CommsInterfaceError),
+    #[error("Could not build the coinbase: {0}")]
+    CoinbaseBuildError(#[from] CoinbaseBuildError),
+    #[error("Could not derive the one-sided payment keys for the wallet")]
+    KeyDerivationFailed,
+}
+
+/// Produces blocks on demand for the regtest developer chain
+#[derive(Clone)]
+pub struct BlockGenerator {
+    node_service: LocalNodeCommsInterface,
+    consensus_rules: ConsensusManager,
+    factories: CryptoFactories,
+}
+
+impl BlockGenerator {
+    pub fn new(
+        node_service: LocalNodeCommsInterface,
+        consensus_rules: ConsensusManager,
+        factories: CryptoFactories,
+    ) -> Self
+    {
+        Self {
+            node_service,
+            consensus_rules,
+            factories,
+        }
+    }
+
+    /// Returns true if the node's network allows blocks to be generated on demand
+    pub fn is_enabled(&self) -> bool {
+        self.consensus_rules.network() == Network::LocalNet
+    }
+
+    /// Generate, mine and submit `num_blocks` blocks, up to `GENERATE_BLOCKS_MAX_BLOCKS`, on top of the current tip. If
+    /// a wallet public key is given, the coinbase of every block is paid to that wallet as a one-sided payment.
+    /// Returns the hashes of the new blocks.
+    pub async fn generate_blocks(
+        &mut self,
+        num_blocks: u64,
+        wallet_public_key: Option<PublicKey>,
+    ) -> Result<Vec<HashOutput>, BlockGeneratorError>
+    {
+        if !self.is_enabled() {
+            return Err(BlockGeneratorError::NotRegtest(self.consensus_rules.network()));
+        }
+        if num_blocks == 0 || num_blocks > GENERATE_BLOCKS_MAX_BLOCKS {
+            return Err(BlockGeneratorError::InvalidNumBlocks {
+                requested: num_blocks,
+                max: GENERATE_BLOCKS_MAX_BLOCKS,
+            });
+        }
+
+        let mut block_hashes = Vec::with_capacity(num_blocks as usize);
+        for _ in 0..num_blocks {
+            let block_hash = self.generate_block(wallet_public_key.as_ref()).await?;
+            block_hashes.push(block_hash);
+        }
+        Ok(block_hashes)
+    }
+
+    async fn generate_block(
+        &mut self,
+        wallet_public_key: Option<&PublicKey>,
+    ) -> Result<HashOutput, BlockGeneratorError>
+    {
+        let mut template = self.node_service.get_new_block_template(PowAlgorithm::Sha3, 0).await?;
+        let height = template.header.height;
+
+        let nonce = PrivateKey::random(&mut OsRng);
+        let coinbase_builder = CoinbaseBuilder::new(self.factories.clone())
+            .with_block_height(height)
+            .with_fees(template.total_fees)
+            .with_nonce(nonce.clone());
+        let coinbase_builder = match wallet_public_key {
+            Some(public_key) => {
+                let keys = OneSidedPaymentKeys::for_sender(&nonce, public_key)
+                    .map_err(|_| BlockGeneratorError::KeyDerivationFailed)?;
+                coinbase_builder
+                    .with_spend_key(keys.spending_key)
+                    .with_rewind_data(keys.rewind_data)
+            },
+            None => coinbase_builder.with_spend_key(PrivateKey::random(&mut OsRng)),
+        };
+        let (coinbase, _) =
+            coinbase_builder.build_with_reward(self.consensus_rules.consensus_constants(height), template.reward)?;
+        for output in coinbase.body.outputs() {
+            template.body.add_output(output.clone());
+        }
+        for kernel in coinbase.body.kernels() {
+            template.body.add_kernel(kernel.clone());
+        }
+
+        let target_difficulty = template.target_difficulty;
+        let mut block = self.node_service.get_new_block(template).await?;
+        while sha3_difficulty(&block.header) < target_difficulty {
+            block.header.nonce = block.header.nonce.wrapping_add(1);
+        }
+
+        let block_hash = self.node_service.submit_block(block, Broadcast::from(true)).await?;
+        debug!(target: LOG_TARGET, "Generated regtest block #{}", height);
+        Ok(block_hash)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use futures::{channel::mpsc, StreamExt};
+    use std::sync::Arc;
+    use tari_core::{
+        base_node::{comms_interface::InboundNodeCommsHandlers, OutboundNodeCommsInterface},
+        chain_storage::BlockchainDatabase,
+        consensus::ConsensusManagerBuilder,
+        mempool::{Mempool, MempoolConfig},
+        test_helpers::blockchain::{create_store_with_consensus, TempDatabase},
+        validation::mocks::MockValidator,
+    };
+    use tari_crypto::keys::PublicKey as PublicKeyTrait;
+    use tari_service_framework::reply_channel;
+    use tokio::{sync::broadcast, task};
+
+    /// Create a block generator backed by a base node with an empty blockchain on `network`. Requests are handled by
+    /// the inbound handlers of the node directly.
+    fn setup(network: Network) -> (BlockGenerator, BlockchainDatabase<TempDatabase>) {
+        let consensus_rules = ConsensusManagerBuilder::new(network).build();
+        let store = create_store_with_consensus(&consensus_rules);
+        let mempool = Mempool::new(MempoolConfig::default(), Arc::new(MockValidator::new(true)));
+        let (block_event_sender, _) = broadcast::channel(50);
+        let (outbound_request_sender, _) = reply_channel::unbounded();
+        let (outbound_block_sender, mut outbound_block_receiver) = mpsc::unbounded();
+        let inbound_nch = InboundNodeCommsHandlers::new(
+            block_event_sender.clone(),
+            store.clone().into(),
+            mempool,
+            consensus_rules.clone(),
+            OutboundNodeCommsInterface::new(outbound_request_sender, outbound_block_sender),
+        );
+
+        let (request_sender, mut request_receiver) = reply_channel::unbounded();
+        let (block_sender, mut block_receiver) = reply_channel::unbounded();
+        let inbound_nch_clone = inbound_nch.clone();
+        task::spawn(async move {
+            while let Some(request_context) = request_receiver.next().await {
+                let (request, reply_tx) = request_context.split();
+                let _ = reply_tx.send(inbound_nch_clone.handle_request(request).await);
+            }
+        });
+        task::spawn(async move {
+            while let Some(block_context) = block_receiver.next().await {
+                let ((block, broadcast), reply_tx) = block_context.split();
+                let _ = reply_tx.send(inbound_nch.handle_block(Arc::new(block), broadcast, None).await);
+            }
+        });
+        // Blocks propagated to peers are dropped
+        task::spawn(async move { while outbound_block_receiver.next().await.is_some() {} });
+
+        let node_service = LocalNodeCommsInterface::new(request_sender, block_sender, block_event_sender);
+        let generator = BlockGenerator::new(node_service, consensus_rules, CryptoFactories::default());
+        (generator, store)
+    }
+
+    #[tokio_macros::test]
+    async fn it_refuses_to_generate_blocks_on_other_networks() {
+        let (mut generator, store) = setup(Network::Stibbons);
+        assert!(!generator.is_enabled());
+
+        let err = generator.generate_blocks(1, None).await.unwrap_err();
+        assert!(matches!(err, BlockGeneratorError::NotRegtest(Network::Stibbons)));
+        assert_eq!(store.get_chain_metadata().unwrap().height_of_longest_chain(), 0);
+    }
+
+    #[tokio_macros::test]
+    async fn it_bounds_the_number_of_blocks() {
+        let (mut generator, store) = setup(Network::LocalNet);
+        assert!(generator.is_enabled());
+
+        for num_blocks in &[0, GENERATE_BLOCKS_MAX_BLOCKS + 1] {
+            let err = generator.generate_blocks(*num_blocks, None).await.unwrap_err();
+            assert!(matches!(
+                err,
+                BlockGeneratorError::InvalidNumBlocks { requested, max: GENERATE_BLOCKS_MAX_BLOCKS }
+                    if requested == *num_blocks
+            ));
+        }
+        assert_eq!(store.get_chain_metadata().unwrap().height_of_longest_chain(), 0);
+    }
+
+    #[tokio_macros::test]
+    async fn it_generates_blocks_on_localnet() {
+        let (mut generator, store) = setup(Network::LocalNet);
+
+        let block_hashes = generator.generate_blocks(3, None).await.unwrap();
+        assert_eq!(block_hashes.len(), 3);
+        let metadata = store.get_chain_metadata().unwrap();
+        assert_eq!(metadata.height_of_longest_chain(), 3);
+        assert_eq!(metadata.best_block(), &block_hashes[2]);
+
+        // The coinbases can be paid to a wallet
+        let (_, wallet_public_key) = PublicKey::random_keypair(&mut OsRng);
+        let block_hashes = generator.generate_blocks(2, Some(wallet_public_key)).await.unwrap();
+        let metadata = store.get_chain_metadata().unwrap();
+        assert_eq!(metadata.height_of_longest_chain(), 5);
+        assert_eq!(metadata.best_block(), &block_hashes[1]);
+    }
+}
@@ -31,7 +31,11 @@ use std::{
 };
 use tari_app_utilities::utilities::parse_emoji_id_or_public_key;
 
-use tari_core::transactions::{tari_amount::MicroTari, types::PublicKey};
+use tari_core::transactions::{
+    tari_amount::MicroTari,
+    types::{Commitment, PublicKey},
+};
+use tari_crypto::tari_utilities::hex::Hex;
 
 #[derive(Debug)]
 pub struct ParsedCommand {
@@ -50,6 +54,14 @@ impl Display for ParsedCommand {
             WalletCommand::Whois => "whois",
             WalletCommand::ListUtxos => "list-utxos",
             WalletCommand::CountUtxos => "count-utxos",
+            WalletCommand::GetRewindPublicKeys => "get-rewind-public-keys",
+            WalletCommand::ListWatchedOutputs => "list-watched-outputs",
+            WalletCommand::WatchCommitments => "watch-commitments",
+            WalletCommand::CreateAccount => "create-account",
+            WalletCommand::ListAccounts => "list-accounts",
+            WalletCommand::SelectAccount => "select-account",
+            WalletCommand::ExportContacts => "export-contacts",
+            WalletCommand::ImportContacts => "import-contacts",
         };
 
         let args = self
@@ -71,6 +83,8 @@ pub enum ParsedArgument {
     Float(f64),
     Int(u64),
     Date(DateTime<Utc>),
+    Flag(String),
+    Commitment(Commitment),
 }
 
 impl Display for ParsedArgument {
@@ -82,6 +96,8 @@ impl Display for ParsedArgument {
             ParsedArgument::Float(v) => write!(f, "{}", v.to_string()),
             ParsedArgument::Int(v) => write!(f, "{}", v.to_string()),
             ParsedArgument::Date(v) => write!(f, "{}", v.to_string()),
+            ParsedArgument::Flag(v) => write!(f, "--{}", v),
+            ParsedArgument::Commitment(v) => write!(f, "{}", v.to_hex()),
         }
     }
 }
@@ -103,6 +119,14 @@ pub fn parse_command(command: &str) -> Result<ParsedCommand, ParseError> {
         Whois => parse_whois(args)?,
         ListUtxos => Vec::new(), // todo: only show X number of utxos
         CountUtxos => Vec::new(),
+        GetRewindPublicKeys => Vec::new(),
+        ListWatchedOutputs => Vec::new(),
+        WatchCommitments => parse_watch_commitments(args)?,
+        CreateAccount => parse_account_name(args)?,
+        ListAccounts => Vec::new(),
+        SelectAccount => parse_account_name(args)?,
+        ExportContacts => parse_file_path(args)?,
+        ImportContacts => parse_file_path(args)?,
     };
 
     Ok(ParsedCommand { command, args })
@@ -194,9 +218,17 @@ fn parse_make_it_rain(mut args: SplitWhitespace) -> Result<Vec<ParsedArgument>,
     Ok(parsed_args)
 }
 
-fn parse_send_tari(mut args: SplitWhitespace) -> Result<Vec<ParsedArgument>, ParseError> {
+/// The flag for `send-tari` that sends a one-sided transaction instead of using the interactive protocol
+pub const ONE_SIDED_FLAG: &str = "one-sided";
+
+fn parse_send_tari(args: SplitWhitespace) -> Result<Vec<ParsedArgument>, ParseError> {
     let mut parsed_args = Vec::new();
 
+    // optional one-sided flag
+    let one_sided_flag = format!("--{}", ONE_SIDED_FLAG);
+    let one_sided = args.clone().any(|a| a == one_sided_flag);
+    let mut args = args.filter(|a| *a != one_sided_flag);
+
     // amount
     let amount = args.next().ok_or_else(|| ParseError::Empty("amount".to_string()))?;
     let amount = MicroTari::from_str(amount)?;
@@ -213,9 +245,48 @@ fn parse_send_tari(mut args: SplitWhitespace) -> Result<Vec<ParsedArgument>, Par
     let message = args.collect::<Vec<&str>>().join(" ");
     parsed_args.push(ParsedArgument::Text(message));
 
+    if one_sided {
+        parsed_args.push(ParsedArgument::Flag(ONE_SIDED_FLAG.to_string()));
+    }
+
+    Ok(parsed_args)
+}
+
+fn parse_watch_commitments(args: SplitWhitespace) -> Result<Vec<ParsedArgument>, ParseError> {
+    let parsed_args = args
+        .map(|c| {
+            Commitment::from_hex(c)
+                .map(ParsedArgument::Commitment)
+                .map_err(|_| ParseError::Commitment)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if parsed_args.is_empty() {
+        return Err(ParseError::Empty("commitment".to_string()));
+    }
+
     Ok(parsed_args)
 }
 
+fn parse_account_name(mut args: SplitWhitespace) -> Result<Vec<ParsedArgument>, ParseError> {
+    let name = args
+        .next()
+        .ok_or_else(|| ParseError::Empty("account name".to_string()))?;
+    if args.next().is_some() {
+        return Err(ParseError::Invalid);
+    }
+
+    Ok(vec![ParsedArgument::Text(name.to_string())])
+}
+
+fn parse_file_path(mut args: SplitWhitespace) -> Result<Vec<ParsedArgument>, ParseError> {
+    let path = args.next().ok_or_else(|| ParseError::Empty("file path".to_string()))?;
+    if args.next().is_some() {
+        return Err(ParseError::Invalid);
+    }
+
+    Ok(vec![ParsedArgument::Text(path.to_string())])
+}
+
 fn parse_coin_split(mut args: SplitWhitespace) -> Result<Vec<ParsedArgument>, ParseError> {
     let mut parsed_args = vec![];
 
@@ -276,6 +347,27 @@ fn test_parse_command() {
         panic!("Parsed message is not the same as provided.");
     }
 
+    assert_eq!(parsed.args.len(), 3);
+
+    let command_str = format!("send-tari --one-sided 999T {} msg text", public_key);
+    let parsed = parse_command(&command_str).unwrap();
+
+    if let ParsedArgument::PublicKey(pk) = parsed.args[1].clone() {
+        assert_eq!(pk, public_key);
+    } else {
+        panic!("Parsed public key is not the same as provided.");
+    }
+    if let ParsedArgument::Text(msg) = parsed.args[2].clone() {
+        assert_eq!(msg, "msg text");
+    } else {
+        panic!("Parsed message is not the same as provided.");
+    }
+    if let ParsedArgument::Flag(flag) = parsed.args[3].clone() {
+        assert_eq!(flag, ONE_SIDED_FLAG);
+    } else {
+        panic!("Parsed one-sided flag is missing.");
+    }
+
     let command_str = format!("send-tari 999ut {}", public_key);
     let parsed = parse_command(&command_str).unwrap();
 
@@ -302,4 +394,23 @@ fn test_parse_command() {
     } else {
         panic!("Parsed public key is not the same as provided.");
     }
+
+    let command_str = "watch-commitments";
+    let parsed = parse_command(command_str);
+    assert!(parsed.is_err());
+
+    let command_str = "watch-commitments not-a-commitment";
+    let parsed = parse_command(command_str);
+    assert!(parsed.is_err());
+
+    let commitment = Commitment::from_public_key(&public_key);
+    let command_str = format!("watch-commitments {} {}", commitment.to_hex(), commitment.to_hex());
+    let parsed = parse_command(&command_str).unwrap();
+
+    assert_eq!(parsed.args.len(), 2);
+    if let ParsedArgument::Commitment(c) = parsed.args[0].clone() {
+        assert_eq!(c, commitment);
+    } else {
+        panic!("Parsed commitment is not the same as provided.");
+    }
 }
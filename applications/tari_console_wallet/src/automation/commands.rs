@@ -20,12 +20,13 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use crate::automation::command_parser::{ParsedArgument, ParsedCommand};
+use crate::automation::command_parser::{ParsedArgument, ParsedCommand, ONE_SIDED_FLAG};
 use chrono::{DateTime, Utc};
 
 use futures::{FutureExt, StreamExt};
 use log::*;
 use std::{
+    fs,
     str::FromStr,
     time::{Duration, Instant},
 };
@@ -64,6 +65,14 @@ pub enum WalletCommand {
     Whois,
     ListUtxos,
     CountUtxos,
+    GetRewindPublicKeys,
+    ListWatchedOutputs,
+    WatchCommitments,
+    CreateAccount,
+    ListAccounts,
+    SelectAccount,
+    ExportContacts,
+    ImportContacts,
 }
 
 #[derive(Debug, EnumString, PartialEq, Clone)]
@@ -106,10 +115,22 @@ pub async fn send_tari(
         _ => Err(CommandError::Argument),
     }?;
 
-    wallet_transaction_service
-        .send_transaction(dest_pubkey, amount, fee_per_gram, message)
-        .await
-        .map_err(CommandError::Transaction)
+    let one_sided = match args.get(3) {
+        Some(Flag(flag)) => flag == ONE_SIDED_FLAG,
+        _ => false,
+    };
+
+    if one_sided {
+        wallet_transaction_service
+            .send_one_sided_transaction(dest_pubkey, amount, fee_per_gram, message)
+            .await
+            .map_err(CommandError::Transaction)
+    } else {
+        wallet_transaction_service
+            .send_transaction(dest_pubkey, amount, fee_per_gram, message)
+            .await
+            .map_err(CommandError::Transaction)
+    }
 }
 
 pub async fn coin_split(
@@ -401,6 +422,7 @@ pub async fn command_runner(
 
     let transaction_service = wallet.transaction_service.clone();
     let mut output_service = wallet.output_manager_service.clone();
+    let mut contacts_service = wallet.contacts_service.clone();
     let dht_service = wallet.dht_service.discovery_service_requester().clone();
     let connectivity_requester = wallet.comms.connectivity();
     let mut online = false;
@@ -415,6 +437,12 @@ pub async fn command_runner(
         println!("{}. {}", idx + 1, parsed);
 
         match parsed.command {
+            GetBalance if wallet.watch_only => match wallet.get_watch_only_balance().await {
+                Ok(balance) => {
+                    println!("{}", balance);
+                },
+                Err(e) => eprintln!("GetBalance error! {}", e),
+            },
             GetBalance => match output_service.clone().get_balance().await {
                 Ok(balance) => {
                     println!("{}", balance);
@@ -480,6 +508,94 @@ pub async fn command_runner(
                     println!("Maximum value UTXO   : {}", max);
                 }
             },
+            GetRewindPublicKeys => {
+                let keys = output_service.get_rewind_public_keys().await?;
+                println!("Rewind public key         : {}", keys.rewind_public_key.to_hex());
+                println!(
+                    "Rewind blinding public key: {}",
+                    keys.rewind_blinding_public_key.to_hex()
+                );
+                println!(
+                    "Create a watch-only wallet with: --watch-only {},{}",
+                    keys.rewind_public_key.to_hex(),
+                    keys.rewind_blinding_public_key.to_hex()
+                );
+            },
+            ListWatchedOutputs => {
+                let outputs = wallet.get_watched_outputs().await?;
+                for (i, output) in outputs.iter().enumerate() {
+                    let value = output
+                        .value
+                        .map(|v| v.to_string())
+                        .unwrap_or_else(|| "unknown".to_string());
+                    let status = match (output.mined_height, output.spent) {
+                        (None, _) => "not mined".to_string(),
+                        (Some(height), false) => format!("mined at height {}", height),
+                        (Some(height), true) => format!("mined at height {}, spent", height),
+                    };
+                    println!(
+                        "{}. Commitment: {} Value: {} ({})",
+                        i + 1,
+                        output.commitment.to_hex(),
+                        value,
+                        status
+                    );
+                }
+                println!("Total number of watched outputs: {}", outputs.len());
+            },
+            WatchCommitments => {
+                let commitments = parsed
+                    .args
+                    .iter()
+                    .map(|arg| match arg {
+                        ParsedArgument::Commitment(c) => Ok(c.clone()),
+                        _ => Err(CommandError::Argument),
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                let added = wallet.add_watched_commitments(commitments).await?;
+                println!("Watching {} new commitment(s)", added);
+            },
+            CreateAccount => {
+                let name = match parsed.args[0].clone() {
+                    ParsedArgument::Text(name) => Ok(name),
+                    _ => Err(CommandError::Argument),
+                }?;
+                let account = output_service.create_account(name).await?;
+                println!("Created account '{}' (Id: {})", account.name, account.id);
+            },
+            ListAccounts => {
+                let active_account = output_service.get_active_account().await?;
+                for account in output_service.get_accounts().await? {
+                    let marker = if account.id == active_account.id { "*" } else { " " };
+                    println!("{} {}. {}", marker, account.id, account.name);
+                }
+            },
+            SelectAccount => {
+                let name = match parsed.args[0].clone() {
+                    ParsedArgument::Text(name) => Ok(name),
+                    _ => Err(CommandError::Argument),
+                }?;
+                let account = output_service.set_active_account(name).await?;
+                println!("Selected account '{}'", account.name);
+            },
+            ExportContacts => {
+                let path = match parsed.args[0].clone() {
+                    ParsedArgument::Text(path) => Ok(path),
+                    _ => Err(CommandError::Argument),
+                }?;
+                let json = contacts_service.export_contacts().await?;
+                fs::write(&path, json)?;
+                println!("Exported contacts to {}", path);
+            },
+            ImportContacts => {
+                let path = match parsed.args[0].clone() {
+                    ParsedArgument::Text(path) => Ok(path),
+                    _ => Err(CommandError::Argument),
+                }?;
+                let json = fs::read_to_string(&path)?;
+                let imported = contacts_service.import_contacts(&json).await?;
+                println!("Imported {} contact(s) from {}", imported, path);
+            },
         }
     }
 
@@ -27,6 +27,8 @@ use log::*;
 use tari_app_utilities::utilities::ExitCodes;
 use tari_core::transactions::tari_amount::MicroTariError;
 use tari_wallet::{
+    contacts_service::error::ContactsServiceError,
+    error::WalletError,
     output_manager_service::error::OutputManagerError,
     transaction_service::error::TransactionServiceError,
 };
@@ -43,6 +45,12 @@ pub enum CommandError {
     Transaction(#[from] TransactionServiceError),
     #[error("Output manager error: `{0}`")]
     OutputManagerError(#[from] OutputManagerError),
+    #[error("Wallet error: `{0}`")]
+    WalletError(#[from] WalletError),
+    #[error("Contacts service error: `{0}`")]
+    ContactsServiceError(#[from] ContactsServiceError),
+    #[error("File error: `{0}`")]
+    Io(#[from] std::io::Error),
     #[error("Tokio join error `{0}`")]
     Join(#[from] JoinError),
     #[error("Config error `{0}`")]
@@ -75,6 +83,8 @@ pub enum ParseError {
     Int(#[from] ParseIntError),
     #[error("Failed to parse date. {0}")]
     Date(#[from] DateError),
+    #[error("Failed to parse commitment.")]
+    Commitment,
     #[error("Invalid combination of arguments.")]
     Invalid,
     #[error("Parsing not yet implemented for {0}.")]
@@ -1,11 +1,23 @@
+use chrono::{NaiveDateTime, Utc};
 use futures::future;
 use log::*;
 use tari_app_grpc::{
     conversions::naive_datetime_to_timestamp,
     tari_rpc::{
+        payment_recipient::PaymentType,
         wallet_server,
+        AddWatchedCommitmentsRequest,
+        AddWatchedCommitmentsResponse,
         CoinSplitRequest,
         CoinSplitResponse,
+        CreateAccountRequest,
+        CreateAccountResponse,
+        CreatePaymentRequestRequest,
+        CreatePaymentRequestResponse,
+        ExportContactsRequest,
+        ExportContactsResponse,
+        GetAccountsRequest,
+        GetAccountsResponse,
         GetBalanceRequest,
         GetBalanceResponse,
         GetCoinbaseRequest,
@@ -14,26 +26,46 @@ use tari_app_grpc::{
         GetCompletedTransactionsResponse,
         GetIdentityRequest,
         GetIdentityResponse,
+        GetRewindPublicKeysRequest,
+        GetRewindPublicKeysResponse,
         GetTransactionInfoRequest,
         GetTransactionInfoResponse,
         GetVersionRequest,
         GetVersionResponse,
+        GetWatchOnlyBalanceRequest,
+        GetWatchOnlyBalanceResponse,
+        GetWatchedOutputsRequest,
+        GetWatchedOutputsResponse,
+        ImportContactsRequest,
+        ImportContactsResponse,
+        ParsePaymentRequestRequest,
+        ParsePaymentRequestResponse,
+        PaymentRequest as GrpcPaymentRequest,
+        SelectAccountRequest,
+        SelectAccountResponse,
         TransactionDirection,
         TransactionInfo,
         TransactionStatus,
         TransferRequest,
         TransferResponse,
         TransferResult,
+        WalletAccount,
+        WatchedOutput,
     },
 };
 use tari_comms::types::CommsPublicKey;
 use tari_core::{
     tari_utilities::{hex::Hex, ByteArray},
-    transactions::tari_amount::MicroTari,
+    transactions::{tari_amount::MicroTari, types::Commitment},
 };
 use tari_wallet::{
-    output_manager_service::handle::OutputManagerHandle,
+    output_manager_service::{
+        handle::OutputManagerHandle,
+        storage::models::{account_of, Account},
+    },
     transaction_service::{handle::TransactionServiceHandle, storage::models},
+    util::{emoji::EmojiId, payment_request::PaymentRequest},
+    watch_only,
     WalletSqlite,
 };
 use tokio::{sync::mpsc, task};
@@ -81,6 +113,18 @@ impl wallet_server::Wallet for WalletGrpcServer {
     }
 
     async fn get_balance(&self, _request: Request<GetBalanceRequest>) -> Result<Response<GetBalanceResponse>, Status> {
+        if self.wallet.watch_only {
+            let balance = self
+                .wallet
+                .get_watch_only_balance()
+                .await
+                .map_err(|e| Status::not_found(format!("GetBalance error! {}", e)))?;
+            return Ok(Response::new(GetBalanceResponse {
+                available_balance: balance.available_balance.0,
+                pending_incoming_balance: 0,
+                pending_outgoing_balance: 0,
+            }));
+        }
         let mut output_service = self.get_output_manager_service();
         let balance;
         match output_service.get_balance().await {
@@ -123,22 +167,34 @@ impl wallet_server::Wallet for WalletGrpcServer {
             .map(|(idx, dest)| -> Result<_, String> {
                 let pk = CommsPublicKey::from_hex(&dest.address)
                     .map_err(|_| format!("Destination address at index {} is malformed", idx))?;
-                Ok((dest.address, pk, dest.amount, dest.fee_per_gram, dest.message))
+                let one_sided = dest.payment_type == PaymentType::OneSided as i32;
+                Ok((
+                    dest.address,
+                    pk,
+                    dest.amount,
+                    dest.fee_per_gram,
+                    dest.message,
+                    one_sided,
+                ))
             })
             .collect::<Result<Vec<_>, _>>()
             .map_err(Status::invalid_argument)?;
 
         let transfers = recipients
             .into_iter()
-            .map(|(address, pk, amount, fee_per_gram, message)| {
+            .map(|(address, pk, amount, fee_per_gram, message, one_sided)| {
                 let mut transaction_service = self.get_transaction_service();
                 async move {
-                    (
-                        address,
+                    let result = if one_sided {
+                        transaction_service
+                            .send_one_sided_transaction(pk, amount.into(), fee_per_gram.into(), message)
+                            .await
+                    } else {
                         transaction_service
                             .send_transaction(pk, amount.into(), fee_per_gram.into(), message)
-                            .await,
-                    )
+                            .await
+                    };
+                    (address, result)
                 }
             });
 
@@ -217,12 +273,22 @@ impl wallet_server::Wallet for WalletGrpcServer {
             "Incoming GRPC request for GetAllCompletedTransactions"
         );
         let mut transaction_service = self.get_transaction_service();
-        let transactions = transaction_service
+        let mut output_manager_service = self.get_output_manager_service();
+        let active_account = output_manager_service
+            .get_active_account()
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+        let account_transactions = output_manager_service
+            .get_account_transactions()
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+        let mut transactions = transaction_service
             .get_completed_transactions()
             .await
             .map_err(|err| Status::not_found(format!("No completed transactions found: {:?}", err)))?;
+        transactions.retain(|tx_id, _| account_of(&account_transactions, *tx_id) == active_account.id);
 
-        let (mut sender, receiver) = mpsc::channel(transactions.len());
+        let (mut sender, receiver) = mpsc::channel(transactions.len().max(1));
         task::spawn(async move {
             for (_, txn) in transactions {
                 let response = GetCompletedTransactionsResponse {
@@ -289,6 +355,237 @@ impl wallet_server::Wallet for WalletGrpcServer {
 
         Ok(Response::new(CoinSplitResponse { tx_id }))
     }
+
+    async fn get_rewind_public_keys(
+        &self,
+        _: Request<GetRewindPublicKeysRequest>,
+    ) -> Result<Response<GetRewindPublicKeysResponse>, Status>
+    {
+        let keys = self
+            .get_output_manager_service()
+            .get_rewind_public_keys()
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+
+        Ok(Response::new(GetRewindPublicKeysResponse {
+            rewind_public_key: keys.rewind_public_key.to_vec(),
+            rewind_blinding_public_key: keys.rewind_blinding_public_key.to_vec(),
+        }))
+    }
+
+    async fn get_watch_only_balance(
+        &self,
+        _: Request<GetWatchOnlyBalanceRequest>,
+    ) -> Result<Response<GetWatchOnlyBalanceResponse>, Status>
+    {
+        let balance = self
+            .wallet
+            .get_watch_only_balance()
+            .await
+            .map_err(|e| Status::failed_precondition(e.to_string()))?;
+
+        Ok(Response::new(GetWatchOnlyBalanceResponse {
+            available_balance: balance.available_balance.0,
+            unspent_outputs: balance.unspent_outputs as u64,
+            unspent_outputs_with_unknown_value: balance.unspent_outputs_with_unknown_value as u64,
+            scanned_height: balance.scanned_height.unwrap_or(0),
+        }))
+    }
+
+    async fn get_watched_outputs(
+        &self,
+        request: Request<GetWatchedOutputsRequest>,
+    ) -> Result<Response<GetWatchedOutputsResponse>, Status>
+    {
+        let from_height = request.into_inner().from_height;
+        let outputs = self
+            .wallet
+            .get_watched_outputs()
+            .await
+            .map_err(|e| Status::failed_precondition(e.to_string()))?
+            .into_iter()
+            .filter(|o| from_height == 0 || o.mined_height.map(|h| h >= from_height).unwrap_or(false))
+            .map(convert_watched_output)
+            .collect();
+
+        Ok(Response::new(GetWatchedOutputsResponse { outputs }))
+    }
+
+    async fn add_watched_commitments(
+        &self,
+        request: Request<AddWatchedCommitmentsRequest>,
+    ) -> Result<Response<AddWatchedCommitmentsResponse>, Status>
+    {
+        let commitments = request
+            .into_inner()
+            .commitments
+            .iter()
+            .map(|c| Commitment::from_bytes(c))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| Status::invalid_argument(format!("Invalid commitment: {}", e)))?;
+
+        let added = self
+            .wallet
+            .add_watched_commitments(commitments)
+            .await
+            .map_err(|e| Status::failed_precondition(e.to_string()))?;
+
+        Ok(Response::new(AddWatchedCommitmentsResponse { added: added as u64 }))
+    }
+
+    async fn create_account(
+        &self,
+        request: Request<CreateAccountRequest>,
+    ) -> Result<Response<CreateAccountResponse>, Status>
+    {
+        let name = request.into_inner().name;
+        if name.trim().is_empty() {
+            return Err(Status::invalid_argument("Account name cannot be empty"));
+        }
+        let account = self
+            .get_output_manager_service()
+            .create_account(name)
+            .await
+            .map_err(|e| Status::failed_precondition(e.to_string()))?;
+
+        Ok(Response::new(CreateAccountResponse {
+            account: Some(convert_account(account)),
+        }))
+    }
+
+    async fn get_accounts(&self, _: Request<GetAccountsRequest>) -> Result<Response<GetAccountsResponse>, Status> {
+        let mut output_manager_service = self.get_output_manager_service();
+        let accounts = output_manager_service
+            .get_accounts()
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+        let active_account = output_manager_service
+            .get_active_account()
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+
+        Ok(Response::new(GetAccountsResponse {
+            accounts: accounts.into_iter().map(convert_account).collect(),
+            active_account_id: active_account.id,
+        }))
+    }
+
+    async fn select_account(
+        &self,
+        request: Request<SelectAccountRequest>,
+    ) -> Result<Response<SelectAccountResponse>, Status>
+    {
+        let account = self
+            .get_output_manager_service()
+            .set_active_account(request.into_inner().name)
+            .await
+            .map_err(|e| Status::not_found(e.to_string()))?;
+
+        Ok(Response::new(SelectAccountResponse {
+            account: Some(convert_account(account)),
+        }))
+    }
+
+    async fn create_payment_request(
+        &self,
+        request: Request<CreatePaymentRequestRequest>,
+    ) -> Result<Response<CreatePaymentRequestResponse>, Status>
+    {
+        let message = request.into_inner();
+        let mut payment_request =
+            PaymentRequest::new(self.wallet.comms.node_identity().public_key().clone()).with_message(message.message);
+        if message.amount > 0 {
+            payment_request = payment_request.with_amount(MicroTari::from(message.amount));
+        }
+        if let Some(expiry) = message.expiry {
+            payment_request = payment_request.with_expiry(NaiveDateTime::from_timestamp(expiry.seconds, 0));
+        }
+        if message.fee_per_gram > 0 {
+            payment_request = payment_request.with_fee_per_gram(MicroTari::from(message.fee_per_gram));
+        }
+
+        Ok(Response::new(CreatePaymentRequestResponse {
+            uri: payment_request.to_uri(),
+            emoji_request: payment_request.to_emoji_string(),
+        }))
+    }
+
+    async fn parse_payment_request(
+        &self,
+        request: Request<ParsePaymentRequestRequest>,
+    ) -> Result<Response<ParsePaymentRequestResponse>, Status>
+    {
+        let payment_request = request
+            .into_inner()
+            .request
+            .parse::<PaymentRequest>()
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+
+        Ok(Response::new(ParsePaymentRequestResponse {
+            request: Some(GrpcPaymentRequest {
+                public_key: payment_request.destination.to_vec(),
+                emoji_id: EmojiId::from_pubkey(&payment_request.destination).to_string(),
+                amount: payment_request.amount.map(u64::from).unwrap_or(0),
+                is_expired: payment_request.is_expired(Utc::now().naive_utc()),
+                message: payment_request.message,
+                expiry: payment_request.expiry.map(naive_datetime_to_timestamp),
+                fee_per_gram: payment_request.fee_per_gram.map(u64::from).unwrap_or(0),
+            }),
+        }))
+    }
+
+    async fn export_contacts(
+        &self,
+        _: Request<ExportContactsRequest>,
+    ) -> Result<Response<ExportContactsResponse>, Status>
+    {
+        let contacts_json = self
+            .wallet
+            .contacts_service
+            .clone()
+            .export_contacts()
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+
+        Ok(Response::new(ExportContactsResponse { contacts_json }))
+    }
+
+    async fn import_contacts(
+        &self,
+        request: Request<ImportContactsRequest>,
+    ) -> Result<Response<ImportContactsResponse>, Status>
+    {
+        let imported = self
+            .wallet
+            .contacts_service
+            .clone()
+            .import_contacts(&request.into_inner().contacts_json)
+            .await
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+
+        Ok(Response::new(ImportContactsResponse {
+            imported: imported as u64,
+        }))
+    }
+}
+
+fn convert_account(account: Account) -> WalletAccount {
+    WalletAccount {
+        id: account.id,
+        name: account.name,
+    }
+}
+
+fn convert_watched_output(output: watch_only::WatchedOutput) -> WatchedOutput {
+    WatchedOutput {
+        commitment: output.commitment.to_vec(),
+        hash: output.hash.unwrap_or_default(),
+        value: output.value.map(u64::from).unwrap_or(0),
+        value_known: output.value.is_some(),
+        mined_height: output.mined_height.unwrap_or(0),
+        is_mined: output.mined_height.is_some(),
+        is_spent: output.spent,
+    }
 }
 
 fn convert_wallet_transaction_into_transaction_info(
@@ -38,9 +38,9 @@ use tari_comms::{
 use tari_comms_dht::{DbConnectionUrl, DhtConfig};
 use tari_core::{
     consensus::Network as NetworkType,
-    transactions::types::{CryptoFactories, PrivateKey},
+    transactions::types::{CryptoFactories, PrivateKey, PublicKey},
 };
-use tari_crypto::keys::SecretKey;
+use tari_crypto::{keys::SecretKey, tari_utilities::hex::Hex};
 use tari_p2p::{
     initialization::CommsConfig,
     seed_peer::SeedPeer,
@@ -54,6 +54,7 @@ use tari_wallet::{
     output_manager_service::{
         config::OutputManagerServiceConfig,
         protocols::txo_validation_protocol::TxoValidationType,
+        signer::{remote::RemoteSigner, TransactionSigner},
         storage::{
             database::{
                 DbKeyValuePair as OutputDbKeyValuePair,
@@ -74,6 +75,7 @@ use tari_wallet::{
     },
     types::ValidationRetryStrategy,
     wallet::WalletConfig,
+    watch_only::WatchOnlyKeys,
     Wallet,
     WalletSqlite,
 };
@@ -139,7 +141,7 @@ pub async fn change_password(
     shutdown_signal: ShutdownSignal,
 ) -> Result<(), ExitCodes>
 {
-    let mut wallet = init_wallet(config, arg_password, None, shutdown_signal).await?;
+    let mut wallet = init_wallet(config, arg_password, None, None, shutdown_signal).await?;
 
     let passphrase = prompt_password("New wallet password: ")?;
     let confirmed = prompt_password("Confirm new password: ")?;
@@ -255,11 +257,40 @@ pub fn get_notify_script(bootstrap: &ConfigBootstrap, config: &GlobalConfig) ->
     Ok(notify_script)
 }
 
+/// Get the watch-only keys from the `--watch-only` command line argument if provided
+pub fn get_watch_only_keys(bootstrap: &ConfigBootstrap) -> Result<Option<WatchOnlyKeys>, ExitCodes> {
+    let arg = match &bootstrap.watch_only {
+        Some(arg) => arg,
+        None => return Ok(None),
+    };
+    if bootstrap.recovery {
+        return Err(ExitCodes::InputError(
+            "A watch-only wallet cannot be recovered from seed words".to_string(),
+        ));
+    }
+
+    let keys = arg
+        .split(',')
+        .map(|k| PublicKey::from_hex(k.trim()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| ExitCodes::InputError(format!("Invalid watch-only public key: {}", e)))?;
+    match keys.as_slice() {
+        [rewind_public_key, rewind_blinding_public_key] => Ok(Some(WatchOnlyKeys {
+            rewind_public_key: rewind_public_key.clone(),
+            rewind_blinding_public_key: rewind_blinding_public_key.clone(),
+        })),
+        _ => Err(ExitCodes::InputError(
+            "Watch-only keys must be provided as `<rewind public key>,<rewind blinding public key>`".to_string(),
+        )),
+    }
+}
+
 /// Set up the app environment and state for use by the UI
 pub async fn init_wallet(
     config: &GlobalConfig,
     arg_password: Option<String>,
     master_key: Option<PrivateKey>,
+    watch_only_keys: Option<WatchOnlyKeys>,
     shutdown_signal: ShutdownSignal,
 ) -> Result<WalletSqlite, ExitCodes>
 {
@@ -325,6 +356,13 @@ pub async fn init_wallet(
         },
     };
 
+    let signer = get_signer(config, node_identity.clone())?;
+    if signer.is_some() && master_key.is_some() {
+        return Err(ExitCodes::ConfigError(
+            "A wallet with an external signer cannot be recovered from a master key".to_string(),
+        ));
+    }
+
     let transport_type = setup_wallet_transport_type(&config);
     let transport_type = match transport_type {
         Tor(mut tor_config) => {
@@ -365,6 +403,8 @@ pub async fn init_wallet(
         peer_seeds: Default::default(),
         dns_seeds: Default::default(),
         dns_seeds_use_dnssec: true,
+        max_bandwidth_bytes_per_sec: None,
+        max_peer_bandwidth_bytes_per_sec: None,
     };
 
     let network = match &config.network {
@@ -406,6 +446,8 @@ pub async fn init_wallet(
         Some(config.buffer_rate_limit_base_node_wallet),
     );
     wallet_config.buffer_size = std::cmp::max(BASE_NODE_BUFFER_MIN_SIZE, config.buffer_size_base_node);
+    wallet_config.watch_only_keys = watch_only_keys;
+    wallet_config.signer = signer;
 
     let recovery = set_master_key(&output_manager_backend, master_key).await?;
 
@@ -428,7 +470,7 @@ pub async fn init_wallet(
     if let Some(hs) = wallet.comms.hidden_service() {
         wallet
             .db
-            .set_tor_identity(hs.tor_identity().clone())
+            .set_tor_identity(hs.tor_identity())
             .await
             .map_err(|e| ExitCodes::WalletError(format!("Problem writing tor identity. {}", e)))?;
     }
@@ -457,7 +499,8 @@ pub async fn init_wallet(
 
         debug!(target: LOG_TARGET, "Wallet encrypted.");
 
-        if interactive && !recovery {
+        // The seed words of a watch-only wallet do not control any funds
+        if interactive && !recovery && !wallet.watch_only {
             confirm_seed_words(&mut wallet).await?;
         }
     }
@@ -467,6 +510,33 @@ pub async fn init_wallet(
 
 /// If a master key is provided, set the initial key manager state to use that master key.
 /// Returns true if the master key was provided, which means recovery is required.
+/// The external signer selected by the `wallet.signer` config entry, if any. The connection to the signer is
+/// authenticated with its configured public key.
+fn get_signer(
+    config: &GlobalConfig,
+    node_identity: Arc<NodeIdentity>,
+) -> Result<Option<Arc<dyn TransactionSigner>>, ExitCodes>
+{
+    let signer = match &config.console_wallet_signer {
+        Some(signer) => {
+            SeedPeer::from_str(signer).map_err(|err| ExitCodes::ConfigError(format!("Malformed signer: {}", err)))?
+        },
+        None => return Ok(None),
+    };
+    let address = signer
+        .addresses
+        .into_iter()
+        .next()
+        .ok_or_else(|| ExitCodes::ConfigError("Configured signer has no address".to_string()))?;
+    info!(target: LOG_TARGET, "Using the external signer at {}", address);
+
+    Ok(Some(Arc::new(RemoteSigner::new(
+        address,
+        signer.public_key,
+        node_identity,
+    ))))
+}
+
 async fn set_master_key(
     output_manager_backend: &OutputManagerSqliteDatabase,
     master_key: Option<PrivateKey>,
@@ -638,8 +708,8 @@ pub(crate) fn boot(bootstrap: &ConfigBootstrap, config: &GlobalConfig) -> Result
         // normal startup of existing wallet
         Ok(WalletBoot::Existing)
     } else {
-        // automation/wallet created with --password
-        if bootstrap.password.is_some() {
+        // automation/wallet created with --password, or a watch-only wallet which has no seed words to recover from
+        if bootstrap.password.is_some() || bootstrap.watch_only.is_some() {
             return Ok(WalletBoot::New);
         }
 
@@ -11,6 +11,7 @@ use init::{
     change_password,
     get_base_node_peer_config,
     get_notify_script,
+    get_watch_only_keys,
     init_wallet,
     start_wallet,
     tari_splash_screen,
@@ -22,6 +23,7 @@ use recovery::prompt_private_key_from_seed_words;
 use tari_app_utilities::{initialization::init_configuration, utilities::ExitCodes};
 use tari_common::configuration::bootstrap::ApplicationType;
 use tari_shutdown::Shutdown;
+use tari_wallet::tasks::wallet_recovery::is_recovery_in_progress;
 use wallet_modes::{command_mode, grpc_mode, recovery_mode, script_mode, tui_mode, WalletMode};
 
 pub const LOG_TARGET: &str = "wallet::console_wallet::main";
@@ -90,8 +92,28 @@ fn main_inner() -> Result<(), ExitCodes> {
         return runtime.block_on(change_password(&config, arg_password, shutdown_signal));
     }
 
+    // an existing wallet may only be opened with watch-only keys if it is already a watch-only wallet
+    let watch_only_keys = get_watch_only_keys(&bootstrap)?;
+    let converts_existing_wallet = watch_only_keys.is_some() && matches!(boot_mode, WalletBoot::Existing);
+
     // initialize wallet
-    let mut wallet = runtime.block_on(init_wallet(&config, arg_password, master_key, shutdown_signal))?;
+    let mut wallet = runtime.block_on(init_wallet(
+        &config,
+        arg_password,
+        master_key,
+        if converts_existing_wallet {
+            None
+        } else {
+            watch_only_keys
+        },
+        shutdown_signal,
+    ))?;
+    if converts_existing_wallet && !wallet.watch_only {
+        return Err(ExitCodes::InputError(format!(
+            "The wallet at {:#?} is not a watch-only wallet",
+            config.console_wallet_db_file
+        )));
+    }
 
     // get base node/s
     let base_node_config = runtime.block_on(get_base_node_peer_config(&config, &mut wallet))?;
@@ -100,6 +122,14 @@ fn main_inner() -> Result<(), ExitCodes> {
     // start wallet
     runtime.block_on(start_wallet(&mut wallet, &base_node))?;
 
+    // resume a recovery that was interrupted
+    let boot_mode = if runtime.block_on(is_recovery_in_progress(&wallet.db))? {
+        info!(target: LOG_TARGET, "Resuming an interrupted wallet recovery.");
+        WalletBoot::Recovery
+    } else {
+        boot_mode
+    };
+
     // optional path to notify script
     let notify_script = get_notify_script(&bootstrap, &config)?;
 
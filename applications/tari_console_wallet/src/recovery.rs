@@ -20,22 +20,18 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use chrono::offset::Local;
 use futures::StreamExt;
 use log::*;
 use rustyline::Editor;
-use std::convert::TryFrom;
 use tari_app_utilities::utilities::ExitCodes;
 use tari_comms::peer_manager::Peer;
-use tari_core::{
-    base_node::sync::rpc,
-    blocks::BlockHeader,
-    proto::base_node::{SyncUtxosRequest, SyncUtxosResponse},
-    tari_utilities::{hex::Hex, Hashable},
-    transactions::{tari_amount::MicroTari, transaction::TransactionOutput, types::PrivateKey},
-};
+use tari_core::transactions::types::PrivateKey;
+use tari_crypto::tari_utilities::hex::Hex;
 use tari_key_manager::mnemonic::to_secretkey;
-use tari_wallet::WalletSqlite;
+use tari_wallet::{
+    tasks::wallet_recovery::{WalletRecoveryEvent, WalletRecoveryTask},
+    WalletSqlite,
+};
 
 pub const LOG_TARGET: &str = "wallet::recovery";
 
@@ -64,79 +60,51 @@ pub fn prompt_private_key_from_seed_words() -> Result<PrivateKey, ExitCodes> {
 
 /// Recovers wallet funds by connecting to a given base node peer, downloading the transaction outputs stored in the
 /// blockchain, and attempting to rewind them. Any outputs that are successfully rewound are then imported into the
-/// wallet.
-pub async fn wallet_recovery(wallet: &mut WalletSqlite, base_node: &Peer) -> Result<(), ExitCodes> {
+/// wallet. An interrupted recovery will resume from where it left off.
+pub async fn wallet_recovery(wallet: WalletSqlite, base_node: &Peer) -> Result<(), ExitCodes> {
     println!(
         "Connecting to base node with public key: {}",
         base_node.public_key.to_hex()
     );
 
-    let node_id = wallet.comms.node_identity();
-    let public_key = node_id.public_key();
-
-    let mut conn = wallet.comms.connectivity().dial_peer(base_node.node_id.clone()).await?;
-    let mut client = conn.connect_rpc::<rpc::BaseNodeSyncRpcClient>().await?;
-    println!("Base node connected.");
-
-    let latency = client.get_last_request_latency().await?;
-    println!("Latency: {} ms.", latency.unwrap_or_default().as_millis());
-
-    let chain_metadata = client.get_chain_metadata().await?;
-    let height = chain_metadata.height_of_longest_chain();
-    println!("Chain Height: {}.", height);
-
-    let header = client.get_header_by_height(height).await?;
-    let header = BlockHeader::try_from(header).map_err(ExitCodes::ConversionError)?;
-    let start = 0;
-    let end_header_hash = header.hash();
-    let request = SyncUtxosRequest { start, end_header_hash };
-    let mut output_stream = client.sync_utxos(request).await?;
-    println!("Streaming transaction outputs...");
-
-    let mut num_utxos = 0;
-    let mut total_amount = MicroTari::from(0);
-
-    while let Some(response) = output_stream.next().await {
-        let response: SyncUtxosResponse = response.map_err(|e| ExitCodes::ConversionError(e.to_string()))?;
-
-        let outputs: Vec<TransactionOutput> = response
-            .utxos
-            .into_iter()
-            .filter_map(|utxo| {
-                if let Some(output) = utxo.output {
-                    TransactionOutput::try_from(output).ok()
-                } else {
-                    None
-                }
-            })
-            .collect();
-        println!("Scanning {} outputs...", outputs.len());
-
-        let unblinded_outputs = wallet.output_manager_service.rewind_outputs(outputs).await?;
-
-        if !unblinded_outputs.is_empty() {
-            println!("Importing {} outputs...", unblinded_outputs.len());
-
-            for uo in unblinded_outputs {
-                wallet
-                    .import_utxo(
-                        uo.value,
-                        &uo.spending_key,
-                        public_key,
-                        format!("Recovered on {}.", Local::now()),
-                    )
-                    .await?;
-
-                num_utxos += 1;
-                total_amount += uo.value;
-            }
+    let recovery_task = WalletRecoveryTask::new(wallet, vec![base_node.public_key.clone()]);
+    let mut event_stream = recovery_task.get_event_receiver();
+    let recovery_join_handle = tokio::spawn(recovery_task.run());
+
+    while let Some(event) = event_stream.next().await {
+        match event {
+            Ok(WalletRecoveryEvent::ConnectingToBaseNode(_)) => {},
+            Ok(WalletRecoveryEvent::ConnectedToBaseNode(_, latency)) => {
+                println!("Base node connected.");
+                println!("Latency: {} ms.", latency.as_millis());
+                println!("Streaming transaction outputs...");
+            },
+            Ok(WalletRecoveryEvent::ConnectionFailedToBaseNode(_, attempt, retry_limit, error)) => {
+                println!(
+                    "Failed to recover from base node (attempt {} of {}): {}",
+                    attempt, retry_limit, error
+                );
+            },
+            Ok(WalletRecoveryEvent::Progress(current, total)) => {
+                println!("Scanned {} of {} transaction outputs.", current, total);
+            },
+            Ok(WalletRecoveryEvent::Completed(num_utxos, total_amount)) => {
+                println!(
+                    "Recovered and imported {} outputs, with a total value of {}.",
+                    num_utxos, total_amount
+                );
+            },
+            Ok(WalletRecoveryEvent::RecoveryFailed(error)) => {
+                println!("Recovery failed: {}", error);
+            },
+            Err(e) => {
+                debug!(target: LOG_TARGET, "Missed recovery events: {}", e);
+            },
         }
     }
 
-    println!(
-        "Recovered and imported {} outputs, with a total value of {}.",
-        num_utxos, total_amount
-    );
-
-    Ok(())
+    recovery_join_handle
+        .await
+        .map_err(|e| ExitCodes::WalletError(e.to_string()))?
+        .map_err(ExitCodes::from)
 }
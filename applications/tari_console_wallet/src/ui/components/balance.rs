@@ -39,7 +39,7 @@ impl<B: Backend> Component<B> for Balance {
             .split(block_title_body[1]);
 
         let block = Block::default().borders(Borders::ALL).title(Span::styled(
-            "Balance",
+            format!("Balance ({} account)", app_state.get_active_account().name),
             Style::default().fg(Color::White).add_modifier(Modifier::BOLD),
         ));
         f.render_widget(block, area);
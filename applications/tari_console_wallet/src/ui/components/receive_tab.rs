@@ -1,18 +1,141 @@
-use crate::ui::{components::Component, state::AppState};
+use crate::ui::{components::Component, state::AppState, widgets::draw_dialog};
+use chrono::{Duration, Utc};
+use tari_core::transactions::{tari_amount::MicroTari, types::PublicKey};
+use tari_crypto::tari_utilities::hex::Hex;
+use tari_wallet::util::payment_request::PaymentRequest;
 use tui::{
     backend::Backend,
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
-    text::Span,
-    widgets::{Block, Borders, Paragraph},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph, Wrap},
     Frame,
 };
+use unicode_width::UnicodeWidthStr;
 
-pub struct ReceiveTab {}
+pub struct ReceiveTab {
+    request_input_mode: RequestInputMode,
+    amount_field: String,
+    message_field: String,
+    expiry_field: String,
+    payment_request: Option<PaymentRequest>,
+    error_message: Option<String>,
+}
 
 impl ReceiveTab {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            request_input_mode: RequestInputMode::None,
+            amount_field: "".to_string(),
+            message_field: "".to_string(),
+            expiry_field: "".to_string(),
+            payment_request: None,
+            error_message: None,
+        }
+    }
+
+    fn generate_payment_request(&mut self, app_state: &AppState) {
+        let destination = match PublicKey::from_hex(app_state.get_identity().public_key.as_str()) {
+            Ok(pk) => pk,
+            Err(_) => {
+                self.error_message = Some("Could not read wallet public key\nPress Enter to continue.".to_string());
+                return;
+            },
+        };
+        let mut request = PaymentRequest::new(destination).with_message(self.message_field.clone());
+        if !self.amount_field.is_empty() {
+            match self.amount_field.parse::<u64>() {
+                Ok(v) => request = request.with_amount(MicroTari::from(v)),
+                Err(_) => {
+                    self.error_message = Some("Amount should be an integer\nPress Enter to continue.".to_string());
+                    return;
+                },
+            }
+        }
+        if !self.expiry_field.is_empty() {
+            match self.expiry_field.parse::<i64>() {
+                Ok(hours) => request = request.with_expiry(Utc::now().naive_utc() + Duration::hours(hours)),
+                Err(_) => {
+                    self.error_message =
+                        Some("Expiry should be a whole number of hours\nPress Enter to continue.".to_string());
+                    return;
+                },
+            }
+        }
+        self.payment_request = Some(request);
+    }
+
+    fn draw_payment_request<B>(&self, f: &mut Frame<B>, area: Rect)
+    where B: Backend {
+        let block = Block::default().borders(Borders::ALL).title(Span::styled(
+            "Payment Request",
+            Style::default().fg(Color::White).add_modifier(Modifier::BOLD),
+        ));
+        f.render_widget(block, area);
+        let vert_chunks = Layout::default()
+            .constraints([Constraint::Length(2), Constraint::Length(3), Constraint::Min(1)].as_ref())
+            .margin(1)
+            .split(area);
+
+        let instructions = Paragraph::new(Spans::from(vec![
+            Span::raw("Press "),
+            Span::styled("A", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(" to edit "),
+            Span::styled("Amount", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(", "),
+            Span::styled("M", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(" to edit "),
+            Span::styled("Message", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(", "),
+            Span::styled("E", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(" to edit "),
+            Span::styled("Expiry", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(", "),
+            Span::styled("G", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(" to generate the request."),
+        ]))
+        .block(Block::default());
+        f.render_widget(instructions, vert_chunks[0]);
+
+        let input_layout = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(
+                [
+                    Constraint::Percentage(25),
+                    Constraint::Percentage(50),
+                    Constraint::Percentage(25),
+                ]
+                .as_ref(),
+            )
+            .split(vert_chunks[1]);
+        let fields = [
+            (&self.amount_field, RequestInputMode::Amount, "(A)mount (uT):"),
+            (&self.message_field, RequestInputMode::Message, "(M)essage:"),
+            (&self.expiry_field, RequestInputMode::Expiry, "(E)xpiry (hours):"),
+        ];
+        for (i, (value, mode, title)) in fields.iter().enumerate() {
+            let input = Paragraph::new(value.as_str())
+                .style(if self.request_input_mode == *mode {
+                    Style::default().fg(Color::Magenta)
+                } else {
+                    Style::default()
+                })
+                .block(Block::default().borders(Borders::ALL).title(*title));
+            f.render_widget(input, input_layout[i]);
+            if self.request_input_mode == *mode {
+                // Put cursor past the end of the input text, one line down from the border
+                f.set_cursor(input_layout[i].x + value.width() as u16 + 1, input_layout[i].y + 1);
+            }
+        }
+
+        if let Some(request) = self.payment_request.as_ref() {
+            let request_text = Paragraph::new(vec![
+                Spans::from(Span::raw(request.to_uri())),
+                Spans::from(Span::raw(request.to_emoji_string())),
+            ])
+            .wrap(Wrap { trim: false });
+            f.render_widget(request_text, vert_chunks[2]);
+        }
     }
 
     fn draw_whoami<B>(&self, f: &mut Frame<B>, area: Rect, app_state: &AppState)
@@ -93,19 +216,87 @@ impl ReceiveTab {
 impl<B: Backend> Component<B> for ReceiveTab {
     fn draw(&mut self, f: &mut Frame<B>, area: Rect, app_state: &AppState) {
         let areas = Layout::default()
-            .constraints([Constraint::Min(42)].as_ref())
+            .constraints([Constraint::Min(24), Constraint::Length(10)].as_ref())
             .split(area);
 
         self.draw_whoami(f, areas[0], app_state);
+        self.draw_payment_request(f, areas[1]);
+
+        if let Some(msg) = self.error_message.clone() {
+            draw_dialog(f, area, "Error!".to_string(), msg, Color::Red, 120, 9);
+        }
     }
 
-    fn on_key(&mut self, _app_state: &mut AppState, _c: char) {}
+    fn on_key(&mut self, app_state: &mut AppState, c: char) {
+        if self.error_message.is_some() {
+            if '\n' == c {
+                self.error_message = None;
+            }
+            return;
+        }
+
+        match self.request_input_mode {
+            RequestInputMode::None => (),
+            RequestInputMode::Amount | RequestInputMode::Expiry => {
+                match c {
+                    '\n' | '\t' => self.request_input_mode = RequestInputMode::None,
+                    c if c.is_numeric() => {
+                        if self.request_input_mode == RequestInputMode::Amount {
+                            self.amount_field.push(c);
+                        } else {
+                            self.expiry_field.push(c);
+                        }
+                    },
+                    _ => (),
+                }
+                return;
+            },
+            RequestInputMode::Message => {
+                match c {
+                    '\n' | '\t' => self.request_input_mode = RequestInputMode::None,
+                    c => self.message_field.push(c),
+                }
+                return;
+            },
+        }
+
+        match c {
+            'a' => self.request_input_mode = RequestInputMode::Amount,
+            'm' => self.request_input_mode = RequestInputMode::Message,
+            'e' => self.request_input_mode = RequestInputMode::Expiry,
+            'g' => self.generate_payment_request(app_state),
+            _ => {},
+        }
+    }
 
     fn on_up(&mut self, _app_state: &mut AppState) {}
 
     fn on_down(&mut self, _app_state: &mut AppState) {}
 
-    fn on_esc(&mut self, _: &mut AppState) {}
+    fn on_esc(&mut self, _: &mut AppState) {
+        self.request_input_mode = RequestInputMode::None;
+    }
+
+    fn on_backspace(&mut self, _app_state: &mut AppState) {
+        match self.request_input_mode {
+            RequestInputMode::Amount => {
+                let _ = self.amount_field.pop();
+            },
+            RequestInputMode::Message => {
+                let _ = self.message_field.pop();
+            },
+            RequestInputMode::Expiry => {
+                let _ = self.expiry_field.pop();
+            },
+            RequestInputMode::None => {},
+        }
+    }
+}
 
-    fn on_backspace(&mut self, _app_state: &mut AppState) {}
+#[derive(PartialEq, Debug)]
+pub enum RequestInputMode {
+    None,
+    Amount,
+    Message,
+    Expiry,
 }
@@ -7,7 +7,9 @@ use crate::{
     },
     utils::formatting::display_compressed_string,
 };
-use tari_wallet::types::DEFAULT_FEE_PER_GRAM;
+use chrono::Utc;
+use tari_crypto::tari_utilities::hex::Hex;
+use tari_wallet::{types::DEFAULT_FEE_PER_GRAM, util::payment_request::PaymentRequest};
 use tokio::{runtime::Handle, sync::watch};
 use tui::{
     backend::Backend,
@@ -109,7 +111,7 @@ impl SendTab {
             .block(
                 Block::default()
                     .borders(Borders::ALL)
-                    .title("(T)o (Public Key or Emoji ID) :"),
+                    .title("(T)o (Public Key, Emoji ID or Payment Request) :"),
             );
         f.render_widget(to_input, vert_chunks[1]);
 
@@ -171,6 +173,34 @@ impl SendTab {
         }
     }
 
+    /// If the To field holds a payment request, fill in the send form from it
+    fn apply_payment_request(&mut self) {
+        if !self.to_field.contains('?') && !self.to_field.starts_with("tari:") {
+            return;
+        }
+        let request = match self.to_field.parse::<PaymentRequest>() {
+            Ok(r) => r,
+            Err(e) => {
+                self.error_message = Some(format!("Invalid payment request: {}\nPress Enter to continue.", e));
+                return;
+            },
+        };
+        if request.is_expired(Utc::now().naive_utc()) {
+            self.error_message = Some("This payment request has expired\nPress Enter to continue.".to_string());
+            return;
+        }
+        self.to_field = request.destination.to_hex();
+        if let Some(amount) = request.amount {
+            self.amount_field = u64::from(amount).to_string();
+        }
+        if let Some(fee_per_gram) = request.fee_per_gram {
+            self.fee_field = u64::from(fee_per_gram).to_string();
+        }
+        if !request.message.is_empty() {
+            self.message_field = request.message;
+        }
+    }
+
     fn draw_contacts<B>(&mut self, f: &mut Frame<B>, area: Rect, app_state: &AppState)
     where B: Backend {
         let block = Block::default().borders(Borders::ALL).title(Span::styled(
@@ -476,6 +506,7 @@ impl<B: Backend> Component<B> for SendTab {
                 SendInputMode::None => (),
                 SendInputMode::To => match c {
                     '\n' | '\t' => {
+                        self.apply_payment_request();
                         self.send_input_mode = SendInputMode::Amount;
                     },
                     c => {
@@ -407,7 +407,9 @@ impl<B: Backend> Component<B> for TransactionsTab {
         ));
         span_vec.push(Span::raw(" to select a transaction, "));
         span_vec.push(Span::styled("C", Style::default().add_modifier(Modifier::BOLD)));
-        span_vec.push(Span::raw(" to cancel a selected Pending Tx."));
+        span_vec.push(Span::raw(" to cancel a selected Pending Tx, "));
+        span_vec.push(Span::styled("A", Style::default().add_modifier(Modifier::BOLD)));
+        span_vec.push(Span::raw(" to switch account."));
 
         let instructions = Paragraph::new(Spans::from(span_vec)).wrap(Wrap { trim: true });
         f.render_widget(instructions, areas[1]);
@@ -494,6 +496,15 @@ impl<B: Backend> Component<B> for TransactionsTab {
                     return;
                 }
             },
+            'a' => {
+                if let Err(e) = Handle::current().block_on(app_state.select_next_account()) {
+                    self.error_message = Some(format!("Could not switch account.\n{}\nPress Enter to continue.", e));
+                }
+                self.selected_tx_list = SelectedTransactionList::None;
+                self.pending_list_state.select(None);
+                self.completed_list_state.select(None);
+                self.detailed_transaction = None;
+            },
             '\n' => match self.selected_tx_list {
                 SelectedTransactionList::None => {},
                 SelectedTransactionList::PendingTxs => {
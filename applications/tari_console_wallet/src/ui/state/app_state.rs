@@ -34,6 +34,7 @@ use tari_wallet::{
         handle::OutputManagerEventReceiver,
         protocols::txo_validation_protocol::TxoValidationType,
         service::Balance,
+        storage::models::{account_of, Account},
         TxId,
     },
     transaction_service::{
@@ -257,6 +258,20 @@ impl AppState {
         &self.cached_data.connected_peers
     }
 
+    pub fn get_active_account(&self) -> &Account {
+        &self.cached_data.active_account
+    }
+
+    /// Make the account after the active one the active account, wrapping around to the default account
+    pub async fn select_next_account(&mut self) -> Result<(), UiError> {
+        let mut inner = self.inner.write().await;
+        inner.select_next_account().await?;
+        if let Some(data) = inner.get_updated_app_state() {
+            self.cached_data = data;
+        }
+        Ok(())
+    }
+
     pub fn get_balance(&self) -> &Balance {
         &self.cached_data.balance
     }
@@ -355,7 +370,36 @@ impl AppStateInner {
         }
     }
 
+    /// Only the transactions of the active account are shown
+    async fn is_in_active_account(&mut self, tx_id: TxId) -> Result<bool, UiError> {
+        let account_transactions = self.wallet.output_manager_service.get_account_transactions().await?;
+        Ok(account_of(&account_transactions, tx_id) == self.data.active_account.id)
+    }
+
+    pub async fn select_next_account(&mut self) -> Result<(), UiError> {
+        let accounts = self.wallet.output_manager_service.get_accounts().await?;
+        let next = accounts
+            .iter()
+            .position(|a| a.id == self.data.active_account.id)
+            .map(|i| (i + 1) % accounts.len())
+            .unwrap_or(0);
+        if let Some(account) = accounts.get(next) {
+            self.data.active_account = self
+                .wallet
+                .output_manager_service
+                .set_active_account(account.name.clone())
+                .await?;
+        }
+        self.refresh_full_transaction_state().await
+    }
+
     pub async fn refresh_full_transaction_state(&mut self) -> Result<(), UiError> {
+        self.data.active_account = self.wallet.output_manager_service.get_active_account().await?;
+        let account_transactions = self.wallet.output_manager_service.get_account_transactions().await?;
+        let active_account_id = self.data.active_account.id;
+        let in_active_account =
+            |t: &CompletedTransaction| account_of(&account_transactions, t.tx_id) == active_account_id;
+
         let mut pending_transactions: Vec<CompletedTransaction> = Vec::new();
         pending_transactions.extend(
             self.wallet
@@ -376,6 +420,7 @@ impl AppStateInner {
                 .collect::<Vec<CompletedTransaction>>(),
         );
 
+        pending_transactions.retain(in_active_account);
         pending_transactions.sort_by(|a: &CompletedTransaction, b: &CompletedTransaction| {
             b.timestamp.partial_cmp(&a.timestamp).unwrap()
         });
@@ -402,6 +447,7 @@ impl AppStateInner {
                 .collect::<Vec<CompletedTransaction>>(),
         );
 
+        completed_transactions.retain(in_active_account);
         completed_transactions.sort_by(|a, b| {
             b.timestamp
                 .partial_cmp(&a.timestamp)
@@ -452,6 +498,9 @@ impl AppStateInner {
             },
             Some(tx) => {
                 let tx = CompletedTransaction::from(tx);
+                if !self.is_in_active_account(tx_id).await? {
+                    return Ok(());
+                }
                 if let Some(index) = self.data.pending_txs.iter().position(|i| i.tx_id == tx_id) {
                     if tx.status == TransactionStatus::Pending && !tx.cancelled {
                         self.data.pending_txs[index] = tx;
@@ -744,6 +793,7 @@ struct AppStateData {
     contacts: Vec<UiContact>,
     connected_peers: Vec<Peer>,
     balance: Balance,
+    active_account: Account,
     base_node_state: BaseNodeState,
     base_node_selected: Peer,
     base_node_previous: Peer,
@@ -808,6 +858,7 @@ impl AppStateData {
             contacts: Vec::new(),
             connected_peers: Vec::new(),
             balance: Balance::zero(),
+            active_account: Account::default_account(),
             base_node_state: BaseNodeState::default(),
             base_node_selected,
             base_node_previous,
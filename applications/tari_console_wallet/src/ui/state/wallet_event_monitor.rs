@@ -65,7 +65,8 @@ impl WalletEventMonitor {
                             Ok(msg) => {
                                 trace!(target: LOG_TARGET, "Wallet Event Monitor received wallet event {:?}", msg);
                                 match (*msg).clone() {
-                                    TransactionEvent::ReceivedFinalizedTransaction(tx_id) => {
+                                    TransactionEvent::ReceivedFinalizedTransaction(tx_id) |
+                                    TransactionEvent::TransactionImported(tx_id) => {
                                         self.trigger_tx_state_refresh(tx_id).await;
                                         notifier.transaction_received(tx_id);
                                     },
@@ -86,6 +87,7 @@ impl WalletEventMonitor {
                                     TransactionEvent::ReceivedTransaction(tx_id) |
                                     TransactionEvent::ReceivedTransactionReply(tx_id) |
                                     TransactionEvent::TransactionBroadcast(tx_id) |
+                                    TransactionEvent::TransactionDeliveredViaStoreAndForward(tx_id) |
                                     TransactionEvent::TransactionMinedRequestTimedOut(tx_id) => {
                                         self.trigger_tx_state_refresh(tx_id).await;
                                     },
@@ -29,7 +29,7 @@ use crate::{
 
 use log::*;
 use rand::{rngs::OsRng, seq::SliceRandom};
-use std::{fs, fs::remove_file, io::Stdout, net::SocketAddr, path::PathBuf};
+use std::{fs, io::Stdout, net::SocketAddr, path::PathBuf};
 use tari_app_utilities::utilities::ExitCodes;
 use tari_common::GlobalConfig;
 use tari_comms::peer_manager::Peer;
@@ -175,20 +175,18 @@ pub fn tui_mode(
 pub fn recovery_mode(
     handle: Handle,
     config: GlobalConfig,
-    mut wallet: WalletSqlite,
+    wallet: WalletSqlite,
     base_node_selected: Peer,
     base_node_config: PeerConfig,
     notify_script: Option<PathBuf>,
 ) -> Result<(), ExitCodes>
 {
     println!("Starting recovery...");
-    match handle.block_on(wallet_recovery(&mut wallet, &base_node_selected)) {
+    match handle.block_on(wallet_recovery(wallet.clone(), &base_node_selected)) {
         Ok(_) => println!("Wallet recovered!"),
         Err(e) => {
             error!(target: LOG_TARGET, "Recovery failed: {}", e);
-            println!("Recovery failed.");
-            // remove the wallet file
-            remove_file(config.console_wallet_db_file).map_err(|e| ExitCodes::IOError(e.to_string()))?;
+            println!("Recovery failed. Restart the wallet to resume the recovery from where it stopped.");
 
             return Err(e);
         },
@@ -0,0 +1,114 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Generates the genesis block of a custom network.
+//!
+//! The consensus constants are read from a consensus file (see `tari_core::consensus::custom_network`), and a genesis
+//! block holding a single faucet output with the network's faucet value is written as JSON, ready to be used as the
+//! `genesis_block_file` of a base node running on localnet. The faucet's spending key is written to a separate file
+//! that must be kept private.
+
+use rand::rngs::OsRng;
+use serde::Serialize;
+use std::{fs, io, path::PathBuf};
+use structopt::StructOpt;
+use tari_core::{
+    consensus::{custom_network, ConsensusFile, CustomNetworkError},
+    transactions::types::{CryptoFactories, PrivateKey},
+};
+use tari_crypto::{
+    keys::SecretKey,
+    tari_utilities::{epoch_time::EpochTime, hex::Hex},
+};
+use thiserror::Error;
+
+#[derive(Debug, StructOpt)]
+#[structopt(name = "tari_genesis_generator")]
+struct Arguments {
+    /// The consensus file describing the custom network
+    #[structopt(short, long, parse(from_os_str))]
+    consensus_file: PathBuf,
+    /// Where to write the genesis block
+    #[structopt(short, long, parse(from_os_str), default_value = "genesis_block.json")]
+    output: PathBuf,
+    /// Where to write the faucet spending key
+    #[structopt(long, parse(from_os_str), default_value = "faucet_key.json")]
+    faucet_key_file: PathBuf,
+    /// The genesis block timestamp, in seconds since the Unix epoch. Defaults to the current time.
+    #[structopt(long)]
+    timestamp: Option<u64>,
+}
+
+#[derive(Debug, Error)]
+enum GeneratorError {
+    #[error("{0}")]
+    CustomNetworkError(#[from] CustomNetworkError),
+    #[error("Could not write output: {0}")]
+    IoError(#[from] io::Error),
+    #[error("Could not serialize output: {0}")]
+    JsonError(#[from] serde_json::Error),
+}
+
+/// The faucet output of the genesis block, with the key needed to spend it
+#[derive(Serialize)]
+struct FaucetKey {
+    spending_key: String,
+    value: u64,
+    maturity: u64,
+    commitment: String,
+}
+
+fn main() {
+    let args = Arguments::from_args();
+    if let Err(err) = run(args) {
+        eprintln!("Failed to generate the genesis block: {}", err);
+        std::process::exit(1);
+    }
+}
+
+fn run(args: Arguments) -> Result<(), GeneratorError> {
+    let constants = ConsensusFile::load(&args.consensus_file)?.into_consensus_constants()?;
+    let factories = CryptoFactories::default();
+    let faucet_key = PrivateKey::random(&mut OsRng);
+    let timestamp = args.timestamp.map(EpochTime::from).unwrap_or_else(EpochTime::now);
+
+    let genesis_block =
+        custom_network::generate_genesis_block(&factories, &constants[0], faucet_key.clone(), timestamp)?;
+    fs::write(&args.output, serde_json::to_string_pretty(&genesis_block)?)?;
+
+    let faucet = &genesis_block.block.body.outputs()[0];
+    let faucet_key = FaucetKey {
+        spending_key: faucet_key.to_hex(),
+        value: constants[0].faucet_value().into(),
+        maturity: faucet.features.maturity,
+        commitment: faucet.commitment.to_hex(),
+    };
+    fs::write(&args.faucet_key_file, serde_json::to_string_pretty(&faucet_key)?)?;
+
+    println!("Genesis block hash: {}", genesis_block.accumulated_data.hash.to_hex());
+    println!("Genesis block written to {}", args.output.display());
+    println!(
+        "Faucet spending key written to {}, keep it safe",
+        args.faucet_key_file.display()
+    );
+    Ok(())
+}
@@ -31,6 +31,7 @@
 //! where Tari Wallet Node can be found
 //! - num_mining_threads - number of mining threads, defaults to number of cpu cores
 //! - mine_on_tip_only - will start mining only when node is reporting bootstrapped state
+//! - proof_of_work_algo - algorithm to mine with, either `Sha3` (default) or `Blake`
 //! - validate_tip_timeout_sec - will check tip with node every N seconds to validate that still
 //! mining on a tip
 //! All miner options configured under `[mining_node]` section of
@@ -54,6 +55,7 @@ pub struct MinerConfig {
 #[derive(Serialize, Deserialize)]
 pub enum ProofOfWork {
     Sha3,
+    Blake,
 }
 
 impl NetworkConfigPath for MinerConfig {
@@ -93,6 +95,9 @@ impl MinerConfig {
             ProofOfWork::Sha3 => Some(PowAlgo {
                 pow_algo: PowAlgos::Sha3.into(),
             }),
+            ProofOfWork::Blake => Some(PowAlgo {
+                pow_algo: PowAlgos::Blake.into(),
+            }),
         };
         NewBlockTemplateRequest { algo, max_weight: 0 }
     }
@@ -21,23 +21,68 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use crate::errors::{err_empty, MinerError};
-use sha3::{Digest, Sha3_256};
+use sha3::Sha3_256;
 use tari_app_grpc::tari_rpc::BlockHeader;
 use tari_core::large_ints::U256;
+use tari_crypto::common::Blake256;
 
 pub type Difficulty = u64;
 
-pub struct BlockHeaderSha3 {
+/// A hash function the miner can mine with. The header fields are chained in one at a time so that the hash state up
+/// to the timestamp and the nonce can be reused for every attempt.
+pub trait MiningHasher: Clone {
+    fn new() -> Self;
+
+    fn chain<B: AsRef<[u8]>>(self, data: B) -> Self;
+
+    /// Finalizes the header hash and hashes it once more, giving the proof of work hash
+    fn pow_hash(self) -> Vec<u8>;
+}
+
+impl MiningHasher for Sha3_256 {
+    fn new() -> Self {
+        sha3::Digest::new()
+    }
+
+    fn chain<B: AsRef<[u8]>>(self, data: B) -> Self {
+        sha3::Digest::chain(self, data)
+    }
+
+    fn pow_hash(self) -> Vec<u8> {
+        let hash = sha3::Digest::finalize(self);
+        <Sha3_256 as sha3::Digest>::digest(&hash).to_vec()
+    }
+}
+
+impl MiningHasher for Blake256 {
+    fn new() -> Self {
+        digest::Digest::new()
+    }
+
+    fn chain<B: AsRef<[u8]>>(self, data: B) -> Self {
+        digest::Digest::chain(self, data)
+    }
+
+    fn pow_hash(self) -> Vec<u8> {
+        let hash = digest::Digest::result(self);
+        <Blake256 as digest::Digest>::digest(&hash).to_vec()
+    }
+}
+
+pub type BlockHeaderSha3 = BlockHeaderHasher<Sha3_256>;
+pub type BlockHeaderBlake = BlockHeaderHasher<Blake256>;
+
+pub struct BlockHeaderHasher<H> {
     header: BlockHeader,
     pow_bytes: Vec<u8>,
-    hash_before_timestamp: Sha3_256,
-    hash_before_nonce: Sha3_256,
+    hash_before_timestamp: H,
+    hash_before_nonce: H,
     pub timestamp: u64,
     pub nonce: u64,
     pub hashes: u64,
 }
 
-impl BlockHeaderSha3 {
+impl<H: MiningHasher> BlockHeaderHasher<H> {
     pub fn new(header: BlockHeader) -> Result<Self, MinerError> {
         use std::convert::TryFrom;
         use tari_core::proof_of_work::ProofOfWork; // this is only dep left on tari_code
@@ -46,7 +91,7 @@ impl BlockHeaderSha3 {
         let pow = ProofOfWork::try_from(header.pow.clone().ok_or_else(|| err_empty("header.pow"))?)
             .map_err(MinerError::BlockHeader)?;
         let timestamp = header.timestamp.as_ref().ok_or_else(|| err_empty("header.timestamp"))?;
-        let hash_before_timestamp = Sha3_256::new()
+        let hash_before_timestamp = H::new()
             .chain((header.version as u16).to_le_bytes())
             .chain(header.height.to_le_bytes())
             .chain(&header.prev_hash);
@@ -99,8 +144,7 @@ impl BlockHeaderSha3 {
             .clone()
             .chain(self.nonce.to_le_bytes())
             .chain(&self.pow_bytes)
-            .finalize();
-        let hash = Sha3_256::digest(&hash);
+            .pow_hash();
         big_endian_difficulty(&hash)
     }
 
@@ -130,25 +174,31 @@ fn big_endian_difficulty(hash: &[u8]) -> Difficulty {
 pub mod test {
     use super::*;
     use chrono::{DateTime, NaiveDate, Utc};
-    use tari_core::{blocks::BlockHeader as CoreBlockHeader, proof_of_work::sha3_difficulty as core_sha3_difficulty};
+    use tari_core::{
+        blocks::BlockHeader as CoreBlockHeader,
+        proof_of_work::{
+            blake_difficulty as core_blake_difficulty,
+            sha3_difficulty as core_sha3_difficulty,
+            PowAlgorithm,
+        },
+    };
 
-    pub fn get_header() -> (BlockHeader, CoreBlockHeader) {
+    pub fn get_header(algo: PowAlgorithm) -> (BlockHeader, CoreBlockHeader) {
         let mut header = CoreBlockHeader::new(0);
         header.timestamp = DateTime::<Utc>::from_utc(NaiveDate::from_ymd(2000, 1, 1).and_hms(1, 1, 1), Utc).into();
-        header.pow.pow_algo = tari_core::proof_of_work::PowAlgorithm::Sha3;
+        header.pow.pow_algo = algo;
         (header.clone().into(), header)
     }
 
-    #[test]
-    fn validate_nonce_difficulty() {
-        let (mut header, mut core_header) = get_header();
+    fn validate_nonce_difficulty<H: MiningHasher>(algo: PowAlgorithm, core_difficulty: fn(&CoreBlockHeader) -> u64) {
+        let (mut header, mut core_header) = get_header(algo);
         header.nonce = 1;
         core_header.nonce = 1;
-        let mut hasher = BlockHeaderSha3::new(header).unwrap();
+        let mut hasher = BlockHeaderHasher::<H>::new(header).unwrap();
         for _ in 0..1000 {
             assert_eq!(
                 hasher.difficulty(),
-                core_sha3_difficulty(&core_header).as_u64(),
+                core_difficulty(&core_header),
                 "with nonces = {}:{}",
                 hasher.nonce,
                 core_header.nonce
@@ -158,17 +208,20 @@ pub mod test {
         }
     }
 
-    #[test]
-    fn validate_timestamp_difficulty() {
-        let (mut header, mut core_header) = get_header();
+    fn validate_timestamp_difficulty<H: MiningHasher>(
+        algo: PowAlgorithm,
+        core_difficulty: fn(&CoreBlockHeader) -> u64,
+    )
+    {
+        let (mut header, mut core_header) = get_header(algo);
         header.nonce = 1;
         core_header.nonce = 1;
-        let mut hasher = BlockHeaderSha3::new(header).unwrap();
+        let mut hasher = BlockHeaderHasher::<H>::new(header).unwrap();
         let mut timestamp = core_header.timestamp;
         for _ in 0..1000 {
             assert_eq!(
                 hasher.difficulty(),
-                core_sha3_difficulty(&core_header).as_u64(),
+                core_difficulty(&core_header),
                 "with timestamp = {}",
                 timestamp
             );
@@ -177,4 +230,32 @@ pub mod test {
             hasher.set_timestamp(timestamp.as_u64());
         }
     }
+
+    fn sha3_difficulty(header: &CoreBlockHeader) -> u64 {
+        core_sha3_difficulty(header).as_u64()
+    }
+
+    fn blake_difficulty(header: &CoreBlockHeader) -> u64 {
+        core_blake_difficulty(header).as_u64()
+    }
+
+    #[test]
+    fn validate_sha3_nonce_difficulty() {
+        validate_nonce_difficulty::<Sha3_256>(PowAlgorithm::Sha3, sha3_difficulty);
+    }
+
+    #[test]
+    fn validate_sha3_timestamp_difficulty() {
+        validate_timestamp_difficulty::<Sha3_256>(PowAlgorithm::Sha3, sha3_difficulty);
+    }
+
+    #[test]
+    fn validate_blake_nonce_difficulty() {
+        validate_nonce_difficulty::<Blake256>(PowAlgorithm::Blake, blake_difficulty);
+    }
+
+    #[test]
+    fn validate_blake_timestamp_difficulty() {
+        validate_timestamp_difficulty::<Blake256>(PowAlgorithm::Blake, blake_difficulty);
+    }
 }
@@ -20,7 +20,7 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 //
-use super::difficulty::BlockHeaderSha3;
+use super::difficulty::{BlockHeaderBlake, BlockHeaderHasher, BlockHeaderSha3, MiningHasher};
 use crossbeam::channel::{bounded, Select, Sender, TrySendError};
 use futures::Stream;
 use log::*;
@@ -31,6 +31,7 @@ use std::{
     time::{Duration, Instant},
 };
 use tari_app_grpc::{conversions::timestamp, tari_rpc::BlockHeader};
+use tari_core::proof_of_work::PowAlgorithm;
 use thread::JoinHandle;
 
 // Identify how often mining thread is reporting / checking context
@@ -155,7 +156,7 @@ impl Stream for Miner {
 }
 
 /// Miner starts with a random nonce and iterates until it finds a header hash that meets the desired
-/// target
+/// target. The hash function is selected by the proof of work algorithm of the header.
 pub fn mining_task(
     header: BlockHeader,
     target_difficulty: u64,
@@ -163,9 +164,40 @@ pub fn mining_task(
     waker: Waker,
     miner: usize,
 )
+{
+    let is_blake = header
+        .pow
+        .as_ref()
+        .map(|pow| pow.pow_algo == PowAlgorithm::Blake.as_u64())
+        .unwrap_or(false);
+    if is_blake {
+        mine(
+            BlockHeaderBlake::new(header).unwrap(),
+            target_difficulty,
+            sender,
+            waker,
+            miner,
+        )
+    } else {
+        mine(
+            BlockHeaderSha3::new(header).unwrap(),
+            target_difficulty,
+            sender,
+            waker,
+            miner,
+        )
+    }
+}
+
+fn mine<H: MiningHasher>(
+    mut hasher: BlockHeaderHasher<H>,
+    target_difficulty: u64,
+    sender: Sender<MiningReport>,
+    waker: Waker,
+    miner: usize,
+)
 {
     let start = Instant::now();
-    let mut hasher = BlockHeaderSha3::new(header).unwrap();
     hasher.random_nonce();
     // We're mining over here!
     info!("Mining thread {} started", miner);
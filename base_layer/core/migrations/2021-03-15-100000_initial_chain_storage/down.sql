@@ -0,0 +1,18 @@
+DROP TABLE IF EXISTS metadata;
+DROP TABLE IF EXISTS headers;
+DROP TABLE IF EXISTS header_accumulated_data;
+DROP TABLE IF EXISTS mmr_peak_data;
+DROP TABLE IF EXISTS block_hashes;
+DROP TABLE IF EXISTS utxos;
+DROP TABLE IF EXISTS inputs;
+DROP TABLE IF EXISTS txos_hash_to_index;
+DROP TABLE IF EXISTS kernels;
+DROP TABLE IF EXISTS kernel_excess_index;
+DROP TABLE IF EXISTS kernel_excess_sig_index;
+DROP TABLE IF EXISTS kernel_mmr_size_index;
+DROP TABLE IF EXISTS utxo_mmr_size_index;
+DROP TABLE IF EXISTS orphans;
+DROP TABLE IF EXISTS monero_seed_height;
+DROP TABLE IF EXISTS orphan_accumulated_data;
+DROP TABLE IF EXISTS orphan_chain_tips;
+DROP TABLE IF EXISTS orphan_parent_map_index;
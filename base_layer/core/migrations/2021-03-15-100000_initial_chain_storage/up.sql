@@ -0,0 +1,90 @@
+CREATE TABLE metadata (
+    key BLOB PRIMARY KEY NOT NULL,
+    value BLOB NOT NULL
+);
+
+CREATE TABLE headers (
+    key BLOB PRIMARY KEY NOT NULL,
+    value BLOB NOT NULL
+);
+
+CREATE TABLE header_accumulated_data (
+    key BLOB PRIMARY KEY NOT NULL,
+    value BLOB NOT NULL
+);
+
+CREATE TABLE mmr_peak_data (
+    key BLOB PRIMARY KEY NOT NULL,
+    value BLOB NOT NULL
+);
+
+CREATE TABLE block_hashes (
+    key BLOB PRIMARY KEY NOT NULL,
+    value BLOB NOT NULL
+);
+
+CREATE TABLE utxos (
+    key BLOB PRIMARY KEY NOT NULL,
+    value BLOB NOT NULL
+);
+
+CREATE TABLE inputs (
+    key BLOB PRIMARY KEY NOT NULL,
+    value BLOB NOT NULL
+);
+
+CREATE TABLE txos_hash_to_index (
+    key BLOB PRIMARY KEY NOT NULL,
+    value BLOB NOT NULL
+);
+
+CREATE TABLE kernels (
+    key BLOB PRIMARY KEY NOT NULL,
+    value BLOB NOT NULL
+);
+
+CREATE TABLE kernel_excess_index (
+    key BLOB PRIMARY KEY NOT NULL,
+    value BLOB NOT NULL
+);
+
+CREATE TABLE kernel_excess_sig_index (
+    key BLOB PRIMARY KEY NOT NULL,
+    value BLOB NOT NULL
+);
+
+CREATE TABLE kernel_mmr_size_index (
+    key BLOB PRIMARY KEY NOT NULL,
+    value BLOB NOT NULL
+);
+
+CREATE TABLE utxo_mmr_size_index (
+    key BLOB PRIMARY KEY NOT NULL,
+    value BLOB NOT NULL
+);
+
+CREATE TABLE orphans (
+    key BLOB PRIMARY KEY NOT NULL,
+    value BLOB NOT NULL
+);
+
+CREATE TABLE monero_seed_height (
+    key BLOB PRIMARY KEY NOT NULL,
+    value BLOB NOT NULL
+);
+
+CREATE TABLE orphan_accumulated_data (
+    key BLOB PRIMARY KEY NOT NULL,
+    value BLOB NOT NULL
+);
+
+CREATE TABLE orphan_chain_tips (
+    key BLOB PRIMARY KEY NOT NULL,
+    value BLOB NOT NULL
+);
+
+CREATE TABLE orphan_parent_map_index (
+    key BLOB NOT NULL,
+    value BLOB NOT NULL,
+    PRIMARY KEY (key, value)
+);
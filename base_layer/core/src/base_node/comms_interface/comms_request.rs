@@ -21,7 +21,7 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use crate::{
-    blocks::NewBlockTemplate,
+    blocks::{NewBlockTemplate, ShortKernelId},
     chain_storage::MmrTree,
     proof_of_work::PowAlgorithm,
     transactions::types::{Commitment, HashOutput, Signature},
@@ -57,6 +57,7 @@ pub enum NodeCommsRequest {
     GetNewBlockTemplate(GetNewBlockTemplateRequest),
     GetNewBlock(NewBlockTemplate),
     FetchKernelByExcessSig(Signature),
+    FetchMempoolTransactionsByShortKernelIds(HashOutput, Vec<ShortKernelId>),
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -90,6 +91,12 @@ impl Display for NodeCommsRequest {
                 s.get_public_nonce().to_hex(),
                 s.get_signature().to_hex()
             ),
+            FetchMempoolTransactionsByShortKernelIds(block_hash, v) => write!(
+                f,
+                "FetchMempoolTransactionsByShortKernelIds (block={}, n={})",
+                block_hash.to_hex(),
+                v.len()
+            ),
         }
     }
 }
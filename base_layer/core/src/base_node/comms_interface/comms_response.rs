@@ -21,11 +21,11 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use crate::{
-    blocks::{block_header::BlockHeader, Block, NewBlockTemplate},
+    blocks::{block_header::BlockHeader, Block, NewBlockTemplate, ShortKernelId},
     chain_storage::HistoricalBlock,
     proof_of_work::Difficulty,
     transactions::{
-        transaction::{TransactionKernel, TransactionOutput},
+        transaction::{Transaction, TransactionKernel, TransactionOutput},
         types::HashOutput,
     },
 };
@@ -52,6 +52,10 @@ pub enum NodeCommsResponse {
     TargetDifficulty(Difficulty),
     FetchHeadersAfterResponse(Vec<BlockHeader>),
     MmrNodes(Vec<HashOutput>, Vec<u8>),
+    FetchMempoolTransactionsByShortKernelIdsResponse {
+        transactions: Vec<Transaction>,
+        not_found: Vec<ShortKernelId>,
+    },
 }
 
 impl Display for NodeCommsResponse {
@@ -79,6 +83,15 @@ impl Display for NodeCommsResponse {
             TargetDifficulty(_) => write!(f, "TargetDifficulty"),
             FetchHeadersAfterResponse(_) => write!(f, "FetchHeadersAfterResponse"),
             MmrNodes(_, _) => write!(f, "MmrNodes"),
+            FetchMempoolTransactionsByShortKernelIdsResponse {
+                transactions,
+                not_found,
+            } => write!(
+                f,
+                "FetchMempoolTransactionsByShortKernelIdsResponse({} transaction(s), {} not found)",
+                transactions.len(),
+                not_found.len()
+            ),
         }
     }
 }
@@ -29,15 +29,22 @@ use crate::{
         },
         OutboundNodeCommsInterface,
     },
-    blocks::{block_header::BlockHeader, Block, NewBlock, NewBlockTemplate},
-    chain_storage::{async_db::AsyncBlockchainDb, BlockAddResult, BlockchainBackend, ChainBlock},
+    blocks::{
+        block_header::{BlockHeader, BlockHeaderValidationError},
+        short_kernel_id,
+        Block,
+        NewBlock,
+        NewBlockTemplate,
+    },
+    chain_storage::{async_db::AsyncBlockchainDb, BlockAddResult, BlockchainBackend, ChainBlock, ChainStorageError},
     consensus::{ConsensusConstants, ConsensusManager},
     mempool::{async_mempool, Mempool},
-    proof_of_work::{Difficulty, PowAlgorithm},
-    transactions::transaction::TransactionKernel,
+    proof_of_work::{Difficulty, PowAlgorithm, PowError},
+    transactions::{aggregated_body::AggregateBody, transaction::TransactionKernel},
 };
 use log::*;
 use std::{
+    collections::HashSet,
     fmt::{Display, Error, Formatter},
     sync::Arc,
 };
@@ -372,6 +379,15 @@ where T: BlockchainBackend + 'static
 
                 let mut header = BlockHeader::from_previous(&best_block_header.header)?;
                 let constants = self.consensus_manager.consensus_constants(header.height);
+                if !constants.accepts_pow_algorithm(request.algo) {
+                    return Err(
+                        BlockHeaderValidationError::ProofOfWorkError(PowError::AlgorithmNotAccepted {
+                            algo: request.algo,
+                            height: header.height,
+                        })
+                        .into(),
+                    );
+                }
                 header.version = constants.blockchain_version();
                 header.pow.pow_algo = request.algo;
 
@@ -427,6 +443,14 @@ where T: BlockchainBackend + 'static
 
                 Ok(NodeCommsResponse::TransactionKernels(kernels))
             },
+            NodeCommsRequest::FetchMempoolTransactionsByShortKernelIds(block_hash, short_ids) => {
+                let (transactions, not_found) =
+                    async_mempool::retrieve_by_short_kernel_ids(self.mempool.clone(), block_hash, short_ids).await?;
+                Ok(NodeCommsResponse::FetchMempoolTransactionsByShortKernelIdsResponse {
+                    transactions: transactions.into_iter().map(|tx| (*tx).clone()).collect(),
+                    not_found,
+                })
+            },
         }
     }
 
@@ -440,13 +464,14 @@ where T: BlockchainBackend + 'static
         source_peer: NodeId,
     ) -> Result<(), CommsInterfaceError>
     {
-        let NewBlock { block_hash } = new_block;
+        let block_hash = new_block.hash();
 
         // Only a single block request can complete at a time.
         // As multiple NewBlock requests arrive from propagation, this semaphore prevents multiple requests to nodes for
         // the same full block. The first request that succeeds will stop the node from requesting the block from any
         // other node (block_exists is true).
-        let _permit = self.new_block_request_semaphore.acquire().await;
+        let semaphore = self.new_block_request_semaphore.clone();
+        let _permit = semaphore.acquire().await;
 
         if self.blockchain_db.block_exists(block_hash.clone()).await? {
             debug!(
@@ -457,9 +482,170 @@ where T: BlockchainBackend + 'static
             return Ok(());
         }
 
+        let header = match new_block.header.as_ref() {
+            Some(header) => header,
+            // The peer only propagated the block hash
+            None => return self.handle_full_block_from_peer(block_hash, source_peer).await,
+        };
+        if header.hash() != block_hash {
+            return Err(CommsInterfaceError::InvalidPeerResponse(format!(
+                "Peer `{}` propagated a block header that does not match the block hash `{}`",
+                source_peer.short_str(),
+                block_hash.to_hex()
+            )));
+        }
+
+        debug!(
+            target: LOG_TARGET,
+            "Block with hash `{}` is unknown. Reconstructing it from the mempool.",
+            block_hash.to_hex(),
+        );
+        let block = match self.reconstruct_block(new_block, &source_peer).await? {
+            Some(block) => block,
+            None => return self.handle_full_block_from_peer(block_hash, source_peer).await,
+        };
+
+        match self
+            .handle_block(Arc::new(block), true.into(), Some(source_peer.clone()))
+            .await
+        {
+            Ok(_) => Ok(()),
+            // The reconstructed block may differ from the propagated block (e.g. the mempool contains a different
+            // transaction with the same kernel, or a kernel whose short ID collides), so give the full block a chance
+            // before giving up
+            Err(CommsInterfaceError::ChainStorageError(err))
+                if matches!(
+                    err,
+                    ChainStorageError::ValidationError { .. } | ChainStorageError::MismatchedMmrRoot(_)
+                ) =>
+            {
+                debug!(
+                    target: LOG_TARGET,
+                    "Reconstructed block `{}` failed validation ({}). Requesting the full block from peer `{}`.",
+                    block_hash.to_hex(),
+                    err,
+                    source_peer.short_str()
+                );
+                self.handle_full_block_from_peer(block_hash, source_peer).await
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Reconstructs the full block from a compact `NewBlock` using the transactions in the local mempool. Transactions
+    /// that are not in the mempool are requested from the source peer. `None` is returned if the block could not be
+    /// reconstructed, in which case the full block should be requested instead.
+    async fn reconstruct_block(
+        &mut self,
+        new_block: NewBlock,
+        source_peer: &NodeId,
+    ) -> Result<Option<Block>, CommsInterfaceError>
+    {
+        let NewBlock {
+            block_hash,
+            header,
+            coinbase_kernels,
+            coinbase_outputs,
+            kernel_short_ids,
+        } = new_block;
+        let header = match header {
+            Some(header) => header,
+            None => return Ok(None),
+        };
+
+        let (known_transactions, missing_short_ids) = async_mempool::retrieve_by_short_kernel_ids(
+            self.mempool.clone(),
+            block_hash.clone(),
+            kernel_short_ids.clone(),
+        )
+        .await?;
+        let mut transactions = known_transactions
+            .into_iter()
+            .map(|tx| (*tx).clone())
+            .collect::<Vec<_>>();
+
+        if !missing_short_ids.is_empty() {
+            debug!(
+                target: LOG_TARGET,
+                "{} of {} transaction(s) for block #{} are not in the mempool. Requesting them from peer `{}`.",
+                missing_short_ids.len(),
+                kernel_short_ids.len(),
+                header.height,
+                source_peer.short_str()
+            );
+            let (missing_transactions, not_found) = match self
+                .outbound_nci
+                .request_transactions_by_short_kernel_ids_from_peer(
+                    block_hash.clone(),
+                    missing_short_ids,
+                    Some(source_peer.clone()),
+                )
+                .await
+            {
+                Ok(resp) => resp,
+                Err(err) => {
+                    debug!(
+                        target: LOG_TARGET,
+                        "Failed to request missing transactions from peer `{}`: {}",
+                        source_peer.short_str(),
+                        err
+                    );
+                    return Ok(None);
+                },
+            };
+            if !not_found.is_empty() {
+                debug!(
+                    target: LOG_TARGET,
+                    "Peer `{}` could not provide {} of the missing transaction(s)",
+                    source_peer.short_str(),
+                    not_found.len()
+                );
+                return Ok(None);
+            }
+            transactions.extend(missing_transactions);
+        }
+
+        // The transactions must contain exactly the kernels of the propagated block
+        let expected_short_ids = kernel_short_ids.iter().collect::<HashSet<_>>();
+        let short_ids = transactions
+            .iter()
+            .flat_map(|tx| tx.body.kernels())
+            .map(|kernel| short_kernel_id(&block_hash, &kernel.excess_sig))
+            .collect::<Vec<_>>();
+        if short_ids.len() != kernel_short_ids.len() || short_ids.iter().any(|id| !expected_short_ids.contains(id)) {
+            debug!(
+                target: LOG_TARGET,
+                "Transactions for block #{} do not match the propagated kernels", header.height
+            );
+            return Ok(None);
+        }
+
+        let mut inputs = Vec::new();
+        let mut outputs = coinbase_outputs;
+        let mut kernels = coinbase_kernels;
+        for tx in transactions {
+            let (tx_inputs, tx_outputs, tx_kernels) = tx.body.dissolve();
+            inputs.extend(tx_inputs);
+            outputs.extend(tx_outputs);
+            kernels.extend(tx_kernels);
+        }
+        let mut body = AggregateBody::new(inputs, outputs, kernels);
+        body.do_cut_through();
+        body.sort();
+
+        Ok(Some(Block { header, body }))
+    }
+
+    /// Requests the full block from the source peer and handles it.
+    async fn handle_full_block_from_peer(
+        &mut self,
+        block_hash: BlockHash,
+        source_peer: NodeId,
+    ) -> Result<(), CommsInterfaceError>
+    {
         debug!(
             target: LOG_TARGET,
-            "Block with hash `{}` is unknown. Requesting it from peer `{}`.",
+            "Requesting full block `{}` from peer `{}`.",
             block_hash.to_hex(),
             source_peer.short_str()
         );
@@ -525,7 +711,7 @@ where T: BlockchainBackend + 'static
 
                 self.blockchain_db.cleanup_orphans().await?;
 
-                self.publish_block_event(BlockEvent::ValidBlockAdded(block, block_add_result, broadcast));
+                self.publish_block_event(BlockEvent::ValidBlockAdded(block.clone(), block_add_result, broadcast));
 
                 if should_propagate && broadcast.is_true() {
                     info!(
@@ -534,7 +720,7 @@ where T: BlockchainBackend + 'static
                         block_hash.to_hex()
                     );
                     let exclude_peers = source_peer.into_iter().collect();
-                    let new_block = NewBlock::new(block_hash.clone());
+                    let new_block = NewBlock::from(&*block);
                     self.outbound_nci.propagate_block(new_block, exclude_peers).await?;
                 }
                 Ok(block_hash)
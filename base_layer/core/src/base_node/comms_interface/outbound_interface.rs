@@ -22,9 +22,12 @@
 
 use crate::{
     base_node::comms_interface::{error::CommsInterfaceError, NodeCommsRequest, NodeCommsResponse},
-    blocks::{block_header::BlockHeader, NewBlock},
+    blocks::{block_header::BlockHeader, NewBlock, ShortKernelId},
     chain_storage::HistoricalBlock,
-    transactions::{transaction::TransactionOutput, types::HashOutput},
+    transactions::{
+        transaction::{Transaction, TransactionOutput},
+        types::HashOutput,
+    },
 };
 use futures::channel::mpsc::UnboundedSender;
 use log::*;
@@ -239,6 +242,33 @@ impl OutboundNodeCommsInterface {
         }
     }
 
+    /// Fetch the transactions containing the kernels with the given short IDs in the block with the given hash from the
+    /// mempool of a specific base node. The short IDs that the base node could not find in its mempool are returned
+    /// along with the transactions.
+    pub async fn request_transactions_by_short_kernel_ids_from_peer(
+        &mut self,
+        block_hash: BlockHash,
+        short_ids: Vec<ShortKernelId>,
+        node_id: Option<NodeId>,
+    ) -> Result<(Vec<Transaction>, Vec<ShortKernelId>), CommsInterfaceError>
+    {
+        if let NodeCommsResponse::FetchMempoolTransactionsByShortKernelIdsResponse {
+            transactions,
+            not_found,
+        } = self
+            .request_sender
+            .call((
+                NodeCommsRequest::FetchMempoolTransactionsByShortKernelIds(block_hash, short_ids),
+                node_id,
+            ))
+            .await??
+        {
+            Ok((transactions, not_found))
+        } else {
+            Err(CommsInterfaceError::UnexpectedApiResponse)
+        }
+    }
+
     /// Transmit a block to remote base nodes, excluding the provided peers.
     pub async fn propagate_block(
         &self,
@@ -48,7 +48,7 @@ pub mod state_machine_service;
 #[cfg(feature = "base_node")]
 pub use state_machine_service::{BaseNodeStateMachine, BaseNodeStateMachineConfig, StateMachineHandle};
 
-#[cfg(feature = "base_node")]
+#[cfg(any(feature = "base_node", feature = "base_node_proto"))]
 pub mod sync;
 #[cfg(feature = "base_node")]
 pub use sync::{
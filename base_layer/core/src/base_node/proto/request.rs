@@ -31,6 +31,7 @@ use crate::{
             FetchHeadersAfter as ProtoFetchHeadersAfter,
             HashOutputs,
         },
+        utils::short_kernel_ids_from_bytes,
     },
     transactions::types::{Commitment, HashOutput, Signature},
 };
@@ -89,6 +90,12 @@ impl TryInto<ci::NodeCommsRequest> for ProtoNodeCommsRequest {
             FetchKernelByExcessSig(sig) => ci::NodeCommsRequest::FetchKernelByExcessSig(
                 Signature::try_from(sig).map_err(|err: ByteArrayError| err.to_string())?,
             ),
+            FetchMempoolTransactionsByShortKernelIds(request) => {
+                ci::NodeCommsRequest::FetchMempoolTransactionsByShortKernelIds(
+                    request.block_hash,
+                    short_kernel_ids_from_bytes(&request.short_ids)?,
+                )
+            },
         };
         Ok(request)
     }
@@ -130,6 +137,12 @@ impl From<ci::NodeCommsRequest> for ProtoNodeCommsRequest {
             },
             GetNewBlock(block_template) => ProtoNodeCommsRequest::GetNewBlock(block_template.into()),
             FetchKernelByExcessSig(signature) => ProtoNodeCommsRequest::FetchKernelByExcessSig(signature.into()),
+            FetchMempoolTransactionsByShortKernelIds(block_hash, short_ids) => {
+                ProtoNodeCommsRequest::FetchMempoolTransactionsByShortKernelIds(proto::ShortKernelIds {
+                    block_hash,
+                    short_ids: short_ids.iter().map(|id| id.to_vec()).collect(),
+                })
+            },
         }
     }
 }
@@ -31,6 +31,7 @@ use crate::{
         base_node as base_node_proto,
         base_node::{
             BlockHeaders as ProtoBlockHeaders,
+            FetchMempoolTransactionsResponse as ProtoFetchMempoolTransactionsResponse,
             HistoricalBlocks as ProtoHistoricalBlocks,
             MmrNodes as ProtoMmrNodes,
             NewBlockResponse as ProtoNewBlockResponse,
@@ -38,6 +39,7 @@ use crate::{
             TransactionOutputs as ProtoTransactionOutputs,
         },
         core as core_proto_types,
+        utils::short_kernel_ids_from_bytes,
     },
     tari_utilities::convert::try_convert_all,
 };
@@ -86,6 +88,14 @@ impl TryInto<ci::NodeCommsResponse> for ProtoNodeCommsResponse {
             },
             TargetDifficulty(difficulty) => ci::NodeCommsResponse::TargetDifficulty(Difficulty::from(difficulty)),
             MmrNodes(response) => ci::NodeCommsResponse::MmrNodes(response.added, response.deleted),
+            FetchMempoolTransactionsByShortKernelIdsResponse(response) => {
+                let transactions = try_convert_all(response.transactions)?;
+                let not_found = short_kernel_ids_from_bytes(&response.not_found)?;
+                ci::NodeCommsResponse::FetchMempoolTransactionsByShortKernelIdsResponse {
+                    transactions,
+                    not_found,
+                }
+            },
         };
 
         Ok(response)
@@ -127,6 +137,15 @@ impl From<ci::NodeCommsResponse> for ProtoNodeCommsResponse {
             }),
             TargetDifficulty(difficulty) => ProtoNodeCommsResponse::TargetDifficulty(difficulty.as_u64()),
             MmrNodes(added, deleted) => ProtoNodeCommsResponse::MmrNodes(ProtoMmrNodes { added, deleted }),
+            FetchMempoolTransactionsByShortKernelIdsResponse {
+                transactions,
+                not_found,
+            } => ProtoNodeCommsResponse::FetchMempoolTransactionsByShortKernelIdsResponse(
+                ProtoFetchMempoolTransactionsResponse {
+                    transactions: transactions.into_iter().map(Into::into).collect(),
+                    not_found: not_found.iter().map(|id| id.to_vec()).collect(),
+                },
+            ),
         }
     }
 }
@@ -26,9 +26,11 @@ mod service;
 use crate::base_node::StateMachineHandle;
 use crate::proto::{
     base_node::{
+        BlockOutputsResponse,
         FetchMatchingUtxos,
         FetchUtxosResponse,
         Signatures,
+        TipInfoResponse,
         TxQueryBatchResponses,
         TxQueryResponse,
         TxSubmissionResponse,
@@ -68,6 +70,12 @@ pub trait BaseNodeWalletService: Send + Sync + 'static {
         &self,
         request: Request<FetchMatchingUtxos>,
     ) -> Result<Response<FetchUtxosResponse>, RpcStatus>;
+
+    #[rpc(method = 5)]
+    async fn get_tip_info(&self, request: Request<()>) -> Result<Response<TipInfoResponse>, RpcStatus>;
+
+    #[rpc(method = 6)]
+    async fn get_block_outputs(&self, request: Request<u64>) -> Result<Response<BlockOutputsResponse>, RpcStatus>;
 }
 
 #[cfg(feature = "base_node")]
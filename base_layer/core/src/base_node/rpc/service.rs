@@ -22,13 +22,15 @@
 
 use crate::{
     base_node::{rpc::BaseNodeWalletService, state_machine_service::states::StateInfo, StateMachineHandle},
-    chain_storage::{async_db::AsyncBlockchainDb, BlockchainBackend},
+    chain_storage::{async_db::AsyncBlockchainDb, BlockchainBackend, PrunedOutput},
     mempool::{service::MempoolHandle, TxStorageResponse},
     proto::{
         base_node::{
+            BlockOutputsResponse,
             FetchMatchingUtxos,
             FetchUtxosResponse,
             Signatures as SignaturesProto,
+            TipInfoResponse,
             TxLocation,
             TxQueryBatchResponse,
             TxQueryBatchResponses,
@@ -127,6 +129,7 @@ impl<B: BlockchainBackend + 'static> BaseNodeWalletRpcService<B> {
             TxStorageResponse::NotStoredOrphan |
             TxStorageResponse::NotStoredTimeLocked |
             TxStorageResponse::NotStoredAlreadySpent |
+            TxStorageResponse::NotStoredInvalid |
             TxStorageResponse::NotStored => TxQueryResponse {
                 location: TxLocation::NotStored as i32,
                 block_hash: None,
@@ -181,7 +184,7 @@ impl<B: BlockchainBackend + 'static> BaseNodeWalletService for BaseNodeWalletRpc
                 is_synced,
             },
 
-            TxStorageResponse::NotStored => TxSubmissionResponse {
+            TxStorageResponse::NotStoredInvalid | TxStorageResponse::NotStored => TxSubmissionResponse {
                 accepted: false,
                 rejection_reason: TxSubmissionRejectionReason::ValidationFailed.into(),
                 is_synced,
@@ -308,4 +311,102 @@ impl<B: BlockchainBackend + 'static> BaseNodeWalletService for BaseNodeWalletRpc
             is_synced,
         }))
     }
+
+    async fn get_tip_info(&self, _request: Request<()>) -> Result<Response<TipInfoResponse>, RpcStatus> {
+        let state_machine = self.state_machine();
+        // Determine if we are synced
+        let status_watch = state_machine.get_status_info_watch();
+        let is_synced = match (*status_watch.borrow()).state_info {
+            StateInfo::Listening(li) => li.is_synced(),
+            _ => false,
+        };
+
+        let metadata = self
+            .db()
+            .get_chain_metadata()
+            .await
+            .map_err(RpcStatus::log_internal_error(LOG_TARGET))?;
+
+        Ok(Response::new(TipInfoResponse {
+            metadata: Some(metadata.into()),
+            is_synced,
+        }))
+    }
+
+    async fn get_block_outputs(&self, request: Request<u64>) -> Result<Response<BlockOutputsResponse>, RpcStatus> {
+        let height = request.into_message();
+
+        let state_machine = self.state_machine();
+        // Determine if we are synced
+        let status_watch = state_machine.get_status_info_watch();
+        let is_synced = match (*status_watch.borrow()).state_info {
+            StateInfo::Listening(li) => li.is_synced(),
+            _ => false,
+        };
+
+        let db = self.db();
+        let metadata = db
+            .get_chain_metadata()
+            .await
+            .map_err(RpcStatus::log_internal_error(LOG_TARGET))?;
+        if height > metadata.height_of_longest_chain() {
+            return Err(RpcStatus::not_found(format!("Block not found at height {}", height)));
+        }
+
+        // Pruned nodes do not have full blocks below the pruning horizon, so the outputs and kernels are read by MMR
+        // position. Outputs that were spent and pruned are not returned.
+        let header = db
+            .fetch_chain_header(height)
+            .await
+            .map_err(RpcStatus::log_internal_error(LOG_TARGET))?;
+        let (prev_output_mmr_size, prev_kernel_mmr_size) = if height == 0 {
+            (0, 0)
+        } else {
+            let prev_header = db
+                .fetch_header(height - 1)
+                .await
+                .map_err(RpcStatus::log_internal_error(LOG_TARGET))?
+                .ok_or_else(|| RpcStatus::not_found(format!("Block header not found at height {}", height - 1)))?;
+            (prev_header.output_mmr_size, prev_header.kernel_mmr_size)
+        };
+
+        let outputs = if header.header.output_mmr_size > prev_output_mmr_size {
+            let (outputs, _) = db
+                .fetch_utxos_by_mmr_position(
+                    prev_output_mmr_size,
+                    header.header.output_mmr_size - 1,
+                    metadata.best_block().clone(),
+                )
+                .await
+                .map_err(RpcStatus::log_internal_error(LOG_TARGET))?;
+            outputs
+                .into_iter()
+                .filter_map(|output| match output {
+                    PrunedOutput::NotPruned { output } => Some(output.into()),
+                    PrunedOutput::Pruned { .. } => None,
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let kernels = if header.header.kernel_mmr_size > prev_kernel_mmr_size {
+            db.fetch_kernels_by_mmr_position(prev_kernel_mmr_size, header.header.kernel_mmr_size - 1)
+                .await
+                .map_err(RpcStatus::log_internal_error(LOG_TARGET))?
+                .into_iter()
+                .map(Into::into)
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let header_hash = header.hash().clone();
+
+        Ok(Response::new(BlockOutputsResponse {
+            height,
+            header_hash,
+            outputs,
+            is_synced,
+            kernels,
+        }))
+    }
 }
@@ -34,7 +34,7 @@ use crate::{
         StateMachineHandle,
     },
     blocks::{Block, NewBlock},
-    chain_storage::BlockchainBackend,
+    chain_storage::{BlockchainBackend, ChainStorageError},
     proto as shared_protos,
     proto::{base_node as proto, base_node::base_node_service_request::Request},
 };
@@ -55,7 +55,10 @@ use tari_common_types::{
     types::BlockHash,
     waiting_requests::{generate_request_key, RequestKey, WaitingRequests},
 };
-use tari_comms::peer_manager::NodeId;
+use tari_comms::{
+    connectivity::ConnectivityRequester,
+    peer_manager::{NodeId, ReputationEvent, SuccessfulExchangeDebouncer},
+};
 use tari_comms_dht::{
     domain_message::OutboundDomainMessage,
     envelope::NodeDestination,
@@ -67,6 +70,9 @@ use tari_service_framework::reply_channel::RequestContext;
 use tokio::task;
 
 const LOG_TARGET: &str = "c::bn::base_node_service::service";
+/// Every reputation event is written to the peer database, so a peer that propagates many valid blocks is credited
+/// with a successful exchange at most once in this interval.
+const SUCCESSFUL_EXCHANGE_REPORT_INTERVAL: Duration = Duration::from_secs(60);
 
 /// Configuration for the BaseNodeService.
 #[derive(Clone, Copy)]
@@ -123,6 +129,8 @@ pub(super) struct BaseNodeService<B> {
     timeout_receiver_stream: Option<Receiver<RequestKey>>,
     config: BaseNodeServiceConfig,
     state_machine_handle: StateMachineHandle,
+    connectivity: ConnectivityRequester,
+    exchange_debouncer: SuccessfulExchangeDebouncer,
 }
 
 impl<B> BaseNodeService<B>
@@ -133,6 +141,7 @@ where B: BlockchainBackend + 'static
         inbound_nch: InboundNodeCommsHandlers<B>,
         config: BaseNodeServiceConfig,
         state_machine_handle: StateMachineHandle,
+        connectivity: ConnectivityRequester,
     ) -> Self
     {
         let (timeout_sender, timeout_receiver) = channel(100);
@@ -144,6 +153,8 @@ where B: BlockchainBackend + 'static
             timeout_receiver_stream: Some(timeout_receiver),
             config,
             state_machine_handle,
+            connectivity,
+            exchange_debouncer: SuccessfulExchangeDebouncer::new(SUCCESSFUL_EXCHANGE_REPORT_INTERVAL),
         }
     }
 
@@ -321,14 +332,16 @@ where B: BlockchainBackend + 'static
             debug!(
                 target: LOG_TARGET,
                 "Propagated block `{}` from peer `{}` not processed while busy with initial sync.",
-                new_block.inner.block_hash.to_hex(),
+                new_block.inner.hash().to_hex(),
                 new_block.source_peer.node_id.short_str(),
             );
             return;
         }
         let inbound_nch = self.inbound_nch.clone();
+        let connectivity = self.connectivity.clone();
+        let exchange_debouncer = self.exchange_debouncer.clone();
         task::spawn(async move {
-            let result = handle_incoming_block(inbound_nch, new_block).await;
+            let result = handle_incoming_block(inbound_nch, connectivity, exchange_debouncer, new_block).await;
 
             if let Err(e) = result {
                 error!(target: LOG_TARGET, "Failed to handle incoming block message: {:?}", e);
@@ -665,6 +678,8 @@ fn spawn_request_timeout(mut timeout_sender: Sender<RequestKey>, request_key: Re
 
 async fn handle_incoming_block<B: BlockchainBackend + 'static>(
     mut inbound_nch: InboundNodeCommsHandlers<B>,
+    mut connectivity: ConnectivityRequester,
+    exchange_debouncer: SuccessfulExchangeDebouncer,
     domain_block_msg: DomainMessage<NewBlock>,
 ) -> Result<(), BaseNodeServiceError>
 {
@@ -677,15 +692,33 @@ async fn handle_incoming_block<B: BlockchainBackend + 'static>(
     debug!(
         target: LOG_TARGET,
         "New candidate block with hash `{}` received from `{}`.",
-        new_block.block_hash.to_hex(),
+        new_block.hash().to_hex(),
         source_peer.node_id.short_str()
     );
 
-    inbound_nch
-        .handle_new_block_message(new_block, source_peer.node_id)
-        .await?;
-
-    // TODO - retain peer info for stats and potential banning for sending invalid blocks
+    let result = inbound_nch
+        .handle_new_block_message(new_block, source_peer.node_id.clone())
+        .await;
+
+    let reputation_event = match &result {
+        Ok(_) if exchange_debouncer.should_report(&source_peer.node_id) => Some(ReputationEvent::SuccessfulExchange),
+        Ok(_) => None,
+        // Only penalise the peer if the block is provably invalid. Other validation errors may be caused by our own
+        // chain state or clock and the block could have been propagated in good faith.
+        Err(CommsInterfaceError::ChainStorageError(ChainStorageError::ValidationError { source }))
+            if source.proves_block_is_invalid() =>
+        {
+            Some(ReputationEvent::InvalidBlock)
+        },
+        Err(CommsInterfaceError::InvalidPeerResponse(_)) => Some(ReputationEvent::RpcError),
+        Err(_) => None,
+    };
+    if let Some(event) = reputation_event {
+        if let Err(err) = connectivity.report_peer(source_peer.node_id, event).await {
+            warn!(target: LOG_TARGET, "Failed to report peer: {}", err);
+        }
+    }
 
+    result?;
     Ok(())
 }
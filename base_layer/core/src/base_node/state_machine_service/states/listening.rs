@@ -40,6 +40,7 @@ use std::{
     ops::Deref,
 };
 use tari_common_types::chain_metadata::ChainMetadata;
+use tari_comms::PeerManager;
 use tari_crypto::tari_utilities::epoch_time::EpochTime;
 use tokio::sync::broadcast;
 
@@ -186,6 +187,7 @@ impl Listening {
                     } else {
                         peer_metadata_list
                     };
+                    let sync_peers = order_by_reputation(&shared.peer_manager, sync_peers).await;
                     let sync_mode = determine_sync_mode(
                         shared.config.blocks_behind_before_considered_lagging,
                         &local,
@@ -260,6 +262,21 @@ fn select_sync_peers(
         .collect()
 }
 
+/// Orders the sync peers from the most to the least reputable, so that peers with a poor reputation are tried last.
+async fn order_by_reputation(peer_manager: &PeerManager, sync_peers: Vec<PeerChainMetadata>) -> Vec<PeerChainMetadata> {
+    let mut scored_peers = Vec::with_capacity(sync_peers.len());
+    for peer in sync_peers {
+        let score = peer_manager
+            .find_by_node_id(&peer.node_id)
+            .await
+            .map(|p| p.reputation().score())
+            .unwrap_or_default();
+        scored_peers.push((score, peer));
+    }
+    scored_peers.sort_by(|(score_a, _), (score_b, _)| score_b.cmp(score_a));
+    scored_peers.into_iter().map(|(_, peer)| peer).collect()
+}
+
 /// Determine the best metadata from a set of metadata received from the network.
 fn best_metadata(metadata_list: &[PeerChainMetadata]) -> Option<&ChainMetadata> {
     // TODO: Use heuristics to weed out outliers / dishonest nodes.
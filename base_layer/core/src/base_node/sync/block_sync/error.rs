@@ -23,6 +23,7 @@
 use crate::{chain_storage::ChainStorageError, proof_of_work::PowError, validation::ValidationError};
 use tari_comms::{
     connectivity::ConnectivityError,
+    peer_manager::ReputationEvent,
     protocol::rpc::{RpcError, RpcStatus},
 };
 
@@ -49,3 +50,17 @@ pub enum BlockSyncError {
     #[error("Block validation failed: {0}")]
     ValidationError(#[from] ValidationError),
 }
+
+impl BlockSyncError {
+    /// Returns the reputation event that should be reported for the sync peer, if this error was caused by the peer
+    pub fn reputation_event(&self) -> Option<ReputationEvent> {
+        use BlockSyncError::*;
+        match self {
+            RpcError(_) | RpcRequestError(_) => Some(ReputationEvent::RpcError),
+            ReceivedInvalidBlockBody(_) | PeerSentBlockThatDidNotFormAChain { .. } | ValidationError(_) => {
+                Some(ReputationEvent::InvalidBlock)
+            },
+            _ => None,
+        }
+    }
+}
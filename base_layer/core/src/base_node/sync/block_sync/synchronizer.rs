@@ -40,7 +40,7 @@ use std::{
 };
 use tari_comms::{
     connectivity::{ConnectivityRequester, ConnectivitySelection},
-    peer_manager::NodeId,
+    peer_manager::{NodeId, ReputationEvent},
     PeerConnection,
 };
 use tokio::task;
@@ -89,7 +89,17 @@ impl<B: BlockchainBackend + 'static> BlockSynchronizer<B> {
             target: LOG_TARGET,
             "Attempting to synchronize blocks with `{}`", node_id
         );
-        self.attempt_block_sync(peer_conn).await?;
+        let result = self.attempt_block_sync(peer_conn).await;
+        let reputation_event = match &result {
+            Ok(_) => Some(ReputationEvent::SuccessfulExchange),
+            Err(err) => err.reputation_event(),
+        };
+        if let Some(event) = reputation_event {
+            if let Err(err) = self.connectivity.report_peer(node_id, event).await {
+                warn!(target: LOG_TARGET, "Failed to report sync peer: {}", err);
+            }
+        }
+        result?;
 
         self.db.cleanup_all_orphans().await?;
         Ok(())
@@ -40,7 +40,7 @@ use log::*;
 use std::{convert::TryFrom, sync::Arc, time::Duration};
 use tari_comms::{
     connectivity::{ConnectivityError, ConnectivityRequester, ConnectivitySelection},
-    peer_manager::NodeId,
+    peer_manager::{NodeId, ReputationEvent},
     protocol::rpc::RpcError,
     PeerConnection,
 };
@@ -102,7 +102,10 @@ impl<'a, B: BlockchainBackend + 'static> HeaderSynchronizer<'a, B> {
                 "Attempting to synchronize headers with `{}`", node_id
             );
             match self.attempt_sync(peer_conn.clone()).await {
-                Ok(()) => return Ok(peer_conn),
+                Ok(()) => {
+                    self.report_peer(node_id, ReputationEvent::SuccessfulExchange).await;
+                    return Ok(peer_conn);
+                },
                 // Try another peer
                 Err(err @ BlockHeaderSyncError::NotInSync) => {
                     debug!(target: LOG_TARGET, "{}", err);
@@ -112,6 +115,13 @@ impl<'a, B: BlockchainBackend + 'static> HeaderSynchronizer<'a, B> {
                     debug!(target: LOG_TARGET, "{}", err);
                     self.ban_peer_short(node_id, BanReason::RpcNegotiationTimedOut).await?;
                 },
+                Err(err @ BlockHeaderSyncError::RpcError(_)) | Err(err @ BlockHeaderSyncError::RpcRequestError(_)) => {
+                    debug!(
+                        target: LOG_TARGET,
+                        "Failed to synchronize headers from peer `{}`: {}", node_id, err
+                    );
+                    self.report_peer(node_id, ReputationEvent::RpcError).await;
+                },
                 Err(BlockHeaderSyncError::ValidationFailed(err)) => {
                     debug!(target: LOG_TARGET, "Block header validation failed: {}", err);
                     self.ban_peer_long(node_id, err.into()).await?;
@@ -193,7 +203,7 @@ impl<'a, B: BlockchainBackend + 'static> HeaderSynchronizer<'a, B> {
             })
             .collect::<FuturesUnordered<_>>();
 
-        let connections = tasks
+        let mut connections = tasks
             .filter_map(|r| match r {
                 Ok(conn) => future::ready(Some(conn)),
                 Err(err) => {
@@ -203,6 +213,8 @@ impl<'a, B: BlockchainBackend + 'static> HeaderSynchronizer<'a, B> {
             })
             .collect::<Vec<_>>()
             .await;
+        // Sync peers are provided in order of preference, so retain that order regardless of which dial completed first
+        connections.sort_by_key(|conn| self.sync_peers.iter().position(|n| n == conn.peer_node_id()));
         debug!(
             target: LOG_TARGET,
             "Successfully dialed {} of {} sync peer(s)",
@@ -212,6 +224,12 @@ impl<'a, B: BlockchainBackend + 'static> HeaderSynchronizer<'a, B> {
         Ok(connections)
     }
 
+    async fn report_peer(&mut self, node_id: NodeId, event: ReputationEvent) {
+        if let Err(err) = self.connectivity.report_peer(node_id, event).await {
+            warn!(target: LOG_TARGET, "Failed to report sync peer: {}", err);
+        }
+    }
+
     async fn ban_peer_long(&mut self, node_id: NodeId, reason: BanReason) -> Result<(), BlockHeaderSyncError> {
         self.ban_peer_for(node_id, reason, self.config.ban_period).await
     }
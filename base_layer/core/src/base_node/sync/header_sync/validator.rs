@@ -35,11 +35,12 @@ use crate::{
     },
     common::rolling_vec::RollingVec,
     consensus::ConsensusManager,
-    proof_of_work::{randomx_factory::RandomXFactory, PowAlgorithm},
+    proof_of_work::randomx_factory::RandomXFactory,
     tari_utilities::{epoch_time::EpochTime, hash::Hashable, hex::Hex},
     transactions::types::HashOutput,
     validation::helpers::{
         check_header_timestamp_greater_than_median,
+        check_pow_algorithm,
         check_pow_data,
         check_target_difficulty,
         check_timestamp_ftl,
@@ -95,10 +96,9 @@ impl<B: BlockchainBackend + 'static> BlockHeaderSyncValidator<B> {
             })?;
         debug!(
             target: LOG_TARGET,
-            "Setting header validator state ({} timestamp(s), target difficulties: {} SHA3, {} Monero)",
+            "Setting header validator state ({} timestamp(s), target difficulties: {})",
             timestamps.len(),
-            target_difficulties.get(PowAlgorithm::Sha3).len(),
-            target_difficulties.get(PowAlgorithm::Monero).len(),
+            target_difficulties,
         );
         self.state = Some(State {
             current_height: start_header.height,
@@ -124,11 +124,8 @@ impl<B: BlockchainBackend + 'static> BlockHeaderSyncValidator<B> {
         let state = self.state();
         check_header_timestamp_greater_than_median(&header, &state.timestamps)?;
 
-        let constants = self.consensus_rules.consensus_constants(header.height);
-        let target_difficulty = state.target_difficulties.get(header.pow_algo()).calculate(
-            constants.min_pow_difficulty(header.pow_algo()),
-            constants.max_pow_difficulty(header.pow_algo()),
-        );
+        check_pow_algorithm(&header, self.consensus_rules.consensus_constants(header.height))?;
+        let target_difficulty = state.target_difficulties.calculate(header.pow_algo(), header.height);
         let achieved = check_target_difficulty(&header, target_difficulty, &self.randomx_factory)?;
         let metadata = BlockHeaderAccumulatedDataBuilder::default()
             .hash(header.hash())
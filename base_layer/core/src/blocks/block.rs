@@ -31,10 +31,18 @@ use crate::{
     transactions::{
         aggregated_body::AggregateBody,
         tari_amount::MicroTari,
-        transaction::{Transaction, TransactionError, TransactionInput, TransactionKernel, TransactionOutput},
-        types::CryptoFactories,
+        transaction::{
+            KernelFeatures,
+            Transaction,
+            TransactionError,
+            TransactionInput,
+            TransactionKernel,
+            TransactionOutput,
+        },
+        types::{CryptoFactories, HashDigest, Signature},
     },
 };
+use digest::Digest;
 use log::*;
 use serde::{Deserialize, Serialize};
 use std::{
@@ -42,7 +50,7 @@ use std::{
     fmt::{Display, Formatter},
 };
 use tari_common_types::types::BlockHash;
-use tari_crypto::tari_utilities::Hashable;
+use tari_crypto::tari_utilities::{ByteArray, Hashable};
 use thiserror::Error;
 
 #[derive(Clone, Debug, PartialEq, Error)]
@@ -248,20 +256,76 @@ impl Hashable for Block {
 }
 
 //---------------------------------- NewBlock --------------------------------------------//
+/// The number of bytes in a short kernel ID
+pub const SHORT_KERNEL_ID_LEN: usize = 6;
+
+/// A short identifier for a kernel in a `NewBlock`. It is the truncated hash of the block hash and the kernel excess
+/// signature, so that the IDs of the same kernel differ from block to block.
+pub type ShortKernelId = [u8; SHORT_KERNEL_ID_LEN];
+
+/// Calculates the short ID of the kernel with the given excess signature in the block with the given hash
+pub fn short_kernel_id(block_hash: &[u8], excess_sig: &Signature) -> ShortKernelId {
+    let hash = HashDigest::new()
+        .chain(block_hash)
+        .chain(excess_sig.get_public_nonce().as_bytes())
+        .chain(excess_sig.get_signature().as_bytes())
+        .result();
+    let mut short_id = [0u8; SHORT_KERNEL_ID_LEN];
+    short_id.copy_from_slice(&hash[..SHORT_KERNEL_ID_LEN]);
+    short_id
+}
+
+/// The compact representation of a block that is propagated around the network. Apart from the coinbase, which can
+/// never be in the mempool, the body of the block is represented by the short IDs of its kernels. A receiving node
+/// reconstructs the block from the transactions in its mempool, and only requests the transactions (or the full block)
+/// it is missing from the sending peer.
+#[derive(Clone, Debug, PartialEq)]
 pub struct NewBlock {
+    /// The hash of the block
     pub block_hash: BlockHash,
+    /// The header of the block. Nodes that only propagate the block hash leave this out, in which case the receiver
+    /// requests the full block.
+    pub header: Option<BlockHeader>,
+    /// The coinbase kernels of the block
+    pub coinbase_kernels: Vec<TransactionKernel>,
+    /// The coinbase outputs of the block
+    pub coinbase_outputs: Vec<TransactionOutput>,
+    /// The short IDs of the remaining (non-coinbase) kernels in the block
+    pub kernel_short_ids: Vec<ShortKernelId>,
 }
 
 impl NewBlock {
-    pub fn new(block_hash: BlockHash) -> Self {
-        Self { block_hash }
+    /// The hash of the block this `NewBlock` represents
+    pub fn hash(&self) -> BlockHash {
+        self.block_hash.clone()
     }
 }
 
 impl From<&Block> for NewBlock {
     fn from(block: &Block) -> Self {
+        let block_hash = block.hash();
+        let (coinbase_kernels, kernels) = block
+            .body
+            .kernels()
+            .iter()
+            .cloned()
+            .partition::<Vec<_>, _>(|k| k.features.contains(KernelFeatures::COINBASE_KERNEL));
+        let kernel_short_ids = kernels
+            .iter()
+            .map(|k| short_kernel_id(&block_hash, &k.excess_sig))
+            .collect();
         Self {
-            block_hash: block.hash(),
+            block_hash,
+            header: Some(block.header.clone()),
+            coinbase_kernels,
+            coinbase_outputs: block
+                .body
+                .outputs()
+                .iter()
+                .filter(|o| o.is_coinbase())
+                .cloned()
+                .collect(),
+            kernel_short_ids,
         }
     }
 }
@@ -75,6 +75,8 @@ pub enum BlockHeaderValidationError {
     ProofOfWorkError(#[from] PowError),
     #[error("Monero seed hash too old")]
     OldSeedHash,
+    #[error("Block header version {version} is not accepted at height {height}")]
+    InvalidVersion { version: u16, height: u64 },
 }
 
 /// The BlockHeader contains all the metadata for the block, including proof of work, a link to the previous block
@@ -101,6 +101,7 @@ pub fn get_stibbons_genesis_block_raw() -> Block {
         vec![],
         vec![TransactionOutput {
             features: OutputFeatures {
+                version: 0,
                 flags: OutputFlags::COINBASE_OUTPUT,
                 maturity: 60,
             },
@@ -201,6 +202,7 @@ pub fn get_ridcully_genesis_block_raw() -> Block {
         vec![],
         vec![TransactionOutput {
             features: OutputFeatures {
+                version: 0,
                 flags: OutputFlags::COINBASE_OUTPUT,
                 maturity: 60,
             },
@@ -27,7 +27,15 @@ mod new_blockheader_template;
 
 pub mod genesis_block;
 
-pub use block::{Block, BlockBuilder, BlockValidationError, NewBlock};
+pub use block::{
+    short_kernel_id,
+    Block,
+    BlockBuilder,
+    BlockValidationError,
+    NewBlock,
+    ShortKernelId,
+    SHORT_KERNEL_ID_LEN,
+};
 pub use block_header::{BlockHeader, BlockHeaderValidationError};
 pub use new_block_template::NewBlockTemplate;
 pub use new_blockheader_template::NewBlockHeaderTemplate;
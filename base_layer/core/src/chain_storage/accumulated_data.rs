@@ -204,16 +204,14 @@ impl BlockHeaderAccumulatedDataBuilder {
         achieved: Difficulty,
     ) -> Self
     {
-        match algo {
-            PowAlgorithm::Monero => {
-                self.accumulated_monero_difficulty = Some(previous.accumulated_monero_difficulty + achieved);
-                self.accumulated_blake_difficulty = Some(previous.accumulated_blake_difficulty);
-            },
-            PowAlgorithm::Blake => unimplemented!(),
-            PowAlgorithm::Sha3 => {
-                self.accumulated_monero_difficulty = Some(previous.accumulated_monero_difficulty);
-                self.accumulated_blake_difficulty = Some(previous.accumulated_blake_difficulty + achieved);
-            },
+        // Merge mined difficulty is accumulated separately from the difficulty of the natively mined (Sha3 and Blake)
+        // algorithms
+        if algo.implementation().is_merge_mined() {
+            self.accumulated_monero_difficulty = Some(previous.accumulated_monero_difficulty + achieved);
+            self.accumulated_blake_difficulty = Some(previous.accumulated_blake_difficulty);
+        } else {
+            self.accumulated_monero_difficulty = Some(previous.accumulated_monero_difficulty);
+            self.accumulated_blake_difficulty = Some(previous.accumulated_blake_difficulty + achieved);
         }
         self.achieved_difficulty = Some(achieved);
         self
@@ -266,6 +264,7 @@ pub struct BlockHeaderAccumulatedData {
     /// The total accumulated difficulty for each proof of work algorithms for all blocks since Genesis,
     /// but not including this block, tracked separately.
     pub accumulated_monero_difficulty: Difficulty,
+    /// The accumulated difficulty of the algorithms mined by the Tari mining node (Sha3 and Blake)
     pub accumulated_blake_difficulty: Difficulty,
     /// The target difficulty for solving the current block using the specified proof of work algorithm.
     pub target_difficulty: Difficulty,
@@ -281,7 +280,11 @@ impl Display for BlockHeaderAccumulatedData {
             "Accumulated monero difficulty: {}",
             self.accumulated_monero_difficulty
         )?;
-        writeln!(f, "Accumulated sha3 difficulty: {}", self.accumulated_blake_difficulty)?;
+        writeln!(
+            f,
+            "Accumulated sha3/blake difficulty: {}",
+            self.accumulated_blake_difficulty
+        )?;
         writeln!(f, "Target difficulty: {}", self.target_difficulty)?;
         Ok(())
     }
@@ -30,6 +30,7 @@ use crate::{
         BlockchainDatabase,
         ChainBlock,
         ChainHeader,
+        ChainIntegrityReport,
         ChainStorageError,
         DbTransaction,
         HistoricalBlock,
@@ -156,6 +157,10 @@ impl<B: BlockchainBackend + 'static> AsyncBlockchainDb<B> {
 
     make_async_fn!(rewind_to_height(height: u64) -> Vec<Arc<ChainBlock>>, "rewind_to_height");
 
+    make_async_fn!(rewind_to_block_height(height: u64, discard_blocks: bool) -> (u64, Vec<Arc<ChainBlock>>), "rewind_to_block_height");
+
+    make_async_fn!(verify_integrity(balance_check_interval: u64) -> ChainIntegrityReport, "verify_integrity");
+
     //---------------------------------- Headers --------------------------------------------//
     make_async_fn!(fetch_header(height: u64) -> Option<BlockHeader>, "fetch_header");
 
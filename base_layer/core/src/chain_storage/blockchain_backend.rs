@@ -153,3 +153,179 @@ pub trait BlockchainBackend: Send + Sync {
 
     fn fetch_horizon_data(&self) -> Result<Option<HorizonData>, ChainStorageError>;
 }
+
+/// Allows the backend to be chosen at runtime (e.g. from the `db_type` config setting) by boxing it.
+impl<B> BlockchainBackend for Box<B>
+where B: BlockchainBackend + ?Sized
+{
+    fn write(&mut self, tx: DbTransaction) -> Result<(), ChainStorageError> {
+        (**self).write(tx)
+    }
+
+    fn fetch(&self, key: &DbKey) -> Result<Option<DbValue>, ChainStorageError> {
+        (**self).fetch(key)
+    }
+
+    fn contains(&self, key: &DbKey) -> Result<bool, ChainStorageError> {
+        (**self).contains(key)
+    }
+
+    fn fetch_header_and_accumulated_data(
+        &self,
+        height: u64,
+    ) -> Result<(BlockHeader, BlockHeaderAccumulatedData), ChainStorageError>
+    {
+        (**self).fetch_header_and_accumulated_data(height)
+    }
+
+    fn fetch_header_accumulated_data(
+        &self,
+        hash: &HashOutput,
+    ) -> Result<Option<BlockHeaderAccumulatedData>, ChainStorageError>
+    {
+        (**self).fetch_header_accumulated_data(hash)
+    }
+
+    fn fetch_chain_header_in_all_chains(&self, hash: &HashOutput) -> Result<Option<ChainHeader>, ChainStorageError> {
+        (**self).fetch_chain_header_in_all_chains(hash)
+    }
+
+    fn fetch_header_containing_kernel_mmr(&self, mmr_position: u64) -> Result<ChainHeader, ChainStorageError> {
+        (**self).fetch_header_containing_kernel_mmr(mmr_position)
+    }
+
+    fn fetch_header_containing_utxo_mmr(&self, mmr_position: u64) -> Result<ChainHeader, ChainStorageError> {
+        (**self).fetch_header_containing_utxo_mmr(mmr_position)
+    }
+
+    fn is_empty(&self) -> Result<bool, ChainStorageError> {
+        (**self).is_empty()
+    }
+
+    fn fetch_block_accumulated_data(
+        &self,
+        header_hash: &HashOutput,
+    ) -> Result<Option<BlockAccumulatedData>, ChainStorageError>
+    {
+        (**self).fetch_block_accumulated_data(header_hash)
+    }
+
+    fn fetch_block_accumulated_data_by_height(
+        &self,
+        height: u64,
+    ) -> Result<Option<BlockAccumulatedData>, ChainStorageError>
+    {
+        (**self).fetch_block_accumulated_data_by_height(height)
+    }
+
+    fn fetch_kernels_in_block(&self, header_hash: &HashOutput) -> Result<Vec<TransactionKernel>, ChainStorageError> {
+        (**self).fetch_kernels_in_block(header_hash)
+    }
+
+    fn fetch_kernel_by_excess(
+        &self,
+        excess: &[u8],
+    ) -> Result<Option<(TransactionKernel, HashOutput)>, ChainStorageError>
+    {
+        (**self).fetch_kernel_by_excess(excess)
+    }
+
+    fn fetch_kernel_by_excess_sig(
+        &self,
+        excess_sig: &Signature,
+    ) -> Result<Option<(TransactionKernel, HashOutput)>, ChainStorageError>
+    {
+        (**self).fetch_kernel_by_excess_sig(excess_sig)
+    }
+
+    fn fetch_kernels_by_mmr_position(&self, start: u64, end: u64) -> Result<Vec<TransactionKernel>, ChainStorageError> {
+        (**self).fetch_kernels_by_mmr_position(start, end)
+    }
+
+    fn fetch_utxos_by_mmr_position(
+        &self,
+        start: u64,
+        end: u64,
+        deleted: &Bitmap,
+    ) -> Result<(Vec<PrunedOutput>, Vec<Bitmap>), ChainStorageError>
+    {
+        (**self).fetch_utxos_by_mmr_position(start, end, deleted)
+    }
+
+    fn fetch_output(&self, output_hash: &HashOutput) -> Result<Option<(TransactionOutput, u32)>, ChainStorageError> {
+        (**self).fetch_output(output_hash)
+    }
+
+    fn fetch_outputs_in_block(&self, header_hash: &HashOutput) -> Result<Vec<PrunedOutput>, ChainStorageError> {
+        (**self).fetch_outputs_in_block(header_hash)
+    }
+
+    fn fetch_inputs_in_block(&self, header_hash: &HashOutput) -> Result<Vec<TransactionInput>, ChainStorageError> {
+        (**self).fetch_inputs_in_block(header_hash)
+    }
+
+    fn fetch_mmr_size(&self, tree: MmrTree) -> Result<u64, ChainStorageError> {
+        (**self).fetch_mmr_size(tree)
+    }
+
+    fn fetch_mmr_leaf_index(&self, tree: MmrTree, hash: &HashOutput) -> Result<Option<u32>, ChainStorageError> {
+        (**self).fetch_mmr_leaf_index(tree, hash)
+    }
+
+    fn orphan_count(&self) -> Result<usize, ChainStorageError> {
+        (**self).orphan_count()
+    }
+
+    fn fetch_last_header(&self) -> Result<BlockHeader, ChainStorageError> {
+        (**self).fetch_last_header()
+    }
+
+    fn fetch_tip_header(&self) -> Result<ChainHeader, ChainStorageError> {
+        (**self).fetch_tip_header()
+    }
+
+    fn fetch_chain_metadata(&self) -> Result<ChainMetadata, ChainStorageError> {
+        (**self).fetch_chain_metadata()
+    }
+
+    fn utxo_count(&self) -> Result<usize, ChainStorageError> {
+        (**self).utxo_count()
+    }
+
+    fn kernel_count(&self) -> Result<usize, ChainStorageError> {
+        (**self).kernel_count()
+    }
+
+    fn fetch_orphan_chain_tip_by_hash(&self, hash: &HashOutput) -> Result<Option<ChainHeader>, ChainStorageError> {
+        (**self).fetch_orphan_chain_tip_by_hash(hash)
+    }
+
+    fn fetch_orphan_children_of(&self, hash: HashOutput) -> Result<Vec<Block>, ChainStorageError> {
+        (**self).fetch_orphan_children_of(hash)
+    }
+
+    fn fetch_orphan_header_accumulated_data(
+        &self,
+        hash: HashOutput,
+    ) -> Result<BlockHeaderAccumulatedData, ChainStorageError>
+    {
+        (**self).fetch_orphan_header_accumulated_data(hash)
+    }
+
+    fn delete_oldest_orphans(
+        &mut self,
+        horizon_height: u64,
+        orphan_storage_capacity: usize,
+    ) -> Result<(), ChainStorageError>
+    {
+        (**self).delete_oldest_orphans(horizon_height, orphan_storage_capacity)
+    }
+
+    fn fetch_monero_seed_first_seen_height(&self, seed: &str) -> Result<u64, ChainStorageError> {
+        (**self).fetch_monero_seed_first_seen_height(seed)
+    }
+
+    fn fetch_horizon_data(&self) -> Result<Option<HorizonData>, ChainStorageError> {
+        (**self).fetch_horizon_data()
+    }
+}
@@ -30,6 +30,7 @@ use crate::{
         },
         db_transaction::{DbKey, DbTransaction, DbValue},
         error::ChainStorageError,
+        integrity::{verify_chain_integrity, ChainIntegrityReport},
         pruned_output::PrunedOutput,
         BlockchainBackend,
         ChainBlock,
@@ -964,18 +965,55 @@ where B: BlockchainBackend
 
     /// Rewind the blockchain state to the block height given and return the blocks that were removed and orphaned.
     ///
+    /// The outputs, inputs and kernels of the removed blocks are deleted along with their block accumulated data, which
+    /// restores the output MMR deletions (i.e. the outputs they spent are unspent again) and MMR roots of the new tip.
+    /// Headers above the height are also removed, so that header sync can resume from the new tip.
+    ///
     /// The operation will fail if
     /// * The block height is in the future
-    /// * The block height is before the horizon block height determined by the pruning horizon
+    /// * The block height is before the pruned height of a pruned node
     pub fn rewind_to_height(&self, height: u64) -> Result<Vec<Arc<ChainBlock>>, ChainStorageError> {
         let mut db = self.db_write_access()?;
-        rewind_to_height(&mut *db, height)
+        rewind_to_height(&mut *db, height, false)
+    }
+
+    /// Rewind the main chain to the block height given and return the height of the previous chain tip along with the
+    /// blocks that were removed. This is used for manual rewinds and database repairs.
+    ///
+    /// Unlike [rewind_to_height](Self::rewind_to_height), the height is checked against the block chain tip while
+    /// holding the write lock, so the operation will fail if the block height is above the chain tip. If
+    /// `discard_blocks` is set, the removed blocks are deleted rather than kept as chained orphans, so that they cannot
+    /// be reorged back onto the chain.
+    pub fn rewind_to_block_height(
+        &self,
+        height: u64,
+        discard_blocks: bool,
+    ) -> Result<(u64, Vec<Arc<ChainBlock>>), ChainStorageError>
+    {
+        let mut db = self.db_write_access()?;
+        let tip_height = db.fetch_chain_metadata()?.height_of_longest_chain();
+        if height > tip_height {
+            return Err(ChainStorageError::InvalidQuery(format!(
+                "Cannot rewind to height {} because the chain tip is at height {}",
+                height, tip_height
+            )));
+        }
+        let removed_blocks = rewind_to_height(&mut *db, height, discard_blocks)?;
+        Ok((tip_height, removed_blocks))
     }
 
     pub fn fetch_horizon_data(&self) -> Result<Option<HorizonData>, ChainStorageError> {
         let db = self.db_read_access()?;
         db.fetch_horizon_data()
     }
+
+    /// Replays the stored chain from the genesis block (or the pruned height for pruned nodes) and checks that the MMR
+    /// roots, accumulated difficulty and total kernel offset of every block match the values recomputed from the
+    /// block data. The chain balance is checked every `balance_check_interval` blocks and at the tip. The replay stops
+    /// at the first inconsistent height, which is returned in the report.
+    pub fn verify_integrity(&self, balance_check_interval: u64) -> Result<ChainIntegrityReport, ChainStorageError> {
+        verify_chain_integrity(self, self.consensus_manager.clone(), balance_check_interval)
+    }
 }
 
 fn unexpected_result<T>(req: DbKey, res: DbValue) -> Result<T, ChainStorageError> {
@@ -1220,7 +1258,7 @@ pub fn fetch_target_difficulty<T: BlockchainBackend>(
     Ok(target_difficulties)
 }
 
-fn fetch_block<T: BlockchainBackend>(db: &T, height: u64) -> Result<HistoricalBlock, ChainStorageError> {
+pub(super) fn fetch_block<T: BlockchainBackend>(db: &T, height: u64) -> Result<HistoricalBlock, ChainStorageError> {
     let mark = Instant::now();
     let (tip_height, is_pruned) = check_for_valid_height(&*db, height)?;
     let (header, accumulated_data) = db.fetch_header_and_accumulated_data(height)?;
@@ -1426,7 +1464,12 @@ fn check_for_valid_height<T: BlockchainBackend>(db: &T, height: u64) -> Result<(
     Ok((tip_height, height < pruned_height))
 }
 
-fn rewind_to_height<T: BlockchainBackend>(db: &mut T, height: u64) -> Result<Vec<Arc<ChainBlock>>, ChainStorageError> {
+fn rewind_to_height<T: BlockchainBackend>(
+    db: &mut T,
+    height: u64,
+    discard_blocks: bool,
+) -> Result<Vec<Arc<ChainBlock>>, ChainStorageError>
+{
     let last_header = db.fetch_last_header()?;
 
     let mut txn = DbTransaction::new();
@@ -1435,6 +1478,10 @@ fn rewind_to_height<T: BlockchainBackend>(db: &mut T, height: u64) -> Result<Vec
     let last_header_height = last_header.height;
     let metadata = db.fetch_chain_metadata()?;
     let last_block_height = metadata.height_of_longest_chain();
+    // Blocks at or below the pruned height no longer have their outputs and inputs, so they cannot be restored
+    if height < metadata.pruned_height() {
+        return Err(ChainStorageError::BeyondPruningHorizon);
+    }
     let steps_back = last_header_height
         .checked_sub(cmp::max(last_block_height, height))
         .ok_or_else(|| {
@@ -1474,12 +1521,14 @@ fn rewind_to_height<T: BlockchainBackend>(db: &mut T, height: u64) -> Result<Vec
     for h in 0..steps_back {
         let block = fetch_block(db, last_block_height - h)?;
         if block.is_pruned() {
-            unimplemented!("Rewinding past the pruning horizon not implemented.");
+            return Err(ChainStorageError::BeyondPruningHorizon);
         }
         let block = Arc::new(block.clone().try_into_chain_block()?);
         txn.delete_block(block.block.hash());
         txn.delete_header(last_block_height - h);
-        txn.insert_chained_orphan(block.clone());
+        if !discard_blocks {
+            txn.insert_chained_orphan(block.clone());
+        }
         removed_blocks.push(block);
     }
 
@@ -1649,7 +1698,7 @@ fn reorganize_chain<T: BlockchainBackend>(
 ) -> Result<Vec<Arc<ChainBlock>>, ChainStorageError>
 {
     let removed_blocks = if height <= current_tip_height {
-        rewind_to_height(backend, height)?
+        rewind_to_height(backend, height, false)?
     } else {
         vec![]
     };
@@ -1708,7 +1757,7 @@ fn restore_reorged_chain<T: BlockchainBackend>(
     previous_chain: Vec<Arc<ChainBlock>>,
 ) -> Result<(), ChainStorageError>
 {
-    let invalid_chain = rewind_to_height(db, height)?;
+    let invalid_chain = rewind_to_height(db, height, false)?;
     debug!(
         target: LOG_TARGET,
         "Removed {} blocks during chain restore: {:?}.",
@@ -96,6 +96,11 @@ pub enum ChainStorageError {
         #[from]
         source: LMDBError,
     },
+    #[error("SQLite error: {source}")]
+    SqliteError {
+        #[from]
+        source: diesel::result::Error,
+    },
     #[error("Invalid proof of work: {source}")]
     ProofOfWorkError {
         #[from]
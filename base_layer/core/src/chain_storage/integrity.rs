@@ -0,0 +1,273 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Deep verification of the chain data held in the blockchain database.
+//!
+//! The stored chain is replayed block by block and everything that can be derived from the block data is recomputed
+//! and compared against what is stored: the kernel, output and range proof MMR roots and sizes in each header, the
+//! accumulated difficulty and total kernel offset in the header accumulated data, and (at selected heights) the chain
+//! balance of the running UTXO and kernel sums.
+
+use crate::{
+    chain_storage::{
+        calculate_mmr_roots,
+        BlockHeaderAccumulatedData,
+        BlockHeaderAccumulatedDataBuilder,
+        BlockchainBackend,
+        BlockchainDatabase,
+        ChainStorageError,
+        MmrTree,
+    },
+    consensus::ConsensusManager,
+    transactions::types::{Commitment, CryptoFactories},
+    validation::{ChainBalanceValidator, FinalHorizonStateValidation, ValidationError},
+};
+use log::*;
+use tari_crypto::tari_utilities::{hex::Hex, Hashable};
+use thiserror::Error;
+
+const LOG_TARGET: &str = "c::cs::integrity";
+
+/// The number of blocks between progress log messages
+const PROGRESS_LOG_INTERVAL: u64 = 1000;
+
+/// An inconsistency found while replaying the stored chain
+#[derive(Debug, Error)]
+pub enum ChainIntegrityError {
+    #[error("The stored block hash does not match the hash of the block header")]
+    BlockHashMismatch,
+    #[error("The block does not link to the previous block: expected prev_hash {expected}, got {actual}")]
+    BrokenChainLink { expected: String, actual: String },
+    #[error("The {0} MMR root in the block header does not match the recomputed root")]
+    MismatchedMmrRoot(MmrTree),
+    #[error("The {tree} MMR size in the block header ({expected}) does not match the recomputed size ({actual})")]
+    MismatchedMmrSize { tree: MmrTree, expected: u64, actual: u64 },
+    #[error("The stored accumulated difficulty does not match the recomputed accumulated difficulty")]
+    AccumulatedDifficultyMismatch,
+    #[error("The stored total kernel offset does not match the recomputed total kernel offset")]
+    TotalKernelOffsetMismatch,
+    #[error("Chain balance check failed: {0}")]
+    ChainBalance(#[from] ValidationError),
+    #[error("Could not read the block data: {0}")]
+    StorageError(#[from] ChainStorageError),
+}
+
+/// The first inconsistency found while replaying the stored chain
+#[derive(Debug)]
+pub struct ChainInconsistency {
+    pub height: u64,
+    pub error: ChainIntegrityError,
+}
+
+/// The outcome of [BlockchainDatabase::verify_integrity](crate::chain_storage::BlockchainDatabase::verify_integrity)
+#[derive(Debug)]
+pub struct ChainIntegrityReport {
+    /// The first height that was replayed. This is the genesis block for archival nodes and the block following the
+    /// pruned height for pruned nodes.
+    pub start_height: u64,
+    /// The chain tip height at the time the verification started
+    pub tip_height: u64,
+    /// The last height at which all checks passed
+    pub last_verified_height: Option<u64>,
+    /// The heights at which the chain balance was checked
+    pub balance_checked_heights: Vec<u64>,
+    /// The first inconsistency found, if any. The replay stops at this height.
+    pub inconsistency: Option<ChainInconsistency>,
+}
+
+impl ChainIntegrityReport {
+    pub fn is_consistent(&self) -> bool {
+        self.inconsistency.is_none()
+    }
+}
+
+/// Replays the stored chain and returns a report containing the first inconsistent height, if any. The chain balance
+/// is checked every `balance_check_interval` blocks (0 disables the interval checks) and at the tip.
+pub(super) fn verify_chain_integrity<B: BlockchainBackend>(
+    db: &BlockchainDatabase<B>,
+    rules: ConsensusManager,
+    balance_check_interval: u64,
+) -> Result<ChainIntegrityReport, ChainStorageError>
+{
+    let metadata = db.get_chain_metadata()?;
+    let tip_height = metadata.height_of_longest_chain();
+    let pruned_height = metadata.pruned_height();
+
+    // Blocks at or below the pruned height no longer have their outputs, so the replay starts from the horizon state
+    let (start_height, mut utxo_sum, mut kernel_sum) = if pruned_height > 0 {
+        let horizon_data = db
+            .fetch_horizon_data()?
+            .ok_or_else(|| ChainStorageError::ValueNotFound {
+                entity: "HorizonData".to_string(),
+                field: "pruned_height".to_string(),
+                value: pruned_height.to_string(),
+            })?;
+        (
+            pruned_height + 1,
+            horizon_data.utxo_sum().clone(),
+            horizon_data.kernel_sum().clone(),
+        )
+    } else {
+        (0, Commitment::default(), Commitment::default())
+    };
+
+    let mut previous = if start_height > 0 {
+        Some(db.fetch_header_and_accumulated_data(start_height - 1)?.1)
+    } else {
+        None
+    };
+
+    info!(
+        target: LOG_TARGET,
+        "Verifying chain integrity from height {} to {}", start_height, tip_height
+    );
+    let balance_validator = ChainBalanceValidator::<B>::new(rules, CryptoFactories::default());
+    let mut report = ChainIntegrityReport {
+        start_height,
+        tip_height,
+        last_verified_height: None,
+        balance_checked_heights: Vec::new(),
+        inconsistency: None,
+    };
+
+    for height in start_height..=tip_height {
+        let backend = db.db_read_access()?;
+        let result =
+            verify_block(&*backend, height, previous.as_ref(), &mut utxo_sum, &mut kernel_sum).and_then(|accum| {
+                // The genesis block is hard-coded in the consensus rules, so the balance is checked from height 1
+                let is_check_height =
+                    height == tip_height || (balance_check_interval > 0 && height % balance_check_interval == 0);
+                if height > 0 && is_check_height {
+                    balance_validator.validate(height, &utxo_sum, &kernel_sum, &*backend)?;
+                    report.balance_checked_heights.push(height);
+                }
+                Ok(accum)
+            });
+
+        match result {
+            Ok(accum) => {
+                previous = Some(accum);
+                report.last_verified_height = Some(height);
+            },
+            Err(error) => {
+                warn!(
+                    target: LOG_TARGET,
+                    "Chain integrity check failed at height {}: {}", height, error
+                );
+                report.inconsistency = Some(ChainInconsistency { height, error });
+                break;
+            },
+        }
+
+        if height % PROGRESS_LOG_INTERVAL == 0 {
+            info!(
+                target: LOG_TARGET,
+                "Verified chain integrity up to height {} of {}", height, tip_height
+            );
+        }
+    }
+
+    Ok(report)
+}
+
+/// Verifies a single block against the previous block's accumulated data and adds its outputs, inputs and kernels to
+/// the running sums. The block's accumulated data is returned so that the next block can be verified against it.
+fn verify_block<T: BlockchainBackend>(
+    db: &T,
+    height: u64,
+    previous: Option<&BlockHeaderAccumulatedData>,
+    utxo_sum: &mut Commitment,
+    kernel_sum: &mut Commitment,
+) -> Result<BlockHeaderAccumulatedData, ChainIntegrityError>
+{
+    let historical_block = super::blockchain_database::fetch_block(db, height)?;
+    let accumulated_data = historical_block.accumulated_data.clone();
+    let block = historical_block.try_into_block()?;
+    let header = &block.header;
+
+    if accumulated_data.hash != header.hash() {
+        return Err(ChainIntegrityError::BlockHashMismatch);
+    }
+
+    // The genesis block has no previous block to verify against
+    if let Some(previous) = previous {
+        if header.prev_hash != previous.hash {
+            return Err(ChainIntegrityError::BrokenChainLink {
+                expected: previous.hash.to_hex(),
+                actual: header.prev_hash.to_hex(),
+            });
+        }
+
+        let roots = calculate_mmr_roots(db, &block)?;
+        if header.kernel_mr != roots.kernel_mr {
+            return Err(ChainIntegrityError::MismatchedMmrRoot(MmrTree::Kernel));
+        }
+        if header.kernel_mmr_size != roots.kernel_mmr_size {
+            return Err(ChainIntegrityError::MismatchedMmrSize {
+                tree: MmrTree::Kernel,
+                expected: header.kernel_mmr_size,
+                actual: roots.kernel_mmr_size,
+            });
+        }
+        if header.output_mr != roots.output_mr {
+            return Err(ChainIntegrityError::MismatchedMmrRoot(MmrTree::Utxo));
+        }
+        if header.output_mmr_size != roots.output_mmr_size {
+            return Err(ChainIntegrityError::MismatchedMmrSize {
+                tree: MmrTree::Utxo,
+                expected: header.output_mmr_size,
+                actual: roots.output_mmr_size,
+            });
+        }
+        if header.range_proof_mr != roots.range_proof_mr {
+            return Err(ChainIntegrityError::MismatchedMmrRoot(MmrTree::RangeProof));
+        }
+
+        let expected = BlockHeaderAccumulatedDataBuilder::default()
+            .hash(accumulated_data.hash.clone())
+            .target_difficulty(accumulated_data.target_difficulty)
+            .achieved_difficulty(previous, header.pow_algo(), accumulated_data.achieved_difficulty)
+            .total_kernel_offset(&previous.total_kernel_offset, &header.total_kernel_offset)
+            .build()?;
+        if expected.accumulated_monero_difficulty != accumulated_data.accumulated_monero_difficulty ||
+            expected.accumulated_blake_difficulty != accumulated_data.accumulated_blake_difficulty ||
+            expected.total_accumulated_difficulty != accumulated_data.total_accumulated_difficulty
+        {
+            return Err(ChainIntegrityError::AccumulatedDifficultyMismatch);
+        }
+        if expected.total_kernel_offset != accumulated_data.total_kernel_offset {
+            return Err(ChainIntegrityError::TotalKernelOffsetMismatch);
+        }
+    }
+
+    for output in block.body.outputs() {
+        *utxo_sum = &*utxo_sum + &output.commitment;
+    }
+    for input in block.body.inputs() {
+        *utxo_sum = &*utxo_sum - &input.commitment;
+    }
+    for kernel in block.body.kernels() {
+        *kernel_sum = &*kernel_sum + &kernel.excess;
+    }
+
+    Ok(accumulated_data)
+}
@@ -66,6 +66,9 @@ pub use mmr_tree::*;
 mod error;
 pub use error::{ChainStorageError, Optional, OrNotFound};
 
+mod integrity;
+pub use integrity::{ChainInconsistency, ChainIntegrityError, ChainIntegrityReport};
+
 mod historical_block;
 pub use historical_block::HistoricalBlock;
 
@@ -89,5 +92,8 @@ pub use lmdb_db::{
     LMDB_DB_UTXOS,
 };
 
+mod sqlite_db;
+pub use sqlite_db::{create_sqlite_database, SqliteDatabase};
+
 mod target_difficulties;
 pub use target_difficulties::TargetDifficulties;
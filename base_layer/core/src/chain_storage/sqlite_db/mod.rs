@@ -0,0 +1,47 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+mod sqlite;
+#[allow(clippy::module_inception)]
+mod sqlite_db;
+
+pub use sqlite_db::{create_sqlite_database, SqliteDatabase};
+
+// Each table mirrors one of the LMDB databases and stores bincode-encoded values against a binary key.
+const SQLITE_TABLE_METADATA: &str = "metadata";
+const SQLITE_TABLE_HEADERS: &str = "headers";
+const SQLITE_TABLE_HEADER_ACCUMULATED_DATA: &str = "header_accumulated_data";
+const SQLITE_TABLE_BLOCK_ACCUMULATED_DATA: &str = "mmr_peak_data";
+const SQLITE_TABLE_BLOCK_HASHES: &str = "block_hashes";
+const SQLITE_TABLE_UTXOS: &str = "utxos";
+const SQLITE_TABLE_INPUTS: &str = "inputs";
+const SQLITE_TABLE_TXOS_HASH_TO_INDEX: &str = "txos_hash_to_index";
+const SQLITE_TABLE_KERNELS: &str = "kernels";
+const SQLITE_TABLE_KERNEL_EXCESS_INDEX: &str = "kernel_excess_index";
+const SQLITE_TABLE_KERNEL_EXCESS_SIG_INDEX: &str = "kernel_excess_sig_index";
+const SQLITE_TABLE_KERNEL_MMR_SIZE_INDEX: &str = "kernel_mmr_size_index";
+const SQLITE_TABLE_UTXO_MMR_SIZE_INDEX: &str = "utxo_mmr_size_index";
+const SQLITE_TABLE_ORPHANS: &str = "orphans";
+const SQLITE_TABLE_MONERO_SEED_HEIGHT: &str = "monero_seed_height";
+const SQLITE_TABLE_ORPHAN_HEADER_ACCUMULATED_DATA: &str = "orphan_accumulated_data";
+const SQLITE_TABLE_ORPHAN_CHAIN_TIPS: &str = "orphan_chain_tips";
+const SQLITE_TABLE_ORPHAN_PARENT_MAP_INDEX: &str = "orphan_parent_map_index";
@@ -0,0 +1,335 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Helpers that give the SQLite tables the same key-value semantics as the LMDB helpers in `lmdb_db::lmdb`. Keys are
+//! stored as BLOBs, which SQLite compares with `memcmp`, so range and prefix queries see the same ordering as LMDB.
+
+use crate::chain_storage::error::ChainStorageError;
+use diesel::{
+    sql_query,
+    sql_types::{BigInt, Binary},
+    RunQueryDsl,
+    SqliteConnection,
+};
+use log::*;
+use serde::{de::DeserializeOwned, Serialize};
+use tari_crypto::tari_utilities::hex::to_hex;
+
+pub const LOG_TARGET: &str = "c::cs::sqlite_db::sqlite";
+
+#[derive(QueryableByName)]
+struct ValueRow {
+    #[sql_type = "Binary"]
+    value: Vec<u8>,
+}
+
+#[derive(QueryableByName)]
+struct CountRow {
+    #[sql_type = "BigInt"]
+    count: i64,
+}
+
+pub fn serialize<T>(data: &T) -> Result<Vec<u8>, ChainStorageError>
+where T: Serialize {
+    bincode::serialize(data).map_err(|e| {
+        error!(target: LOG_TARGET, "Could not serialize sqlite value: {:?}", e);
+        ChainStorageError::AccessError(e.to_string())
+    })
+}
+
+pub fn deserialize<T>(buf_bytes: &[u8]) -> Result<T, ChainStorageError>
+where T: DeserializeOwned {
+    bincode::deserialize(buf_bytes).map_err(|e| {
+        error!(target: LOG_TARGET, "Could not deserialize sqlite value: {:?}", e);
+        ChainStorageError::AccessError(e.to_string())
+    })
+}
+
+/// Inserts the value at the given key. An error is returned if the key already exists.
+pub fn sqlite_insert<V>(
+    conn: &SqliteConnection,
+    table: &'static str,
+    key: &[u8],
+    val: &V,
+) -> Result<(), ChainStorageError>
+where
+    V: Serialize,
+{
+    let val_buf = serialize(val)?;
+    sql_query(format!("INSERT INTO {} (key, value) VALUES (?, ?)", table))
+        .bind::<Binary, _>(key)
+        .bind::<Binary, _>(&val_buf)
+        .execute(conn)
+        .map_err(|e| {
+            error!(
+                target: LOG_TARGET,
+                "Could not insert value into sqlite {} ({}): {:?}",
+                table,
+                to_hex(key),
+                e,
+            );
+            ChainStorageError::InsertError {
+                table,
+                error: e.to_string(),
+            }
+        })?;
+    Ok(())
+}
+
+/// Inserts the key-value pair into a table that allows more than one value per key. Inserting a pair that already
+/// exists is a no-op.
+pub fn sqlite_insert_dup<V>(
+    conn: &SqliteConnection,
+    table: &'static str,
+    key: &[u8],
+    val: &V,
+) -> Result<(), ChainStorageError>
+where
+    V: Serialize,
+{
+    let val_buf = serialize(val)?;
+    sql_query(format!("INSERT OR IGNORE INTO {} (key, value) VALUES (?, ?)", table))
+        .bind::<Binary, _>(key)
+        .bind::<Binary, _>(&val_buf)
+        .execute(conn)?;
+    Ok(())
+}
+
+pub fn sqlite_replace<V>(
+    conn: &SqliteConnection,
+    table: &'static str,
+    key: &[u8],
+    val: &V,
+) -> Result<(), ChainStorageError>
+where
+    V: Serialize,
+{
+    let val_buf = serialize(val)?;
+    sql_query(format!("INSERT OR REPLACE INTO {} (key, value) VALUES (?, ?)", table))
+        .bind::<Binary, _>(key)
+        .bind::<Binary, _>(&val_buf)
+        .execute(conn)?;
+    Ok(())
+}
+
+/// Deletes the given key. An error is returned if the key does not exist
+pub fn sqlite_delete(conn: &SqliteConnection, table: &'static str, key: &[u8]) -> Result<(), ChainStorageError> {
+    let num_deleted = sql_query(format!("DELETE FROM {} WHERE key = ?", table))
+        .bind::<Binary, _>(key)
+        .execute(conn)?;
+    if num_deleted == 0 {
+        return Err(ChainStorageError::ValueNotFound {
+            entity: table.to_string(),
+            field: "key".to_string(),
+            value: to_hex(key),
+        });
+    }
+    Ok(())
+}
+
+/// Deletes a single key-value pair from a table that allows more than one value per key. An error is returned if the
+/// pair does not exist
+pub fn sqlite_delete_key_value<V>(
+    conn: &SqliteConnection,
+    table: &'static str,
+    key: &[u8],
+    value: &V,
+) -> Result<(), ChainStorageError>
+where
+    V: Serialize,
+{
+    let num_deleted = sql_query(format!("DELETE FROM {} WHERE key = ? AND value = ?", table))
+        .bind::<Binary, _>(key)
+        .bind::<Binary, _>(serialize(value)?)
+        .execute(conn)?;
+    if num_deleted == 0 {
+        return Err(ChainStorageError::ValueNotFound {
+            entity: table.to_string(),
+            field: "key".to_string(),
+            value: to_hex(key),
+        });
+    }
+    Ok(())
+}
+
+pub fn sqlite_delete_keys_starting_with<V>(
+    conn: &SqliteConnection,
+    table: &'static str,
+    key: &str,
+) -> Result<Vec<V>, ChainStorageError>
+where
+    V: DeserializeOwned,
+{
+    debug!(target: LOG_TARGET, "Deleting rows matching pattern: {}", key);
+    let result = sqlite_fetch_keys_starting_with(key, conn, table)?;
+    let prefix = key.as_bytes();
+    match prefix_upper_bound(prefix) {
+        Some(upper) => sql_query(format!("DELETE FROM {} WHERE key >= ? AND key < ?", table))
+            .bind::<Binary, _>(prefix)
+            .bind::<Binary, _>(upper)
+            .execute(conn)?,
+        None => sql_query(format!("DELETE FROM {} WHERE key >= ?", table))
+            .bind::<Binary, _>(prefix)
+            .execute(conn)?,
+    };
+    Ok(result)
+}
+
+pub fn sqlite_get<V>(conn: &SqliteConnection, table: &'static str, key: &[u8]) -> Result<Option<V>, ChainStorageError>
+where V: DeserializeOwned {
+    let rows = sql_query(format!("SELECT value FROM {} WHERE key = ?", table))
+        .bind::<Binary, _>(key)
+        .load::<ValueRow>(conn)?;
+    match rows.into_iter().next() {
+        Some(row) => deserialize(&row.value).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Returns every value stored against the key, in the same (byte) order LMDB uses for duplicate values
+pub fn sqlite_get_multiple<V>(
+    conn: &SqliteConnection,
+    table: &'static str,
+    key: &[u8],
+) -> Result<Vec<V>, ChainStorageError>
+where
+    V: DeserializeOwned,
+{
+    sql_query(format!("SELECT value FROM {} WHERE key = ? ORDER BY value", table))
+        .bind::<Binary, _>(key)
+        .load::<ValueRow>(conn)?
+        .iter()
+        .map(|row| deserialize(&row.value))
+        .collect()
+}
+
+pub fn sqlite_last<V>(conn: &SqliteConnection, table: &'static str) -> Result<Option<V>, ChainStorageError>
+where V: DeserializeOwned {
+    let rows = sql_query(format!("SELECT value FROM {} ORDER BY key DESC LIMIT 1", table)).load::<ValueRow>(conn)?;
+    match rows.into_iter().next() {
+        Some(row) => deserialize(&row.value).map(Some),
+        None => Ok(None),
+    }
+}
+
+pub fn sqlite_exists(conn: &SqliteConnection, table: &'static str, key: &[u8]) -> Result<bool, ChainStorageError> {
+    let rows = sql_query(format!("SELECT COUNT(*) AS count FROM {} WHERE key = ?", table))
+        .bind::<Binary, _>(key)
+        .load::<CountRow>(conn)?;
+    Ok(rows.first().map(|row| row.count > 0).unwrap_or(false))
+}
+
+pub fn sqlite_len(conn: &SqliteConnection, table: &'static str) -> Result<usize, ChainStorageError> {
+    let rows = sql_query(format!("SELECT COUNT(*) AS count FROM {}", table)).load::<CountRow>(conn)?;
+    Ok(rows.first().map(|row| row.count as usize).unwrap_or(0))
+}
+
+pub fn sqlite_fetch_keys_starting_with<V>(
+    key: &str,
+    conn: &SqliteConnection,
+    table: &'static str,
+) -> Result<Vec<V>, ChainStorageError>
+where
+    V: DeserializeOwned,
+{
+    trace!(target: LOG_TARGET, "Getting rows matching pattern: {}", key);
+    let prefix = key.as_bytes();
+    let rows = match prefix_upper_bound(prefix) {
+        Some(upper) => sql_query(format!(
+            "SELECT value FROM {} WHERE key >= ? AND key < ? ORDER BY key",
+            table
+        ))
+        .bind::<Binary, _>(prefix)
+        .bind::<Binary, _>(upper)
+        .load::<ValueRow>(conn)?,
+        None => sql_query(format!("SELECT value FROM {} WHERE key >= ? ORDER BY key", table))
+            .bind::<Binary, _>(prefix)
+            .load::<ValueRow>(conn)?,
+    };
+    rows.iter().map(|row| deserialize(&row.value)).collect()
+}
+
+/// Returns the value of the first key that is greater than or equal to the given key
+pub fn sqlite_first_after<V>(
+    conn: &SqliteConnection,
+    table: &'static str,
+    key: &[u8],
+) -> Result<Option<V>, ChainStorageError>
+where
+    V: DeserializeOwned,
+{
+    let rows = sql_query(format!(
+        "SELECT value FROM {} WHERE key >= ? ORDER BY key LIMIT 1",
+        table
+    ))
+    .bind::<Binary, _>(key)
+    .load::<ValueRow>(conn)?;
+    match rows.into_iter().next() {
+        Some(row) => deserialize(&row.value).map(Some),
+        None => Ok(None),
+    }
+}
+
+pub fn sqlite_filter_map_values<F, V, R>(
+    conn: &SqliteConnection,
+    table: &'static str,
+    f: F,
+) -> Result<Vec<R>, ChainStorageError>
+where
+    F: Fn(V) -> Result<Option<R>, ChainStorageError>,
+    V: DeserializeOwned,
+{
+    let rows = sql_query(format!("SELECT value FROM {} ORDER BY key", table)).load::<ValueRow>(conn)?;
+    let mut result = vec![];
+    for row in rows {
+        if let Some(r) = f(deserialize(&row.value)?)? {
+            result.push(r);
+        }
+    }
+    Ok(result)
+}
+
+/// Returns the smallest key that is greater than every key starting with `prefix`, or None if no such key exists
+/// (i.e. the prefix is empty or consists only of `0xff` bytes).
+fn prefix_upper_bound(prefix: &[u8]) -> Option<Vec<u8>> {
+    let mut upper = prefix.to_vec();
+    while let Some(last) = upper.pop() {
+        if last < u8::MAX {
+            upper.push(last + 1);
+            return Some(upper);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn prefix_upper_bound_increments_last_byte() {
+        assert_eq!(prefix_upper_bound(b"ab"), Some(b"ac".to_vec()));
+        assert_eq!(prefix_upper_bound(&[1, 0xff]), Some(vec![2]));
+        assert_eq!(prefix_upper_bound(&[0xff, 0xff]), None);
+        assert_eq!(prefix_upper_bound(&[]), None);
+    }
+}
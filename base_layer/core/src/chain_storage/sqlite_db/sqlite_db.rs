@@ -0,0 +1,1432 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::{
+    blocks::{block_header::BlockHeader, Block},
+    chain_storage::{
+        accumulated_data::{BlockAccumulatedData, BlockHeaderAccumulatedData, DeletedBitmap},
+        db_transaction::{DbKey, DbTransaction, DbValue, WriteOperation},
+        error::{ChainStorageError, OrNotFound},
+        lmdb_db::{TransactionInputRowData, TransactionKernelRowData, TransactionOutputRowData},
+        sqlite_db::{
+            sqlite::{
+                sqlite_delete,
+                sqlite_delete_key_value,
+                sqlite_delete_keys_starting_with,
+                sqlite_exists,
+                sqlite_fetch_keys_starting_with,
+                sqlite_filter_map_values,
+                sqlite_first_after,
+                sqlite_get,
+                sqlite_get_multiple,
+                sqlite_insert,
+                sqlite_insert_dup,
+                sqlite_last,
+                sqlite_len,
+                sqlite_replace,
+            },
+            SQLITE_TABLE_BLOCK_ACCUMULATED_DATA,
+            SQLITE_TABLE_BLOCK_HASHES,
+            SQLITE_TABLE_HEADERS,
+            SQLITE_TABLE_HEADER_ACCUMULATED_DATA,
+            SQLITE_TABLE_INPUTS,
+            SQLITE_TABLE_KERNELS,
+            SQLITE_TABLE_KERNEL_EXCESS_INDEX,
+            SQLITE_TABLE_KERNEL_EXCESS_SIG_INDEX,
+            SQLITE_TABLE_KERNEL_MMR_SIZE_INDEX,
+            SQLITE_TABLE_METADATA,
+            SQLITE_TABLE_MONERO_SEED_HEIGHT,
+            SQLITE_TABLE_ORPHANS,
+            SQLITE_TABLE_ORPHAN_CHAIN_TIPS,
+            SQLITE_TABLE_ORPHAN_HEADER_ACCUMULATED_DATA,
+            SQLITE_TABLE_ORPHAN_PARENT_MAP_INDEX,
+            SQLITE_TABLE_TXOS_HASH_TO_INDEX,
+            SQLITE_TABLE_UTXOS,
+            SQLITE_TABLE_UTXO_MMR_SIZE_INDEX,
+        },
+        BlockchainBackend,
+        ChainHeader,
+        HorizonData,
+        MmrTree,
+        PrunedOutput,
+    },
+    crypto::tari_utilities::hex::to_hex,
+    transactions::{
+        aggregated_body::AggregateBody,
+        transaction::{TransactionInput, TransactionKernel, TransactionOutput},
+        types::{Commitment, HashDigest, HashOutput, Signature},
+    },
+};
+use croaring::Bitmap;
+use diesel::{Connection, SqliteConnection};
+use fs2::FileExt;
+use log::*;
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, MutexGuard},
+    time::Instant,
+};
+use tari_common_types::{chain_metadata::ChainMetadata, types::BLOCK_HASH_LENGTH};
+use tari_crypto::tari_utilities::{hash::Hashable, hex::Hex, ByteArray};
+use tari_mmr::{Hash, MerkleMountainRange, MutableMmr};
+
+pub const LOG_TARGET: &str = "c::cs::sqlite_db::sqlite_db";
+
+const SQLITE_DB_FILE_NAME: &str = "chain_storage.sqlite3";
+
+/// This is a SQLite-based blockchain database for persistent storage of the chain state. It stores the same data, in
+/// the same layout, as [LMDBDatabase](crate::chain_storage::LMDBDatabase) and is interchangeable with it.
+pub struct SqliteDatabase {
+    conn: Arc<Mutex<SqliteConnection>>,
+    mem_metadata: Option<ChainMetadata>,
+    is_mem_metadata_dirty: bool,
+    _file_lock: Arc<File>,
+}
+
+impl SqliteDatabase {
+    pub fn new(conn: SqliteConnection, file_lock: File) -> Result<Self, ChainStorageError> {
+        let mut res = Self {
+            conn: Arc::new(Mutex::new(conn)),
+            mem_metadata: None,
+            is_mem_metadata_dirty: false,
+            _file_lock: Arc::new(file_lock),
+        };
+        if !res.is_empty()? {
+            res.refresh_chain_metadata()?;
+        }
+        Ok(res)
+    }
+
+    fn apply_db_transaction(&mut self, txn: DbTransaction) -> Result<(), ChainStorageError> {
+        let conn = self.conn.clone();
+        {
+            let conn = acquire_lock(&conn)?;
+            conn.transaction::<_, ChainStorageError, _>(|| {
+                for op in txn.into_operations() {
+                    trace!(target: LOG_TARGET, "[apply_db_transaction] WriteOperation: {}", op);
+                    self.apply_operation(&conn, op)?;
+                }
+                Ok(())
+            })?;
+        }
+        if self.is_mem_metadata_dirty {
+            self.refresh_chain_metadata()?;
+        }
+        Ok(())
+    }
+
+    fn apply_operation(&mut self, conn: &SqliteConnection, op: WriteOperation) -> Result<(), ChainStorageError> {
+        use WriteOperation::*;
+        match op {
+            InsertOrphanBlock(block) => self.insert_orphan_block(conn, &block)?,
+            Delete(delete) => self.op_delete(conn, delete)?,
+            InsertHeader { header } => {
+                let height = header.header.height;
+                if !self.insert_header(conn, &header.header, header.accumulated_data)? {
+                    return Err(ChainStorageError::InvalidOperation(format!(
+                        "Duplicate `BlockHeader` key `{}`",
+                        height
+                    )));
+                }
+            },
+            InsertBlock { block } => {
+                self.insert_header(conn, &block.block.header, block.accumulated_data.clone())?;
+                self.insert_block_body(conn, &block.block.header, block.block.body.clone())?;
+            },
+            InsertKernel {
+                header_hash,
+                kernel,
+                mmr_position,
+            } => {
+                trace!(
+                    target: LOG_TARGET,
+                    "Inserting kernel `{}`",
+                    kernel.excess_sig.get_signature().to_hex()
+                );
+                self.insert_kernel(conn, header_hash, *kernel, mmr_position)?;
+            },
+            InsertOutput {
+                header_hash,
+                output,
+                mmr_position,
+            } => {
+                trace!(
+                    target: LOG_TARGET,
+                    "Inserting output `{}`",
+                    to_hex(&output.commitment.as_bytes())
+                );
+                self.insert_output(conn, header_hash, *output, mmr_position)?;
+            },
+            InsertPrunedOutput {
+                header_hash,
+                output_hash,
+                proof_hash,
+                mmr_position,
+            } => {
+                self.insert_pruned_output(conn, header_hash, output_hash, proof_hash, mmr_position)?;
+            },
+            InsertInput {
+                header_hash,
+                input,
+                mmr_position,
+            } => {
+                trace!(
+                    target: LOG_TARGET,
+                    "Inserting input `{}`",
+                    to_hex(&input.commitment.as_bytes())
+                );
+                self.insert_input(conn, header_hash, *input, mmr_position)?;
+            },
+            DeleteOrphanChainTip(hash) => {
+                sqlite_delete(conn, SQLITE_TABLE_ORPHAN_CHAIN_TIPS, &hash)?;
+            },
+            InsertOrphanChainTip(hash) => {
+                sqlite_replace(conn, SQLITE_TABLE_ORPHAN_CHAIN_TIPS, &hash, &hash)?;
+            },
+            DeleteBlock(hash) => self.delete_block_body(conn, &hash)?,
+            InsertMoneroSeedHeight(data, height) => {
+                let current_height =
+                    sqlite_get(conn, SQLITE_TABLE_MONERO_SEED_HEIGHT, data.as_bytes())?.unwrap_or(std::u64::MAX);
+                if height < current_height {
+                    sqlite_replace(conn, SQLITE_TABLE_MONERO_SEED_HEIGHT, data.as_bytes(), &height)?;
+                };
+            },
+            InsertChainOrphanBlock(chain_block) => {
+                self.insert_orphan_block(conn, &chain_block.block)?;
+                sqlite_replace(
+                    conn,
+                    SQLITE_TABLE_ORPHAN_HEADER_ACCUMULATED_DATA,
+                    chain_block.accumulated_data.hash.as_slice(),
+                    &chain_block.accumulated_data,
+                )?;
+            },
+            UpdatePrunedHashSet {
+                mmr_tree,
+                header_hash,
+                pruned_hash_set,
+            } => {
+                let height = fetch_height_from_hash(conn, &header_hash).or_not_found(
+                    "BlockHash",
+                    "hash",
+                    header_hash.to_hex(),
+                )?;
+                let mut block_accum_data =
+                    fetch_block_accumulated_data(conn, height)?.unwrap_or_else(BlockAccumulatedData::default);
+                match mmr_tree {
+                    MmrTree::Kernel => block_accum_data.kernels = *pruned_hash_set,
+                    MmrTree::Utxo => block_accum_data.outputs = *pruned_hash_set,
+                    MmrTree::RangeProof => block_accum_data.range_proofs = *pruned_hash_set,
+                }
+
+                update_block_accumulated_data(conn, height, &block_accum_data)?;
+            },
+            UpdateDeletedBlockAccumulatedData { header_hash, deleted } => {
+                let height = fetch_height_from_hash(conn, &header_hash).or_not_found(
+                    "BlockHash",
+                    "hash",
+                    header_hash.to_hex(),
+                )?;
+                let mut block_accum_data =
+                    fetch_block_accumulated_data(conn, height)?.unwrap_or_else(BlockAccumulatedData::default);
+
+                block_accum_data.deleted = DeletedBitmap { deleted };
+                update_block_accumulated_data(conn, height, &block_accum_data)?;
+            },
+            PruneOutputsAndUpdateHorizon {
+                output_positions,
+                horizon,
+            } => {
+                let horizon_data = fetch_horizon_data(conn).or_not_found("HorizonData", "", "".to_string())?;
+                let utxo_sum = horizon_data.utxo_sum().clone();
+                for pos in output_positions {
+                    let (_height, hash) = sqlite_first_after::<(u64, Vec<u8>)>(
+                        conn,
+                        SQLITE_TABLE_UTXO_MMR_SIZE_INDEX,
+                        &pos.to_be_bytes(),
+                    )
+                    .or_not_found("BlockHeader", "mmr_position", pos.to_string())?;
+                    let key = format!("{}-{:010}", hash.to_hex(), pos);
+                    info!(target: LOG_TARGET, "Pruning output: {}", key);
+                    prune_output(conn, key.as_str())?;
+                }
+
+                self.set_metadata(conn, MetadataKey::PrunedHeight, &horizon)?;
+                self.set_metadata(
+                    conn,
+                    MetadataKey::HorizonData,
+                    &HorizonData::new(horizon_data.kernel_sum().clone(), utxo_sum),
+                )?;
+            },
+            UpdateKernelSum {
+                header_hash,
+                kernel_sum,
+            } => {
+                let height = fetch_height_from_hash(conn, &header_hash).or_not_found(
+                    "BlockHash",
+                    "hash",
+                    header_hash.to_hex(),
+                )?;
+                let mut block_accum_data =
+                    fetch_block_accumulated_data(conn, height)?.unwrap_or_else(BlockAccumulatedData::default);
+
+                block_accum_data.kernel_sum = kernel_sum;
+                update_block_accumulated_data(conn, height, &block_accum_data)?;
+            },
+            SetBestBlock {
+                height,
+                hash,
+                accumulated_difficulty,
+            } => {
+                self.set_metadata(conn, MetadataKey::ChainHeight, &height)?;
+                self.set_metadata(conn, MetadataKey::BestBlock, &hash)?;
+                self.set_metadata(conn, MetadataKey::AccumulatedWork, &accumulated_difficulty)?;
+            },
+            SetPruningHorizonConfig(pruning_horizon) => {
+                self.set_metadata(conn, MetadataKey::PruningHorizon, &pruning_horizon)?;
+            },
+            SetPrunedHeight {
+                height,
+                kernel_sum,
+                utxo_sum,
+            } => {
+                self.set_metadata(conn, MetadataKey::PrunedHeight, &height)?;
+                self.set_metadata(conn, MetadataKey::HorizonData, &HorizonData::new(kernel_sum, utxo_sum))?;
+            },
+        }
+        Ok(())
+    }
+
+    fn refresh_chain_metadata(&mut self) -> Result<(), ChainStorageError> {
+        let metadata = {
+            let conn = acquire_lock(&self.conn)?;
+            fetch_metadata(&conn)?
+        };
+        self.mem_metadata = Some(metadata);
+        self.is_mem_metadata_dirty = false;
+        Ok(())
+    }
+
+    fn insert_output(
+        &mut self,
+        conn: &SqliteConnection,
+        header_hash: HashOutput,
+        output: TransactionOutput,
+        mmr_position: u32,
+    ) -> Result<(), ChainStorageError>
+    {
+        let output_hash = output.hash();
+        let proof_hash = output.proof.hash();
+        let key = format!("{}-{:010}", header_hash.to_hex(), mmr_position);
+        sqlite_insert(
+            conn,
+            SQLITE_TABLE_TXOS_HASH_TO_INDEX,
+            output_hash.as_slice(),
+            &(mmr_position, key.clone()),
+        )?;
+        sqlite_insert(conn, SQLITE_TABLE_UTXOS, key.as_bytes(), &TransactionOutputRowData {
+            output: Some(output),
+            header_hash,
+            mmr_position,
+            hash: output_hash,
+            range_proof_hash: proof_hash,
+        })
+    }
+
+    fn insert_pruned_output(
+        &mut self,
+        conn: &SqliteConnection,
+        header_hash: HashOutput,
+        output_hash: HashOutput,
+        proof_hash: HashOutput,
+        mmr_position: u32,
+    ) -> Result<(), ChainStorageError>
+    {
+        let key = format!(
+            "{}-{:010}-{}-{}",
+            header_hash.to_hex(),
+            mmr_position,
+            output_hash.to_hex(),
+            proof_hash.to_hex()
+        );
+        sqlite_insert(
+            conn,
+            SQLITE_TABLE_TXOS_HASH_TO_INDEX,
+            output_hash.as_slice(),
+            &(mmr_position, key.clone()),
+        )?;
+        sqlite_insert(conn, SQLITE_TABLE_UTXOS, key.as_bytes(), &TransactionOutputRowData {
+            output: None,
+            header_hash,
+            mmr_position,
+            hash: output_hash,
+            range_proof_hash: proof_hash,
+        })
+    }
+
+    fn insert_kernel(
+        &mut self,
+        conn: &SqliteConnection,
+        header_hash: HashOutput,
+        kernel: TransactionKernel,
+        mmr_position: u32,
+    ) -> Result<(), ChainStorageError>
+    {
+        let hash = kernel.hash();
+        let key = format!("{}-{:010}-{}", header_hash.to_hex(), mmr_position, hash.to_hex());
+
+        sqlite_insert(
+            conn,
+            SQLITE_TABLE_KERNEL_EXCESS_INDEX,
+            kernel.excess.as_bytes(),
+            &(header_hash.clone(), mmr_position, hash.clone()),
+        )?;
+        sqlite_insert(
+            conn,
+            SQLITE_TABLE_KERNEL_EXCESS_SIG_INDEX,
+            &excess_sig_key(&kernel.excess_sig),
+            &(header_hash.clone(), mmr_position, hash.clone()),
+        )?;
+        sqlite_insert(conn, SQLITE_TABLE_KERNELS, key.as_bytes(), &TransactionKernelRowData {
+            kernel,
+            header_hash,
+            mmr_position,
+            hash,
+        })
+    }
+
+    fn insert_input(
+        &mut self,
+        conn: &SqliteConnection,
+        header_hash: HashOutput,
+        input: TransactionInput,
+        mmr_position: u32,
+    ) -> Result<(), ChainStorageError>
+    {
+        let hash = input.hash();
+        let key = format!("{}-{:010}-{}", header_hash.to_hex(), mmr_position, hash.to_hex());
+        sqlite_insert(conn, SQLITE_TABLE_INPUTS, key.as_bytes(), &TransactionInputRowData {
+            input,
+            header_hash,
+            mmr_position,
+            hash,
+        })
+    }
+
+    fn set_metadata<V: Serialize>(
+        &mut self,
+        conn: &SqliteConnection,
+        k: MetadataKey,
+        v: &V,
+    ) -> Result<(), ChainStorageError>
+    {
+        sqlite_replace(conn, SQLITE_TABLE_METADATA, &k.to_key(), v)?;
+        self.is_mem_metadata_dirty = true;
+        Ok(())
+    }
+
+    fn insert_orphan_block(&mut self, conn: &SqliteConnection, block: &Block) -> Result<(), ChainStorageError> {
+        let k = block.hash();
+        if sqlite_exists(conn, SQLITE_TABLE_ORPHANS, &k)? {
+            return Ok(());
+        }
+
+        sqlite_insert_dup(conn, SQLITE_TABLE_ORPHAN_PARENT_MAP_INDEX, &block.header.prev_hash, &k)?;
+        sqlite_replace(conn, SQLITE_TABLE_ORPHANS, &k, block)?;
+
+        Ok(())
+    }
+
+    /// Inserts the header and header accumulated data. True is returned if a new header is inserted, otherwise false if
+    /// the header already exists
+    fn insert_header(
+        &mut self,
+        conn: &SqliteConnection,
+        header: &BlockHeader,
+        accum_data: BlockHeaderAccumulatedData,
+    ) -> Result<bool, ChainStorageError>
+    {
+        let height_key = header.height.to_be_bytes();
+        if let Some(current_header_at_height) = sqlite_get::<BlockHeader>(conn, SQLITE_TABLE_HEADERS, &height_key)? {
+            let hash = current_header_at_height.hash();
+            if hash != accum_data.hash {
+                return Err(ChainStorageError::InvalidOperation(format!(
+                    "There is a different header stored at height {} already. New header ({}), current header: ({})",
+                    header.height,
+                    accum_data.hash.to_hex(),
+                    hash.to_hex()
+                )));
+            }
+            return Ok(false);
+        }
+
+        sqlite_replace(conn, SQLITE_TABLE_HEADER_ACCUMULATED_DATA, &height_key, &accum_data)?;
+        sqlite_insert(conn, SQLITE_TABLE_BLOCK_HASHES, &header.hash(), &header.height)?;
+        sqlite_insert(conn, SQLITE_TABLE_HEADERS, &height_key, header)?;
+        sqlite_insert(
+            conn,
+            SQLITE_TABLE_KERNEL_MMR_SIZE_INDEX,
+            &header.kernel_mmr_size.to_be_bytes(),
+            &header.height,
+        )?;
+        sqlite_insert(
+            conn,
+            SQLITE_TABLE_UTXO_MMR_SIZE_INDEX,
+            &header.output_mmr_size.to_be_bytes(),
+            &(header.height, header.hash()),
+        )?;
+        Ok(true)
+    }
+
+    fn op_delete(&mut self, conn: &SqliteConnection, key: DbKey) -> Result<(), ChainStorageError> {
+        match key {
+            DbKey::BlockHeader(k) => {
+                let height_key = k.to_be_bytes();
+                if let Some(v) = sqlite_get::<BlockHeader>(conn, SQLITE_TABLE_HEADERS, &height_key)? {
+                    let hash = v.hash();
+                    // Check that there are no utxos or kernels linked to this.
+                    if !sqlite_fetch_keys_starting_with::<TransactionKernelRowData>(
+                        hash.to_hex().as_str(),
+                        conn,
+                        SQLITE_TABLE_KERNELS,
+                    )?
+                    .is_empty()
+                    {
+                        return Err(ChainStorageError::InvalidOperation(
+                            "Cannot delete header because there are kernels linked to it".to_string(),
+                        ));
+                    }
+                    if !sqlite_fetch_keys_starting_with::<TransactionOutputRowData>(
+                        hash.to_hex().as_str(),
+                        conn,
+                        SQLITE_TABLE_UTXOS,
+                    )?
+                    .is_empty()
+                    {
+                        return Err(ChainStorageError::InvalidOperation(
+                            "Cannot delete header because there are utxos linked to it".to_string(),
+                        ));
+                    }
+
+                    sqlite_delete(conn, SQLITE_TABLE_BLOCK_HASHES, &hash)?;
+                    sqlite_delete(conn, SQLITE_TABLE_HEADERS, &height_key)?;
+                    sqlite_delete(conn, SQLITE_TABLE_HEADER_ACCUMULATED_DATA, &height_key)?;
+                    sqlite_delete(
+                        conn,
+                        SQLITE_TABLE_KERNEL_MMR_SIZE_INDEX,
+                        &v.kernel_mmr_size.to_be_bytes(),
+                    )?;
+                    sqlite_delete(conn, SQLITE_TABLE_UTXO_MMR_SIZE_INDEX, &v.output_mmr_size.to_be_bytes())?;
+                }
+            },
+            DbKey::BlockHash(_) => {
+                return Err(ChainStorageError::InvalidOperation(
+                    "Deleting by block hash is not supported. Use delete by height".to_string(),
+                ));
+            },
+            DbKey::OrphanBlock(k) => {
+                if let Some(orphan) = sqlite_get::<Block>(conn, SQLITE_TABLE_ORPHANS, &k)? {
+                    let parent_hash = orphan.header.prev_hash;
+                    sqlite_delete_key_value(conn, SQLITE_TABLE_ORPHAN_PARENT_MAP_INDEX, &parent_hash, &k)?;
+                    if sqlite_exists(conn, SQLITE_TABLE_ORPHAN_CHAIN_TIPS, &k)? {
+                        if sqlite_exists(conn, SQLITE_TABLE_ORPHANS, &parent_hash)? {
+                            sqlite_insert(conn, SQLITE_TABLE_ORPHAN_CHAIN_TIPS, &parent_hash, &parent_hash)?;
+                        }
+                        sqlite_delete(conn, SQLITE_TABLE_ORPHAN_CHAIN_TIPS, &k)?;
+                    }
+                    sqlite_delete(conn, SQLITE_TABLE_ORPHANS, &k)?;
+                }
+            },
+        }
+
+        Ok(())
+    }
+
+    #[allow(clippy::ptr_arg)]
+    fn delete_block_body(&mut self, conn: &SqliteConnection, hash: &HashOutput) -> Result<(), ChainStorageError> {
+        let hash_hex = hash.to_hex();
+        debug!(target: LOG_TARGET, "Deleting block `{}`", hash_hex);
+        debug!(target: LOG_TARGET, "Deleting UTXOs...");
+        if let Some(height) = fetch_height_from_hash(conn, hash)? {
+            sqlite_delete(conn, SQLITE_TABLE_BLOCK_ACCUMULATED_DATA, &height.to_be_bytes())?;
+        }
+        let rows = sqlite_delete_keys_starting_with::<TransactionOutputRowData>(conn, SQLITE_TABLE_UTXOS, &hash_hex)?;
+        for utxo in rows {
+            trace!(target: LOG_TARGET, "Deleting UTXO `{}`", to_hex(&utxo.hash));
+            sqlite_delete(conn, SQLITE_TABLE_TXOS_HASH_TO_INDEX, &utxo.hash)?;
+        }
+        debug!(target: LOG_TARGET, "Deleting kernels...");
+        let kernels =
+            sqlite_delete_keys_starting_with::<TransactionKernelRowData>(conn, SQLITE_TABLE_KERNELS, &hash_hex)?;
+        for kernel in kernels {
+            trace!(
+                target: LOG_TARGET,
+                "Deleting excess `{}`",
+                to_hex(kernel.kernel.excess.as_bytes())
+            );
+            sqlite_delete(conn, SQLITE_TABLE_KERNEL_EXCESS_INDEX, kernel.kernel.excess.as_bytes())?;
+            let excess_sig_key = excess_sig_key(&kernel.kernel.excess_sig);
+            trace!(
+                target: LOG_TARGET,
+                "Deleting excess signature `{}`",
+                to_hex(&excess_sig_key)
+            );
+            sqlite_delete(conn, SQLITE_TABLE_KERNEL_EXCESS_SIG_INDEX, &excess_sig_key)?;
+        }
+        debug!(target: LOG_TARGET, "Deleting Inputs...");
+        sqlite_delete_keys_starting_with::<TransactionInputRowData>(conn, SQLITE_TABLE_INPUTS, &hash_hex)?;
+        Ok(())
+    }
+
+    fn insert_block_body(
+        &mut self,
+        conn: &SqliteConnection,
+        header: &BlockHeader,
+        body: AggregateBody,
+    ) -> Result<(), ChainStorageError>
+    {
+        let block_hash = header.hash();
+        debug!(
+            target: LOG_TARGET,
+            "Inserting block body for header `{}`: {}",
+            block_hash.to_hex(),
+            body.to_counts_string()
+        );
+
+        let (inputs, outputs, kernels) = body.dissolve();
+
+        let data = if header.height == 0 {
+            BlockAccumulatedData::default()
+        } else {
+            fetch_block_accumulated_data(conn, header.height - 1)?.ok_or_else(|| ChainStorageError::ValueNotFound {
+                entity: "BlockAccumulatedData".to_string(),
+                field: "prev_hash".to_string(),
+                value: header.prev_hash.to_hex(),
+            })?
+        };
+
+        let mut total_kernel_sum = Commitment::from_bytes(&[0u8; 32]).expect("Could not create commitment");
+        let mut total_utxo_sum = Commitment::from_bytes(&[0u8; 32]).expect("Could not create commitment");
+        let BlockAccumulatedData {
+            kernels: pruned_kernel_set,
+            outputs: pruned_output_set,
+            deleted,
+            range_proofs: pruned_proof_set,
+            ..
+        } = data;
+
+        let mut kernel_mmr = MerkleMountainRange::<HashDigest, _>::new(pruned_kernel_set);
+
+        for kernel in kernels {
+            total_kernel_sum = &total_kernel_sum + &kernel.excess;
+            let pos = kernel_mmr.push(kernel.hash())?;
+            self.insert_kernel(conn, block_hash.clone(), kernel, pos as u32)?;
+        }
+
+        let mut output_mmr = MutableMmr::<HashDigest, _>::new(pruned_output_set, deleted.deleted)?;
+        let mut proof_mmr = MerkleMountainRange::<HashDigest, _>::new(pruned_proof_set);
+        for output in outputs {
+            total_utxo_sum = &total_utxo_sum + &output.commitment;
+            output_mmr.push(output.hash())?;
+            proof_mmr.push(output.proof().hash())?;
+            self.insert_output(
+                conn,
+                block_hash.clone(),
+                output,
+                (proof_mmr.get_leaf_count()? - 1) as u32,
+            )?;
+        }
+
+        for input in inputs {
+            total_utxo_sum = &total_utxo_sum - &input.commitment;
+            let index =
+                fetch_mmr_leaf_index(conn, MmrTree::Utxo, &input.hash())?.ok_or(ChainStorageError::UnspendableInput)?;
+            if !output_mmr.delete(index) {
+                return Err(ChainStorageError::InvalidOperation(format!(
+                    "Could not delete index {} from the output MMR",
+                    index
+                )));
+            }
+            self.insert_input(conn, block_hash.clone(), input, index)?;
+        }
+        output_mmr.compress();
+
+        update_block_accumulated_data(
+            conn,
+            header.height,
+            &BlockAccumulatedData::new(
+                kernel_mmr.get_pruned_hash_set()?,
+                output_mmr.mmr().get_pruned_hash_set()?,
+                proof_mmr.get_pruned_hash_set()?,
+                output_mmr.deleted().clone(),
+                total_kernel_sum,
+            ),
+        )?;
+
+        Ok(())
+    }
+}
+
+/// Opens (or creates) the SQLite blockchain database in the given directory and brings its schema up to date.
+pub fn create_sqlite_database<P: AsRef<Path>>(path: P) -> Result<SqliteDatabase, ChainStorageError> {
+    let _ = std::fs::create_dir_all(&path);
+
+    let file_lock = acquire_exclusive_file_lock(&path.as_ref().to_path_buf())?;
+
+    let db_file = path.as_ref().join(SQLITE_DB_FILE_NAME);
+    let db_file = db_file
+        .to_str()
+        .ok_or_else(|| ChainStorageError::CriticalError("The database path is not valid unicode".to_string()))?;
+    let conn = SqliteConnection::establish(db_file)
+        .map_err(|err| ChainStorageError::CriticalError(format!("Could not open SQLite database:{}", err)))?;
+    conn.execute("PRAGMA journal_mode = WAL; PRAGMA busy_timeout = 60000;")?;
+
+    embed_migrations!("./migrations");
+    embedded_migrations::run(&conn)
+        .map_err(|err| ChainStorageError::CriticalError(format!("Database migration failed:{}", err)))?;
+
+    SqliteDatabase::new(conn, file_lock)
+}
+
+fn acquire_exclusive_file_lock(db_path: &PathBuf) -> Result<File, ChainStorageError> {
+    let lock_file_path = db_path.join(".chain_storage_file.lock");
+
+    let file = File::create(lock_file_path)?;
+    // Attempt to acquire exclusive OS level Write Lock
+    if let Err(e) = file.try_lock_exclusive() {
+        error!(
+            target: LOG_TARGET,
+            "Could not acquire exclusive write lock on database lock file: {:?}", e
+        );
+        return Err(ChainStorageError::CannotAcquireFileLock);
+    }
+
+    Ok(file)
+}
+
+fn acquire_lock(conn: &Mutex<SqliteConnection>) -> Result<MutexGuard<'_, SqliteConnection>, ChainStorageError> {
+    conn.lock().map_err(|e| ChainStorageError::AccessError(e.to_string()))
+}
+
+impl BlockchainBackend for SqliteDatabase {
+    fn write(&mut self, txn: DbTransaction) -> Result<(), ChainStorageError> {
+        if txn.operations().is_empty() {
+            return Ok(());
+        }
+
+        let mark = Instant::now();
+        let num_operations = txn.operations().len();
+        match self.apply_db_transaction(txn) {
+            Ok(_) => {
+                trace!(
+                    target: LOG_TARGET,
+                    "Database completed {} operation(s) in {:.0?}",
+                    num_operations,
+                    mark.elapsed()
+                );
+                Ok(())
+            },
+            Err(e) => {
+                error!(target: LOG_TARGET, "Failed to apply DB transaction: {}", e);
+                Err(e)
+            },
+        }
+    }
+
+    fn fetch(&self, key: &DbKey) -> Result<Option<DbValue>, ChainStorageError> {
+        let mark = Instant::now();
+        let conn = acquire_lock(&self.conn)?;
+        let res = match key {
+            DbKey::BlockHeader(k) => fetch_header_by_height(&conn, *k)?.map(|val| DbValue::BlockHeader(Box::new(val))),
+            DbKey::BlockHash(hash) => {
+                if hash.len() != BLOCK_HASH_LENGTH {
+                    return Err(ChainStorageError::InvalidQuery(format!(
+                        "Invalid block hash length. Expected length: {} Got: {}",
+                        BLOCK_HASH_LENGTH,
+                        hash.len()
+                    )));
+                }
+                match fetch_height_from_hash(&conn, hash)? {
+                    Some(k) => {
+                        trace!(
+                            target: LOG_TARGET,
+                            "Header with hash:{} found at height:{}",
+                            hash.to_hex(),
+                            k
+                        );
+                        fetch_header_by_height(&conn, k)?.map(|val| DbValue::BlockHash(Box::new(val)))
+                    },
+                    None => {
+                        trace!(
+                            target: LOG_TARGET,
+                            "Header with hash:{} not found in block_hashes table",
+                            hash.to_hex()
+                        );
+                        None
+                    },
+                }
+            },
+            DbKey::OrphanBlock(k) => {
+                sqlite_get::<Block>(&conn, SQLITE_TABLE_ORPHANS, k)?.map(|val| DbValue::OrphanBlock(Box::new(val)))
+            },
+        };
+        trace!(target: LOG_TARGET, "Fetched key {} in {:.0?}", key, mark.elapsed());
+        Ok(res)
+    }
+
+    fn contains(&self, key: &DbKey) -> Result<bool, ChainStorageError> {
+        let conn = acquire_lock(&self.conn)?;
+        match key {
+            DbKey::BlockHeader(k) => sqlite_exists(&conn, SQLITE_TABLE_HEADERS, &k.to_be_bytes()),
+            DbKey::BlockHash(h) => sqlite_exists(&conn, SQLITE_TABLE_BLOCK_HASHES, h),
+            DbKey::OrphanBlock(k) => sqlite_exists(&conn, SQLITE_TABLE_ORPHANS, k),
+        }
+    }
+
+    fn fetch_header_and_accumulated_data(
+        &self,
+        height: u64,
+    ) -> Result<(BlockHeader, BlockHeaderAccumulatedData), ChainStorageError>
+    {
+        let conn = acquire_lock(&self.conn)?;
+        fetch_header_and_accumulated_data(&conn, height)
+    }
+
+    fn fetch_header_accumulated_data(
+        &self,
+        hash: &HashOutput,
+    ) -> Result<Option<BlockHeaderAccumulatedData>, ChainStorageError>
+    {
+        let conn = acquire_lock(&self.conn)?;
+        match fetch_height_from_hash(&conn, hash)? {
+            Some(h) => fetch_header_accumulated_data_by_height(&conn, h),
+            None => Ok(None),
+        }
+    }
+
+    fn fetch_chain_header_in_all_chains(&self, hash: &HashOutput) -> Result<Option<ChainHeader>, ChainStorageError> {
+        let conn = acquire_lock(&self.conn)?;
+        if let Some(h) = fetch_height_from_hash(&conn, hash)? {
+            let (header, accumulated_data) = fetch_header_and_accumulated_data(&conn, h)?;
+            return Ok(Some(ChainHeader {
+                header,
+                accumulated_data,
+            }));
+        }
+        let orphan_accum: Option<BlockHeaderAccumulatedData> =
+            sqlite_get(&conn, SQLITE_TABLE_ORPHAN_HEADER_ACCUMULATED_DATA, hash)?;
+        if let Some(accumulated_data) = orphan_accum {
+            if let Some(orphan) = sqlite_get::<Block>(&conn, SQLITE_TABLE_ORPHANS, hash)? {
+                return Ok(Some(ChainHeader {
+                    header: orphan.header,
+                    accumulated_data,
+                }));
+            }
+        }
+        Ok(None)
+    }
+
+    fn fetch_header_containing_kernel_mmr(&self, mmr_position: u64) -> Result<ChainHeader, ChainStorageError> {
+        let conn = acquire_lock(&self.conn)?;
+        let height = sqlite_first_after::<u64>(&conn, SQLITE_TABLE_KERNEL_MMR_SIZE_INDEX, &mmr_position.to_be_bytes())
+            .or_not_found("BlockHeader", "mmr_position", mmr_position.to_string())?;
+        let (header, accumulated_data) = fetch_header_and_accumulated_data(&conn, height)?;
+        Ok(ChainHeader {
+            header,
+            accumulated_data,
+        })
+    }
+
+    fn fetch_header_containing_utxo_mmr(&self, mmr_position: u64) -> Result<ChainHeader, ChainStorageError> {
+        let conn = acquire_lock(&self.conn)?;
+        let (height, _hash) =
+            sqlite_first_after::<(u64, Vec<u8>)>(&conn, SQLITE_TABLE_UTXO_MMR_SIZE_INDEX, &mmr_position.to_be_bytes())
+                .or_not_found("BlockHeader", "mmr_position", mmr_position.to_string())?;
+        let (header, accumulated_data) = fetch_header_and_accumulated_data(&conn, height)?;
+        Ok(ChainHeader {
+            header,
+            accumulated_data,
+        })
+    }
+
+    fn is_empty(&self) -> Result<bool, ChainStorageError> {
+        let conn = acquire_lock(&self.conn)?;
+        Ok(sqlite_len(&conn, SQLITE_TABLE_HEADERS)? == 0)
+    }
+
+    fn fetch_block_accumulated_data(
+        &self,
+        header_hash: &HashOutput,
+    ) -> Result<Option<BlockAccumulatedData>, ChainStorageError>
+    {
+        let conn = acquire_lock(&self.conn)?;
+        match fetch_height_from_hash(&conn, header_hash)? {
+            Some(height) => fetch_block_accumulated_data(&conn, height),
+            None => Ok(None),
+        }
+    }
+
+    fn fetch_block_accumulated_data_by_height(
+        &self,
+        height: u64,
+    ) -> Result<Option<BlockAccumulatedData>, ChainStorageError>
+    {
+        let conn = acquire_lock(&self.conn)?;
+        fetch_block_accumulated_data(&conn, height)
+    }
+
+    fn fetch_kernels_in_block(&self, header_hash: &HashOutput) -> Result<Vec<TransactionKernel>, ChainStorageError> {
+        let conn = acquire_lock(&self.conn)?;
+        Ok(
+            sqlite_fetch_keys_starting_with(header_hash.to_hex().as_str(), &conn, SQLITE_TABLE_KERNELS)?
+                .into_iter()
+                .map(|f: TransactionKernelRowData| f.kernel)
+                .collect(),
+        )
+    }
+
+    fn fetch_kernel_by_excess(
+        &self,
+        excess: &[u8],
+    ) -> Result<Option<(TransactionKernel, HashOutput)>, ChainStorageError>
+    {
+        let conn = acquire_lock(&self.conn)?;
+        fetch_kernel_by_index(&conn, SQLITE_TABLE_KERNEL_EXCESS_INDEX, excess)
+    }
+
+    fn fetch_kernel_by_excess_sig(
+        &self,
+        excess_sig: &Signature,
+    ) -> Result<Option<(TransactionKernel, HashOutput)>, ChainStorageError>
+    {
+        let conn = acquire_lock(&self.conn)?;
+        fetch_kernel_by_index(&conn, SQLITE_TABLE_KERNEL_EXCESS_SIG_INDEX, &excess_sig_key(excess_sig))
+    }
+
+    fn fetch_kernels_by_mmr_position(&self, start: u64, end: u64) -> Result<Vec<TransactionKernel>, ChainStorageError> {
+        let conn = acquire_lock(&self.conn)?;
+        let start_height =
+            match sqlite_first_after::<u64>(&conn, SQLITE_TABLE_KERNEL_MMR_SIZE_INDEX, &(start + 1).to_be_bytes())? {
+                Some(h) => h,
+                None => return Ok(vec![]),
+            };
+        let end_height: u64 = sqlite_first_after(&conn, SQLITE_TABLE_KERNEL_MMR_SIZE_INDEX, &(end + 1).to_be_bytes())?
+            .unwrap_or(start_height);
+
+        let previous_mmr_count = if start_height == 0 {
+            0
+        } else {
+            fetch_header_by_height(&conn, start_height - 1)
+                .or_not_found("BlockHeader", "height", (start_height - 1).to_string())?
+                .kernel_mmr_size
+        };
+
+        let total_size = (end - start) as usize + 1;
+        let mut result = Vec::with_capacity(total_size);
+
+        let mut skip_amount = (start - previous_mmr_count) as usize;
+        debug!(
+            target: LOG_TARGET,
+            "Fetching kernels by MMR position. Start {}, end {}, in headers at height {}-{},  prev mmr count: {}, \
+             skipping the first:{}",
+            start,
+            end,
+            start_height,
+            end_height,
+            previous_mmr_count,
+            skip_amount
+        );
+
+        for height in start_height..=end_height {
+            let hash = fetch_header_accumulated_data_by_height(&conn, height)
+                .or_not_found("BlockHeader", "height", height.to_string())?
+                .hash;
+
+            result.extend(
+                sqlite_fetch_keys_starting_with::<TransactionKernelRowData>(
+                    hash.to_hex().as_str(),
+                    &conn,
+                    SQLITE_TABLE_KERNELS,
+                )?
+                .into_iter()
+                .skip(skip_amount)
+                .take(total_size - result.len())
+                .map(|f| f.kernel),
+            );
+
+            skip_amount = 0;
+        }
+        Ok(result)
+    }
+
+    fn fetch_utxos_by_mmr_position(
+        &self,
+        start: u64,
+        end: u64,
+        deleted: &Bitmap,
+    ) -> Result<(Vec<PrunedOutput>, Vec<Bitmap>), ChainStorageError>
+    {
+        let conn = acquire_lock(&self.conn)?;
+        let start_height: u64 = match sqlite_first_after::<(u64, Vec<u8>)>(
+            &conn,
+            SQLITE_TABLE_UTXO_MMR_SIZE_INDEX,
+            &(start + 1).to_be_bytes(),
+        )? {
+            Some((h, _)) => h,
+            None => return Ok((vec![], vec![])),
+        };
+        let end_height =
+            sqlite_first_after::<(u64, Vec<u8>)>(&conn, SQLITE_TABLE_UTXO_MMR_SIZE_INDEX, &(end + 1).to_be_bytes())?
+                .map(|(h, _)| h)
+                .unwrap_or(start_height);
+
+        let previous_mmr_count = if start_height == 0 {
+            0
+        } else {
+            fetch_header_by_height(&conn, start_height - 1)
+                .or_not_found("BlockHeader", "height", (start_height - 1).to_string())?
+                .output_mmr_size
+        };
+
+        let total_size = (end - start) as usize + 1;
+        let mut result = Vec::with_capacity(total_size);
+        let mut deleted_result = vec![];
+
+        let mut skip_amount = (start - previous_mmr_count) as usize;
+        debug!(
+            target: LOG_TARGET,
+            "Fetching outputs by MMR position. Start {}, end {}, starting in header at height {},  prev mmr count: \
+             {}, skipping the first:{}",
+            start,
+            end,
+            start_height,
+            previous_mmr_count,
+            skip_amount
+        );
+
+        for height in start_height..=end_height {
+            let accum_data = fetch_header_accumulated_data_by_height(&conn, height).or_not_found(
+                "BlockHeader",
+                "height",
+                height.to_string(),
+            )?;
+
+            result.extend(
+                sqlite_fetch_keys_starting_with::<TransactionOutputRowData>(
+                    accum_data.hash.to_hex().as_str(),
+                    &conn,
+                    SQLITE_TABLE_UTXOS,
+                )?
+                .into_iter()
+                .skip(skip_amount)
+                .take(total_size - result.len())
+                .map(|row| match row.output {
+                    Some(output) if !deleted.contains(row.mmr_position) => PrunedOutput::NotPruned { output },
+                    _ => PrunedOutput::Pruned {
+                        output_hash: row.hash,
+                        range_proof_hash: row.range_proof_hash,
+                    },
+                }),
+            );
+
+            let block_accum_data = fetch_block_accumulated_data(&conn, height).or_not_found(
+                "BlockAccumulatedData",
+                "height",
+                height.to_string(),
+            )?;
+            deleted_result.push(block_accum_data.deleted().clone());
+
+            skip_amount = 0;
+        }
+        Ok((result, deleted_result))
+    }
+
+    fn fetch_output(&self, output_hash: &HashOutput) -> Result<Option<(TransactionOutput, u32)>, ChainStorageError> {
+        debug!(target: LOG_TARGET, "Fetch output: {}", output_hash.to_hex());
+        let conn = acquire_lock(&self.conn)?;
+        let (index, key) = match sqlite_get::<(u32, String)>(&conn, SQLITE_TABLE_TXOS_HASH_TO_INDEX, output_hash)? {
+            Some(v) => v,
+            None => {
+                debug!(
+                    target: LOG_TARGET,
+                    "Fetch output: {} NOT found in index",
+                    output_hash.to_hex()
+                );
+                return Ok(None);
+            },
+        };
+        debug!(
+            target: LOG_TARGET,
+            "Fetch output: {} Found ({}, {})",
+            output_hash.to_hex(),
+            index,
+            key
+        );
+        match sqlite_get::<TransactionOutputRowData>(&conn, SQLITE_TABLE_UTXOS, key.as_bytes())? {
+            Some(TransactionOutputRowData {
+                output: Some(output),
+                mmr_position,
+                ..
+            }) => Ok(Some((output, mmr_position))),
+            // The output has been pruned
+            Some(_) => Err(ChainStorageError::BeyondPruningHorizon),
+            None => Ok(None),
+        }
+    }
+
+    fn fetch_outputs_in_block(&self, header_hash: &HashOutput) -> Result<Vec<PrunedOutput>, ChainStorageError> {
+        let conn = acquire_lock(&self.conn)?;
+        Ok(
+            sqlite_fetch_keys_starting_with(header_hash.to_hex().as_str(), &conn, SQLITE_TABLE_UTXOS)?
+                .into_iter()
+                .map(|f: TransactionOutputRowData| match f.output {
+                    Some(o) => PrunedOutput::NotPruned { output: o },
+                    None => PrunedOutput::Pruned {
+                        output_hash: f.hash,
+                        range_proof_hash: f.range_proof_hash,
+                    },
+                })
+                .collect(),
+        )
+    }
+
+    fn fetch_inputs_in_block(&self, header_hash: &HashOutput) -> Result<Vec<TransactionInput>, ChainStorageError> {
+        let conn = acquire_lock(&self.conn)?;
+        Ok(
+            sqlite_fetch_keys_starting_with(header_hash.to_hex().as_str(), &conn, SQLITE_TABLE_INPUTS)?
+                .into_iter()
+                .map(|f: TransactionInputRowData| f.input)
+                .collect(),
+        )
+    }
+
+    fn fetch_mmr_size(&self, tree: MmrTree) -> Result<u64, ChainStorageError> {
+        let conn = acquire_lock(&self.conn)?;
+        match tree {
+            MmrTree::Kernel => Ok(sqlite_len(&conn, SQLITE_TABLE_KERNELS)? as u64),
+            // Every output contributes exactly one leaf to both the UTXO and range proof MMRs
+            MmrTree::Utxo | MmrTree::RangeProof => Ok(sqlite_len(&conn, SQLITE_TABLE_UTXOS)? as u64),
+        }
+    }
+
+    fn fetch_mmr_leaf_index(&self, tree: MmrTree, hash: &Hash) -> Result<Option<u32>, ChainStorageError> {
+        trace!(target: LOG_TARGET, "Fetch MMR leaf index");
+        let conn = acquire_lock(&self.conn)?;
+        fetch_mmr_leaf_index(&conn, tree, hash)
+    }
+
+    /// Returns the number of blocks in the block orphan pool.
+    fn orphan_count(&self) -> Result<usize, ChainStorageError> {
+        trace!(target: LOG_TARGET, "Get orphan count");
+        let conn = acquire_lock(&self.conn)?;
+        sqlite_len(&conn, SQLITE_TABLE_ORPHANS)
+    }
+
+    /// Finds and returns the last stored header.
+    fn fetch_last_header(&self) -> Result<BlockHeader, ChainStorageError> {
+        let conn = acquire_lock(&self.conn)?;
+        sqlite_last(&conn, SQLITE_TABLE_HEADERS)?.ok_or_else(|| {
+            ChainStorageError::InvalidOperation("Cannot fetch last header because database is empty".to_string())
+        })
+    }
+
+    fn fetch_tip_header(&self) -> Result<ChainHeader, ChainStorageError> {
+        let height = self.fetch_chain_metadata()?.height_of_longest_chain();
+        let conn = acquire_lock(&self.conn)?;
+        let (header, accumulated_data) = fetch_header_and_accumulated_data(&conn, height)?;
+        Ok(ChainHeader {
+            header,
+            accumulated_data,
+        })
+    }
+
+    /// Returns the metadata of the chain.
+    fn fetch_chain_metadata(&self) -> Result<ChainMetadata, ChainStorageError> {
+        // This should only be None if the database is empty
+        self.mem_metadata.as_ref().cloned().ok_or_else(|| {
+            ChainStorageError::AccessError("Cannot retrieve chain metadata because the database is empty".to_string())
+        })
+    }
+
+    fn utxo_count(&self) -> Result<usize, ChainStorageError> {
+        let conn = acquire_lock(&self.conn)?;
+        sqlite_len(&conn, SQLITE_TABLE_UTXOS)
+    }
+
+    fn kernel_count(&self) -> Result<usize, ChainStorageError> {
+        let conn = acquire_lock(&self.conn)?;
+        sqlite_len(&conn, SQLITE_TABLE_KERNELS)
+    }
+
+    fn fetch_orphan_chain_tip_by_hash(&self, hash: &HashOutput) -> Result<Option<ChainHeader>, ChainStorageError> {
+        trace!(target: LOG_TARGET, "Call to fetch_orphan_chain_tips()");
+        let conn = acquire_lock(&self.conn)?;
+        if !sqlite_exists(&conn, SQLITE_TABLE_ORPHAN_CHAIN_TIPS, hash)? {
+            return Ok(None);
+        }
+        let orphan: Block =
+            sqlite_get(&conn, SQLITE_TABLE_ORPHANS, hash).or_not_found("Orphan", "hash", hash.to_hex())?;
+        let accumulated_data = sqlite_get(&conn, SQLITE_TABLE_ORPHAN_HEADER_ACCUMULATED_DATA, hash).or_not_found(
+            "Orphan accumulated data",
+            "hash",
+            hash.to_hex(),
+        )?;
+        Ok(Some(ChainHeader {
+            header: orphan.header,
+            accumulated_data,
+        }))
+    }
+
+    fn fetch_orphan_children_of(&self, hash: HashOutput) -> Result<Vec<Block>, ChainStorageError> {
+        trace!(
+            target: LOG_TARGET,
+            "Call to fetch_orphan_children_of({})",
+            hash.to_hex()
+        );
+        let conn = acquire_lock(&self.conn)?;
+        let orphan_hashes: Vec<HashOutput> = sqlite_get_multiple(&conn, SQLITE_TABLE_ORPHAN_PARENT_MAP_INDEX, &hash)?;
+        orphan_hashes
+            .into_iter()
+            .map(|hash| sqlite_get(&conn, SQLITE_TABLE_ORPHANS, &hash).or_not_found("Orphan", "hash", hash.to_hex()))
+            .collect()
+    }
+
+    fn fetch_orphan_header_accumulated_data(
+        &self,
+        hash: HashOutput,
+    ) -> Result<BlockHeaderAccumulatedData, ChainStorageError>
+    {
+        let conn = acquire_lock(&self.conn)?;
+        sqlite_get(&conn, SQLITE_TABLE_ORPHAN_HEADER_ACCUMULATED_DATA, &hash).or_not_found(
+            "Orphan accumulated data",
+            "hash",
+            hash.to_hex(),
+        )
+    }
+
+    fn delete_oldest_orphans(
+        &mut self,
+        horizon_height: u64,
+        orphan_storage_capacity: usize,
+    ) -> Result<(), ChainStorageError>
+    {
+        let orphan_count = self.orphan_count()?;
+        let num_over_limit = orphan_count.saturating_sub(orphan_storage_capacity);
+        if num_over_limit == 0 {
+            return Ok(());
+        }
+        debug!(
+            target: LOG_TARGET,
+            "Orphan block storage limit of {} reached, performing cleanup of {} entries.",
+            orphan_storage_capacity,
+            num_over_limit,
+        );
+
+        let mut orphans = {
+            let conn = acquire_lock(&self.conn)?;
+            sqlite_filter_map_values(&conn, SQLITE_TABLE_ORPHANS, |block: Block| {
+                Ok(Some((block.header.height, block.hash())))
+            })?
+        };
+
+        orphans.sort_by(|a, b| a.0.cmp(&b.0));
+        let mut txn = DbTransaction::new();
+        for (removed_count, (height, block_hash)) in orphans.into_iter().enumerate() {
+            if height > horizon_height && removed_count >= num_over_limit {
+                break;
+            }
+            debug!(
+                target: LOG_TARGET,
+                "Discarding orphan block #{} ({}).",
+                height,
+                block_hash.to_hex()
+            );
+            txn.delete(DbKey::OrphanBlock(block_hash));
+        }
+        self.write(txn)
+    }
+
+    fn fetch_monero_seed_first_seen_height(&self, seed: &str) -> Result<u64, ChainStorageError> {
+        let conn = acquire_lock(&self.conn)?;
+        Ok(sqlite_get(&conn, SQLITE_TABLE_MONERO_SEED_HEIGHT, seed.as_bytes())?.unwrap_or(0))
+    }
+
+    fn fetch_horizon_data(&self) -> Result<Option<HorizonData>, ChainStorageError> {
+        let conn = acquire_lock(&self.conn)?;
+        fetch_horizon_data(&conn)
+    }
+}
+
+fn prune_output(conn: &SqliteConnection, key: &str) -> Result<Option<TransactionOutput>, ChainStorageError> {
+    let mut output: TransactionOutputRowData = sqlite_get(conn, SQLITE_TABLE_UTXOS, key.as_bytes()).or_not_found(
+        "TransactionOutput",
+        "key",
+        key.to_string(),
+    )?;
+    let result = output.output.take();
+    sqlite_replace(conn, SQLITE_TABLE_UTXOS, key.as_bytes(), &output)?;
+    Ok(result)
+}
+
+fn update_block_accumulated_data(
+    conn: &SqliteConnection,
+    header_height: u64,
+    data: &BlockAccumulatedData,
+) -> Result<(), ChainStorageError>
+{
+    sqlite_replace(
+        conn,
+        SQLITE_TABLE_BLOCK_ACCUMULATED_DATA,
+        &header_height.to_be_bytes(),
+        data,
+    )
+}
+
+#[allow(clippy::ptr_arg)]
+fn fetch_mmr_leaf_index(conn: &SqliteConnection, tree: MmrTree, hash: &Hash) -> Result<Option<u32>, ChainStorageError> {
+    match tree {
+        MmrTree::Utxo => {
+            Ok(sqlite_get::<(u32, String)>(conn, SQLITE_TABLE_TXOS_HASH_TO_INDEX, hash)?.map(|(index, _)| index))
+        },
+        _ => Err(ChainStorageError::InvalidQuery(format!(
+            "Fetching the leaf index of the {} MMR is not supported",
+            tree
+        ))),
+    }
+}
+
+fn fetch_block_accumulated_data(
+    conn: &SqliteConnection,
+    height: u64,
+) -> Result<Option<BlockAccumulatedData>, ChainStorageError>
+{
+    sqlite_get(conn, SQLITE_TABLE_BLOCK_ACCUMULATED_DATA, &height.to_be_bytes())
+}
+
+#[allow(clippy::ptr_arg)]
+fn fetch_height_from_hash(conn: &SqliteConnection, header_hash: &HashOutput) -> Result<Option<u64>, ChainStorageError> {
+    sqlite_get(conn, SQLITE_TABLE_BLOCK_HASHES, header_hash)
+}
+
+fn fetch_header_by_height(conn: &SqliteConnection, height: u64) -> Result<Option<BlockHeader>, ChainStorageError> {
+    sqlite_get(conn, SQLITE_TABLE_HEADERS, &height.to_be_bytes())
+}
+
+fn fetch_header_accumulated_data_by_height(
+    conn: &SqliteConnection,
+    height: u64,
+) -> Result<Option<BlockHeaderAccumulatedData>, ChainStorageError>
+{
+    sqlite_get(conn, SQLITE_TABLE_HEADER_ACCUMULATED_DATA, &height.to_be_bytes())
+}
+
+fn fetch_header_and_accumulated_data(
+    conn: &SqliteConnection,
+    height: u64,
+) -> Result<(BlockHeader, BlockHeaderAccumulatedData), ChainStorageError>
+{
+    let header = fetch_header_by_height(conn, height).or_not_found("BlockHeader", "height", height.to_string())?;
+    let accum_data = fetch_header_accumulated_data_by_height(conn, height).or_not_found(
+        "BlockHeaderAccumulatedData",
+        "height",
+        height.to_string(),
+    )?;
+    Ok((header, accum_data))
+}
+
+/// Looks up a kernel via one of the kernel indexes, which map to the kernel's (header_hash, mmr_position, hash)
+fn fetch_kernel_by_index(
+    conn: &SqliteConnection,
+    index_table: &'static str,
+    index_key: &[u8],
+) -> Result<Option<(TransactionKernel, HashOutput)>, ChainStorageError>
+{
+    match sqlite_get::<(HashOutput, u32, HashOutput)>(conn, index_table, index_key)? {
+        Some((header_hash, mmr_position, hash)) => {
+            let key = format!("{}-{:010}-{}", header_hash.to_hex(), mmr_position, hash.to_hex());
+            Ok(sqlite_get(conn, SQLITE_TABLE_KERNELS, key.as_bytes())?
+                .map(|kernel: TransactionKernelRowData| (kernel.kernel, header_hash)))
+        },
+        None => Ok(None),
+    }
+}
+
+fn excess_sig_key(excess_sig: &Signature) -> Vec<u8> {
+    let mut key = Vec::<u8>::new();
+    key.extend(excess_sig.get_public_nonce().as_bytes());
+    key.extend(excess_sig.get_signature().as_bytes());
+    key
+}
+
+// Fetch the chain metadata
+fn fetch_metadata(conn: &SqliteConnection) -> Result<ChainMetadata, ChainStorageError> {
+    Ok(ChainMetadata::new(
+        fetch_metadata_value(conn, MetadataKey::ChainHeight).or_not_found(
+            "ChainMetadata",
+            "ChainHeight",
+            "".to_string(),
+        )?,
+        fetch_metadata_value(conn, MetadataKey::BestBlock).or_not_found(
+            "ChainMetadata",
+            "BestBlock",
+            "".to_string(),
+        )?,
+        fetch_metadata_value(conn, MetadataKey::PruningHorizon)?.unwrap_or(0),
+        fetch_metadata_value(conn, MetadataKey::PrunedHeight)?.unwrap_or(0),
+        fetch_metadata_value(conn, MetadataKey::AccumulatedWork).or_not_found(
+            "ChainMetadata",
+            "AccumulatedWork",
+            "".to_string(),
+        )?,
+    ))
+}
+
+fn fetch_horizon_data(conn: &SqliteConnection) -> Result<Option<HorizonData>, ChainStorageError> {
+    fetch_metadata_value(conn, MetadataKey::HorizonData)
+}
+
+fn fetch_metadata_value<V: DeserializeOwned>(
+    conn: &SqliteConnection,
+    key: MetadataKey,
+) -> Result<Option<V>, ChainStorageError>
+{
+    sqlite_get(conn, SQLITE_TABLE_METADATA, &key.to_key())
+}
+
+/// Each metadata entry is stored as its own bincode-encoded value, so unlike the LMDB backend no wrapping enum is
+/// needed.
+#[derive(Debug, Clone, PartialEq, Copy)]
+enum MetadataKey {
+    ChainHeight,
+    BestBlock,
+    AccumulatedWork,
+    PruningHorizon,
+    PrunedHeight,
+    HorizonData,
+}
+
+impl MetadataKey {
+    fn to_key(self) -> [u8; 4] {
+        (self as u32).to_be_bytes()
+    }
+}
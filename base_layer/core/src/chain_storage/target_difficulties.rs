@@ -19,62 +19,198 @@
 //  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
 //  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
-
 use crate::{
     blocks::BlockHeader,
     consensus::ConsensusManager,
-    proof_of_work::{Difficulty, PowAlgorithm, TargetDifficultyWindow},
+    proof_of_work::{Difficulty, PowAlgorithm},
+};
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::{Display, Error, Formatter},
 };
+use tari_crypto::tari_utilities::epoch_time::EpochTime;
 
+/// The timestamps and target difficulties of the most recent headers mined with each proof of work algorithm that
+/// was accepted at any height up to the starting height. Algorithms that are activated later start with an empty
+/// history. Target difficulties are calculated from this history in the same way as when a block is added to the
+/// chain, using the consensus constants at the height of the header being validated.
 #[derive(Debug, Clone)]
 pub struct TargetDifficulties {
-    monero: TargetDifficultyWindow,
-    sha3: TargetDifficultyWindow,
+    consensus_rules: ConsensusManager,
+    capacity: usize,
+    history: HashMap<PowAlgorithm, VecDeque<(EpochTime, Difficulty)>>,
 }
 
 impl TargetDifficulties {
     pub fn new(consensus_rules: &ConsensusManager, height: u64) -> Self {
+        let history = PowAlgorithm::all()
+            .iter()
+            .copied()
+            .filter(|algo| consensus_rules.has_accepted_pow_algorithm(*algo, height))
+            .map(|algo| (algo, VecDeque::new()))
+            .collect();
         Self {
-            monero: consensus_rules.new_target_difficulty(PowAlgorithm::Monero, height),
-            sha3: consensus_rules.new_target_difficulty(PowAlgorithm::Sha3, height),
+            consensus_rules: consensus_rules.clone(),
+            // A window of N blocks holds N + 1 target difficulties
+            capacity: consensus_rules.max_difficulty_block_window() as usize + 1,
+            history,
         }
     }
 
+    /// Adds a more recent target difficulty
     pub fn add_back(&mut self, header: &BlockHeader, target_difficulty: Difficulty) {
-        self.get_mut(header.pow_algo())
-            .add_back(header.timestamp(), target_difficulty);
+        let capacity = self.capacity;
+        let history = self.history.entry(header.pow_algo()).or_insert_with(VecDeque::new);
+        history.push_back((header.timestamp(), target_difficulty));
+        if history.len() > capacity {
+            history.pop_front();
+        }
     }
 
+    /// Adds an older target difficulty, if the history for the header's algorithm is not yet full
     pub fn add_front(&mut self, header: &BlockHeader, target_difficulty: Difficulty) {
-        self.get_mut(header.pow_algo())
-            .add_front(header.timestamp(), target_difficulty);
+        let capacity = self.capacity;
+        let history = self.history.entry(header.pow_algo()).or_insert_with(VecDeque::new);
+        if history.len() < capacity {
+            history.push_front((header.timestamp(), target_difficulty));
+        }
     }
 
     pub fn is_algo_full(&self, algo: PowAlgorithm) -> bool {
-        self.get(algo).is_full()
+        self.history.get(&algo).map(VecDeque::len).unwrap_or(0) >= self.capacity
     }
 
     pub fn is_full(&self) -> bool {
-        self.sha3.is_full() && self.monero.is_full()
+        self.history.keys().all(|algo| self.is_algo_full(*algo))
+    }
+
+    /// Calculates the target difficulty of a header mined with `algo` at `height`
+    pub fn calculate(&self, algo: PowAlgorithm, height: u64) -> Difficulty {
+        let mut window = self.consensus_rules.new_target_difficulty(algo, height);
+        if let Some(history) = self.history.get(&algo) {
+            for (timestamp, target_difficulty) in history.iter().rev() {
+                if window.is_full() {
+                    break;
+                }
+                window.add_front(*timestamp, *target_difficulty);
+            }
+        }
+        let constants = self.consensus_rules.consensus_constants(height);
+        window.calculate(constants.min_pow_difficulty(algo), constants.max_pow_difficulty(algo))
+    }
+}
+
+impl Display for TargetDifficulties {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), Error> {
+        let mut first = true;
+        for algo in PowAlgorithm::all() {
+            if let Some(history) = self.history.get(algo) {
+                if !first {
+                    fmt.write_str(", ")?;
+                }
+                write!(fmt, "{} {}", history.len(), algo)?;
+                first = false;
+            }
+        }
+        Ok(())
     }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        consensus::{
+            consensus_constants::PowAlgorithmConstants,
+            ConsensusConstantsBuilder,
+            ConsensusManagerBuilder,
+            Network,
+        },
+        proof_of_work::TargetDifficultyWindow,
+    };
+    use std::collections::HashMap;
 
-    pub fn get(&self, algo: PowAlgorithm) -> &TargetDifficultyWindow {
-        use PowAlgorithm::*;
-        match algo {
-            Monero => &self.monero,
-            // TODO: remove
-            Blake => unimplemented!(),
-            Sha3 => &self.sha3,
+    fn algo_constants(min_difficulty: u64) -> PowAlgorithmConstants {
+        PowAlgorithmConstants {
+            max_target_time: 1800,
+            min_difficulty: min_difficulty.into(),
+            max_difficulty: u64::MAX.into(),
+            target_time: 300,
         }
     }
 
-    fn get_mut(&mut self, algo: PowAlgorithm) -> &mut TargetDifficultyWindow {
-        use PowAlgorithm::*;
-        match algo {
-            Monero => &mut self.monero,
-            // TODO: remove
-            Blake => unimplemented!(),
-            Sha3 => &mut self.sha3,
+    /// Sha3 is accepted from genesis and Blake from height 10
+    fn create_consensus_manager() -> ConsensusManager {
+        let mut sha3_only = HashMap::new();
+        sha3_only.insert(PowAlgorithm::Sha3, algo_constants(10));
+        let mut sha3_and_blake = sha3_only.clone();
+        sha3_and_blake.insert(PowAlgorithm::Blake, algo_constants(20));
+        ConsensusManagerBuilder::new(Network::LocalNet)
+            .with_consensus_constants(
+                ConsensusConstantsBuilder::new(Network::LocalNet)
+                    .with_difficulty_block_window(5)
+                    .with_proof_of_work(sha3_only)
+                    .build(),
+            )
+            .with_consensus_constants(
+                ConsensusConstantsBuilder::new(Network::LocalNet)
+                    .with_effective_from_height(10)
+                    .with_difficulty_block_window(5)
+                    .with_proof_of_work(sha3_and_blake)
+                    .build(),
+            )
+            .build()
+    }
+
+    fn create_header(algo: PowAlgorithm, height: u64, timestamp: u64) -> BlockHeader {
+        let mut header = BlockHeader::new(0);
+        header.pow.pow_algo = algo;
+        header.height = height;
+        header.timestamp = timestamp.into();
+        header
+    }
+
+    #[test]
+    fn it_only_tracks_algorithms_accepted_at_the_start_height() {
+        let rules = create_consensus_manager();
+        let targets = TargetDifficulties::new(&rules, 5);
+        assert!(!targets.is_full());
+        assert_eq!(targets.to_string(), "0 Sha3");
+        let targets = TargetDifficulties::new(&rules, 10);
+        assert_eq!(targets.to_string(), "0 Blake, 0 Sha3");
+    }
+
+    #[test]
+    fn it_uses_the_minimum_difficulty_at_the_header_height_without_history() {
+        let rules = create_consensus_manager();
+        let targets = TargetDifficulties::new(&rules, 5);
+        assert_eq!(targets.calculate(PowAlgorithm::Sha3, 6), 10.into());
+        // Blake was not accepted when the history was created, but its target difficulty can still be calculated once
+        // it is activated
+        assert_eq!(targets.calculate(PowAlgorithm::Blake, 10), 20.into());
+    }
+
+    #[test]
+    fn it_calculates_the_same_target_difficulty_as_a_window() {
+        let rules = create_consensus_manager();
+        let mut targets = TargetDifficulties::new(&rules, 5);
+        let mut window = TargetDifficultyWindow::new(5, 300, 1800);
+        for height in 1..=12 {
+            let header = create_header(PowAlgorithm::Sha3, height, height * 250);
+            let target_difficulty = Difficulty::from(1000 + height * 10);
+            targets.add_back(&header, target_difficulty);
+            window.add_back(header.timestamp(), target_difficulty);
         }
+        assert!(targets.is_algo_full(PowAlgorithm::Sha3));
+        assert_eq!(
+            targets.calculate(PowAlgorithm::Sha3, 13),
+            window.calculate(10.into(), u64::MAX.into())
+        );
+
+        // Blake headers added after activation get their own history
+        let header = create_header(PowAlgorithm::Blake, 13, 13 * 250);
+        targets.add_back(&header, 500.into());
+        assert!(!targets.is_algo_full(PowAlgorithm::Blake));
+        assert_eq!(targets.to_string(), "1 Blake, 6 Sha3");
     }
 }
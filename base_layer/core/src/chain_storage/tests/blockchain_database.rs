@@ -322,3 +322,33 @@ mod fetch_block_hashes_from_header_tip {
         assert_eq!(&hashes[5], genesis.hash());
     }
 }
+
+mod verify_integrity {
+    use super::*;
+    use crate::chain_storage::{ChainIntegrityError, MmrTree};
+
+    #[test]
+    fn it_passes_for_the_genesis_block() {
+        let db = setup();
+        let report = db.verify_integrity(10).unwrap();
+        assert!(report.is_consistent());
+        assert_eq!(report.start_height, 0);
+        assert_eq!(report.last_verified_height, Some(0));
+        assert!(report.balance_checked_heights.is_empty());
+    }
+
+    #[test]
+    fn it_reports_the_first_inconsistent_height() {
+        let db = setup();
+        // These blocks are added with mock validators and do not commit to the correct MMR roots
+        add_many_chained_blocks(3, &db);
+        let report = db.verify_integrity(10).unwrap();
+        assert!(!report.is_consistent());
+        assert_eq!(report.tip_height, 3);
+        assert_eq!(report.last_verified_height, Some(0));
+        let inconsistency = report.inconsistency.unwrap();
+        assert_eq!(inconsistency.height, 1);
+        unpack_enum!(ChainIntegrityError::MismatchedMmrRoot(tree) = inconsistency.error);
+        assert_eq!(tree, MmrTree::Kernel);
+    }
+}
@@ -26,7 +26,10 @@ use crate::{
     transactions::tari_amount::{uT, MicroTari, T},
 };
 use chrono::{DateTime, Duration, Utc};
-use std::{collections::HashMap, ops::Add};
+use std::{
+    collections::HashMap,
+    ops::{Add, RangeInclusive},
+};
 use tari_crypto::tari_utilities::epoch_time::EpochTime;
 
 /// This is the inner struct used to control all consensus values.
@@ -38,6 +41,14 @@ pub struct ConsensusConstants {
     coinbase_lock_height: u64,
     /// Current version of the blockchain
     blockchain_version: u16,
+    /// The oldest block header version that is still accepted
+    min_blockchain_version: u16,
+    /// The version of the output features for newly created outputs
+    output_features_version: u8,
+    /// The oldest output features version that is still accepted
+    min_output_features_version: u8,
+    /// The optional validation rules that are enforced from `effective_from_height`
+    validation_rules: ValidationRules,
     /// The Future Time Limit (FTL) of the blockchain in seconds. This is the max allowable timestamp that is excepted.
     /// We use T*N/20 where T = desired chain target time, and N = block_window
     future_time_limit: u64,
@@ -63,6 +74,16 @@ pub struct ConsensusConstants {
     faucet_value: MicroTari,
 }
 
+bitflags! {
+    /// Validation rules that are only enforced once the consensus constants that enable them become effective. Each
+    /// rule is activated by including it in all consensus constants from the activation height onwards.
+    pub struct ValidationRules: u32 {
+        /// The coinbase output of a block must mature no earlier than the coinbase lock height after the block, as is
+        /// already required of orphan blocks.
+        const COINBASE_MATURITY = 0b0000_0001;
+    }
+}
+
 /// This is just a convenience  wrapper to put all the info into a hashmap per diff algo
 #[derive(Clone, Debug)]
 pub struct PowAlgorithmConstants {
@@ -98,6 +119,26 @@ impl ConsensusConstants {
         self.blockchain_version
     }
 
+    /// The block header versions that are accepted.
+    pub fn valid_blockchain_version_range(&self) -> RangeInclusive<u16> {
+        self.min_blockchain_version..=self.blockchain_version
+    }
+
+    /// The output features version used for newly created outputs.
+    pub fn output_features_version(&self) -> u8 {
+        self.output_features_version
+    }
+
+    /// The output features versions that are accepted.
+    pub fn valid_output_features_version_range(&self) -> RangeInclusive<u8> {
+        self.min_output_features_version..=self.output_features_version
+    }
+
+    /// The optional validation rules that are enforced.
+    pub fn validation_rules(&self) -> ValidationRules {
+        self.validation_rules
+    }
+
     /// This returns the FTL(Future Time Limit) for blocks
     /// Any block with a timestamp greater than this is rejected.
     pub fn ftl(&self) -> EpochTime {
@@ -134,6 +175,20 @@ impl ConsensusConstants {
         self.proof_of_work.len() as u64
     }
 
+    /// The PoW algorithms accepted by the Tari chain while these constants are effective
+    pub fn pow_algorithms(&self) -> Vec<PowAlgorithm> {
+        PowAlgorithm::all()
+            .iter()
+            .copied()
+            .filter(|algo| self.accepts_pow_algorithm(*algo))
+            .collect()
+    }
+
+    /// Returns true if blocks mined with the given PoW algorithm are accepted while these constants are effective
+    pub fn accepts_pow_algorithm(&self, pow_algo: PowAlgorithm) -> bool {
+        self.proof_of_work.contains_key(&pow_algo)
+    }
+
     /// The target time used by the difficulty adjustment algorithms, their target time is the target block interval /
     /// algo block percentage
     pub fn get_diff_target_block_interval(&self, pow_algo: PowAlgorithm) -> u64 {
@@ -197,10 +252,21 @@ impl ConsensusConstants {
             max_difficulty: 1.into(),
             target_time: 200,
         });
+        // Blake is only accepted on localnet for now. As difficulties are fixed here, the block split is irrelevant.
+        algos.insert(PowAlgorithm::Blake, PowAlgorithmConstants {
+            max_target_time: 1800,
+            min_difficulty: 1.into(),
+            max_difficulty: 1.into(),
+            target_time: 300,
+        });
         vec![ConsensusConstants {
             effective_from_height: 0,
             coinbase_lock_height: 2,
             blockchain_version: 1,
+            min_blockchain_version: 1,
+            output_features_version: 0,
+            min_output_features_version: 0,
+            validation_rules: ValidationRules::empty(),
             future_time_limit: 540,
             difficulty_block_window,
             max_block_transaction_weight: 19500,
@@ -234,6 +300,10 @@ impl ConsensusConstants {
             effective_from_height: 0,
             coinbase_lock_height: 1,
             blockchain_version: 1,
+            min_blockchain_version: 1,
+            output_features_version: 0,
+            min_output_features_version: 0,
+            validation_rules: ValidationRules::empty(),
             future_time_limit: 540,
             difficulty_block_window,
             max_block_transaction_weight: 19500,
@@ -294,6 +364,10 @@ impl ConsensusConstants {
                 effective_from_height: 0,
                 coinbase_lock_height: 60,
                 blockchain_version: 1,
+                min_blockchain_version: 1,
+                output_features_version: 0,
+                min_output_features_version: 0,
+                validation_rules: ValidationRules::empty(),
                 future_time_limit: 540,
                 difficulty_block_window: 90,
                 max_block_transaction_weight: 19500,
@@ -309,6 +383,10 @@ impl ConsensusConstants {
                 effective_from_height: 1400,
                 coinbase_lock_height: 60,
                 blockchain_version: 1,
+                min_blockchain_version: 1,
+                output_features_version: 0,
+                min_output_features_version: 0,
+                validation_rules: ValidationRules::empty(),
                 future_time_limit: 540,
                 difficulty_block_window: 90,
                 max_block_transaction_weight: 19500,
@@ -343,6 +421,10 @@ impl ConsensusConstants {
             effective_from_height: 0,
             coinbase_lock_height: 1,
             blockchain_version: 1,
+            min_blockchain_version: 1,
+            output_features_version: 0,
+            min_output_features_version: 0,
+            validation_rules: ValidationRules::empty(),
             future_time_limit: 540,
             difficulty_block_window,
             max_block_transaction_weight: 19500,
@@ -382,6 +464,56 @@ impl ConsensusConstantsBuilder {
         self
     }
 
+    pub fn with_effective_from_height(mut self, height: u64) -> Self {
+        self.consensus.effective_from_height = height;
+        self
+    }
+
+    pub fn with_blockchain_version(mut self, version: u16) -> Self {
+        self.consensus.blockchain_version = version;
+        self
+    }
+
+    pub fn with_min_blockchain_version(mut self, version: u16) -> Self {
+        self.consensus.min_blockchain_version = version;
+        self
+    }
+
+    pub fn with_output_features_version(mut self, version: u8) -> Self {
+        self.consensus.output_features_version = version;
+        self
+    }
+
+    pub fn with_min_output_features_version(mut self, version: u8) -> Self {
+        self.consensus.min_output_features_version = version;
+        self
+    }
+
+    pub fn with_validation_rules(mut self, rules: ValidationRules) -> Self {
+        self.consensus.validation_rules = rules;
+        self
+    }
+
+    pub fn with_future_time_limit(mut self, seconds: u64) -> Self {
+        self.consensus.future_time_limit = seconds;
+        self
+    }
+
+    pub fn with_difficulty_block_window(mut self, window: u64) -> Self {
+        self.consensus.difficulty_block_window = window;
+        self
+    }
+
+    pub fn with_max_block_transaction_weight(mut self, weight: u64) -> Self {
+        self.consensus.max_block_transaction_weight = weight;
+        self
+    }
+
+    pub fn with_median_timestamp_count(mut self, count: usize) -> Self {
+        self.consensus.median_timestamp_count = count;
+        self
+    }
+
     pub fn with_consensus_constants(mut self, consensus: ConsensusConstants) -> Self {
         self.consensus = consensus;
         self
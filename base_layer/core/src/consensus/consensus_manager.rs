@@ -42,7 +42,7 @@ use crate::{
     proof_of_work::{DifficultyAdjustmentError, PowAlgorithm, TargetDifficultyWindow},
     transactions::tari_amount::MicroTari,
 };
-use std::{convert::TryFrom, sync::Arc};
+use std::sync::Arc;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -87,7 +87,12 @@ impl ConsensusManager {
             Network::MainNet => get_mainnet_block_hash(),
             Network::Ridcully => get_ridcully_block_hash(),
             Network::Stibbons => get_stibbons_block_hash(),
-            Network::LocalNet => get_ridcully_block_hash(),
+            Network::LocalNet => self
+                .inner
+                .gen_block
+                .as_ref()
+                .map(|block| block.accumulated_data.hash.clone())
+                .unwrap_or_else(get_stibbons_block_hash),
         }
     }
 
@@ -119,17 +124,32 @@ impl ConsensusManager {
         constants
     }
 
+    /// Returns true if the proof of work algorithm is accepted by any consensus constants that are effective at or
+    /// before the given height
+    pub fn has_accepted_pow_algorithm(&self, pow_algo: PowAlgorithm, height: u64) -> bool {
+        self.inner
+            .consensus_constants
+            .iter()
+            .take_while(|c| c.effective_from_height() <= height)
+            .any(|c| c.accepts_pow_algorithm(pow_algo))
+    }
+
+    /// The largest target difficulty block window of all consensus constants
+    pub fn max_difficulty_block_window(&self) -> u64 {
+        self.inner
+            .consensus_constants
+            .iter()
+            .map(ConsensusConstants::get_difficulty_block_window)
+            .max()
+            .unwrap_or(0)
+    }
+
     /// Create a new TargetDifficulty for the given proof of work using constants that are effective from the given
     /// height
     pub(crate) fn new_target_difficulty(&self, pow_algo: PowAlgorithm, height: u64) -> TargetDifficultyWindow {
-        let constants = self.consensus_constants(height);
-        let block_window = constants.get_difficulty_block_window();
-
-        TargetDifficultyWindow::new(
-            usize::try_from(block_window).expect("difficulty block window exceeds usize::MAX"),
-            constants.get_diff_target_block_interval(pow_algo),
-            constants.get_difficulty_max_block_interval(pow_algo),
-        )
+        pow_algo
+            .implementation()
+            .target_difficulty_window(self.consensus_constants(height))
     }
 
     /// Creates a total_coinbase offset containing all fees for the validation from block
@@ -0,0 +1,493 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Custom networks, for running private chains without patching `tari_core`.
+//!
+//! A custom network is described by a consensus file, a TOML document with one `[[constants]]` table per set of
+//! consensus constants, and a genesis block generated to match it (see [generate_genesis_block]). Both are loaded by
+//! the base node on `localnet`, which is the only network that accepts custom consensus rules.
+//!
+//! ```toml
+//! [[constants]]
+//! effective_from_height = 0
+//! coinbase_lock_height = 6
+//! blockchain_version = 1
+//! future_time_limit = 540
+//! difficulty_block_window = 90
+//! max_block_transaction_weight = 19500
+//! median_timestamp_count = 11
+//! emission_initial = 5538846115
+//! emission_decay = [22, 23, 24, 26, 27]
+//! emission_tail = 100
+//! faucet_value = 20000000000000
+//!
+//! [constants.proof_of_work.sha3]
+//! max_target_time = 1800
+//! min_difficulty = 60000000
+//! target_time = 300
+//!
+//! [constants.proof_of_work.monero]
+//! max_target_time = 1200
+//! min_difficulty = 60000
+//! target_time = 200
+//! ```
+//!
+//! All amounts are in µT. `max_difficulty` and `max_randomx_seed_height` may be omitted, in which case they are
+//! unlimited. Blake2b mining is accepted by adding a `[constants.proof_of_work.blake]` table.
+//!
+//! Rule changes are activated by adding a set of constants that is effective from the activation height. Such a set
+//! may raise `blockchain_version` and `output_features_version`, set `min_blockchain_version` and
+//! `min_output_features_version` to stop accepting older versions (both default to the current version), and list
+//! the optional `validation_rules` to enforce, e.g. `validation_rules = ["coinbase_maturity"]`.
+
+use crate::{
+    blocks::{Block, BlockHeader},
+    chain_storage::{BlockHeaderAccumulatedData, ChainBlock},
+    consensus::{
+        consensus_constants::PowAlgorithmConstants,
+        ConsensusConstants,
+        ConsensusConstantsBuilder,
+        Network,
+        ValidationRules,
+    },
+    proof_of_work::PowAlgorithm,
+    transactions::{
+        tari_amount::MicroTari,
+        types::{CryptoFactories, HashDigest, PrivateKey},
+        CoinbaseBuildError,
+        CoinbaseBuilder,
+    },
+};
+use croaring::Bitmap;
+use rand::rngs::OsRng;
+use serde::Deserialize;
+use std::{collections::HashMap, fs, io, path::Path};
+use tari_crypto::{
+    keys::SecretKey,
+    tari_utilities::{epoch_time::EpochTime, hash::Hashable},
+};
+use tari_mmr::{error::MerkleMountainRangeError, MutableMmr};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum CustomNetworkError {
+    #[error("Could not read file: {0}")]
+    IoError(#[from] io::Error),
+    #[error("Could not parse the consensus file: {0}")]
+    TomlError(#[from] toml::de::Error),
+    #[error("Could not parse the genesis block file: {0}")]
+    JsonError(#[from] serde_json::Error),
+    #[error("Invalid consensus constants: {0}")]
+    InvalidConsensusConstants(String),
+    #[error("Could not build the genesis faucet output: {0}")]
+    CoinbaseBuildError(#[from] CoinbaseBuildError),
+    #[error("Could not calculate the genesis block merkle roots: {0}")]
+    MerkleMountainRangeError(#[from] MerkleMountainRangeError),
+    #[error("The genesis block is invalid: {0}")]
+    InvalidGenesisBlock(String),
+}
+
+/// The contents of a consensus file
+#[derive(Debug, Clone, Deserialize)]
+pub struct ConsensusFile {
+    pub constants: Vec<ConsensusConstantsConfig>,
+}
+
+/// A single set of consensus constants, as written in a consensus file
+#[derive(Debug, Clone, Deserialize)]
+pub struct ConsensusConstantsConfig {
+    pub effective_from_height: u64,
+    pub coinbase_lock_height: u64,
+    pub blockchain_version: u16,
+    pub min_blockchain_version: Option<u16>,
+    pub output_features_version: Option<u8>,
+    pub min_output_features_version: Option<u8>,
+    #[serde(default)]
+    pub validation_rules: Vec<String>,
+    pub future_time_limit: u64,
+    pub difficulty_block_window: u64,
+    pub max_block_transaction_weight: u64,
+    pub median_timestamp_count: usize,
+    pub emission_initial: u64,
+    pub emission_decay: Vec<u64>,
+    pub emission_tail: u64,
+    pub max_randomx_seed_height: Option<u64>,
+    pub faucet_value: u64,
+    pub proof_of_work: ProofOfWorkConfig,
+}
+
+/// The block split between the proof of work algorithms, given as the constants for each algorithm that is accepted
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProofOfWorkConfig {
+    pub sha3: Option<PowAlgorithmConfig>,
+    pub blake: Option<PowAlgorithmConfig>,
+    pub monero: Option<PowAlgorithmConfig>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PowAlgorithmConfig {
+    pub max_target_time: u64,
+    pub min_difficulty: u64,
+    pub max_difficulty: Option<u64>,
+    pub target_time: u64,
+}
+
+impl From<PowAlgorithmConfig> for PowAlgorithmConstants {
+    fn from(config: PowAlgorithmConfig) -> Self {
+        Self {
+            max_target_time: config.max_target_time,
+            min_difficulty: config.min_difficulty.into(),
+            max_difficulty: config.max_difficulty.unwrap_or(u64::MAX).into(),
+            target_time: config.target_time,
+        }
+    }
+}
+
+impl ConsensusFile {
+    /// Parse a consensus file from a TOML string
+    pub fn from_toml(contents: &str) -> Result<Self, CustomNetworkError> {
+        Ok(toml::from_str(contents)?)
+    }
+
+    /// Read and parse a consensus file
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, CustomNetworkError> {
+        Self::from_toml(&fs::read_to_string(path)?)
+    }
+
+    /// Convert the file into the consensus constants used by the `ConsensusManager`, checking that they are usable.
+    pub fn into_consensus_constants(self) -> Result<Vec<ConsensusConstants>, CustomNetworkError> {
+        if self.constants.is_empty() {
+            return Err(CustomNetworkError::InvalidConsensusConstants(
+                "At least one set of constants is required".to_string(),
+            ));
+        }
+        if self.constants[0].effective_from_height != 0 {
+            return Err(CustomNetworkError::InvalidConsensusConstants(
+                "The first set of constants must be effective from height 0".to_string(),
+            ));
+        }
+        if self
+            .constants
+            .windows(2)
+            .any(|w| w[0].effective_from_height >= w[1].effective_from_height)
+        {
+            return Err(CustomNetworkError::InvalidConsensusConstants(
+                "Constants must be given in order of increasing effective height".to_string(),
+            ));
+        }
+
+        self.constants
+            .into_iter()
+            .map(ConsensusConstants::try_from_config)
+            .collect()
+    }
+}
+
+impl ConsensusConstants {
+    fn try_from_config(config: ConsensusConstantsConfig) -> Result<Self, CustomNetworkError> {
+        let mut proof_of_work = HashMap::new();
+        if let Some(sha3) = config.proof_of_work.sha3 {
+            proof_of_work.insert(PowAlgorithm::Sha3, sha3.into());
+        }
+        if let Some(blake) = config.proof_of_work.blake {
+            proof_of_work.insert(PowAlgorithm::Blake, blake.into());
+        }
+        if let Some(monero) = config.proof_of_work.monero {
+            proof_of_work.insert(PowAlgorithm::Monero, monero.into());
+        }
+        if proof_of_work.is_empty() {
+            return Err(CustomNetworkError::InvalidConsensusConstants(format!(
+                "No proof of work algorithm is accepted from height {}",
+                config.effective_from_height
+            )));
+        }
+        if config.emission_decay.is_empty() {
+            return Err(CustomNetworkError::InvalidConsensusConstants(
+                "The emission decay cannot be empty".to_string(),
+            ));
+        }
+        if config.median_timestamp_count == 0 || config.difficulty_block_window == 0 {
+            return Err(CustomNetworkError::InvalidConsensusConstants(
+                "The median timestamp count and difficulty block window must be greater than zero".to_string(),
+            ));
+        }
+
+        let min_blockchain_version = config.min_blockchain_version.unwrap_or(config.blockchain_version);
+        let output_features_version = config.output_features_version.unwrap_or(0);
+        let min_output_features_version = config.min_output_features_version.unwrap_or(output_features_version);
+        if min_blockchain_version > config.blockchain_version || min_output_features_version > output_features_version {
+            return Err(CustomNetworkError::InvalidConsensusConstants(format!(
+                "The minimum versions from height {} cannot exceed the current versions",
+                config.effective_from_height
+            )));
+        }
+        let mut validation_rules = ValidationRules::empty();
+        for name in &config.validation_rules {
+            validation_rules |= parse_validation_rule(name)?;
+        }
+
+        // The emission schedule borrows the decay for the lifetime of the program. Consensus files are only loaded
+        // once at startup, so leaking it here is bounded.
+        let emission_decay: &'static [u64] = Box::leak(config.emission_decay.into_boxed_slice());
+
+        Ok(ConsensusConstantsBuilder::new(Network::LocalNet)
+            .with_effective_from_height(config.effective_from_height)
+            .with_coinbase_lockheight(config.coinbase_lock_height)
+            .with_blockchain_version(config.blockchain_version)
+            .with_min_blockchain_version(min_blockchain_version)
+            .with_output_features_version(output_features_version)
+            .with_min_output_features_version(min_output_features_version)
+            .with_validation_rules(validation_rules)
+            .with_future_time_limit(config.future_time_limit)
+            .with_difficulty_block_window(config.difficulty_block_window)
+            .with_max_block_transaction_weight(config.max_block_transaction_weight)
+            .with_median_timestamp_count(config.median_timestamp_count)
+            .with_emission_amounts(
+                config.emission_initial.into(),
+                emission_decay,
+                config.emission_tail.into(),
+            )
+            .with_max_randomx_seed_height(config.max_randomx_seed_height.unwrap_or(u64::MAX))
+            .with_faucet_value(config.faucet_value.into())
+            .with_proof_of_work(proof_of_work)
+            .build())
+    }
+}
+
+fn parse_validation_rule(name: &str) -> Result<ValidationRules, CustomNetworkError> {
+    match name {
+        "coinbase_maturity" => Ok(ValidationRules::COINBASE_MATURITY),
+        _ => Err(CustomNetworkError::InvalidConsensusConstants(format!(
+            "Unknown validation rule '{}'",
+            name
+        ))),
+    }
+}
+
+/// Generate the genesis block of a custom network. The block holds a single faucet output containing the network's
+/// faucet value, which can be spent with `faucet_key` once the coinbase lock height has passed.
+pub fn generate_genesis_block(
+    factories: &CryptoFactories,
+    constants: &ConsensusConstants,
+    faucet_key: PrivateKey,
+    timestamp: EpochTime,
+) -> Result<ChainBlock, CustomNetworkError>
+{
+    let (faucet, _) = CoinbaseBuilder::new(factories.clone())
+        .with_block_height(0)
+        .with_fees(MicroTari::from(0))
+        .with_spend_key(faucet_key)
+        .with_nonce(PrivateKey::random(&mut OsRng))
+        .build_with_reward(constants, constants.faucet_value())?;
+
+    let mut header = BlockHeader::new(constants.blockchain_version());
+    header.timestamp = timestamp;
+    header.output_mmr_size = faucet.body.outputs().len() as u64;
+    header.kernel_mmr_size = faucet.body.kernels().len() as u64;
+    let mut block = header
+        .into_builder()
+        .with_coinbase_utxo(faucet.body.outputs()[0].clone(), faucet.body.kernels()[0].clone())
+        .build();
+
+    let kernel_hashes = block.body.kernels().iter().map(|k| k.hash()).collect::<Vec<_>>();
+    let output_hashes = block.body.outputs().iter().map(|o| o.hash()).collect::<Vec<_>>();
+    let proof_hashes = block.body.outputs().iter().map(|o| o.proof().hash()).collect::<Vec<_>>();
+    block.header.kernel_mr = MutableMmr::<HashDigest, _>::new(kernel_hashes, Bitmap::create())?.get_merkle_root()?;
+    block.header.output_mr = MutableMmr::<HashDigest, _>::new(output_hashes, Bitmap::create())?.get_merkle_root()?;
+    block.header.range_proof_mr =
+        MutableMmr::<HashDigest, _>::new(proof_hashes, Bitmap::create())?.get_merkle_root()?;
+
+    Ok(genesis_chain_block(block))
+}
+
+/// Read a genesis block written by the genesis generator, checking that its accumulated data matches the block
+pub fn load_genesis_block<P: AsRef<Path>>(path: P) -> Result<ChainBlock, CustomNetworkError> {
+    let chain_block: ChainBlock = serde_json::from_str(&fs::read_to_string(path)?)?;
+    if chain_block.block.header.height != 0 {
+        return Err(CustomNetworkError::InvalidGenesisBlock(format!(
+            "Expected a block at height 0 but got height {}",
+            chain_block.block.header.height
+        )));
+    }
+    if chain_block.accumulated_data.hash != chain_block.block.hash() {
+        return Err(CustomNetworkError::InvalidGenesisBlock(
+            "The block hash does not match the block".to_string(),
+        ));
+    }
+    Ok(chain_block)
+}
+
+fn genesis_chain_block(block: Block) -> ChainBlock {
+    let accumulated_data = BlockHeaderAccumulatedData {
+        hash: block.hash(),
+        total_kernel_offset: block.header.total_kernel_offset.clone(),
+        achieved_difficulty: 1.into(),
+        total_accumulated_difficulty: 1,
+        accumulated_monero_difficulty: 1.into(),
+        accumulated_blake_difficulty: 1.into(),
+        target_difficulty: 1.into(),
+    };
+    ChainBlock {
+        block,
+        accumulated_data,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const CONSENSUS_FILE: &str = r#"
+        [[constants]]
+        effective_from_height = 0
+        coinbase_lock_height = 6
+        blockchain_version = 1
+        future_time_limit = 540
+        difficulty_block_window = 90
+        max_block_transaction_weight = 19500
+        median_timestamp_count = 11
+        emission_initial = 5538846115
+        emission_decay = [22, 23, 24, 26, 27]
+        emission_tail = 100
+        faucet_value = 20000000000000
+
+        [constants.proof_of_work.sha3]
+        max_target_time = 1800
+        min_difficulty = 60000000
+        target_time = 300
+
+        [[constants]]
+        effective_from_height = 1000
+        coinbase_lock_height = 60
+        blockchain_version = 2
+        min_blockchain_version = 1
+        output_features_version = 1
+        validation_rules = ["coinbase_maturity"]
+        future_time_limit = 540
+        difficulty_block_window = 90
+        max_block_transaction_weight = 25000
+        median_timestamp_count = 11
+        emission_initial = 5538846115
+        emission_decay = [22, 23, 24, 26, 27]
+        emission_tail = 100
+        max_randomx_seed_height = 2880
+        faucet_value = 20000000000000
+
+        [constants.proof_of_work.sha3]
+        max_target_time = 1800
+        min_difficulty = 60000000
+        target_time = 300
+
+        [constants.proof_of_work.blake]
+        max_target_time = 3600
+        min_difficulty = 60000000
+        target_time = 600
+
+        [constants.proof_of_work.monero]
+        max_target_time = 1200
+        min_difficulty = 60000
+        max_difficulty = 1000000000
+        target_time = 200
+    "#;
+
+    #[test]
+    fn it_loads_consensus_constants() {
+        let constants = ConsensusFile::from_toml(CONSENSUS_FILE)
+            .unwrap()
+            .into_consensus_constants()
+            .unwrap();
+        assert_eq!(constants.len(), 2);
+        assert_eq!(constants[0].coinbase_lock_height(), 6);
+        assert_eq!(constants[0].get_pow_algo_count(), 1);
+        assert_eq!(constants[0].max_pow_difficulty(PowAlgorithm::Sha3), u64::MAX.into());
+        assert_eq!(constants[0].max_randomx_seed_height(), u64::MAX);
+        assert_eq!(constants[0].emission_amounts().1, &[22, 23, 24, 26, 27]);
+        assert_eq!(constants[0].valid_blockchain_version_range(), 1..=1);
+        assert_eq!(constants[0].valid_output_features_version_range(), 0..=0);
+        assert!(constants[0].validation_rules().is_empty());
+        assert_eq!(constants[1].effective_from_height(), 1000);
+        assert_eq!(constants[1].get_max_block_transaction_weight(), 25000);
+        assert_eq!(constants[1].get_pow_algo_count(), 3);
+        assert!(!constants[0].accepts_pow_algorithm(PowAlgorithm::Blake));
+        assert_eq!(constants[1].get_diff_target_block_interval(PowAlgorithm::Blake), 600);
+        assert_eq!(
+            constants[1].max_pow_difficulty(PowAlgorithm::Monero),
+            1_000_000_000.into()
+        );
+        assert_eq!(constants[1].max_randomx_seed_height(), 2880);
+        assert_eq!(constants[1].valid_blockchain_version_range(), 1..=2);
+        assert_eq!(constants[1].valid_output_features_version_range(), 1..=1);
+        assert_eq!(constants[1].validation_rules(), ValidationRules::COINBASE_MATURITY);
+    }
+
+    #[test]
+    fn it_rejects_invalid_constants() {
+        let file = ConsensusFile::from_toml("constants = []").unwrap();
+        assert!(file.into_consensus_constants().is_err());
+
+        let mut file = ConsensusFile::from_toml(CONSENSUS_FILE).unwrap();
+        file.constants.swap(0, 1);
+        assert!(file.into_consensus_constants().is_err());
+
+        let mut file = ConsensusFile::from_toml(CONSENSUS_FILE).unwrap();
+        file.constants[0].proof_of_work.sha3 = None;
+        assert!(file.into_consensus_constants().is_err());
+
+        let mut file = ConsensusFile::from_toml(CONSENSUS_FILE).unwrap();
+        file.constants[1].min_blockchain_version = Some(3);
+        assert!(file.into_consensus_constants().is_err());
+
+        let mut file = ConsensusFile::from_toml(CONSENSUS_FILE).unwrap();
+        file.constants[1].validation_rules.push("no_such_rule".to_string());
+        assert!(file.into_consensus_constants().is_err());
+    }
+
+    #[test]
+    fn it_generates_a_loadable_genesis_block() {
+        let factories = CryptoFactories::default();
+        let constants = ConsensusFile::from_toml(CONSENSUS_FILE)
+            .unwrap()
+            .into_consensus_constants()
+            .unwrap();
+        let genesis = generate_genesis_block(
+            &factories,
+            &constants[0],
+            PrivateKey::random(&mut OsRng),
+            1_611_835_200.into(),
+        )
+        .unwrap();
+        assert_eq!(genesis.block.header.height, 0);
+        assert_eq!(genesis.block.body.outputs().len(), 1);
+        assert_eq!(genesis.block.body.outputs()[0].features.maturity, 6);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("genesis.json");
+        fs::write(&path, serde_json::to_string(&genesis).unwrap()).unwrap();
+        let loaded = load_genesis_block(&path).unwrap();
+        assert_eq!(loaded, genesis);
+
+        let mut tampered = genesis;
+        tampered.block.header.nonce += 1;
+        fs::write(&path, serde_json::to_string(&tampered).unwrap()).unwrap();
+        assert!(load_genesis_block(&path).is_err());
+    }
+}
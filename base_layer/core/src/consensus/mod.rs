@@ -26,6 +26,8 @@ pub(crate) mod chain_strength_comparer;
 pub mod consensus_constants;
 #[cfg(feature = "base_node")]
 mod consensus_manager;
+#[cfg(feature = "base_node")]
+pub mod custom_network;
 #[cfg(any(feature = "base_node", feature = "transactions"))]
 pub mod emission;
 #[cfg(any(feature = "base_node", feature = "transactions"))]
@@ -39,8 +41,10 @@ pub const WEIGHT_PER_OUTPUT: u64 = 13;
 pub const KERNEL_WEIGHT: u64 = 3; // Constant weight per transaction; covers kernel and part of header.
 
 #[cfg(any(feature = "base_node", feature = "transactions"))]
-pub use consensus_constants::{ConsensusConstants, ConsensusConstantsBuilder};
+pub use consensus_constants::{ConsensusConstants, ConsensusConstantsBuilder, ValidationRules};
 #[cfg(feature = "base_node")]
 pub use consensus_manager::{ConsensusManager, ConsensusManagerBuilder, ConsensusManagerError};
+#[cfg(feature = "base_node")]
+pub use custom_network::{ConsensusFile, CustomNetworkError};
 #[cfg(any(feature = "base_node", feature = "transactions"))]
 pub use network::Network;
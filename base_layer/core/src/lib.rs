@@ -36,6 +36,12 @@
 
 #[macro_use]
 extern crate bitflags;
+#[cfg(feature = "base_node")]
+#[macro_use]
+extern crate diesel;
+#[cfg(feature = "base_node")]
+#[macro_use]
+extern crate diesel_migrations;
 
 #[cfg(feature = "base_node")]
 pub mod blocks;
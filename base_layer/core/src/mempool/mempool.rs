@@ -21,7 +21,7 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use crate::{
-    blocks::Block,
+    blocks::{Block, ShortKernelId},
     mempool::{
         error::MempoolError,
         mempool_storage::MempoolStorage,
@@ -34,6 +34,7 @@ use crate::{
     validation::MempoolTransactionValidation,
 };
 use std::sync::{Arc, RwLock};
+use tari_common_types::types::BlockHash;
 
 /// The Mempool consists of an Unconfirmed Transaction Pool, Pending Pool, Orphan Pool and Reorg Pool and is responsible
 /// for managing and maintaining all unconfirmed transactions have not yet been included in a block, and transactions
@@ -100,6 +101,20 @@ impl Mempool {
             .retrieve(total_weight)
     }
 
+    /// Returns the unconfirmed transactions containing the kernels with the given short IDs in the block with the given
+    /// hash, and the short IDs that were not found.
+    pub fn retrieve_by_short_kernel_ids(
+        &self,
+        block_hash: BlockHash,
+        short_ids: Vec<ShortKernelId>,
+    ) -> Result<(Vec<Arc<Transaction>>, Vec<ShortKernelId>), MempoolError>
+    {
+        self.pool_storage
+            .read()
+            .map_err(|e| MempoolError::BackendError(e.to_string()))?
+            .retrieve_by_short_kernel_ids(block_hash, short_ids)
+    }
+
     /// Check if the specified transaction is stored in the Mempool.
     pub fn has_tx_with_excess_sig(&self, excess_sig: Signature) -> Result<TxStorageResponse, MempoolError> {
         self.pool_storage
@@ -21,7 +21,7 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use crate::{
-    blocks::Block,
+    blocks::{Block, ShortKernelId},
     mempool::{
         error::MempoolError,
         reorg_pool::ReorgPool,
@@ -36,6 +36,7 @@ use crate::{
 };
 use log::*;
 use std::sync::Arc;
+use tari_common_types::types::BlockHash;
 use tari_crypto::tari_utilities::{hex::Hex, Hashable};
 
 pub const LOG_TARGET: &str = "c::mp::mempool";
@@ -89,6 +90,13 @@ impl MempoolStorage {
                 warn!(target: LOG_TARGET, "Validation failed due to maturity error");
                 Ok(TxStorageResponse::NotStoredTimeLocked)
             },
+            Err(ValidationError::TransactionError(e)) => {
+                warn!(
+                    target: LOG_TARGET,
+                    "Validation failed due to invalid transaction: {}", e
+                );
+                Ok(TxStorageResponse::NotStoredInvalid)
+            },
             Err(e) => {
                 warn!(target: LOG_TARGET, "Validation failed due to error:{}", e);
                 Ok(TxStorageResponse::NotStored)
@@ -207,6 +215,19 @@ impl MempoolStorage {
         }
     }
 
+    /// Returns the unconfirmed transactions containing the kernels with the given short IDs in the block with the given
+    /// hash, and the short IDs that were not found.
+    pub fn retrieve_by_short_kernel_ids(
+        &self,
+        block_hash: BlockHash,
+        short_ids: Vec<ShortKernelId>,
+    ) -> Result<(Vec<Arc<Transaction>>, Vec<ShortKernelId>), MempoolError>
+    {
+        Ok(self
+            .unconfirmed_pool
+            .retrieve_by_short_kernel_ids(&block_hash, &short_ids))
+    }
+
     // Returns the total number of transactions in the Mempool.
     fn len(&self) -> Result<usize, MempoolError> {
         Ok(self.unconfirmed_pool.len() + self.reorg_pool.len()?)
@@ -132,6 +132,8 @@ pub enum TxStorageResponse {
     NotStoredOrphan,
     NotStoredTimeLocked,
     NotStoredAlreadySpent,
+    /// The transaction failed the context-free consistency checks (signatures, range proofs or balance)
+    NotStoredInvalid,
     NotStored,
 }
 
@@ -149,6 +151,7 @@ impl Display for TxStorageResponse {
             TxStorageResponse::NotStoredOrphan => "Not stored orphan transaction",
             TxStorageResponse::NotStoredTimeLocked => "Not stored time locked transaction",
             TxStorageResponse::NotStoredAlreadySpent => "Not stored output already spent",
+            TxStorageResponse::NotStoredInvalid => "Not stored invalid transaction",
             TxStorageResponse::NotStored => "Not stored",
         };
         fmt.write_str(&storage)
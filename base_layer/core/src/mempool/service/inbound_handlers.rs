@@ -94,7 +94,7 @@ impl MempoolInboundHandlers {
         &mut self,
         tx: Transaction,
         source_peer: Option<NodeId>,
-    ) -> Result<(), MempoolServiceError>
+    ) -> Result<TxStorageResponse, MempoolServiceError>
     {
         debug!(
             target: LOG_TARGET,
@@ -106,7 +106,7 @@ impl MempoolInboundHandlers {
                 .unwrap_or_else(|| "local services".to_string())
         );
         let exclude_peers = source_peer.into_iter().collect();
-        self.submit_transaction(tx, exclude_peers).await.map(|_| ())
+        self.submit_transaction(tx, exclude_peers).await
     }
 
     // Submits a transaction to the mempool and propagate valid transactions.
@@ -143,6 +143,7 @@ impl MempoolInboundHandlers {
                     TxStorageResponse::NotStoredOrphan => false,
                     TxStorageResponse::NotStoredTimeLocked => false,
                     TxStorageResponse::NotStoredAlreadySpent => false,
+                    TxStorageResponse::NotStoredInvalid => false,
                 };
                 if propagate {
                     debug!(
@@ -40,6 +40,7 @@ use crate::{
 use futures::{channel::mpsc, future, Future, Stream, StreamExt};
 use log::*;
 use std::{convert::TryFrom, sync::Arc};
+use tari_comms::connectivity::ConnectivityRequester;
 use tari_comms_dht::Dht;
 use tari_p2p::{
     comms_connector::{PeerMessage, SubscriptionFactory},
@@ -172,6 +173,7 @@ impl ServiceInitializer for MempoolServiceInitializer {
             let outbound_message_service = handles.expect_handle::<Dht>().outbound_requester();
             let state_machine = handles.expect_handle::<StateMachineHandle>();
             let base_node = handles.expect_handle::<LocalNodeCommsInterface>();
+            let connectivity = handles.expect_handle::<ConnectivityRequester>();
 
             let streams = MempoolStreams {
                 outbound_request_stream,
@@ -183,8 +185,14 @@ impl ServiceInitializer for MempoolServiceInitializer {
                 block_event_stream: base_node.get_block_event_stream(),
                 request_receiver,
             };
-            let service =
-                MempoolService::new(outbound_message_service, inbound_handlers, config, state_machine).start(streams);
+            let service = MempoolService::new(
+                outbound_message_service,
+                inbound_handlers,
+                config,
+                state_machine,
+                connectivity,
+            )
+            .start(streams);
             futures::pin_mut!(service);
             future::select(service, handles.get_shutdown_signal()).await;
             info!(target: LOG_TARGET, "Mempool Service shutdown");
@@ -34,6 +34,7 @@ use crate::{
             MempoolResponse,
         },
         MempoolServiceConfig,
+        TxStorageResponse,
     },
     proto,
     transactions::transaction::Transaction,
@@ -47,9 +48,12 @@ use futures::{
 };
 use log::*;
 use rand::rngs::OsRng;
-use std::{convert::TryInto, sync::Arc, time::Duration};
+use std::{convert::TryInto, time::Duration};
 use tari_common_types::waiting_requests::{generate_request_key, RequestKey, WaitingRequests};
-use tari_comms::peer_manager::NodeId;
+use tari_comms::{
+    connectivity::ConnectivityRequester,
+    peer_manager::{NodeId, ReputationEvent, SuccessfulExchangeDebouncer},
+};
 use tari_comms_dht::{
     domain_message::OutboundDomainMessage,
     envelope::NodeDestination,
@@ -61,6 +65,9 @@ use tari_service_framework::{reply_channel, reply_channel::RequestContext};
 use tokio::task;
 
 const LOG_TARGET: &str = "c::mempool::service::service";
+/// Every reputation event is written to the peer database, so a peer that relays many valid transactions is credited
+/// with a successful exchange at most once in this interval.
+const SUCCESSFUL_EXCHANGE_REPORT_INTERVAL: Duration = Duration::from_secs(60);
 
 /// A convenience struct to hold all the Mempool service streams
 pub struct MempoolStreams<SOutReq, SInReq, SInRes, STxIn, SLocalReq> {
@@ -84,6 +91,8 @@ pub struct MempoolService {
     timeout_receiver_stream: Option<mpsc::Receiver<RequestKey>>,
     config: MempoolServiceConfig,
     state_machine: StateMachineHandle,
+    connectivity: ConnectivityRequester,
+    exchange_debouncer: SuccessfulExchangeDebouncer,
 }
 
 impl MempoolService {
@@ -92,6 +101,7 @@ impl MempoolService {
         inbound_handlers: MempoolInboundHandlers,
         config: MempoolServiceConfig,
         state_machine: StateMachineHandle,
+        connectivity: ConnectivityRequester,
     ) -> Self
     {
         let (timeout_sender, timeout_receiver) = mpsc::channel(100);
@@ -103,6 +113,8 @@ impl MempoolService {
             timeout_receiver_stream: Some(timeout_receiver),
             config,
             state_machine,
+            connectivity,
+            exchange_debouncer: SuccessfulExchangeDebouncer::new(SUCCESSFUL_EXCHANGE_REPORT_INTERVAL),
         }
     }
 
@@ -277,8 +289,10 @@ impl MempoolService {
             return;
         }
         let inbound_handlers = self.inbound_handlers.clone();
+        let connectivity = self.connectivity.clone();
+        let exchange_debouncer = self.exchange_debouncer.clone();
         task::spawn(async move {
-            let result = handle_incoming_tx(inbound_handlers, tx_msg).await;
+            let result = handle_incoming_tx(inbound_handlers, connectivity, exchange_debouncer, tx_msg).await;
             if let Err(e) = result {
                 error!(
                     target: LOG_TARGET,
@@ -443,6 +457,8 @@ async fn handle_outbound_request(
 
 async fn handle_incoming_tx(
     mut inbound_handlers: MempoolInboundHandlers,
+    mut connectivity: ConnectivityRequester,
+    exchange_debouncer: SuccessfulExchangeDebouncer,
     domain_transaction_msg: DomainMessage<Transaction>,
 ) -> Result<(), MempoolServiceError>
 {
@@ -459,10 +475,26 @@ async fn handle_incoming_tx(
         inner,
         source_peer.public_key
     );
-    inbound_handlers
-        .handle_transaction(inner, Some(source_peer.node_id))
+    let tx_storage = inbound_handlers
+        .handle_transaction(inner, Some(source_peer.node_id.clone()))
         .await?;
 
+    let reputation_event = match tx_storage {
+        TxStorageResponse::UnconfirmedPool if exchange_debouncer.should_report(&source_peer.node_id) => {
+            Some(ReputationEvent::SuccessfulExchange)
+        },
+        // Only a transaction that fails the context-free checks proves that the peer relayed something invalid
+        TxStorageResponse::NotStoredInvalid => Some(ReputationEvent::InvalidTransaction),
+        // Any other transaction that was not stored could have been propagated in good faith, e.g. it may have been
+        // valid on the peer's view of the chain
+        _ => None,
+    };
+    if let Some(event) = reputation_event {
+        if let Err(err) = connectivity.report_peer(source_peer.node_id, event).await {
+            warn!(target: LOG_TARGET, "Failed to report peer: {}", err);
+        }
+    }
+
     Ok(())
 }
 
@@ -32,7 +32,7 @@ use crate::{
 use log::*;
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, HashMap, HashSet},
     convert::TryFrom,
     sync::Arc,
 };
@@ -137,6 +137,42 @@ impl UnconfirmedPool {
         self.txs_by_signature.contains_key(excess_sig)
     }
 
+    /// Returns the transactions containing any of the kernels with the given short IDs in the block with the given
+    /// hash, along with the short IDs that could not be found in the UnconfirmedPool. Transactions are returned at most
+    /// once, even if more than one of their kernels are requested.
+    pub fn retrieve_by_short_kernel_ids(
+        &self,
+        block_hash: &[u8],
+        short_ids: &[ShortKernelId],
+    ) -> (Vec<Arc<Transaction>>, Vec<ShortKernelId>)
+    {
+        let mut remaining = short_ids.iter().collect::<HashSet<_>>();
+        let mut found = Vec::new();
+
+        // Short IDs are salted with the block hash, so the IDs of every transaction in the pool have to be calculated
+        for ptx in self.txs_by_signature.values() {
+            if remaining.is_empty() {
+                break;
+            }
+            let ids = ptx
+                .transaction
+                .body
+                .kernels()
+                .iter()
+                .map(|k| short_kernel_id(block_hash, &k.excess_sig))
+                .collect::<Vec<_>>();
+            if ids.iter().any(|id| remaining.contains(id)) {
+                for id in &ids {
+                    remaining.remove(id);
+                }
+                found.push(ptx.transaction.clone());
+            }
+        }
+
+        let not_found = short_ids.iter().filter(|id| remaining.contains(id)).cloned().collect();
+        (found, not_found)
+    }
+
     /// Returns a set of the highest priority unconfirmed transactions, that can be included in a block
     pub fn highest_priority_txs(&self, total_weight: u64) -> Result<Vec<Arc<Transaction>>, UnconfirmedPoolError> {
         let mut selected_txs: Vec<Arc<Transaction>> = Vec::new();
@@ -352,6 +388,32 @@ mod test {
         assert!(unconfirmed_pool.check_status());
     }
 
+    #[test]
+    fn test_retrieve_by_short_kernel_ids() {
+        let tx1 = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(50), inputs: 2, outputs: 1).0);
+        let tx2 = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(20), inputs: 3, outputs: 1).0);
+        let tx3 = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(100), inputs: 1, outputs: 1).0);
+
+        let mut unconfirmed_pool = UnconfirmedPool::new(UnconfirmedPoolConfig::default());
+        unconfirmed_pool.insert_txs(vec![tx1.clone(), tx2.clone()]).unwrap();
+
+        let block_hash = vec![1u8; 32];
+        let short_ids = [&tx1, &tx3, &tx2]
+            .iter()
+            .map(|tx| short_kernel_id(&block_hash, &tx.body.kernels()[0].excess_sig))
+            .collect::<Vec<_>>();
+        let (found, not_found) = unconfirmed_pool.retrieve_by_short_kernel_ids(&block_hash, &short_ids);
+        assert_eq!(found.len(), 2);
+        assert!(found.contains(&tx1));
+        assert!(found.contains(&tx2));
+        assert_eq!(not_found, vec![short_ids[1]]);
+
+        // The same kernels have different short IDs in another block
+        let (found, not_found) = unconfirmed_pool.retrieve_by_short_kernel_ids(&[2u8; 32], &short_ids);
+        assert!(found.is_empty());
+        assert_eq!(not_found, short_ids);
+    }
+
     #[test]
     fn test_double_spend_inputs() {
         let (tx1, _, _) = tx!(MicroTari(5_000), fee: MicroTari(50), inputs: 1, outputs: 1);
@@ -0,0 +1,138 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::{
+    blocks::BlockHeader,
+    chain_storage::BlockchainBackend,
+    consensus::ConsensusConstants,
+    proof_of_work::{
+        blake_pow::BlakePow,
+        difficulty::util::big_endian_difficulty,
+        monero_rx::MoneroPow,
+        randomx_factory::RandomXFactory,
+        sha3_pow::Sha3Pow,
+        Difficulty,
+        PowAlgorithm,
+        PowError,
+        TargetDifficultyWindow,
+    },
+};
+use std::convert::TryFrom;
+
+/// The chain state a proof of work algorithm may need to validate the proof of work data of a header
+pub trait PowChainData {
+    /// Returns the height at which the given Monero RandomX seed was first seen on the chain, or 0 if it has not been
+    /// seen yet
+    fn fetch_monero_seed_first_seen_height(&self, seed: &str) -> Result<u64, PowError>;
+}
+
+impl<B: BlockchainBackend> PowChainData for B {
+    fn fetch_monero_seed_first_seen_height(&self, seed: &str) -> Result<u64, PowError> {
+        BlockchainBackend::fetch_monero_seed_first_seen_height(self, seed)
+            .map_err(|e| PowError::FatalStorageError(e.to_string()))
+    }
+}
+
+/// The behaviour every proof of work algorithm accepted on the Tari chain has to provide. Each algorithm lives in its
+/// own module and is registered in [PowAlgorithm::implementation].
+pub trait ProofOfWorkAlgorithm: Send + Sync {
+    /// The algorithm this implementation is registered for
+    fn algorithm(&self) -> PowAlgorithm;
+
+    /// Returns true if the algorithm is merge mined with another chain (using the merge mining proxy) instead of being
+    /// mined by the Tari mining node. Merge mined and natively mined difficulties are accumulated separately.
+    fn is_merge_mined(&self) -> bool {
+        false
+    }
+
+    /// Checks that the supplemental proof of work data of the header is valid for this algorithm. By default, no
+    /// proof of work data is allowed.
+    fn check_pow_data(
+        &self,
+        header: &BlockHeader,
+        _constants: &ConsensusConstants,
+        _chain: &dyn PowChainData,
+    ) -> Result<(), PowError>
+    {
+        if header.pow.pow_data.is_empty() {
+            Ok(())
+        } else {
+            Err(PowError::UnexpectedPowData(self.algorithm()))
+        }
+    }
+
+    /// Calculates the proof of work hash for the header
+    fn hash(&self, header: &BlockHeader, randomx_factory: &RandomXFactory) -> Result<Vec<u8>, PowError>;
+
+    /// Calculates the difficulty achieved by the header. By default the proof of work hash is interpreted as a big
+    /// endian number.
+    fn achieved_difficulty(
+        &self,
+        header: &BlockHeader,
+        randomx_factory: &RandomXFactory,
+    ) -> Result<Difficulty, PowError>
+    {
+        let hash = self.hash(header, randomx_factory)?;
+        Ok(big_endian_difficulty(&hash))
+    }
+
+    /// Creates an empty target difficulty window for this algorithm using the given consensus constants
+    fn target_difficulty_window(&self, constants: &ConsensusConstants) -> TargetDifficultyWindow {
+        let algo = self.algorithm();
+        TargetDifficultyWindow::new(
+            usize::try_from(constants.get_difficulty_block_window())
+                .expect("difficulty block window exceeds usize::MAX"),
+            constants.get_diff_target_block_interval(algo),
+            constants.get_difficulty_max_block_interval(algo),
+        )
+    }
+}
+
+impl PowAlgorithm {
+    /// Returns the implementation of this proof of work algorithm
+    pub fn implementation(self) -> &'static dyn ProofOfWorkAlgorithm {
+        match self {
+            PowAlgorithm::Monero => &MoneroPow,
+            PowAlgorithm::Blake => &BlakePow,
+            PowAlgorithm::Sha3 => &Sha3Pow,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn implementations_are_registered() {
+        for algo in PowAlgorithm::all() {
+            assert_eq!(algo.implementation().algorithm(), *algo);
+        }
+    }
+
+    #[test]
+    fn merge_mined() {
+        assert!(PowAlgorithm::Monero.implementation().is_merge_mined());
+        assert!(!PowAlgorithm::Sha3.implementation().is_merge_mined());
+        assert!(!PowAlgorithm::Blake.implementation().is_merge_mined());
+    }
+}
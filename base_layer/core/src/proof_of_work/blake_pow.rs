@@ -0,0 +1,130 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::{
+    blocks::BlockHeader,
+    proof_of_work::{
+        difficulty::util::big_endian_difficulty,
+        randomx_factory::RandomXFactory,
+        Difficulty,
+        PowAlgorithm,
+        PowError,
+        ProofOfWorkAlgorithm,
+    },
+    transactions::types::HashDigest,
+};
+use digest::Digest;
+use tari_crypto::tari_utilities::ByteArray;
+
+/// A Blake2b proof of work, mined by the Tari mining node alongside Sha3.
+///
+/// The proof of work difficulty is given by `H256(H256(header))` where H256 is the 256-bit Blake2b digest. The header
+/// fields are hashed in the same order as for Sha3.
+pub fn blake_difficulty(header: &BlockHeader) -> Difficulty {
+    big_endian_difficulty(&blake_pow_hash(header))
+}
+
+pub fn blake_hash(header: &BlockHeader) -> Vec<u8> {
+    HashDigest::new()
+        .chain(header.version.to_le_bytes())
+        .chain(header.height.to_le_bytes())
+        .chain(header.prev_hash.as_bytes())
+        .chain(header.timestamp.as_u64().to_le_bytes())
+        .chain(header.output_mr.as_bytes())
+        .chain(header.range_proof_mr.as_bytes())
+        .chain(header.kernel_mr.as_bytes())
+        .chain(header.total_kernel_offset.as_bytes())
+        .chain(header.nonce.to_le_bytes())
+        .chain(header.pow.to_bytes())
+        .result()
+        .to_vec()
+}
+
+fn blake_pow_hash(header: &BlockHeader) -> Vec<u8> {
+    HashDigest::digest(&blake_hash(header)).to_vec()
+}
+
+/// The Blake2b proof of work algorithm, mined by the Tari mining node
+pub struct BlakePow;
+
+impl ProofOfWorkAlgorithm for BlakePow {
+    fn algorithm(&self) -> PowAlgorithm {
+        PowAlgorithm::Blake
+    }
+
+    fn hash(&self, header: &BlockHeader, _randomx_factory: &RandomXFactory) -> Result<Vec<u8>, PowError> {
+        Ok(blake_pow_hash(header))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        consensus::ConsensusConstants,
+        proof_of_work::{sha3_test::get_header, PowChainData},
+    };
+
+    struct NoChainData;
+
+    impl PowChainData for NoChainData {
+        fn fetch_monero_seed_first_seen_height(&self, _seed: &str) -> Result<u64, PowError> {
+            Ok(0)
+        }
+    }
+
+    #[test]
+    fn blake_difficulty_matches_implementation() {
+        let mut header = get_header();
+        header.pow.pow_algo = PowAlgorithm::Blake;
+        let factory = RandomXFactory::default();
+        for nonce in 0..100 {
+            header.nonce = nonce;
+            assert_eq!(
+                BlakePow.achieved_difficulty(&header, &factory).unwrap(),
+                blake_difficulty(&header)
+            );
+        }
+    }
+
+    #[test]
+    fn blake_hash_differs_from_sha3() {
+        let mut header = get_header();
+        header.pow.pow_algo = PowAlgorithm::Blake;
+        header.nonce = 1;
+        assert_ne!(blake_hash(&header), crate::proof_of_work::sha3_pow::sha3_hash(&header));
+        assert_eq!(blake_pow_hash(&header).len(), 32);
+    }
+
+    #[test]
+    fn pow_data_must_be_empty() {
+        let mut header = get_header();
+        header.pow.pow_algo = PowAlgorithm::Blake;
+        let constants = ConsensusConstants::localnet().remove(0);
+        assert!(BlakePow.check_pow_data(&header, &constants, &NoChainData).is_ok());
+        header.pow.pow_data = vec![1];
+        assert!(matches!(
+            BlakePow.check_pow_data(&header, &constants, &NoChainData),
+            Err(PowError::UnexpectedPowData(PowAlgorithm::Blake))
+        ));
+    }
+}
@@ -32,8 +32,19 @@ pub enum PowError {
     #[error("Invalid target difficulty (expected: {expected}, got: {got})")]
     InvalidTargetDifficulty { expected: Difficulty, got: Difficulty },
     #[cfg(feature = "base_node")]
+    #[error("Proof of work data must be empty for {0} blocks")]
+    UnexpectedPowData(super::PowAlgorithm),
+    #[cfg(feature = "base_node")]
+    #[error("Proof of work algorithm {algo} is not accepted at height {height}")]
+    AlgorithmNotAccepted { algo: super::PowAlgorithm, height: u64 },
+    #[cfg(feature = "base_node")]
     #[error("Invalid merge mining data or operation: {0}")]
     MergeMineError(#[from] super::monero_rx::MergeMineError),
+    #[cfg(feature = "base_node")]
+    #[error("The RandomX seed was first seen {seed_age} blocks ago, which exceeds the maximum of {max_seed_height}")]
+    OldSeedHash { seed_age: u64, max_seed_height: u64 },
+    #[error("Fatal storage error while checking proof of work: {0}")]
+    FatalStorageError(String),
 }
 
 #[derive(Debug, Error, Clone, PartialEq)]
@@ -30,10 +30,20 @@ mod error;
 #[cfg(any(feature = "base_node", feature = "transactions"))]
 pub use error::{DifficultyAdjustmentError, PowError};
 
+#[cfg(feature = "base_node")]
+mod algorithm;
+#[cfg(feature = "base_node")]
+pub use algorithm::{PowChainData, ProofOfWorkAlgorithm};
+
+#[cfg(feature = "base_node")]
+mod blake_pow;
+#[cfg(feature = "base_node")]
+pub use blake_pow::{blake_difficulty, BlakePow};
+
 #[cfg(feature = "base_node")]
 pub mod monero_rx;
 #[cfg(feature = "base_node")]
-pub use monero_rx::monero_difficulty;
+pub use monero_rx::{monero_difficulty, monero_hash, MoneroPow};
 
 #[cfg(feature = "base_node")]
 #[allow(clippy::module_inception)]
@@ -48,10 +58,10 @@ pub use proof_of_work_algorithm::PowAlgorithm;
 
 #[cfg(feature = "base_node")]
 mod sha3_pow;
-#[cfg(feature = "base_node")]
-pub use sha3_pow::sha3_difficulty;
 #[cfg(all(test, feature = "base_node"))]
 pub use sha3_pow::test as sha3_test;
+#[cfg(feature = "base_node")]
+pub use sha3_pow::{sha3_difficulty, Sha3Pow};
 
 #[cfg(feature = "base_node")]
 mod target_difficulty;
@@ -22,10 +22,15 @@
 
 use crate::{
     blocks::BlockHeader,
+    consensus::ConsensusConstants,
     proof_of_work::{
         difficulty::util::little_endian_difficulty,
         randomx_factory::{RandomXFactory, RandomXVMInstance},
         Difficulty,
+        PowAlgorithm,
+        PowChainData,
+        PowError,
+        ProofOfWorkAlgorithm,
     },
     tari_utilities::ByteArray,
 };
@@ -197,6 +202,19 @@ impl MoneroData {
 /// Internal function to calculate the difficulty attained for the given block Deserialized the Monero header from the
 /// provided header
 pub fn monero_difficulty(header: &BlockHeader, randomx_factory: &RandomXFactory) -> Result<Difficulty, MergeMineError> {
+    monero_difficulty_with_hash(header, randomx_factory).map(|(diff, _)| diff)
+}
+
+/// Calculates the RandomX hash of the Monero block header contained in the proof of work data of the given header
+pub fn monero_hash(header: &BlockHeader, randomx_factory: &RandomXFactory) -> Result<Vec<u8>, MergeMineError> {
+    monero_difficulty_with_hash(header, randomx_factory).map(|(_, hash)| hash)
+}
+
+fn monero_difficulty_with_hash(
+    header: &BlockHeader,
+    randomx_factory: &RandomXFactory,
+) -> Result<(Difficulty, Vec<u8>), MergeMineError>
+{
     let monero = MoneroData::from_header(header)?;
     verify_header(&header, &monero)?;
 
@@ -213,7 +231,59 @@ pub fn monero_difficulty(header: &BlockHeader, randomx_factory: &RandomXFactory)
     let input = from_hex(&input)?;
     let key_bytes = from_hex(&key)?;
     let vm = randomx_factory.create(&key_bytes)?;
-    get_random_x_difficulty(&input, &vm).map(|(diff, _)| diff)
+    get_random_x_difficulty(&input, &vm)
+}
+
+/// The Monero RandomX proof of work algorithm, merge mined with Monero using the merge mining proxy
+pub struct MoneroPow;
+
+impl ProofOfWorkAlgorithm for MoneroPow {
+    fn algorithm(&self) -> PowAlgorithm {
+        PowAlgorithm::Monero
+    }
+
+    fn is_merge_mined(&self) -> bool {
+        true
+    }
+
+    /// The merge mining data must be valid and the RandomX seed may not have been first seen on the chain more than
+    /// the maximum seed height ago
+    fn check_pow_data(
+        &self,
+        header: &BlockHeader,
+        constants: &ConsensusConstants,
+        chain: &dyn PowChainData,
+    ) -> Result<(), PowError>
+    {
+        let monero_data = MoneroData::from_header(header)?;
+        let seed_height = chain.fetch_monero_seed_first_seen_height(&monero_data.key)?;
+        if seed_height == 0 {
+            return Ok(());
+        }
+        let seed_age = header.height.saturating_sub(seed_height);
+        let max_seed_height = constants.max_randomx_seed_height();
+        if seed_age > max_seed_height {
+            return Err(PowError::OldSeedHash {
+                seed_age,
+                max_seed_height,
+            });
+        }
+        Ok(())
+    }
+
+    fn hash(&self, header: &BlockHeader, randomx_factory: &RandomXFactory) -> Result<Vec<u8>, PowError> {
+        Ok(monero_hash(header, randomx_factory)?)
+    }
+
+    /// RandomX hashes are interpreted as little endian numbers
+    fn achieved_difficulty(
+        &self,
+        header: &BlockHeader,
+        randomx_factory: &RandomXFactory,
+    ) -> Result<Difficulty, PowError>
+    {
+        Ok(monero_difficulty(header, randomx_factory)?)
+    }
 }
 
 fn get_random_x_difficulty(input: &[u8], vm: &RandomXVMInstance) -> Result<(Difficulty, Vec<u8>), MergeMineError> {
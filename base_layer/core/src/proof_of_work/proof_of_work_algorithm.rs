@@ -31,10 +31,20 @@ pub enum PowAlgorithm {
 }
 
 impl PowAlgorithm {
+    /// All the proof of work algorithms known to this node. An algorithm is only accepted on the chain at heights
+    /// where the consensus constants include it.
+    pub fn all() -> &'static [PowAlgorithm] {
+        &[PowAlgorithm::Monero, PowAlgorithm::Blake, PowAlgorithm::Sha3]
+    }
+
     pub fn is_monero(&self) -> bool {
         matches!(self, Self::Monero)
     }
 
+    pub fn is_blake(&self) -> bool {
+        matches!(self, Self::Blake)
+    }
+
     pub fn is_sha3(&self) -> bool {
         matches!(self, Self::Sha3)
     }
@@ -50,8 +60,22 @@ impl TryFrom<u64> for PowAlgorithm {
     fn try_from(v: u64) -> Result<Self, Self::Error> {
         match v {
             0 => Ok(PowAlgorithm::Monero),
+            1 => Ok(PowAlgorithm::Blake),
             2 => Ok(PowAlgorithm::Sha3),
             _ => Err("Invalid PoWAlgorithm".into()),
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn u64_round_trip() {
+        for algo in PowAlgorithm::all() {
+            assert_eq!(PowAlgorithm::try_from(algo.as_u64()).unwrap(), *algo);
+        }
+        assert!(PowAlgorithm::try_from(3).is_err());
+    }
+}
@@ -22,7 +22,14 @@
 
 use crate::{
     blocks::BlockHeader,
-    proof_of_work::{difficulty::util::big_endian_difficulty, Difficulty},
+    proof_of_work::{
+        difficulty::util::big_endian_difficulty,
+        randomx_factory::RandomXFactory,
+        Difficulty,
+        PowAlgorithm,
+        PowError,
+        ProofOfWorkAlgorithm,
+    },
 };
 use sha3::{Digest, Sha3_256};
 use tari_crypto::tari_utilities::ByteArray;
@@ -59,6 +66,19 @@ fn sha3_difficulty_with_hash(header: &BlockHeader) -> (Difficulty, Vec<u8>) {
     (difficulty, hash.to_vec())
 }
 
+/// The Sha3 proof of work algorithm, mined by the Tari mining node
+pub struct Sha3Pow;
+
+impl ProofOfWorkAlgorithm for Sha3Pow {
+    fn algorithm(&self) -> PowAlgorithm {
+        PowAlgorithm::Sha3
+    }
+
+    fn hash(&self, header: &BlockHeader, _randomx_factory: &RandomXFactory) -> Result<Vec<u8>, PowError> {
+        Ok(sha3_difficulty_with_hash(header).1)
+    }
+}
+
 #[cfg(test)]
 pub mod test {
     use crate::{
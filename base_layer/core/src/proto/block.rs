@@ -25,6 +25,7 @@ use crate::{
     blocks::{Block, BlockHeader, NewBlock, NewBlockHeaderTemplate, NewBlockTemplate},
     chain_storage::{BlockHeaderAccumulatedData, HistoricalBlock},
     proof_of_work::{PowAlgorithm, ProofOfWork},
+    proto::utils::short_kernel_ids_from_bytes,
     transactions::types::BlindingFactor,
 };
 use prost_types::Timestamp;
@@ -321,7 +322,29 @@ impl TryFrom<proto::NewBlock> for NewBlock {
             ));
         }
 
-        Ok(Self { block_hash })
+        let header = new_block.header.map(TryInto::try_into).transpose()?;
+
+        let coinbase_kernels = new_block
+            .coinbase_kernels
+            .into_iter()
+            .map(TryInto::try_into)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let coinbase_outputs = new_block
+            .coinbase_outputs
+            .into_iter()
+            .map(TryInto::try_into)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let kernel_short_ids = short_kernel_ids_from_bytes(&new_block.kernel_short_ids)?;
+
+        Ok(Self {
+            block_hash,
+            header,
+            coinbase_kernels,
+            coinbase_outputs,
+            kernel_short_ids,
+        })
     }
 }
 
@@ -329,6 +352,10 @@ impl From<NewBlock> for proto::NewBlock {
     fn from(new_block: NewBlock) -> Self {
         Self {
             block_hash: new_block.block_hash,
+            header: new_block.header.map(Into::into),
+            coinbase_kernels: new_block.coinbase_kernels.into_iter().map(Into::into).collect(),
+            coinbase_outputs: new_block.coinbase_outputs.into_iter().map(Into::into).collect(),
+            kernel_short_ids: new_block.kernel_short_ids.iter().map(|id| id.to_vec()).collect(),
         }
     }
 }
@@ -159,6 +159,7 @@ impl TryFrom<proto::types::OutputFeatures> for OutputFeatures {
 
     fn try_from(features: proto::types::OutputFeatures) -> Result<Self, Self::Error> {
         Ok(Self {
+            version: u8::try_from(features.version).map_err(|_| "Invalid output features version".to_string())?,
             flags: OutputFlags::from_bits(features.flags as u8)
                 .ok_or_else(|| "Invalid or unrecognised output flags".to_string())?,
             maturity: features.maturity,
@@ -171,6 +172,7 @@ impl From<OutputFeatures> for proto::types::OutputFeatures {
         Self {
             flags: features.flags.bits() as u32,
             maturity: features.maturity,
+            version: features.version as u32,
         }
     }
 }
@@ -20,7 +20,8 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::convert::TryInto;
+use crate::blocks::{ShortKernelId, SHORT_KERNEL_ID_LEN};
+use std::convert::{TryFrom, TryInto};
 
 /// Tries to convert a series of `T`s to `U`s, returning an error at the first failure
 pub fn try_convert_all<T, U, I>(into_iter: I) -> Result<Vec<U>, T::Error>
@@ -35,3 +36,19 @@ where
     }
     Ok(result)
 }
+
+/// Converts the bytes of short kernel IDs to `ShortKernelId`s, returning an error if any of them has the wrong length
+pub fn short_kernel_ids_from_bytes(short_ids: &[Vec<u8>]) -> Result<Vec<ShortKernelId>, String> {
+    short_ids
+        .iter()
+        .map(|id| {
+            ShortKernelId::try_from(id.as_slice()).map_err(|_| {
+                format!(
+                    "Short kernel ID has an incorrect length. (len={}, expected={})",
+                    id.len(),
+                    SHORT_KERNEL_ID_LEN
+                )
+            })
+        })
+        .collect()
+}
@@ -24,6 +24,7 @@ use crate::{
     blocks::{genesis_block::get_ridcully_genesis_block, Block, BlockHeader},
     chain_storage::{
         create_lmdb_database,
+        create_sqlite_database,
         BlockAccumulatedData,
         BlockHeaderAccumulatedData,
         BlockchainBackend,
@@ -35,7 +36,6 @@ use crate::{
         DbTransaction,
         DbValue,
         HorizonData,
-        LMDBDatabase,
         MmrTree,
         PrunedOutput,
         Validators,
@@ -58,6 +58,7 @@ use crate::{
 };
 use croaring::Bitmap;
 use std::{
+    env,
     fs,
     ops::Deref,
     path::{Path, PathBuf},
@@ -121,9 +122,21 @@ pub fn create_test_db() -> TempDatabase {
     TempDatabase::new()
 }
 
+/// The environment variable used to select the backend opened by [open_test_backend] and [create_test_db]. Set it to
+/// `sqlite` to run the tests against the SQLite backend; any other value (or none) selects LMDB.
+pub const TEST_CHAIN_BACKEND_ENV_VAR: &str = "TARI_TEST_CHAIN_BACKEND";
+
+/// Opens (or creates) the blockchain backend selected by [TEST_CHAIN_BACKEND_ENV_VAR] at the given path.
+pub fn open_test_backend<P: AsRef<Path>>(path: P) -> Box<dyn BlockchainBackend> {
+    match env::var(TEST_CHAIN_BACKEND_ENV_VAR) {
+        Ok(backend) if backend.eq_ignore_ascii_case("sqlite") => Box::new(create_sqlite_database(path).unwrap()),
+        _ => Box::new(create_lmdb_database(path, LMDBConfig::default()).unwrap()),
+    }
+}
+
 pub struct TempDatabase {
     path: PathBuf,
-    db: LMDBDatabase,
+    db: Box<dyn BlockchainBackend>,
 }
 
 impl TempDatabase {
@@ -131,17 +144,17 @@ impl TempDatabase {
         let temp_path = create_temporary_data_path();
 
         Self {
-            db: create_lmdb_database(&temp_path, LMDBConfig::default()).unwrap(),
+            db: open_test_backend(&temp_path),
             path: temp_path,
         }
     }
 }
 
 impl Deref for TempDatabase {
-    type Target = LMDBDatabase;
+    type Target = dyn BlockchainBackend;
 
     fn deref(&self) -> &Self::Target {
-        &self.db
+        &*self.db
     }
 }
 
@@ -31,10 +31,11 @@ use crate::{
             OutputFeatures,
             Transaction,
             TransactionBuilder,
+            TransactionOutput,
             UnblindedOutput,
         },
         transaction_protocol::{build_challenge, RewindData, TransactionMetadata},
-        types::{BlindingFactor, CryptoFactories, PrivateKey, PublicKey, Signature},
+        types::{BlindingFactor, Commitment, CryptoFactories, MessageHash, PrivateKey, PublicKey, Signature},
     },
 };
 use tari_crypto::{commitment::HomomorphicCommitmentFactory, keys::PublicKey as PK};
@@ -137,7 +138,6 @@ impl CoinbaseBuilder {
     ///
     /// After `build_with_reward` is called, the struct is destroyed and the private keys stored are dropped and the
     /// memory zeroed out (by virtue of the zero_on_drop crate).
-    #[allow(clippy::erasing_op)] // This is for 0 * uT
     pub fn build_with_reward(
         self,
         constants: &ConsensusConstants,
@@ -151,11 +151,9 @@ impl CoinbaseBuilder {
         let nonce = self.private_nonce.ok_or_else(|| CoinbaseBuildError::MissingNonce)?;
         let public_nonce = PublicKey::from_secret_key(&nonce);
         let key = self.spend_key.ok_or_else(|| CoinbaseBuildError::MissingSpendKey)?;
-        let output_features = OutputFeatures::create_coinbase(height + constants.coinbase_lock_height());
+        let output_features = Self::output_features(constants, height);
         let excess = self.factories.commitment.commit_value(&key, 0);
-        let kernel_features = KernelFeatures::create_coinbase();
-        let metadata = TransactionMetadata::default();
-        let challenge = build_challenge(&public_nonce, &metadata);
+        let challenge = Self::kernel_challenge(&public_nonce);
         let sig = Signature::sign(key.clone(), nonce, &challenge)
             .map_err(|_| CoinbaseBuildError::BuildError("Challenge could not be represented as a scalar".into()))?;
         let unblinded_output = UnblindedOutput::new(total_reward, key, Some(output_features));
@@ -168,12 +166,73 @@ impl CoinbaseBuilder {
                 .as_transaction_output(&self.factories)
                 .map_err(|e| CoinbaseBuildError::BuildError(e.to_string()))?
         };
+        let tx = Self::build_transaction(&self.factories, output, total_reward, &excess, &sig)?;
+        Ok((tx, unblinded_output))
+    }
+
+    /// The value and output features of the coinbase output for the given block reward. A caller whose spend key is
+    /// held by an external signer has the signer build an output with these, and passes it to
+    /// `build_with_signed_output`.
+    pub fn output_value_and_features(
+        &self,
+        constants: &ConsensusConstants,
+        block_reward: MicroTari,
+    ) -> Result<(MicroTari, OutputFeatures), CoinbaseBuildError>
+    {
+        let height = self
+            .block_height
+            .ok_or_else(|| CoinbaseBuildError::MissingBlockHeight)?;
+        let total_reward = block_reward + self.fees.ok_or_else(|| CoinbaseBuildError::MissingFees)?;
+        Ok((total_reward, Self::output_features(constants, height)))
+    }
+
+    /// The challenge that the spend key of the coinbase output signs, with `public_nonce`, for the coinbase kernel
+    pub fn kernel_challenge(public_nonce: &PublicKey) -> MessageHash {
+        build_challenge(public_nonce, &TransactionMetadata::default())
+    }
+
+    /// Construct a Coinbase Transaction from an output and kernel signature that were produced by an external signer.
+    /// The output must have the value and features returned by `output_value_and_features`, `public_spend_key` is the
+    /// public key of its spend key and `signature` is the spend key's signature on `kernel_challenge`.
+    pub fn build_with_signed_output(
+        self,
+        constants: &ConsensusConstants,
+        block_reward: MicroTari,
+        output: TransactionOutput,
+        public_spend_key: &PublicKey,
+        signature: Signature,
+    ) -> Result<Transaction, CoinbaseBuildError>
+    {
+        let (total_reward, output_features) = self.output_value_and_features(constants, block_reward)?;
+        if output.features != output_features {
+            return Err(CoinbaseBuildError::InvalidTransaction);
+        }
+        let excess = Commitment::from_public_key(public_spend_key);
+        Self::build_transaction(&self.factories, output, total_reward, &excess, &signature)
+    }
+
+    fn output_features(constants: &ConsensusConstants, height: u64) -> OutputFeatures {
+        OutputFeatures {
+            version: constants.output_features_version(),
+            ..OutputFeatures::create_coinbase(height + constants.coinbase_lock_height())
+        }
+    }
+
+    #[allow(clippy::erasing_op)] // This is for 0 * uT
+    fn build_transaction(
+        factories: &CryptoFactories,
+        output: TransactionOutput,
+        total_reward: MicroTari,
+        excess: &Commitment,
+        signature: &Signature,
+    ) -> Result<Transaction, CoinbaseBuildError>
+    {
         let kernel = KernelBuilder::new()
             .with_fee(0 * uT)
-            .with_features(kernel_features)
+            .with_features(KernelFeatures::create_coinbase())
             .with_lock_height(0)
-            .with_excess(&excess)
-            .with_signature(&sig)
+            .with_excess(excess)
+            .with_signature(signature)
             .build()
             .map_err(|e| CoinbaseBuildError::BuildError(e.to_string()))?;
 
@@ -183,10 +242,9 @@ impl CoinbaseBuilder {
             .add_offset(BlindingFactor::default())
             .with_reward(total_reward)
             .with_kernel(kernel);
-        let tx = builder
-            .build(&self.factories)
-            .map_err(|e| CoinbaseBuildError::BuildError(e.to_string()))?;
-        Ok((tx, unblinded_output))
+        builder
+            .build(factories)
+            .map_err(|e| CoinbaseBuildError::BuildError(e.to_string()))
     }
 }
 
@@ -200,12 +258,15 @@ mod test {
             tari_amount::uT,
             transaction::{KernelFeatures, OutputFeatures, OutputFlags, TransactionError, UnblindedOutput},
             transaction_protocol::RewindData,
-            types::{BlindingFactor, CryptoFactories, PrivateKey},
+            types::{BlindingFactor, CryptoFactories, PrivateKey, PublicKey, Signature},
             CoinbaseBuilder,
         },
     };
     use rand::rngs::OsRng;
-    use tari_crypto::{commitment::HomomorphicCommitmentFactory, keys::SecretKey as SecretKeyTrait};
+    use tari_crypto::{
+        commitment::HomomorphicCommitmentFactory,
+        keys::{PublicKey as PublicKeyTrait, SecretKey as SecretKeyTrait},
+    };
 
     fn get_builder() -> (CoinbaseBuilder, ConsensusManager, CryptoFactories) {
         let network = Network::LocalNet;
@@ -317,6 +378,70 @@ mod test {
         assert_eq!(rewind_result.blinding_factor, p.spend_key);
     }
 
+    #[test]
+    fn valid_coinbase_with_signed_output() {
+        // The spend key and nonce are held by an external signer, which builds the output and signs the kernel
+        let p = TestParams::new();
+        let (builder, rules, factories) = get_builder();
+        let builder = builder.with_block_height(42).with_fees(145 * uT);
+        let block_reward = rules.emission_schedule().block_reward(42);
+        let (value, features) = builder
+            .output_value_and_features(rules.consensus_constants(42), block_reward)
+            .unwrap();
+        assert_eq!(value, block_reward + 145 * uT);
+        let output = UnblindedOutput::new(value, p.spend_key.clone(), Some(features))
+            .as_transaction_output(&factories)
+            .unwrap();
+        let challenge = CoinbaseBuilder::kernel_challenge(&PublicKey::from_secret_key(&p.nonce));
+        let signature = Signature::sign(p.spend_key.clone(), p.nonce.clone(), &challenge).unwrap();
+        let tx = builder
+            .build_with_signed_output(
+                rules.consensus_constants(42),
+                block_reward,
+                output,
+                &PublicKey::from_secret_key(&p.spend_key),
+                signature,
+            )
+            .unwrap();
+        assert_eq!(
+            tx.body.check_coinbase_output(
+                value,
+                rules.consensus_constants(0).coinbase_lock_height(),
+                &factories,
+                42
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn signed_coinbase_output_with_wrong_features() {
+        let p = TestParams::new();
+        let (builder, rules, factories) = get_builder();
+        let builder = builder.with_block_height(42).with_fees(145 * uT);
+        let block_reward = rules.emission_schedule().block_reward(42);
+        let (value, _) = builder
+            .output_value_and_features(rules.consensus_constants(42), block_reward)
+            .unwrap();
+        let output = UnblindedOutput::new(value, p.spend_key.clone(), None)
+            .as_transaction_output(&factories)
+            .unwrap();
+        let challenge = CoinbaseBuilder::kernel_challenge(&PublicKey::from_secret_key(&p.nonce));
+        let signature = Signature::sign(p.spend_key.clone(), p.nonce.clone(), &challenge).unwrap();
+        assert_eq!(
+            builder
+                .build_with_signed_output(
+                    rules.consensus_constants(42),
+                    block_reward,
+                    output,
+                    &PublicKey::from_secret_key(&p.spend_key),
+                    signature,
+                )
+                .unwrap_err(),
+            CoinbaseBuildError::InvalidTransaction
+        );
+    }
+
     #[test]
     fn invalid_coinbase_maturity() {
         let p = TestParams::new();
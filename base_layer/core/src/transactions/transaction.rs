@@ -41,7 +41,15 @@ use crate::transactions::{
     },
 };
 use digest::Input;
-use serde::{Deserialize, Serialize};
+use serde::{
+    de,
+    de::{SeqAccess, Visitor},
+    ser::SerializeTuple,
+    Deserialize,
+    Deserializer,
+    Serialize,
+    Serializer,
+};
 use std::{
     cmp::{max, min, Ordering},
     fmt,
@@ -87,8 +95,16 @@ impl KernelFeatures {
 }
 
 /// Options for UTXO's
-#[derive(Debug, Clone, Hash, PartialEq, Deserialize, Serialize, Eq)]
+///
+/// In binary (non human-readable) encodings, version 0 features are serialized as `(flags, maturity)`, which is the
+/// layout used before the version was introduced, so that outputs stored in existing databases still decode and the
+/// hashes of existing outputs are unchanged. Later versions set `OUTPUT_FEATURES_VERSIONED_MARKER` in the flags byte
+/// and are serialized as `(flags | marker, version, maturity)`.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub struct OutputFeatures {
+    /// The version of the output features. The versions that are accepted at a given height are set by the consensus
+    /// constants.
+    pub version: u8,
     /// Flags are the feature flags that differentiate between outputs, eg Coinbase all of which has different rules
     pub flags: OutputFlags,
     /// the maturity of the specific UTXO. This is the min lock height at which an UTXO can be spend. Coinbase UTXO
@@ -105,6 +121,7 @@ impl OutputFeatures {
 
     pub fn create_coinbase(maturity_height: u64) -> OutputFeatures {
         OutputFeatures {
+            version: 0,
             flags: OutputFlags::COINBASE_OUTPUT,
             maturity: maturity_height,
         }
@@ -122,6 +139,7 @@ impl OutputFeatures {
 impl Default for OutputFeatures {
     fn default() -> Self {
         OutputFeatures {
+            version: 0,
             flags: OutputFlags::empty(),
             maturity: 0,
         }
@@ -144,12 +162,97 @@ impl Display for OutputFeatures {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "OutputFeatures: Flags = {:?}, Maturity = {}",
-            self.flags, self.maturity
+            "OutputFeatures: Version = {}, Flags = {:?}, Maturity = {}",
+            self.version, self.flags, self.maturity
         )
     }
 }
 
+/// Set in the serialized flags byte of versioned (version > 0) output features. `OutputFlags` must never define this
+/// bit.
+const OUTPUT_FEATURES_VERSIONED_MARKER: u8 = 0b1000_0000;
+
+/// The human-readable representation of `OutputFeatures`
+#[derive(Deserialize, Serialize)]
+struct OutputFeaturesFields {
+    #[serde(default)]
+    version: u8,
+    flags: OutputFlags,
+    maturity: u64,
+}
+
+impl Serialize for OutputFeatures {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: Serializer {
+        if serializer.is_human_readable() {
+            return OutputFeaturesFields {
+                version: self.version,
+                flags: self.flags,
+                maturity: self.maturity,
+            }
+            .serialize(serializer);
+        }
+
+        if self.version == 0 {
+            let mut tuple = serializer.serialize_tuple(2)?;
+            tuple.serialize_element(&self.flags.bits())?;
+            tuple.serialize_element(&self.maturity)?;
+            tuple.end()
+        } else {
+            let mut tuple = serializer.serialize_tuple(3)?;
+            tuple.serialize_element(&(self.flags.bits() | OUTPUT_FEATURES_VERSIONED_MARKER))?;
+            tuple.serialize_element(&self.version)?;
+            tuple.serialize_element(&self.maturity)?;
+            tuple.end()
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for OutputFeatures {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: Deserializer<'de> {
+        if deserializer.is_human_readable() {
+            let fields = OutputFeaturesFields::deserialize(deserializer)?;
+            return Ok(OutputFeatures {
+                version: fields.version,
+                flags: fields.flags,
+                maturity: fields.maturity,
+            });
+        }
+
+        struct OutputFeaturesVisitor;
+
+        impl<'de> Visitor<'de> for OutputFeaturesVisitor {
+            type Value = OutputFeatures;
+
+            fn expecting(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+                formatter.write_str("output features")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where A: SeqAccess<'de> {
+                let flags: u8 = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(0, &self))?;
+                let version = if flags & OUTPUT_FEATURES_VERSIONED_MARKER == 0 {
+                    0
+                } else {
+                    seq.next_element()?.ok_or_else(|| de::Error::invalid_length(1, &self))?
+                };
+                let maturity = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(2, &self))?;
+                let flags = OutputFlags::from_bits(flags & !OUTPUT_FEATURES_VERSIONED_MARKER)
+                    .ok_or_else(|| de::Error::custom(format!("Invalid output flags: {:#010b}", flags)))?;
+                Ok(OutputFeatures {
+                    version,
+                    flags,
+                    maturity,
+                })
+            }
+        }
+
+        // The visitor stops after two elements for version 0 features
+        deserializer.deserialize_tuple(3, OutputFeaturesVisitor)
+    }
+}
+
 bitflags! {
     #[derive(Deserialize, Serialize)]
     pub struct OutputFlags: u8 {
@@ -937,6 +1040,70 @@ mod test {
         assert_eq!(features.flags, OutputFlags::empty());
     }
 
+    #[test]
+    fn output_features_bytes() {
+        // Version 0 features keep their original serialization, so that existing output hashes are unchanged
+        let features = OutputFeatures::create_coinbase(60);
+        assert_eq!(features.to_bytes(), vec![1, 60, 0, 0, 0, 0, 0, 0, 0]);
+
+        let features = OutputFeatures {
+            version: 1,
+            ..OutputFeatures::create_coinbase(60)
+        };
+        assert_eq!(features.to_bytes(), vec![0b1000_0001, 1, 60, 0, 0, 0, 0, 0, 0, 0]);
+        let decoded: OutputFeatures = bincode::deserialize(&features.to_bytes()).unwrap();
+        assert_eq!(decoded, features);
+
+        let json = serde_json::to_string(&features).unwrap();
+        assert_eq!(serde_json::from_str::<OutputFeatures>(&json).unwrap(), features);
+        let legacy_json = r#"{"flags":{"bits":1},"maturity":60}"#;
+        assert_eq!(
+            serde_json::from_str::<OutputFeatures>(legacy_json).unwrap(),
+            OutputFeatures::create_coinbase(60)
+        );
+    }
+
+    #[test]
+    fn decode_pre_version_output() {
+        // The layout of outputs stored before output features were versioned
+        #[derive(Serialize)]
+        struct LegacyOutputFeatures {
+            flags: OutputFlags,
+            maturity: u64,
+        }
+        #[derive(Serialize)]
+        struct LegacyTransactionOutput {
+            features: LegacyOutputFeatures,
+            commitment: Commitment,
+            proof: RangeProof,
+        }
+
+        let factories = CryptoFactories::default();
+        let output = UnblindedOutput::new(
+            MicroTari::from(1234),
+            BlindingFactor::random(&mut OsRng),
+            Some(OutputFeatures::create_coinbase(42)),
+        )
+        .as_transaction_output(&factories)
+        .unwrap();
+        let legacy = LegacyTransactionOutput {
+            features: LegacyOutputFeatures {
+                flags: OutputFlags::COINBASE_OUTPUT,
+                maturity: 42,
+            },
+            commitment: output.commitment.clone(),
+            proof: output.proof.clone(),
+        };
+        let legacy_bytes = bincode::serialize(&legacy).unwrap();
+
+        let decoded: TransactionOutput = bincode::deserialize(&legacy_bytes).unwrap();
+        assert_eq!(decoded, output);
+        assert_eq!(decoded.features.version, 0);
+        assert_eq!(decoded.hash(), output.hash());
+        // Re-encoding version 0 features reproduces the stored bytes
+        assert_eq!(bincode::serialize(&decoded).unwrap(), legacy_bytes);
+    }
+
     #[test]
     fn range_proof_verification() {
         let factories = CryptoFactories::new(32);
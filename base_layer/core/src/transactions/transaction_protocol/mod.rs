@@ -80,6 +80,8 @@
 //!   end
 //! </div>
 
+pub mod multisig;
+pub mod one_sided;
 pub mod proto;
 pub mod recipient;
 pub mod sender;
@@ -0,0 +1,727 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! n-of-n jointly owned outputs and the multi-party signing protocol used to spend them.
+//!
+//! A joint output is an ordinary Mimblewimble output whose spending key `k` is split into additive shares
+//! `k = k_1 + ... + k_n`, one per co-owner. The output can only be spent if every co-owner contributes a partial
+//! signature with its share.
+//!
+//! Bulletproofs cannot be produced from shares of a blinding factor, so the output is created by a _dealer_ (the
+//! wallet funding it): it picks `k`, builds the output and its range proof, splits `k` into shares with
+//! [deal_key_shares] and hands every co-owner a [JointOutputShare] before forgetting `k`. Co-owners have to trust the
+//! dealer to have discarded `k`; each can check with [JointOutputShare::verify] that the public shares sum to the
+//! commitment and that its own share matches its public share.
+//!
+//! Spending is coordinated by any one of the co-owners. The coordinator publishes a [JointSpendProposal] that sweeps
+//! the joint output (less the fee) into an output it owns, after which every party runs a [MultiSigSession]:
+//!
+//! 1. every party broadcasts a hash commitment to its public nonce ([MultiSigMessage::NonceCommitment]),
+//! 1. once all commitments are in, every party reveals its public nonce ([MultiSigMessage::PublicNonce]),
+//! 1. once all nonces are in and match their commitments, every party broadcasts its partial signature over the
+//!    aggregate nonce ([MultiSigMessage::PartialSignature]).
+//!
+//! The partial signatures are checked against the public excess of each party and summed into the kernel signature.
+//! Committing to the nonces before revealing them stops the last party from choosing its nonce as a function of the
+//! others' and forging the aggregate signature.
+
+use crate::transactions::{
+    tari_amount::MicroTari,
+    transaction::{
+        KernelBuilder,
+        KernelFeatures,
+        OutputFeatures,
+        Transaction,
+        TransactionBuilder,
+        TransactionInput,
+        TransactionOutput,
+        UnblindedOutput,
+    },
+    transaction_protocol::{build_challenge, TransactionMetadata, TransactionProtocolError as TPE},
+    types::{
+        BlindingFactor,
+        Commitment,
+        CommitmentFactory,
+        CryptoFactories,
+        HashDigest,
+        PrivateKey,
+        PublicKey,
+        Signature,
+    },
+};
+use digest::Digest;
+use rand::rngs::OsRng;
+use serde::{Deserialize, Serialize};
+use tari_crypto::{
+    commitment::HomomorphicCommitmentFactory,
+    keys::{PublicKey as PublicKeyTrait, SecretKey},
+    tari_utilities::ByteArray,
+};
+
+const NONCE_COMMITMENT_LABEL: &[u8] = b"multisig_nonce_commitment";
+
+/// Split `spending_key` into `num_parties` random additive shares that sum to the key
+pub fn deal_key_shares(spending_key: &PrivateKey, num_parties: usize) -> Vec<PrivateKey> {
+    if num_parties == 0 {
+        return Vec::new();
+    }
+    let mut shares = (1..num_parties)
+        .map(|_| PrivateKey::random(&mut OsRng))
+        .collect::<Vec<_>>();
+    let last = shares.iter().fold(spending_key.clone(), |acc, k| &acc - k);
+    shares.push(last);
+    shares
+}
+
+/// The public description of an output owned jointly by `n` parties
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JointOutput {
+    pub commitment: Commitment,
+    pub value: MicroTari,
+    pub features: OutputFeatures,
+    /// The public key of every party's share of the spending key, in party order
+    pub public_shares: Vec<PublicKey>,
+}
+
+impl JointOutput {
+    pub fn new(
+        commitment: Commitment,
+        value: MicroTari,
+        features: OutputFeatures,
+        public_shares: Vec<PublicKey>,
+    ) -> Self
+    {
+        Self {
+            commitment,
+            value,
+            features,
+            public_shares,
+        }
+    }
+
+    pub fn num_parties(&self) -> usize {
+        self.public_shares.len()
+    }
+
+    /// The public spending key of the output, i.e. the sum of the public shares
+    pub fn aggregate_public_key(&self) -> PublicKey {
+        self.public_shares.iter().fold(PublicKey::default(), |acc, k| &acc + k)
+    }
+
+    /// Check that the public shares and value open the commitment
+    pub fn verify(&self, factory: &CommitmentFactory) -> Result<(), TPE> {
+        if self.public_shares.is_empty() {
+            return Err(TPE::ValidationError(
+                "A joint output needs at least one party".to_string(),
+            ));
+        }
+        let value = factory.commit_value(&PrivateKey::default(), self.value.into());
+        if self.commitment != &Commitment::from_public_key(&self.aggregate_public_key()) + &value {
+            return Err(TPE::ValidationError(
+                "The public key shares do not open the joint output commitment".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn as_transaction_input(&self) -> TransactionInput {
+        TransactionInput::new(self.features.clone(), self.commitment.clone())
+    }
+
+    fn public_share(&self, party_index: usize) -> Result<&PublicKey, TPE> {
+        self.public_shares
+            .get(party_index)
+            .ok_or_else(|| TPE::ValidationError(format!("Party {} is not an owner of the joint output", party_index)))
+    }
+}
+
+/// The share of a joint output handed to a single co-owner by the dealer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JointOutputShare {
+    pub output: JointOutput,
+    pub party_index: usize,
+    pub secret_share: PrivateKey,
+    /// The public keys used to address each of the co-owners, in party order
+    pub owners: Vec<PublicKey>,
+}
+
+impl JointOutputShare {
+    /// Create the shares of every co-owner of an output with spending key `spending_key`. `owners` are the public
+    /// keys used to address the co-owners; the shares are returned in the same order.
+    pub fn deal(
+        spending_key: &PrivateKey,
+        value: MicroTari,
+        features: OutputFeatures,
+        owners: Vec<PublicKey>,
+        factory: &CommitmentFactory,
+    ) -> Vec<JointOutputShare>
+    {
+        let shares = deal_key_shares(spending_key, owners.len());
+        let output = JointOutput::new(
+            factory.commit_value(spending_key, value.into()),
+            value,
+            features,
+            shares.iter().map(PublicKey::from_secret_key).collect(),
+        );
+        shares
+            .into_iter()
+            .enumerate()
+            .map(|(party_index, secret_share)| JointOutputShare {
+                output: output.clone(),
+                party_index,
+                secret_share,
+                owners: owners.clone(),
+            })
+            .collect()
+    }
+
+    /// Check that the joint output is consistent and that the secret share belongs to it
+    pub fn verify(&self, factory: &CommitmentFactory) -> Result<(), TPE> {
+        self.output.verify(factory)?;
+        if self.owners.len() != self.output.num_parties() {
+            return Err(TPE::ValidationError(
+                "The number of owners does not match the number of key shares".to_string(),
+            ));
+        }
+        if &PublicKey::from_secret_key(&self.secret_share) != self.output.public_share(self.party_index)? {
+            return Err(TPE::ValidationError(
+                "The secret share does not match its public share".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// A proposal by one of the co-owners to sweep a joint output into an output it owns
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JointSpendProposal {
+    pub session_id: u64,
+    /// The commitment of the joint output being spent
+    pub joint_commitment: Commitment,
+    pub coordinator_index: usize,
+    pub metadata: TransactionMetadata,
+    pub offset: BlindingFactor,
+    pub output: TransactionOutput,
+    /// The public excess of the coordinator. The public excess of every other party is the negation of its public
+    /// key share.
+    pub coordinator_public_excess: PublicKey,
+}
+
+impl JointSpendProposal {
+    /// The value the coordinator receives, i.e. the value of the joint output less the fee
+    pub fn amount(&self, joint_output: &JointOutput) -> MicroTari {
+        joint_output.value - self.metadata.fee
+    }
+
+    /// Check that the proposal spends `joint_output` and that the public excesses of all parties balance the
+    /// transaction, which fixes the value of the new output at the value of the joint output less the fee.
+    pub fn verify(&self, joint_output: &JointOutput, factories: &CryptoFactories) -> Result<(), TPE> {
+        if self.joint_commitment != joint_output.commitment {
+            return Err(TPE::ValidationError(
+                "The proposal does not spend this joint output".to_string(),
+            ));
+        }
+        joint_output.public_share(self.coordinator_index)?;
+        if self.metadata.fee > joint_output.value {
+            return Err(TPE::ValidationError(
+                "The fee exceeds the value of the joint output".to_string(),
+            ));
+        }
+        if !self.output.verify_range_proof(&factories.range_proof)? {
+            return Err(TPE::ValidationError("The output range proof is invalid".to_string()));
+        }
+        let excess = (0..joint_output.num_parties())
+            .map(|i| self.public_excess(joint_output, i))
+            .collect::<Result<Vec<_>, _>>()?
+            .iter()
+            .fold(PublicKey::default(), |acc, x| &acc + x);
+        let offset = factories.commitment.commit_value(&self.offset, 0);
+        let fee = factories
+            .commitment
+            .commit_value(&PrivateKey::default(), self.metadata.fee.into());
+        let lhs = &Commitment::from_public_key(&excess) + &offset;
+        let rhs = &(&self.output.commitment - &self.joint_commitment) + &fee;
+        if lhs != rhs {
+            return Err(TPE::ValidationError(
+                "The public excesses do not balance the proposed transaction".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// The public excess that party `party_index` signs with
+    pub fn public_excess(&self, joint_output: &JointOutput, party_index: usize) -> Result<PublicKey, TPE> {
+        let share = joint_output.public_share(party_index)?;
+        if party_index == self.coordinator_index {
+            Ok(self.coordinator_public_excess.clone())
+        } else {
+            Ok(&PublicKey::default() - share)
+        }
+    }
+}
+
+/// The messages exchanged by the co-owners of a joint output
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum MultiSigMessage {
+    /// Sent by the dealer to each co-owner of a new joint output
+    JointOutputShare(Box<JointOutputShare>),
+    /// Sent by the coordinator to start a signing session
+    SpendProposal(Box<JointSpendProposal>),
+    /// Round 1: a hash commitment to the public nonce of a party
+    NonceCommitment {
+        session_id: u64,
+        party_index: usize,
+        commitment: Vec<u8>,
+    },
+    /// Round 2: the public nonce of a party
+    PublicNonce {
+        session_id: u64,
+        party_index: usize,
+        public_nonce: PublicKey,
+    },
+    /// Round 3: the partial signature of a party
+    PartialSignature {
+        session_id: u64,
+        party_index: usize,
+        signature: Signature,
+    },
+}
+
+impl MultiSigMessage {
+    /// The signing session this message belongs to, if any
+    pub fn session_id(&self) -> Option<u64> {
+        match self {
+            MultiSigMessage::JointOutputShare(_) => None,
+            MultiSigMessage::SpendProposal(p) => Some(p.session_id),
+            MultiSigMessage::NonceCommitment { session_id, .. } |
+            MultiSigMessage::PublicNonce { session_id, .. } |
+            MultiSigMessage::PartialSignature { session_id, .. } => Some(*session_id),
+        }
+    }
+}
+
+/// The signing state of one co-owner taking part in the spend of a joint output
+#[derive(Debug, Clone)]
+pub struct MultiSigSession {
+    joint_output: JointOutput,
+    proposal: JointSpendProposal,
+    party_index: usize,
+    private_excess: PrivateKey,
+    private_nonce: PrivateKey,
+    nonce_commitments: Vec<Option<Vec<u8>>>,
+    public_nonces: Vec<Option<PublicKey>>,
+    partial_signatures: Vec<Option<Signature>>,
+}
+
+impl MultiSigSession {
+    /// Start a session as the coordinator. The joint output is swept, less the fee, into an output with spending key
+    /// `output_key`. Returns the session and the proposal to send to the other co-owners.
+    pub fn propose(
+        session_id: u64,
+        share: &JointOutputShare,
+        output_key: PrivateKey,
+        metadata: TransactionMetadata,
+        factories: &CryptoFactories,
+    ) -> Result<(Self, JointSpendProposal), TPE>
+    {
+        share.verify(&factories.commitment)?;
+        if metadata.fee > share.output.value {
+            return Err(TPE::ValidationError(
+                "The fee exceeds the value of the joint output".to_string(),
+            ));
+        }
+        let output = UnblindedOutput::new(share.output.value - metadata.fee, output_key.clone(), None)
+            .as_transaction_output(factories)?;
+        let offset = PrivateKey::random(&mut OsRng);
+        let private_excess = &(&output_key - &offset) - &share.secret_share;
+        let proposal = JointSpendProposal {
+            session_id,
+            joint_commitment: share.output.commitment.clone(),
+            coordinator_index: share.party_index,
+            metadata,
+            offset,
+            output,
+            coordinator_public_excess: PublicKey::from_secret_key(&private_excess),
+        };
+        let session = Self::new(share, proposal.clone(), private_excess, factories)?;
+        Ok((session, proposal))
+    }
+
+    /// Join a session proposed by another co-owner
+    pub fn join(
+        share: &JointOutputShare,
+        proposal: JointSpendProposal,
+        factories: &CryptoFactories,
+    ) -> Result<Self, TPE>
+    {
+        share.verify(&factories.commitment)?;
+        if proposal.coordinator_index == share.party_index {
+            return Err(TPE::ValidationError(
+                "Cannot join a session as its coordinator".to_string(),
+            ));
+        }
+        let private_excess = &PrivateKey::default() - &share.secret_share;
+        Self::new(share, proposal, private_excess, factories)
+    }
+
+    fn new(
+        share: &JointOutputShare,
+        proposal: JointSpendProposal,
+        private_excess: PrivateKey,
+        factories: &CryptoFactories,
+    ) -> Result<Self, TPE>
+    {
+        proposal.verify(&share.output, factories)?;
+        let n = share.output.num_parties();
+        let private_nonce = PrivateKey::random(&mut OsRng);
+        let public_nonce = PublicKey::from_secret_key(&private_nonce);
+        let mut session = Self {
+            joint_output: share.output.clone(),
+            proposal,
+            party_index: share.party_index,
+            private_excess,
+            private_nonce,
+            nonce_commitments: vec![None; n],
+            public_nonces: vec![None; n],
+            partial_signatures: vec![None; n],
+        };
+        session.nonce_commitments[session.party_index] = Some(nonce_commitment(&public_nonce));
+        session.public_nonces[session.party_index] = Some(public_nonce);
+        Ok(session)
+    }
+
+    pub fn session_id(&self) -> u64 {
+        self.proposal.session_id
+    }
+
+    pub fn party_index(&self) -> usize {
+        self.party_index
+    }
+
+    pub fn proposal(&self) -> &JointSpendProposal {
+        &self.proposal
+    }
+
+    pub fn joint_output(&self) -> &JointOutput {
+        &self.joint_output
+    }
+
+    /// This party's round 1 message
+    pub fn nonce_commitment_message(&self) -> MultiSigMessage {
+        MultiSigMessage::NonceCommitment {
+            session_id: self.session_id(),
+            party_index: self.party_index,
+            commitment: nonce_commitment(&PublicKey::from_secret_key(&self.private_nonce)),
+        }
+    }
+
+    /// This party's round 2 message. Only available once every party has committed to its nonce.
+    pub fn public_nonce_message(&self) -> Result<MultiSigMessage, TPE> {
+        if !self.all_nonces_committed() {
+            return Err(TPE::IncompleteStateError(
+                "Not all nonce commitments have been received".to_string(),
+            ));
+        }
+        Ok(MultiSigMessage::PublicNonce {
+            session_id: self.session_id(),
+            party_index: self.party_index,
+            public_nonce: PublicKey::from_secret_key(&self.private_nonce),
+        })
+    }
+
+    /// This party's round 3 message. Only available once every party has revealed its nonce.
+    pub fn partial_signature_message(&mut self) -> Result<MultiSigMessage, TPE> {
+        let e = self.challenge()?;
+        let signature =
+            Signature::sign(self.private_excess.clone(), self.private_nonce.clone(), &e).map_err(TPE::SigningError)?;
+        self.partial_signatures[self.party_index] = Some(signature.clone());
+        Ok(MultiSigMessage::PartialSignature {
+            session_id: self.session_id(),
+            party_index: self.party_index,
+            signature,
+        })
+    }
+
+    /// Process a round message from another party
+    pub fn handle_message(&mut self, message: MultiSigMessage) -> Result<(), TPE> {
+        if message.session_id() != Some(self.session_id()) {
+            return Err(TPE::ValidationError(
+                "The message does not belong to this session".to_string(),
+            ));
+        }
+        match message {
+            MultiSigMessage::NonceCommitment {
+                party_index,
+                commitment,
+                ..
+            } => {
+                self.check_party(party_index)?;
+                if self.nonce_commitments[party_index].is_some() {
+                    return Err(TPE::InvalidTransitionError);
+                }
+                self.nonce_commitments[party_index] = Some(commitment);
+            },
+            MultiSigMessage::PublicNonce {
+                party_index,
+                public_nonce,
+                ..
+            } => {
+                self.check_party(party_index)?;
+                if self.public_nonces[party_index].is_some() {
+                    return Err(TPE::InvalidTransitionError);
+                }
+                match &self.nonce_commitments[party_index] {
+                    Some(c) if c == &nonce_commitment(&public_nonce) => {},
+                    Some(_) => {
+                        return Err(TPE::ValidationError(format!(
+                            "The public nonce of party {} does not match its commitment",
+                            party_index
+                        )))
+                    },
+                    None => return Err(TPE::InvalidTransitionError),
+                }
+                self.public_nonces[party_index] = Some(public_nonce);
+            },
+            MultiSigMessage::PartialSignature {
+                party_index, signature, ..
+            } => {
+                self.check_party(party_index)?;
+                if self.partial_signatures[party_index].is_some() {
+                    return Err(TPE::InvalidTransitionError);
+                }
+                let e = self.challenge()?;
+                let excess = self.proposal.public_excess(&self.joint_output, party_index)?;
+                if self.public_nonces[party_index].as_ref() != Some(signature.get_public_nonce()) ||
+                    !signature.verify_challenge(&excess, &e)
+                {
+                    return Err(TPE::InvalidSignatureError);
+                }
+                self.partial_signatures[party_index] = Some(signature);
+            },
+            _ => return Err(TPE::InvalidStateError),
+        }
+        Ok(())
+    }
+
+    pub fn all_nonces_committed(&self) -> bool {
+        self.nonce_commitments.iter().all(Option::is_some)
+    }
+
+    pub fn all_nonces_revealed(&self) -> bool {
+        self.public_nonces.iter().all(Option::is_some)
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.partial_signatures.iter().all(Option::is_some)
+    }
+
+    /// Aggregate the partial signatures into the kernel and build the final, validated transaction
+    pub fn finalize(&self, factories: &CryptoFactories) -> Result<Transaction, TPE> {
+        if !self.is_complete() {
+            return Err(TPE::IncompleteStateError(
+                "Not all partial signatures have been received".to_string(),
+            ));
+        }
+        let mut signatures = self.partial_signatures.iter().flatten();
+        let first = signatures.next().cloned().ok_or(TPE::InvalidStateError)?;
+        let signature = signatures.fold(first, |acc, s| &acc + s);
+        let excess = (0..self.joint_output.num_parties())
+            .map(|i| self.proposal.public_excess(&self.joint_output, i))
+            .collect::<Result<Vec<_>, _>>()?
+            .iter()
+            .fold(PublicKey::default(), |acc, x| &acc + x);
+        let kernel = KernelBuilder::new()
+            .with_fee(self.proposal.metadata.fee)
+            .with_lock_height(self.proposal.metadata.lock_height)
+            .with_features(KernelFeatures::empty())
+            .with_excess(&Commitment::from_public_key(&excess))
+            .with_signature(&signature)
+            .build()?;
+        let mut builder = TransactionBuilder::new();
+        builder
+            .add_input(self.joint_output.as_transaction_input())
+            .add_output(self.proposal.output.clone())
+            .add_offset(self.proposal.offset.clone())
+            .with_kernel(kernel);
+        Ok(builder.build(factories)?)
+    }
+
+    fn challenge(&self) -> Result<Vec<u8>, TPE> {
+        if !self.all_nonces_revealed() {
+            return Err(TPE::IncompleteStateError(
+                "Not all public nonces have been received".to_string(),
+            ));
+        }
+        let nonce_sum = self
+            .public_nonces
+            .iter()
+            .flatten()
+            .fold(PublicKey::default(), |acc, r| &acc + r);
+        Ok(build_challenge(&nonce_sum, &self.proposal.metadata))
+    }
+
+    fn check_party(&self, party_index: usize) -> Result<(), TPE> {
+        if party_index >= self.joint_output.num_parties() || party_index == self.party_index {
+            return Err(TPE::ValidationError(format!(
+                "Unexpected message from party {}",
+                party_index
+            )));
+        }
+        Ok(())
+    }
+}
+
+fn nonce_commitment(public_nonce: &PublicKey) -> Vec<u8> {
+    HashDigest::new()
+        .chain(NONCE_COMMITMENT_LABEL)
+        .chain(public_nonce.as_bytes())
+        .result()
+        .to_vec()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::transactions::tari_amount::uT;
+
+    fn deal(n: usize, factories: &CryptoFactories) -> Vec<JointOutputShare> {
+        let owners = (0..n).map(|_| PublicKey::random_keypair(&mut OsRng).1).collect();
+        JointOutputShare::deal(
+            &PrivateKey::random(&mut OsRng),
+            MicroTari::from(10_000),
+            OutputFeatures::default(),
+            owners,
+            &factories.commitment,
+        )
+    }
+
+    fn metadata() -> TransactionMetadata {
+        TransactionMetadata {
+            fee: 100 * uT,
+            lock_height: 0,
+        }
+    }
+
+    #[test]
+    fn dealt_shares_open_the_joint_output() {
+        let factories = CryptoFactories::default();
+        let shares = deal(3, &factories);
+        assert_eq!(shares.len(), 3);
+        for share in &shares {
+            assert!(share.verify(&factories.commitment).is_ok());
+        }
+
+        let mut bad_share = shares[1].clone();
+        bad_share.secret_share = PrivateKey::random(&mut OsRng);
+        assert!(bad_share.verify(&factories.commitment).is_err());
+
+        let mut bad_output = shares[0].clone();
+        bad_output.output.value = MicroTari::from(1);
+        assert!(bad_output.verify(&factories.commitment).is_err());
+    }
+
+    #[test]
+    fn all_parties_sign_the_joint_spend() {
+        let factories = CryptoFactories::default();
+        let shares = deal(3, &factories);
+        let output_key = PrivateKey::random(&mut OsRng);
+        let (coordinator, proposal) =
+            MultiSigSession::propose(42, &shares[1], output_key.clone(), metadata(), &factories).unwrap();
+        assert_eq!(proposal.amount(&shares[0].output), MicroTari::from(9_900));
+
+        let mut sessions = vec![
+            MultiSigSession::join(&shares[0], proposal.clone(), &factories).unwrap(),
+            coordinator,
+            MultiSigSession::join(&shares[2], proposal, &factories).unwrap(),
+        ];
+
+        let commitments = sessions
+            .iter()
+            .map(|s| s.nonce_commitment_message())
+            .collect::<Vec<_>>();
+        deliver(&mut sessions, commitments);
+        // Nonces cannot be revealed twice or before all commitments are known
+        let nonces = sessions
+            .iter()
+            .map(|s| s.public_nonce_message().unwrap())
+            .collect::<Vec<_>>();
+        deliver(&mut sessions, nonces.clone());
+        assert_eq!(
+            sessions[0].handle_message(nonces[1].clone()),
+            Err(TPE::InvalidTransitionError)
+        );
+        let signatures = sessions
+            .iter_mut()
+            .map(|s| s.partial_signature_message().unwrap())
+            .collect::<Vec<_>>();
+        deliver(&mut sessions, signatures);
+
+        for session in &sessions {
+            assert!(session.is_complete());
+            let tx = session.finalize(&factories).unwrap();
+            assert!(tx.validate_internal_consistency(&factories, None).is_ok());
+            assert!(tx.body.outputs()[0]
+                .commitment
+                .eq(&factories.commitment.commit_value(&output_key, 9_900)));
+        }
+    }
+
+    #[test]
+    fn reject_nonce_that_does_not_match_commitment() {
+        let factories = CryptoFactories::default();
+        let shares = deal(2, &factories);
+        let (mut coordinator, proposal) =
+            MultiSigSession::propose(1, &shares[0], PrivateKey::random(&mut OsRng), metadata(), &factories).unwrap();
+        let other = MultiSigSession::join(&shares[1], proposal, &factories).unwrap();
+
+        coordinator.handle_message(other.nonce_commitment_message()).unwrap();
+        let result = coordinator.handle_message(MultiSigMessage::PublicNonce {
+            session_id: 1,
+            party_index: 1,
+            public_nonce: PublicKey::random_keypair(&mut OsRng).1,
+        });
+        assert!(matches!(result, Err(TPE::ValidationError(_))));
+    }
+
+    #[test]
+    fn reject_proposal_that_does_not_balance() {
+        let factories = CryptoFactories::default();
+        let shares = deal(2, &factories);
+        let (_, mut proposal) =
+            MultiSigSession::propose(1, &shares[0], PrivateKey::random(&mut OsRng), metadata(), &factories).unwrap();
+        // The coordinator tries to pay itself more than the joint output holds
+        proposal.output = UnblindedOutput::new(MicroTari::from(20_000), PrivateKey::random(&mut OsRng), None)
+            .as_transaction_output(&factories)
+            .unwrap();
+        assert!(MultiSigSession::join(&shares[1], proposal, &factories).is_err());
+    }
+
+    fn deliver(sessions: &mut [MultiSigSession], messages: Vec<MultiSigMessage>) {
+        for (i, message) in messages.into_iter().enumerate() {
+            for (j, session) in sessions.iter_mut().enumerate() {
+                if i != j {
+                    session.handle_message(message.clone()).unwrap();
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,131 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Key derivation for one-sided payments.
+//!
+//! A one-sided payment lets a sender pay to a recipient's public key without the recipient taking part in the
+//! transaction protocol. The sender builds the transaction on its own, using its private nonce `r` and the recipient's
+//! public key `P` to compute the Diffie-Hellman shared secret `r·P`. The public nonce `R` of the sender ends up as the
+//! public nonce of the kernel signature, so the recipient can compute the same shared secret `p·R` from its private key
+//! and rewind the output to discover the value and commitment key.
+//!
+//! The sender has to know the blinding factor of the commitment to build the range proof and the kernel excess, so the
+//! commitment key of the payment output is derived from the shared secret and is known to both parties. Ownership is
+//! handed to the recipient by claiming the output: the recipient spends it into an output with a new key of its own,
+//! which the sender does not know.
+//!
+//! A sender whose nonce is held by an external signer has the signer compute `r·P` and derives the keys with
+//! `OneSidedPaymentKeys::from_shared_secret`.
+
+use crate::transactions::{
+    transaction_protocol::RewindData,
+    types::{HashDigest, PrivateKey, PublicKey},
+};
+use digest::Digest;
+use tari_crypto::{
+    keys::DiffieHellmanSharedSecret,
+    range_proof::REWIND_USER_MESSAGE_LENGTH,
+    tari_utilities::{ByteArray, ByteArrayError},
+};
+
+const SPEND_KEY_LABEL: &[u8] = b"one_sided_spend_key";
+const REWIND_KEY_LABEL: &[u8] = b"one_sided_rewind_key";
+const REWIND_BLINDING_KEY_LABEL: &[u8] = b"one_sided_rewind_blinding_key";
+
+/// The keys required to create, detect and claim a one-sided payment output
+#[derive(Debug, Clone)]
+pub struct OneSidedPaymentKeys {
+    /// The commitment key of the payment output, known to both the sender and the recipient
+    pub spending_key: PrivateKey,
+    pub rewind_data: RewindData,
+}
+
+impl OneSidedPaymentKeys {
+    /// Derive the keys used by the sender, from its private nonce and the recipient's public key
+    pub fn for_sender(private_nonce: &PrivateKey, recipient_public_key: &PublicKey) -> Result<Self, ByteArrayError> {
+        Self::from_shared_secret(&PublicKey::shared_secret(private_nonce, recipient_public_key))
+    }
+
+    /// Derive the keys used by the recipient, from its private key and the public nonce of a transaction kernel
+    pub fn for_recipient(recipient_private_key: &PrivateKey, public_nonce: &PublicKey) -> Result<Self, ByteArrayError> {
+        Self::from_shared_secret(&PublicKey::shared_secret(recipient_private_key, public_nonce))
+    }
+
+    /// Derive the keys from the Diffie-Hellman shared secret of the sender's nonce and the recipient's key
+    pub fn from_shared_secret(shared_secret: &PublicKey) -> Result<Self, ByteArrayError> {
+        Ok(Self {
+            spending_key: derive_key(SPEND_KEY_LABEL, shared_secret)?,
+            rewind_data: RewindData {
+                rewind_key: derive_key(REWIND_KEY_LABEL, shared_secret)?,
+                rewind_blinding_key: derive_key(REWIND_BLINDING_KEY_LABEL, shared_secret)?,
+                proof_message: [0u8; REWIND_USER_MESSAGE_LENGTH],
+            },
+        })
+    }
+}
+
+fn derive_key(label: &[u8], shared_secret: &PublicKey) -> Result<PrivateKey, ByteArrayError> {
+    PrivateKey::from_bytes(
+        HashDigest::new()
+            .chain(label)
+            .chain(shared_secret.as_bytes())
+            .result()
+            .as_slice(),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::transactions::{tari_amount::MicroTari, transaction::UnblindedOutput, types::CryptoFactories};
+    use rand::rngs::OsRng;
+    use tari_crypto::keys::PublicKey as PublicKeyTrait;
+
+    #[test]
+    fn recipient_can_rewind_one_sided_output() {
+        let factories = CryptoFactories::default();
+        let (sender_nonce, sender_public_nonce) = PublicKey::random_keypair(&mut OsRng);
+        let (recipient_key, recipient_public_key) = PublicKey::random_keypair(&mut OsRng);
+
+        let sender_keys = OneSidedPaymentKeys::for_sender(&sender_nonce, &recipient_public_key).unwrap();
+        let output = UnblindedOutput::new(MicroTari::from(5000), sender_keys.spending_key.clone(), None)
+            .as_rewindable_transaction_output(&factories, &sender_keys.rewind_data)
+            .unwrap();
+
+        let recipient_keys = OneSidedPaymentKeys::for_recipient(&recipient_key, &sender_public_nonce).unwrap();
+        assert_eq!(recipient_keys.spending_key, sender_keys.spending_key);
+        let rewound = output
+            .full_rewind_range_proof(
+                &factories.range_proof,
+                &recipient_keys.rewind_data.rewind_key,
+                &recipient_keys.rewind_data.rewind_blinding_key,
+            )
+            .unwrap();
+        assert_eq!(rewound.committed_value, MicroTari::from(5000));
+        assert_eq!(rewound.blinding_factor, recipient_keys.spending_key);
+
+        // Anyone else derives different keys
+        let (other_key, _) = PublicKey::random_keypair(&mut OsRng);
+        let other_keys = OneSidedPaymentKeys::for_recipient(&other_key, &sender_public_nonce).unwrap();
+        assert_ne!(other_keys.spending_key, sender_keys.spending_key);
+    }
+}
@@ -22,6 +22,7 @@
 
 pub use crate::proto::transaction_protocol as protocol;
 
+pub mod multisig;
 pub mod recipient_signed_message;
 pub mod transaction_metadata;
 pub mod transaction_sender;
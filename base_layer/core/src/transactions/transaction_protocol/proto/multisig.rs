@@ -0,0 +1,210 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use super::protocol as proto;
+use crate::transactions::{
+    transaction_protocol::multisig::{JointOutput, JointOutputShare, JointSpendProposal, MultiSigMessage},
+    types::{BlindingFactor, Commitment, PrivateKey, PublicKey},
+};
+use proto::multi_sig_message::Message as ProtoMultiSigMessage;
+use std::convert::{TryFrom, TryInto};
+use tari_crypto::tari_utilities::ByteArray;
+
+//---------------------------------- MultiSigMessage --------------------------------------------//
+
+impl TryFrom<proto::MultiSigMessage> for MultiSigMessage {
+    type Error = String;
+
+    fn try_from(message: proto::MultiSigMessage) -> Result<Self, Self::Error> {
+        let message = match message
+            .message
+            .ok_or_else(|| "MultiSigMessage.message not provided".to_string())?
+        {
+            ProtoMultiSigMessage::JointOutputShare(share) => {
+                MultiSigMessage::JointOutputShare(Box::new(share.try_into()?))
+            },
+            ProtoMultiSigMessage::SpendProposal(proposal) => {
+                MultiSigMessage::SpendProposal(Box::new(proposal.try_into()?))
+            },
+            ProtoMultiSigMessage::NonceCommitment(m) => MultiSigMessage::NonceCommitment {
+                session_id: m.session_id,
+                party_index: m.party_index as usize,
+                commitment: m.commitment,
+            },
+            ProtoMultiSigMessage::PublicNonce(m) => MultiSigMessage::PublicNonce {
+                session_id: m.session_id,
+                party_index: m.party_index as usize,
+                public_nonce: PublicKey::from_bytes(&m.public_nonce).map_err(|err| err.to_string())?,
+            },
+            ProtoMultiSigMessage::PartialSignature(m) => MultiSigMessage::PartialSignature {
+                session_id: m.session_id,
+                party_index: m.party_index as usize,
+                signature: m
+                    .signature
+                    .map(TryInto::try_into)
+                    .ok_or_else(|| "Partial signature not provided".to_string())?
+                    .map_err(|err| format!("{}", err))?,
+            },
+        };
+        Ok(message)
+    }
+}
+
+impl From<MultiSigMessage> for proto::MultiSigMessage {
+    fn from(message: MultiSigMessage) -> Self {
+        let message = match message {
+            MultiSigMessage::JointOutputShare(share) => ProtoMultiSigMessage::JointOutputShare((*share).into()),
+            MultiSigMessage::SpendProposal(proposal) => ProtoMultiSigMessage::SpendProposal((*proposal).into()),
+            MultiSigMessage::NonceCommitment {
+                session_id,
+                party_index,
+                commitment,
+            } => ProtoMultiSigMessage::NonceCommitment(proto::MultiSigNonceCommitment {
+                session_id,
+                party_index: party_index as u32,
+                commitment,
+            }),
+            MultiSigMessage::PublicNonce {
+                session_id,
+                party_index,
+                public_nonce,
+            } => ProtoMultiSigMessage::PublicNonce(proto::MultiSigPublicNonce {
+                session_id,
+                party_index: party_index as u32,
+                public_nonce: public_nonce.to_vec(),
+            }),
+            MultiSigMessage::PartialSignature {
+                session_id,
+                party_index,
+                signature,
+            } => ProtoMultiSigMessage::PartialSignature(proto::MultiSigPartialSignature {
+                session_id,
+                party_index: party_index as u32,
+                signature: Some(signature.into()),
+            }),
+        };
+        Self { message: Some(message) }
+    }
+}
+
+//---------------------------------- JointOutput --------------------------------------------//
+
+impl TryFrom<proto::JointOutput> for JointOutput {
+    type Error = String;
+
+    fn try_from(output: proto::JointOutput) -> Result<Self, Self::Error> {
+        Ok(Self {
+            commitment: Commitment::from_bytes(&output.commitment).map_err(|err| err.to_string())?,
+            value: output.value.into(),
+            features: output
+                .features
+                .map(TryInto::try_into)
+                .ok_or_else(|| "Output features not provided".to_string())??,
+            public_shares: public_keys_from_bytes(&output.public_shares)?,
+        })
+    }
+}
+
+impl From<JointOutput> for proto::JointOutput {
+    fn from(output: JointOutput) -> Self {
+        Self {
+            commitment: output.commitment.to_vec(),
+            value: output.value.into(),
+            features: Some(output.features.into()),
+            public_shares: output.public_shares.iter().map(|k| k.to_vec()).collect(),
+        }
+    }
+}
+
+//---------------------------------- JointOutputShare --------------------------------------------//
+
+impl TryFrom<proto::JointOutputShare> for JointOutputShare {
+    type Error = String;
+
+    fn try_from(share: proto::JointOutputShare) -> Result<Self, Self::Error> {
+        Ok(Self {
+            output: share
+                .output
+                .map(TryInto::try_into)
+                .ok_or_else(|| "Joint output not provided".to_string())??,
+            party_index: share.party_index as usize,
+            secret_share: PrivateKey::from_bytes(&share.secret_share).map_err(|err| err.to_string())?,
+            owners: public_keys_from_bytes(&share.owners)?,
+        })
+    }
+}
+
+impl From<JointOutputShare> for proto::JointOutputShare {
+    fn from(share: JointOutputShare) -> Self {
+        Self {
+            output: Some(share.output.into()),
+            party_index: share.party_index as u32,
+            secret_share: share.secret_share.to_vec(),
+            owners: share.owners.iter().map(|k| k.to_vec()).collect(),
+        }
+    }
+}
+
+//---------------------------------- JointSpendProposal --------------------------------------------//
+
+impl TryFrom<proto::JointSpendProposal> for JointSpendProposal {
+    type Error = String;
+
+    fn try_from(proposal: proto::JointSpendProposal) -> Result<Self, Self::Error> {
+        Ok(Self {
+            session_id: proposal.session_id,
+            joint_commitment: Commitment::from_bytes(&proposal.joint_commitment).map_err(|err| err.to_string())?,
+            coordinator_index: proposal.coordinator_index as usize,
+            metadata: proposal
+                .metadata
+                .map(Into::into)
+                .ok_or_else(|| "Transaction metadata not provided".to_string())?,
+            offset: BlindingFactor::from_bytes(&proposal.offset).map_err(|err| err.to_string())?,
+            output: proposal
+                .output
+                .map(TryInto::try_into)
+                .ok_or_else(|| "Transaction output not provided".to_string())??,
+            coordinator_public_excess: PublicKey::from_bytes(&proposal.coordinator_public_excess)
+                .map_err(|err| err.to_string())?,
+        })
+    }
+}
+
+impl From<JointSpendProposal> for proto::JointSpendProposal {
+    fn from(proposal: JointSpendProposal) -> Self {
+        Self {
+            session_id: proposal.session_id,
+            joint_commitment: proposal.joint_commitment.to_vec(),
+            coordinator_index: proposal.coordinator_index as u32,
+            metadata: Some(proposal.metadata.into()),
+            offset: proposal.offset.to_vec(),
+            output: Some(proposal.output.into()),
+            coordinator_public_excess: proposal.coordinator_public_excess.to_vec(),
+        }
+    }
+}
+
+fn public_keys_from_bytes(keys: &[Vec<u8>]) -> Result<Vec<PublicKey>, String> {
+    keys.iter()
+        .map(|k| PublicKey::from_bytes(k).map_err(|err| err.to_string()))
+        .collect()
+}
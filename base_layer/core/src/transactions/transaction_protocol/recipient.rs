@@ -142,6 +142,14 @@ impl ReceiverTransactionProtocol {
         ReceiverTransactionProtocol { state }
     }
 
+    /// Create a finalised recipient protocol from the output and partial signature of a recipient whose spending key
+    /// is held by an external signer. See `SingleReceiverTransactionProtocol::create` for how these are constructed.
+    pub fn new_with_signed_data(data: RecipientSignedMessage) -> ReceiverTransactionProtocol {
+        ReceiverTransactionProtocol {
+            state: RecipientState::Finalized(Box::new(data)),
+        }
+    }
+
     /// Returns true if the recipient protocol is finalised, and the signature data is ready to be sent to the sender.
     pub fn is_finalized(&self) -> bool {
         matches!(self.state, RecipientState::Finalized(_))
@@ -40,7 +40,7 @@ use crate::transactions::{
         TransactionMetadata,
         TransactionProtocolError as TPE,
     },
-    types::{BlindingFactor, CryptoFactories, PrivateKey, PublicKey, RangeProofService, Signature},
+    types::{BlindingFactor, CryptoFactories, MessageHash, PrivateKey, PublicKey, RangeProofService, Signature},
 };
 use digest::Digest;
 use serde::{Deserialize, Serialize};
@@ -63,10 +63,11 @@ pub(super) struct RawTransactionInfo {
     pub inputs: Vec<TransactionInput>,
     pub outputs: Vec<TransactionOutput>,
     pub offset: BlindingFactor,
-    // The sender's blinding factor shifted by the sender-selected offset
+    // The sender's blinding factor shifted by the sender-selected offset. If an external signer holds part of the
+    // sender's excess, this is only the part that is not held by the signer.
     pub offset_blinding_factor: BlindingFactor,
     pub public_excess: PublicKey,
-    // The sender's private nonce
+    // The sender's private nonce, zero if the nonce is held by an external signer
     pub private_nonce: PrivateKey,
     // The sender's public nonce
     pub public_nonce: PublicKey,
@@ -360,14 +361,24 @@ impl SenderTransactionProtocol {
         }
     }
 
-    /// Produce the sender's partial signature
-    fn sign(&mut self) -> Result<(), TPE> {
+    /// Produce the sender's partial signature. If `signer_signature` is given, it is the partial signature of an
+    /// external signer that holds the sender's nonce and its share of the excess, and it is combined with the share of
+    /// the excess that is known here.
+    fn sign(&mut self, signer_signature: Option<Signature>) -> Result<(), TPE> {
         match &mut self.state {
             SenderState::Finalizing(info) => {
                 let e = build_challenge(&info.public_nonce_sum, &info.metadata);
                 let k = info.offset_blinding_factor.clone();
-                let r = info.private_nonce.clone();
-                let s = Signature::sign(k, r, &e).map_err(TPE::SigningError)?;
+                let s = match signer_signature {
+                    Some(signer_signature) => {
+                        let local = Signature::sign(k, PrivateKey::default(), &e).map_err(TPE::SigningError)?;
+                        &signer_signature + &local
+                    },
+                    None => {
+                        let r = info.private_nonce.clone();
+                        Signature::sign(k, r, &e).map_err(TPE::SigningError)?
+                    },
+                };
                 info.signatures.push(s);
                 Ok(())
             },
@@ -375,6 +386,27 @@ impl SenderTransactionProtocol {
         }
     }
 
+    /// Return the challenge that the sender's partial signature is made on. This is only available once all of the
+    /// recipients' data has been added.
+    pub fn get_kernel_challenge(&self) -> Result<MessageHash, TPE> {
+        match &self.state {
+            SenderState::Finalizing(info) => Ok(build_challenge(&info.public_nonce_sum, &info.metadata)),
+            _ => Err(TPE::InvalidStateError),
+        }
+    }
+
+    /// Return the offset that the sender chose for this transaction
+    pub fn get_offset(&self) -> Result<BlindingFactor, TPE> {
+        match &self.state {
+            SenderState::Initializing(info) |
+            SenderState::Finalizing(info) |
+            SenderState::SingleRoundMessageReady(info) |
+            SenderState::CollectingSingleSignature(info) => Ok(info.offset.clone()),
+            SenderState::FinalizedTransaction(_) => Err(TPE::InvalidStateError),
+            SenderState::Failed(_) => Err(TPE::InvalidStateError),
+        }
+    }
+
     /// Try and finalise the transaction. If the current state is Finalizing, the result will be whether the
     /// transaction was valid or not. If the result is false, the transaction will be in a Failed state. Calling
     /// finalize while in any other state will result in an error.
@@ -384,10 +416,32 @@ impl SenderTransactionProtocol {
     /// the transaction protocol moves to Failed state and we are done; you can't rescue the situation. The function
     /// returns `Ok(false)` in this instance.
     pub fn finalize(&mut self, features: KernelFeatures, factories: &CryptoFactories) -> Result<(), TPE> {
+        self.finalize_with(features, factories, None)
+    }
+
+    /// Finalize a transaction that was built with `with_signer_excess`, as per `finalize`. `signature` is the external
+    /// signer's partial signature on the challenge returned by `get_kernel_challenge`.
+    pub fn finalize_with_signer_signature(
+        &mut self,
+        features: KernelFeatures,
+        factories: &CryptoFactories,
+        signature: Signature,
+    ) -> Result<(), TPE>
+    {
+        self.finalize_with(features, factories, Some(signature))
+    }
+
+    fn finalize_with(
+        &mut self,
+        features: KernelFeatures,
+        factories: &CryptoFactories,
+        signer_signature: Option<Signature>,
+    ) -> Result<(), TPE>
+    {
         // Create the final aggregated signature, moving to the Failed state if anything goes wrong
         match &mut self.state {
             SenderState::Finalizing(_) => {
-                if let Err(e) = self.sign() {
+                if let Err(e) = self.sign(signer_signature) {
                     self.state = SenderState::Failed(e.clone());
                     return Err(e);
                 }
@@ -557,7 +611,7 @@ mod test {
             RewindData,
             TransactionProtocolError,
         },
-        types::{CryptoFactories, PrivateKey, PublicKey},
+        types::{CryptoFactories, PrivateKey, PublicKey, Signature},
     };
     use rand::rngs::OsRng;
     use tari_crypto::{
@@ -724,6 +778,102 @@ mod test {
         assert!(tx.clone().validate_internal_consistency(&factories, None).is_ok());
     }
 
+    #[test]
+    fn single_recipient_with_external_signer() {
+        let factories = CryptoFactories::default();
+        // Alice's parameters. Her signer holds the keys of the input and the change output, and her nonce
+        let a = TestParams::new();
+        // Bob's parameters
+        let b = TestParams::new();
+        let (utxo, input) = make_input(&mut OsRng, MicroTari(25000), &factories.commitment);
+        let mut builder = SenderTransactionProtocol::builder(1);
+        let fee = Fee::calculate(MicroTari(20), 1, 1, 2);
+        builder
+            .with_lock_height(0)
+            .with_fee_per_gram(MicroTari(20))
+            .with_offset(a.offset.clone())
+            .with_signed_input(utxo.clone(), input.value)
+            .with_amount(0, MicroTari(5000));
+        let (calculated_fee, change) = builder.calculate_fee_and_change().unwrap();
+        assert_eq!(calculated_fee, fee);
+        assert_eq!(change, MicroTari(25000) - MicroTari(5000) - fee);
+        let change_output = UnblindedOutput::new(change, a.change_key.clone(), None)
+            .as_transaction_output(&factories)
+            .unwrap();
+        let signer_excess = &a.change_key - &input.spending_key;
+        builder
+            .with_signed_change_output(change_output, change)
+            .with_signer_excess(
+                PublicKey::from_secret_key(&signer_excess),
+                PublicKey::from_secret_key(&a.nonce),
+            );
+        let mut alice = builder.build::<Blake256>(&factories).unwrap();
+        assert!(alice.is_single_round_message_ready());
+        assert_eq!(alice.get_change_amount().unwrap(), change);
+        let msg = alice.build_single_round_message().unwrap();
+        assert_eq!(msg.public_nonce, PublicKey::from_secret_key(&a.nonce));
+
+        let bob_info = SingleReceiverTransactionProtocol::create(
+            &msg,
+            b.nonce,
+            b.spend_key,
+            OutputFeatures::default(),
+            &factories,
+            None,
+        )
+        .unwrap();
+        alice
+            .add_single_recipient_info(bob_info, &factories.range_proof)
+            .unwrap();
+        assert!(alice.is_finalizing());
+        // The signer signs the kernel challenge with its nonce and its share of the excess
+        let challenge = alice.get_kernel_challenge().unwrap();
+        let signature = Signature::sign(signer_excess, a.nonce.clone(), &challenge).unwrap();
+        match alice.finalize_with_signer_signature(KernelFeatures::empty(), &factories, signature) {
+            Ok(_) => (),
+            Err(e) => panic!("{:?}", e),
+        };
+
+        assert!(alice.is_finalized());
+        let tx = alice.get_transaction().unwrap();
+        assert_eq!(tx.offset, a.offset);
+        assert_eq!(tx.body.kernels()[0].fee, fee);
+        assert_eq!(tx.body.inputs().len(), 1);
+        assert_eq!(tx.body.inputs()[0], utxo);
+        assert_eq!(tx.body.outputs().len(), 2);
+        assert!(tx.clone().validate_internal_consistency(&factories, None).is_ok());
+    }
+
+    #[test]
+    fn external_signer_with_wrong_signature_fails() {
+        let factories = CryptoFactories::default();
+        let a = TestParams::new();
+        let (utxo, input) = make_input(&mut OsRng, MicroTari(1200), &factories.commitment);
+        let mut builder = SenderTransactionProtocol::builder(0);
+        builder
+            .with_lock_height(0)
+            .with_fee_per_gram(MicroTari(10))
+            .with_offset(a.offset.clone())
+            .with_signed_input(utxo, input.value);
+        let (_, change) = builder.calculate_fee_and_change().unwrap();
+        let output = UnblindedOutput::new(change, a.spend_key.clone(), None)
+            .as_transaction_output(&factories)
+            .unwrap();
+        builder.with_signed_output(output, change).with_signer_excess(
+            PublicKey::from_secret_key(&(&a.spend_key - &input.spending_key)),
+            PublicKey::from_secret_key(&a.nonce),
+        );
+        let mut sender = builder.build::<Blake256>(&factories).unwrap();
+        assert!(sender.is_finalizing());
+        // A signature with a key other than the signer's share of the excess doesn't validate
+        let challenge = sender.get_kernel_challenge().unwrap();
+        let signature = Signature::sign(a.spend_key.clone(), a.nonce, &challenge).unwrap();
+        assert!(sender
+            .finalize_with_signer_signature(KernelFeatures::empty(), &factories, signature)
+            .is_err());
+        assert!(sender.is_failed());
+    }
+
     #[test]
     fn single_recipient_range_proof_fail() {
         let factories = CryptoFactories::new(32);
@@ -66,12 +66,17 @@ pub struct SenderTransactionInitializer {
     fee_per_gram: Option<MicroTari>,
     inputs: Vec<TransactionInput>,
     unblinded_inputs: Vec<UnblindedOutput>,
+    signed_input_value: MicroTari,
     outputs: Vec<UnblindedOutput>,
+    output_rewind_data: Vec<Option<RewindData>>,
+    signed_outputs: Vec<(TransactionOutput, MicroTari)>,
+    signed_change: MicroTari,
     change_secret: Option<BlindingFactor>,
     rewind_data: Option<RewindData>,
     offset: Option<BlindingFactor>,
     excess_blinding_factor: BlindingFactor,
     private_nonce: Option<PrivateKey>,
+    signer_excess: Option<(PublicKey, PublicKey)>,
     message: Option<String>,
     prevent_fee_gt_amount: bool,
 }
@@ -96,11 +101,16 @@ impl SenderTransactionInitializer {
             fee_per_gram: None,
             inputs: Vec::new(),
             unblinded_inputs: Vec::new(),
+            signed_input_value: MicroTari(0),
             outputs: Vec::new(),
+            output_rewind_data: Vec::new(),
+            signed_outputs: Vec::new(),
+            signed_change: MicroTari(0),
             change_secret: None,
             rewind_data: None,
             offset: None,
             private_nonce: None,
+            signer_excess: None,
             excess_blinding_factor: BlindingFactor::default(),
             message: None,
             prevent_fee_gt_amount: true,
@@ -145,6 +155,45 @@ impl SenderTransactionInitializer {
     pub fn with_output(&mut self, output: UnblindedOutput) -> &mut Self {
         self.excess_blinding_factor = &self.excess_blinding_factor + &output.spending_key;
         self.outputs.push(output);
+        self.output_rewind_data.push(None);
+        self
+    }
+
+    /// Adds an output with a rewindable range proof to the transaction. This can be called multiple times
+    pub fn with_rewindable_output(&mut self, output: UnblindedOutput, rewind_data: RewindData) -> &mut Self {
+        self.excess_blinding_factor = &self.excess_blinding_factor + &output.spending_key;
+        self.outputs.push(output);
+        self.output_rewind_data.push(Some(rewind_data));
+        self
+    }
+
+    /// Adds an input whose blinding factor is held by an external signer. The signer's share of the excess is provided
+    /// with `with_signer_excess`.
+    pub fn with_signed_input(&mut self, utxo: TransactionInput, value: MicroTari) -> &mut Self {
+        self.inputs.push(utxo);
+        self.signed_input_value += value;
+        self
+    }
+
+    /// Adds an output that was built by an external signer, which holds its blinding factor. The signer's share of
+    /// the excess is provided with `with_signer_excess`.
+    pub fn with_signed_output(&mut self, output: TransactionOutput, value: MicroTari) -> &mut Self {
+        self.signed_outputs.push((output, value));
+        self
+    }
+
+    /// Adds a change output that was built by an external signer. Its value is taken from
+    /// `calculate_fee_and_change` and is reported as the change of the transaction.
+    pub fn with_signed_change_output(&mut self, output: TransactionOutput, value: MicroTari) -> &mut Self {
+        self.signed_change += value;
+        self.with_signed_output(output, value)
+    }
+
+    /// Provide the public excess and public nonce of an external signer that holds the blinding factors of the signed
+    /// inputs and outputs. The signer keeps its share of the excess and its nonce, so no private nonce is needed and
+    /// the sender's partial signature is completed with `finalize_with_signer_signature`.
+    pub fn with_signer_excess(&mut self, public_excess: PublicKey, public_nonce: PublicKey) -> &mut Self {
+        self.signer_excess = Some((public_excess, public_nonce));
         self
     }
 
@@ -186,15 +235,17 @@ impl SenderTransactionInitializer {
         self
     }
 
-    /// Tries to make a change output with the given transaction parameters and add it to the set of outputs. The total
-    /// fee, including the additional change output (if any) is returned along with the amount of change.
-    /// The change output **always has default output features**.
-    fn add_change_if_required(&mut self) -> Result<(MicroTari, MicroTari, Option<UnblindedOutput>), String> {
+    /// Calculate the total fee and the amount of change of the transaction as it stands. A caller that creates the
+    /// change output itself, e.g. with an external signer, adds an output of this value before the transaction is
+    /// built; the transaction then needs no further change.
+    pub fn calculate_fee_and_change(&self) -> Result<(MicroTari, MicroTari), String> {
         // The number of outputs excluding a possible residual change output
-        let num_outputs = self.outputs.len() + self.num_recipients;
+        let num_outputs = self.outputs.len() + self.signed_outputs.len() + self.num_recipients;
         let num_inputs = self.inputs.len();
-        let total_being_spent = self.unblinded_inputs.iter().map(|i| i.value).sum::<MicroTari>();
-        let total_to_self = self.outputs.iter().map(|o| o.value).sum::<MicroTari>();
+        let total_being_spent =
+            self.unblinded_inputs.iter().map(|i| i.value).sum::<MicroTari>() + self.signed_input_value;
+        let total_to_self = self.outputs.iter().map(|o| o.value).sum::<MicroTari>() +
+            self.signed_outputs.iter().map(|(_, v)| *v).sum::<MicroTari>();
         let total_amount = self.amounts.sum().ok_or_else(|| "Not all amounts have been provided")?;
         let fee_per_gram = self.fee_per_gram.ok_or_else(|| "Fee per gram was not provided")?;
         let fee_without_change = Fee::calculate(fee_per_gram, 1, num_inputs, num_outputs);
@@ -204,27 +255,36 @@ impl SenderTransactionInitializer {
         let change_amount = total_being_spent.checked_sub(total_to_self + total_amount + fee_without_change);
         match change_amount {
             None => Err("You are spending more than you're providing".into()),
-            Some(MicroTari(0)) => Ok((fee_without_change, MicroTari(0), None)),
+            Some(MicroTari(0)) => Ok((fee_without_change, MicroTari(0))),
             Some(v) => {
                 let change_amount = v.checked_sub(extra_fee);
                 match change_amount {
                     // You can't win. Just add the change to the fee (which is less than the cost of adding another
                     // output and go without a change output
-                    None => Ok((fee_without_change + v, MicroTari(0), None)),
-                    Some(MicroTari(0)) => Ok((fee_without_change + v, MicroTari(0), None)),
-                    Some(v) => {
-                        let change_key = self
-                            .change_secret
-                            .as_ref()
-                            .ok_or_else(|| "Change spending key was not provided")?;
-                        let change_unblinded_output = UnblindedOutput::new(v, change_key.clone(), None);
-                        Ok((fee_with_change, v, Some(change_unblinded_output)))
-                    },
+                    None => Ok((fee_without_change + v, MicroTari(0))),
+                    Some(MicroTari(0)) => Ok((fee_without_change + v, MicroTari(0))),
+                    Some(v) => Ok((fee_with_change, v)),
                 }
             },
         }
     }
 
+    /// Tries to make a change output with the given transaction parameters and add it to the set of outputs. The total
+    /// fee, including the additional change output (if any) is returned along with the amount of change.
+    /// The change output **always has default output features**.
+    fn add_change_if_required(&mut self) -> Result<(MicroTari, MicroTari, Option<UnblindedOutput>), String> {
+        let (fee, change) = self.calculate_fee_and_change()?;
+        if change == MicroTari(0) {
+            return Ok((fee, change, None));
+        }
+        let change_key = self
+            .change_secret
+            .as_ref()
+            .ok_or_else(|| "Change spending key was not provided")?;
+        let change_unblinded_output = UnblindedOutput::new(change, change_key.clone(), None);
+        Ok((fee, change, Some(change_unblinded_output)))
+    }
+
     fn check_value<T>(name: &str, val: &Option<T>, vec: &mut Vec<String>) {
         if val.is_none() {
             vec.push(name.to_string());
@@ -253,7 +313,9 @@ impl SenderTransactionInitializer {
         Self::check_value("Missing Lock Height", &self.lock_height, &mut message);
         Self::check_value("Missing Fee per gram", &self.fee_per_gram, &mut message);
         Self::check_value("Missing Offset", &self.offset, &mut message);
-        Self::check_value("Missing Private nonce", &self.private_nonce, &mut message);
+        if self.signer_excess.is_none() {
+            Self::check_value("Missing Private nonce", &self.private_nonce, &mut message);
+        }
         if !message.is_empty() {
             return self.build_err(&message.join(","));
         }
@@ -285,7 +347,11 @@ impl SenderTransactionInitializer {
         let mut outputs = match self
             .outputs
             .iter()
-            .map(|o| o.as_transaction_output(factories))
+            .zip(self.output_rewind_data.iter())
+            .map(|(o, rewind_data)| match rewind_data {
+                Some(rewind_data) => o.as_rewindable_transaction_output(factories, rewind_data),
+                None => o.as_transaction_output(factories),
+            })
             .collect::<Result<Vec<TransactionOutput>, _>>()
         {
             Ok(o) => o,
@@ -293,6 +359,7 @@ impl SenderTransactionInitializer {
                 return self.build_err(&e.to_string());
             },
         };
+        outputs.extend(self.signed_outputs.iter().map(|(o, _)| o.clone()));
 
         if let Some(change_unblinded_output) = change_output {
             self.excess_blinding_factor = self.excess_blinding_factor + change_unblinded_output.spending_key.clone();
@@ -322,13 +389,25 @@ impl SenderTransactionInitializer {
             return self.build_err("Too many outputs in transaction");
         }
 
-        let nonce = self.private_nonce.clone().unwrap();
-        let public_nonce = PublicKey::from_secret_key(&nonce);
         let offset = self.offset.clone().unwrap();
         let excess_blinding_factor = self.excess_blinding_factor.clone();
         let offset_blinding_factor = &excess_blinding_factor - &offset;
-        let excess = PublicKey::from_secret_key(&offset_blinding_factor);
-        let amount_to_self = self.outputs.iter().fold(MicroTari::from(0), |sum, o| sum + o.value);
+        // When an external signer holds part of the excess, the private nonce is the signer's and
+        // `offset_blinding_factor` is only the share of the excess that is known here
+        let (nonce, public_nonce, excess) = match self.signer_excess.clone() {
+            Some((signer_excess, signer_nonce)) => (
+                PrivateKey::default(),
+                signer_nonce,
+                &signer_excess + &PublicKey::from_secret_key(&offset_blinding_factor),
+            ),
+            None => {
+                let nonce = self.private_nonce.clone().unwrap();
+                let public_nonce = PublicKey::from_secret_key(&nonce);
+                (nonce, public_nonce, PublicKey::from_secret_key(&offset_blinding_factor))
+            },
+        };
+        let amount_to_self = self.outputs.iter().fold(MicroTari::from(0), |sum, o| sum + o.value) +
+            self.signed_outputs.iter().map(|(_, v)| *v).sum::<MicroTari>();
 
         let recipient_info = match self.num_recipients {
             0 => RecipientInfo::None,
@@ -364,7 +443,7 @@ impl SenderTransactionInitializer {
             amount_to_self,
             ids,
             amounts: self.amounts.into_vec(),
-            change,
+            change: change + self.signed_change,
             metadata: TransactionMetadata {
                 fee: total_fee,
                 lock_height: self.lock_height.unwrap(),
@@ -23,7 +23,7 @@ use crate::{
     blocks::{Block, BlockValidationError},
     chain_storage,
     chain_storage::{BlockchainBackend, ChainBlock, MmrTree},
-    consensus::ConsensusManager,
+    consensus::{ConsensusConstants, ConsensusManager, ValidationRules},
     transactions::{
         aggregated_body::AggregateBody,
         transaction::{KernelFeatures, OutputFlags, TransactionError},
@@ -35,6 +35,7 @@ use crate::{
             check_block_weight,
             check_coinbase_output,
             check_cut_through,
+            check_output_features_versions,
             is_all_unique_and_sorted,
         },
         traits::PostOrphanBodyValidation,
@@ -68,6 +69,7 @@ impl OrphanBlockValidator {
 impl OrphanValidation for OrphanBlockValidator {
     /// The consensus checks that are done (in order of cheapest to verify to most expensive):
     /// 1. Is the block weight of the block under the prescribed limit?
+    /// 1. Are the output features versions accepted at this height?
     /// 1. Does it contain only unique inputs and outputs?
     /// 1. Where all the rules for the spent outputs followed?
     /// 1. Was cut through applied in the block?
@@ -86,8 +88,15 @@ impl OrphanValidation for OrphanBlockValidator {
         };
         trace!(target: LOG_TARGET, "Validating {}", block_id);
 
-        check_block_weight(&block, &self.rules.consensus_constants(block.header.height))?;
+        let constants = self.rules.consensus_constants(block.header.height);
+        check_block_weight(&block, constants)?;
         trace!(target: LOG_TARGET, "SV - Block weight is ok for {} ", &block_id);
+        check_output_features_versions(&block.body, block.header.height, constants)?;
+        trace!(
+            target: LOG_TARGET,
+            "SV - Output features versions are ok for {} ",
+            &block_id
+        );
 
         trace!(
             target: LOG_TARGET,
@@ -313,7 +322,7 @@ impl<B: BlockchainBackend> BlockValidator<B> {
         Ok(())
     }
 
-    fn check_outputs(&self, block: &Block) -> Result<(), ValidationError> {
+    fn check_outputs(&self, block: &Block, constants: &ConsensusConstants) -> Result<(), ValidationError> {
         let outputs = block.body.outputs();
         let mut coinbase_output = None;
         for (j, output) in outputs.iter().enumerate() {
@@ -341,6 +350,20 @@ impl<B: BlockchainBackend> BlockValidator<B> {
             },
         };
 
+        if constants
+            .validation_rules()
+            .contains(ValidationRules::COINBASE_MATURITY) &&
+            coinbase_output.features.maturity < block.header.height + constants.coinbase_lock_height()
+        {
+            warn!(
+                target: LOG_TARGET,
+                "Block #{} failed to validate: coinbase {} matures too early", block.header.height, coinbase_output
+            );
+            return Err(ValidationError::TransactionError(
+                TransactionError::InvalidCoinbaseMaturity,
+            ));
+        }
+
         let mut coinbase_kernel = None;
         for kernel in block.body.kernels() {
             if kernel.features.contains(KernelFeatures::COINBASE_KERNEL) {
@@ -438,6 +461,7 @@ impl<B: BlockchainBackend> BlockValidator<B> {
 impl<B: BlockchainBackend> CandidateBlockBodyValidation<B> for BlockValidator<B> {
     /// The following consensus checks are done:
     /// 1. Does the block satisfy the stateless checks?
+    /// 1. Are the output features versions and the validation rules in effect at this height satisfied?
     /// 1. Are the block header MMR roots valid?
     fn validate_body(&self, block: &ChainBlock, backend: &B) -> Result<(), ValidationError> {
         let block_id = format!("block #{}", block.block.header.height);
@@ -446,9 +470,15 @@ impl<B: BlockchainBackend> CandidateBlockBodyValidation<B> for BlockValidator<B>
         let constants = self.rules.consensus_constants(block.block.header.height);
         check_block_weight(&block.block, &constants)?;
         trace!(target: LOG_TARGET, "SV - Block weight is ok for {} ", &block_id);
+        check_output_features_versions(&block.block.body, block.block.header.height, constants)?;
+        trace!(
+            target: LOG_TARGET,
+            "SV - Output features versions are ok for {} ",
+            &block_id
+        );
 
         self.check_inputs(&block.block)?;
-        self.check_outputs(&block.block)?;
+        self.check_outputs(&block.block, constants)?;
 
         check_accounting_balance(&block.block, &self.rules, &self.factories)?;
         trace!(target: LOG_TARGET, "SV - accounting balance correct for {}", &block_id);
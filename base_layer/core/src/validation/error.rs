@@ -73,6 +73,8 @@ pub enum ValidationError {
     UnsortedOrDuplicateInput,
     #[error("Duplicate or unsorted output found in block body")]
     UnsortedOrDuplicateOutput,
+    #[error("Output features version {version} is not accepted at height {height}")]
+    InvalidOutputFeaturesVersion { version: u8, height: u64 },
     #[error("Error in merge mine data:{0}")]
     MergeMineError(#[from] MergeMineError),
 }
@@ -89,4 +91,51 @@ impl ValidationError {
     pub fn custom_error<T: ToString>(err: T) -> Self {
         ValidationError::CustomError(err.to_string())
     }
+
+    /// Returns true if the error proves that the block itself is invalid, e.g. invalid proof of work, signatures,
+    /// range proofs or merkle roots. Errors that depend on this node's chain state or clock, such as an unknown
+    /// previous block, unknown inputs or a timestamp beyond the future time limit, return false because an honest peer
+    /// with a different view of the chain may have sent the block in good faith.
+    pub fn proves_block_is_invalid(&self) -> bool {
+        use ValidationError::*;
+        match self {
+            BlockHeaderError(err) => match err {
+                BlockHeaderValidationError::ProofOfWorkError(err) => pow_error_proves_block_is_invalid(err),
+                BlockHeaderValidationError::InvalidVersion { .. } => true,
+                _ => false,
+            },
+            BlockError(err) => !matches!(err, BlockValidationError::InvalidInput),
+            TransactionError(_) |
+            InvalidAccountingBalance |
+            InvalidOutputMr |
+            InvalidKernelMr |
+            InvalidRangeProofMr |
+            UnsortedOrDuplicateInput |
+            UnsortedOrDuplicateOutput |
+            InvalidOutputFeaturesVersion { .. } => true,
+            ProofOfWorkError(err) => pow_error_proves_block_is_invalid(err),
+            MergeMineError(err) => merge_mine_error_proves_block_is_invalid(err),
+            _ => false,
+        }
+    }
+}
+
+fn pow_error_proves_block_is_invalid(err: &PowError) -> bool {
+    match err {
+        PowError::InvalidProofOfWork |
+        PowError::AchievedDifficultyTooLow { .. } |
+        PowError::UnexpectedPowData(_) |
+        PowError::AlgorithmNotAccepted { .. } => true,
+        PowError::MergeMineError(err) => merge_mine_error_proves_block_is_invalid(err),
+        // The expected target difficulty and the seed age depend on our view of the chain
+        _ => false,
+    }
+}
+
+fn merge_mine_error_proves_block_is_invalid(err: &MergeMineError) -> bool {
+    // Serialization, hashing and RandomX VM errors are local failures
+    matches!(
+        err,
+        MergeMineError::DeserializeError(_) | MergeMineError::ValidationError(_) | MergeMineError::HexError(_)
+    )
 }
@@ -11,7 +11,9 @@ use crate::{
     proof_of_work::{randomx_factory::RandomXFactory, Difficulty},
     validation::{
         helpers::{
+            check_blockchain_version,
             check_header_timestamp_greater_than_median,
+            check_pow_algorithm,
             check_pow_data,
             check_target_difficulty,
             check_timestamp_ftl,
@@ -86,7 +88,9 @@ impl HeaderValidator {
 
 impl<B: BlockchainBackend> HeaderValidation<B> for HeaderValidator {
     /// The consensus checks that are done (in order of cheapest to verify to most expensive):
+    /// 1. Is the header version accepted at this height?
     /// 1. Is the block timestamp within the Future Time Limit (FTL)?
+    /// 1. Is the Proof of Work algorithm accepted at this height?
     /// 1. Is the Proof of Work valid?
     /// 1. Is the achieved difficulty of this block >= the target difficulty for this block?
 
@@ -97,9 +101,15 @@ impl<B: BlockchainBackend> HeaderValidation<B> for HeaderValidator {
         previous_data: &BlockHeaderAccumulatedData,
     ) -> Result<BlockHeaderAccumulatedDataBuilder, ValidationError>
     {
-        check_timestamp_ftl(&header, &self.rules)?;
         let hash = header.hash();
         let header_id = format!("header #{} ({})", header.height, header.hash().to_hex());
+        check_blockchain_version(header, self.rules.consensus_constants(header.height))?;
+        trace!(
+            target: LOG_TARGET,
+            "BlockHeader validation: Version is ok for {} ",
+            header_id
+        );
+        check_timestamp_ftl(&header, &self.rules)?;
         trace!(
             target: LOG_TARGET,
             "BlockHeader validation: FTL timestamp is ok for {} ",
@@ -111,6 +121,7 @@ impl<B: BlockchainBackend> HeaderValidation<B> for HeaderValidator {
             "BlockHeader validation: Median timestamp is ok for {} ",
             header_id
         );
+        check_pow_algorithm(header, self.rules.consensus_constants(header.height))?;
         check_pow_data(header, &self.rules, backend)?;
         let (achieved, target) = self.check_achieved_and_target_difficulty(backend, header)?;
         let accum_data = BlockHeaderAccumulatedDataBuilder::default()
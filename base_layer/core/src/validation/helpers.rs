@@ -28,16 +28,8 @@ use crate::{
     },
     chain_storage::BlockchainBackend,
     consensus::{ConsensusConstants, ConsensusManager},
-    proof_of_work::{
-        monero_difficulty,
-        monero_rx::MoneroData,
-        randomx_factory::RandomXFactory,
-        sha3_difficulty,
-        Difficulty,
-        PowAlgorithm,
-        PowError,
-    },
-    transactions::types::CryptoFactories,
+    proof_of_work::{randomx_factory::RandomXFactory, Difficulty, PowError},
+    transactions::{aggregated_body::AggregateBody, types::CryptoFactories},
     validation::ValidationError,
 };
 use log::*;
@@ -118,52 +110,111 @@ pub fn check_header_timestamp_greater_than_median(
     Ok(())
 }
 
-/// Check the PoW data in the BlockHeader. This currently only applies to blocks merged mined with Monero.
-pub fn check_pow_data<B: BlockchainBackend>(
+/// Checks that the header version is accepted by the consensus constants in effect at the header's height.
+pub fn check_blockchain_version(
     block_header: &BlockHeader,
-    rules: &ConsensusManager,
-    db: &B,
+    consensus_constants: &ConsensusConstants,
 ) -> Result<(), ValidationError>
 {
-    use PowAlgorithm::*;
-    match block_header.pow.pow_algo {
-        Monero => {
-            let monero_data =
-                MoneroData::from_header(block_header).map_err(|e| ValidationError::CustomError(e.to_string()))?;
-            let seed_height = db.fetch_monero_seed_first_seen_height(&monero_data.key)?;
-            if (seed_height != 0) &&
-                (block_header.height - seed_height >
-                    rules.consensus_constants(block_header.height).max_randomx_seed_height())
-            {
-                return Err(ValidationError::BlockHeaderError(
-                    BlockHeaderValidationError::OldSeedHash,
-                ));
-            }
+    if consensus_constants
+        .valid_blockchain_version_range()
+        .contains(&block_header.version)
+    {
+        Ok(())
+    } else {
+        warn!(
+            target: LOG_TARGET,
+            "Header #{} has version {} which is not in the accepted range {:?}",
+            block_header.height,
+            block_header.version,
+            consensus_constants.valid_blockchain_version_range()
+        );
+        Err(BlockHeaderValidationError::InvalidVersion {
+            version: block_header.version,
+            height: block_header.height,
+        }
+        .into())
+    }
+}
 
-            Ok(())
-        },
-        Blake | Sha3 => {
-            if !block_header.pow.pow_data.is_empty() {
-                return Err(ValidationError::CustomError(
-                    "Proof of work data must be empty for Sha3 blocks".to_string(),
-                ));
-            }
-            Ok(())
+/// Checks that the features version of every output is accepted by the consensus constants in effect at `height`.
+pub fn check_output_features_versions(
+    body: &AggregateBody,
+    height: u64,
+    consensus_constants: &ConsensusConstants,
+) -> Result<(), ValidationError>
+{
+    let valid_versions = consensus_constants.valid_output_features_version_range();
+    match body
+        .outputs()
+        .iter()
+        .find(|output| !valid_versions.contains(&output.features.version))
+    {
+        Some(output) => {
+            warn!(
+                target: LOG_TARGET,
+                "Output {} has a features version that is not accepted at height {}", output, height
+            );
+            Err(ValidationError::InvalidOutputFeaturesVersion {
+                version: output.features.version,
+                height,
+            })
         },
+        None => Ok(()),
     }
 }
 
+/// Check that the PoW algorithm of the header is accepted at its height.
+pub fn check_pow_algorithm(
+    block_header: &BlockHeader,
+    consensus_constants: &ConsensusConstants,
+) -> Result<(), ValidationError>
+{
+    let algo = block_header.pow_algo();
+    if consensus_constants.accepts_pow_algorithm(algo) {
+        return Ok(());
+    }
+    warn!(
+        target: LOG_TARGET,
+        "Header #{} was mined with {} which is not accepted at this height", block_header.height, algo
+    );
+    Err(ValidationError::BlockHeaderError(
+        BlockHeaderValidationError::ProofOfWorkError(PowError::AlgorithmNotAccepted {
+            algo,
+            height: block_header.height,
+        }),
+    ))
+}
+
+/// Check the PoW data in the BlockHeader using the rules of the proof of work algorithm the header was mined with
+pub fn check_pow_data<B: BlockchainBackend>(
+    block_header: &BlockHeader,
+    rules: &ConsensusManager,
+    db: &B,
+) -> Result<(), ValidationError>
+{
+    block_header
+        .pow_algo()
+        .implementation()
+        .check_pow_data(block_header, rules.consensus_constants(block_header.height), db)
+        .map_err(|e| match e {
+            PowError::OldSeedHash { .. } => ValidationError::BlockHeaderError(BlockHeaderValidationError::OldSeedHash),
+            PowError::FatalStorageError(e) => ValidationError::FatalStorageError(e),
+            e => ValidationError::BlockHeaderError(BlockHeaderValidationError::ProofOfWorkError(e)),
+        })
+}
+
 pub fn check_target_difficulty(
     block_header: &BlockHeader,
     target: Difficulty,
     randomx_factory: &RandomXFactory,
 ) -> Result<Difficulty, ValidationError>
 {
-    let achieved = match block_header.pow_algo() {
-        PowAlgorithm::Monero => monero_difficulty(block_header, randomx_factory)?,
-        PowAlgorithm::Blake => unimplemented!(),
-        PowAlgorithm::Sha3 => sha3_difficulty(block_header),
-    };
+    let achieved = block_header
+        .pow_algo()
+        .implementation()
+        .achieved_difficulty(block_header, randomx_factory)
+        .map_err(|e| ValidationError::BlockHeaderError(BlockHeaderValidationError::ProofOfWorkError(e)))?;
     if achieved < target {
         warn!(
             target: LOG_TARGET,
@@ -21,10 +21,11 @@
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use crate::{
-    blocks::BlockHeader,
+    blocks::{BlockHeader, BlockHeaderValidationError, BlockValidationError},
     consensus::{ConsensusManagerBuilder, Network},
+    proof_of_work::PowError,
     test_helpers::blockchain::create_store_with_consensus,
-    validation::header_iter::HeaderIter,
+    validation::{header_iter::HeaderIter, ValidationError},
 };
 
 #[test]
@@ -261,3 +262,35 @@ fn chain_balance_validation() {
     // validator.validate(&header4).unwrap_err();
     unimplemented!();
 }
+
+#[test]
+fn only_block_specific_validation_errors_prove_a_block_is_invalid() {
+    let invalid = vec![
+        ValidationError::BlockHeaderError(BlockHeaderValidationError::ProofOfWorkError(
+            PowError::InvalidProofOfWork,
+        )),
+        ValidationError::BlockError(BlockValidationError::MismatchedMmrRoots),
+        ValidationError::BlockError(BlockValidationError::BlockTooLarge),
+        ValidationError::InvalidKernelMr,
+        ValidationError::UnsortedOrDuplicateOutput,
+    ];
+    for err in invalid {
+        assert!(err.proves_block_is_invalid(), "{}", err);
+    }
+
+    let neutral = vec![
+        ValidationError::BlockHeaderError(BlockHeaderValidationError::InvalidTimestampFutureTimeLimit),
+        ValidationError::BlockHeaderError(BlockHeaderValidationError::InvalidChaining),
+        ValidationError::BlockError(BlockValidationError::InvalidInput),
+        ValidationError::PreviousHashNotFound,
+        ValidationError::UnknownInputs,
+        ValidationError::FatalStorageError("MMR position not found".to_string()),
+        ValidationError::ProofOfWorkError(PowError::InvalidTargetDifficulty {
+            expected: 2.into(),
+            got: 1.into(),
+        }),
+    ];
+    for err in neutral {
+        assert!(!err.proves_block_is_invalid(), "{}", err);
+    }
+}
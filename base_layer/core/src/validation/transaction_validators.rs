@@ -22,9 +22,10 @@
 
 use crate::{
     chain_storage::{BlockchainBackend, BlockchainDatabase, MmrTree},
+    consensus::ConsensusManager,
     tari_utilities::hex::Hex,
     transactions::{transaction::Transaction, types::CryptoFactories},
-    validation::{MempoolTransactionValidation, ValidationError},
+    validation::{helpers::check_output_features_versions, MempoolTransactionValidation, ValidationError},
 };
 use log::*;
 use tari_crypto::tari_utilities::hash::Hashable;
@@ -36,22 +37,28 @@ pub const LOG_TARGET: &str = "c::val::transaction_validators";
 /// 1. The sum of inputs, outputs and fees equal the (public excess value + offset)
 /// 1. The signature signs the canonical message with the private excess
 /// 1. Range proofs of the outputs are valid
+/// 1. The output features versions are accepted at the height of the next block
 ///
 /// This function does NOT check that inputs come from the UTXO set
-pub struct TxInternalConsistencyValidator {
+#[derive(Clone)]
+pub struct TxInternalConsistencyValidator<B> {
+    db: BlockchainDatabase<B>,
+    rules: ConsensusManager,
     factories: CryptoFactories,
 }
 
-impl TxInternalConsistencyValidator {
-    pub fn new(factories: CryptoFactories) -> Self {
-        Self { factories }
+impl<B: BlockchainBackend> TxInternalConsistencyValidator<B> {
+    pub fn new(db: BlockchainDatabase<B>, rules: ConsensusManager, factories: CryptoFactories) -> Self {
+        Self { db, rules, factories }
     }
 }
 
-impl MempoolTransactionValidation for TxInternalConsistencyValidator {
+impl<B: BlockchainBackend> MempoolTransactionValidation for TxInternalConsistencyValidator<B> {
     fn validate(&self, tx: &Transaction) -> Result<(), ValidationError> {
         tx.validate_internal_consistency(&self.factories, None)
             .map_err(ValidationError::TransactionError)?;
+        let height = self.db.get_height()? + 1;
+        check_output_features_versions(&tx.body, height, self.rules.consensus_constants(height))?;
         Ok(())
     }
 }
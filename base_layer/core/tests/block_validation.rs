@@ -20,30 +20,35 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use crate::helpers::block_builders::chain_block_with_new_coinbase;
+use crate::helpers::block_builders::{chain_block_with_coinbase, chain_block_with_new_coinbase, create_coinbase};
 use monero::{blockdata::block::Block as MoneroBlock, consensus::deserialize};
 use std::{collections::HashMap, sync::Arc};
 use tari_core::{
     blocks::{Block, BlockHeaderValidationError},
-    chain_storage::{BlockchainDatabase, BlockchainDatabaseConfig, ChainStorageError, Validators},
+    chain_storage::{BlockchainDatabase, BlockchainDatabaseConfig, ChainBlock, ChainStorageError, Validators},
     consensus::{
         consensus_constants::PowAlgorithmConstants,
+        ConsensusConstants,
         ConsensusConstantsBuilder,
+        ConsensusManager,
         ConsensusManagerBuilder,
         Network,
+        ValidationRules,
     },
     proof_of_work::{
         monero_rx,
         monero_rx::MoneroData,
         randomx_factory::{RandomXConfig, RandomXFactory},
         PowAlgorithm,
+        PowError,
     },
-    test_helpers::blockchain::{create_store_with_consensus_and_validators, create_test_db},
-    transactions::types::CryptoFactories,
+    test_helpers::blockchain::{create_store_with_consensus_and_validators, create_test_db, TempDatabase},
+    transactions::{transaction::TransactionError, types::CryptoFactories},
     validation::{
-        block_validators::{BodyOnlyValidator, OrphanBlockValidator},
+        block_validators::{BlockValidator, BodyOnlyValidator, OrphanBlockValidator},
         header_validator::HeaderValidator,
         mocks::MockValidator,
+        CandidateBlockBodyValidation,
         ValidationError,
     },
 };
@@ -141,6 +146,228 @@ fn test_monero_blocks() {
     db.add_block(Arc::new(block_3)).unwrap().assert_added();
 }
 
+/// LocalNet consensus rules that switch to the `fork` constants at their effective height
+fn rules_with_fork(fork: ConsensusConstants) -> ConsensusManager {
+    let network = Network::LocalNet;
+    ConsensusManagerBuilder::new(network)
+        .with_consensus_constants(ConsensusConstantsBuilder::new(network).build())
+        .with_consensus_constants(fork)
+        .build()
+}
+
+/// Create the block following `prev`, with a coinbase of the given maturity and output features version
+fn block_with_coinbase(
+    db: &BlockchainDatabase<TempDatabase>,
+    prev: &ChainBlock,
+    rules: &ConsensusManager,
+    factories: &CryptoFactories,
+    maturity: u64,
+    features_version: u8,
+) -> Block
+{
+    let reward = rules.emission_schedule().block_reward(prev.height() + 1);
+    let (mut coinbase, kernel, _) = create_coinbase(factories, reward, maturity);
+    coinbase.features.version = features_version;
+    let template = chain_block_with_coinbase(prev, vec![], coinbase, kernel, rules);
+    db.prepare_block_merkle_roots(template).unwrap()
+}
+
+#[test]
+fn header_version_activation() {
+    let factories = CryptoFactories::default();
+    let fork = ConsensusConstantsBuilder::new(Network::LocalNet)
+        .with_effective_from_height(3)
+        .with_blockchain_version(2)
+        .with_min_blockchain_version(2)
+        .build();
+    let rules = rules_with_fork(fork);
+    let db = create_store_with_consensus_and_validators(
+        &rules,
+        Validators::new(
+            MockValidator::new(true),
+            HeaderValidator::new(rules.clone(), RandomXFactory::default()),
+            MockValidator::new(true),
+        ),
+    );
+    let mut prev = db.fetch_block(0).unwrap().try_into_chain_block().unwrap();
+    for height in 1..=4 {
+        let (expected_version, other_version) = if height < 3 { (1, 2) } else { (2, 1) };
+        let (template, _) = chain_block_with_new_coinbase(&prev, vec![], &rules, &factories);
+        let block = db.prepare_block_merkle_roots(template).unwrap();
+        assert_eq!(block.header.version, expected_version);
+
+        let mut other = block.clone();
+        other.header.version = other_version;
+        match db.add_block(Arc::new(other)) {
+            Err(ChainStorageError::ValidationError {
+                source:
+                    ValidationError::BlockHeaderError(BlockHeaderValidationError::InvalidVersion {
+                        version,
+                        height: rejected_height,
+                    }),
+            }) => {
+                assert_eq!(version, other_version);
+                assert_eq!(rejected_height, height);
+            },
+            res => panic!(
+                "Unexpected result for version {} at height {}: {:?}",
+                other_version, height, res
+            ),
+        }
+
+        prev = db.add_block(Arc::new(block)).unwrap().assert_added();
+    }
+}
+
+#[test]
+fn output_features_version_activation() {
+    let factories = CryptoFactories::default();
+    let fork = ConsensusConstantsBuilder::new(Network::LocalNet)
+        .with_effective_from_height(3)
+        .with_output_features_version(1)
+        .with_min_output_features_version(1)
+        .build();
+    let rules = rules_with_fork(fork);
+    let db = create_store_with_consensus_and_validators(
+        &rules,
+        Validators::new(
+            BodyOnlyValidator::default(),
+            HeaderValidator::new(rules.clone(), RandomXFactory::default()),
+            OrphanBlockValidator::new(rules.clone(), factories.clone()),
+        ),
+    );
+    let lock_height = rules.consensus_constants(0).coinbase_lock_height();
+    let mut prev = db.fetch_block(0).unwrap().try_into_chain_block().unwrap();
+    for height in 1..=4 {
+        let (valid_version, invalid_version) = if height < 3 { (0, 1) } else { (1, 0) };
+        let block = block_with_coinbase(&db, &prev, &rules, &factories, height + lock_height, invalid_version);
+        match db.add_block(Arc::new(block)) {
+            Err(ChainStorageError::ValidationError {
+                source:
+                    ValidationError::InvalidOutputFeaturesVersion {
+                        version,
+                        height: rejected_height,
+                    },
+            }) => {
+                assert_eq!(version, invalid_version);
+                assert_eq!(rejected_height, height);
+            },
+            res => panic!(
+                "Unexpected result for version {} at height {}: {:?}",
+                invalid_version, height, res
+            ),
+        }
+
+        let block = block_with_coinbase(&db, &prev, &rules, &factories, height + lock_height, valid_version);
+        prev = db.add_block(Arc::new(block)).unwrap().assert_added();
+    }
+}
+
+#[test]
+fn coinbase_maturity_rule_activation() {
+    let factories = CryptoFactories::default();
+    let fork = ConsensusConstantsBuilder::new(Network::LocalNet)
+        .with_effective_from_height(3)
+        .with_validation_rules(ValidationRules::COINBASE_MATURITY)
+        .build();
+    let rules = rules_with_fork(fork);
+    let db = create_store_with_consensus_and_validators(
+        &rules,
+        Validators::new(
+            MockValidator::new(true),
+            MockValidator::new(true),
+            MockValidator::new(true),
+        ),
+    );
+    let validator = BlockValidator::new(rules.clone(), factories.clone());
+    let validate = |block: &Block| {
+        let chain_block = ChainBlock {
+            accumulated_data: Default::default(),
+            block: block.clone(),
+        };
+        validator.validate_body(&chain_block, &*db.db_read_access().unwrap())
+    };
+    let lock_height = rules.consensus_constants(0).coinbase_lock_height();
+    let mut prev = db.fetch_block(0).unwrap().try_into_chain_block().unwrap();
+    for height in 1..=4 {
+        // A coinbase that is spendable straight away is only rejected once the rule is active
+        let block = block_with_coinbase(&db, &prev, &rules, &factories, height, 0);
+        match validate(&block) {
+            Ok(()) if height < 3 => (),
+            Err(ValidationError::TransactionError(TransactionError::InvalidCoinbaseMaturity)) if height >= 3 => (),
+            res => panic!("Unexpected result at height {}: {:?}", height, res),
+        }
+
+        let block = block_with_coinbase(&db, &prev, &rules, &factories, height + lock_height, 0);
+        validate(&block).unwrap();
+        prev = db.add_block(Arc::new(block)).unwrap().assert_added();
+    }
+}
+
+#[test]
+fn blake_pow_activation() {
+    let factories = CryptoFactories::default();
+    let network = Network::LocalNet;
+    let pow_constants = PowAlgorithmConstants {
+        max_target_time: 1800,
+        min_difficulty: 1.into(),
+        max_difficulty: 1.into(),
+        target_time: 300,
+    };
+    let mut algos = HashMap::new();
+    algos.insert(PowAlgorithm::Sha3, pow_constants.clone());
+    let initial = ConsensusConstantsBuilder::new(network)
+        .with_proof_of_work(algos.clone())
+        .build();
+    algos.insert(PowAlgorithm::Blake, pow_constants);
+    let fork = ConsensusConstantsBuilder::new(network)
+        .with_effective_from_height(2)
+        .with_proof_of_work(algos)
+        .build();
+    let rules = ConsensusManagerBuilder::new(network)
+        .with_consensus_constants(initial)
+        .with_consensus_constants(fork)
+        .build();
+    let db = create_store_with_consensus_and_validators(
+        &rules,
+        Validators::new(
+            MockValidator::new(true),
+            HeaderValidator::new(rules.clone(), RandomXFactory::default()),
+            MockValidator::new(true),
+        ),
+    );
+    let mut prev = db.fetch_block(0).unwrap().try_into_chain_block().unwrap();
+    for height in 1..=3 {
+        let (template, _) = chain_block_with_new_coinbase(&prev, vec![], &rules, &factories);
+        let block = db.prepare_block_merkle_roots(template).unwrap();
+        let mut blake_block = block.clone();
+        blake_block.header.pow.pow_algo = PowAlgorithm::Blake;
+        if height < 2 {
+            match db.add_block(Arc::new(blake_block)) {
+                Err(ChainStorageError::ValidationError {
+                    source:
+                        ValidationError::BlockHeaderError(BlockHeaderValidationError::ProofOfWorkError(
+                            PowError::AlgorithmNotAccepted { algo, .. },
+                        )),
+                }) => assert_eq!(algo, PowAlgorithm::Blake),
+                res => panic!("Unexpected result for Blake block at height {}: {:?}", height, res),
+            }
+            prev = db.add_block(Arc::new(block)).unwrap().assert_added();
+        } else {
+            let added = db.add_block(Arc::new(blake_block)).unwrap().assert_added();
+            assert_eq!(
+                added.accumulated_data.accumulated_blake_difficulty,
+                prev.accumulated_data.accumulated_blake_difficulty + added.accumulated_data.achieved_difficulty
+            );
+            assert_eq!(
+                added.accumulated_data.accumulated_monero_difficulty,
+                prev.accumulated_data.accumulated_monero_difficulty
+            );
+            prev = added;
+        }
+    }
+}
+
 fn add_monero_data(tblock: &mut Block, seed_hash: String) {
     let blocktemplate_blob = "0c0c8cd6a0fa057fe21d764e7abf004e975396a2160773b93712bf6118c3b4959ddd8ee0f76aad0000000002e1ea2701ffa5ea2701d5a299e2abb002028eb3066ced1b2cc82ea046f3716a48e9ae37144057d5fb48a97f941225a1957b2b0106225b7ec0a6544d8da39abe68d8bd82619b4a7c5bdae89c3783b256a8fa47820208f63aa86d2e857f070000".to_string();
     let bytes = hex::decode(blocktemplate_blob).unwrap();
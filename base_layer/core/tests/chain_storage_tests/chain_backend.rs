@@ -22,7 +22,15 @@
 
 use crate::helpers::database::create_orphan_block;
 use tari_core::{
-    chain_storage::{create_lmdb_database, BlockchainBackend, ChainStorageError, DbKey, DbTransaction, DbValue},
+    chain_storage::{
+        create_lmdb_database,
+        create_sqlite_database,
+        BlockchainBackend,
+        ChainStorageError,
+        DbKey,
+        DbTransaction,
+        DbValue,
+    },
     consensus::{ConsensusManagerBuilder, Network},
     test_helpers::blockchain::create_test_db,
     tx,
@@ -319,3 +327,27 @@ fn lmdb_file_lock() {
         }
     }
 }
+
+#[test]
+fn sqlite_file_lock() {
+    let temp_path = create_temporary_data_path();
+
+    {
+        let db = create_sqlite_database(&temp_path).unwrap();
+
+        match create_sqlite_database(&temp_path) {
+            Err(ChainStorageError::CannotAcquireFileLock) => {},
+            _ => panic!("Should not be able to make this db"),
+        }
+
+        drop(db);
+
+        let _db2 = create_sqlite_database(&temp_path).expect("Should be able to make a new sqlite db now");
+    }
+
+    if std::path::Path::new(&temp_path).exists() {
+        if let Err(e) = std::fs::remove_dir_all(&temp_path) {
+            println!("\n{:?}\n", e)
+        }
+    }
+}
@@ -41,7 +41,6 @@ use tari_common_types::types::BlockHash;
 use tari_core::{
     blocks::{genesis_block, Block, BlockHeader},
     chain_storage::{
-        create_lmdb_database,
         BlockAddResult,
         BlockchainBackend,
         BlockchainDatabase,
@@ -57,6 +56,7 @@ use tari_core::{
         create_store_with_consensus_and_validators,
         create_test_blockchain_db,
         create_test_db,
+        open_test_backend,
     },
     transactions::{
         helpers::spend_utxos,
@@ -68,7 +68,6 @@ use tari_core::{
     validation::{mocks::MockValidator, ValidationError},
 };
 use tari_crypto::tari_utilities::Hashable;
-use tari_storage::lmdb_store::LMDBConfig;
 use tari_test_utils::{paths::create_temporary_data_path, unpack_enum};
 
 #[test]
@@ -237,8 +236,54 @@ fn rewind_to_height() {
     // Invalid rewind
     assert!(db.rewind_to_height(4).is_err());
     assert_eq!(db.get_height().unwrap(), 3);
-    db.rewind_to_height(1).unwrap();
+
+    let spent_in_b2 = b2.block.body.inputs()[0].hash();
+    let (_, is_spent) = db.fetch_utxos(vec![spent_in_b2.clone()], None).unwrap()[0]
+        .clone()
+        .unwrap();
+    assert!(is_spent);
+
+    let removed_blocks = db.rewind_to_height(1).unwrap();
+    assert_eq!(db.get_height().unwrap(), 1);
+    assert_eq!(db.fetch_last_header().unwrap().height, 1);
+    assert_eq!(removed_blocks.len(), 2);
+    assert_eq!(removed_blocks[0].hash(), b3.hash());
+    assert_eq!(removed_blocks[1].hash(), b2.hash());
+    assert_eq!(db.get_chain_metadata().unwrap().best_block(), b1.hash());
+
+    // The outputs spent in the removed blocks are unspent again and their kernels are gone
+    let (_, is_spent) = db.fetch_utxos(vec![spent_in_b2], None).unwrap()[0].clone().unwrap();
+    assert!(!is_spent);
+    let b3_kernel = b3.block.body.kernels()[0].excess_sig.clone();
+    assert!(db.fetch_kernel_by_excess_sig(b3_kernel).unwrap().is_none());
+}
+
+#[test]
+fn rewind_to_block_height() {
+    let network = Network::LocalNet;
+    let (mut db, mut blocks, mut outputs, consensus_manager) = create_new_blockchain(network);
+    for _ in 0..3 {
+        generate_new_block(&mut db, &mut blocks, &mut outputs, vec![], &consensus_manager).unwrap();
+    }
+    assert_eq!(db.get_height().unwrap(), 3);
+
+    // The height is checked against the block chain tip
+    assert!(db.rewind_to_block_height(4, true).is_err());
+    assert_eq!(db.get_height().unwrap(), 3);
+
+    // Kept as chained orphans
+    let (previous_height, removed_blocks) = db.rewind_to_block_height(2, false).unwrap();
+    assert_eq!(previous_height, 3);
+    assert_eq!(removed_blocks.len(), 1);
+    assert_eq!(db.orphan_count().unwrap(), 1);
+
+    // Discarded
+    let (previous_height, removed_blocks) = db.rewind_to_block_height(1, true).unwrap();
+    assert_eq!(previous_height, 2);
+    assert_eq!(removed_blocks.len(), 1);
     assert_eq!(db.get_height().unwrap(), 1);
+    assert_eq!(db.orphan_count().unwrap(), 1);
+    assert!(db.fetch_orphan(removed_blocks[0].hash().clone()).is_err());
 }
 
 #[test]
@@ -854,7 +899,7 @@ fn restore_metadata_and_pruning_horizon_update() {
         let pruning_horizon1: u64 = 1000;
         let pruning_horizon2: u64 = 900;
         {
-            let db = create_lmdb_database(&path, LMDBConfig::default()).unwrap();
+            let db = open_test_backend(&path);
             config.pruning_horizon = pruning_horizon1;
             let db = BlockchainDatabase::new(db, &rules, validators.clone(), config, false).unwrap();
 
@@ -869,7 +914,7 @@ fn restore_metadata_and_pruning_horizon_update() {
         // Restore blockchain db with larger pruning horizon
         {
             config.pruning_horizon = 2000;
-            let db = create_lmdb_database(&path, LMDBConfig::default()).unwrap();
+            let db = open_test_backend(&path);
             let db = BlockchainDatabase::new(db, &rules, validators.clone(), config, false).unwrap();
 
             let metadata = db.get_chain_metadata().unwrap();
@@ -880,7 +925,7 @@ fn restore_metadata_and_pruning_horizon_update() {
         // Restore blockchain db with smaller pruning horizon update
         {
             config.pruning_horizon = 900;
-            let db = create_lmdb_database(&path, LMDBConfig::default()).unwrap();
+            let db = open_test_backend(&path);
             let db = BlockchainDatabase::new(db, &rules, validators, config, false).unwrap();
 
             let metadata = db.get_chain_metadata().unwrap();
@@ -1255,7 +1300,7 @@ fn orphan_cleanup_delete_all_orphans() {
     };
     // Test cleanup during runtime
     {
-        let db = create_lmdb_database(&path, LMDBConfig::default()).unwrap();
+        let db = open_test_backend(&path);
         let store = BlockchainDatabase::new(db, &consensus_manager, validators.clone(), config, false).unwrap();
 
         let orphan1 = create_orphan_block(500, vec![], &consensus_manager);
@@ -1301,14 +1346,14 @@ fn orphan_cleanup_delete_all_orphans() {
 
     // Test orphans are present on open
     {
-        let db = create_lmdb_database(&path, LMDBConfig::default()).unwrap();
+        let db = open_test_backend(&path);
         let store = BlockchainDatabase::new(db, &consensus_manager, validators.clone(), config, false).unwrap();
         assert_eq!(store.db_read_access().unwrap().orphan_count().unwrap(), 5);
     }
 
     // Test orphans cleanup on open
     {
-        let db = create_lmdb_database(&path, LMDBConfig::default()).unwrap();
+        let db = open_test_backend(&path);
         let store = BlockchainDatabase::new(db, &consensus_manager, validators, config, true).unwrap();
         assert_eq!(store.db_read_access().unwrap().orphan_count().unwrap(), 0);
     }
@@ -49,15 +49,20 @@ use tari_core::{
     mempool::{Mempool, MempoolConfig, MempoolServiceConfig, MempoolServiceError, TxStorageResponse},
     proof_of_work::Difficulty,
     proto,
+    test_helpers::blockchain::create_store_with_consensus,
     transactions::{
-        helpers::{schema_to_transaction, spend_utxos},
+        helpers::{create_test_input, schema_to_transaction, spend_utxos},
         tari_amount::{uT, T},
         transaction::{OutputFeatures, Transaction},
         types::CryptoFactories,
     },
     tx,
     txn_schema,
-    validation::transaction_validators::TxInputAndMaturityValidator,
+    validation::{
+        transaction_validators::{TxInputAndMaturityValidator, TxInternalConsistencyValidator},
+        MempoolTransactionValidation,
+        ValidationError,
+    },
 };
 use tari_p2p::{services::liveness::LivenessConfig, tari_message::TariMessageType};
 use tari_test_utils::async_assert_eventually;
@@ -241,6 +246,54 @@ fn test_time_locked() {
     assert_eq!(mempool.insert(tx2).unwrap(), TxStorageResponse::UnconfirmedPool);
 }
 
+#[test]
+#[allow(clippy::identity_op)]
+fn test_output_features_version_activation() {
+    let factories = CryptoFactories::default();
+    let network = Network::LocalNet;
+    // Output features version 1 replaces version 0 from height 2
+    let consensus_manager = ConsensusManagerBuilder::new(network)
+        .with_consensus_constants(ConsensusConstantsBuilder::new(network).build())
+        .with_consensus_constants(
+            ConsensusConstantsBuilder::new(network)
+                .with_effective_from_height(2)
+                .with_output_features_version(1)
+                .with_min_output_features_version(1)
+                .build(),
+        )
+        .build();
+    let store = create_store_with_consensus(&consensus_manager);
+    let validator = TxInternalConsistencyValidator::new(store.clone(), consensus_manager.clone(), factories.clone());
+    let tx_with_version = |version| {
+        let (_, input) = create_test_input(10 * T, 0, &factories.commitment);
+        let features = OutputFeatures {
+            version,
+            ..Default::default()
+        };
+        spend_utxos(txn_schema!(from: vec![input], to: vec![1 * T], fee: 20 * uT, lock: 0, features)).0
+    };
+
+    // The next block is at height 1, before the activation
+    validator.validate(&tx_with_version(0)).unwrap();
+    assert!(matches!(
+        validator.validate(&tx_with_version(1)),
+        Err(ValidationError::InvalidOutputFeaturesVersion { version: 1, height: 1 })
+    ));
+
+    let genesis = store.fetch_block(0).unwrap().try_into_chain_block().unwrap();
+    let block = store
+        .prepare_block_merkle_roots(chain_block(&genesis.block, vec![], &consensus_manager))
+        .unwrap();
+    store.add_block(Arc::new(block)).unwrap().assert_added();
+
+    // The next block is at height 2, from where only version 1 is accepted
+    assert!(matches!(
+        validator.validate(&tx_with_version(0)),
+        Err(ValidationError::InvalidOutputFeaturesVersion { version: 0, height: 2 })
+    ));
+    validator.validate(&tx_with_version(1)).unwrap();
+}
+
 #[test]
 #[allow(clippy::identity_op)]
 fn test_retrieve() {
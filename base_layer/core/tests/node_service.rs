@@ -39,9 +39,10 @@ use helpers::{
         random_node_identity,
         wait_until_online,
         BaseNodeBuilder,
+        NodeInterfaces,
     },
 };
-use std::time::Duration;
+use std::{sync::Arc, time::Duration};
 use tari_comms::protocol::messaging::MessagingEvent;
 use tari_core::{
     base_node::{
@@ -49,14 +50,15 @@ use tari_core::{
         service::BaseNodeServiceConfig,
         state_machine_service::states::{ListeningInfo, StateInfo, StatusInfo},
     },
-    blocks::NewBlock,
-    chain_storage::BlockchainDatabaseConfig,
-    consensus::{ConsensusConstantsBuilder, ConsensusManagerBuilder, Network},
+    blocks::{short_kernel_id, NewBlock},
+    chain_storage::{BlockchainDatabaseConfig, ChainBlock},
+    consensus::{ConsensusConstantsBuilder, ConsensusManager, ConsensusManagerBuilder, Network},
     mempool::MempoolServiceConfig,
     proof_of_work::PowAlgorithm,
     transactions::{
         helpers::schema_to_transaction,
         tari_amount::{uT, T},
+        transaction::Transaction,
         types::CryptoFactories,
     },
     txn_schema,
@@ -344,8 +346,9 @@ fn propagate_and_forward_many_valid_blocks() {
 static EMISSION: [u64; 2] = [10, 10];
 #[test]
 fn propagate_and_forward_invalid_block_hash() {
-    // Alice will propagate a "made up" block hash to Bob, Bob will request the block from Alice. Alice will not be able
-    // to provide the block and so Bob will not propagate the hash further to Carol.
+    // Alice will propagate a "made up" block to Bob. Bob does not have the block's transaction in his mempool and will
+    // request it from Alice, who also does not have it, so Bob will request the full block from Alice. Alice will not
+    // be able to provide the block and so Bob will not propagate it further to Carol.
     // alice -> bob -> carol
 
     let mut runtime = Runtime::new().unwrap();
@@ -359,7 +362,7 @@ fn propagate_and_forward_invalid_block_hash() {
     let consensus_constants = ConsensusConstantsBuilder::new(network)
         .with_emission_amounts(100_000_000.into(), &EMISSION, 100.into())
         .build();
-    let (block0, _) = create_genesis_block(&factories, &consensus_constants);
+    let (block0, outputs) = create_genesis_block_with_utxos(&factories, &[T], &consensus_constants);
     let rules = ConsensusManagerBuilder::new(network)
         .with_consensus_constants(consensus_constants)
         .with_block(block0.clone())
@@ -393,7 +396,15 @@ fn propagate_and_forward_invalid_block_hash() {
         state_info: StateInfo::Listening(ListeningInfo::new(true)),
     });
 
-    let mut block1 = append_block(&alice_node.blockchain_db, &block0, vec![], &rules, 1.into()).unwrap();
+    let (txs, _) = schema_to_transaction(&[txn_schema!(from: vec![outputs[1].clone()], to: vec![10_000 * uT])]);
+    let mut block1 = append_block(
+        &alice_node.blockchain_db,
+        &block0,
+        vec![(*txs[0]).clone()],
+        &rules,
+        1.into(),
+    )
+    .unwrap();
     // Create unknown block hash
     block1.block.header.height = 0;
 
@@ -414,7 +425,7 @@ fn propagate_and_forward_invalid_block_hash() {
             .unwrap()
             .unwrap();
         unpack_enum!(MessagingEvent::MessageReceived(_a, _b) = &*msg_event);
-        // Sent the request for the block to Alice
+        // Sent the request for the missing transaction to Alice
         // Bob received a response from Alice
         let msg_event = event_stream_next(&mut bob_message_events, Duration::from_secs(10))
             .await
@@ -547,6 +558,242 @@ fn propagate_and_forward_invalid_block() {
     });
 }
 
+/// Starts Alice and Bob with a genesis block containing two spendable UTXOs and returns two transactions spending them
+fn setup_compact_block_propagation(
+    runtime: &mut Runtime,
+    data_path: &str,
+) -> (
+    NodeInterfaces,
+    NodeInterfaces,
+    ChainBlock,
+    Vec<Arc<Transaction>>,
+    ConsensusManager,
+)
+{
+    let factories = CryptoFactories::default();
+    let network = Network::LocalNet;
+    let consensus_constants = network.create_consensus_constants();
+    let (block0, outputs) = create_genesis_block_with_utxos(&factories, &[T, T], &consensus_constants[0]);
+    let rules = ConsensusManagerBuilder::new(network)
+        .with_consensus_constants(consensus_constants[0].clone())
+        .with_block(block0.clone())
+        .build();
+    let (mut alice_node, mut bob_node, rules) = create_network_with_2_base_nodes_with_config(
+        runtime,
+        BlockchainDatabaseConfig::default(),
+        BaseNodeServiceConfig::default(),
+        MempoolServiceConfig::default(),
+        LivenessConfig::default(),
+        rules,
+        data_path,
+    );
+    for node in &mut [&mut alice_node, &mut bob_node] {
+        node.mock_base_node_state_machine.publish_status(StatusInfo {
+            bootstrapped: true,
+            state_info: StateInfo::Listening(ListeningInfo::new(true)),
+        });
+    }
+
+    let schema = [
+        txn_schema!(from: vec![outputs[1].clone()], to: vec![10_000 * uT, 20_000 * uT]),
+        txn_schema!(from: vec![outputs[2].clone()], to: vec![30_000 * uT, 40_000 * uT]),
+    ];
+    let (txs, _) = schema_to_transaction(&schema);
+    (alice_node, bob_node, block0, txs, rules)
+}
+
+#[test]
+fn propagate_compact_block_reconstructed_from_mempool() {
+    // Bob has all of the block's transactions in his mempool, and reconstructs the block from the compact block that
+    // Alice propagates.
+    let mut runtime = Runtime::new().unwrap();
+    let temp_dir = tempdir().unwrap();
+    let (alice_node, bob_node, block0, txs, rules) =
+        setup_compact_block_propagation(&mut runtime, temp_dir.path().to_str().unwrap());
+    for tx in &txs {
+        assert!(bob_node.mempool.insert(tx.clone()).is_ok());
+    }
+    let block1 = append_block(
+        &alice_node.blockchain_db,
+        &block0,
+        txs.iter().map(|tx| (**tx).clone()).collect(),
+        &rules,
+        1.into(),
+    )
+    .unwrap();
+
+    let new_block = NewBlock::from(&block1.block);
+    assert_eq!(new_block.kernel_short_ids.len(), 2);
+    assert_eq!(new_block.header.as_ref(), Some(&block1.block.header));
+    assert_eq!(new_block.coinbase_kernels.len(), 1);
+    assert_eq!(new_block.coinbase_outputs.len(), 1);
+    assert_eq!(&new_block.hash(), block1.hash());
+
+    let mut bob_block_event_stream = bob_node.local_nci.get_block_event_stream();
+    runtime.block_on(async {
+        alice_node
+            .outbound_nci
+            .propagate_block(new_block, vec![])
+            .await
+            .unwrap();
+
+        let event = event_stream_next(&mut bob_block_event_stream, Duration::from_secs(20)).await;
+        if let BlockEvent::ValidBlockAdded(received_block, result, _) = &*event.unwrap().unwrap() {
+            assert_eq!(&received_block.hash(), block1.hash());
+            assert_eq!(received_block.body, block1.block.body);
+            result.assert_added();
+        } else {
+            panic!("Bob's node did not reconstruct and add the propagated block");
+        }
+
+        alice_node.shutdown().await;
+        bob_node.shutdown().await;
+    });
+}
+
+#[test]
+fn propagate_compact_block_with_missing_transactions() {
+    // Bob only has one of the block's transactions, and requests the other from Alice's mempool
+    let mut runtime = Runtime::new().unwrap();
+    let temp_dir = tempdir().unwrap();
+    let (alice_node, mut bob_node, block0, txs, rules) =
+        setup_compact_block_propagation(&mut runtime, temp_dir.path().to_str().unwrap());
+    assert!(bob_node.mempool.insert(txs[0].clone()).is_ok());
+    assert!(alice_node.mempool.insert(txs[1].clone()).is_ok());
+    let block1 = append_block(
+        &alice_node.blockchain_db,
+        &block0,
+        txs.iter().map(|tx| (**tx).clone()).collect(),
+        &rules,
+        1.into(),
+    )
+    .unwrap();
+
+    let mut bob_block_event_stream = bob_node.local_nci.get_block_event_stream();
+    runtime.block_on(async {
+        let short_ids = txs
+            .iter()
+            .map(|tx| short_kernel_id(block1.hash(), &tx.body.kernels()[0].excess_sig))
+            .collect::<Vec<_>>();
+        let (transactions, not_found) = bob_node
+            .outbound_nci
+            .request_transactions_by_short_kernel_ids_from_peer(
+                block1.hash().clone(),
+                short_ids.clone(),
+                Some(alice_node.node_identity.node_id().clone()),
+            )
+            .await
+            .unwrap();
+        assert_eq!(transactions, vec![(*txs[1]).clone()]);
+        assert_eq!(not_found, vec![short_ids[0]]);
+
+        alice_node
+            .outbound_nci
+            .propagate_block(NewBlock::from(&block1.block), vec![])
+            .await
+            .unwrap();
+
+        let event = event_stream_next(&mut bob_block_event_stream, Duration::from_secs(20)).await;
+        if let BlockEvent::ValidBlockAdded(received_block, result, _) = &*event.unwrap().unwrap() {
+            assert_eq!(&received_block.hash(), block1.hash());
+            assert_eq!(received_block.body, block1.block.body);
+            result.assert_added();
+        } else {
+            panic!("Bob's node did not reconstruct and add the propagated block");
+        }
+
+        alice_node.shutdown().await;
+        bob_node.shutdown().await;
+    });
+}
+
+#[test]
+fn propagate_compact_block_falls_back_to_full_block() {
+    // Neither Bob nor Alice have the block's transactions in their mempools, so Bob requests the full block
+    let mut runtime = Runtime::new().unwrap();
+    let temp_dir = tempdir().unwrap();
+    let (alice_node, bob_node, block0, txs, rules) =
+        setup_compact_block_propagation(&mut runtime, temp_dir.path().to_str().unwrap());
+    let block1 = append_block(
+        &alice_node.blockchain_db,
+        &block0,
+        txs.iter().map(|tx| (**tx).clone()).collect(),
+        &rules,
+        1.into(),
+    )
+    .unwrap();
+
+    let mut bob_block_event_stream = bob_node.local_nci.get_block_event_stream();
+    runtime.block_on(async {
+        alice_node
+            .outbound_nci
+            .propagate_block(NewBlock::from(&block1.block), vec![])
+            .await
+            .unwrap();
+
+        let event = event_stream_next(&mut bob_block_event_stream, Duration::from_secs(20)).await;
+        if let BlockEvent::ValidBlockAdded(received_block, result, _) = &*event.unwrap().unwrap() {
+            assert_eq!(&received_block.hash(), block1.hash());
+            assert_eq!(received_block.body, block1.block.body);
+            result.assert_added();
+        } else {
+            panic!("Bob's node did not fetch and add the propagated block");
+        }
+
+        alice_node.shutdown().await;
+        bob_node.shutdown().await;
+    });
+}
+
+#[test]
+fn propagate_block_hash_only_fetches_full_block() {
+    // Alice propagates only the block hash, as nodes that do not support compact blocks do, so Bob requests the full
+    // block
+    let mut runtime = Runtime::new().unwrap();
+    let temp_dir = tempdir().unwrap();
+    let (alice_node, bob_node, block0, txs, rules) =
+        setup_compact_block_propagation(&mut runtime, temp_dir.path().to_str().unwrap());
+    for tx in &txs {
+        assert!(bob_node.mempool.insert(tx.clone()).is_ok());
+    }
+    let block1 = append_block(
+        &alice_node.blockchain_db,
+        &block0,
+        txs.iter().map(|tx| (**tx).clone()).collect(),
+        &rules,
+        1.into(),
+    )
+    .unwrap();
+
+    let new_block = NewBlock {
+        block_hash: block1.hash().clone(),
+        header: None,
+        coinbase_kernels: vec![],
+        coinbase_outputs: vec![],
+        kernel_short_ids: vec![],
+    };
+    let mut bob_block_event_stream = bob_node.local_nci.get_block_event_stream();
+    runtime.block_on(async {
+        alice_node
+            .outbound_nci
+            .propagate_block(new_block, vec![])
+            .await
+            .unwrap();
+
+        let event = event_stream_next(&mut bob_block_event_stream, Duration::from_secs(20)).await;
+        if let BlockEvent::ValidBlockAdded(received_block, result, _) = &*event.unwrap().unwrap() {
+            assert_eq!(&received_block.hash(), block1.hash());
+            assert_eq!(received_block.body, block1.block.body);
+            result.assert_added();
+        } else {
+            panic!("Bob's node did not fetch and add the propagated block");
+        }
+
+        alice_node.shutdown().await;
+        bob_node.shutdown().await;
+    });
+}
+
 #[test]
 fn service_request_timeout() {
     let mut runtime = Runtime::new().unwrap();
@@ -127,6 +127,19 @@ where
         }
     }
 
+    /// Derive a private key on this key manager's branch: derived_key=H(H(master_key||branch_seed)||index). Unlike
+    /// [derive_key](KeyManager::derive_key), which keeps existing wallets' keys stable by leaving the branch seed out
+    /// of the derivation, every branch seed yields an independent chain of keys from the same master key, similar
+    /// to a BIP-32 account.
+    pub fn derive_branch_key(&self, key_index: u64) -> Result<DerivedKey<K>, ByteArrayError> {
+        let branch_key = D::digest(format!("{}{}", self.master_key.to_hex(), self.branch_seed).as_bytes());
+        let concatenated = format!("{}{}", branch_key.to_vec().to_hex(), key_index.to_string());
+        match K::from_bytes(D::digest(&concatenated.into_bytes()).as_slice()) {
+            Ok(k) => Ok(DerivedKey { k, key_index }),
+            Err(e) => Err(e),
+        }
+    }
+
     /// Generate next deterministic private key derived from master key
     pub fn next_key(&mut self) -> Result<DerivedKey<K>, ByteArrayError> {
         self.primary_key_index += 1;
@@ -216,6 +229,22 @@ mod test {
         assert_eq!(next_key2.key_index, desired_key_index2);
     }
 
+    #[test]
+    fn test_derive_branch_key() {
+        let km = KeyManager::<RistrettoSecretKey, Sha256>::new(&mut OsRng);
+        let account1 =
+            KeyManager::<RistrettoSecretKey, Sha256>::from(km.master_key.clone(), "account-1".to_string(), 0);
+        let account2 =
+            KeyManager::<RistrettoSecretKey, Sha256>::from(km.master_key.clone(), "account-2".to_string(), 0);
+
+        let key1 = account1.derive_branch_key(1).unwrap();
+        assert_eq!(key1.k, account1.derive_branch_key(1).unwrap().k);
+        assert_eq!(key1.key_index, 1);
+        assert_ne!(key1.k, account1.derive_branch_key(2).unwrap().k);
+        assert_ne!(key1.k, account2.derive_branch_key(1).unwrap().k);
+        assert_ne!(key1.k, account1.derive_key(1).unwrap().k);
+    }
+
     #[test]
     fn test_to_file_and_from_file() {
         let desired_km = KeyManager::<RistrettoSecretKey, Sha256>::new(&mut OsRng);
@@ -52,7 +52,7 @@ use tari_comms::{
     },
     tor,
     tor::HiddenServiceControllerError,
-    transports::{MemoryTransport, SocksTransport, TcpWithTorTransport},
+    transports::{MemoryTransport, SocksTransport, TcpWithTorTransport, WebSocketTransport},
     utils::cidr::parse_cidrs,
     CommsBuilder,
     CommsBuilderError,
@@ -120,7 +120,8 @@ pub struct CommsConfig {
     pub peer_database_name: String,
     /// The maximum number of concurrent Inbound tasks allowed before back-pressure is applied to peers
     pub max_concurrent_inbound_tasks: usize,
-    /// The size of the buffer (channel) which holds pending outbound message requests
+    /// The size of the buffer (channel) which holds outbound messages that have passed through the outbound
+    /// pipeline. Pending outbound message requests are held in the DHT outbound priority queues.
     pub outbound_buffer_size: usize,
     /// Configuration for DHT
     pub dht: DhtConfig,
@@ -149,6 +150,10 @@ pub struct CommsConfig {
     pub dns_seeds_name_server: SocketAddr,
     /// All DNS seed records must pass DNSSEC validation
     pub dns_seeds_use_dnssec: bool,
+    /// The maximum bytes per second sent and received across all peer connections. None for unlimited.
+    pub max_bandwidth_bytes_per_sec: Option<u64>,
+    /// The maximum bytes per second sent and received on a single peer connection. None for unlimited.
+    pub max_peer_bandwidth_bytes_per_sec: Option<u64>,
 }
 
 /// Initialize Tari Comms configured for tests
@@ -198,8 +203,8 @@ where
 
     add_all_peers(&comms.peer_manager(), &comms.node_identity(), seed_peers).await?;
 
-    // Create outbound channel
-    let (outbound_tx, outbound_rx) = mpsc::channel(10);
+    // Create outbound channel. Outbound requests are buffered in the DHT priority queue, not in this channel.
+    let (outbound_tx, outbound_rx) = mpsc::channel(0);
 
     let dht = DhtBuilder::new(
         comms.node_identity(),
@@ -290,6 +295,13 @@ pub async fn spawn_comms_using_transport(
                 .spawn_with_transport(transport)
                 .await?
         },
+        TransportType::WebSocket { listener_address } => {
+            debug!(target: LOG_TARGET, "Building WebSocket comms stack");
+            comms
+                .with_listener_address(listener_address)
+                .spawn_with_transport(WebSocketTransport::new())
+                .await?
+        },
     };
 
     Ok(comms)
@@ -304,12 +316,17 @@ async fn initialize_hidden_service(
         .with_socks_address_override(config.socks_address_override)
         .with_socks_authentication(config.socks_auth)
         .with_control_server_auth(config.control_server_auth)
-        .with_control_server_address(config.control_server_addr);
+        .with_control_server_address(config.control_server_addr)
+        .with_additional_services(config.additional_services);
 
     if let Some(identity) = config.identity {
         builder = builder.with_tor_identity(*identity);
     }
 
+    if let Some(interval) = config.key_rotation_interval {
+        builder = builder.with_key_rotation_interval(interval);
+    }
+
     builder.build().await
 }
 
@@ -344,8 +361,8 @@ where
         .with_peer_storage(peer_database, Some(file_lock))
         .build()?;
 
-    // Create outbound channel
-    let (outbound_tx, outbound_rx) = mpsc::channel(config.outbound_buffer_size);
+    // Create outbound channel. Outbound requests are buffered in the DHT priority queue, not in this channel.
+    let (outbound_tx, outbound_rx) = mpsc::channel(0);
 
     let dht = DhtBuilder::new(
         comms.node_identity(),
@@ -542,7 +559,11 @@ impl ServiceInitializer for P2pInitializer {
             let mut builder = CommsBuilder::new()
                 .with_shutdown_signal(context.get_shutdown_signal())
                 .with_node_identity(config.node_identity.clone())
-                .with_user_agent(&config.user_agent);
+                .with_user_agent(&config.user_agent)
+                .with_bandwidth_limits(
+                    config.max_bandwidth_bytes_per_sec,
+                    config.max_peer_bandwidth_bytes_per_sec,
+                );
 
             if config.allow_test_addresses {
                 builder = builder.allow_test_addresses();
@@ -20,7 +20,7 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::fmt;
+use std::{fmt, time::Duration};
 use tari_comms::{multiaddr::Multiaddr, socks, tor, transports::SocksConfig};
 
 #[derive(Debug, Clone)]
@@ -41,6 +41,9 @@ pub enum TransportType {
         socks_config: SocksConfig,
         listener_address: Multiaddr,
     },
+    /// Use a WebSocketTransport. This transport listens on a `/ws` address and can connect to TCP/IP, DNS and
+    /// WebSocket addresses.
+    WebSocket { listener_address: Multiaddr },
 }
 
 #[derive(Debug, Clone)]
@@ -58,14 +61,28 @@ pub struct TorConfig {
     pub socks_address_override: Option<Multiaddr>,
     /// Authentication for the Tor SOCKS5 proxy
     pub socks_auth: socks::Authentication,
+    /// Additional onion services to create alongside the node's public onion service
+    pub additional_services: Vec<tor::OnionServiceConfig>,
+    /// If Some, the onion key of the public onion service is rotated at this interval
+    pub key_rotation_interval: Option<Duration>,
 }
 
 impl fmt::Display for TorConfig {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "control_server_addr = {}, control_server_auth = {}, {}, socks_address_override = {:?}",
-            self.control_server_addr, self.control_server_auth, self.port_mapping, self.socks_address_override
+            "control_server_addr = {}, control_server_auth = {}, {}, socks_address_override = {:?}, \
+             additional_services = [{}], key_rotation_interval = {:?}",
+            self.control_server_addr,
+            self.control_server_auth,
+            self.port_mapping,
+            self.socks_address_override,
+            self.additional_services
+                .iter()
+                .map(|s| s.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", "),
+            self.key_rotation_interval
         )
     }
 }
@@ -0,0 +1,12 @@
+ALTER TABLE outputs ADD COLUMN account_id INTEGER NOT NULL DEFAULT 0;
+
+CREATE TABLE accounts (
+    id INTEGER PRIMARY KEY NOT NULL,
+    name TEXT NOT NULL UNIQUE,
+    key_index INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE TABLE account_transactions (
+    tx_id INTEGER PRIMARY KEY NOT NULL,
+    account_id INTEGER NOT NULL
+);
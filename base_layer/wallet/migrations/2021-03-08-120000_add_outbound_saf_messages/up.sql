@@ -0,0 +1,10 @@
+CREATE TABLE outbound_saf_messages (
+    message_tag BIGINT PRIMARY KEY NOT NULL,
+    tx_id BIGINT NOT NULL,
+    destination_public_key BLOB NOT NULL,
+    storer_node_id BLOB NOT NULL,
+    sent_at DATETIME NOT NULL,
+    delivered_at DATETIME NULL
+);
+
+CREATE INDEX outbound_saf_messages_tx_id ON outbound_saf_messages (tx_id);
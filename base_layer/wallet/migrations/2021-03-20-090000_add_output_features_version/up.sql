@@ -0,0 +1 @@
+ALTER TABLE outputs ADD COLUMN features_version INTEGER NOT NULL DEFAULT 0;
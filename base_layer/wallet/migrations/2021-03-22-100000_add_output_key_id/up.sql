@@ -0,0 +1 @@
+ALTER TABLE outputs ADD COLUMN key_id BLOB NULL DEFAULT NULL;
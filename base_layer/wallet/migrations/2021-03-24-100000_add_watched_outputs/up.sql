@@ -0,0 +1,7 @@
+CREATE TABLE watched_outputs (
+    commitment BLOB PRIMARY KEY NOT NULL,
+    hash BLOB NULL DEFAULT NULL,
+    value INTEGER NULL DEFAULT NULL,
+    mined_height INTEGER NULL DEFAULT NULL,
+    spent INTEGER NOT NULL DEFAULT 0
+);
@@ -0,0 +1,19 @@
+PRAGMA foreign_keys=off;
+ALTER TABLE pending_transaction_outputs RENAME TO pending_transaction_outputs_old;
+CREATE TABLE pending_transaction_outputs (
+    tx_id INTEGER PRIMARY KEY NOT NULL,
+    short_term INTEGER NOT NULL,
+    timestamp DATETIME NOT NULL,
+    coinbase_block_height INTEGER NULL
+);
+INSERT INTO pending_transaction_outputs SELECT tx_id, short_term, timestamp, coinbase_block_height FROM pending_transaction_outputs_old;
+DROP TABLE pending_transaction_outputs_old;
+ALTER TABLE accounts RENAME TO accounts_old;
+CREATE TABLE accounts (
+    id INTEGER PRIMARY KEY NOT NULL,
+    name TEXT NOT NULL UNIQUE,
+    key_index INTEGER NOT NULL DEFAULT 0
+);
+INSERT INTO accounts SELECT id, name, key_index FROM accounts_old;
+DROP TABLE accounts_old;
+PRAGMA foreign_keys=on;
@@ -0,0 +1,2 @@
+ALTER TABLE pending_transaction_outputs ADD COLUMN account_id INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE accounts ADD COLUMN active INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1 @@
+DROP TABLE IF EXISTS kernel_signing_records;
@@ -0,0 +1,6 @@
+CREATE TABLE kernel_signing_records (
+    request_hash BLOB PRIMARY KEY NOT NULL,
+    nonce_salt BLOB NOT NULL,
+    challenge BLOB NULL,
+    timestamp DATETIME NOT NULL
+);
@@ -36,12 +36,14 @@ pub type BaseNodeEventReceiver = broadcast::Receiver<Arc<BaseNodeEvent>>;
 #[derive(Debug)]
 pub enum BaseNodeServiceRequest {
     GetChainMetadata,
+    GetBaseNodeState,
     SetBaseNodePeer(Box<Peer>),
 }
 /// API Response enum
 #[derive(Debug)]
 pub enum BaseNodeServiceResponse {
     ChainMetadata(Option<ChainMetadata>),
+    BaseNodeState(Box<BaseNodeState>),
     BaseNodePeerSet,
 }
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
@@ -81,6 +83,13 @@ impl BaseNodeServiceHandle {
         }
     }
 
+    pub async fn get_base_node_state(&mut self) -> Result<BaseNodeState, BaseNodeServiceError> {
+        match self.handle.call(BaseNodeServiceRequest::GetBaseNodeState).await?? {
+            BaseNodeServiceResponse::BaseNodeState(state) => Ok(*state),
+            _ => Err(BaseNodeServiceError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn set_base_node_peer(&mut self, peer: Peer) -> Result<(), BaseNodeServiceError> {
         match self
             .handle
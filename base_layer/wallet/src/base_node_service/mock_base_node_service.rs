@@ -132,6 +132,9 @@ impl MockBaseNodeService {
             BaseNodeServiceRequest::GetChainMetadata => Ok(BaseNodeServiceResponse::ChainMetadata(
                 self.state.chain_metadata.clone(),
             )),
+            BaseNodeServiceRequest::GetBaseNodeState => {
+                Ok(BaseNodeServiceResponse::BaseNodeState(Box::new(self.state.clone())))
+            },
         }
     }
 }
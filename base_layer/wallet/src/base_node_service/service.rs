@@ -399,6 +399,9 @@ where
                     Ok(BaseNodeServiceResponse::ChainMetadata(metadata))
                 },
             },
+            BaseNodeServiceRequest::GetBaseNodeState => {
+                Ok(BaseNodeServiceResponse::BaseNodeState(Box::new(self.state.clone())))
+            },
         }
     }
 }
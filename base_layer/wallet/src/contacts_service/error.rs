@@ -36,6 +36,8 @@ pub enum ContactsServiceError {
     ContactsServiceStorageError(#[from] ContactsServiceStorageError),
     #[error("Transport channel error: `{0}`")]
     TransportChannelError(#[from] TransportChannelError),
+    #[error("Contacts serialization error: `{0}`")]
+    SerializationError(String),
 }
 
 #[derive(Debug, Error, PartialEq)]
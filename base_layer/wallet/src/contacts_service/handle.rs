@@ -31,6 +31,7 @@ pub enum ContactsServiceRequest {
     UpsertContact(Contact),
     RemoveContact(CommsPublicKey),
     GetContacts,
+    ImportContacts(Vec<Contact>),
 }
 
 #[derive(Debug)]
@@ -39,6 +40,7 @@ pub enum ContactsServiceResponse {
     ContactRemoved(Contact),
     Contact(Contact),
     Contacts(Vec<Contact>),
+    ContactsImported(usize),
 }
 
 #[derive(Clone)]
@@ -87,4 +89,26 @@ impl ContactsServiceHandle {
             _ => Err(ContactsServiceError::UnexpectedApiResponse),
         }
     }
+
+    /// Export the address book as a JSON array of contacts
+    pub async fn export_contacts(&mut self) -> Result<String, ContactsServiceError> {
+        let contacts = self.get_contacts().await?;
+        serde_json::to_string_pretty(&contacts).map_err(|e| ContactsServiceError::SerializationError(e.to_string()))
+    }
+
+    /// Import contacts from JSON produced by `export_contacts`, updating the alias of contacts that already exist.
+    /// Nothing is imported if the JSON is invalid. Returns the number of contacts that were added or had their alias
+    /// changed.
+    pub async fn import_contacts(&mut self, json: &str) -> Result<usize, ContactsServiceError> {
+        let contacts: Vec<Contact> =
+            serde_json::from_str(json).map_err(|e| ContactsServiceError::SerializationError(e.to_string()))?;
+        match self
+            .handle
+            .call(ContactsServiceRequest::ImportContacts(contacts))
+            .await??
+        {
+            ContactsServiceResponse::ContactsImported(n) => Ok(n),
+            _ => Err(ContactsServiceError::UnexpectedApiResponse),
+        }
+    }
 }
@@ -131,6 +131,11 @@ where T: ContactsBackend + 'static
             ContactsServiceRequest::GetContacts => {
                 Ok(self.db.get_contacts().await.map(ContactsServiceResponse::Contacts)?)
             },
+            ContactsServiceRequest::ImportContacts(contacts) => {
+                let num_imported = self.db.upsert_contacts(contacts).await?;
+                info!(target: LOG_TARGET, "{} contact(s) imported", num_imported);
+                Ok(ContactsServiceResponse::ContactsImported(num_imported))
+            },
         }
     }
 }
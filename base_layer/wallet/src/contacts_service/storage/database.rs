@@ -22,6 +22,7 @@
 
 use crate::contacts_service::error::ContactsServiceStorageError;
 use log::*;
+use serde::{Deserialize, Serialize};
 use std::{
     fmt::{Display, Error, Formatter},
     sync::Arc,
@@ -30,7 +31,7 @@ use tari_comms::types::CommsPublicKey;
 
 const LOG_TARGET: &str = "wallet::contacts_service::database";
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Contact {
     pub alias: String,
     pub public_key: CommsPublicKey,
@@ -42,6 +43,9 @@ pub trait ContactsBackend: Send + Sync + Clone {
     fn fetch(&self, key: &DbKey) -> Result<Option<DbValue>, ContactsServiceStorageError>;
     /// Modify the state the of the backend with a write operation
     fn write(&self, op: WriteOperation) -> Result<Option<DbValue>, ContactsServiceStorageError>;
+    /// Insert or update the provided contacts in a single transaction and return the number of contacts that were
+    /// inserted or had their alias changed
+    fn upsert_contacts(&self, contacts: Vec<Contact>) -> Result<usize, ContactsServiceStorageError>;
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -129,6 +133,14 @@ where T: ContactsBackend + 'static
         Ok(())
     }
 
+    pub async fn upsert_contacts(&self, contacts: Vec<Contact>) -> Result<usize, ContactsServiceStorageError> {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || db_clone.upsert_contacts(contacts))
+            .await
+            .map_err(|err| ContactsServiceStorageError::BlockingTaskSpawnError(err.to_string()))
+            .and_then(|inner_result| inner_result)
+    }
+
     pub async fn remove_contact(&self, pub_key: CommsPublicKey) -> Result<Contact, ContactsServiceStorageError> {
         let db_clone = self.db.clone();
         let pub_key_clone = pub_key.clone();
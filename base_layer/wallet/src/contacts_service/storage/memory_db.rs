@@ -87,4 +87,21 @@ impl ContactsBackend for ContactsServiceMemoryDatabase {
 
         Ok(None)
     }
+
+    fn upsert_contacts(&self, contacts: Vec<Contact>) -> Result<usize, ContactsServiceStorageError> {
+        let mut db = acquire_write_lock!(self.db);
+        let mut num_changed = 0;
+        for contact in contacts {
+            match db.contacts.iter_mut().find(|c| c.public_key == contact.public_key) {
+                None => db.contacts.push(contact),
+                Some(existing_contact) if existing_contact.alias != contact.alias => {
+                    existing_contact.alias = contact.alias
+                },
+                Some(_) => continue,
+            }
+            num_changed += 1;
+        }
+
+        Ok(num_changed)
+    }
 }
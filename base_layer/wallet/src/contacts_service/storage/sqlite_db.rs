@@ -94,6 +94,33 @@ impl ContactsBackend for ContactsServiceSqliteDatabase {
 
         Ok(None)
     }
+
+    fn upsert_contacts(&self, contacts: Vec<Contact>) -> Result<usize, ContactsServiceStorageError> {
+        let conn = self.database_connection.acquire_lock();
+
+        conn.transaction::<_, ContactsServiceStorageError, _>(|| {
+            let mut num_changed = 0;
+            for contact in contacts {
+                match ContactSql::find(&contact.public_key.to_vec(), &(*conn)) {
+                    Ok(found_c) if found_c.alias == contact.alias => continue,
+                    Ok(found_c) => {
+                        let _ = found_c.update(
+                            UpdateContact {
+                                alias: Some(contact.alias),
+                            },
+                            &(*conn),
+                        )?;
+                    },
+                    Err(ContactsServiceStorageError::DieselError(DieselError::NotFound)) => {
+                        ContactSql::from(contact).commit(&conn)?;
+                    },
+                    Err(e) => return Err(e),
+                }
+                num_changed += 1;
+            }
+            Ok(num_changed)
+        })
+    }
 }
 
 /// A Sql version of the Contact struct
@@ -30,7 +30,7 @@ use crate::{
 use diesel::result::Error as DieselError;
 use log::SetLoggerError;
 use serde_json::Error as SerdeJsonError;
-use tari_comms::{connectivity::ConnectivityError, multiaddr, peer_manager::PeerManagerError};
+use tari_comms::{connectivity::ConnectivityError, multiaddr, peer_manager::PeerManagerError, protocol::rpc::RpcError};
 use tari_comms_dht::store_forward::StoreAndForwardError;
 use tari_crypto::tari_utilities::{hex::HexError, ByteArrayError};
 use tari_p2p::{initialization::CommsInitializationError, services::liveness::error::LivenessError};
@@ -65,6 +65,16 @@ pub enum WalletError {
     ServiceInitializationError(#[from] ServiceInitializationError),
     #[error("Base Node Service error: {0}")]
     BaseNodeServiceError(#[from] BaseNodeServiceError),
+    #[error("RPC error: `{0}`")]
+    RpcError(#[from] RpcError),
+    #[error("Wallet recovery error: `{0}`")]
+    WalletRecoveryError(String),
+    #[error("UTXO scanner error: `{0}`")]
+    UtxoScannerError(String),
+    #[error("Watch-only wallet error: `{0}`")]
+    WatchOnlyError(String),
+    #[error("Shutdown Signal Received")]
+    Shutdown,
 }
 
 #[derive(Debug, Error)]
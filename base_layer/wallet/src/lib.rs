@@ -14,13 +14,16 @@ mod macros;
 pub mod base_node_service;
 pub mod contacts_service;
 pub mod error;
+pub mod multisig_service;
 pub mod output_manager_service;
 pub mod storage;
+pub mod tasks;
 pub mod test_utils;
 pub mod transaction_service;
 pub mod types;
 pub mod util;
 pub mod wallet;
+pub mod watch_only;
 
 #[cfg(feature = "test_harness")]
 pub mod testnet_utils;
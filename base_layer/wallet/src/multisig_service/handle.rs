@@ -0,0 +1,182 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::{multisig_service::error::MultiSigServiceError, output_manager_service::TxId};
+use futures::{stream::Fuse, StreamExt};
+use std::{fmt, sync::Arc};
+use tari_comms::types::CommsPublicKey;
+use tari_core::transactions::{
+    tari_amount::MicroTari,
+    transaction_protocol::multisig::{JointOutputShare, JointSpendProposal},
+    types::Commitment,
+};
+use tari_crypto::tari_utilities::hex::Hex;
+use tari_service_framework::reply_channel::SenderService;
+use tokio::sync::broadcast;
+use tower::Service;
+
+pub type MultiSigEventSender = broadcast::Sender<Arc<MultiSigEvent>>;
+pub type MultiSigEventReceiver = broadcast::Receiver<Arc<MultiSigEvent>>;
+
+/// API Request enum
+#[derive(Debug)]
+pub enum MultiSigServiceRequest {
+    CreateJointOutput((Vec<CommsPublicKey>, MicroTari, MicroTari, String)),
+    GetJointOutputs,
+    GetSpendProposals,
+    ProposeSpend((Commitment, MicroTari)),
+    ApproveSpend(u64),
+}
+
+impl fmt::Display for MultiSigServiceRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::CreateJointOutput((owners, amount, _, _)) => {
+                write!(f, "CreateJointOutput ({} with {} co-owners)", amount, owners.len())
+            },
+            Self::GetJointOutputs => write!(f, "GetJointOutputs"),
+            Self::GetSpendProposals => write!(f, "GetSpendProposals"),
+            Self::ProposeSpend((commitment, _)) => write!(f, "ProposeSpend ({})", commitment.to_hex()),
+            Self::ApproveSpend(session_id) => write!(f, "ApproveSpend ({})", session_id),
+        }
+    }
+}
+
+/// API Response enum
+#[derive(Debug)]
+pub enum MultiSigServiceResponse {
+    JointOutputCreated((TxId, Commitment)),
+    JointOutputs(Vec<JointOutputShare>),
+    SpendProposals(Vec<JointSpendProposal>),
+    SpendProposed(u64),
+    SpendApproved,
+}
+
+/// Events that can be published on the MultiSig Service Event Stream
+#[derive(Clone, Debug, PartialEq)]
+pub enum MultiSigEvent {
+    /// A co-owner dealt this wallet a share of a new joint output
+    ReceivedJointOutput(Commitment),
+    /// A co-owner proposed to spend a joint output; the spend needs to be approved with `approve_spend`
+    ReceivedSpendProposal(u64),
+    /// All co-owners signed the spend of a joint output, which is waiting to be confirmed
+    SpendCompleted(u64),
+    /// The spend of the joint output with this commitment was confirmed and its shares were dropped
+    SpendConfirmed(Commitment),
+    /// A signing session failed and was abandoned
+    SessionFailed(u64, String),
+}
+
+/// The MultiSig Service Handle is used to create and spend outputs that are jointly owned by several wallets
+#[derive(Clone)]
+pub struct MultiSigServiceHandle {
+    handle: SenderService<MultiSigServiceRequest, Result<MultiSigServiceResponse, MultiSigServiceError>>,
+    event_stream_sender: MultiSigEventSender,
+}
+
+impl MultiSigServiceHandle {
+    pub fn new(
+        handle: SenderService<MultiSigServiceRequest, Result<MultiSigServiceResponse, MultiSigServiceError>>,
+        event_stream_sender: MultiSigEventSender,
+    ) -> Self
+    {
+        Self {
+            handle,
+            event_stream_sender,
+        }
+    }
+
+    pub fn get_event_stream_fused(&self) -> Fuse<MultiSigEventReceiver> {
+        self.event_stream_sender.subscribe().fuse()
+    }
+
+    /// Fund an output of `amount` that is owned jointly by this wallet and `co_owners`. Every owner receives a share
+    /// of the spending key; all of them have to sign to spend the output. Returns the id of the funding transaction
+    /// and the commitment of the joint output.
+    pub async fn create_joint_output(
+        &mut self,
+        co_owners: Vec<CommsPublicKey>,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        message: String,
+    ) -> Result<(TxId, Commitment), MultiSigServiceError>
+    {
+        match self
+            .handle
+            .call(MultiSigServiceRequest::CreateJointOutput((
+                co_owners,
+                amount,
+                fee_per_gram,
+                message,
+            )))
+            .await??
+        {
+            MultiSigServiceResponse::JointOutputCreated(v) => Ok(v),
+            _ => Err(MultiSigServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Returns this wallet's shares of all joint outputs whose spend has not been confirmed
+    pub async fn get_joint_outputs(&mut self) -> Result<Vec<JointOutputShare>, MultiSigServiceError> {
+        match self.handle.call(MultiSigServiceRequest::GetJointOutputs).await?? {
+            MultiSigServiceResponse::JointOutputs(outputs) => Ok(outputs),
+            _ => Err(MultiSigServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Returns the spend proposals received from co-owners that have not been approved yet
+    pub async fn get_spend_proposals(&mut self) -> Result<Vec<JointSpendProposal>, MultiSigServiceError> {
+        match self.handle.call(MultiSigServiceRequest::GetSpendProposals).await?? {
+            MultiSigServiceResponse::SpendProposals(proposals) => Ok(proposals),
+            _ => Err(MultiSigServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Propose to sweep a joint output into this wallet. Returns the id of the signing session.
+    pub async fn propose_spend(
+        &mut self,
+        joint_commitment: Commitment,
+        fee_per_gram: MicroTari,
+    ) -> Result<u64, MultiSigServiceError>
+    {
+        match self
+            .handle
+            .call(MultiSigServiceRequest::ProposeSpend((joint_commitment, fee_per_gram)))
+            .await??
+        {
+            MultiSigServiceResponse::SpendProposed(session_id) => Ok(session_id),
+            _ => Err(MultiSigServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Approve a spend proposed by a co-owner and take part in signing it
+    pub async fn approve_spend(&mut self, session_id: u64) -> Result<(), MultiSigServiceError> {
+        match self
+            .handle
+            .call(MultiSigServiceRequest::ApproveSpend(session_id))
+            .await??
+        {
+            MultiSigServiceResponse::SpendApproved => Ok(()),
+            _ => Err(MultiSigServiceError::UnexpectedApiResponse),
+        }
+    }
+}
@@ -0,0 +1,761 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::{
+    base_node_service::handle::{BaseNodeEvent, BaseNodeEventReceiver},
+    multisig_service::{
+        error::MultiSigServiceError,
+        handle::{MultiSigEvent, MultiSigEventSender, MultiSigServiceRequest, MultiSigServiceResponse},
+    },
+    output_manager_service::{handle::OutputManagerHandle, TxId},
+    storage::database::{WalletBackend, WalletDatabase},
+    transaction_service::handle::TransactionServiceHandle,
+};
+use futures::{pin_mut, stream::Fuse, Stream, StreamExt};
+use log::*;
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, convert::TryFrom, sync::Arc};
+use tari_comms::{
+    connectivity::ConnectivityRequester,
+    peer_manager::{NodeId, NodeIdentity},
+    types::CommsPublicKey,
+};
+use tari_comms_dht::{
+    domain_message::OutboundDomainMessage,
+    outbound::{OutboundEncryption, OutboundMessageRequester, SendMessageParams},
+};
+use tari_core::{
+    base_node::{
+        proto::wallet_rpc::{TxLocation, TxQueryResponse, TxSubmissionResponse},
+        rpc::BaseNodeWalletRpcClient,
+    },
+    transactions::{
+        fee::Fee,
+        tari_amount::MicroTari,
+        transaction::{OutputFeatures, Transaction, UnblindedOutput},
+        transaction_protocol::{
+            multisig::{JointOutputShare, JointSpendProposal, MultiSigMessage, MultiSigSession},
+            proto,
+            TransactionMetadata,
+        },
+        types::{Commitment, CryptoFactories, PrivateKey},
+    },
+};
+use tari_crypto::{keys::SecretKey, tari_utilities::hex::Hex};
+use tari_p2p::{domain_message::DomainMessage, tari_message::TariMessageType};
+use tari_service_framework::reply_channel::Receiver;
+use tari_shutdown::ShutdownSignal;
+
+const LOG_TARGET: &str = "wallet::multisig_service::service";
+
+/// Client key-value store key holding this wallet's JSON encoded shares of joint outputs whose spend has not been
+/// confirmed
+pub const JOINT_OUTPUTS_KEY: &str = "multisig_joint_outputs";
+/// Client key-value store key holding the JSON encoded signed spends of joint outputs that have not been confirmed
+pub const JOINT_SPENDS_KEY: &str = "multisig_joint_spends";
+
+/// The number of confirmations after which the spend of a joint output is final and the shares of the output are
+/// dropped
+const SPEND_CONFIRMATIONS: u64 = 3;
+/// The maximum number of round messages kept for a session this wallet has not joined yet. Every co-owner sends three
+/// round messages per session.
+const MAX_EARLY_MESSAGES_PER_SESSION: usize = 64;
+
+/// A fully signed spend of a joint output that has not been confirmed on chain. Every owner keeps it, along with its
+/// share of the output, so that any of them can broadcast it again if the coordinator does not.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct JointSpend {
+    pub joint_commitment: Commitment,
+    pub transaction: Transaction,
+}
+
+/// A signing session this wallet takes part in
+struct ActiveSession {
+    session: MultiSigSession,
+    owners: Vec<CommsPublicKey>,
+    commitment_sent: bool,
+    nonce_sent: bool,
+    signature_sent: bool,
+    /// The spending key of the swept output, only known to the coordinator
+    output_key: Option<PrivateKey>,
+}
+
+/// The MultiSig service creates outputs that are owned jointly by several wallets and runs the multi-party signing
+/// protocol needed to spend them. Protocol messages are exchanged with the co-owners as encrypted direct DHT messages.
+pub struct MultiSigService<TMessageStream, T>
+where T: WalletBackend + 'static
+{
+    request_stream: Option<Receiver<MultiSigServiceRequest, Result<MultiSigServiceResponse, MultiSigServiceError>>>,
+    message_stream: Option<TMessageStream>,
+    db: WalletDatabase<T>,
+    output_manager_service: OutputManagerHandle,
+    transaction_service: TransactionServiceHandle,
+    outbound_message_service: OutboundMessageRequester,
+    event_publisher: MultiSigEventSender,
+    node_identity: Arc<NodeIdentity>,
+    factories: CryptoFactories,
+    shutdown_signal: Option<ShutdownSignal>,
+    connectivity: ConnectivityRequester,
+    base_node_events: Option<Fuse<BaseNodeEventReceiver>>,
+    base_node: Option<NodeId>,
+    sessions: HashMap<u64, ActiveSession>,
+    /// Proposals received from co-owners that have not been approved yet
+    pending_proposals: HashMap<u64, JointSpendProposal>,
+    /// Round messages received for sessions this wallet has not joined yet
+    early_messages: HashMap<u64, Vec<(CommsPublicKey, MultiSigMessage)>>,
+}
+
+impl<TMessageStream, T> MultiSigService<TMessageStream, T>
+where
+    T: WalletBackend + 'static,
+    TMessageStream: Stream<Item = DomainMessage<proto::MultiSigMessage>>,
+{
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        request_stream: Receiver<MultiSigServiceRequest, Result<MultiSigServiceResponse, MultiSigServiceError>>,
+        message_stream: TMessageStream,
+        db: WalletDatabase<T>,
+        output_manager_service: OutputManagerHandle,
+        transaction_service: TransactionServiceHandle,
+        outbound_message_service: OutboundMessageRequester,
+        event_publisher: MultiSigEventSender,
+        node_identity: Arc<NodeIdentity>,
+        factories: CryptoFactories,
+        connectivity: ConnectivityRequester,
+        base_node_events: Fuse<BaseNodeEventReceiver>,
+        shutdown_signal: ShutdownSignal,
+    ) -> Self
+    {
+        Self {
+            request_stream: Some(request_stream),
+            message_stream: Some(message_stream),
+            db,
+            output_manager_service,
+            transaction_service,
+            outbound_message_service,
+            event_publisher,
+            node_identity,
+            factories,
+            shutdown_signal: Some(shutdown_signal),
+            connectivity,
+            base_node_events: Some(base_node_events),
+            base_node: None,
+            sessions: HashMap::new(),
+            pending_proposals: HashMap::new(),
+            early_messages: HashMap::new(),
+        }
+    }
+
+    pub async fn start(mut self) -> Result<(), MultiSigServiceError> {
+        let request_stream = self
+            .request_stream
+            .take()
+            .expect("MultiSig Service initialized without request_stream")
+            .fuse();
+        pin_mut!(request_stream);
+
+        let message_stream = self
+            .message_stream
+            .take()
+            .expect("MultiSig Service initialized without message_stream")
+            .fuse();
+        pin_mut!(message_stream);
+
+        let mut base_node_events = self
+            .base_node_events
+            .take()
+            .expect("MultiSig Service initialized without base node event stream");
+
+        let mut shutdown_signal = self
+            .shutdown_signal
+            .take()
+            .expect("MultiSig Service initialized without shutdown signal");
+
+        info!(target: LOG_TARGET, "MultiSig Service started");
+        loop {
+            futures::select! {
+                request_context = request_stream.select_next_some() => {
+                    let (request, reply_tx) = request_context.split();
+                    trace!(target: LOG_TARGET, "Handling MultiSig Service API Request: {}", request);
+                    let response = self.handle_request(request).await.map_err(|e| {
+                        warn!(target: LOG_TARGET, "Error handling request: {:?}", e);
+                        e
+                    });
+                    let _ = reply_tx.send(response).map_err(|e| {
+                        warn!(target: LOG_TARGET, "Failed to send reply");
+                        e
+                    });
+                },
+                message = message_stream.select_next_some() => {
+                    if let Err(e) = self.handle_message(message).await {
+                        warn!(target: LOG_TARGET, "Failed to handle multisig message: {}", e);
+                    }
+                },
+                event = base_node_events.select_next_some() => {
+                    match event {
+                        Ok(event) => self.handle_base_node_event(&*event).await,
+                        Err(e) => trace!(target: LOG_TARGET, "Lagging read on base node event stream: {:?}", e),
+                    }
+                },
+                _ = shutdown_signal => {
+                    info!(target: LOG_TARGET, "MultiSig Service shutting down because it received the shutdown signal");
+                    break Ok(());
+                }
+            }
+        }
+    }
+
+    async fn handle_request(
+        &mut self,
+        request: MultiSigServiceRequest,
+    ) -> Result<MultiSigServiceResponse, MultiSigServiceError>
+    {
+        match request {
+            MultiSigServiceRequest::CreateJointOutput((co_owners, amount, fee_per_gram, message)) => self
+                .create_joint_output(co_owners, amount, fee_per_gram, message)
+                .await
+                .map(MultiSigServiceResponse::JointOutputCreated),
+            MultiSigServiceRequest::GetJointOutputs => {
+                Ok(MultiSigServiceResponse::JointOutputs(self.get_joint_outputs().await?))
+            },
+            MultiSigServiceRequest::GetSpendProposals => Ok(MultiSigServiceResponse::SpendProposals(
+                self.pending_proposals.values().cloned().collect(),
+            )),
+            MultiSigServiceRequest::ProposeSpend((joint_commitment, fee_per_gram)) => self
+                .propose_spend(joint_commitment, fee_per_gram)
+                .await
+                .map(MultiSigServiceResponse::SpendProposed),
+            MultiSigServiceRequest::ApproveSpend(session_id) => self
+                .approve_spend(session_id)
+                .await
+                .map(|_| MultiSigServiceResponse::SpendApproved),
+        }
+    }
+
+    /// Fund a joint output from this wallet, acting as the dealer of the key shares. The spending key only exists in
+    /// memory until the shares have been dealt.
+    async fn create_joint_output(
+        &mut self,
+        co_owners: Vec<CommsPublicKey>,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        message: String,
+    ) -> Result<(TxId, Commitment), MultiSigServiceError>
+    {
+        let mut owners = vec![self.node_identity.public_key().clone()];
+        for owner in co_owners {
+            if !owners.contains(&owner) {
+                owners.push(owner);
+            }
+        }
+        if owners.len() < 2 {
+            return Err(MultiSigServiceError::NoCoOwners);
+        }
+
+        let spending_key = PrivateKey::random(&mut OsRng);
+        let (tx_id, fee, tx) = self
+            .output_manager_service
+            .create_joint_output_transaction(spending_key.clone(), amount, fee_per_gram, None, message.clone())
+            .await?;
+        let mut shares = JointOutputShare::deal(
+            &spending_key,
+            amount,
+            OutputFeatures::default(),
+            owners.clone(),
+            &self.factories.commitment,
+        );
+        drop(spending_key);
+        let commitment = shares[0].output.commitment.clone();
+
+        self.transaction_service
+            .submit_transaction(tx_id, tx, fee, amount, message)
+            .await?;
+
+        let own_share = shares.remove(0);
+        let mut stored = self.get_joint_outputs().await?;
+        stored.push(own_share);
+        self.set_joint_outputs(&stored).await?;
+
+        for share in shares {
+            let owner = owners[share.party_index].clone();
+            self.send_message(owner, MultiSigMessage::JointOutputShare(Box::new(share)))
+                .await?;
+        }
+        info!(
+            target: LOG_TARGET,
+            "Created joint output {} owned by {} parties (TxId: {})",
+            commitment.to_hex(),
+            owners.len(),
+            tx_id
+        );
+        Ok((tx_id, commitment))
+    }
+
+    /// Start a signing session that sweeps a joint output into this wallet
+    async fn propose_spend(
+        &mut self,
+        joint_commitment: Commitment,
+        fee_per_gram: MicroTari,
+    ) -> Result<u64, MultiSigServiceError>
+    {
+        let share = self.find_share(&joint_commitment).await?;
+        let metadata = TransactionMetadata {
+            fee: Fee::calculate(fee_per_gram, 1, 1, 1),
+            lock_height: 0,
+        };
+        let session_id = OsRng.next_u64();
+        let output_key = PrivateKey::random(&mut OsRng);
+        let (session, proposal) =
+            MultiSigSession::propose(session_id, &share, output_key.clone(), metadata, &self.factories)?;
+        self.broadcast(
+            &share.owners,
+            share.party_index,
+            MultiSigMessage::SpendProposal(Box::new(proposal)),
+        )
+        .await?;
+        self.sessions.insert(session_id, ActiveSession {
+            session,
+            owners: share.owners,
+            commitment_sent: false,
+            nonce_sent: false,
+            signature_sent: false,
+            output_key: Some(output_key),
+        });
+        self.advance_session(session_id).await?;
+        Ok(session_id)
+    }
+
+    /// Join a signing session proposed by a co-owner
+    async fn approve_spend(&mut self, session_id: u64) -> Result<(), MultiSigServiceError> {
+        let proposal = self
+            .pending_proposals
+            .remove(&session_id)
+            .ok_or_else(|| MultiSigServiceError::SessionNotFound(session_id))?;
+        let share = self.find_share(&proposal.joint_commitment).await?;
+        let session = MultiSigSession::join(&share, proposal, &self.factories)?;
+        self.sessions.insert(session_id, ActiveSession {
+            session,
+            owners: share.owners,
+            commitment_sent: false,
+            nonce_sent: false,
+            signature_sent: false,
+            output_key: None,
+        });
+        // Early messages were kept before their sender could be checked against the owners of the joint output
+        for (source, message) in self.early_messages.remove(&session_id).unwrap_or_default() {
+            if let Err(e) = self.apply_round_message(session_id, source, message) {
+                warn!(
+                    target: LOG_TARGET,
+                    "Ignoring early message for signing session {}: {}", session_id, e
+                );
+            }
+        }
+        self.advance_session(session_id).await
+    }
+
+    async fn handle_message(
+        &mut self,
+        message: DomainMessage<proto::MultiSigMessage>,
+    ) -> Result<(), MultiSigServiceError>
+    {
+        let (source, message) = message.into_origin_and_inner();
+        let message = MultiSigMessage::try_from(message).map_err(MultiSigServiceError::InvalidMessage)?;
+        match message {
+            MultiSigMessage::JointOutputShare(share) => self.receive_joint_output(source, *share).await,
+            MultiSigMessage::SpendProposal(proposal) => self.receive_proposal(source, *proposal).await,
+            message => {
+                let session_id = message.session_id().unwrap_or_default();
+                if !self.sessions.contains_key(&session_id) {
+                    // The co-owner might be ahead of us; keep the message until the session is approved
+                    if let Some(proposal) = self.pending_proposals.get(&session_id) {
+                        let joint_commitment = proposal.joint_commitment.clone();
+                        if !self.find_share(&joint_commitment).await?.owners.contains(&source) {
+                            return Err(MultiSigServiceError::InvalidMessage(
+                                "Received a round message from a peer that does not own the output".to_string(),
+                            ));
+                        }
+                        let early_messages = self.early_messages.entry(session_id).or_insert_with(Vec::new);
+                        if early_messages.len() >= MAX_EARLY_MESSAGES_PER_SESSION {
+                            return Err(MultiSigServiceError::TooManyEarlyMessages(session_id));
+                        }
+                        early_messages.push((source, message));
+                        return Ok(());
+                    }
+                    return Err(MultiSigServiceError::SessionNotFound(session_id));
+                }
+                if let Err(e) = self.apply_round_message(session_id, source, message) {
+                    self.fail_session(session_id, e.to_string());
+                    return Err(e);
+                }
+                self.advance_session(session_id).await
+            },
+        }
+    }
+
+    async fn receive_joint_output(
+        &mut self,
+        source: CommsPublicKey,
+        share: JointOutputShare,
+    ) -> Result<(), MultiSigServiceError>
+    {
+        share.verify(&self.factories.commitment)?;
+        if share.owners.get(share.party_index) != Some(self.node_identity.public_key()) {
+            return Err(MultiSigServiceError::InvalidMessage(
+                "Received a joint output share addressed to another owner".to_string(),
+            ));
+        }
+        if !share.owners.contains(&source) {
+            return Err(MultiSigServiceError::InvalidMessage(
+                "Received a joint output share from a peer that does not own the output".to_string(),
+            ));
+        }
+        let mut stored = self.get_joint_outputs().await?;
+        if stored.iter().any(|s| s.output.commitment == share.output.commitment) {
+            return Ok(());
+        }
+        let commitment = share.output.commitment.clone();
+        stored.push(share);
+        self.set_joint_outputs(&stored).await?;
+        info!(
+            target: LOG_TARGET,
+            "Received share of joint output {}",
+            commitment.to_hex()
+        );
+        let _ = self
+            .event_publisher
+            .send(Arc::new(MultiSigEvent::ReceivedJointOutput(commitment)));
+        Ok(())
+    }
+
+    async fn receive_proposal(
+        &mut self,
+        source: CommsPublicKey,
+        proposal: JointSpendProposal,
+    ) -> Result<(), MultiSigServiceError>
+    {
+        let share = self.find_share(&proposal.joint_commitment).await?;
+        if share.owners.get(proposal.coordinator_index) != Some(&source) {
+            return Err(MultiSigServiceError::InvalidMessage(
+                "Spend proposal was not sent by its coordinator".to_string(),
+            ));
+        }
+        proposal.verify(&share.output, &self.factories)?;
+        let session_id = proposal.session_id;
+        if self.sessions.contains_key(&session_id) || self.pending_proposals.contains_key(&session_id) {
+            return Ok(());
+        }
+        info!(
+            target: LOG_TARGET,
+            "Received proposal to spend joint output {} (session {})",
+            proposal.joint_commitment.to_hex(),
+            session_id
+        );
+        self.pending_proposals.insert(session_id, proposal);
+        let _ = self
+            .event_publisher
+            .send(Arc::new(MultiSigEvent::ReceivedSpendProposal(session_id)));
+        Ok(())
+    }
+
+    fn apply_round_message(
+        &mut self,
+        session_id: u64,
+        source: CommsPublicKey,
+        message: MultiSigMessage,
+    ) -> Result<(), MultiSigServiceError>
+    {
+        let active = self
+            .sessions
+            .get_mut(&session_id)
+            .ok_or_else(|| MultiSigServiceError::SessionNotFound(session_id))?;
+        let party_index = match &message {
+            MultiSigMessage::NonceCommitment { party_index, .. } |
+            MultiSigMessage::PublicNonce { party_index, .. } |
+            MultiSigMessage::PartialSignature { party_index, .. } => *party_index,
+            _ => return Err(MultiSigServiceError::InvalidMessage("Unexpected message".to_string())),
+        };
+        if active.owners.get(party_index) != Some(&source) {
+            return Err(MultiSigServiceError::InvalidMessage(format!(
+                "Message for party {} was not sent by that party",
+                party_index
+            )));
+        }
+        active.session.handle_message(message)?;
+        Ok(())
+    }
+
+    /// Send this party's messages for every round that has become available and finish the session once all partial
+    /// signatures are in
+    async fn advance_session(&mut self, session_id: u64) -> Result<(), MultiSigServiceError> {
+        let active = self
+            .sessions
+            .get_mut(&session_id)
+            .ok_or_else(|| MultiSigServiceError::SessionNotFound(session_id))?;
+        let mut outbound = Vec::new();
+        if !active.commitment_sent {
+            outbound.push(active.session.nonce_commitment_message());
+            active.commitment_sent = true;
+        }
+        if !active.nonce_sent && active.session.all_nonces_committed() {
+            outbound.push(active.session.public_nonce_message()?);
+            active.nonce_sent = true;
+        }
+        if !active.signature_sent && active.session.all_nonces_revealed() {
+            outbound.push(active.session.partial_signature_message()?);
+            active.signature_sent = true;
+        }
+        let is_complete = active.session.is_complete();
+        let owners = active.owners.clone();
+        let party_index = active.session.party_index();
+
+        for message in outbound {
+            self.broadcast(&owners, party_index, message).await?;
+        }
+        if is_complete {
+            self.complete_session(session_id).await?;
+        }
+        Ok(())
+    }
+
+    /// Finish a session once all partial signatures are in. Every owner keeps its share of the joint output and the
+    /// signed spend until the spend is confirmed on chain, see `check_joint_spends`. The coordinator broadcasts the
+    /// spend and adds the swept output as a pending incoming output of the spend, so that it only becomes spendable
+    /// once the spend is mined.
+    async fn complete_session(&mut self, session_id: u64) -> Result<(), MultiSigServiceError> {
+        let active = self
+            .sessions
+            .remove(&session_id)
+            .ok_or_else(|| MultiSigServiceError::SessionNotFound(session_id))?;
+        let tx = active.session.finalize(&self.factories)?;
+        let joint_output = active.session.joint_output();
+        let proposal = active.session.proposal();
+
+        let mut spends = self.get_joint_spends().await?;
+        spends.push(JointSpend {
+            joint_commitment: joint_output.commitment.clone(),
+            transaction: tx.clone(),
+        });
+        self.set_joint_spends(&spends).await?;
+
+        // Only the coordinator owns the swept output and broadcasts the transaction
+        if let Some(output_key) = active.output_key {
+            let tx_id = OsRng.next_u64();
+            let amount = proposal.amount(joint_output);
+            self.output_manager_service
+                .add_pending_incoming_output(tx_id, UnblindedOutput::new(amount, output_key, None))
+                .await?;
+            self.transaction_service
+                .submit_transaction(
+                    tx_id,
+                    tx,
+                    proposal.metadata.fee,
+                    amount,
+                    format!("Spend of joint output {}", joint_output.commitment.to_hex()),
+                )
+                .await?;
+        }
+
+        info!(
+            target: LOG_TARGET,
+            "Signing session {} completed, spend of joint output {} is waiting to be confirmed",
+            session_id,
+            joint_output.commitment.to_hex()
+        );
+        let _ = self
+            .event_publisher
+            .send(Arc::new(MultiSigEvent::SpendCompleted(session_id)));
+        Ok(())
+    }
+
+    async fn handle_base_node_event(&mut self, event: &BaseNodeEvent) {
+        match event {
+            BaseNodeEvent::BaseNodePeerSet(peer) => {
+                self.base_node = Some(peer.node_id.clone());
+            },
+            BaseNodeEvent::BaseNodeState(_) => {
+                let peer = match self.base_node.clone() {
+                    Some(peer) => peer,
+                    None => return,
+                };
+                if let Err(e) = self.check_joint_spends(peer.clone()).await {
+                    warn!(
+                        target: LOG_TARGET,
+                        "Error checking joint output spends with base node {}: {}", peer, e
+                    );
+                }
+            },
+        }
+    }
+
+    /// Query the base node for the signed spends of joint outputs. The shares and spends of a joint output are dropped
+    /// once one of its spends has `SPEND_CONFIRMATIONS` confirmations. A spend the base node does not know about is
+    /// submitted again.
+    async fn check_joint_spends(&mut self, peer: NodeId) -> Result<(), MultiSigServiceError> {
+        let spends = self.get_joint_spends().await?;
+        if spends.is_empty() {
+            return Ok(());
+        }
+
+        let mut connection = self.connectivity.dial_peer(peer).await?;
+        let mut client = connection.connect_rpc::<BaseNodeWalletRpcClient>().await?;
+        let mut confirmed = Vec::new();
+        for spend in &spends {
+            let signature = match spend.transaction.body.kernels().first() {
+                Some(kernel) => kernel.excess_sig.clone(),
+                None => continue,
+            };
+            let response = TxQueryResponse::try_from(client.transaction_query(signature.into()).await?)
+                .map_err(MultiSigServiceError::InvalidBaseNodeResponse)?;
+            match response.location {
+                TxLocation::Mined if response.confirmations >= SPEND_CONFIRMATIONS => {
+                    confirmed.push(spend.joint_commitment.clone());
+                },
+                TxLocation::NotStored if response.is_synced => {
+                    let response = TxSubmissionResponse::try_from(
+                        client.submit_transaction(spend.transaction.clone().into()).await?,
+                    )
+                    .map_err(MultiSigServiceError::InvalidBaseNodeResponse)?;
+                    debug!(
+                        target: LOG_TARGET,
+                        "Submitted the spend of joint output {} again (accepted: {}, rejection reason: {})",
+                        spend.joint_commitment.to_hex(),
+                        response.accepted,
+                        response.rejection_reason
+                    );
+                },
+                _ => (),
+            }
+        }
+        if confirmed.is_empty() {
+            return Ok(());
+        }
+
+        let mut shares = self.get_joint_outputs().await?;
+        shares.retain(|s| !confirmed.contains(&s.output.commitment));
+        self.set_joint_outputs(&shares).await?;
+        let remaining = spends
+            .into_iter()
+            .filter(|s| !confirmed.contains(&s.joint_commitment))
+            .collect::<Vec<_>>();
+        self.set_joint_spends(&remaining).await?;
+        for commitment in confirmed {
+            info!(
+                target: LOG_TARGET,
+                "Spend of joint output {} confirmed",
+                commitment.to_hex()
+            );
+            let _ = self
+                .event_publisher
+                .send(Arc::new(MultiSigEvent::SpendConfirmed(commitment)));
+        }
+        Ok(())
+    }
+
+    fn fail_session(&mut self, session_id: u64, reason: String) {
+        warn!(target: LOG_TARGET, "Signing session {} failed: {}", session_id, reason);
+        self.sessions.remove(&session_id);
+        let _ = self
+            .event_publisher
+            .send(Arc::new(MultiSigEvent::SessionFailed(session_id, reason)));
+    }
+
+    /// Send a message to every owner except this party
+    async fn broadcast(
+        &mut self,
+        owners: &[CommsPublicKey],
+        party_index: usize,
+        message: MultiSigMessage,
+    ) -> Result<(), MultiSigServiceError>
+    {
+        for (i, owner) in owners.iter().enumerate() {
+            if i != party_index {
+                self.send_message(owner.clone(), message.clone()).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn send_message(
+        &mut self,
+        destination: CommsPublicKey,
+        message: MultiSigMessage,
+    ) -> Result<(), MultiSigServiceError>
+    {
+        let _ = self
+            .outbound_message_service
+            .send_message(
+                SendMessageParams::new()
+                    .direct_public_key(destination.clone())
+                    .with_encryption(OutboundEncryption::EncryptFor(Box::new(destination)))
+                    .with_discovery(true)
+                    .finish(),
+                OutboundDomainMessage::new(TariMessageType::MultiSig, proto::MultiSigMessage::from(message)),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn find_share(&self, commitment: &Commitment) -> Result<JointOutputShare, MultiSigServiceError> {
+        self.get_joint_outputs()
+            .await?
+            .into_iter()
+            .find(|s| &s.output.commitment == commitment)
+            .ok_or(MultiSigServiceError::JointOutputNotFound)
+    }
+
+    async fn get_joint_outputs(&self) -> Result<Vec<JointOutputShare>, MultiSigServiceError> {
+        match self.db.get_client_key_value(JOINT_OUTPUTS_KEY.to_string()).await? {
+            Some(value) => {
+                serde_json::from_str(&value).map_err(|e| MultiSigServiceError::SerializationError(e.to_string()))
+            },
+            None => Ok(Vec::new()),
+        }
+    }
+
+    async fn get_joint_spends(&self) -> Result<Vec<JointSpend>, MultiSigServiceError> {
+        match self.db.get_client_key_value(JOINT_SPENDS_KEY.to_string()).await? {
+            Some(value) => {
+                serde_json::from_str(&value).map_err(|e| MultiSigServiceError::SerializationError(e.to_string()))
+            },
+            None => Ok(Vec::new()),
+        }
+    }
+
+    async fn set_joint_spends(&self, spends: &[JointSpend]) -> Result<(), MultiSigServiceError> {
+        let value =
+            serde_json::to_string(spends).map_err(|e| MultiSigServiceError::SerializationError(e.to_string()))?;
+        self.db
+            .set_client_key_value(JOINT_SPENDS_KEY.to_string(), value)
+            .await?;
+        Ok(())
+    }
+
+    async fn set_joint_outputs(&self, shares: &[JointOutputShare]) -> Result<(), MultiSigServiceError> {
+        let value =
+            serde_json::to_string(shares).map_err(|e| MultiSigServiceError::SerializationError(e.to_string()))?;
+        self.db
+            .set_client_key_value(JOINT_OUTPUTS_KEY.to_string(), value)
+            .await?;
+        Ok(())
+    }
+}
@@ -28,6 +28,8 @@ pub struct OutputManagerServiceConfig {
     pub max_utxo_query_size: usize,
     pub prevent_fee_gt_amount: bool,
     pub peer_dial_retry_timeout: Duration,
+    /// When set the Output Manager refuses to build or receive transactions
+    pub watch_only: bool,
 }
 
 impl Default for OutputManagerServiceConfig {
@@ -37,6 +39,7 @@ impl Default for OutputManagerServiceConfig {
             max_utxo_query_size: 5000,
             prevent_fee_gt_amount: true,
             peer_dial_retry_timeout: Duration::from_secs(20),
+            watch_only: false,
         }
     }
 }
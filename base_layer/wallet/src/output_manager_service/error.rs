@@ -20,7 +20,10 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use crate::{base_node_service::error::BaseNodeServiceError, output_manager_service::storage::database::DbKey};
+use crate::{
+    base_node_service::error::BaseNodeServiceError,
+    output_manager_service::{signer::SignerError, storage::database::DbKey},
+};
 use diesel::result::Error as DieselError;
 use tari_comms::{peer_manager::node_id::NodeIdError, protocol::rpc::RpcError};
 use tari_comms_dht::outbound::DhtOutboundError;
@@ -99,6 +102,14 @@ pub enum OutputManagerError {
     RpcError(#[from] RpcError),
     #[error("Node ID error: `{0}`")]
     NodeIdError(#[from] NodeIdError),
+    #[error("Signer error: `{0}`")]
+    SignerError(#[from] SignerError),
+    #[error("Transactions cannot be built by a watch-only wallet")]
+    WatchOnlyWallet,
+    #[error("An account named `{0}` already exists")]
+    AccountAlreadyExists(String),
+    #[error("No account named `{0}` exists")]
+    AccountNotFound(String),
 }
 
 #[derive(Debug, Error, PartialEq)]
@@ -25,20 +25,25 @@ use crate::{
         error::OutputManagerError,
         protocols::txo_validation_protocol::TxoValidationType,
         service::Balance,
-        storage::database::PendingTransactionOutputs,
+        signer::{KeyId, RewoundOutput},
+        storage::{
+            database::PendingTransactionOutputs,
+            models::{Account, AccountId},
+        },
         TxId,
     },
     types::ValidationRetryStrategy,
 };
 use aes_gcm::Aes256Gcm;
 use futures::{stream::Fuse, StreamExt};
+use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, fmt, sync::Arc, time::Duration};
 use tari_comms::types::CommsPublicKey;
 use tari_core::transactions::{
     tari_amount::MicroTari,
     transaction::{Transaction, TransactionInput, TransactionOutput, UnblindedOutput},
     transaction_protocol::sender::TransactionSenderMessage,
-    types::PublicKey,
+    types::{PrivateKey, PublicKey},
     ReceiverTransactionProtocol,
     SenderTransactionProtocol,
 };
@@ -50,12 +55,17 @@ use tower::Service;
 pub enum OutputManagerRequest {
     GetBalance,
     AddOutput(UnblindedOutput),
+    AddPendingIncomingOutput((TxId, UnblindedOutput)),
     GetRecipientTransaction(TransactionSenderMessage),
     GetCoinbaseTransaction((u64, MicroTari, MicroTari, u64)),
     ConfirmPendingTransaction(u64),
     ConfirmTransaction((u64, Vec<TransactionInput>, Vec<TransactionOutput>)),
     PrepareToSendTransaction((MicroTari, MicroTari, Option<u64>, String)),
     CreatePayToSelfTransaction((MicroTari, MicroTari, Option<u64>, String)),
+    CreateOneSidedTransaction((PublicKey, MicroTari, MicroTari, Option<u64>, String)),
+    CreateJointOutputTransaction((PrivateKey, MicroTari, MicroTari, Option<u64>, String)),
+    CreateClaimTransaction((UnblindedOutput, MicroTari)),
+    FinalizeTransaction(Box<SenderTransactionProtocol>),
     CancelTransaction(u64),
     TimeoutTransactions(Duration),
     GetPendingTransactions,
@@ -70,7 +80,15 @@ pub enum OutputManagerRequest {
     RemoveEncryption,
     GetPublicRewindKeys,
     FeeEstimate((MicroTari, MicroTari, u64, u64)),
-    RewindOutputs(Vec<TransactionOutput>),
+    FeeEstimateWithSelection((MicroTari, MicroTari, u64, u64)),
+    RecoverOutputs(Vec<TransactionOutput>),
+    RewindUnknownOutputs(Vec<TransactionOutput>),
+    AddRecoveredOutput(Box<RecoverableOutput>),
+    CreateAccount(String),
+    GetAccounts,
+    SetActiveAccount(String),
+    GetActiveAccount,
+    GetAccountTransactions,
 }
 
 impl fmt::Display for OutputManagerRequest {
@@ -79,11 +97,18 @@ impl fmt::Display for OutputManagerRequest {
         match self {
             GetBalance => write!(f, "GetBalance"),
             AddOutput(v) => write!(f, "AddOutput ({})", v.value),
+            AddPendingIncomingOutput((tx_id, v)) => write!(f, "AddPendingIncomingOutput ({}, {})", tx_id, v.value),
             GetRecipientTransaction(_) => write!(f, "GetRecipientTransaction"),
             ConfirmTransaction(v) => write!(f, "ConfirmTransaction ({})", v.0),
             ConfirmPendingTransaction(v) => write!(f, "ConfirmPendingTransaction ({})", v),
             PrepareToSendTransaction((_, _, _, msg)) => write!(f, "PrepareToSendTransaction ({})", msg),
             CreatePayToSelfTransaction((_, _, _, msg)) => write!(f, "CreatePayToSelfTransaction ({})", msg),
+            CreateOneSidedTransaction((k, _, _, _, msg)) => write!(f, "CreateOneSidedTransaction (to {}, {})", k, msg),
+            CreateJointOutputTransaction((_, v, _, _, msg)) => {
+                write!(f, "CreateJointOutputTransaction ({}, {})", v, msg)
+            },
+            CreateClaimTransaction((output, _)) => write!(f, "CreateClaimTransaction ({})", output.value),
+            FinalizeTransaction(_) => write!(f, "FinalizeTransaction"),
             CancelTransaction(v) => write!(f, "CancelTransaction ({})", v),
             TimeoutTransactions(d) => write!(f, "TimeoutTransactions ({}s)", d.as_secs()),
             GetPendingTransactions => write!(f, "GetPendingTransactions"),
@@ -99,7 +124,15 @@ impl fmt::Display for OutputManagerRequest {
             GetCoinbaseTransaction(_) => write!(f, "GetCoinbaseTransaction"),
             GetPublicRewindKeys => write!(f, "GetPublicRewindKeys"),
             FeeEstimate(_) => write!(f, "FeeEstimate"),
-            RewindOutputs(_) => write!(f, "RewindAndImportOutputs"),
+            FeeEstimateWithSelection(_) => write!(f, "FeeEstimateWithSelection"),
+            RecoverOutputs(_) => write!(f, "RecoverOutputs"),
+            RewindUnknownOutputs(_) => write!(f, "RewindUnknownOutputs"),
+            AddRecoveredOutput(output) => write!(f, "AddRecoveredOutput ({})", output.value),
+            CreateAccount(name) => write!(f, "CreateAccount ({})", name),
+            GetAccounts => write!(f, "GetAccounts"),
+            SetActiveAccount(name) => write!(f, "SetActiveAccount ({})", name),
+            GetActiveAccount => write!(f, "GetActiveAccount"),
+            GetAccountTransactions => write!(f, "GetAccountTransactions"),
         }
     }
 }
@@ -114,6 +147,10 @@ pub enum OutputManagerResponse {
     OutputConfirmed,
     PendingTransactionConfirmed,
     PayToSelfTransaction((TxId, MicroTari, Transaction)),
+    OneSidedTransaction((TxId, MicroTari, Transaction)),
+    JointOutputTransaction((TxId, MicroTari, Transaction)),
+    ClaimTransaction((TxId, MicroTari, Transaction)),
+    TransactionFinalized(Box<SenderTransactionProtocol>),
     TransactionConfirmed,
     TransactionToSend(SenderTransactionProtocol),
     TransactionCancelled,
@@ -130,7 +167,14 @@ pub enum OutputManagerResponse {
     EncryptionRemoved,
     PublicRewindKeys(Box<PublicRewindKeys>),
     FeeEstimate(MicroTari),
-    RewindOutputs(Vec<UnblindedOutput>),
+    FeeEstimateWithSelection((MicroTari, Vec<UnblindedOutput>)),
+    RecoveredOutputs(Vec<RewoundOutput>),
+    RecoverableOutputs(Vec<RecoverableOutput>),
+    RecoveredOutputAdded(bool),
+    AccountCreated(Account),
+    Accounts(Vec<Account>),
+    ActiveAccount(Account),
+    AccountTransactions(HashMap<TxId, AccountId>),
 }
 
 pub type OutputManagerEventSender = broadcast::Sender<Arc<OutputManagerEvent>>;
@@ -147,7 +191,15 @@ pub enum OutputManagerEvent {
     Error(String),
 }
 
+/// An output on the blockchain that was rewound with the signer's rewind keys but has not been added to the wallet
 #[derive(Debug, Clone)]
+pub struct RecoverableOutput {
+    pub output: TransactionOutput,
+    pub value: MicroTari,
+    pub key_id: KeyId,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PublicRewindKeys {
     pub rewind_public_key: PublicKey,
     pub rewind_blinding_public_key: PublicKey,
@@ -182,6 +234,24 @@ impl OutputManagerHandle {
         }
     }
 
+    /// Add an output that is received by the transaction `tx_id`. The output is pending until the transaction is
+    /// confirmed.
+    pub async fn add_pending_incoming_output(
+        &mut self,
+        tx_id: TxId,
+        output: UnblindedOutput,
+    ) -> Result<(), OutputManagerError>
+    {
+        match self
+            .handle
+            .call(OutputManagerRequest::AddPendingIncomingOutput((tx_id, output)))
+            .await??
+        {
+            OutputManagerResponse::OutputAdded => Ok(()),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn get_balance(&mut self) -> Result<Balance, OutputManagerError> {
         match self.handle.call(OutputManagerRequest::GetBalance).await?? {
             OutputManagerResponse::Balance(b) => Ok(b),
@@ -275,6 +345,31 @@ impl OutputManagerHandle {
         }
     }
 
+    /// Get a fee estimate as per `fee_estimate` along with the unspent outputs that would currently be selected to
+    /// fund the transaction. The outputs are not encumbered.
+    pub async fn fee_estimate_with_selection(
+        &mut self,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        num_kernels: u64,
+        num_outputs: u64,
+    ) -> Result<(MicroTari, Vec<UnblindedOutput>), OutputManagerError>
+    {
+        match self
+            .handle
+            .call(OutputManagerRequest::FeeEstimateWithSelection((
+                amount,
+                fee_per_gram,
+                num_kernels,
+                num_outputs,
+            )))
+            .await??
+        {
+            OutputManagerResponse::FeeEstimateWithSelection(estimate) => Ok(estimate),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn confirm_pending_transaction(&mut self, tx_id: u64) -> Result<(), OutputManagerError> {
         match self
             .handle
@@ -444,13 +539,49 @@ impl OutputManagerHandle {
         }
     }
 
-    pub async fn rewind_outputs(
+    /// Rewind the given outputs with the signer's rewind keys and add those that belong to this wallet and are not
+    /// already known. The value and key id of each output that was added are returned.
+    pub async fn recover_outputs(
+        &mut self,
+        outputs: Vec<TransactionOutput>,
+    ) -> Result<Vec<RewoundOutput>, OutputManagerError>
+    {
+        match self
+            .handle
+            .call(OutputManagerRequest::RecoverOutputs(outputs))
+            .await??
+        {
+            OutputManagerResponse::RecoveredOutputs(outputs) => Ok(outputs),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Rewind the given outputs with the signer's rewind keys and return those that belong to this wallet and are not
+    /// already known, without adding them. This allows a caller to record an output elsewhere before it is added with
+    /// `add_recovered_output`.
+    pub async fn rewind_unknown_outputs(
         &mut self,
         outputs: Vec<TransactionOutput>,
-    ) -> Result<Vec<UnblindedOutput>, OutputManagerError>
+    ) -> Result<Vec<RecoverableOutput>, OutputManagerError>
     {
-        match self.handle.call(OutputManagerRequest::RewindOutputs(outputs)).await?? {
-            OutputManagerResponse::RewindOutputs(outputs) => Ok(outputs),
+        match self
+            .handle
+            .call(OutputManagerRequest::RewindUnknownOutputs(outputs))
+            .await??
+        {
+            OutputManagerResponse::RecoverableOutputs(outputs) => Ok(outputs),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Add an output returned by `rewind_unknown_outputs`. Returns false if the output is already known.
+    pub async fn add_recovered_output(&mut self, output: RecoverableOutput) -> Result<bool, OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::AddRecoveredOutput(Box::new(output)))
+            .await??
+        {
+            OutputManagerResponse::RecoveredOutputAdded(added) => Ok(added),
             _ => Err(OutputManagerError::UnexpectedApiResponse),
         }
     }
@@ -477,4 +608,134 @@ impl OutputManagerHandle {
             _ => Err(OutputManagerError::UnexpectedApiResponse),
         }
     }
+
+    /// Create a transaction that pays `amount` to `recipient_public_key` without any interaction with the recipient.
+    /// The returned transaction is complete and can be broadcast immediately.
+    pub async fn create_one_sided_transaction(
+        &mut self,
+        recipient_public_key: PublicKey,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        lock_height: Option<u64>,
+        message: String,
+    ) -> Result<(TxId, MicroTari, Transaction), OutputManagerError>
+    {
+        match self
+            .handle
+            .call(OutputManagerRequest::CreateOneSidedTransaction((
+                recipient_public_key,
+                amount,
+                fee_per_gram,
+                lock_height,
+                message,
+            )))
+            .await??
+        {
+            OutputManagerResponse::OneSidedTransaction(outputs) => Ok(outputs),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Create a transaction that pays `amount` into an output spendable with `spending_key`. The output is not added to
+    /// this wallet. The returned transaction is complete and can be broadcast immediately.
+    pub async fn create_joint_output_transaction(
+        &mut self,
+        spending_key: PrivateKey,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        lock_height: Option<u64>,
+        message: String,
+    ) -> Result<(TxId, MicroTari, Transaction), OutputManagerError>
+    {
+        match self
+            .handle
+            .call(OutputManagerRequest::CreateJointOutputTransaction((
+                spending_key,
+                amount,
+                fee_per_gram,
+                lock_height,
+                message,
+            )))
+            .await??
+        {
+            OutputManagerResponse::JointOutputTransaction(outputs) => Ok(outputs),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Create a transaction that spends the one-sided payment `output` into an output with a new key held by the
+    /// signer, which the sender of the payment does not know. The returned transaction is complete and can be broadcast
+    /// once `output` is mature.
+    pub async fn create_claim_transaction(
+        &mut self,
+        output: UnblindedOutput,
+        fee_per_gram: MicroTari,
+    ) -> Result<(TxId, MicroTari, Transaction), OutputManagerError>
+    {
+        match self
+            .handle
+            .call(OutputManagerRequest::CreateClaimTransaction((output, fee_per_gram)))
+            .await??
+        {
+            OutputManagerResponse::ClaimTransaction(outputs) => Ok(outputs),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Finalize a transaction that was prepared by `prepare_transaction_to_send` once the recipient's reply has been
+    /// added. The sender's partial signature is produced by the signer.
+    pub async fn finalize_transaction(
+        &mut self,
+        stp: SenderTransactionProtocol,
+    ) -> Result<SenderTransactionProtocol, OutputManagerError>
+    {
+        match self
+            .handle
+            .call(OutputManagerRequest::FinalizeTransaction(Box::new(stp)))
+            .await??
+        {
+            OutputManagerResponse::TransactionFinalized(stp) => Ok(*stp),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Create a new named account with its own key branch
+    pub async fn create_account(&mut self, name: String) -> Result<Account, OutputManagerError> {
+        match self.handle.call(OutputManagerRequest::CreateAccount(name)).await?? {
+            OutputManagerResponse::AccountCreated(account) => Ok(account),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// List all the accounts of this wallet, starting with the default account
+    pub async fn get_accounts(&mut self) -> Result<Vec<Account>, OutputManagerError> {
+        match self.handle.call(OutputManagerRequest::GetAccounts).await?? {
+            OutputManagerResponse::Accounts(accounts) => Ok(accounts),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Select the account that balances, outputs and new transactions apply to
+    pub async fn set_active_account(&mut self, name: String) -> Result<Account, OutputManagerError> {
+        match self.handle.call(OutputManagerRequest::SetActiveAccount(name)).await?? {
+            OutputManagerResponse::ActiveAccount(account) => Ok(account),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    pub async fn get_active_account(&mut self) -> Result<Account, OutputManagerError> {
+        match self.handle.call(OutputManagerRequest::GetActiveAccount).await?? {
+            OutputManagerResponse::ActiveAccount(account) => Ok(account),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Returns the account each transaction belongs to. Transactions that are not included belong to the default
+    /// account, see [account_of](crate::output_manager_service::storage::models::account_of).
+    pub async fn get_account_transactions(&mut self) -> Result<HashMap<TxId, AccountId>, OutputManagerError> {
+        match self.handle.call(OutputManagerRequest::GetAccountTransactions).await?? {
+            OutputManagerResponse::AccountTransactions(txs) => Ok(txs),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
 }
@@ -26,12 +26,14 @@ use crate::{
         config::OutputManagerServiceConfig,
         handle::OutputManagerHandle,
         service::OutputManagerService,
+        signer::TransactionSigner,
         storage::database::{OutputManagerBackend, OutputManagerDatabase},
     },
     transaction_service::handle::TransactionServiceHandle,
 };
 use futures::{future, Future};
 use log::*;
+use std::sync::Arc;
 use tari_comms::connectivity::ConnectivityRequester;
 use tari_core::{
     consensus::{ConsensusConstantsBuilder, Network},
@@ -51,6 +53,7 @@ pub mod handle;
 pub mod protocols;
 #[allow(unused_assignments)]
 pub mod service;
+pub mod signer;
 pub mod storage;
 
 const LOG_TARGET: &str = "wallet::output_manager_service::initializer";
@@ -64,6 +67,7 @@ where T: OutputManagerBackend
     backend: Option<T>,
     factories: CryptoFactories,
     network: Network,
+    signer: Option<Arc<dyn TransactionSigner>>,
 }
 
 impl<T> OutputManagerServiceInitializer<T>
@@ -75,8 +79,15 @@ where T: OutputManagerBackend + 'static
             backend: Some(backend),
             factories,
             network,
+            signer: None,
         }
     }
+
+    /// Use `signer` instead of the default in-process signer for all operations that require the wallet's secret keys
+    pub fn with_signer(mut self, signer: Arc<dyn TransactionSigner>) -> Self {
+        self.signer = Some(signer);
+        self
+    }
 }
 
 impl<T> ServiceInitializer for OutputManagerServiceInitializer<T>
@@ -105,6 +116,7 @@ where T: OutputManagerBackend + 'static
         let factories = self.factories.clone();
         let config = self.config.clone();
         let constants = ConsensusConstantsBuilder::new(self.network).build();
+        let signer = self.signer.clone();
 
         context.spawn_when_ready(move |handles| async move {
             let transaction_service = handles.expect_handle::<TransactionServiceHandle>();
@@ -122,6 +134,7 @@ where T: OutputManagerBackend + 'static
                 handles.get_shutdown_signal(),
                 base_node_service_handle,
                 connectivity_manager,
+                signer,
             )
             .await
             .expect("Could not initialize Output Manager Service")
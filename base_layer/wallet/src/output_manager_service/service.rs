@@ -24,17 +24,24 @@ use crate::{
     base_node_service::handle::BaseNodeServiceHandle,
     output_manager_service::{
         config::OutputManagerServiceConfig,
-        error::{OutputManagerError, OutputManagerProtocolError},
-        handle::{OutputManagerEventSender, OutputManagerRequest, OutputManagerResponse, PublicRewindKeys},
+        error::{OutputManagerError, OutputManagerProtocolError, OutputManagerStorageError},
+        handle::{
+            OutputManagerEventSender,
+            OutputManagerRequest,
+            OutputManagerResponse,
+            PublicRewindKeys,
+            RecoverableOutput,
+        },
         protocols::txo_validation_protocol::{TxoValidationProtocol, TxoValidationType},
+        signer::{in_process::InProcessSigner, KernelRequest, KeyBranch, KeyId, RewoundOutput, TransactionSigner},
         storage::{
             database::{KeyManagerState, OutputManagerBackend, OutputManagerDatabase, PendingTransactionOutputs},
-            models::DbUnblindedOutput,
+            models::{Account, AccountId, DbUnblindedOutput},
         },
         TxId,
     },
     transaction_service::handle::TransactionServiceHandle,
-    types::{HashDigest, KeyDigest, ValidationRetryStrategy},
+    types::{HashDigest, ValidationRetryStrategy},
 };
 use futures::{pin_mut, stream::FuturesUnordered, StreamExt};
 use log::*;
@@ -43,6 +50,7 @@ use std::{
     cmp::Ordering,
     collections::HashMap,
     fmt::{self, Display},
+    sync::Arc,
     time::Duration,
 };
 use tari_comms::{connectivity::ConnectivityRequester, types::CommsPublicKey};
@@ -59,21 +67,20 @@ use tari_core::{
             TransactionOutput,
             UnblindedOutput,
         },
-        transaction_protocol::{sender::TransactionSenderMessage, RewindData},
+        transaction_protocol::{
+            build_challenge,
+            one_sided::OneSidedPaymentKeys,
+            recipient::RecipientSignedMessage,
+            sender::TransactionSenderMessage,
+            transaction_initializer::SenderTransactionInitializer,
+        },
         types::{CryptoFactories, PrivateKey, PublicKey},
         CoinbaseBuilder,
         ReceiverTransactionProtocol,
         SenderTransactionProtocol,
     },
 };
-use tari_crypto::{
-    keys::{PublicKey as PublicKeyTrait, SecretKey as SecretKeyTrait},
-    range_proof::REWIND_USER_MESSAGE_LENGTH,
-};
-use tari_key_manager::{
-    key_manager::KeyManager,
-    mnemonic::{from_secret_key, MnemonicLanguage},
-};
+use tari_crypto::keys::SecretKey as SecretKeyTrait;
 use tari_service_framework::reply_channel;
 use tari_shutdown::ShutdownSignal;
 use tokio::{
@@ -84,10 +91,6 @@ use tokio::{
 const LOG_TARGET: &str = "wallet::output_manager_service";
 const LOG_TARGET_STRESS: &str = "stress_test::output_manager_service";
 
-const KEY_MANAGER_COINBASE_BRANCH_KEY: &str = "coinbase";
-const KEY_MANAGER_RECOVERY_VIEWONLY_BRANCH_KEY: &str = "recovery_viewonly";
-const KEY_MANAGER_RECOVERY_BLINDING_BRANCH_KEY: &str = "recovery_blinding";
-
 /// This service will manage a wallet's available outputs and the key manager that produces the keys for these outputs.
 /// The service will assemble transactions to be sent from the wallets available outputs and provide keys to receive
 /// outputs. When the outputs are detected on the blockchain the Transaction service will call this Service to confirm
@@ -96,8 +99,10 @@ pub struct OutputManagerService<TBackend>
 where TBackend: OutputManagerBackend + 'static
 {
     resources: OutputManagerResources<TBackend>,
-    key_manager: Mutex<KeyManager<PrivateKey, KeyDigest>>,
-    coinbase_key_manager: Mutex<KeyManager<PrivateKey, KeyDigest>>,
+    signer: Arc<dyn TransactionSigner>,
+    primary_key_index: Mutex<u64>,
+    coinbase_key_index: Mutex<u64>,
+    active_account: Mutex<Account>,
     request_stream:
         Option<reply_channel::Receiver<OutputManagerRequest, Result<OutputManagerResponse, OutputManagerError>>>,
     base_node_update_publisher: broadcast::Sender<CommsPublicKey>,
@@ -122,13 +127,19 @@ where TBackend: OutputManagerBackend + 'static
         shutdown_signal: ShutdownSignal,
         base_node_service: BaseNodeServiceHandle,
         connectivity_manager: ConnectivityRequester,
+        signer: Option<Arc<dyn TransactionSigner>>,
     ) -> Result<OutputManagerService<TBackend>, OutputManagerError>
     {
-        // Check to see if there is any persisted state, otherwise start fresh
+        // Check to see if there is any persisted state, otherwise start fresh. An external signer holds its own master
+        // key, so only the key index is kept for it.
         let key_manager_state = match db.get_key_manager_state().await? {
             None => {
+                let master_key = match signer {
+                    Some(_) => PrivateKey::default(),
+                    None => PrivateKey::random(&mut OsRng),
+                };
                 let starting_state = KeyManagerState {
-                    master_key: PrivateKey::random(&mut OsRng),
+                    master_key,
                     branch_seed: "".to_string(),
                     primary_key_index: 0,
                 };
@@ -138,42 +149,31 @@ where TBackend: OutputManagerBackend + 'static
             Some(km) => km,
         };
 
-        let coinbase_key_manager = KeyManager::<PrivateKey, KeyDigest>::from(
-            key_manager_state.master_key.clone(),
-            KEY_MANAGER_COINBASE_BRANCH_KEY.to_string(),
-            0,
-        );
-
-        let key_manager = KeyManager::<PrivateKey, KeyDigest>::from(
-            key_manager_state.master_key.clone(),
-            key_manager_state.branch_seed,
-            key_manager_state.primary_key_index,
-        );
-
-        let rewind_key_manager = KeyManager::<PrivateKey, KeyDigest>::from(
-            key_manager_state.master_key.clone(),
-            KEY_MANAGER_RECOVERY_VIEWONLY_BRANCH_KEY.to_string(),
-            0,
-        );
-        let rewind_key = rewind_key_manager.derive_key(0)?.k;
-
-        let rewind_blinding_key_manager = KeyManager::<PrivateKey, KeyDigest>::from(
-            key_manager_state.master_key,
-            KEY_MANAGER_RECOVERY_BLINDING_BRANCH_KEY.to_string(),
-            0,
-        );
-        let rewind_blinding_key = rewind_blinding_key_manager.derive_key(0)?.k;
-
-        let rewind_data = RewindData {
-            rewind_key,
-            rewind_blinding_key,
-            proof_message: [0u8; REWIND_USER_MESSAGE_LENGTH],
+        let signer = match signer {
+            Some(signer) => signer,
+            None => Arc::new(InProcessSigner::new(
+                key_manager_state.master_key,
+                key_manager_state.branch_seed,
+                factories.clone(),
+                Arc::new(db.clone()),
+            )?),
         };
 
         // Clear any encumberances for transactions that were being negotiated but did not complete to become official
         // Pending Transactions.
         db.clear_short_term_encumberances().await?;
 
+        // Restore the account that was active when the wallet was last running
+        let active_account = match db.get_active_account().await? {
+            Some(id) => db
+                .get_accounts()
+                .await?
+                .into_iter()
+                .find(|a| a.id == id)
+                .unwrap_or_else(Account::default_account),
+            None => Account::default_account(),
+        };
+
         let resources = OutputManagerResources {
             config,
             db,
@@ -181,7 +181,6 @@ where TBackend: OutputManagerBackend + 'static
             factories,
             base_node_public_key: None,
             event_publisher,
-            rewind_data,
             consensus_constants,
             connectivity_manager,
             shutdown_signal,
@@ -191,8 +190,10 @@ where TBackend: OutputManagerBackend + 'static
 
         Ok(OutputManagerService {
             resources,
-            key_manager: Mutex::new(key_manager),
-            coinbase_key_manager: Mutex::new(coinbase_key_manager),
+            signer,
+            primary_key_index: Mutex::new(key_manager_state.primary_key_index),
+            coinbase_key_index: Mutex::new(0),
+            active_account: Mutex::new(active_account),
             request_stream: Some(request_stream),
             base_node_update_publisher,
             base_node_service,
@@ -260,6 +261,10 @@ where TBackend: OutputManagerBackend + 'static
             OutputManagerRequest::AddOutput(uo) => {
                 self.add_output(uo).await.map(|_| OutputManagerResponse::OutputAdded)
             },
+            OutputManagerRequest::AddPendingIncomingOutput((tx_id, uo)) => self
+                .add_pending_incoming_output(tx_id, uo)
+                .await
+                .map(|_| OutputManagerResponse::OutputAdded),
             OutputManagerRequest::GetBalance => {
                 let current_chain_tip = match self.base_node_service.get_chain_metadata().await {
                     Ok(metadata) => metadata.map(|m| m.height_of_longest_chain()),
@@ -285,10 +290,42 @@ where TBackend: OutputManagerBackend + 'static
                 .create_pay_to_self_transaction(amount, fee_per_gram, lock_height, message)
                 .await
                 .map(OutputManagerResponse::PayToSelfTransaction),
+            OutputManagerRequest::CreateOneSidedTransaction((
+                recipient_public_key,
+                amount,
+                fee_per_gram,
+                lock_height,
+                message,
+            )) => self
+                .create_one_sided_transaction(recipient_public_key, amount, fee_per_gram, lock_height, message)
+                .await
+                .map(OutputManagerResponse::OneSidedTransaction),
+            OutputManagerRequest::CreateJointOutputTransaction((
+                spending_key,
+                amount,
+                fee_per_gram,
+                lock_height,
+                message,
+            )) => self
+                .create_joint_output_transaction(spending_key, amount, fee_per_gram, lock_height, message)
+                .await
+                .map(OutputManagerResponse::JointOutputTransaction),
+            OutputManagerRequest::CreateClaimTransaction((output, fee_per_gram)) => self
+                .create_claim_transaction(output, fee_per_gram)
+                .await
+                .map(OutputManagerResponse::ClaimTransaction),
+            OutputManagerRequest::FinalizeTransaction(mut stp) => self
+                .finalize_transaction(&mut stp)
+                .await
+                .map(|_| OutputManagerResponse::TransactionFinalized(stp)),
             OutputManagerRequest::FeeEstimate((amount, fee_per_gram, num_kernels, num_outputs)) => self
                 .fee_estimate(amount, fee_per_gram, num_kernels, num_outputs)
                 .await
                 .map(OutputManagerResponse::FeeEstimate),
+            OutputManagerRequest::FeeEstimateWithSelection((amount, fee_per_gram, num_kernels, num_outputs)) => self
+                .fee_estimate_with_selection(amount, fee_per_gram, num_kernels, num_outputs)
+                .await
+                .map(OutputManagerResponse::FeeEstimateWithSelection),
             OutputManagerRequest::ConfirmPendingTransaction(tx_id) => self
                 .confirm_encumberance(tx_id)
                 .await
@@ -310,19 +347,23 @@ where TBackend: OutputManagerBackend + 'static
                 .await
                 .map(OutputManagerResponse::PendingTransactions),
             OutputManagerRequest::GetSpentOutputs => {
+                let account_id = self.active_account_id().await;
                 let outputs = self
                     .fetch_spent_outputs()
                     .await?
                     .into_iter()
+                    .filter(|o| o.account_id == account_id)
                     .map(|v| v.into())
                     .collect();
                 Ok(OutputManagerResponse::SpentOutputs(outputs))
             },
             OutputManagerRequest::GetUnspentOutputs => {
+                let account_id = self.active_account_id().await;
                 let outputs = self
                     .fetch_unspent_outputs()
                     .await?
                     .into_iter()
+                    .filter(|o| o.account_id == account_id)
                     .map(|v| v.into())
                     .collect();
                 Ok(OutputManagerResponse::UnspentOutputs(outputs))
@@ -362,13 +403,41 @@ where TBackend: OutputManagerBackend + 'static
                 .await
                 .map(|_| OutputManagerResponse::EncryptionRemoved)
                 .map_err(OutputManagerError::OutputManagerStorageError),
-            OutputManagerRequest::GetPublicRewindKeys => Ok(OutputManagerResponse::PublicRewindKeys(Box::new(
-                self.get_rewind_public_keys(),
-            ))),
-            OutputManagerRequest::RewindOutputs(outputs) => self
-                .rewind_outputs(outputs)
+            OutputManagerRequest::GetPublicRewindKeys => self
+                .get_rewind_public_keys()
+                .await
+                .map(|keys| OutputManagerResponse::PublicRewindKeys(Box::new(keys))),
+            OutputManagerRequest::RecoverOutputs(outputs) => self
+                .recover_outputs(outputs)
+                .await
+                .map(OutputManagerResponse::RecoveredOutputs),
+            OutputManagerRequest::RewindUnknownOutputs(outputs) => self
+                .rewind_unknown_outputs(outputs)
+                .await
+                .map(OutputManagerResponse::RecoverableOutputs),
+            OutputManagerRequest::AddRecoveredOutput(output) => self
+                .add_recovered_output(*output)
+                .await
+                .map(OutputManagerResponse::RecoveredOutputAdded),
+            OutputManagerRequest::CreateAccount(name) => self
+                .create_account(name)
+                .await
+                .map(OutputManagerResponse::AccountCreated),
+            OutputManagerRequest::GetAccounts => self.get_accounts().await.map(OutputManagerResponse::Accounts),
+            OutputManagerRequest::SetActiveAccount(name) => self
+                .set_active_account(name)
+                .await
+                .map(OutputManagerResponse::ActiveAccount),
+            OutputManagerRequest::GetActiveAccount => Ok(OutputManagerResponse::ActiveAccount(
+                self.active_account.lock().await.clone(),
+            )),
+            OutputManagerRequest::GetAccountTransactions => self
+                .resources
+                .db
+                .get_account_transactions()
                 .await
-                .map(OutputManagerResponse::RewindOutputs),
+                .map(OutputManagerResponse::AccountTransactions)
+                .map_err(OutputManagerError::OutputManagerStorageError),
         }
     }
 
@@ -428,8 +497,36 @@ where TBackend: OutputManagerBackend + 'static
         Ok(self.resources.db.add_unspent_output(output).await?)
     }
 
+    /// Add an output that is received by the transaction `tx_id` to the active account. It becomes spendable once the
+    /// transaction is confirmed.
+    async fn add_pending_incoming_output(
+        &mut self,
+        tx_id: TxId,
+        output: UnblindedOutput,
+    ) -> Result<(), OutputManagerError>
+    {
+        debug!(
+            target: LOG_TARGET,
+            "Add pending incoming output of value {} (TxId: {})", output.value, tx_id
+        );
+        let account_id = self.active_account_id().await;
+        let output = DbUnblindedOutput::from_unblinded_output(output, &self.resources.factories)?;
+        self.resources
+            .db
+            .accept_incoming_pending_transaction(tx_id, output, account_id, None)
+            .await?;
+        self.resources.db.set_account_transaction(tx_id, account_id).await?;
+        self.confirm_encumberance(tx_id).await
+    }
+
+    /// Get the balance of the active account
     async fn get_balance(&self, current_chain_tip: Option<u64>) -> Result<Balance, OutputManagerError> {
-        let balance = self.resources.db.get_balance(current_chain_tip).await?;
+        let account_id = self.active_account_id().await;
+        let balance = self
+            .resources
+            .db
+            .get_account_balance(current_chain_tip, account_id)
+            .await?;
         trace!(target: LOG_TARGET, "Balance: {:?}", balance);
         Ok(balance)
     }
@@ -440,43 +537,55 @@ where TBackend: OutputManagerBackend + 'static
         sender_message: TransactionSenderMessage,
     ) -> Result<ReceiverTransactionProtocol, OutputManagerError>
     {
-        let (tx_id, amount) = match sender_message.single() {
-            Some(data) => (data.tx_id, data.amount),
+        self.ensure_not_watch_only()?;
+        let data = match sender_message.single() {
+            Some(data) => data,
             _ => return Err(OutputManagerError::InvalidSenderMessage),
         };
+        if data.amount == MicroTari::from(0) {
+            return Err(OutputManagerError::InvalidSenderMessage);
+        }
+
+        let key_id = self.get_next_spend_key().await?;
+        let account_id = self.active_account_id().await;
+        let output = self
+            .signer
+            .create_output(key_id, data.amount, OutputFeatures::default())
+            .await?;
+        let request = KernelRequest::for_output(key_id);
+        let commitments = self.signer.kernel_commitments(&request).await?;
+        let challenge = build_challenge(&(&data.public_nonce + &commitments.public_nonce), &data.metadata);
+        let partial_signature = self.signer.sign_kernel(&request, &challenge).await?;
 
-        let key = self.get_next_spend_key().await?;
         self.resources
             .db
             .accept_incoming_pending_transaction(
-                tx_id,
-                amount,
-                key.clone(),
-                OutputFeatures::default(),
-                &self.resources.factories,
+                data.tx_id,
+                DbUnblindedOutput::from_signer_output(&output, data.amount, key_id),
+                account_id,
                 None,
             )
             .await?;
+        self.resources
+            .db
+            .set_account_transaction(data.tx_id, account_id)
+            .await?;
 
-        self.confirm_encumberance(tx_id).await?;
-
-        let nonce = PrivateKey::random(&mut OsRng);
-
-        let rtp = ReceiverTransactionProtocol::new_with_rewindable_output(
-            sender_message,
-            nonce,
-            key,
-            OutputFeatures::default(),
-            &self.resources.factories,
-            &self.resources.rewind_data,
-        );
+        self.confirm_encumberance(data.tx_id).await?;
 
-        Ok(rtp)
+        Ok(ReceiverTransactionProtocol::new_with_signed_data(
+            RecipientSignedMessage {
+                tx_id: data.tx_id,
+                output,
+                public_spend_key: commitments.public_excess,
+                partial_signature,
+            },
+        ))
     }
 
     /// Request a Coinbase transaction for a specific block height. All existing pending transactions with
     /// this blockheight will be cancelled.
-    /// The key will be derived from the coinbase specific keychain using the blockheight as an index. The coinbase
+    /// The key is the next key of the coinbase keychain held by the signer, see `get_next_coinbase_key`. The coinbase
     /// keychain is based on the wallets master_key and the "coinbase" branch.
     async fn get_coinbase_transaction(
         &mut self,
@@ -486,35 +595,44 @@ where TBackend: OutputManagerBackend + 'static
         block_height: u64,
     ) -> Result<Transaction, OutputManagerError>
     {
+        self.ensure_not_watch_only()?;
         self.resources
             .db
             .cancel_pending_transaction_at_block_height(block_height)
             .await?;
 
-        let key = self.get_next_coinbase_key().await?;
+        let key_id = self.get_next_coinbase_key().await?;
 
-        let nonce = PrivateKey::random(&mut OsRng);
-        let (tx, _) = CoinbaseBuilder::new(self.resources.factories.clone())
+        let builder = CoinbaseBuilder::new(self.resources.factories.clone())
             .with_block_height(block_height)
-            .with_fees(fees)
-            .with_spend_key(key.clone())
-            .with_nonce(nonce)
-            .with_rewind_data(self.resources.rewind_data.clone())
-            .build_with_reward(&self.resources.consensus_constants, reward)?;
+            .with_fees(fees);
+        let (value, features) = builder.output_value_and_features(&self.resources.consensus_constants, reward)?;
+        let output = self.signer.create_output(key_id, value, features).await?;
+        let request = KernelRequest::for_output(key_id);
+        let commitments = self.signer.kernel_commitments(&request).await?;
+        let signature = self
+            .signer
+            .sign_kernel(&request, &CoinbaseBuilder::kernel_challenge(&commitments.public_nonce))
+            .await?;
+        let tx = builder.build_with_signed_output(
+            &self.resources.consensus_constants,
+            reward,
+            output.clone(),
+            &commitments.public_excess,
+            signature,
+        )?;
 
+        let account_id = self.active_account_id().await;
         self.resources
             .db
             .accept_incoming_pending_transaction(
                 tx_id,
-                reward + fees,
-                key,
-                OutputFeatures::create_coinbase(
-                    block_height + self.resources.consensus_constants.coinbase_lock_height(),
-                ),
-                &self.resources.factories,
+                DbUnblindedOutput::from_signer_output(&output, value, key_id),
+                account_id,
                 Some(block_height),
             )
             .await?;
+        self.resources.db.set_account_transaction(tx_id, account_id).await?;
 
         self.confirm_encumberance(tx_id).await?;
         Ok(tx)
@@ -537,12 +655,7 @@ where TBackend: OutputManagerBackend + 'static
             ));
         }
 
-        if pending_transaction.outputs_to_be_received[0]
-            .unblinded_output
-            .as_transaction_input(&self.resources.factories.commitment, OutputFeatures::default())
-            .commitment !=
-            received_output.commitment
-        {
+        if pending_transaction.outputs_to_be_received[0].commitment != received_output.commitment {
             return Err(OutputManagerError::IncompleteTransaction(
                 "unexpected commitment received",
             ));
@@ -570,6 +683,21 @@ where TBackend: OutputManagerBackend + 'static
         num_kernels: u64,
         num_outputs: u64,
     ) -> Result<MicroTari, OutputManagerError>
+    {
+        self.fee_estimate_with_selection(amount, fee_per_gram, num_kernels, num_outputs)
+            .await
+            .map(|(fee, _)| fee)
+    }
+
+    /// Get a fee estimate along with the unspent outputs that the current UTXO selection strategy would use to fund the
+    /// transaction.
+    async fn fee_estimate_with_selection(
+        &mut self,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        num_kernels: u64,
+        num_outputs: u64,
+    ) -> Result<(MicroTari, Vec<UnblindedOutput>), OutputManagerError>
     {
         debug!(
             target: LOG_TARGET,
@@ -588,7 +716,7 @@ where TBackend: OutputManagerBackend + 'static
         let fee = Fee::calculate_with_minimum(fee_per_gram, num_kernels as usize, utxos.len(), num_outputs as usize);
 
         debug!(target: LOG_TARGET, "Fee calculated: {}", fee);
-        Ok(fee)
+        Ok((fee, utxos.into_iter().map(|o| o.unblinded_output).collect()))
     }
 
     /// Prepare a Sender Transaction Protocol for the amount and fee_per_gram specified. If required a change output
@@ -601,72 +729,48 @@ where TBackend: OutputManagerBackend + 'static
         message: String,
     ) -> Result<SenderTransactionProtocol, OutputManagerError>
     {
+        self.ensure_not_watch_only()?;
         debug!(
             target: LOG_TARGET,
             "Preparing to send transaction. Amount: {}. Fee per gram: {}. ", amount, fee_per_gram,
         );
         let (outputs, _) = self.select_utxos(amount, fee_per_gram, 1, None).await?;
-        let total = outputs
-            .iter()
-            .fold(MicroTari::from(0), |acc, x| acc + x.unblinded_output.value);
 
         let offset = PrivateKey::random(&mut OsRng);
-        let nonce = PrivateKey::random(&mut OsRng);
 
         let mut builder = SenderTransactionProtocol::builder(1);
         builder
             .with_lock_height(lock_height.unwrap_or(0))
             .with_fee_per_gram(fee_per_gram)
             .with_offset(offset.clone())
-            .with_private_nonce(nonce.clone())
             .with_amount(0, amount)
             .with_message(message)
             .with_prevent_fee_gt_amount(self.resources.config.prevent_fee_gt_amount);
 
-        for uo in outputs.iter() {
-            builder.with_input(
-                uo.unblinded_output.as_transaction_input(
-                    &self.resources.factories.commitment,
-                    uo.unblinded_output.features.clone(),
-                ),
-                uo.unblinded_output.clone(),
-            );
-        }
+        self.add_inputs(&mut builder, &outputs);
         debug!(
             target: LOG_TARGET,
             "Calculating fee for tx with: Fee per gram: {}. Num outputs: {}",
             amount,
             outputs.len()
         );
-        let fee_without_change = Fee::calculate(fee_per_gram, 1, outputs.len(), 1);
-        let mut change_key: Option<PrivateKey> = None;
-        // If the input values > the amount to be sent + fee_without_change then we will need to include a change
-        // output
-        if total > amount + fee_without_change {
-            let key = self.get_next_spend_key().await?;
-            change_key = Some(key.clone());
-            builder.with_rewindable_change_secret(key, self.resources.rewind_data.clone());
-        }
+        // If a change output is created it is added to the pending outputs
+        let mut change_output = Vec::<DbUnblindedOutput>::new();
+        self.add_change_and_signer_excess(&mut builder, offset, &outputs, &mut change_output)
+            .await?;
 
         let stp = builder
             .build::<HashDigest>(&self.resources.factories)
             .map_err(|e| OutputManagerError::BuildError(e.message))?;
 
-        // If a change output was created add it to the pending_outputs list.
-        let mut change_output = Vec::<DbUnblindedOutput>::new();
-        if let Some(key) = change_key {
-            change_output.push(DbUnblindedOutput::from_unblinded_output(
-                UnblindedOutput::new(stp.get_amount_to_self()?, key, None),
-                &self.resources.factories,
-            )?);
-        }
-
+        let account_id = self.active_account_id().await;
         let tx_id = stp.get_tx_id()?;
+        self.resources.db.set_account_transaction(tx_id, account_id).await?;
         // The Transaction Protocol built successfully so we will pull the unspent outputs out of the unspent list and
         // store them until the transaction times out OR is confirmed
         self.resources
             .db
-            .encumber_outputs(tx_id, outputs, change_output)
+            .encumber_outputs(tx_id, account_id, outputs, change_output)
             .await?;
 
         debug!(target: LOG_TARGET, "Prepared transaction (TxId: {}) to send", tx_id);
@@ -686,11 +790,10 @@ where TBackend: OutputManagerBackend + 'static
         message: String,
     ) -> Result<(TxId, MicroTari, Transaction), OutputManagerError>
     {
+        self.ensure_not_watch_only()?;
         let (inputs, _) = self.select_utxos(amount, fee_per_gram, 1, None).await?;
-        let total = inputs.iter().map(|x| x.unblinded_output.value).sum::<MicroTari>();
 
         let offset = PrivateKey::random(&mut OsRng);
-        let nonce = PrivateKey::random(&mut OsRng);
 
         // Create builder with no recipients (other than ourselves)
         let mut builder = SenderTransactionProtocol::builder(0);
@@ -698,72 +801,303 @@ where TBackend: OutputManagerBackend + 'static
             .with_lock_height(lock_height.unwrap_or(0))
             .with_fee_per_gram(fee_per_gram)
             .with_offset(offset.clone())
-            .with_private_nonce(nonce.clone())
             .with_amount(0, amount)
             .with_message(message)
             .with_prevent_fee_gt_amount(self.resources.config.prevent_fee_gt_amount);
 
-        for uo in &inputs {
-            builder.with_input(
-                uo.unblinded_output.as_transaction_input(
-                    &self.resources.factories.commitment,
-                    uo.unblinded_output.features.clone(),
-                ),
-                uo.unblinded_output.clone(),
-            );
-        }
+        self.add_inputs(&mut builder, &inputs);
 
-        let spend_key = self.get_next_spend_key().await?;
-        let utxo = DbUnblindedOutput::rewindable_from_unblinded_output(
-            UnblindedOutput::new(amount, spend_key, None),
-            &self.resources.factories,
-            &self.resources.rewind_data,
-        )?;
-        builder.with_output(utxo.unblinded_output.clone());
+        let (output, utxo) = self.create_signer_output(amount, OutputFeatures::default()).await?;
+        builder.with_signed_output(output, amount);
 
         let mut outputs = vec![utxo];
-        let mut change_key = None;
-
-        let fee = Fee::calculate(fee_per_gram, 1, inputs.len(), 1);
-        let change_value = total.saturating_sub(amount).saturating_sub(fee);
-        if change_value > 0.into() {
-            let key = self.get_next_spend_key().await?;
-            change_key = Some(key.clone());
-            builder.with_rewindable_change_secret(key, self.resources.rewind_data.clone());
-        }
+        self.add_change_and_signer_excess(&mut builder, offset, &inputs, &mut outputs)
+            .await?;
 
-        let factories = CryptoFactories::default();
         let mut stp = builder
             .build::<HashDigest>(&self.resources.factories)
             .map_err(|e| OutputManagerError::BuildError(e.message))?;
 
-        if let Some(key) = change_key {
-            let change_amount = stp.get_change_amount()?;
-            let change_output = DbUnblindedOutput::rewindable_from_unblinded_output(
-                UnblindedOutput::new(change_amount, key, None),
-                &self.resources.factories,
-                &self.resources.rewind_data,
-            )?;
-
-            outputs.push(change_output);
-        }
-
+        let account_id = self.active_account_id().await;
         let tx_id = stp.get_tx_id()?;
+        self.resources.db.set_account_transaction(tx_id, account_id).await?;
         trace!(
             target: LOG_TARGET,
             "Encumber send to self transaction ({}) outputs.",
             tx_id
         );
-        self.resources.db.encumber_outputs(tx_id, inputs, outputs).await?;
+        self.resources
+            .db
+            .encumber_outputs(tx_id, account_id, inputs, outputs)
+            .await?;
         self.confirm_encumberance(tx_id).await?;
         let fee = stp.get_fee_amount()?;
         trace!(target: LOG_TARGET, "Finalize send-to-self transaction ({}).", tx_id);
-        stp.finalize(KernelFeatures::empty(), &factories)?;
+        self.finalize_transaction(&mut stp).await?;
         let tx = stp.take_transaction()?;
 
         Ok((tx_id, fee, tx))
     }
 
+    /// Create a complete transaction paying `amount` to `recipient_public_key`. The spending key and rewind data of
+    /// the recipient's output are derived from the Diffie-Hellman shared secret of the kernel nonce and the recipient's
+    /// public key so that the recipient can find and spend the output without having taken part in the transaction.
+    async fn create_one_sided_transaction(
+        &mut self,
+        recipient_public_key: PublicKey,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        lock_height: Option<u64>,
+        message: String,
+    ) -> Result<(TxId, MicroTari, Transaction), OutputManagerError>
+    {
+        self.create_transaction_to_output(
+            OutputRecipient::OneSided(recipient_public_key),
+            amount,
+            fee_per_gram,
+            lock_height,
+            message,
+        )
+        .await
+    }
+
+    /// Create a complete transaction paying `amount` into an output with the given spending key. The key is chosen by
+    /// the caller (e.g. the dealer of a jointly owned output), so the output is not added to this wallet.
+    async fn create_joint_output_transaction(
+        &mut self,
+        spending_key: PrivateKey,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        lock_height: Option<u64>,
+        message: String,
+    ) -> Result<(TxId, MicroTari, Transaction), OutputManagerError>
+    {
+        self.create_transaction_to_output(
+            OutputRecipient::SpendingKey(spending_key),
+            amount,
+            fee_per_gram,
+            lock_height,
+            message,
+        )
+        .await
+    }
+
+    /// Build, encumber and finalize a transaction that pays `amount` into an output of `recipient` from this wallet's
+    /// UTXOs, without any interaction with the owner of the output. Only the change output is added to this wallet.
+    async fn create_transaction_to_output(
+        &mut self,
+        recipient: OutputRecipient,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        lock_height: Option<u64>,
+        message: String,
+    ) -> Result<(TxId, MicroTari, Transaction), OutputManagerError>
+    {
+        self.ensure_not_watch_only()?;
+        let (inputs, _) = self.select_utxos(amount, fee_per_gram, 1, None).await?;
+
+        let offset = PrivateKey::random(&mut OsRng);
+
+        // Create builder with no recipients, the recipient's output is created by the sender
+        let mut builder = SenderTransactionProtocol::builder(0);
+        builder
+            .with_lock_height(lock_height.unwrap_or(0))
+            .with_fee_per_gram(fee_per_gram)
+            .with_offset(offset.clone())
+            .with_amount(0, amount)
+            .with_message(message)
+            .with_prevent_fee_gt_amount(self.resources.config.prevent_fee_gt_amount);
+
+        self.add_inputs(&mut builder, &inputs);
+
+        // The keys of a one-sided payment depend on the kernel nonce, which depends on the change output, so the change
+        // is worked out before the recipient's output is created. It is only added if the remainder covers the fee of
+        // the recipient and change outputs.
+        let mut outputs = Vec::new();
+        let total = inputs.iter().map(|x| x.unblinded_output.value).sum::<MicroTari>();
+        let fee_with_change = Fee::calculate(fee_per_gram, 1, inputs.len(), 2);
+        let change_value = total.saturating_sub(amount).saturating_sub(fee_with_change);
+        if change_value > 0.into() {
+            let (output, change_output) = self
+                .create_signer_output(change_value, OutputFeatures::default())
+                .await?;
+            builder.with_signed_output(output, change_value);
+            outputs.push(change_output);
+        }
+
+        let request = kernel_request(&inputs, &outputs, offset);
+        match recipient {
+            OutputRecipient::OneSided(recipient_public_key) => {
+                let shared_secret = self
+                    .signer
+                    .kernel_nonce_shared_secret(&request, &recipient_public_key)
+                    .await?;
+                let recipient_keys = OneSidedPaymentKeys::from_shared_secret(&shared_secret)?;
+                builder.with_rewindable_output(
+                    UnblindedOutput::new(amount, recipient_keys.spending_key, None),
+                    recipient_keys.rewind_data,
+                )
+            },
+            OutputRecipient::SpendingKey(spending_key) => {
+                builder.with_output(UnblindedOutput::new(amount, spending_key, None))
+            },
+        };
+        let commitments = self.signer.kernel_commitments(&request).await?;
+        builder.with_signer_excess(commitments.public_excess, commitments.public_nonce);
+
+        let mut stp = builder
+            .build::<HashDigest>(&self.resources.factories)
+            .map_err(|e| OutputManagerError::BuildError(e.message))?;
+
+        let account_id = self.active_account_id().await;
+        let tx_id = stp.get_tx_id()?;
+        self.resources.db.set_account_transaction(tx_id, account_id).await?;
+        trace!(target: LOG_TARGET, "Encumber transaction ({}) outputs.", tx_id);
+        self.resources
+            .db
+            .encumber_outputs(tx_id, account_id, inputs, outputs)
+            .await?;
+        self.confirm_encumberance(tx_id).await?;
+        let fee = stp.get_fee_amount()?;
+        trace!(target: LOG_TARGET, "Finalize transaction ({}).", tx_id);
+        self.finalize_transaction(&mut stp).await?;
+        let tx = stp.take_transaction()?;
+
+        Ok((tx_id, fee, tx))
+    }
+
+    /// Create a transaction that claims the one-sided payment `output` by spending it into an output with a new key
+    /// held by the signer. The commitment key of a one-sided payment is also known to its sender, the new key is not.
+    /// The claim output is rewindable with this wallet's rewind keys so that it is found again when recovering from
+    /// seed.
+    async fn create_claim_transaction(
+        &mut self,
+        output: UnblindedOutput,
+        fee_per_gram: MicroTari,
+    ) -> Result<(TxId, MicroTari, Transaction), OutputManagerError>
+    {
+        self.ensure_not_watch_only()?;
+        let input = DbUnblindedOutput::from_unblinded_output(output.clone(), &self.resources.factories)?;
+        // The output may already have been claimed or spent
+        if self
+            .resources
+            .db
+            .get_unspent_outputs()
+            .await?
+            .iter()
+            .all(|o| o.commitment != input.commitment)
+        {
+            return Err(OutputManagerStorageError::ValuesNotFound.into());
+        }
+
+        let fee = Fee::calculate(fee_per_gram, 1, 1, 1);
+        let claim_value = output
+            .value
+            .checked_sub(fee)
+            .filter(|v| *v > 0.into())
+            .ok_or(OutputManagerError::NotEnoughFunds)?;
+
+        let offset = PrivateKey::random(&mut OsRng);
+        let mut builder = SenderTransactionProtocol::builder(0);
+        builder
+            .with_lock_height(output.features.maturity)
+            .with_fee_per_gram(fee_per_gram)
+            .with_offset(offset.clone())
+            .with_message("Claim one-sided payment".to_string())
+            .with_input(
+                output.as_transaction_input(&self.resources.factories.commitment, output.features.clone()),
+                output,
+            );
+        let (claim_output, claim_db_output) = self
+            .create_signer_output(claim_value, OutputFeatures::default())
+            .await?;
+        builder.with_signed_output(claim_output, claim_value);
+        let inputs = vec![input];
+        let outputs = vec![claim_db_output];
+        let commitments = self
+            .signer
+            .kernel_commitments(&kernel_request(&inputs, &outputs, offset))
+            .await?;
+        builder.with_signer_excess(commitments.public_excess, commitments.public_nonce);
+
+        let mut stp = builder
+            .build::<HashDigest>(&self.resources.factories)
+            .map_err(|e| OutputManagerError::BuildError(e.message))?;
+
+        let account_id = self.active_account_id().await;
+        let tx_id = stp.get_tx_id()?;
+        self.resources.db.set_account_transaction(tx_id, account_id).await?;
+        trace!(target: LOG_TARGET, "Encumber claim transaction ({}) outputs.", tx_id);
+        self.resources
+            .db
+            .encumber_outputs(tx_id, account_id, inputs, outputs)
+            .await?;
+        self.confirm_encumberance(tx_id).await?;
+        let fee = stp.get_fee_amount()?;
+        trace!(target: LOG_TARGET, "Finalize claim transaction ({}).", tx_id);
+        self.finalize_transaction(&mut stp).await?;
+        let tx = stp.take_transaction()?;
+
+        Ok((tx_id, fee, tx))
+    }
+
+    /// Add `inputs` to `builder`. The keys of the outputs that were built by the signer are held by it, so those are
+    /// added as signed inputs.
+    fn add_inputs(&self, builder: &mut SenderTransactionInitializer, inputs: &[DbUnblindedOutput]) {
+        for uo in inputs {
+            match uo.key_id {
+                Some(_) => builder.with_signed_input(uo.as_transaction_input(), uo.unblinded_output.value),
+                None => builder.with_input(
+                    uo.unblinded_output.as_transaction_input(
+                        &self.resources.factories.commitment,
+                        uo.unblinded_output.features.clone(),
+                    ),
+                    uo.unblinded_output.clone(),
+                ),
+            };
+        }
+    }
+
+    /// Have the signer build an output of `value` with a new key of the active account
+    async fn create_signer_output(
+        &self,
+        value: MicroTari,
+        features: OutputFeatures,
+    ) -> Result<(TransactionOutput, DbUnblindedOutput), OutputManagerError>
+    {
+        let key_id = self.get_next_spend_key().await?;
+        let output = self.signer.create_output(key_id, value, features).await?;
+        let account_id = self.active_account_id().await;
+        let db_output = DbUnblindedOutput::from_signer_output(&output, value, key_id).with_account(account_id);
+        Ok((output, db_output))
+    }
+
+    /// Add a change output to `builder` if any change is due, followed by the signer's share of the kernel excess.
+    /// `outputs` are the outputs of the transaction that belong to this wallet, the change output is added to them.
+    async fn add_change_and_signer_excess(
+        &self,
+        builder: &mut SenderTransactionInitializer,
+        offset: PrivateKey,
+        inputs: &[DbUnblindedOutput],
+        outputs: &mut Vec<DbUnblindedOutput>,
+    ) -> Result<(), OutputManagerError>
+    {
+        let (_, change) = builder
+            .calculate_fee_and_change()
+            .map_err(OutputManagerError::BuildError)?;
+        if change > 0.into() {
+            let (output, change_output) = self.create_signer_output(change, OutputFeatures::default()).await?;
+            builder.with_signed_change_output(output, change);
+            outputs.push(change_output);
+        }
+        let commitments = self
+            .signer
+            .kernel_commitments(&kernel_request(inputs, outputs, offset))
+            .await?;
+        builder.with_signer_excess(commitments.public_excess, commitments.public_nonce);
+        Ok(())
+    }
+
     /// Confirm that a transaction has finished being negotiated between parties so the short-term encumberance can be
     /// made official
     async fn confirm_encumberance(&mut self, tx_id: u64) -> Result<(), OutputManagerError> {
@@ -786,11 +1120,10 @@ where TBackend: OutputManagerBackend + 'static
 
         // Check that outputs to be spent can all be found in the provided transaction inputs
         for output_to_spend in pending_transaction.outputs_to_be_spent.iter() {
-            let input_to_check = output_to_spend
-                .unblinded_output
-                .as_transaction_input(&self.resources.factories.commitment, OutputFeatures::default());
-
-            if inputs.iter().all(|input| input.commitment != input_to_check.commitment) {
+            if inputs
+                .iter()
+                .all(|input| input.commitment != output_to_spend.commitment)
+            {
                 return Err(OutputManagerError::IncompleteTransaction(
                     "outputs to spend are missing",
                 ));
@@ -799,13 +1132,9 @@ where TBackend: OutputManagerBackend + 'static
 
         // Check that outputs to be received can all be found in the provided transaction outputs
         for output_to_receive in pending_transaction.outputs_to_be_received.iter() {
-            let output_to_check = output_to_receive
-                .unblinded_output
-                .as_transaction_input(&self.resources.factories.commitment, OutputFeatures::default());
-
             if outputs
                 .iter()
-                .all(|output| output.commitment != output_to_check.commitment)
+                .all(|output| output.commitment != output_to_receive.commitment)
             {
                 return Err(OutputManagerError::IncompleteTransaction(
                     "outputs to receive are missing",
@@ -860,7 +1189,16 @@ where TBackend: OutputManagerBackend + 'static
         let mut fee_without_change = MicroTari::from(0);
         let mut fee_with_change = MicroTari::from(0);
 
-        let uo = self.resources.db.fetch_sorted_unspent_outputs().await?;
+        // Only the outputs of the active account may be spent
+        let account_id = self.active_account_id().await;
+        let uo = self
+            .resources
+            .db
+            .fetch_sorted_unspent_outputs()
+            .await?
+            .into_iter()
+            .filter(|o| o.account_id == account_id)
+            .collect::<Vec<_>>();
 
         // Attempt to get the chain tip height
         let chain_metadata = self.base_node_service.get_chain_metadata().await?;
@@ -1008,6 +1346,7 @@ where TBackend: OutputManagerBackend + 'static
         lock_height: Option<u64>,
     ) -> Result<(u64, Transaction, MicroTari, MicroTari), OutputManagerError>
     {
+        self.ensure_not_watch_only()?;
         trace!(
             target: LOG_TARGET,
             "Select UTXOs and estimate coin split transaction fee."
@@ -1033,24 +1372,15 @@ where TBackend: OutputManagerBackend + 'static
 
         trace!(target: LOG_TARGET, "Construct coin split transaction.");
         let offset = PrivateKey::random(&mut OsRng);
-        let nonce = PrivateKey::random(&mut OsRng);
         let mut builder = SenderTransactionProtocol::builder(0);
         builder
             .with_lock_height(lock_height.unwrap_or(0))
             .with_fee_per_gram(fee_per_gram)
-            .with_offset(offset.clone())
-            .with_private_nonce(nonce.clone());
+            .with_offset(offset.clone());
         trace!(target: LOG_TARGET, "Add inputs to coin split transaction.");
-        for uo in inputs.iter() {
-            builder.with_input(
-                uo.unblinded_output.as_transaction_input(
-                    &self.resources.factories.commitment,
-                    uo.unblinded_output.clone().features,
-                ),
-                uo.unblinded_output.clone(),
-            );
-        }
+        self.add_inputs(&mut builder, &inputs);
         trace!(target: LOG_TARGET, "Add outputs to coin split transaction.");
+        let account_id = self.active_account_id().await;
         let mut outputs: Vec<DbUnblindedOutput> = Vec::with_capacity(output_count);
         let change_output = utxo_total_value
             .checked_sub(fee)
@@ -1064,87 +1394,192 @@ where TBackend: OutputManagerBackend + 'static
                 change_output
             };
 
-            let spend_key = self.get_next_spend_key().await?;
-            let utxo = DbUnblindedOutput::from_unblinded_output(
-                UnblindedOutput::new(output_amount, spend_key, None),
-                &self.resources.factories,
-            )?;
-            outputs.push(utxo.clone());
-            builder.with_output(utxo.unblinded_output);
+            let (output, utxo) = self
+                .create_signer_output(output_amount, OutputFeatures::default())
+                .await?;
+            outputs.push(utxo);
+            builder.with_signed_output(output, output_amount);
         }
+        let commitments = self
+            .signer
+            .kernel_commitments(&kernel_request(&inputs, &outputs, offset))
+            .await?;
+        builder.with_signer_excess(commitments.public_excess, commitments.public_nonce);
         trace!(target: LOG_TARGET, "Build coin split transaction.");
-        let factories = CryptoFactories::default();
         let mut stp = builder
             .build::<HashDigest>(&self.resources.factories)
             .map_err(|e| OutputManagerError::BuildError(e.message))?;
         // The Transaction Protocol built successfully so we will pull the unspent outputs out of the unspent list and
         // store them until the transaction times out OR is confirmed
         let tx_id = stp.get_tx_id()?;
+        self.resources.db.set_account_transaction(tx_id, account_id).await?;
         trace!(
             target: LOG_TARGET,
             "Encumber coin split transaction ({}) outputs.",
             tx_id
         );
-        self.resources.db.encumber_outputs(tx_id, inputs, outputs).await?;
+        self.resources
+            .db
+            .encumber_outputs(tx_id, account_id, inputs, outputs)
+            .await?;
         self.confirm_encumberance(tx_id).await?;
         trace!(target: LOG_TARGET, "Finalize coin split transaction ({}).", tx_id);
-        stp.finalize(KernelFeatures::empty(), &factories)?;
+        self.finalize_transaction(&mut stp).await?;
         let tx = stp.take_transaction()?;
         Ok((tx_id, tx, fee, utxo_total_value))
     }
 
-    /// Return the Seed words for the current Master Key set in the Key Manager
+    /// Finalize a transaction that was built by this service. The signer's share of the kernel is determined by the
+    /// outputs of the pending transaction, and the signer signs the kernel challenge with it.
+    async fn finalize_transaction(&self, stp: &mut SenderTransactionProtocol) -> Result<(), OutputManagerError> {
+        let tx_id = stp.get_tx_id()?;
+        let pending_transaction = self.resources.db.fetch_pending_transaction_outputs(tx_id).await?;
+        let request = kernel_request(
+            &pending_transaction.outputs_to_be_spent,
+            &pending_transaction.outputs_to_be_received,
+            stp.get_offset()?,
+        );
+        let signature = self.signer.sign_kernel(&request, &stp.get_kernel_challenge()?).await?;
+        stp.finalize_with_signer_signature(KernelFeatures::empty(), &self.resources.factories, signature)?;
+        Ok(())
+    }
+
+    /// Return the Seed words for the current Master Key held by the signer
     pub async fn get_seed_words(&self) -> Result<Vec<String>, OutputManagerError> {
-        Ok(from_secret_key(
-            self.key_manager.lock().await.master_key(),
-            &MnemonicLanguage::English,
-        )?)
+        Ok(self.signer.seed_words().await?)
     }
 
     /// Return the public rewind keys
-    fn get_rewind_public_keys(&self) -> PublicRewindKeys {
-        PublicRewindKeys {
-            rewind_public_key: PublicKey::from_secret_key(&self.resources.rewind_data.rewind_key),
-            rewind_blinding_public_key: PublicKey::from_secret_key(&self.resources.rewind_data.rewind_blinding_key),
+    async fn get_rewind_public_keys(&self) -> Result<PublicRewindKeys, OutputManagerError> {
+        Ok(self.signer.rewind_public_keys().await?)
+    }
+
+    /// Have the signer rewind the given transaction outputs and add those that belong to this wallet to the unspent
+    /// outputs of the default account. Outputs that are already known are skipped. The outputs that were added are
+    /// returned.
+    async fn recover_outputs(
+        &mut self,
+        outputs: Vec<TransactionOutput>,
+    ) -> Result<Vec<RewoundOutput>, OutputManagerError>
+    {
+        let mut recovered_outputs = Vec::new();
+        for output in self.rewind_unknown_outputs(outputs).await? {
+            let rewound = RewoundOutput {
+                value: output.value,
+                key_id: output.key_id,
+            };
+            if self.add_recovered_output(output).await? {
+                recovered_outputs.push(rewound);
+            }
         }
+
+        Ok(recovered_outputs)
     }
 
-    /// Attempt to rewind all of the given transaction outputs into unblinded outputs
-    async fn rewind_outputs(
+    async fn rewind_unknown_outputs(
         &mut self,
         outputs: Vec<TransactionOutput>,
-    ) -> Result<Vec<UnblindedOutput>, OutputManagerError>
+    ) -> Result<Vec<RecoverableOutput>, OutputManagerError>
     {
-        let rewind_data = &self.resources.rewind_data;
+        let mut recoverable_outputs = Vec::new();
+        for output in outputs {
+            let rewound = match self.signer.rewind_output(&output).await? {
+                Some(rewound) => rewound,
+                None => continue,
+            };
+            if self.resources.db.is_known_output(output.commitment.clone()).await? {
+                trace!(target: LOG_TARGET, "Rewound output is already known");
+                continue;
+            }
+            recoverable_outputs.push(RecoverableOutput {
+                output,
+                value: rewound.value,
+                key_id: rewound.key_id,
+            });
+        }
 
-        let rewound_outputs: Vec<UnblindedOutput> = outputs
-            .into_iter()
-            .filter_map(|output| {
-                output
-                    .full_rewind_range_proof(
-                        &self.resources.factories.range_proof,
-                        &rewind_data.rewind_key,
-                        &rewind_data.rewind_blinding_key,
-                    )
-                    .ok()
-            })
-            .map(|output| UnblindedOutput::new(output.committed_value, output.blinding_factor, None))
-            .collect();
-
-        Ok(rewound_outputs)
+        Ok(recoverable_outputs)
+    }
+
+    async fn add_recovered_output(&mut self, output: RecoverableOutput) -> Result<bool, OutputManagerError> {
+        let db_output = DbUnblindedOutput::from_signer_output(&output.output, output.value, output.key_id);
+        match self.resources.db.add_unspent_output(db_output).await {
+            Ok(()) => Ok(true),
+            Err(OutputManagerStorageError::DuplicateOutput) => {
+                trace!(target: LOG_TARGET, "Recovered output is already known");
+                Ok(false)
+            },
+            Err(e) => Err(e.into()),
+        }
     }
 
-    async fn get_next_spend_key(&self) -> Result<PrivateKey, OutputManagerError> {
-        let mut km = self.key_manager.lock().await;
-        let key = km.next_key()?;
+    /// A watch-only wallet holds no spending keys for its outputs so it may not build or receive transactions
+    fn ensure_not_watch_only(&self) -> Result<(), OutputManagerError> {
+        if self.resources.config.watch_only {
+            return Err(OutputManagerError::WatchOnlyWallet);
+        }
+        Ok(())
+    }
+
+    /// Allocate the next key of the active account. The key itself is held by the signer.
+    async fn get_next_spend_key(&self) -> Result<KeyId, OutputManagerError> {
+        let mut account = self.active_account.lock().await;
+        if !account.is_default() {
+            account.key_index += 1;
+            self.resources.db.set_account(account.clone()).await?;
+            return Ok(KeyId::new(KeyBranch::Account(account.id), account.key_index));
+        }
+
+        let mut index = self.primary_key_index.lock().await;
+        *index += 1;
         self.resources.db.increment_key_index().await?;
-        Ok(key.k)
+        Ok(KeyId::new(KeyBranch::Spend, *index))
     }
 
-    async fn get_next_coinbase_key(&self) -> Result<PrivateKey, OutputManagerError> {
-        let mut km = self.coinbase_key_manager.lock().await;
-        let key = km.next_key()?;
-        Ok(key.k)
+    async fn active_account_id(&self) -> AccountId {
+        self.active_account.lock().await.id
+    }
+
+    /// Create a new account. Account ids are allocated sequentially after the default account.
+    async fn create_account(&mut self, name: String) -> Result<Account, OutputManagerError> {
+        let accounts = self.get_accounts().await?;
+        if accounts.iter().any(|a| a.name == name) {
+            return Err(OutputManagerError::AccountAlreadyExists(name));
+        }
+        let id = accounts.iter().map(|a| a.id).max().unwrap_or_default() + 1;
+        let account = Account::new(id, name);
+        self.resources.db.set_account(account.clone()).await?;
+        info!(
+            target: LOG_TARGET,
+            "Created account '{}' (Id: {})", account.name, account.id
+        );
+        Ok(account)
+    }
+
+    /// Returns the default account followed by the accounts that have been created
+    async fn get_accounts(&self) -> Result<Vec<Account>, OutputManagerError> {
+        let mut accounts = vec![Account::default_account()];
+        accounts.extend(self.resources.db.get_accounts().await?);
+        Ok(accounts)
+    }
+
+    async fn set_active_account(&mut self, name: String) -> Result<Account, OutputManagerError> {
+        let account = self
+            .get_accounts()
+            .await?
+            .into_iter()
+            .find(|a| a.name == name)
+            .ok_or_else(|| OutputManagerError::AccountNotFound(name))?;
+        self.resources.db.set_active_account(account.id).await?;
+        debug!(target: LOG_TARGET, "Active account set to '{}'", account.name);
+        *self.active_account.lock().await = account.clone();
+        Ok(account)
+    }
+
+    async fn get_next_coinbase_key(&self) -> Result<KeyId, OutputManagerError> {
+        let mut index = self.coinbase_key_index.lock().await;
+        *index += 1;
+        Ok(KeyId::new(KeyBranch::Coinbase, *index))
     }
 }
 
@@ -1171,6 +1606,24 @@ impl Display for UTXOSelectionStrategy {
     }
 }
 
+/// The owner of the output that a transaction built by `create_transaction_to_output` pays into
+enum OutputRecipient {
+    /// A one-sided payment to a public key
+    OneSided(PublicKey),
+    /// An output with a spending key that was chosen by the caller
+    SpendingKey(PrivateKey),
+}
+
+/// The request for the signer's share of the kernel of a transaction that spends `inputs` of this wallet and creates
+/// `outputs` of this wallet. Only the outputs with keys held by the signer are part of its share.
+fn kernel_request(inputs: &[DbUnblindedOutput], outputs: &[DbUnblindedOutput], offset: PrivateKey) -> KernelRequest {
+    KernelRequest {
+        inputs: inputs.iter().filter_map(|o| o.key_id).collect(),
+        outputs: outputs.iter().filter_map(|o| o.key_id).collect(),
+        offset,
+    }
+}
+
 /// This struct holds the detailed balance of the Output Manager Service.
 #[derive(Debug, Clone, PartialEq)]
 pub struct Balance {
@@ -1215,7 +1668,6 @@ where TBackend: OutputManagerBackend + 'static
     pub factories: CryptoFactories,
     pub base_node_public_key: Option<CommsPublicKey>,
     pub event_publisher: OutputManagerEventSender,
-    pub rewind_data: RewindData,
     pub consensus_constants: ConsensusConstants,
     pub connectivity_manager: ConnectivityRequester,
     pub shutdown_signal: ShutdownSignal,
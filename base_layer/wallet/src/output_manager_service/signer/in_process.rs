@@ -0,0 +1,494 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::{
+    output_manager_service::{
+        handle::PublicRewindKeys,
+        signer::{
+            KernelCommitments,
+            KernelRequest,
+            KernelSigningRecord,
+            KernelSigningStore,
+            KeyBranch,
+            KeyId,
+            RewoundOutput,
+            SignerError,
+            TransactionSigner,
+        },
+    },
+    types::{HashDigest, KeyDigest},
+};
+use digest::Digest;
+use log::*;
+use rand::{rngs::OsRng, RngCore};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard},
+};
+use tari_core::transactions::{
+    tari_amount::MicroTari,
+    transaction::{OutputFeatures, TransactionOutput, UnblindedOutput},
+    transaction_protocol::RewindData,
+    types::{CryptoFactories, PrivateKey, PublicKey, Signature},
+};
+use tari_crypto::{
+    keys::{DiffieHellmanSharedSecret, PublicKey as PublicKeyTrait},
+    range_proof::REWIND_USER_MESSAGE_LENGTH,
+    tari_utilities::ByteArray,
+};
+use tari_key_manager::{
+    file_backup::FileBackup,
+    key_manager::KeyManager,
+    mnemonic::{from_secret_key, MnemonicLanguage},
+};
+
+const LOG_TARGET: &str = "wallet::output_manager_service::signer::in_process";
+
+const KEY_MANAGER_COINBASE_BRANCH_KEY: &str = "coinbase";
+const KEY_MANAGER_RECOVERY_VIEWONLY_BRANCH_KEY: &str = "recovery_viewonly";
+const KEY_MANAGER_RECOVERY_BLINDING_BRANCH_KEY: &str = "recovery_blinding";
+const KEY_MANAGER_ACCOUNT_BRANCH_KEY: &str = "account";
+const KERNEL_NONCE_DOMAIN: &[u8] = b"kernel_nonce";
+const KERNEL_NONCE_SALT_LENGTH: usize = 32;
+
+/// Outputs created before key ids were encoded in range proofs are matched against this many keys of the spend and
+/// coinbase branches
+const LEGACY_KEY_SEARCH_DEPTH: u64 = 1000;
+
+/// The branch seed of the key chain of an account other than the default account
+fn account_branch_seed(account_id: u64) -> String {
+    format!("{}-{}", KEY_MANAGER_ACCOUNT_BRANCH_KEY, account_id)
+}
+
+/// A signer that holds the master key in process. This is the default signer used by the Output Manager Service.
+pub struct InProcessSigner {
+    master_key: PrivateKey,
+    branch_seed: String,
+    factories: CryptoFactories,
+    rewind_keys: (PrivateKey, PrivateKey),
+    /// The nonce salt of each kernel request and the challenge it has been signed with
+    kernel_store: Arc<dyn KernelSigningStore>,
+    /// Held while a kernel signing record is read and updated, so that concurrent requests cannot both create a salt
+    /// or sign different challenges
+    kernel_store_lock: tokio::sync::Mutex<()>,
+    /// The key ids of the first keys of the spend and coinbase branches, built the first time a legacy output is
+    /// rewound
+    legacy_keys: Mutex<Option<HashMap<Vec<u8>, KeyId>>>,
+}
+
+impl InProcessSigner {
+    pub fn new(
+        master_key: PrivateKey,
+        branch_seed: String,
+        factories: CryptoFactories,
+        kernel_store: Arc<dyn KernelSigningStore>,
+    ) -> Result<Self, SignerError>
+    {
+        let rewind_key = derive_branch_key(&master_key, KEY_MANAGER_RECOVERY_VIEWONLY_BRANCH_KEY, 0)?;
+        let rewind_blinding_key = derive_branch_key(&master_key, KEY_MANAGER_RECOVERY_BLINDING_BRANCH_KEY, 0)?;
+        Ok(Self {
+            master_key,
+            branch_seed,
+            factories,
+            rewind_keys: (rewind_key, rewind_blinding_key),
+            kernel_store,
+            kernel_store_lock: tokio::sync::Mutex::new(()),
+            legacy_keys: Mutex::new(None),
+        })
+    }
+
+    /// Construct a signer from a key manager backup file, as produced by `FileBackup::to_file`
+    pub fn from_file(
+        path: &str,
+        factories: CryptoFactories,
+        kernel_store: Arc<dyn KernelSigningStore>,
+    ) -> Result<Self, SignerError>
+    {
+        let key_manager = KeyManager::<PrivateKey, KeyDigest>::from_file(path)
+            .map_err(|e| SignerError::KeyDerivationError(e.to_string()))?;
+        Self::new(
+            key_manager.master_key().clone(),
+            key_manager.branch_seed,
+            factories,
+            kernel_store,
+        )
+    }
+
+    fn derive_key(&self, key_id: &KeyId) -> Result<PrivateKey, SignerError> {
+        match key_id.branch {
+            KeyBranch::Spend => derive_branch_key(&self.master_key, &self.branch_seed, key_id.index),
+            KeyBranch::Coinbase => derive_branch_key(&self.master_key, KEY_MANAGER_COINBASE_BRANCH_KEY, key_id.index),
+            KeyBranch::Account(account_id) => {
+                KeyManager::<PrivateKey, KeyDigest>::from(self.master_key.clone(), account_branch_seed(account_id), 0)
+                    .derive_branch_key(key_id.index)
+                    .map(|k| k.k)
+                    .map_err(|e| SignerError::KeyDerivationError(e.to_string()))
+            },
+        }
+    }
+
+    fn rewind_data(&self, key_id: &KeyId) -> RewindData {
+        RewindData {
+            rewind_key: self.rewind_keys.0.clone(),
+            rewind_blinding_key: self.rewind_keys.1.clone(),
+            proof_message: key_id.to_proof_message(),
+        }
+    }
+
+    /// The signer's share of the excess of a kernel
+    fn kernel_excess(&self, request: &KernelRequest) -> Result<PrivateKey, SignerError> {
+        let mut excess = PrivateKey::default();
+        for key_id in &request.outputs {
+            excess = &excess + &self.derive_key(key_id)?;
+        }
+        for key_id in &request.inputs {
+            excess = &excess - &self.derive_key(key_id)?;
+        }
+        Ok(excess)
+    }
+
+    /// The nonce of a kernel is derived from the master key, the request and the request's random salt, so that it is
+    /// the same when the kernel is signed as when its public nonce was requested, even if the signer was restarted in
+    /// between
+    fn kernel_nonce(&self, request: &KernelRequest, nonce_salt: &[u8]) -> Result<PrivateKey, SignerError> {
+        let hash = HashDigest::new()
+            .chain(self.master_key.as_bytes())
+            .chain(KERNEL_NONCE_DOMAIN)
+            .chain(nonce_salt)
+            .chain(request.to_bytes())
+            .result();
+        PrivateKey::from_bytes(hash.as_slice()).map_err(|e| SignerError::KeyDerivationError(e.to_string()))
+    }
+
+    /// The nonce of a kernel request, creating and storing a new salt for it if it has none
+    async fn get_or_create_kernel_nonce(&self, request: &KernelRequest) -> Result<PrivateKey, SignerError> {
+        let _guard = self.kernel_store_lock.lock().await;
+        let request_hash = request_hash(request);
+        let nonce_salt = match self.kernel_store.fetch_kernel_record(&request_hash).await? {
+            Some(record) => record.nonce_salt,
+            None => {
+                let mut nonce_salt = vec![0u8; KERNEL_NONCE_SALT_LENGTH];
+                OsRng.fill_bytes(&mut nonce_salt);
+                self.kernel_store
+                    .set_kernel_record(request_hash, KernelSigningRecord {
+                        nonce_salt: nonce_salt.clone(),
+                        challenge: None,
+                    })
+                    .await?;
+                nonce_salt
+            },
+        };
+        self.kernel_nonce(request, &nonce_salt)
+    }
+
+    /// Find the key id of an output that was created before key ids were encoded in range proofs
+    fn find_legacy_key_id(&self, key: &PrivateKey) -> Result<Option<KeyId>, SignerError> {
+        let mut legacy_keys = lock(&self.legacy_keys);
+        if legacy_keys.is_none() {
+            let mut keys = HashMap::new();
+            // The spend branch is inserted last so that it takes precedence if the branches derive the same keys
+            for branch in &[KeyBranch::Coinbase, KeyBranch::Spend] {
+                for index in 1..=LEGACY_KEY_SEARCH_DEPTH {
+                    let key_id = KeyId::new(*branch, index);
+                    keys.insert(self.derive_key(&key_id)?.as_bytes().to_vec(), key_id);
+                }
+            }
+            *legacy_keys = Some(keys);
+        }
+        Ok(legacy_keys.as_ref().and_then(|keys| keys.get(key.as_bytes())).copied())
+    }
+}
+
+#[tari_comms::async_trait]
+impl TransactionSigner for InProcessSigner {
+    async fn create_output(
+        &self,
+        key_id: KeyId,
+        value: MicroTari,
+        features: OutputFeatures,
+    ) -> Result<TransactionOutput, SignerError>
+    {
+        UnblindedOutput::new(value, self.derive_key(&key_id)?, Some(features))
+            .as_rewindable_transaction_output(&self.factories, &self.rewind_data(&key_id))
+            .map_err(|e| SignerError::OutputError(e.to_string()))
+    }
+
+    async fn kernel_commitments(&self, request: &KernelRequest) -> Result<KernelCommitments, SignerError> {
+        Ok(KernelCommitments {
+            public_excess: PublicKey::from_secret_key(&self.kernel_excess(request)?),
+            public_nonce: PublicKey::from_secret_key(&self.get_or_create_kernel_nonce(request).await?),
+        })
+    }
+
+    async fn sign_kernel(&self, request: &KernelRequest, challenge: &[u8]) -> Result<Signature, SignerError> {
+        let nonce = {
+            let _guard = self.kernel_store_lock.lock().await;
+            let request_hash = request_hash(request);
+            let mut record = match self.kernel_store.fetch_kernel_record(&request_hash).await? {
+                Some(record) => record,
+                None => {
+                    warn!(
+                        target: LOG_TARGET,
+                        "Refusing to sign a kernel request whose public nonce was not requested"
+                    );
+                    return Err(SignerError::KernelNonceNotFound);
+                },
+            };
+            match &record.challenge {
+                Some(signed_challenge) if signed_challenge.as_slice() != challenge => {
+                    warn!(
+                        target: LOG_TARGET,
+                        "Refusing to sign a kernel request with a second challenge"
+                    );
+                    return Err(SignerError::ChallengeMismatch);
+                },
+                Some(_) => (),
+                None => {
+                    // The challenge is stored before the signature is produced, so that it is never signed again
+                    // with another challenge
+                    record.challenge = Some(challenge.to_vec());
+                    self.kernel_store
+                        .set_kernel_record(request_hash, record.clone())
+                        .await?;
+                },
+            }
+            self.kernel_nonce(request, &record.nonce_salt)?
+        };
+        Signature::sign(self.kernel_excess(request)?, nonce, challenge)
+            .map_err(|e| SignerError::SigningError(e.to_string()))
+    }
+
+    async fn kernel_nonce_shared_secret(
+        &self,
+        request: &KernelRequest,
+        public_key: &PublicKey,
+    ) -> Result<PublicKey, SignerError>
+    {
+        Ok(PublicKey::shared_secret(
+            &self.get_or_create_kernel_nonce(request).await?,
+            public_key,
+        ))
+    }
+
+    async fn rewind_public_keys(&self) -> Result<PublicRewindKeys, SignerError> {
+        Ok(PublicRewindKeys {
+            rewind_public_key: PublicKey::from_secret_key(&self.rewind_keys.0),
+            rewind_blinding_public_key: PublicKey::from_secret_key(&self.rewind_keys.1),
+        })
+    }
+
+    async fn rewind_output(&self, output: &TransactionOutput) -> Result<Option<RewoundOutput>, SignerError> {
+        let rewound =
+            match output.full_rewind_range_proof(&self.factories.range_proof, &self.rewind_keys.0, &self.rewind_keys.1)
+            {
+                Ok(rewound) => rewound,
+                Err(_) => return Ok(None),
+            };
+        let key_id = if rewound.proof_message == [0u8; REWIND_USER_MESSAGE_LENGTH] {
+            self.find_legacy_key_id(&rewound.blinding_factor)?
+        } else {
+            KeyId::from_proof_message(&rewound.proof_message)
+                .filter(|key_id| self.derive_key(key_id).ok().as_ref() == Some(&rewound.blinding_factor))
+        };
+        if key_id.is_none() {
+            warn!(
+                target: LOG_TARGET,
+                "An output was rewound with this wallet's keys but its key could not be found"
+            );
+        }
+        Ok(key_id.map(|key_id| RewoundOutput {
+            value: rewound.committed_value,
+            key_id,
+        }))
+    }
+
+    async fn seed_words(&self) -> Result<Vec<String>, SignerError> {
+        from_secret_key(&self.master_key, &MnemonicLanguage::English)
+            .map_err(|e| SignerError::UnsupportedOperation(e.to_string()))
+    }
+}
+
+fn derive_branch_key(master_key: &PrivateKey, branch_seed: &str, index: u64) -> Result<PrivateKey, SignerError> {
+    KeyManager::<PrivateKey, KeyDigest>::from(master_key.clone(), branch_seed.to_string(), 0)
+        .derive_key(index)
+        .map(|k| k.k)
+        .map_err(|e| SignerError::KeyDerivationError(e.to_string()))
+}
+
+/// The key of a kernel request in the kernel signing store
+fn request_hash(request: &KernelRequest) -> Vec<u8> {
+    HashDigest::new().chain(request.to_bytes()).result().to_vec()
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    // The guarded maps are left consistent by every holder, so a poisoned lock can still be used
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::output_manager_service::signer::MemoryKernelSigningStore;
+    use futures::executor::block_on;
+    use tari_core::transactions::types::Commitment;
+    use tari_crypto::{commitment::HomomorphicCommitmentFactory, keys::SecretKey};
+
+    fn new_signer(master_key: PrivateKey) -> InProcessSigner {
+        new_signer_with_store(master_key, Arc::new(MemoryKernelSigningStore::new()))
+    }
+
+    fn new_signer_with_store(master_key: PrivateKey, store: Arc<dyn KernelSigningStore>) -> InProcessSigner {
+        InProcessSigner::new(master_key, "".to_string(), CryptoFactories::default(), store).unwrap()
+    }
+
+    #[test]
+    fn it_derives_the_same_keys_as_the_key_manager() {
+        let master_key = PrivateKey::random(&mut OsRng);
+        let signer = new_signer(master_key.clone());
+        let mut key_manager = KeyManager::<PrivateKey, KeyDigest>::from(master_key, "".to_string(), 0);
+        for i in 1..4 {
+            assert_eq!(
+                signer.derive_key(&KeyId::new(KeyBranch::Spend, i)).unwrap(),
+                key_manager.next_key().unwrap().k
+            );
+        }
+    }
+
+    #[test]
+    fn it_derives_independent_account_keys() {
+        let signer = new_signer(PrivateKey::random(&mut OsRng));
+        let spend = signer.derive_key(&KeyId::new(KeyBranch::Spend, 1)).unwrap();
+        let account1 = signer.derive_key(&KeyId::new(KeyBranch::Account(1), 1)).unwrap();
+        let account2 = signer.derive_key(&KeyId::new(KeyBranch::Account(2), 1)).unwrap();
+        assert_ne!(spend, account1);
+        assert_ne!(account1, account2);
+        assert_eq!(
+            account1,
+            signer.derive_key(&KeyId::new(KeyBranch::Account(1), 1)).unwrap()
+        );
+    }
+
+    #[test]
+    fn it_signs_kernels_without_revealing_keys() {
+        let factories = CryptoFactories::default();
+        let signer = new_signer(PrivateKey::random(&mut OsRng));
+        let input = KeyId::new(KeyBranch::Spend, 1);
+        let change = KeyId::new(KeyBranch::Spend, 2);
+        let input_output = block_on(signer.create_output(input, MicroTari(1000), OutputFeatures::default())).unwrap();
+        let change_output = block_on(signer.create_output(change, MicroTari(400), OutputFeatures::default())).unwrap();
+        let request = KernelRequest {
+            inputs: vec![input],
+            outputs: vec![change],
+            offset: PrivateKey::random(&mut OsRng),
+        };
+
+        // The public excess is the difference of the commitments, less the difference of their values
+        let commitments = block_on(signer.kernel_commitments(&request)).unwrap();
+        let value_difference = factories.commitment.commit_value(&PrivateKey::default(), 600);
+        let expected = &(&change_output.commitment - &input_output.commitment) + &value_difference;
+        assert_eq!(Commitment::from_public_key(&commitments.public_excess), expected);
+        assert_eq!(block_on(signer.kernel_commitments(&request)).unwrap(), commitments);
+
+        let challenge = [1u8; 32];
+        let signature = block_on(signer.sign_kernel(&request, &challenge)).unwrap();
+        assert_eq!(signature.get_public_nonce(), &commitments.public_nonce);
+        assert!(signature.verify_challenge(&commitments.public_excess, &challenge));
+        // Signing the same request again is allowed, but not with a different challenge
+        assert!(block_on(signer.sign_kernel(&request, &challenge)).is_ok());
+        match block_on(signer.sign_kernel(&request, &[2u8; 32])) {
+            Err(SignerError::ChallengeMismatch) => (),
+            r => panic!("Unexpected result {:?}", r),
+        }
+
+        // The owner of a public key computes the same shared secret from the kernel's public nonce
+        let (key, public_key) = PublicKey::random_keypair(&mut OsRng);
+        assert_eq!(
+            block_on(signer.kernel_nonce_shared_secret(&request, &public_key)).unwrap(),
+            PublicKey::shared_secret(&key, &commitments.public_nonce)
+        );
+    }
+
+    #[test]
+    fn it_rewinds_outputs_to_key_ids() {
+        let factories = CryptoFactories::default();
+        let master_key = PrivateKey::random(&mut OsRng);
+        let signer = new_signer(master_key);
+        let key_id = KeyId::new(KeyBranch::Account(3), 5);
+        let output = block_on(signer.create_output(key_id, MicroTari(1234), OutputFeatures::default())).unwrap();
+        let rewound = block_on(signer.rewind_output(&output)).unwrap().unwrap();
+        assert_eq!(rewound.value, MicroTari(1234));
+        assert_eq!(rewound.key_id, key_id);
+
+        // An output created before key ids were encoded is found by its key
+        let legacy_key = signer.derive_key(&KeyId::new(KeyBranch::Spend, 7)).unwrap();
+        let legacy_output = UnblindedOutput::new(MicroTari(55), legacy_key, None)
+            .as_rewindable_transaction_output(&factories, &RewindData {
+                rewind_key: signer.rewind_keys.0.clone(),
+                rewind_blinding_key: signer.rewind_keys.1.clone(),
+                proof_message: [0u8; REWIND_USER_MESSAGE_LENGTH],
+            })
+            .unwrap();
+        let rewound = block_on(signer.rewind_output(&legacy_output)).unwrap().unwrap();
+        assert_eq!(rewound.key_id, KeyId::new(KeyBranch::Spend, 7));
+
+        let other = new_signer(PrivateKey::random(&mut OsRng));
+        assert!(block_on(other.rewind_output(&output)).unwrap().is_none());
+    }
+
+    #[test]
+    fn it_only_signs_requests_with_a_stored_nonce() {
+        let master_key = PrivateKey::random(&mut OsRng);
+        let store = Arc::new(MemoryKernelSigningStore::new());
+        let signer = new_signer_with_store(master_key.clone(), store.clone());
+        let request = KernelRequest::for_output(KeyId::new(KeyBranch::Spend, 1));
+        let challenge = [1u8; 32];
+
+        // A request whose public nonce was never handed out is not signed
+        match block_on(signer.sign_kernel(&request, &challenge)) {
+            Err(SignerError::KernelNonceNotFound) => (),
+            r => panic!("Unexpected result {:?}", r),
+        }
+
+        // A signer restarted with the same store keeps the nonce and the signed challenge of a request
+        let commitments = block_on(signer.kernel_commitments(&request)).unwrap();
+        block_on(signer.sign_kernel(&request, &challenge)).unwrap();
+        let restarted = new_signer_with_store(master_key.clone(), store);
+        assert_eq!(block_on(restarted.kernel_commitments(&request)).unwrap(), commitments);
+        let signature = block_on(restarted.sign_kernel(&request, &challenge)).unwrap();
+        assert_eq!(signature.get_public_nonce(), &commitments.public_nonce);
+        match block_on(restarted.sign_kernel(&request, &[2u8; 32])) {
+            Err(SignerError::ChallengeMismatch) => (),
+            r => panic!("Unexpected result {:?}", r),
+        }
+
+        // A signer that lost its records refuses to sign, and a new public nonce is handed out for the request
+        let forgetful = new_signer(master_key);
+        match block_on(forgetful.sign_kernel(&request, &[2u8; 32])) {
+            Err(SignerError::KernelNonceNotFound) => (),
+            r => panic!("Unexpected result {:?}", r),
+        }
+        assert_ne!(
+            block_on(forgetful.kernel_commitments(&request)).unwrap().public_nonce,
+            commitments.public_nonce
+        );
+    }
+}
@@ -0,0 +1,337 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! # Transaction Signer
+//! The Output Manager Service delegates all operations that require the wallet's secret key material to a
+//! [TransactionSigner]. The signer holds the master key and the rewind keys, and only public data leaves it: the
+//! outputs it builds, the public excess and public nonce of a kernel and its partial signature on the kernel
+//! challenge. The signer's share of a kernel's excess and its nonce are derived from a [KernelRequest] inside the
+//! signer and are never returned.
+//!
+//! Every kernel request gets its own random nonce salt when its public nonce is first requested. The salt, and the
+//! challenge the request was signed with, are kept in a [KernelSigningStore] and written before a signature is
+//! returned. A request is only ever signed with a single challenge, and a request whose salt is no longer in the store
+//! is refused, so a nonce is never used for two challenges, even across restarts.
+//!
+//! By default the keys are held in process by an [InProcessSigner](in_process::InProcessSigner). The
+//! [RemoteSigner](remote::RemoteSigner) forwards the same requests over an authenticated connection to a signer
+//! served by a [SignerServer](remote::SignerServer), which can be run on a separate host.
+
+pub mod in_process;
+pub mod remote;
+
+use crate::output_manager_service::handle::PublicRewindKeys;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, VecDeque},
+    convert::TryInto,
+    io,
+    sync::Mutex,
+};
+use tari_comms::noise::NoiseError;
+use tari_core::transactions::{
+    tari_amount::MicroTari,
+    transaction::{OutputFeatures, TransactionOutput},
+    types::{PrivateKey, PublicKey, Signature},
+};
+use tari_crypto::{range_proof::REWIND_USER_MESSAGE_LENGTH, tari_utilities::ByteArray};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum SignerError {
+    #[error("Could not derive key: `{0}`")]
+    KeyDerivationError(String),
+    #[error("Could not produce signature: `{0}`")]
+    SigningError(String),
+    #[error("Could not build output: `{0}`")]
+    OutputError(String),
+    #[error("The kernel request has already been signed with a different challenge")]
+    ChallengeMismatch,
+    #[error("The nonce of the kernel request is not known to the signer")]
+    KernelNonceNotFound,
+    #[error("Could not access the kernel signing store: `{0}`")]
+    StorageError(String),
+    #[error("This operation is not supported by the signer: `{0}`")]
+    UnsupportedOperation(String),
+    #[error("The remote signer returned an error: `{0}`")]
+    RemoteError(String),
+    #[error("The remote signer returned an unexpected response")]
+    UnexpectedResponse,
+    #[error("The remote signer did not respond in time")]
+    Timeout,
+    #[error("The remote signer closed the connection")]
+    ConnectionClosed,
+    #[error("The remote peer is not the configured signer")]
+    UnexpectedSignerPublicKey,
+    #[error("The client is not allowed to use this signer")]
+    ClientNotAllowed,
+    #[error("IO error: `{0}`")]
+    IoError(#[from] io::Error),
+    #[error("Noise error: `{0}`")]
+    NoiseError(#[from] NoiseError),
+    #[error("Serialization error: `{0}`")]
+    SerializationError(#[from] bincode::Error),
+}
+
+/// The key derivation branches used by the wallet
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum KeyBranch {
+    /// Keys used to spend outputs and receive change
+    Spend,
+    /// Keys used for coinbase outputs
+    Coinbase,
+    /// Keys used to spend outputs and receive change in a named account other than the default account. Every account
+    /// has its own independent branch.
+    Account(u64),
+}
+
+/// The version byte of a key id encoded in a range proof message. Outputs created before key ids were encoded have
+/// an all-zero message.
+const KEY_ID_VERSION: u8 = 1;
+
+/// Identifies a key held by the signer. The key itself never leaves the signer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct KeyId {
+    pub branch: KeyBranch,
+    pub index: u64,
+}
+
+impl KeyId {
+    pub fn new(branch: KeyBranch, index: u64) -> Self {
+        Self { branch, index }
+    }
+
+    /// Encode the key id as the message of a rewindable range proof, so that the signer can identify the key of an
+    /// output that it rewinds
+    pub fn to_proof_message(&self) -> [u8; REWIND_USER_MESSAGE_LENGTH] {
+        let (tag, account_id) = match self.branch {
+            KeyBranch::Spend => (0u8, 0u64),
+            KeyBranch::Coinbase => (1, 0),
+            KeyBranch::Account(account_id) => (2, account_id),
+        };
+        let mut message = [0u8; REWIND_USER_MESSAGE_LENGTH];
+        message[0] = KEY_ID_VERSION;
+        message[1] = tag;
+        message[2..10].copy_from_slice(&account_id.to_le_bytes());
+        message[10..18].copy_from_slice(&self.index.to_le_bytes());
+        message
+    }
+
+    /// Decode a key id from a range proof message, returning `None` if the message does not hold a key id
+    pub fn from_proof_message(message: &[u8]) -> Option<Self> {
+        if message.len() != REWIND_USER_MESSAGE_LENGTH || message[0] != KEY_ID_VERSION {
+            return None;
+        }
+        let account_id = u64::from_le_bytes(message[2..10].try_into().ok()?);
+        let index = u64::from_le_bytes(message[10..18].try_into().ok()?);
+        let branch = match message[1] {
+            0 => KeyBranch::Spend,
+            1 => KeyBranch::Coinbase,
+            2 => KeyBranch::Account(account_id),
+            _ => return None,
+        };
+        Some(Self { branch, index })
+    }
+}
+
+/// A request for the signer's share of a kernel. The signer's share of the excess is the sum of the keys of
+/// `outputs` less the sum of the keys of `inputs`; the nonce is derived from the master key, the request and the
+/// request's nonce salt, so the same request has the same public nonce for as long as its salt is stored. Receiver and
+/// coinbase kernels have a single output and a zero offset.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KernelRequest {
+    pub inputs: Vec<KeyId>,
+    pub outputs: Vec<KeyId>,
+    pub offset: PrivateKey,
+}
+
+impl KernelRequest {
+    /// The request for the kernel of a single output with a key held by the signer, as used by a receiver or a
+    /// coinbase
+    pub fn for_output(key_id: KeyId) -> Self {
+        Self {
+            inputs: Vec::new(),
+            outputs: vec![key_id],
+            offset: PrivateKey::default(),
+        }
+    }
+
+    /// A unique encoding of the request. The order of the inputs and of the outputs does not matter, so a request
+    /// that is rebuilt from stored outputs has the same encoding as the original.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.offset.as_bytes().to_vec();
+        for key_ids in &[&self.inputs, &self.outputs] {
+            let mut encoded = key_ids.iter().map(KeyId::to_proof_message).collect::<Vec<_>>();
+            encoded.sort_unstable();
+            bytes.extend_from_slice(&(encoded.len() as u64).to_le_bytes());
+            for key_id in encoded {
+                bytes.extend_from_slice(&key_id);
+            }
+        }
+        bytes
+    }
+}
+
+/// What the signer keeps about a kernel request: the random salt its nonce is derived from and the challenge it was
+/// signed with, if it has been signed. The salt is not secret on its own, as the nonce also depends on the master key.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KernelSigningRecord {
+    pub nonce_salt: Vec<u8>,
+    pub challenge: Option<Vec<u8>>,
+}
+
+/// The storage of the kernel signing records of a signer, keyed by the hash of the encoded kernel request. A store
+/// may drop its oldest records to bound its size; a request whose record was dropped can no longer be signed.
+#[tari_comms::async_trait]
+pub trait KernelSigningStore: Send + Sync + 'static {
+    async fn fetch_kernel_record(&self, request_hash: &[u8]) -> Result<Option<KernelSigningRecord>, SignerError>;
+
+    /// Insert or replace the record of a request. The record must be durable once this returns.
+    async fn set_kernel_record(&self, request_hash: Vec<u8>, record: KernelSigningRecord) -> Result<(), SignerError>;
+}
+
+/// The maximum number of records kept by a kernel signing store
+pub const MAX_KERNEL_SIGNING_RECORDS: usize = 10_000;
+
+/// A kernel signing store that is held in memory. Its records are lost when the signer stops, after which the
+/// requests that were not yet signed are refused.
+#[derive(Default)]
+pub struct MemoryKernelSigningStore {
+    records: Mutex<(HashMap<Vec<u8>, KernelSigningRecord>, VecDeque<Vec<u8>>)>,
+}
+
+impl MemoryKernelSigningStore {
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+#[tari_comms::async_trait]
+impl KernelSigningStore for MemoryKernelSigningStore {
+    async fn fetch_kernel_record(&self, request_hash: &[u8]) -> Result<Option<KernelSigningRecord>, SignerError> {
+        let records = self.records.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(records.0.get(request_hash).cloned())
+    }
+
+    async fn set_kernel_record(&self, request_hash: Vec<u8>, record: KernelSigningRecord) -> Result<(), SignerError> {
+        let mut records = self.records.lock().unwrap_or_else(|e| e.into_inner());
+        let (map, order) = &mut *records;
+        if map.insert(request_hash.clone(), record).is_none() {
+            order.push_back(request_hash);
+            while order.len() > MAX_KERNEL_SIGNING_RECORDS {
+                if let Some(oldest) = order.pop_front() {
+                    map.remove(&oldest);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The public part of the signer's share of a kernel
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KernelCommitments {
+    pub public_excess: PublicKey,
+    pub public_nonce: PublicKey,
+}
+
+/// The value and key id of an output that was rewound by the signer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RewoundOutput {
+    pub value: MicroTari,
+    pub key_id: KeyId,
+}
+
+/// An interface behind which the wallet's secret key material is held. Implementations can keep keys in process or
+/// forward requests to an external signer, e.g. a hardware device. No method returns secret key material.
+#[tari_comms::async_trait]
+pub trait TransactionSigner: Send + Sync + 'static {
+    /// Build an output with the key `key_id`. The range proof is rewindable with the signer's rewind keys and carries
+    /// the key id, so that the output can be recovered from the seed.
+    async fn create_output(
+        &self,
+        key_id: KeyId,
+        value: MicroTari,
+        features: OutputFeatures,
+    ) -> Result<TransactionOutput, SignerError>;
+
+    /// The public excess and public nonce of the signer's share of the kernel described by `request`
+    async fn kernel_commitments(&self, request: &KernelRequest) -> Result<KernelCommitments, SignerError>;
+
+    /// Produce the signer's partial signature on `challenge` for the kernel described by `request`. A request may only
+    /// be signed with a single challenge, as signing a second challenge with the same nonce would reveal the key, and
+    /// only if its public nonce was requested before.
+    async fn sign_kernel(&self, request: &KernelRequest, challenge: &[u8]) -> Result<Signature, SignerError>;
+
+    /// The Diffie-Hellman shared secret of the nonce of the kernel described by `request` and `public_key`. The nonce
+    /// of a kernel is the public nonce of its signature, so the owner of `public_key` can compute the same secret from
+    /// the kernel. A one-sided payment derives the keys of the recipient's output from it.
+    async fn kernel_nonce_shared_secret(
+        &self,
+        request: &KernelRequest,
+        public_key: &PublicKey,
+    ) -> Result<PublicKey, SignerError>;
+
+    /// The public rewind keys, used to identify outputs that may belong to this wallet
+    async fn rewind_public_keys(&self) -> Result<PublicRewindKeys, SignerError>;
+
+    /// Attempt to rewind the range proof of `output` with the signer's rewind keys, returning `None` if the output does
+    /// not belong to this wallet
+    async fn rewind_output(&self, output: &TransactionOutput) -> Result<Option<RewoundOutput>, SignerError>;
+
+    /// The seed words of the master key, if the signer is willing to export them
+    async fn seed_words(&self) -> Result<Vec<String>, SignerError>;
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_encodes_key_ids_in_proof_messages() {
+        for key_id in &[
+            KeyId::new(KeyBranch::Spend, 1),
+            KeyId::new(KeyBranch::Coinbase, u64::MAX),
+            KeyId::new(KeyBranch::Account(7), 42),
+        ] {
+            assert_eq!(KeyId::from_proof_message(&key_id.to_proof_message()), Some(*key_id));
+        }
+        assert_eq!(KeyId::from_proof_message(&[0u8; REWIND_USER_MESSAGE_LENGTH]), None);
+        assert_eq!(KeyId::from_proof_message(&[1u8; 3]), None);
+    }
+
+    #[test]
+    fn it_drops_the_oldest_kernel_signing_records() {
+        let store = MemoryKernelSigningStore::new();
+        let record = KernelSigningRecord {
+            nonce_salt: vec![1u8; 32],
+            challenge: None,
+        };
+        for i in 0..=MAX_KERNEL_SIGNING_RECORDS as u64 {
+            futures::executor::block_on(store.set_kernel_record(i.to_le_bytes().to_vec(), record.clone())).unwrap();
+        }
+        let fetch = |i: u64| futures::executor::block_on(store.fetch_kernel_record(&i.to_le_bytes())).unwrap();
+        assert_eq!(fetch(0), None);
+        assert_eq!(fetch(1), Some(record.clone()));
+        assert_eq!(fetch(MAX_KERNEL_SIGNING_RECORDS as u64), Some(record));
+    }
+}
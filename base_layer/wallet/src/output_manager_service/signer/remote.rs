@@ -0,0 +1,468 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! A signer that runs in a separate process, possibly on another host, and is reached over TCP. The connection is
+//! upgraded with the Noise protocol using the wallet's node identity: the wallet only talks to the signer with the
+//! configured public key and the signer only serves the wallets whose public keys it allows. Requests and responses
+//! are bincode encoded, one per frame.
+
+use crate::output_manager_service::{
+    handle::PublicRewindKeys,
+    signer::{KernelCommitments, KernelRequest, KeyId, RewoundOutput, SignerError, TransactionSigner},
+};
+use futures::{SinkExt, StreamExt};
+use log::*;
+use serde::{Deserialize, Serialize};
+use std::{sync::Arc, time::Duration};
+use tari_comms::{
+    connection_manager::ConnectionDirection,
+    framing::{self, CanonicalFraming},
+    multiaddr::Multiaddr,
+    noise::{NoiseConfig, NoiseSocket},
+    transports::{TcpSocket, TcpTransport, Transport},
+    types::CommsPublicKey,
+    Bytes,
+    NodeIdentity,
+};
+use tari_core::transactions::{
+    tari_amount::MicroTari,
+    transaction::{OutputFeatures, TransactionOutput},
+    types::{MessageHash, PublicKey, Signature},
+};
+use tokio::{sync::Mutex, time};
+
+const LOG_TARGET: &str = "wallet::output_manager_service::signer::remote";
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+const MAX_FRAME_SIZE: usize = 1024 * 1024;
+
+type SignerConnection = CanonicalFraming<NoiseSocket<TcpSocket>>;
+
+#[derive(Debug, Serialize, Deserialize)]
+enum SignerRequest {
+    CreateOutput {
+        key_id: KeyId,
+        value: MicroTari,
+        features: OutputFeatures,
+    },
+    KernelCommitments(KernelRequest),
+    SignKernel {
+        request: KernelRequest,
+        challenge: MessageHash,
+    },
+    KernelNonceSharedSecret {
+        request: KernelRequest,
+        public_key: PublicKey,
+    },
+    GetRewindPublicKeys,
+    RewindOutput(Box<TransactionOutput>),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+enum SignerResponse {
+    Output(Box<TransactionOutput>),
+    KernelCommitments(KernelCommitments),
+    Signature(Signature),
+    SharedSecret(PublicKey),
+    RewindPublicKeys(PublicRewindKeys),
+    RewoundOutput(Option<RewoundOutput>),
+    Error(String),
+}
+
+/// A signer client that forwards every request to a signer served by a `SignerServer`
+pub struct RemoteSigner {
+    address: Multiaddr,
+    signer_public_key: CommsPublicKey,
+    noise_config: NoiseConfig,
+    timeout: Duration,
+    connection: Mutex<Option<SignerConnection>>,
+}
+
+impl RemoteSigner {
+    /// Create a client for the signer at `address` with the public key `signer_public_key`. The client authenticates
+    /// itself with `node_identity`.
+    pub fn new(address: Multiaddr, signer_public_key: CommsPublicKey, node_identity: Arc<NodeIdentity>) -> Self {
+        Self {
+            address,
+            signer_public_key,
+            noise_config: NoiseConfig::new(node_identity),
+            timeout: DEFAULT_TIMEOUT,
+            connection: Mutex::new(None),
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    async fn connect(&self) -> Result<SignerConnection, SignerError> {
+        let socket = TcpTransport::new().dial(self.address.clone())?.await?;
+        let socket = self
+            .noise_config
+            .upgrade_socket(socket, ConnectionDirection::Outbound)
+            .await?;
+        if socket.get_remote_public_key().as_ref() != Some(&self.signer_public_key) {
+            return Err(SignerError::UnexpectedSignerPublicKey);
+        }
+        debug!(target: LOG_TARGET, "Connected to signer at {}", self.address);
+        Ok(framing::canonical(socket, MAX_FRAME_SIZE))
+    }
+
+    async fn call(&self, request: SignerRequest) -> Result<SignerResponse, SignerError> {
+        // The connection is only put back once a response has been read, so that a request that failed or timed out
+        // cannot leave a stale response on it
+        let mut connection = self.connection.lock().await;
+        let mut conn = match connection.take() {
+            Some(conn) => conn,
+            None => time::timeout(self.timeout, self.connect())
+                .await
+                .map_err(|_| SignerError::Timeout)??,
+        };
+        let response = time::timeout(self.timeout, exchange(&mut conn, &request))
+            .await
+            .map_err(|_| SignerError::Timeout)??;
+        *connection = Some(conn);
+        match response {
+            SignerResponse::Error(e) => Err(SignerError::RemoteError(e)),
+            response => Ok(response),
+        }
+    }
+}
+
+async fn exchange(conn: &mut SignerConnection, request: &SignerRequest) -> Result<SignerResponse, SignerError> {
+    conn.send(Bytes::from(bincode::serialize(request)?)).await?;
+    let frame = conn.next().await.ok_or(SignerError::ConnectionClosed)??;
+    Ok(bincode::deserialize(&frame)?)
+}
+
+#[tari_comms::async_trait]
+impl TransactionSigner for RemoteSigner {
+    async fn create_output(
+        &self,
+        key_id: KeyId,
+        value: MicroTari,
+        features: OutputFeatures,
+    ) -> Result<TransactionOutput, SignerError>
+    {
+        let request = SignerRequest::CreateOutput {
+            key_id,
+            value,
+            features,
+        };
+        match self.call(request).await? {
+            SignerResponse::Output(output) => Ok(*output),
+            _ => Err(SignerError::UnexpectedResponse),
+        }
+    }
+
+    async fn kernel_commitments(&self, request: &KernelRequest) -> Result<KernelCommitments, SignerError> {
+        match self.call(SignerRequest::KernelCommitments(request.clone())).await? {
+            SignerResponse::KernelCommitments(commitments) => Ok(commitments),
+            _ => Err(SignerError::UnexpectedResponse),
+        }
+    }
+
+    async fn sign_kernel(&self, request: &KernelRequest, challenge: &[u8]) -> Result<Signature, SignerError> {
+        let request = SignerRequest::SignKernel {
+            request: request.clone(),
+            challenge: challenge.to_vec(),
+        };
+        match self.call(request).await? {
+            SignerResponse::Signature(signature) => Ok(signature),
+            _ => Err(SignerError::UnexpectedResponse),
+        }
+    }
+
+    async fn kernel_nonce_shared_secret(
+        &self,
+        request: &KernelRequest,
+        public_key: &PublicKey,
+    ) -> Result<PublicKey, SignerError>
+    {
+        let request = SignerRequest::KernelNonceSharedSecret {
+            request: request.clone(),
+            public_key: public_key.clone(),
+        };
+        match self.call(request).await? {
+            SignerResponse::SharedSecret(secret) => Ok(secret),
+            _ => Err(SignerError::UnexpectedResponse),
+        }
+    }
+
+    async fn rewind_public_keys(&self) -> Result<PublicRewindKeys, SignerError> {
+        match self.call(SignerRequest::GetRewindPublicKeys).await? {
+            SignerResponse::RewindPublicKeys(keys) => Ok(keys),
+            _ => Err(SignerError::UnexpectedResponse),
+        }
+    }
+
+    async fn rewind_output(&self, output: &TransactionOutput) -> Result<Option<RewoundOutput>, SignerError> {
+        match self.call(SignerRequest::RewindOutput(Box::new(output.clone()))).await? {
+            SignerResponse::RewoundOutput(rewound) => Ok(rewound),
+            _ => Err(SignerError::UnexpectedResponse),
+        }
+    }
+
+    async fn seed_words(&self) -> Result<Vec<String>, SignerError> {
+        Err(SignerError::UnsupportedOperation(
+            "The seed words are not exported by a remote signer".to_string(),
+        ))
+    }
+}
+
+/// Serves a signer to `RemoteSigner` clients. Only clients whose node public key is in `allowed_clients` are served.
+pub struct SignerServer {
+    signer: Arc<dyn TransactionSigner>,
+    noise_config: NoiseConfig,
+    allowed_clients: Arc<Vec<CommsPublicKey>>,
+}
+
+impl SignerServer {
+    pub fn new(
+        signer: Arc<dyn TransactionSigner>,
+        node_identity: Arc<NodeIdentity>,
+        allowed_clients: Vec<CommsPublicKey>,
+    ) -> Self
+    {
+        Self {
+            signer,
+            noise_config: NoiseConfig::new(node_identity),
+            allowed_clients: Arc::new(allowed_clients),
+        }
+    }
+
+    /// Serve clients that connect to `listener`, as returned by `TcpTransport::listen`, until the listener is closed
+    pub async fn serve(self, mut listener: <TcpTransport as Transport>::Listener) {
+        while let Some(inbound) = listener.next().await {
+            let socket = match inbound {
+                Ok((socket, address)) => {
+                    debug!(target: LOG_TARGET, "Signer client connected from {}", address);
+                    socket
+                },
+                Err(e) => {
+                    warn!(target: LOG_TARGET, "Error accepting signer client: {}", e);
+                    continue;
+                },
+            };
+            let signer = self.signer.clone();
+            let noise_config = self.noise_config.clone();
+            let allowed_clients = self.allowed_clients.clone();
+            tokio::spawn(async move {
+                let result = async move {
+                    let socket = socket.await?;
+                    handle_connection(socket, noise_config, &allowed_clients, signer.as_ref()).await
+                };
+                if let Err(e) = result.await {
+                    warn!(target: LOG_TARGET, "Error serving signer client: {}", e);
+                }
+            });
+        }
+    }
+}
+
+async fn handle_connection(
+    socket: TcpSocket,
+    noise_config: NoiseConfig,
+    allowed_clients: &[CommsPublicKey],
+    signer: &dyn TransactionSigner,
+) -> Result<(), SignerError>
+{
+    let socket = noise_config
+        .upgrade_socket(socket, ConnectionDirection::Inbound)
+        .await?;
+    match socket.get_remote_public_key() {
+        Some(public_key) if allowed_clients.contains(&public_key) => {
+            debug!(target: LOG_TARGET, "Serving signer client {}", public_key);
+        },
+        _ => return Err(SignerError::ClientNotAllowed),
+    }
+    let mut conn = framing::canonical(socket, MAX_FRAME_SIZE);
+    while let Some(frame) = conn.next().await {
+        let request: SignerRequest = bincode::deserialize(&frame?)?;
+        let response = handle_request(request, signer)
+            .await
+            .unwrap_or_else(|e| SignerResponse::Error(e.to_string()));
+        conn.send(Bytes::from(bincode::serialize(&response)?)).await?;
+    }
+    Ok(())
+}
+
+async fn handle_request(request: SignerRequest, signer: &dyn TransactionSigner) -> Result<SignerResponse, SignerError> {
+    use SignerRequest::*;
+    match request {
+        CreateOutput {
+            key_id,
+            value,
+            features,
+        } => signer
+            .create_output(key_id, value, features)
+            .await
+            .map(|output| SignerResponse::Output(Box::new(output))),
+        KernelCommitments(request) => signer
+            .kernel_commitments(&request)
+            .await
+            .map(SignerResponse::KernelCommitments),
+        SignKernel { request, challenge } => signer
+            .sign_kernel(&request, &challenge)
+            .await
+            .map(SignerResponse::Signature),
+        KernelNonceSharedSecret { request, public_key } => signer
+            .kernel_nonce_shared_secret(&request, &public_key)
+            .await
+            .map(SignerResponse::SharedSecret),
+        GetRewindPublicKeys => signer.rewind_public_keys().await.map(SignerResponse::RewindPublicKeys),
+        RewindOutput(output) => signer.rewind_output(&output).await.map(SignerResponse::RewoundOutput),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::output_manager_service::signer::{in_process::InProcessSigner, KeyBranch, MemoryKernelSigningStore};
+    use rand::rngs::OsRng;
+    use tari_comms::peer_manager::PeerFeatures;
+    use tari_core::transactions::types::{CryptoFactories, PrivateKey};
+    use tari_crypto::keys::{PublicKey as PublicKeyTrait, SecretKey};
+
+    fn node_identity() -> Arc<NodeIdentity> {
+        Arc::new(NodeIdentity::random(&mut OsRng, Multiaddr::empty(), PeerFeatures::COMMUNICATION_CLIENT).unwrap())
+    }
+
+    async fn start_server(
+        signer: Arc<InProcessSigner>,
+        server_identity: Arc<NodeIdentity>,
+        allowed: Vec<CommsPublicKey>,
+    ) -> Multiaddr
+    {
+        let (listener, address) = TcpTransport::new()
+            .listen("/ip4/127.0.0.1/tcp/0".parse().unwrap())
+            .unwrap()
+            .await
+            .unwrap();
+        tokio::spawn(SignerServer::new(signer, server_identity, allowed).serve(listener));
+        address
+    }
+
+    #[tokio_macros::test]
+    async fn it_forwards_requests_to_the_served_signer() {
+        let master_key = PrivateKey::random(&mut OsRng);
+        // The local signer shares the served signer's store, so that it derives the same kernel nonces
+        let store = Arc::new(MemoryKernelSigningStore::new());
+        let local = InProcessSigner::new(
+            master_key.clone(),
+            "".to_string(),
+            CryptoFactories::default(),
+            store.clone(),
+        )
+        .unwrap();
+        let served =
+            Arc::new(InProcessSigner::new(master_key, "".to_string(), CryptoFactories::default(), store).unwrap());
+        let server_identity = node_identity();
+        let client_identity = node_identity();
+        let address = start_server(served, server_identity.clone(), vec![client_identity
+            .public_key()
+            .clone()])
+        .await;
+        let remote = RemoteSigner::new(address, server_identity.public_key().clone(), client_identity);
+
+        let key_id = KeyId::new(KeyBranch::Spend, 5);
+        let output = remote
+            .create_output(key_id, MicroTari(500), OutputFeatures::default())
+            .await
+            .unwrap();
+        assert_eq!(
+            output.commitment,
+            local
+                .create_output(key_id, MicroTari(500), OutputFeatures::default())
+                .await
+                .unwrap()
+                .commitment
+        );
+        let rewound = remote.rewind_output(&output).await.unwrap().unwrap();
+        assert_eq!(rewound.value, MicroTari(500));
+        assert_eq!(rewound.key_id, key_id);
+
+        let request = KernelRequest::for_output(key_id);
+        let commitments = remote.kernel_commitments(&request).await.unwrap();
+        assert_eq!(commitments, local.kernel_commitments(&request).await.unwrap());
+        let challenge = [1u8; 32];
+        let signature = remote.sign_kernel(&request, &challenge).await.unwrap();
+        assert!(signature.verify_challenge(&commitments.public_excess, &challenge));
+        let (_, public_key) = PublicKey::random_keypair(&mut OsRng);
+        assert_eq!(
+            remote.kernel_nonce_shared_secret(&request, &public_key).await.unwrap(),
+            local.kernel_nonce_shared_secret(&request, &public_key).await.unwrap()
+        );
+        match remote.sign_kernel(&request, &b"another challenge".to_vec()).await {
+            Err(SignerError::RemoteError(_)) => (),
+            r => panic!("Unexpected result {:?}", r),
+        }
+
+        assert_eq!(
+            remote.rewind_public_keys().await.unwrap().rewind_public_key,
+            local.rewind_public_keys().await.unwrap().rewind_public_key
+        );
+        assert!(remote.seed_words().await.is_err());
+    }
+
+    #[tokio_macros::test]
+    async fn it_only_serves_allowed_clients() {
+        let served = Arc::new(
+            InProcessSigner::new(
+                PrivateKey::random(&mut OsRng),
+                "".to_string(),
+                CryptoFactories::default(),
+                Arc::new(MemoryKernelSigningStore::new()),
+            )
+            .unwrap(),
+        );
+        let server_identity = node_identity();
+        let address = start_server(served, server_identity.clone(), vec![node_identity()
+            .public_key()
+            .clone()])
+        .await;
+        let remote = RemoteSigner::new(address, server_identity.public_key().clone(), node_identity())
+            .with_timeout(Duration::from_secs(5));
+        assert!(remote.rewind_public_keys().await.is_err());
+    }
+
+    #[tokio_macros::test]
+    async fn it_only_talks_to_the_configured_signer() {
+        let served = Arc::new(
+            InProcessSigner::new(
+                PrivateKey::random(&mut OsRng),
+                "".to_string(),
+                CryptoFactories::default(),
+                Arc::new(MemoryKernelSigningStore::new()),
+            )
+            .unwrap(),
+        );
+        let client_identity = node_identity();
+        let address = start_server(served, node_identity(), vec![client_identity.public_key().clone()]).await;
+        let remote = RemoteSigner::new(address, node_identity().public_key().clone(), client_identity);
+        match remote.rewind_public_keys().await {
+            Err(SignerError::UnexpectedSignerPublicKey) => (),
+            r => panic!("Unexpected result {:?}", r),
+        }
+    }
+}
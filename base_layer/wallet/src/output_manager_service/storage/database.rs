@@ -23,7 +23,8 @@
 use crate::output_manager_service::{
     error::OutputManagerStorageError,
     service::Balance,
-    storage::models::DbUnblindedOutput,
+    signer::{KernelSigningRecord, KernelSigningStore, SignerError},
+    storage::models::{Account, AccountId, DbUnblindedOutput},
     TxId,
 };
 use aes_gcm::Aes256Gcm;
@@ -37,8 +38,7 @@ use std::{
 };
 use tari_core::transactions::{
     tari_amount::MicroTari,
-    transaction::{OutputFeatures, UnblindedOutput},
-    types::{BlindingFactor, Commitment, CryptoFactories, PrivateKey},
+    types::{Commitment, PrivateKey},
 };
 
 const LOG_TARGET: &str = "wallet::output_manager_service::database";
@@ -62,6 +62,7 @@ pub trait OutputManagerBackend: Send + Sync + Clone {
     fn short_term_encumber_outputs(
         &self,
         tx_id: TxId,
+        account_id: AccountId,
         outputs_to_send: &[DbUnblindedOutput],
         outputs_to_receive: &[DbUnblindedOutput],
     ) -> Result<(), OutputManagerStorageError>;
@@ -98,9 +99,12 @@ pub trait OutputManagerBackend: Send + Sync + Clone {
         &self,
         commitment: &Commitment,
     ) -> Result<DbUnblindedOutput, OutputManagerStorageError>;
+    /// Check whether an output with the given commitment is stored in any state
+    fn contains_output(&self, commitment: &Commitment) -> Result<bool, OutputManagerStorageError>;
 }
 
-/// Holds the outputs that have been selected for a given pending transaction waiting for confirmation
+/// Holds the outputs that have been selected for a given pending transaction waiting for confirmation. The outputs to
+/// be received are credited to `account_id`.
 #[derive(Debug, Clone, PartialEq)]
 pub struct PendingTransactionOutputs {
     pub tx_id: u64,
@@ -108,6 +112,7 @@ pub struct PendingTransactionOutputs {
     pub outputs_to_be_received: Vec<DbUnblindedOutput>,
     pub timestamp: NaiveDateTime,
     pub coinbase_block_height: Option<u64>,
+    pub account_id: AccountId,
 }
 
 /// Holds the state of the KeyManager being used by the Output Manager Service
@@ -120,8 +125,8 @@ pub struct KeyManagerState {
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum DbKey {
-    SpentOutput(BlindingFactor),
-    UnspentOutput(BlindingFactor),
+    SpentOutput(Commitment),
+    UnspentOutput(Commitment),
     PendingTransactionOutputs(TxId),
     TimeLockedUnspentOutputs(u64),
     UnspentOutputs,
@@ -129,6 +134,10 @@ pub enum DbKey {
     AllPendingTransactionOutputs,
     KeyManagerState,
     InvalidOutputs,
+    Accounts,
+    AccountTransactions,
+    ActiveAccount,
+    KernelSigningRecord(Vec<u8>),
 }
 
 #[derive(Debug)]
@@ -141,13 +150,23 @@ pub enum DbValue {
     InvalidOutputs(Vec<DbUnblindedOutput>),
     AllPendingTransactionOutputs(HashMap<TxId, PendingTransactionOutputs>),
     KeyManagerState(KeyManagerState),
+    Accounts(Vec<Account>),
+    AccountTransactions(HashMap<TxId, AccountId>),
+    ActiveAccount(AccountId),
+    KernelSigningRecord(Box<KernelSigningRecord>),
 }
 
 pub enum DbKeyValuePair {
-    SpentOutput(BlindingFactor, Box<DbUnblindedOutput>),
-    UnspentOutput(BlindingFactor, Box<DbUnblindedOutput>),
+    SpentOutput(Commitment, Box<DbUnblindedOutput>),
+    UnspentOutput(Commitment, Box<DbUnblindedOutput>),
     PendingTransactionOutputs(TxId, Box<PendingTransactionOutputs>),
     KeyManagerState(KeyManagerState),
+    Account(Account),
+    AccountTransaction(TxId, AccountId),
+    ActiveAccount(AccountId),
+    /// Insert or replace the signing record of a kernel request. Backends keep at most
+    /// `MAX_KERNEL_SIGNING_RECORDS` records and drop the oldest ones first.
+    KernelSigningRecord(Vec<u8>, Box<KernelSigningRecord>),
 }
 
 pub enum WriteOperation {
@@ -220,7 +239,7 @@ where T: OutputManagerBackend + 'static
         let db_clone = self.db.clone();
         tokio::task::spawn_blocking(move || {
             db_clone.write(WriteOperation::Insert(DbKeyValuePair::UnspentOutput(
-                output.unblinded_output.spending_key.clone(),
+                output.commitment.clone(),
                 Box::new(output),
             )))
         })
@@ -230,7 +249,34 @@ where T: OutputManagerBackend + 'static
         Ok(())
     }
 
+    pub async fn is_known_output(&self, commitment: Commitment) -> Result<bool, OutputManagerStorageError> {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || db_clone.contains_output(&commitment))
+            .await
+            .map_err(|err| OutputManagerStorageError::BlockingTaskSpawnError(err.to_string()))?
+    }
+
     pub async fn get_balance(&self, current_chain_tip: Option<u64>) -> Result<Balance, OutputManagerStorageError> {
+        self.calculate_balance(current_chain_tip, None).await
+    }
+
+    /// Calculate the balance of the outputs that belong to the given account
+    pub async fn get_account_balance(
+        &self,
+        current_chain_tip: Option<u64>,
+        account_id: AccountId,
+    ) -> Result<Balance, OutputManagerStorageError>
+    {
+        self.calculate_balance(current_chain_tip, Some(account_id)).await
+    }
+
+    async fn calculate_balance(
+        &self,
+        current_chain_tip: Option<u64>,
+        account_id: Option<AccountId>,
+    ) -> Result<Balance, OutputManagerStorageError>
+    {
+        let in_account = move |o: &&DbUnblindedOutput| account_id.map(|id| o.account_id == id).unwrap_or(true);
         let db_clone = self.db.clone();
         let db_clone2 = self.db.clone();
         let db_clone3 = self.db.clone();
@@ -257,6 +303,7 @@ where T: OutputManagerBackend + 'static
             if let DbValue::AllPendingTransactionOutputs(pto) = pending_txs {
                 let available_balance = uo
                     .iter()
+                    .filter(in_account)
                     .fold(MicroTari::from(0), |acc, x| acc + x.unblinded_output.value);
                 let time_locked_balance = if let Some(tip) = current_chain_tip {
                     let time_locked_outputs = tokio::task::spawn_blocking(move || {
@@ -272,6 +319,7 @@ where T: OutputManagerBackend + 'static
                         Some(
                             time_locked_uo
                                 .iter()
+                                .filter(in_account)
                                 .fold(MicroTari::from(0), |acc, x| acc + x.unblinded_output.value),
                         )
                     } else {
@@ -287,10 +335,12 @@ where T: OutputManagerBackend + 'static
                     pending_incoming += v
                         .outputs_to_be_received
                         .iter()
+                        .filter(in_account)
                         .fold(MicroTari::from(0), |acc, x| acc + x.unblinded_output.value);
                     pending_outgoing += v
                         .outputs_to_be_spent
                         .iter()
+                        .filter(in_account)
                         .fold(MicroTari::from(0), |acc, x| acc + x.unblinded_output.value);
                 }
 
@@ -349,23 +399,17 @@ where T: OutputManagerBackend + 'static
             .and_then(|inner_result| inner_result)
     }
 
-    /// This method accepts and stores a pending inbound transaction and creates the `output_to_be_received` from the
-    /// amount and provided spending key.
+    /// This method accepts and stores a pending inbound transaction with the provided `output_to_be_received`, which
+    /// will be credited to the given account.
     pub async fn accept_incoming_pending_transaction(
         &self,
         tx_id: TxId,
-        amount: MicroTari,
-        spending_key: PrivateKey,
-        output_features: OutputFeatures,
-        factory: &CryptoFactories,
+        output: DbUnblindedOutput,
+        account_id: AccountId,
         coinbase_block_height: Option<u64>,
     ) -> Result<(), OutputManagerStorageError>
     {
         let db_clone = self.db.clone();
-        let output = DbUnblindedOutput::from_unblinded_output(
-            UnblindedOutput::new(amount, spending_key.clone(), Some(output_features)),
-            factory,
-        )?;
         tokio::task::spawn_blocking(move || {
             db_clone.write(WriteOperation::Insert(DbKeyValuePair::PendingTransactionOutputs(
                 tx_id,
@@ -375,6 +419,7 @@ where T: OutputManagerBackend + 'static
                     outputs_to_be_received: vec![output],
                     timestamp: Utc::now().naive_utc(),
                     coinbase_block_height,
+                    account_id,
                 }),
             )))
         })
@@ -384,17 +429,18 @@ where T: OutputManagerBackend + 'static
     }
 
     /// This method is called when a transaction is built to be sent. It will encumber unspent outputs against a pending
-    /// transaction in the short term.
+    /// transaction in the short term. Any outputs to be received are credited to the given account.
     pub async fn encumber_outputs(
         &self,
         tx_id: TxId,
+        account_id: AccountId,
         outputs_to_send: Vec<DbUnblindedOutput>,
         outputs_to_receive: Vec<DbUnblindedOutput>,
     ) -> Result<(), OutputManagerStorageError>
     {
         let db_clone = self.db.clone();
         tokio::task::spawn_blocking(move || {
-            db_clone.short_term_encumber_outputs(tx_id, &outputs_to_send, &outputs_to_receive)
+            db_clone.short_term_encumber_outputs(tx_id, account_id, &outputs_to_send, &outputs_to_receive)
         })
         .await
         .map_err(|err| OutputManagerStorageError::BlockingTaskSpawnError(err.to_string()))
@@ -626,6 +672,123 @@ where T: OutputManagerBackend + 'static
             .map_err(|err| OutputManagerStorageError::BlockingTaskSpawnError(err.to_string()))
             .and_then(|inner_result| inner_result)
     }
+
+    /// Retrieve the accounts that have been created in this wallet. The default account is not stored and will not be
+    /// included.
+    pub async fn get_accounts(&self) -> Result<Vec<Account>, OutputManagerStorageError> {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || match db_clone.fetch(&DbKey::Accounts) {
+            Ok(None) => Ok(Vec::new()),
+            Ok(Some(DbValue::Accounts(a))) => Ok(a),
+            Ok(Some(other)) => unexpected_result(DbKey::Accounts, other),
+            Err(e) => log_error(DbKey::Accounts, e),
+        })
+        .await
+        .map_err(|err| OutputManagerStorageError::BlockingTaskSpawnError(err.to_string()))
+        .and_then(|inner_result| inner_result)
+    }
+
+    /// Insert a new account or update the key index of an existing one
+    pub async fn set_account(&self, account: Account) -> Result<(), OutputManagerStorageError> {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || db_clone.write(WriteOperation::Insert(DbKeyValuePair::Account(account))))
+            .await
+            .map_err(|err| OutputManagerStorageError::BlockingTaskSpawnError(err.to_string()))??;
+        Ok(())
+    }
+
+    /// Retrieve the account each recorded transaction belongs to. Transactions that are not recorded belong to the
+    /// default account.
+    pub async fn get_account_transactions(&self) -> Result<HashMap<TxId, AccountId>, OutputManagerStorageError> {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || match db_clone.fetch(&DbKey::AccountTransactions) {
+            Ok(None) => Ok(HashMap::new()),
+            Ok(Some(DbValue::AccountTransactions(t))) => Ok(t),
+            Ok(Some(other)) => unexpected_result(DbKey::AccountTransactions, other),
+            Err(e) => log_error(DbKey::AccountTransactions, e),
+        })
+        .await
+        .map_err(|err| OutputManagerStorageError::BlockingTaskSpawnError(err.to_string()))
+        .and_then(|inner_result| inner_result)
+    }
+
+    pub async fn set_account_transaction(
+        &self,
+        tx_id: TxId,
+        account_id: AccountId,
+    ) -> Result<(), OutputManagerStorageError>
+    {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || {
+            db_clone.write(WriteOperation::Insert(DbKeyValuePair::AccountTransaction(
+                tx_id, account_id,
+            )))
+        })
+        .await
+        .map_err(|err| OutputManagerStorageError::BlockingTaskSpawnError(err.to_string()))??;
+        Ok(())
+    }
+
+    /// Retrieve the id of the account that was last made active, if any
+    pub async fn get_active_account(&self) -> Result<Option<AccountId>, OutputManagerStorageError> {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || match db_clone.fetch(&DbKey::ActiveAccount) {
+            Ok(None) => Ok(None),
+            Ok(Some(DbValue::ActiveAccount(id))) => Ok(Some(id)),
+            Ok(Some(other)) => unexpected_result(DbKey::ActiveAccount, other),
+            Err(e) => log_error(DbKey::ActiveAccount, e),
+        })
+        .await
+        .map_err(|err| OutputManagerStorageError::BlockingTaskSpawnError(err.to_string()))
+        .and_then(|inner_result| inner_result)
+    }
+
+    pub async fn set_active_account(&self, account_id: AccountId) -> Result<(), OutputManagerStorageError> {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || {
+            db_clone.write(WriteOperation::Insert(DbKeyValuePair::ActiveAccount(account_id)))
+        })
+        .await
+        .map_err(|err| OutputManagerStorageError::BlockingTaskSpawnError(err.to_string()))??;
+        Ok(())
+    }
+}
+
+/// The in-process signer of the Output Manager Service keeps its kernel signing records in the wallet database, so
+/// that a kernel request that was signed is never signed again with another challenge after a restart
+#[tari_comms::async_trait]
+impl<T> KernelSigningStore for OutputManagerDatabase<T>
+where T: OutputManagerBackend + 'static
+{
+    async fn fetch_kernel_record(&self, request_hash: &[u8]) -> Result<Option<KernelSigningRecord>, SignerError> {
+        let db_clone = self.db.clone();
+        let key = DbKey::KernelSigningRecord(request_hash.to_vec());
+        tokio::task::spawn_blocking(move || match db_clone.fetch(&key) {
+            Ok(None) => Ok(None),
+            Ok(Some(DbValue::KernelSigningRecord(r))) => Ok(Some(*r)),
+            Ok(Some(other)) => unexpected_result(key, other),
+            Err(e) => log_error(key, e),
+        })
+        .await
+        .map_err(|err| OutputManagerStorageError::BlockingTaskSpawnError(err.to_string()))
+        .and_then(|inner_result| inner_result)
+        .map_err(|e| SignerError::StorageError(e.to_string()))
+    }
+
+    async fn set_kernel_record(&self, request_hash: Vec<u8>, record: KernelSigningRecord) -> Result<(), SignerError> {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || {
+            db_clone.write(WriteOperation::Insert(DbKeyValuePair::KernelSigningRecord(
+                request_hash,
+                Box::new(record),
+            )))
+        })
+        .await
+        .map_err(|err| OutputManagerStorageError::BlockingTaskSpawnError(err.to_string()))
+        .and_then(|inner_result| inner_result)
+        .map_err(|e| SignerError::StorageError(e.to_string()))?;
+        Ok(())
+    }
 }
 
 fn unexpected_result<T>(req: DbKey, res: DbValue) -> Result<T, OutputManagerStorageError> {
@@ -648,6 +811,10 @@ impl Display for DbKey {
             DbKey::KeyManagerState => f.write_str(&"Key Manager State".to_string()),
             DbKey::InvalidOutputs => f.write_str(&"Invalid Outputs Key"),
             DbKey::TimeLockedUnspentOutputs(_t) => f.write_str(&"Timelocked Outputs"),
+            DbKey::Accounts => f.write_str(&"Accounts"),
+            DbKey::AccountTransactions => f.write_str(&"Account Transactions"),
+            DbKey::ActiveAccount => f.write_str(&"Active Account"),
+            DbKey::KernelSigningRecord(_) => f.write_str(&"Kernel Signing Record"),
         }
     }
 }
@@ -663,6 +830,10 @@ impl Display for DbValue {
             DbValue::AllPendingTransactionOutputs(_) => f.write_str("All Pending Transaction Outputs"),
             DbValue::KeyManagerState(_) => f.write_str("Key Manager State"),
             DbValue::InvalidOutputs(_) => f.write_str("Invalid Outputs"),
+            DbValue::Accounts(_) => f.write_str("Accounts"),
+            DbValue::AccountTransactions(_) => f.write_str("Account Transactions"),
+            DbValue::ActiveAccount(_) => f.write_str("Active Account"),
+            DbValue::KernelSigningRecord(_) => f.write_str("Kernel Signing Record"),
         }
     }
 }
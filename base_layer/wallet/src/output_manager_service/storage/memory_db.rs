@@ -22,6 +22,7 @@
 
 use crate::output_manager_service::{
     error::OutputManagerStorageError,
+    signer::{KernelSigningRecord, MAX_KERNEL_SIGNING_RECORDS},
     storage::{
         database::{
             DbKey,
@@ -32,14 +33,14 @@ use crate::output_manager_service::{
             PendingTransactionOutputs,
             WriteOperation,
         },
-        models::DbUnblindedOutput,
+        models::{Account, AccountId, DbUnblindedOutput},
     },
     TxId,
 };
 use aes_gcm::Aes256Gcm;
 use chrono::{Duration as ChronoDuration, Utc};
 use std::{
-    collections::HashMap,
+    collections::{HashMap, VecDeque},
     sync::{Arc, RwLock},
     time::Duration,
 };
@@ -55,6 +56,11 @@ pub struct InnerDatabase {
     pending_transactions: HashMap<TxId, PendingTransactionOutputs>,
     short_term_pending_transactions: HashMap<TxId, PendingTransactionOutputs>,
     key_manager_state: Option<KeyManagerState>,
+    accounts: Vec<Account>,
+    account_transactions: HashMap<TxId, AccountId>,
+    active_account: Option<AccountId>,
+    kernel_signing_records: HashMap<Vec<u8>, KernelSigningRecord>,
+    kernel_signing_record_order: VecDeque<Vec<u8>>,
 }
 
 impl InnerDatabase {
@@ -66,6 +72,11 @@ impl InnerDatabase {
             pending_transactions: HashMap::new(),
             short_term_pending_transactions: Default::default(),
             key_manager_state: None,
+            accounts: Vec::new(),
+            account_transactions: HashMap::new(),
+            active_account: None,
+            kernel_signing_records: HashMap::new(),
+            kernel_signing_record_order: VecDeque::new(),
         }
     }
 }
@@ -90,12 +101,12 @@ impl OutputManagerBackend for OutputManagerMemoryDatabase {
             DbKey::SpentOutput(k) => db
                 .spent_outputs
                 .iter()
-                .find(|v| &v.output.unblinded_output.spending_key == k)
+                .find(|v| &v.output.commitment == k)
                 .map(|v| DbValue::SpentOutput(Box::new(DbUnblindedOutput::from((*v).clone())))),
             DbKey::UnspentOutput(k) => db
                 .unspent_outputs
                 .iter()
-                .find(|v| &v.output.unblinded_output.spending_key == k)
+                .find(|v| &v.output.commitment == k)
                 .map(|v| DbValue::UnspentOutput(Box::new(DbUnblindedOutput::from((*v).clone())))),
             DbKey::PendingTransactionOutputs(tx_id) => {
                 let mut result = db.pending_transactions.get(tx_id);
@@ -145,6 +156,13 @@ impl OutputManagerBackend for OutputManagerMemoryDatabase {
                     .map(|o| DbUnblindedOutput::from((*o).clone()))
                     .collect(),
             )),
+            DbKey::Accounts => Some(DbValue::Accounts(db.accounts.clone())),
+            DbKey::AccountTransactions => Some(DbValue::AccountTransactions(db.account_transactions.clone())),
+            DbKey::ActiveAccount => db.active_account.map(DbValue::ActiveAccount),
+            DbKey::KernelSigningRecord(request_hash) => db
+                .kernel_signing_records
+                .get(request_hash)
+                .map(|r| DbValue::KernelSigningRecord(Box::new(r.clone()))),
         };
 
         Ok(result)
@@ -155,42 +173,53 @@ impl OutputManagerBackend for OutputManagerMemoryDatabase {
         match op {
             WriteOperation::Insert(kvp) => match kvp {
                 DbKeyValuePair::SpentOutput(k, o) => {
-                    if db
-                        .spent_outputs
-                        .iter()
-                        .any(|v| v.output.unblinded_output.spending_key == k) ||
-                        db.unspent_outputs
-                            .iter()
-                            .any(|v| v.output.unblinded_output.spending_key == k)
+                    if db.spent_outputs.iter().any(|v| v.output.commitment == k) ||
+                        db.unspent_outputs.iter().any(|v| v.output.commitment == k)
                     {
                         return Err(OutputManagerStorageError::DuplicateOutput);
                     }
                     db.spent_outputs.push(DbOutput::from(*o));
                 },
                 DbKeyValuePair::UnspentOutput(k, o) => {
-                    if db
-                        .unspent_outputs
-                        .iter()
-                        .any(|v| v.output.unblinded_output.spending_key == k) ||
-                        db.spent_outputs
-                            .iter()
-                            .any(|v| v.output.unblinded_output.spending_key == k)
+                    if db.unspent_outputs.iter().any(|v| v.output.commitment == k) ||
+                        db.spent_outputs.iter().any(|v| v.output.commitment == k)
                     {
                         return Err(OutputManagerStorageError::DuplicateOutput);
                     }
                     db.unspent_outputs.push(DbOutput::from(*o));
                 },
-                DbKeyValuePair::PendingTransactionOutputs(t, p) => {
+                DbKeyValuePair::PendingTransactionOutputs(t, mut p) => {
+                    for o in p.outputs_to_be_received.iter_mut() {
+                        o.account_id = p.account_id;
+                    }
                     db.short_term_pending_transactions.insert(t, *p);
                 },
                 DbKeyValuePair::KeyManagerState(km) => db.key_manager_state = Some(km),
+                DbKeyValuePair::Account(account) => match db.accounts.iter_mut().find(|a| a.id == account.id) {
+                    Some(existing) => *existing = account,
+                    None => db.accounts.push(account),
+                },
+                DbKeyValuePair::AccountTransaction(tx_id, account_id) => {
+                    db.account_transactions.insert(tx_id, account_id);
+                },
+                DbKeyValuePair::ActiveAccount(account_id) => db.active_account = Some(account_id),
+                DbKeyValuePair::KernelSigningRecord(request_hash, record) => {
+                    if db
+                        .kernel_signing_records
+                        .insert(request_hash.clone(), *record)
+                        .is_none()
+                    {
+                        db.kernel_signing_record_order.push_back(request_hash);
+                        while db.kernel_signing_record_order.len() > MAX_KERNEL_SIGNING_RECORDS {
+                            if let Some(oldest) = db.kernel_signing_record_order.pop_front() {
+                                db.kernel_signing_records.remove(&oldest);
+                            }
+                        }
+                    }
+                },
             },
             WriteOperation::Remove(k) => match k {
-                DbKey::SpentOutput(k) => match db
-                    .spent_outputs
-                    .iter()
-                    .position(|v| v.output.unblinded_output.spending_key == k)
-                {
+                DbKey::SpentOutput(k) => match db.spent_outputs.iter().position(|v| v.output.commitment == k) {
                     None => return Err(OutputManagerStorageError::ValueNotFound(DbKey::SpentOutput(k))),
                     Some(pos) => {
                         return Ok(Some(DbValue::SpentOutput(Box::new(DbUnblindedOutput::from(
@@ -198,11 +227,7 @@ impl OutputManagerBackend for OutputManagerMemoryDatabase {
                         )))));
                     },
                 },
-                DbKey::UnspentOutput(k) => match db
-                    .unspent_outputs
-                    .iter()
-                    .position(|v| v.output.unblinded_output.spending_key == k)
-                {
+                DbKey::UnspentOutput(k) => match db.unspent_outputs.iter().position(|v| v.output.commitment == k) {
                     None => return Err(OutputManagerStorageError::ValueNotFound(DbKey::UnspentOutput(k))),
                     Some(pos) => {
                         return Ok(Some(DbValue::UnspentOutput(Box::new(DbUnblindedOutput::from(
@@ -225,6 +250,10 @@ impl OutputManagerBackend for OutputManagerMemoryDatabase {
                 DbKey::KeyManagerState => return Err(OutputManagerStorageError::OperationNotSupported),
                 DbKey::InvalidOutputs => return Err(OutputManagerStorageError::OperationNotSupported),
                 DbKey::TimeLockedUnspentOutputs(_) => return Err(OutputManagerStorageError::OperationNotSupported),
+                DbKey::Accounts => return Err(OutputManagerStorageError::OperationNotSupported),
+                DbKey::AccountTransactions => return Err(OutputManagerStorageError::OperationNotSupported),
+                DbKey::ActiveAccount => return Err(OutputManagerStorageError::OperationNotSupported),
+                DbKey::KernelSigningRecord(_) => return Err(OutputManagerStorageError::OperationNotSupported),
             },
         }
         Ok(None)
@@ -263,6 +292,7 @@ impl OutputManagerBackend for OutputManagerMemoryDatabase {
     fn short_term_encumber_outputs(
         &self,
         tx_id: TxId,
+        account_id: AccountId,
         outputs_to_send: &[DbUnblindedOutput],
         outputs_to_receive: &[DbUnblindedOutput],
     ) -> Result<(), OutputManagerStorageError>
@@ -273,7 +303,7 @@ impl OutputManagerBackend for OutputManagerMemoryDatabase {
             if let Some(pos) = db
                 .unspent_outputs
                 .iter()
-                .position(|v| v.output.unblinded_output.spending_key == i.unblinded_output.spending_key)
+                .position(|v| v.output.commitment == i.commitment)
             {
                 outputs_to_be_spent.push(DbUnblindedOutput::from(db.unspent_outputs.remove(pos)));
             } else {
@@ -287,10 +317,13 @@ impl OutputManagerBackend for OutputManagerMemoryDatabase {
             outputs_to_be_received: Vec::new(),
             timestamp: Utc::now().naive_utc(),
             coinbase_block_height: None,
+            account_id,
         };
 
         for co in outputs_to_receive {
-            pending_transaction.outputs_to_be_received.push(co.clone());
+            pending_transaction
+                .outputs_to_be_received
+                .push(co.clone().with_account(account_id));
         }
 
         db.short_term_pending_transactions.insert(tx_id, pending_transaction);
@@ -384,7 +417,7 @@ impl OutputManagerBackend for OutputManagerMemoryDatabase {
         match db
             .unspent_outputs
             .iter()
-            .position(|v| v.output.unblinded_output.spending_key == output.unblinded_output.spending_key)
+            .position(|v| v.output.commitment == output.commitment)
         {
             Some(pos) => {
                 let output = db.unspent_outputs.remove(pos);
@@ -427,6 +460,26 @@ impl OutputManagerBackend for OutputManagerMemoryDatabase {
         }
     }
 
+    fn contains_output(&self, commitment: &Commitment) -> Result<bool, OutputManagerStorageError> {
+        let db = acquire_read_lock!(self.db);
+        let is_pending = db
+            .pending_transactions
+            .values()
+            .chain(db.short_term_pending_transactions.values())
+            .any(|p| {
+                p.outputs_to_be_spent
+                    .iter()
+                    .chain(p.outputs_to_be_received.iter())
+                    .any(|o| o.commitment == *commitment)
+            });
+        Ok(is_pending ||
+            db.unspent_outputs
+                .iter()
+                .chain(db.spent_outputs.iter())
+                .chain(db.invalid_outputs.iter())
+                .any(|v| v.output.commitment == *commitment))
+    }
+
     fn cancel_pending_transaction_at_block_height(&self, block_height: u64) -> Result<(), OutputManagerStorageError> {
         let pending_txs;
         {
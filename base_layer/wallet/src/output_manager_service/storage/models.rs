@@ -20,22 +20,64 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use crate::output_manager_service::error::OutputManagerStorageError;
-use std::cmp::Ordering;
+use crate::output_manager_service::{error::OutputManagerStorageError, signer::KeyId, TxId};
+use std::{cmp::Ordering, collections::HashMap};
 use tari_core::{
     tari_utilities::hash::Hashable,
     transactions::{
-        transaction::UnblindedOutput,
-        transaction_protocol::RewindData,
-        types::{Commitment, CryptoFactories, HashOutput},
+        tari_amount::MicroTari,
+        transaction::{TransactionInput, TransactionOutput, UnblindedOutput},
+        types::{BlindingFactor, Commitment, CryptoFactories, HashOutput},
     },
 };
 
+/// The identifier of a wallet account
+pub type AccountId = u64;
+
+/// The account that every wallet has. Its keys are derived from the wallet's original spend branch so that wallets
+/// created before accounts existed keep their keys, and recovered or imported outputs are credited to it.
+pub const DEFAULT_ACCOUNT_ID: AccountId = 0;
+pub const DEFAULT_ACCOUNT_NAME: &str = "default";
+
+/// A named account of the wallet. Each account other than the default account derives its keys from its own key
+/// branch and has its own outputs, balance and transaction history.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Account {
+    pub id: AccountId,
+    pub name: String,
+    /// The index of the last key derived on this account's branch
+    pub key_index: u64,
+}
+
+impl Account {
+    pub fn new(id: AccountId, name: String) -> Self {
+        Self { id, name, key_index: 0 }
+    }
+
+    pub fn default_account() -> Self {
+        Self::new(DEFAULT_ACCOUNT_ID, DEFAULT_ACCOUNT_NAME.to_string())
+    }
+
+    pub fn is_default(&self) -> bool {
+        self.id == DEFAULT_ACCOUNT_ID
+    }
+}
+
+/// Look up the account a transaction belongs to. Transactions that were not recorded against an account, such as those
+/// made before accounts existed, belong to the default account.
+pub fn account_of(account_transactions: &HashMap<TxId, AccountId>, tx_id: TxId) -> AccountId {
+    account_transactions.get(&tx_id).copied().unwrap_or(DEFAULT_ACCOUNT_ID)
+}
+
 #[derive(Debug, Clone)]
 pub struct DbUnblindedOutput {
     pub commitment: Commitment,
     pub unblinded_output: UnblindedOutput,
     pub hash: HashOutput,
+    pub account_id: AccountId,
+    /// The id of the output's key if the key is held by the transaction signer. The `spending_key` of such an output
+    /// is zero.
+    pub key_id: Option<KeyId>,
 }
 
 impl DbUnblindedOutput {
@@ -49,21 +91,31 @@ impl DbUnblindedOutput {
             hash: tx_out.hash(),
             commitment: tx_out.commitment,
             unblinded_output: output,
+            account_id: DEFAULT_ACCOUNT_ID,
+            key_id: None,
         })
     }
 
-    pub fn rewindable_from_unblinded_output(
-        output: UnblindedOutput,
-        factory: &CryptoFactories,
-        rewind_data: &RewindData,
-    ) -> Result<DbUnblindedOutput, OutputManagerStorageError>
-    {
-        let tx_out = output.as_rewindable_transaction_output(factory, rewind_data)?;
-        Ok(DbUnblindedOutput {
-            hash: tx_out.hash(),
-            commitment: tx_out.commitment,
-            unblinded_output: output,
-        })
+    /// Record an output that was built by the transaction signer with the key `key_id`
+    pub fn from_signer_output(output: &TransactionOutput, value: MicroTari, key_id: KeyId) -> DbUnblindedOutput {
+        DbUnblindedOutput {
+            hash: output.hash(),
+            commitment: output.commitment.clone(),
+            unblinded_output: UnblindedOutput::new(value, BlindingFactor::default(), Some(output.features.clone())),
+            account_id: DEFAULT_ACCOUNT_ID,
+            key_id: Some(key_id),
+        }
+    }
+
+    /// The input that spends this output
+    pub fn as_transaction_input(&self) -> TransactionInput {
+        TransactionInput::new(self.unblinded_output.features.clone(), self.commitment.clone())
+    }
+
+    /// Assign this output to the given account
+    pub fn with_account(mut self, account_id: AccountId) -> Self {
+        self.account_id = account_id;
+        self
     }
 }
 
@@ -23,6 +23,7 @@
 use crate::{
     output_manager_service::{
         error::OutputManagerStorageError,
+        signer::{KernelSigningRecord, KeyId, MAX_KERNEL_SIGNING_RECORDS},
         storage::{
             database::{
                 DbKey,
@@ -33,11 +34,18 @@ use crate::{
                 PendingTransactionOutputs,
                 WriteOperation,
             },
-            models::DbUnblindedOutput,
+            models::{Account, AccountId, DbUnblindedOutput},
         },
         TxId,
     },
-    schema::{key_manager_states, outputs, pending_transaction_outputs},
+    schema::{
+        account_transactions,
+        accounts,
+        kernel_signing_records,
+        key_manager_states,
+        outputs,
+        pending_transaction_outputs,
+    },
     storage::sqlite_utilities::WalletDbConnection,
     util::encryption::{decrypt_bytes_integral_nonce, encrypt_bytes_integral_nonce, Encryptable},
 };
@@ -146,6 +154,7 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
                             &p.timestamp,
                             outputs,
                             p.coinbase_block_height.map(|h| h as u64),
+                            p.account_id as AccountId,
                         )?,
                     )))
                 },
@@ -213,6 +222,7 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
                             &p_tx.timestamp,
                             outputs,
                             p_tx.coinbase_block_height.map(|h| h as u64),
+                            p_tx.account_id as AccountId,
                         )?,
                     );
                 }
@@ -239,6 +249,20 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
                         .collect::<Result<Vec<_>, _>>()?,
                 ))
             },
+            DbKey::Accounts => Some(DbValue::Accounts(
+                AccountSql::index(&(*conn))?.into_iter().map(Account::from).collect(),
+            )),
+            DbKey::AccountTransactions => Some(DbValue::AccountTransactions(
+                AccountTransactionSql::index(&(*conn))?
+                    .into_iter()
+                    .map(|t| (t.tx_id as TxId, t.account_id as AccountId))
+                    .collect(),
+            )),
+            DbKey::ActiveAccount => {
+                AccountSql::find_active(&(*conn))?.map(|a| DbValue::ActiveAccount(a.id as AccountId))
+            },
+            DbKey::KernelSigningRecord(request_hash) => KernelSigningRecordSql::find(request_hash, &(*conn))?
+                .map(|r| DbValue::KernelSigningRecord(Box::new(KernelSigningRecord::from(r)))),
         };
 
         Ok(result)
@@ -277,6 +301,7 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
                         true,
                         p.timestamp,
                         p.coinbase_block_height.map(|h| h as i64),
+                        p.account_id,
                     )
                     .commit(&(*conn))?;
                     for o in p.outputs_to_be_spent {
@@ -285,7 +310,11 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
                         new_output.commit(&(*conn))?;
                     }
                     for o in p.outputs_to_be_received {
-                        let mut new_output = NewOutputSql::new(o, OutputStatus::EncumberedToBeReceived, Some(p.tx_id));
+                        let mut new_output = NewOutputSql::new(
+                            o.with_account(p.account_id),
+                            OutputStatus::EncumberedToBeReceived,
+                            Some(p.tx_id),
+                        );
                         self.encrypt_if_necessary(&mut new_output)?;
                         new_output.commit(&(*conn))?;
                     }
@@ -295,6 +324,16 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
                     self.encrypt_if_necessary(&mut km_sql)?;
                     km_sql.set_state(&(*conn))?
                 },
+                DbKeyValuePair::Account(account) => NewAccountSql::from(account).set(&(*conn))?,
+                DbKeyValuePair::AccountTransaction(tx_id, account_id) => AccountTransactionSql {
+                    tx_id: tx_id as i64,
+                    account_id: account_id as i64,
+                }
+                .set(&(*conn))?,
+                DbKeyValuePair::ActiveAccount(account_id) => AccountSql::set_active(account_id, &(*conn))?,
+                DbKeyValuePair::KernelSigningRecord(request_hash, record) => {
+                    KernelSigningRecordSql::set(request_hash, *record, &(*conn))?
+                },
             },
             WriteOperation::Remove(k) => match k {
                 DbKey::SpentOutput(s) => match OutputSql::find_status(&s.to_vec(), OutputStatus::Spent, &(*conn)) {
@@ -336,6 +375,7 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
                                 &p.timestamp,
                                 outputs,
                                 p.coinbase_block_height.map(|h| h as u64),
+                                p.account_id as AccountId,
                             )?,
                         ))));
                     },
@@ -352,6 +392,10 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
                 DbKey::KeyManagerState => return Err(OutputManagerStorageError::OperationNotSupported),
                 DbKey::InvalidOutputs => return Err(OutputManagerStorageError::OperationNotSupported),
                 DbKey::TimeLockedUnspentOutputs(_) => return Err(OutputManagerStorageError::OperationNotSupported),
+                DbKey::Accounts => return Err(OutputManagerStorageError::OperationNotSupported),
+                DbKey::AccountTransactions => return Err(OutputManagerStorageError::OperationNotSupported),
+                DbKey::ActiveAccount => return Err(OutputManagerStorageError::OperationNotSupported),
+                DbKey::KernelSigningRecord(_) => return Err(OutputManagerStorageError::OperationNotSupported),
             },
         }
 
@@ -403,6 +447,7 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
     fn short_term_encumber_outputs(
         &self,
         tx_id: u64,
+        account_id: AccountId,
         outputs_to_send: &[DbUnblindedOutput],
         outputs_to_receive: &[DbUnblindedOutput],
     ) -> Result<(), OutputManagerStorageError>
@@ -418,7 +463,7 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
             outputs_to_be_spent.push(output);
         }
 
-        PendingTransactionOutputSql::new(tx_id, true, Utc::now().naive_utc(), None).commit(&(*conn))?;
+        PendingTransactionOutputSql::new(tx_id, true, Utc::now().naive_utc(), None, account_id).commit(&(*conn))?;
 
         for o in outputs_to_be_spent {
             o.update(
@@ -432,7 +477,11 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
         }
 
         for co in outputs_to_receive {
-            let mut new_output = NewOutputSql::new(co.clone(), OutputStatus::EncumberedToBeReceived, Some(tx_id));
+            let mut new_output = NewOutputSql::new(
+                co.clone().with_account(account_id),
+                OutputStatus::EncumberedToBeReceived,
+                Some(tx_id),
+            );
             self.encrypt_if_necessary(&mut new_output)?;
             new_output.commit(&(*conn))?;
         }
@@ -603,6 +652,15 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
         Ok(DbUnblindedOutput::try_from(o)?)
     }
 
+    fn contains_output(&self, commitment: &Commitment) -> Result<bool, OutputManagerStorageError> {
+        let conn = self.database_connection.acquire_lock();
+        match OutputSql::find(&commitment.to_vec(), &(*conn)) {
+            Ok(_) => Ok(true),
+            Err(OutputManagerStorageError::DieselError(DieselError::NotFound)) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
     fn cancel_pending_transaction_at_block_height(&self, block_height: u64) -> Result<(), OutputManagerStorageError> {
         let pending_txs;
         {
@@ -694,6 +752,7 @@ fn pending_transaction_outputs_from_sql_outputs(
     timestamp: &NaiveDateTime,
     outputs: Vec<OutputSql>,
     coinbase_block_height: Option<u64>,
+    account_id: AccountId,
 ) -> Result<PendingTransactionOutputs, OutputManagerStorageError>
 {
     let mut outputs_to_be_spent = Vec::new();
@@ -712,6 +771,7 @@ fn pending_transaction_outputs_from_sql_outputs(
         outputs_to_be_received,
         timestamp: *timestamp,
         coinbase_block_height,
+        account_id,
     })
 }
 
@@ -755,6 +815,9 @@ struct NewOutputSql {
     status: i32,
     tx_id: Option<i64>,
     hash: Option<Vec<u8>>,
+    account_id: i64,
+    features_version: i32,
+    key_id: Option<Vec<u8>>,
 }
 
 impl NewOutputSql {
@@ -768,6 +831,9 @@ impl NewOutputSql {
             status: status as i32,
             tx_id: tx_id.map(|i| i as i64),
             hash: Some(output.hash),
+            account_id: output.account_id as i64,
+            features_version: i32::from(output.unblinded_output.features.version),
+            key_id: output.key_id.map(|k| k.to_proof_message().to_vec()),
         }
     }
 
@@ -802,6 +868,9 @@ struct OutputSql {
     status: i32,
     tx_id: Option<i64>,
     hash: Option<Vec<u8>>,
+    account_id: i64,
+    features_version: i32,
+    key_id: Option<Vec<u8>>,
 }
 
 impl OutputSql {
@@ -827,13 +896,18 @@ impl OutputSql {
             .load(conn)?)
     }
 
-    /// Find a particular Output, if it exists
-    pub fn find(spending_key: &[u8], conn: &SqliteConnection) -> Result<OutputSql, OutputManagerStorageError> {
+    /// Find a particular Output by its commitment, if it exists, regardless of its status
+    pub fn find(commitment: &[u8], conn: &SqliteConnection) -> Result<OutputSql, OutputManagerStorageError> {
         Ok(outputs::table
-            .filter(outputs::spending_key.eq(spending_key))
+            .filter(outputs::commitment.eq(commitment))
             .first::<OutputSql>(conn)?)
     }
 
+    /// Find a particular Output by its id
+    pub fn find_by_id(id: i32, conn: &SqliteConnection) -> Result<OutputSql, OutputManagerStorageError> {
+        Ok(outputs::table.filter(outputs::id.eq(id)).first::<OutputSql>(conn)?)
+    }
+
     /// Find a particular Output by its public_spending_key
     pub fn find_by_commitment(
         commitment: &[u8],
@@ -865,20 +939,19 @@ impl OutputSql {
 
     /// Find a particular Output, if it exists and is in the specified Spent state
     pub fn find_status(
-        spending_key: &[u8],
+        commitment: &[u8],
         status: OutputStatus,
         conn: &SqliteConnection,
     ) -> Result<OutputSql, OutputManagerStorageError>
     {
         Ok(outputs::table
             .filter(outputs::status.eq(status as i32))
-            .filter(outputs::spending_key.eq(spending_key))
+            .filter(outputs::commitment.eq(commitment))
             .first::<OutputSql>(conn)?)
     }
 
     pub fn delete(&self, conn: &SqliteConnection) -> Result<(), OutputManagerStorageError> {
-        let num_deleted =
-            diesel::delete(outputs::table.filter(outputs::spending_key.eq(&self.spending_key))).execute(conn)?;
+        let num_deleted = diesel::delete(outputs::table.filter(outputs::id.eq(&self.id))).execute(conn)?;
 
         if num_deleted == 0 {
             return Err(OutputManagerStorageError::ValuesNotFound);
@@ -903,7 +976,7 @@ impl OutputSql {
             ));
         }
 
-        Ok(OutputSql::find(&self.spending_key, conn)?)
+        Ok(OutputSql::find_by_id(self.id, conn)?)
     }
 
     /// This function is used to update an existing record to set fields to null
@@ -913,7 +986,7 @@ impl OutputSql {
         conn: &SqliteConnection,
     ) -> Result<OutputSql, OutputManagerStorageError>
     {
-        let num_updated = diesel::update(outputs::table.filter(outputs::spending_key.eq(&self.spending_key)))
+        let num_updated = diesel::update(outputs::table.filter(outputs::id.eq(&self.id)))
             .set(updated_null)
             .execute(conn)?;
 
@@ -923,7 +996,7 @@ impl OutputSql {
             ));
         }
 
-        Ok(OutputSql::find(&self.spending_key, conn)?)
+        Ok(OutputSql::find_by_id(self.id, conn)?)
     }
 
     /// Update the changed fields of this record after encryption/decryption is performed
@@ -955,6 +1028,7 @@ impl TryFrom<OutputSql> for DbUnblindedOutput {
                 OutputManagerStorageError::ConversionError
             })?,
             Some(OutputFeatures {
+                version: o.features_version as u8,
                 flags: OutputFlags::from_bits(o.flags as u8)
                     .ok_or_else(|| OutputManagerStorageError::ConversionError)?,
                 maturity: o.maturity as u64,
@@ -976,11 +1050,17 @@ impl TryFrom<OutputSql> for DbUnblindedOutput {
             },
             Some(c) => Commitment::from_vec(&c)?,
         };
+        let key_id = match o.key_id {
+            None => None,
+            Some(k) => Some(KeyId::from_proof_message(&k).ok_or_else(|| OutputManagerStorageError::ConversionError)?),
+        };
 
         Ok(Self {
             commitment,
             unblinded_output,
             hash,
+            account_id: o.account_id as AccountId,
+            key_id,
         })
     }
 }
@@ -1008,6 +1088,9 @@ impl From<OutputSql> for NewOutputSql {
             status: o.status,
             tx_id: o.tx_id,
             hash: o.hash,
+            account_id: o.account_id,
+            features_version: o.features_version,
+            key_id: o.key_id,
         }
     }
 }
@@ -1061,14 +1144,23 @@ struct PendingTransactionOutputSql {
     short_term: i32,
     timestamp: NaiveDateTime,
     coinbase_block_height: Option<i64>,
+    account_id: i64,
 }
 impl PendingTransactionOutputSql {
-    pub fn new(tx_id: TxId, short_term: bool, timestamp: NaiveDateTime, coinbase_block_height: Option<i64>) -> Self {
+    pub fn new(
+        tx_id: TxId,
+        short_term: bool,
+        timestamp: NaiveDateTime,
+        coinbase_block_height: Option<i64>,
+        account_id: AccountId,
+    ) -> Self
+    {
         Self {
             tx_id: tx_id as i64,
             short_term: short_term as i32,
             timestamp,
             coinbase_block_height,
+            account_id: account_id as i64,
         }
     }
 
@@ -1271,6 +1363,181 @@ struct KeyManagerStateUpdateSql {
     primary_key_index: Option<i64>,
 }
 
+#[derive(Clone, Debug, Queryable)]
+struct AccountSql {
+    id: i64,
+    name: String,
+    key_index: i64,
+    active: i32,
+}
+
+impl AccountSql {
+    pub fn index(conn: &SqliteConnection) -> Result<Vec<AccountSql>, OutputManagerStorageError> {
+        Ok(accounts::table.order(accounts::id.asc()).load::<AccountSql>(conn)?)
+    }
+
+    pub fn find_active(conn: &SqliteConnection) -> Result<Option<AccountSql>, OutputManagerStorageError> {
+        Ok(accounts::table
+            .filter(accounts::active.eq(1))
+            .first::<AccountSql>(conn)
+            .optional()?)
+    }
+
+    /// Flag the given account as the active one. The default account is not stored so making it active clears the
+    /// flag on all accounts.
+    pub fn set_active(account_id: AccountId, conn: &SqliteConnection) -> Result<(), OutputManagerStorageError> {
+        conn.transaction::<_, OutputManagerStorageError, _>(|| {
+            diesel::update(accounts::table)
+                .set(accounts::active.eq(0))
+                .execute(conn)?;
+            diesel::update(accounts::table.filter(accounts::id.eq(account_id as i64)))
+                .set(accounts::active.eq(1))
+                .execute(conn)?;
+            Ok(())
+        })
+    }
+}
+
+/// The insertable and updatable part of an account record. The active flag is only changed through
+/// `AccountSql::set_active`.
+#[derive(Clone, Debug, Insertable, AsChangeset)]
+#[table_name = "accounts"]
+struct NewAccountSql {
+    id: i64,
+    name: String,
+    key_index: i64,
+}
+
+impl NewAccountSql {
+    /// Insert this account or, if an account with this id already exists, update it
+    pub fn set(&self, conn: &SqliteConnection) -> Result<(), OutputManagerStorageError> {
+        let num_updated = diesel::update(accounts::table.filter(accounts::id.eq(self.id)))
+            .set(self.clone())
+            .execute(conn)?;
+        if num_updated == 0 {
+            diesel::insert_into(accounts::table)
+                .values(self.clone())
+                .execute(conn)?;
+        }
+        Ok(())
+    }
+}
+
+impl From<Account> for NewAccountSql {
+    fn from(a: Account) -> Self {
+        Self {
+            id: a.id as i64,
+            name: a.name,
+            key_index: a.key_index as i64,
+        }
+    }
+}
+
+impl From<AccountSql> for Account {
+    fn from(a: AccountSql) -> Self {
+        Self {
+            id: a.id as AccountId,
+            name: a.name,
+            key_index: a.key_index as u64,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Queryable, Insertable)]
+#[table_name = "account_transactions"]
+struct AccountTransactionSql {
+    tx_id: i64,
+    account_id: i64,
+}
+
+impl AccountTransactionSql {
+    pub fn index(conn: &SqliteConnection) -> Result<Vec<AccountTransactionSql>, OutputManagerStorageError> {
+        Ok(account_transactions::table.load::<AccountTransactionSql>(conn)?)
+    }
+
+    pub fn set(&self, conn: &SqliteConnection) -> Result<(), OutputManagerStorageError> {
+        diesel::replace_into(account_transactions::table)
+            .values(self.clone())
+            .execute(conn)?;
+        Ok(())
+    }
+}
+
+/// The signing record of a kernel request of the in-process signer. The nonce salt is not encrypted as the nonce is
+/// also derived from the master key.
+#[derive(Clone, Debug, Queryable, Insertable)]
+#[table_name = "kernel_signing_records"]
+struct KernelSigningRecordSql {
+    request_hash: Vec<u8>,
+    nonce_salt: Vec<u8>,
+    challenge: Option<Vec<u8>>,
+    timestamp: NaiveDateTime,
+}
+
+impl KernelSigningRecordSql {
+    pub fn find(
+        request_hash: &[u8],
+        conn: &SqliteConnection,
+    ) -> Result<Option<KernelSigningRecordSql>, OutputManagerStorageError>
+    {
+        Ok(kernel_signing_records::table
+            .filter(kernel_signing_records::request_hash.eq(request_hash))
+            .first::<KernelSigningRecordSql>(conn)
+            .optional()?)
+    }
+
+    /// Update the record of a request, or insert it and drop the oldest records beyond `MAX_KERNEL_SIGNING_RECORDS`
+    pub fn set(
+        request_hash: Vec<u8>,
+        record: KernelSigningRecord,
+        conn: &SqliteConnection,
+    ) -> Result<(), OutputManagerStorageError>
+    {
+        let num_updated = diesel::update(
+            kernel_signing_records::table.filter(kernel_signing_records::request_hash.eq(&request_hash)),
+        )
+        .set((
+            kernel_signing_records::nonce_salt.eq(&record.nonce_salt),
+            kernel_signing_records::challenge.eq(&record.challenge),
+        ))
+        .execute(conn)?;
+        if num_updated > 0 {
+            return Ok(());
+        }
+
+        diesel::insert_into(kernel_signing_records::table)
+            .values(KernelSigningRecordSql {
+                request_hash,
+                nonce_salt: record.nonce_salt,
+                challenge: record.challenge,
+                timestamp: Utc::now().naive_utc(),
+            })
+            .execute(conn)?;
+
+        let count = kernel_signing_records::table.count().get_result::<i64>(conn)?;
+        let excess = count - MAX_KERNEL_SIGNING_RECORDS as i64;
+        if excess > 0 {
+            let oldest = kernel_signing_records::table
+                .select(kernel_signing_records::request_hash)
+                .order(kernel_signing_records::timestamp.asc())
+                .limit(excess)
+                .load::<Vec<u8>>(conn)?;
+            diesel::delete(kernel_signing_records::table.filter(kernel_signing_records::request_hash.eq_any(oldest)))
+                .execute(conn)?;
+        }
+        Ok(())
+    }
+}
+
+impl From<KernelSigningRecordSql> for KernelSigningRecord {
+    fn from(r: KernelSigningRecordSql) -> Self {
+        Self {
+            nonce_salt: r.nonce_salt,
+            challenge: r.challenge,
+        }
+    }
+}
+
 impl Encryptable<Aes256Gcm> for KeyManagerStateSql {
     fn encrypt(&mut self, cipher: &Aes256Gcm) -> Result<(), Error> {
         let encrypted_master_key = encrypt_bytes_integral_nonce(&cipher, self.master_key.clone())?;
@@ -1390,30 +1657,34 @@ mod test {
         );
 
         assert_eq!(
-            OutputSql::find(&outputs[0].spending_key, &conn).unwrap().spending_key,
+            OutputSql::find(outputs[0].commitment.as_ref().unwrap(), &conn)
+                .unwrap()
+                .spending_key,
             outputs[0].spending_key
         );
 
         assert_eq!(
-            OutputSql::find_status(&outputs[0].spending_key, OutputStatus::Unspent, &conn)
+            OutputSql::find_status(outputs[0].commitment.as_ref().unwrap(), OutputStatus::Unspent, &conn)
                 .unwrap()
                 .spending_key,
             outputs[0].spending_key
         );
 
-        assert!(OutputSql::find_status(&outputs[0].spending_key, OutputStatus::Spent, &conn).is_err());
+        assert!(OutputSql::find_status(outputs[0].commitment.as_ref().unwrap(), OutputStatus::Spent, &conn).is_err());
 
-        let _ = OutputSql::find(&outputs[4].spending_key, &conn).unwrap().delete(&conn);
+        let _ = OutputSql::find(outputs[4].commitment.as_ref().unwrap(), &conn)
+            .unwrap()
+            .delete(&conn);
 
         assert_eq!(OutputSql::index(&conn).unwrap().len(), 4);
 
         let tx_id = 44u64;
 
-        PendingTransactionOutputSql::new(tx_id, true, Utc::now().naive_utc(), Some(1))
+        PendingTransactionOutputSql::new(tx_id, true, Utc::now().naive_utc(), Some(1), 0)
             .commit(&conn)
             .unwrap();
 
-        PendingTransactionOutputSql::new(11u64, true, Utc::now().naive_utc(), Some(2))
+        PendingTransactionOutputSql::new(11u64, true, Utc::now().naive_utc(), Some(2), 0)
             .commit(&conn)
             .unwrap();
 
@@ -1425,7 +1696,7 @@ mod test {
 
         assert_eq!(pts.len(), 2);
 
-        let _updated1 = OutputSql::find(&outputs[0].spending_key, &conn)
+        let _updated1 = OutputSql::find(outputs[0].commitment.as_ref().unwrap(), &conn)
             .unwrap()
             .update(
                 UpdateOutput {
@@ -1437,7 +1708,7 @@ mod test {
             )
             .unwrap();
 
-        let _updated2 = OutputSql::find(&outputs[1].spending_key, &conn)
+        let _updated2 = OutputSql::find(outputs[1].commitment.as_ref().unwrap(), &conn)
             .unwrap()
             .update(
                 UpdateOutput {
@@ -1458,6 +1729,7 @@ mod test {
             true,
             Utc::now().naive_utc() - ChronoDuration::from_std(Duration::from_millis(600_000)).unwrap(),
             Some(3),
+            0,
         )
         .commit(&conn)
         .unwrap();
@@ -1472,7 +1744,7 @@ mod test {
         .unwrap();
         assert_eq!(pending_older2.len(), 1);
 
-        PendingTransactionOutputSql::new(13u64, true, Utc::now().naive_utc(), None)
+        PendingTransactionOutputSql::new(13u64, true, Utc::now().naive_utc(), None, 0)
             .commit(&conn)
             .unwrap();
 
@@ -1552,7 +1824,7 @@ mod test {
         let cipher = Aes256Gcm::new(key);
 
         output.commit(&conn).unwrap();
-        let unencrypted_output = OutputSql::find(output.spending_key.as_slice(), &conn).unwrap();
+        let unencrypted_output = OutputSql::find(output.commitment.as_ref().unwrap(), &conn).unwrap();
 
         assert!(unencrypted_output.clone().decrypt(&cipher).is_err());
         unencrypted_output.delete(&conn).unwrap();
@@ -1573,7 +1845,7 @@ mod test {
         decrypted_output.update_encryption(&conn).unwrap();
 
         assert_eq!(
-            OutputSql::find(output.spending_key.as_slice(), &conn)
+            OutputSql::find(output.commitment.as_ref().unwrap(), &conn)
                 .unwrap()
                 .spending_key,
             output.spending_key
@@ -1,3 +1,19 @@
+table! {
+    account_transactions (tx_id) {
+        tx_id -> BigInt,
+        account_id -> BigInt,
+    }
+}
+
+table! {
+    accounts (id) {
+        id -> BigInt,
+        name -> Text,
+        key_index -> BigInt,
+        active -> Integer,
+    }
+}
+
 table! {
     client_key_values (key) {
         key -> Text,
@@ -47,6 +63,15 @@ table! {
     }
 }
 
+table! {
+    kernel_signing_records (request_hash) {
+        request_hash -> Binary,
+        nonce_salt -> Binary,
+        challenge -> Nullable<Binary>,
+        timestamp -> Timestamp,
+    }
+}
+
 table! {
     key_manager_states (id) {
         id -> Nullable<BigInt>,
@@ -57,6 +82,17 @@ table! {
     }
 }
 
+table! {
+    outbound_saf_messages (message_tag) {
+        message_tag -> BigInt,
+        tx_id -> BigInt,
+        destination_public_key -> Binary,
+        storer_node_id -> Binary,
+        sent_at -> Timestamp,
+        delivered_at -> Nullable<Timestamp>,
+    }
+}
+
 table! {
     outbound_transactions (tx_id) {
         tx_id -> BigInt,
@@ -84,6 +120,9 @@ table! {
         status -> Integer,
         tx_id -> Nullable<BigInt>,
         hash -> Nullable<Binary>,
+        account_id -> BigInt,
+        features_version -> Integer,
+        key_id -> Nullable<Binary>,
     }
 }
 
@@ -93,6 +132,7 @@ table! {
         short_term -> Integer,
         timestamp -> Timestamp,
         coinbase_block_height -> Nullable<BigInt>,
+        account_id -> BigInt,
     }
 }
 
@@ -103,14 +143,29 @@ table! {
     }
 }
 
+table! {
+    watched_outputs (commitment) {
+        commitment -> Binary,
+        hash -> Nullable<Binary>,
+        value -> Nullable<BigInt>,
+        mined_height -> Nullable<BigInt>,
+        spent -> Integer,
+    }
+}
+
 allow_tables_to_appear_in_same_query!(
+    account_transactions,
+    accounts,
     client_key_values,
     completed_transactions,
     contacts,
     inbound_transactions,
+    kernel_signing_records,
     key_manager_states,
+    outbound_saf_messages,
     outbound_transactions,
     outputs,
     pending_transaction_outputs,
     wallet_settings,
+    watched_outputs,
 );
@@ -20,7 +20,10 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use crate::error::WalletStorageError;
+use crate::{
+    error::WalletStorageError,
+    watch_only::{WatchOnlyKeys, WatchedOutput},
+};
 use aes_gcm::Aes256Gcm;
 use log::*;
 use std::{
@@ -34,6 +37,7 @@ use tari_comms::{
     tor::TorIdentity,
     types::{CommsPublicKey, CommsSecretKey},
 };
+use tari_crypto::tari_utilities::hex::Hex;
 
 const LOG_TARGET: &str = "wallet::database";
 
@@ -59,6 +63,10 @@ pub enum DbKey {
     TorId,
     BaseNodeChainMetadata,
     ClientKey(String),
+    WatchOnlyKeys,
+    WatchOnlyScannedHeight,
+    WatchOnlyScannedHeaderHash,
+    WatchedOutputs,
 }
 
 pub enum DbValue {
@@ -71,6 +79,10 @@ pub enum DbValue {
     ClientValue(String),
     ValueCleared,
     BaseNodeChainMetadata(ChainMetadata),
+    WatchOnlyKeys(Box<WatchOnlyKeys>),
+    WatchOnlyScannedHeight(u64),
+    WatchOnlyScannedHeaderHash(Vec<u8>),
+    WatchedOutputs(Vec<WatchedOutput>),
 }
 
 #[derive(Clone)]
@@ -80,6 +92,10 @@ pub enum DbKeyValuePair {
     Identity(Box<NodeIdentity>),
     TorId(TorIdentity),
     BaseNodeChainMetadata(ChainMetadata),
+    WatchOnlyKeys(Box<WatchOnlyKeys>),
+    WatchOnlyScannedHeight(u64),
+    WatchOnlyScannedHeaderHash(Vec<u8>),
+    WatchedOutput(Box<WatchedOutput>),
 }
 
 pub enum WriteOperation {
@@ -174,6 +190,111 @@ where T: WalletBackend + 'static
         Ok(())
     }
 
+    pub async fn get_watch_only_keys(&self) -> Result<Option<WatchOnlyKeys>, WalletStorageError> {
+        let db_clone = self.db.clone();
+
+        let c = tokio::task::spawn_blocking(move || match db_clone.fetch(&DbKey::WatchOnlyKeys) {
+            Ok(None) => Ok(None),
+            Ok(Some(DbValue::WatchOnlyKeys(keys))) => Ok(Some(*keys)),
+            Ok(Some(other)) => unexpected_result(DbKey::WatchOnlyKeys, other),
+            Err(e) => log_error(DbKey::WatchOnlyKeys, e),
+        })
+        .await
+        .map_err(|err| WalletStorageError::BlockingTaskSpawnError(err.to_string()))??;
+        Ok(c)
+    }
+
+    pub async fn set_watch_only_keys(&self, keys: WatchOnlyKeys) -> Result<(), WalletStorageError> {
+        let db_clone = self.db.clone();
+
+        tokio::task::spawn_blocking(move || {
+            db_clone.write(WriteOperation::Insert(DbKeyValuePair::WatchOnlyKeys(Box::new(keys))))
+        })
+        .await
+        .map_err(|err| WalletStorageError::BlockingTaskSpawnError(err.to_string()))??;
+        Ok(())
+    }
+
+    pub async fn get_watch_only_scanned_height(&self) -> Result<Option<u64>, WalletStorageError> {
+        let db_clone = self.db.clone();
+
+        let c = tokio::task::spawn_blocking(move || match db_clone.fetch(&DbKey::WatchOnlyScannedHeight) {
+            Ok(None) => Ok(None),
+            Ok(Some(DbValue::WatchOnlyScannedHeight(height))) => Ok(Some(height)),
+            Ok(Some(other)) => unexpected_result(DbKey::WatchOnlyScannedHeight, other),
+            Err(e) => log_error(DbKey::WatchOnlyScannedHeight, e),
+        })
+        .await
+        .map_err(|err| WalletStorageError::BlockingTaskSpawnError(err.to_string()))??;
+        Ok(c)
+    }
+
+    /// Set the height of the last block scanned by the watch-only scanner, `None` if no block has been scanned
+    pub async fn set_watch_only_scanned_height(&self, height: Option<u64>) -> Result<(), WalletStorageError> {
+        let db_clone = self.db.clone();
+
+        tokio::task::spawn_blocking(move || match height {
+            Some(height) => db_clone.write(WriteOperation::Insert(DbKeyValuePair::WatchOnlyScannedHeight(height))),
+            None => db_clone.write(WriteOperation::Remove(DbKey::WatchOnlyScannedHeight)),
+        })
+        .await
+        .map_err(|err| WalletStorageError::BlockingTaskSpawnError(err.to_string()))??;
+        Ok(())
+    }
+
+    pub async fn get_watch_only_scanned_header_hash(&self) -> Result<Option<Vec<u8>>, WalletStorageError> {
+        let db_clone = self.db.clone();
+
+        let c = tokio::task::spawn_blocking(move || match db_clone.fetch(&DbKey::WatchOnlyScannedHeaderHash) {
+            Ok(None) => Ok(None),
+            Ok(Some(DbValue::WatchOnlyScannedHeaderHash(hash))) => Ok(Some(hash)),
+            Ok(Some(other)) => unexpected_result(DbKey::WatchOnlyScannedHeaderHash, other),
+            Err(e) => log_error(DbKey::WatchOnlyScannedHeaderHash, e),
+        })
+        .await
+        .map_err(|err| WalletStorageError::BlockingTaskSpawnError(err.to_string()))??;
+        Ok(c)
+    }
+
+    /// Set the header hash of the last block scanned by the watch-only scanner, `None` if it is not known
+    pub async fn set_watch_only_scanned_header_hash(&self, hash: Option<Vec<u8>>) -> Result<(), WalletStorageError> {
+        let db_clone = self.db.clone();
+
+        tokio::task::spawn_blocking(move || match hash {
+            Some(hash) => db_clone.write(WriteOperation::Insert(DbKeyValuePair::WatchOnlyScannedHeaderHash(hash))),
+            None => db_clone.write(WriteOperation::Remove(DbKey::WatchOnlyScannedHeaderHash)),
+        })
+        .await
+        .map_err(|err| WalletStorageError::BlockingTaskSpawnError(err.to_string()))??;
+        Ok(())
+    }
+
+    pub async fn get_watched_outputs(&self) -> Result<Vec<WatchedOutput>, WalletStorageError> {
+        let db_clone = self.db.clone();
+
+        let c = tokio::task::spawn_blocking(move || match db_clone.fetch(&DbKey::WatchedOutputs) {
+            Ok(None) => Ok(Vec::new()),
+            Ok(Some(DbValue::WatchedOutputs(outputs))) => Ok(outputs),
+            Ok(Some(other)) => unexpected_result(DbKey::WatchedOutputs, other),
+            Err(e) => log_error(DbKey::WatchedOutputs, e),
+        })
+        .await
+        .map_err(|err| WalletStorageError::BlockingTaskSpawnError(err.to_string()))??;
+        Ok(c)
+    }
+
+    /// Insert a watched output or replace the stored output with the same commitment
+    pub async fn set_watched_output(&self, output: WatchedOutput) -> Result<(), WalletStorageError> {
+        let db_clone = self.db.clone();
+
+        tokio::task::spawn_blocking(move || {
+            db_clone.write(WriteOperation::Insert(DbKeyValuePair::WatchedOutput(Box::new(output))))
+        })
+        .await
+        .map_err(|err| WalletStorageError::BlockingTaskSpawnError(err.to_string()))??;
+        Ok(())
+    }
+
     pub async fn clear_comms_secret_key(&self) -> Result<(), WalletStorageError> {
         let db_clone = self.db.clone();
         tokio::task::spawn_blocking(move || db_clone.write(WriteOperation::Remove(DbKey::CommsSecretKey)))
@@ -251,6 +372,10 @@ impl Display for DbKey {
             DbKey::TorId => f.write_str(&"TorId".to_string()),
             DbKey::ClientKey(k) => f.write_str(&format!("ClientKey: {:?}", k)),
             DbKey::BaseNodeChainMetadata => f.write_str(&"Last seen Chain metadata from base node".to_string()),
+            DbKey::WatchOnlyKeys => f.write_str(&"Watch-only keys".to_string()),
+            DbKey::WatchOnlyScannedHeight => f.write_str(&"Watch-only scanned height".to_string()),
+            DbKey::WatchOnlyScannedHeaderHash => f.write_str(&"Watch-only scanned header hash".to_string()),
+            DbKey::WatchedOutputs => f.write_str(&"Watched outputs".to_string()),
         }
     }
 }
@@ -267,6 +392,12 @@ impl Display for DbValue {
             DbValue::TorId(v) => f.write_str(&format!("Tor ID: {}", v)),
             DbValue::Identity(v) => f.write_str(&format!("Node Identity: {}", v)),
             DbValue::BaseNodeChainMetadata(v) => f.write_str(&format!("Last seen Chain metadata from base node:{}", v)),
+            DbValue::WatchOnlyKeys(_) => f.write_str(&"Watch-only keys".to_string()),
+            DbValue::WatchOnlyScannedHeight(v) => f.write_str(&format!("Watch-only scanned height: {}", v)),
+            DbValue::WatchOnlyScannedHeaderHash(v) => {
+                f.write_str(&format!("Watch-only scanned header hash: {}", v.to_hex()))
+            },
+            DbValue::WatchedOutputs(v) => f.write_str(&format!("Watched outputs: {}", v.len())),
         }
     }
 }
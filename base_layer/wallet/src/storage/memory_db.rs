@@ -23,6 +23,7 @@
 use crate::{
     error::WalletStorageError,
     storage::database::{DbKey, DbKeyValuePair, DbValue, WalletBackend, WriteOperation},
+    watch_only::{WatchOnlyKeys, WatchedOutput},
 };
 use aes_gcm::Aes256Gcm;
 use std::{
@@ -47,6 +48,10 @@ pub struct InnerDatabase {
     identity: Option<NodeIdentity>,
     tor_id: Option<TorIdentity>,
     chain_metadata: Option<ChainMetadata>,
+    watch_only_keys: Option<WatchOnlyKeys>,
+    watch_only_scanned_height: Option<u64>,
+    watch_only_scanned_header_hash: Option<Vec<u8>>,
+    watched_outputs: Vec<WatchedOutput>,
 }
 
 impl InnerDatabase {
@@ -59,6 +64,10 @@ impl InnerDatabase {
             identity: None,
             tor_id: None,
             chain_metadata: None,
+            watch_only_keys: None,
+            watch_only_scanned_height: None,
+            watch_only_scanned_header_hash: None,
+            watched_outputs: Vec::new(),
         }
     }
 }
@@ -97,6 +106,13 @@ impl WalletBackend for WalletMemoryDatabase {
             DbKey::Identity => db.identity.clone().map(DbValue::Identity),
             DbKey::TorId => db.tor_id.clone().map(DbValue::TorId),
             DbKey::BaseNodeChainMetadata => db.chain_metadata.clone().map(DbValue::BaseNodeChainMetadata),
+            DbKey::WatchOnlyKeys => db.watch_only_keys.clone().map(|k| DbValue::WatchOnlyKeys(Box::new(k))),
+            DbKey::WatchOnlyScannedHeight => db.watch_only_scanned_height.map(DbValue::WatchOnlyScannedHeight),
+            DbKey::WatchOnlyScannedHeaderHash => db
+                .watch_only_scanned_header_hash
+                .clone()
+                .map(DbValue::WatchOnlyScannedHeaderHash),
+            DbKey::WatchedOutputs => Some(DbValue::WatchedOutputs(db.watched_outputs.clone())),
         };
 
         Ok(result)
@@ -121,6 +137,25 @@ impl WalletBackend for WalletMemoryDatabase {
                 DbKeyValuePair::BaseNodeChainMetadata(metadata) => {
                     db.chain_metadata = Some(metadata);
                 },
+                DbKeyValuePair::WatchOnlyKeys(keys) => {
+                    db.watch_only_keys = Some(*keys);
+                },
+                DbKeyValuePair::WatchOnlyScannedHeight(height) => {
+                    db.watch_only_scanned_height = Some(height);
+                },
+                DbKeyValuePair::WatchOnlyScannedHeaderHash(hash) => {
+                    db.watch_only_scanned_header_hash = Some(hash);
+                },
+                DbKeyValuePair::WatchedOutput(output) => {
+                    match db
+                        .watched_outputs
+                        .iter_mut()
+                        .find(|o| o.commitment == output.commitment)
+                    {
+                        Some(existing) => *existing = *output,
+                        None => db.watched_outputs.push(*output),
+                    }
+                },
             },
             WriteOperation::Remove(k) => match k {
                 DbKey::CommsSecretKey => {
@@ -151,6 +186,18 @@ impl WalletBackend for WalletMemoryDatabase {
                 DbKey::TorId => {
                     db.tor_id = None;
                 },
+                DbKey::WatchOnlyScannedHeight => {
+                    db.watch_only_scanned_height = None;
+                },
+                DbKey::WatchOnlyScannedHeaderHash => {
+                    db.watch_only_scanned_header_hash = None;
+                },
+                DbKey::WatchOnlyKeys => {
+                    return Err(WalletStorageError::OperationNotSupported);
+                },
+                DbKey::WatchedOutputs => {
+                    return Err(WalletStorageError::OperationNotSupported);
+                },
             },
         }
 
@@ -22,12 +22,13 @@
 
 use crate::{
     error::WalletStorageError,
-    schema::{client_key_values, wallet_settings},
+    schema::{client_key_values, wallet_settings, watched_outputs},
     storage::{
         database::{DbKey, DbKeyValuePair, DbValue, WalletBackend, WriteOperation},
         sqlite_utilities::WalletDbConnection,
     },
     util::encryption::{decrypt_bytes_integral_nonce, encrypt_bytes_integral_nonce, Encryptable, AES_NONCE_BYTES},
+    watch_only::{WatchOnlyKeys, WatchedOutput},
 };
 use aes_gcm::{
     aead::{generic_array::GenericArray, Aead},
@@ -37,6 +38,7 @@ use aes_gcm::{
 use diesel::{prelude::*, SqliteConnection};
 use log::*;
 use std::{
+    convert::TryFrom,
     str::{from_utf8, FromStr},
     sync::{Arc, RwLock},
 };
@@ -47,6 +49,7 @@ use tari_comms::{
     tor::TorIdentity,
     types::{CommsPublicKey, CommsSecretKey},
 };
+use tari_core::transactions::{tari_amount::MicroTari, types::Commitment};
 use tari_crypto::{
     keys::PublicKey,
     tari_utilities::{
@@ -324,6 +327,43 @@ impl WalletSqliteDatabase {
             Ok(None)
         }
     }
+
+    fn set_watch_only_keys(&self, keys: WatchOnlyKeys, conn: &SqliteConnection) -> Result<(), WalletStorageError> {
+        let bytes = bincode::serialize(&keys).map_err(|e| WalletStorageError::ConversionError(e.to_string()))?;
+        WalletSettingSql::new(DbKey::WatchOnlyKeys.to_string(), bytes.to_hex()).set(&conn)?;
+        Ok(())
+    }
+
+    fn get_watch_only_keys(&self, conn: &SqliteConnection) -> Result<Option<WatchOnlyKeys>, WalletStorageError> {
+        if let Some(key_str) = WalletSettingSql::get(DbKey::WatchOnlyKeys.to_string(), &conn)? {
+            let keys = bincode::deserialize(&from_hex(&key_str)?)
+                .map_err(|e| WalletStorageError::ConversionError(e.to_string()))?;
+            Ok(Some(keys))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn get_watch_only_scanned_height(&self, conn: &SqliteConnection) -> Result<Option<u64>, WalletStorageError> {
+        if let Some(key_str) = WalletSettingSql::get(DbKey::WatchOnlyScannedHeight.to_string(), &conn)? {
+            let height = u64::from_str(&key_str).map_err(|e| WalletStorageError::ConversionError(e.to_string()))?;
+            Ok(Some(height))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn get_watch_only_scanned_header_hash(
+        &self,
+        conn: &SqliteConnection,
+    ) -> Result<Option<Vec<u8>>, WalletStorageError>
+    {
+        if let Some(key_str) = WalletSettingSql::get(DbKey::WatchOnlyScannedHeaderHash.to_string(), &conn)? {
+            Ok(Some(from_hex(&key_str)?))
+        } else {
+            Ok(None)
+        }
+    }
 }
 
 impl WalletBackend for WalletSqliteDatabase {
@@ -368,6 +408,21 @@ impl WalletBackend for WalletSqliteDatabase {
             DbKey::TorId => self.get_tor_id(&conn)?,
             DbKey::CommsFeatures => self.get_comms_features(&conn)?.map(DbValue::CommsFeatures),
             DbKey::BaseNodeChainMetadata => self.get_chain_metadata(&conn)?.map(DbValue::BaseNodeChainMetadata),
+            DbKey::WatchOnlyKeys => self
+                .get_watch_only_keys(&conn)?
+                .map(|k| DbValue::WatchOnlyKeys(Box::new(k))),
+            DbKey::WatchOnlyScannedHeight => self
+                .get_watch_only_scanned_height(&conn)?
+                .map(DbValue::WatchOnlyScannedHeight),
+            DbKey::WatchOnlyScannedHeaderHash => self
+                .get_watch_only_scanned_header_hash(&conn)?
+                .map(DbValue::WatchOnlyScannedHeaderHash),
+            DbKey::WatchedOutputs => Some(DbValue::WatchedOutputs(
+                WatchedOutputSql::index(&conn)?
+                    .into_iter()
+                    .map(WatchedOutput::try_from)
+                    .collect::<Result<Vec<_>, _>>()?,
+            )),
         };
 
         Ok(result)
@@ -389,6 +444,18 @@ impl WalletBackend for WalletSqliteDatabase {
                 DbKeyValuePair::BaseNodeChainMetadata(metadata) => {
                     self.set_chain_metadata(metadata, &(*conn))?;
                 },
+                DbKeyValuePair::WatchOnlyKeys(keys) => {
+                    self.set_watch_only_keys(*keys, &(*conn))?;
+                },
+                DbKeyValuePair::WatchOnlyScannedHeight(height) => {
+                    WalletSettingSql::new(DbKey::WatchOnlyScannedHeight.to_string(), height.to_string()).set(&conn)?;
+                },
+                DbKeyValuePair::WatchOnlyScannedHeaderHash(hash) => {
+                    WalletSettingSql::new(DbKey::WatchOnlyScannedHeaderHash.to_string(), hash.to_hex()).set(&conn)?;
+                },
+                DbKeyValuePair::WatchedOutput(output) => {
+                    WatchedOutputSql::from(*output).set(&conn)?;
+                },
                 DbKeyValuePair::ClientKeyValue(k, v) => {
                     // First see if we will overwrite a value so we can return the old value
                     let value_to_return = if let Some(mut found_value) = ClientKeyValueSql::get(&k, &conn)? {
@@ -431,6 +498,18 @@ impl WalletBackend for WalletSqliteDatabase {
                 DbKey::TorId => {
                     let _ = WalletSettingSql::clear(DbKey::TorId.to_string(), &conn)?;
                 },
+                DbKey::WatchOnlyScannedHeight => {
+                    let _ = WalletSettingSql::clear(DbKey::WatchOnlyScannedHeight.to_string(), &conn)?;
+                },
+                DbKey::WatchOnlyScannedHeaderHash => {
+                    let _ = WalletSettingSql::clear(DbKey::WatchOnlyScannedHeaderHash.to_string(), &conn)?;
+                },
+                DbKey::WatchOnlyKeys => {
+                    return Err(WalletStorageError::OperationNotSupported);
+                },
+                DbKey::WatchedOutputs => {
+                    return Err(WalletStorageError::OperationNotSupported);
+                },
             },
         }
 
@@ -606,6 +685,58 @@ impl ClientKeyValueSql {
     }
 }
 
+/// A Sql version of an output tracked by a watch-only wallet
+#[derive(Clone, Debug, Queryable, Insertable, PartialEq)]
+#[table_name = "watched_outputs"]
+struct WatchedOutputSql {
+    commitment: Vec<u8>,
+    hash: Option<Vec<u8>>,
+    value: Option<i64>,
+    mined_height: Option<i64>,
+    spent: i32,
+}
+
+impl WatchedOutputSql {
+    pub fn index(conn: &SqliteConnection) -> Result<Vec<Self>, WalletStorageError> {
+        Ok(watched_outputs::table.load::<WatchedOutputSql>(conn)?)
+    }
+
+    pub fn set(&self, conn: &SqliteConnection) -> Result<(), WalletStorageError> {
+        diesel::replace_into(watched_outputs::table)
+            .values(self)
+            .execute(conn)?;
+
+        Ok(())
+    }
+}
+
+impl From<WatchedOutput> for WatchedOutputSql {
+    fn from(o: WatchedOutput) -> Self {
+        Self {
+            commitment: o.commitment.to_vec(),
+            hash: o.hash,
+            value: o.value.map(|v| u64::from(v) as i64),
+            mined_height: o.mined_height.map(|h| h as i64),
+            spent: o.spent as i32,
+        }
+    }
+}
+
+impl TryFrom<WatchedOutputSql> for WatchedOutput {
+    type Error = WalletStorageError;
+
+    fn try_from(o: WatchedOutputSql) -> Result<Self, Self::Error> {
+        Ok(Self {
+            commitment: Commitment::from_vec(&o.commitment)
+                .map_err(|e| WalletStorageError::ConversionError(e.to_string()))?,
+            hash: o.hash,
+            value: o.value.map(|v| MicroTari::from(v as u64)),
+            mined_height: o.mined_height.map(|h| h as u64),
+            spent: o.spent != 0,
+        })
+    }
+}
+
 impl Encryptable<Aes256Gcm> for ClientKeyValueSql {
     #[allow(unused_assignments)]
     fn encrypt(&mut self, cipher: &Aes256Gcm) -> Result<(), AeadError> {
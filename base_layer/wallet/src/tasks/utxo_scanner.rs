@@ -0,0 +1,568 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::{
+    base_node_service::handle::{BaseNodeEvent, BaseNodeEventReceiver},
+    error::WalletError,
+    output_manager_service::{
+        error::{OutputManagerError, OutputManagerStorageError},
+        handle::OutputManagerHandle,
+        TxId,
+    },
+    storage::database::{WalletBackend, WalletDatabase},
+    transaction_service::handle::TransactionServiceHandle,
+    types::DEFAULT_FEE_PER_GRAM,
+};
+use futures::{pin_mut, stream::Fuse, FutureExt, StreamExt};
+use log::*;
+use std::{cmp, convert::TryFrom, sync::Arc};
+use tari_comms::{
+    connectivity::ConnectivityRequester,
+    peer_manager::{NodeId, NodeIdentity},
+};
+use tari_core::{
+    base_node::rpc::BaseNodeWalletRpcClient,
+    transactions::{
+        tari_amount::MicroTari,
+        transaction::{TransactionKernel, TransactionOutput, UnblindedOutput},
+        transaction_protocol::one_sided::OneSidedPaymentKeys,
+        types::{CryptoFactories, PublicKey},
+    },
+};
+use tari_crypto::{
+    keys::PublicKey as PublicKeyTrait,
+    tari_utilities::hex::{from_hex, to_hex},
+};
+use tari_shutdown::ShutdownSignal;
+
+const LOG_TARGET: &str = "wallet::utxo_scanner";
+
+/// Client key-value store key holding the height of the last block scanned for outputs belonging to this wallet
+pub const UTXO_SCANNER_HEIGHT_KEY: &str = "utxo_scanner_height";
+/// Client key-value store key holding the hex encoded header hash of the last block scanned
+pub const UTXO_SCANNER_HEADER_HASH_KEY: &str = "utxo_scanner_header_hash";
+/// The number of blocks below the fork point candidate that are scanned again when the chain has been reorged
+const REORG_RESCAN_DEPTH: u64 = 10;
+
+/// Continuously scans new blocks on the wallet's base node for outputs that can be rewound with the wallet's rewind
+/// keys, such as outputs sent to this wallet without the interactive transaction protocol completing, and for one-sided
+/// payments made to the wallet's public key. Any such outputs that are not already known to the Output Manager are
+/// imported and recorded as received transactions.
+///
+/// A cheap value-only rewind using the wallet's public rewind keys is used to filter outputs before the full rewind is
+/// requested from the Output Manager. The height and header hash of the last scanned block are persisted in the wallet
+/// database so that blocks mined while the wallet was offline are scanned on the next start. If the base node no longer
+/// has that block in its main chain, the chain has been reorged and the last `REORG_RESCAN_DEPTH` blocks are scanned
+/// again. On the very first run scanning starts at the current tip; historic outputs are found by the wallet recovery
+/// task instead.
+///
+/// The commitment key of a one-sided payment is also known to its sender, so each one-sided payment is claimed into an
+/// output with a key held by this wallet's signer once it is mature. Pending claims are kept in memory and the
+/// persisted block is held before the block of the earliest pending claim, so that the claim is found again after a
+/// restart.
+pub struct UtxoScannerTask<T>
+where T: WalletBackend + 'static
+{
+    db: WalletDatabase<T>,
+    connectivity: ConnectivityRequester,
+    output_manager_service: OutputManagerHandle,
+    transaction_service: TransactionServiceHandle,
+    base_node_events: Fuse<BaseNodeEventReceiver>,
+    factories: CryptoFactories,
+    node_identity: Arc<NodeIdentity>,
+    base_node: Option<NodeId>,
+    last_scanned: Option<ScannedBlock>,
+    pending_claims: Vec<PendingClaim>,
+    shutdown_signal: ShutdownSignal,
+}
+
+/// The height and header hash of a block that has been scanned
+#[derive(Debug, Clone, PartialEq)]
+struct ScannedBlock {
+    height: u64,
+    header_hash: Vec<u8>,
+}
+
+/// A one-sided payment that still has to be claimed into an output with a key held by this wallet's signer
+struct PendingClaim {
+    output: UnblindedOutput,
+    height: u64,
+    /// The block scanned before the block the payment was found in
+    previous_block: ScannedBlock,
+}
+
+impl<T> UtxoScannerTask<T>
+where T: WalletBackend + 'static
+{
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        db: WalletDatabase<T>,
+        connectivity: ConnectivityRequester,
+        output_manager_service: OutputManagerHandle,
+        transaction_service: TransactionServiceHandle,
+        base_node_events: Fuse<BaseNodeEventReceiver>,
+        factories: CryptoFactories,
+        node_identity: Arc<NodeIdentity>,
+        shutdown_signal: ShutdownSignal,
+    ) -> Self
+    {
+        Self {
+            db,
+            connectivity,
+            output_manager_service,
+            transaction_service,
+            base_node_events,
+            factories,
+            node_identity,
+            base_node: None,
+            last_scanned: None,
+            pending_claims: Vec::new(),
+            shutdown_signal,
+        }
+    }
+
+    pub async fn run(mut self) {
+        let mut shutdown = self.shutdown_signal.clone();
+        info!(target: LOG_TARGET, "UTXO scanner starting");
+        loop {
+            futures::select! {
+                event = self.base_node_events.select_next_some() => {
+                    match event {
+                        Ok(event) => self.handle_base_node_event(&*event).await,
+                        Err(e) => trace!(target: LOG_TARGET, "Lagging read on base node event stream: {:?}", e),
+                    }
+                },
+                _ = shutdown => {
+                    info!(target: LOG_TARGET, "UTXO scanner shutting down because it received the shutdown signal");
+                    break;
+                },
+                complete => {
+                    info!(target: LOG_TARGET, "UTXO scanner shutting down because the event stream ended");
+                    break;
+                },
+            }
+        }
+    }
+
+    async fn handle_base_node_event(&mut self, event: &BaseNodeEvent) {
+        match event {
+            BaseNodeEvent::BaseNodePeerSet(peer) => {
+                self.base_node = Some(peer.node_id.clone());
+            },
+            BaseNodeEvent::BaseNodeState(state) => {
+                let peer = match self.base_node.clone() {
+                    Some(peer) => peer,
+                    None => return,
+                };
+                // Nothing to scan if the base node's tip is the last scanned block
+                if let (Some(metadata), Some(last_scanned)) =
+                    (state.chain_metadata.as_ref(), self.last_scanned.as_ref())
+                {
+                    if metadata.best_block() == &last_scanned.header_hash {
+                        return;
+                    }
+                }
+                match self.scan_to_tip(peer.clone()).await {
+                    Ok(()) | Err(WalletError::Shutdown) => {},
+                    Err(e) => warn!(
+                        target: LOG_TARGET,
+                        "Error scanning blocks from base node {}: {}", peer, e
+                    ),
+                }
+                if let Some(tip_height) = self.last_scanned.as_ref().map(|b| b.height) {
+                    self.submit_mature_claims(tip_height).await;
+                }
+            },
+        }
+    }
+
+    /// Scan all the blocks after the last scanned block up to and including the base node's tip.
+    async fn scan_to_tip(&mut self, peer: NodeId) -> Result<(), WalletError> {
+        let mut connection = self.connectivity.dial_peer(peer).await?;
+        let mut client = connection.connect_rpc::<BaseNodeWalletRpcClient>().await?;
+
+        let tip_info = client.get_tip_info().await?;
+        if !tip_info.is_synced {
+            debug!(target: LOG_TARGET, "Base node is not synced, not scanning for outputs");
+            return Ok(());
+        }
+        let (tip_height, tip_hash) = match tip_info
+            .metadata
+            .and_then(|m| m.height_of_longest_chain.zip(m.best_block))
+        {
+            Some(tip) => tip,
+            None => return Ok(()),
+        };
+
+        let last_scanned = match self.last_scanned.clone() {
+            Some(block) => block,
+            None => match get_scanned_block(&self.db).await? {
+                Some(block) => block,
+                None => {
+                    debug!(
+                        target: LOG_TARGET,
+                        "First run of the UTXO scanner, starting from the chain tip at height {}", tip_height
+                    );
+                    return self
+                        .set_last_scanned(ScannedBlock {
+                            height: tip_height,
+                            header_hash: tip_hash,
+                        })
+                        .await;
+                },
+            },
+        };
+        if last_scanned.height == tip_height && last_scanned.header_hash == tip_hash {
+            self.last_scanned = Some(last_scanned);
+            return Ok(());
+        }
+
+        // Compare the last scanned block to the base node's block at that height to detect a reorg
+        let is_on_main_chain = last_scanned.height <= tip_height &&
+            client.get_block_outputs(last_scanned.height).await?.header_hash == last_scanned.header_hash;
+        let mut previous_block = if is_on_main_chain {
+            last_scanned
+        } else {
+            let height = cmp::min(last_scanned.height, tip_height)
+                .saturating_sub(REORG_RESCAN_DEPTH)
+                .max(1) -
+                1;
+            warn!(
+                target: LOG_TARGET,
+                "Block {} at height {} is no longer in the main chain, scanning again from height {}",
+                to_hex(&last_scanned.header_hash),
+                last_scanned.height,
+                height + 1
+            );
+            self.pending_claims.retain(|claim| claim.height <= height);
+            ScannedBlock {
+                height,
+                header_hash: client.get_block_outputs(height).await?.header_hash,
+            }
+        };
+
+        let rewind_keys = self.output_manager_service.get_rewind_public_keys().await?;
+        let mut shutdown = self.shutdown_signal.clone();
+        let start_height = previous_block.height + 1;
+
+        for height in start_height..=tip_height {
+            let response = {
+                let request = client.get_block_outputs(height).fuse();
+                pin_mut!(request);
+                futures::select! {
+                    response = request => response?,
+                    _ = shutdown => return Err(WalletError::Shutdown),
+                }
+            };
+
+            let (candidates, others): (Vec<_>, Vec<_>) = response
+                .outputs
+                .into_iter()
+                .filter_map(|o| TransactionOutput::try_from(o).ok())
+                .partition(|o| {
+                    o.rewind_range_proof_value_only(
+                        &self.factories.range_proof,
+                        &rewind_keys.rewind_public_key,
+                        &rewind_keys.rewind_blinding_public_key,
+                    )
+                    .is_ok()
+                });
+
+            let recovered_outputs = if candidates.is_empty() {
+                Vec::new()
+            } else {
+                self.output_manager_service.recover_outputs(candidates).await?
+            };
+            let kernels = response
+                .kernels
+                .into_iter()
+                .filter_map(|k| TransactionKernel::try_from(k).ok())
+                .collect::<Vec<_>>();
+            for output in recovered_outputs {
+                self.record_import(output.value, height, &response.header_hash).await?;
+            }
+
+            for output in self.find_one_sided_payments(&others, &kernels) {
+                // A payment that is already known was found again after a restart and may still be unclaimed
+                self.import_output(output.clone(), height, &response.header_hash)
+                    .await?;
+                self.queue_claim(PendingClaim {
+                    output,
+                    height,
+                    previous_block: previous_block.clone(),
+                });
+            }
+
+            previous_block = ScannedBlock {
+                height,
+                header_hash: response.header_hash,
+            };
+            self.set_last_scanned(previous_block.clone()).await?;
+        }
+        trace!(
+            target: LOG_TARGET,
+            "Scanned blocks {} to {} for wallet outputs",
+            start_height,
+            tip_height
+        );
+
+        Ok(())
+    }
+
+    /// Find the outputs that were paid to this wallet's public key using a one-sided payment. The shared secret of a
+    /// one-sided payment is derived from the public nonce of the transaction's kernel, so the keys of every kernel
+    /// in the block are derived once. Each output is then matched to its kernel with a cheap value-only rewind
+    /// before a single full rewind is done.
+    fn find_one_sided_payments(
+        &self,
+        outputs: &[TransactionOutput],
+        kernels: &[TransactionKernel],
+    ) -> Vec<UnblindedOutput>
+    {
+        let kernel_keys = kernels
+            .iter()
+            .filter_map(|kernel| {
+                let public_nonce = kernel.excess_sig.get_public_nonce();
+                let keys = OneSidedPaymentKeys::for_recipient(self.node_identity.secret_key(), public_nonce).ok()?;
+                let rewind_public_key = PublicKey::from_secret_key(&keys.rewind_data.rewind_key);
+                let rewind_blinding_public_key = PublicKey::from_secret_key(&keys.rewind_data.rewind_blinding_key);
+                Some((keys, rewind_public_key, rewind_blinding_public_key))
+            })
+            .collect::<Vec<_>>();
+
+        let mut found = Vec::new();
+        for output in outputs {
+            let matched = kernel_keys
+                .iter()
+                .find(|(_, rewind_public_key, rewind_blinding_public_key)| {
+                    output
+                        .rewind_range_proof_value_only(
+                            &self.factories.range_proof,
+                            rewind_public_key,
+                            rewind_blinding_public_key,
+                        )
+                        .is_ok()
+                });
+            let keys = match matched {
+                Some((keys, _, _)) => keys,
+                None => continue,
+            };
+            let rewound = match output.full_rewind_range_proof(
+                &self.factories.range_proof,
+                &keys.rewind_data.rewind_key,
+                &keys.rewind_data.rewind_blinding_key,
+            ) {
+                Ok(rewound) if rewound.blinding_factor == keys.spending_key => rewound,
+                _ => continue,
+            };
+            found.push(UnblindedOutput::new(
+                rewound.committed_value,
+                keys.spending_key.clone(),
+                Some(output.features.clone()),
+            ));
+        }
+        found
+    }
+
+    fn queue_claim(&mut self, claim: PendingClaim) {
+        if self
+            .pending_claims
+            .iter()
+            .all(|c| c.output.spending_key != claim.output.spending_key)
+        {
+            self.pending_claims.push(claim);
+        }
+    }
+
+    /// Claim the one-sided payments that are mature at `tip_height`. Claims that fail because the payment has already
+    /// been spent, or because its value does not cover the fee, are dropped; any other failure is retried on the next
+    /// base node state update.
+    async fn submit_mature_claims(&mut self, tip_height: u64) {
+        let (mature, immature): (Vec<_>, Vec<_>) = self
+            .pending_claims
+            .drain(..)
+            .partition(|claim| claim.output.features.maturity <= tip_height);
+        self.pending_claims = immature;
+        if mature.is_empty() {
+            return;
+        }
+
+        for claim in mature {
+            let value = claim.output.value;
+            let result = self
+                .output_manager_service
+                .create_claim_transaction(claim.output.clone(), DEFAULT_FEE_PER_GRAM)
+                .await;
+            let (tx_id, fee, tx) = match result {
+                Ok(claim_tx) => claim_tx,
+                Err(OutputManagerError::OutputManagerStorageError(OutputManagerStorageError::ValuesNotFound)) |
+                Err(OutputManagerError::NotEnoughFunds) => {
+                    debug!(
+                        target: LOG_TARGET,
+                        "One-sided payment of value {} found in block {} cannot be claimed", value, claim.height
+                    );
+                    continue;
+                },
+                Err(e) => {
+                    warn!(target: LOG_TARGET, "Error creating one-sided payment claim: {}", e);
+                    self.pending_claims.push(claim);
+                    continue;
+                },
+            };
+            let amount = value - fee;
+            match self
+                .transaction_service
+                .submit_transaction(tx_id, tx, fee, amount, "Claim one-sided payment".to_string())
+                .await
+            {
+                Ok(()) => info!(
+                    target: LOG_TARGET,
+                    "Submitted claim of one-sided payment of value {} found in block {} (TxId: {})",
+                    value,
+                    claim.height,
+                    tx_id
+                ),
+                Err(e) => warn!(
+                    target: LOG_TARGET,
+                    "Error submitting one-sided payment claim (TxId: {}): {}", tx_id, e
+                ),
+            }
+        }
+
+        // Move the held back checkpoint on to the earliest claim that is still pending
+        if let Some(block) = self.last_scanned.clone() {
+            if let Err(e) = self.set_last_scanned(block).await {
+                warn!(target: LOG_TARGET, "Error updating the last scanned block: {}", e);
+            }
+        }
+    }
+
+    /// Record the last scanned block. The persisted block is held before the block of the earliest pending claim so
+    /// that the claim is found again if the wallet restarts before it is submitted.
+    async fn set_last_scanned(&mut self, block: ScannedBlock) -> Result<(), WalletError> {
+        let checkpoint = self
+            .pending_claims
+            .iter()
+            .map(|claim| &claim.previous_block)
+            .chain(Some(&block))
+            .min_by_key(|b| b.height)
+            .cloned()
+            .unwrap_or_else(|| block.clone());
+        self.last_scanned = Some(block);
+        set_scanned_block(&self.db, &checkpoint).await
+    }
+
+    async fn import_output(
+        &mut self,
+        output: UnblindedOutput,
+        height: u64,
+        header_hash: &[u8],
+    ) -> Result<Option<TxId>, WalletError>
+    {
+        let value = output.value;
+        match self.output_manager_service.add_output(output).await {
+            Ok(()) => {},
+            // Outputs created by this wallet through the interactive protocol are already known
+            Err(OutputManagerError::OutputManagerStorageError(OutputManagerStorageError::DuplicateOutput)) |
+            Err(OutputManagerError::DuplicateOutput) => return Ok(None),
+            Err(e) => return Err(e.into()),
+        }
+
+        self.record_import(value, height, header_hash).await.map(Some)
+    }
+
+    /// Record an output that was added to the Output Manager as a received transaction
+    async fn record_import(&mut self, value: MicroTari, height: u64, header_hash: &[u8]) -> Result<TxId, WalletError> {
+        let tx_id = self
+            .transaction_service
+            .import_utxo(
+                value,
+                self.node_identity.public_key().clone(),
+                format!("Detected in block {} ({})", height, to_hex(header_hash)),
+            )
+            .await?;
+        info!(
+            target: LOG_TARGET,
+            "Imported output of value {} found in block {} (TxId: {})", value, height, tx_id
+        );
+
+        Ok(tx_id)
+    }
+}
+
+async fn get_scanned_block<T: WalletBackend + 'static>(
+    db: &WalletDatabase<T>,
+) -> Result<Option<ScannedBlock>, WalletError> {
+    let height = match db.get_client_key_value(UTXO_SCANNER_HEIGHT_KEY.to_string()).await? {
+        Some(value) => value
+            .parse::<u64>()
+            .map_err(|e| WalletError::UtxoScannerError(format!("Invalid scanned height `{}`: {}", value, e)))?,
+        None => return Ok(None),
+    };
+    let header_hash = match db
+        .get_client_key_value(UTXO_SCANNER_HEADER_HASH_KEY.to_string())
+        .await?
+    {
+        Some(value) => from_hex(&value)
+            .map_err(|e| WalletError::UtxoScannerError(format!("Invalid scanned header hash `{}`: {}", value, e)))?,
+        None => return Ok(None),
+    };
+    Ok(Some(ScannedBlock { height, header_hash }))
+}
+
+async fn set_scanned_block<T: WalletBackend + 'static>(
+    db: &WalletDatabase<T>,
+    block: &ScannedBlock,
+) -> Result<(), WalletError>
+{
+    db.set_client_key_value(UTXO_SCANNER_HEIGHT_KEY.to_string(), block.height.to_string())
+        .await?;
+    db.set_client_key_value(UTXO_SCANNER_HEADER_HASH_KEY.to_string(), to_hex(&block.header_hash))
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::storage::memory_db::WalletMemoryDatabase;
+    use tokio::runtime::Runtime;
+
+    #[test]
+    fn test_scanned_block_checkpoint() {
+        let mut runtime = Runtime::new().unwrap();
+        let db = WalletDatabase::new(WalletMemoryDatabase::new());
+
+        assert_eq!(runtime.block_on(get_scanned_block(&db)).unwrap(), None);
+        let block = ScannedBlock {
+            height: 1234,
+            header_hash: vec![1, 2, 3, 4],
+        };
+        runtime.block_on(set_scanned_block(&db, &block)).unwrap();
+        assert_eq!(runtime.block_on(get_scanned_block(&db)).unwrap(), Some(block));
+
+        runtime
+            .block_on(db.set_client_key_value(UTXO_SCANNER_HEIGHT_KEY.to_string(), "not a height".to_string()))
+            .unwrap();
+        assert!(runtime.block_on(get_scanned_block(&db)).is_err());
+    }
+}
@@ -0,0 +1,386 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::{
+    contacts_service::storage::database::ContactsBackend,
+    error::WalletError,
+    output_manager_service::{storage::database::OutputManagerBackend, TxId},
+    storage::database::{WalletBackend, WalletDatabase},
+    transaction_service::{
+        error::{TransactionServiceError, TransactionStorageError},
+        storage::database::TransactionBackend,
+    },
+    wallet::Wallet,
+};
+use chrono::Utc;
+use futures::StreamExt;
+use log::*;
+use std::{
+    convert::TryFrom,
+    time::{Duration, Instant},
+};
+use tari_comms::{peer_manager::NodeId, protocol::rpc::RpcError, types::CommsPublicKey};
+use tari_core::{
+    base_node::sync::rpc::BaseNodeSyncRpcClient,
+    proto::base_node::SyncUtxosRequest,
+    transactions::{tari_amount::MicroTari, transaction::TransactionOutput},
+};
+use tari_crypto::tari_utilities::hash::Hashable;
+use tari_shutdown::ShutdownSignal;
+use tokio::{sync::broadcast, time::delay_for};
+
+const LOG_TARGET: &str = "wallet::recovery";
+
+/// Client key-value store key holding the MMR position of the next output to be scanned
+pub const RECOVERY_INDEX_KEY: &str = "recovery_index";
+/// Client key-value store key holding the number of outputs recovered so far
+pub const RECOVERY_NUM_UTXOS_KEY: &str = "recovery_num_utxos";
+/// Client key-value store key holding the total value of the outputs recovered so far, in MicroTari
+pub const RECOVERY_TOTAL_AMOUNT_KEY: &str = "recovery_total_amount";
+
+const DEFAULT_RETRY_LIMIT: usize = 3;
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum WalletRecoveryEvent {
+    ConnectingToBaseNode(NodeId),
+    ConnectedToBaseNode(NodeId, Duration),
+    /// Connecting to or streaming from a base node failed: peer, attempt number, retry limit, error
+    ConnectionFailedToBaseNode(NodeId, usize, usize, String),
+    /// Recovery progress: the number of outputs scanned and the total number of outputs to scan
+    Progress(u64, u64),
+    /// Recovery completed: the number of recovered outputs and their total value
+    Completed(u64, MicroTari),
+    RecoveryFailed(String),
+}
+
+/// Recovers wallet funds by streaming the transaction outputs stored in the blockchain from a base node and attempting
+/// to rewind them using the wallet's rewind keys. Any outputs that are successfully rewound are imported into the
+/// wallet.
+///
+/// The MMR position of the last scanned output is checkpointed in the wallet database after every batch so that an
+/// interrupted recovery resumes where it left off the next time the task is run. The recovered output count and value
+/// are checkpointed after every recovered output.
+pub struct WalletRecoveryTask<T, U, V, W>
+where
+    T: WalletBackend + 'static,
+    U: TransactionBackend + 'static,
+    V: OutputManagerBackend + 'static,
+    W: ContactsBackend + 'static,
+{
+    wallet: Wallet<T, U, V, W>,
+    peer_seeds: Vec<CommsPublicKey>,
+    retry_limit: usize,
+    event_sender: broadcast::Sender<WalletRecoveryEvent>,
+    shutdown_signal: ShutdownSignal,
+}
+
+impl<T, U, V, W> WalletRecoveryTask<T, U, V, W>
+where
+    T: WalletBackend + 'static,
+    U: TransactionBackend + 'static,
+    V: OutputManagerBackend + 'static,
+    W: ContactsBackend + 'static,
+{
+    /// Create a recovery task that will use the base nodes in `peer_seeds`, in order, to stream the outputs from.
+    pub fn new(wallet: Wallet<T, U, V, W>, peer_seeds: Vec<CommsPublicKey>) -> Self {
+        let (event_sender, _) = broadcast::channel(200);
+        let shutdown_signal = wallet.comms.shutdown_signal();
+        Self {
+            wallet,
+            peer_seeds,
+            retry_limit: DEFAULT_RETRY_LIMIT,
+            event_sender,
+            shutdown_signal,
+        }
+    }
+
+    /// Set the number of failed attempts, across all peer seeds, after which the recovery is abandoned.
+    pub fn with_retry_limit(mut self, retry_limit: usize) -> Self {
+        self.retry_limit = retry_limit;
+        self
+    }
+
+    pub fn get_event_receiver(&self) -> broadcast::Receiver<WalletRecoveryEvent> {
+        self.event_sender.subscribe()
+    }
+
+    /// Run the recovery to completion. The recovery checkpoint is cleared from the wallet database once all outputs
+    /// have been scanned.
+    pub async fn run(mut self) -> Result<(), WalletError> {
+        // Write the checkpoint before contacting any base node so that a recovery that fails on its first connection
+        // attempt is still reported as in progress, and resumed, the next time the wallet starts
+        let (index, num_utxos, total_amount) = get_recovery_progress(&self.wallet.db).await?;
+        set_recovery_progress(&self.wallet.db, index, num_utxos, total_amount).await?;
+
+        if self.peer_seeds.is_empty() {
+            let msg = "No base node peers were provided to recover from".to_string();
+            self.publish_event(WalletRecoveryEvent::RecoveryFailed(msg.clone()));
+            return Err(WalletError::WalletRecoveryError(msg));
+        }
+
+        let mut attempts = 0;
+        loop {
+            let peer = NodeId::from_public_key(&self.peer_seeds[attempts % self.peer_seeds.len()]);
+            match self.attempt_sync(peer.clone()).await {
+                Ok((num_utxos, total_amount)) => {
+                    clear_recovery_progress(&self.wallet.db).await?;
+                    info!(
+                        target: LOG_TARGET,
+                        "Recovery complete: {} outputs recovered with a total value of {}", num_utxos, total_amount
+                    );
+                    self.publish_event(WalletRecoveryEvent::Completed(num_utxos, total_amount));
+                    return Ok(());
+                },
+                Err(WalletError::Shutdown) => {
+                    info!(
+                        target: LOG_TARGET,
+                        "Wallet recovery shutting down because it received the shutdown signal"
+                    );
+                    return Err(WalletError::Shutdown);
+                },
+                Err(e) => {
+                    attempts += 1;
+                    warn!(
+                        target: LOG_TARGET,
+                        "Recovery attempt {} of {} from base node {} failed: {}", attempts, self.retry_limit, peer, e
+                    );
+                    self.publish_event(WalletRecoveryEvent::ConnectionFailedToBaseNode(
+                        peer,
+                        attempts,
+                        self.retry_limit,
+                        e.to_string(),
+                    ));
+                    if attempts >= self.retry_limit {
+                        self.publish_event(WalletRecoveryEvent::RecoveryFailed(e.to_string()));
+                        return Err(e);
+                    }
+                    delay_for(RETRY_DELAY).await;
+                },
+            }
+        }
+    }
+
+    async fn attempt_sync(&mut self, peer: NodeId) -> Result<(u64, MicroTari), WalletError> {
+        self.publish_event(WalletRecoveryEvent::ConnectingToBaseNode(peer.clone()));
+        let mut connection = self.wallet.comms.connectivity().dial_peer(peer.clone()).await?;
+        let mut client = connection.connect_rpc::<BaseNodeSyncRpcClient>().await?;
+
+        let latency = client.get_last_request_latency().await?;
+        self.publish_event(WalletRecoveryEvent::ConnectedToBaseNode(
+            peer,
+            latency.unwrap_or_default(),
+        ));
+
+        let chain_metadata = client.get_chain_metadata().await?;
+        let chain_height = chain_metadata.height_of_longest_chain.unwrap_or(0);
+        let end_header_hash = chain_metadata
+            .best_block
+            .ok_or_else(|| WalletError::WalletRecoveryError("Base node reported an empty chain".to_string()))?;
+        let end_header = client.get_header_by_height(chain_height).await?;
+        let total_outputs = end_header.output_mmr_size;
+
+        let (mut index, mut num_utxos, mut total_amount) = get_recovery_progress(&self.wallet.db).await?;
+        info!(
+            target: LOG_TARGET,
+            "Scanning outputs {} to {} up to chain height {}", index, total_outputs, chain_height
+        );
+        self.publish_event(WalletRecoveryEvent::Progress(index, total_outputs));
+
+        let request = SyncUtxosRequest {
+            start: index,
+            end_header_hash,
+        };
+        let mut output_stream = client.sync_utxos(request).await?.fuse();
+        let mut shutdown = self.shutdown_signal.clone();
+        let own_public_key = self.wallet.comms.node_identity().public_key().clone();
+
+        loop {
+            let response = futures::select! {
+                response = output_stream.next() => response,
+                _ = shutdown => return Err(WalletError::Shutdown),
+            };
+            let response = match response {
+                Some(response) => response.map_err(RpcError::from)?,
+                None => break,
+            };
+
+            let timer = Instant::now();
+            let batch_size = response.utxos.len() as u64;
+            let outputs = response
+                .utxos
+                .into_iter()
+                .filter_map(|utxo| utxo.output.and_then(|o| TransactionOutput::try_from(o).ok()))
+                .collect::<Vec<_>>();
+
+            // The transaction record is written before the output so that an output recovered by an earlier run that
+            // was interrupted always has a record. An output whose record was written but which was not stored yet is
+            // returned again and its import is retried under the same transaction id.
+            let recoverable_outputs = self
+                .wallet
+                .output_manager_service
+                .rewind_unknown_outputs(outputs)
+                .await?;
+            for output in recoverable_outputs {
+                let value = output.value;
+                let tx_id = recovered_output_tx_id(&output.output);
+                let result = self
+                    .wallet
+                    .transaction_service
+                    .import_utxo_with_id(
+                        tx_id,
+                        value,
+                        own_public_key.clone(),
+                        format!("Recovered on {}.", Utc::now().naive_utc()),
+                    )
+                    .await;
+                match result {
+                    Ok(_) => {},
+                    Err(TransactionServiceError::TransactionStorageError(TransactionStorageError::DuplicateOutput)) => {
+                        debug!(
+                            target: LOG_TARGET,
+                            "Recovered output already has transaction record {}", tx_id
+                        );
+                    },
+                    Err(e) => return Err(e.into()),
+                }
+                if self.wallet.output_manager_service.add_recovered_output(output).await? {
+                    num_utxos += 1;
+                    total_amount += value;
+                    set_recovery_progress(&self.wallet.db, index, num_utxos, total_amount).await?;
+                }
+            }
+
+            index += batch_size;
+            set_recovery_progress(&self.wallet.db, index, num_utxos, total_amount).await?;
+            trace!(
+                target: LOG_TARGET,
+                "Scanned {} outputs in {:.2?}, {} of {} scanned",
+                batch_size,
+                timer.elapsed(),
+                index,
+                total_outputs
+            );
+            self.publish_event(WalletRecoveryEvent::Progress(index, total_outputs));
+        }
+
+        Ok((num_utxos, total_amount))
+    }
+
+    fn publish_event(&self, event: WalletRecoveryEvent) {
+        trace!(target: LOG_TARGET, "Publishing event: {:?}", event);
+        let _ = self.event_sender.send(event).map_err(|_| {
+            trace!(
+                target: LOG_TARGET,
+                "Could not publish WalletRecoveryEvent as there are no subscribers"
+            )
+        });
+    }
+}
+
+/// The transaction id of the imported transaction recording a recovered output is derived from the output hash so that
+/// it is the same every time the output is recovered.
+fn recovered_output_tx_id(output: &TransactionOutput) -> TxId {
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&output.hash()[..8]);
+    u64::from_le_bytes(bytes)
+}
+
+/// Returns true if a recovery was started on this wallet database and has not yet completed.
+pub async fn is_recovery_in_progress<T: WalletBackend + 'static>(db: &WalletDatabase<T>) -> Result<bool, WalletError> {
+    Ok(db.get_client_key_value(RECOVERY_INDEX_KEY.to_string()).await?.is_some())
+}
+
+async fn get_recovery_progress<T: WalletBackend + 'static>(
+    db: &WalletDatabase<T>,
+) -> Result<(u64, u64, MicroTari), WalletError> {
+    let index = get_progress_value(db, RECOVERY_INDEX_KEY).await?;
+    let num_utxos = get_progress_value(db, RECOVERY_NUM_UTXOS_KEY).await?;
+    let total_amount = get_progress_value(db, RECOVERY_TOTAL_AMOUNT_KEY).await?;
+    Ok((index, num_utxos, MicroTari::from(total_amount)))
+}
+
+async fn get_progress_value<T: WalletBackend + 'static>(db: &WalletDatabase<T>, key: &str) -> Result<u64, WalletError> {
+    match db.get_client_key_value(key.to_string()).await? {
+        Some(value) => value
+            .parse::<u64>()
+            .map_err(|e| WalletError::WalletRecoveryError(format!("Invalid recovery value for `{}`: {}", key, e))),
+        None => Ok(0),
+    }
+}
+
+async fn set_recovery_progress<T: WalletBackend + 'static>(
+    db: &WalletDatabase<T>,
+    index: u64,
+    num_utxos: u64,
+    total_amount: MicroTari,
+) -> Result<(), WalletError>
+{
+    db.set_client_key_value(RECOVERY_NUM_UTXOS_KEY.to_string(), num_utxos.to_string())
+        .await?;
+    db.set_client_key_value(
+        RECOVERY_TOTAL_AMOUNT_KEY.to_string(),
+        u64::from(total_amount).to_string(),
+    )
+    .await?;
+    db.set_client_key_value(RECOVERY_INDEX_KEY.to_string(), index.to_string())
+        .await?;
+    Ok(())
+}
+
+async fn clear_recovery_progress<T: WalletBackend + 'static>(db: &WalletDatabase<T>) -> Result<(), WalletError> {
+    db.clear_client_value(RECOVERY_INDEX_KEY.to_string()).await?;
+    db.clear_client_value(RECOVERY_NUM_UTXOS_KEY.to_string()).await?;
+    db.clear_client_value(RECOVERY_TOTAL_AMOUNT_KEY.to_string()).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::storage::memory_db::WalletMemoryDatabase;
+    use tokio::runtime::Runtime;
+
+    #[test]
+    fn test_recovery_progress_checkpoint() {
+        let mut runtime = Runtime::new().unwrap();
+        let db = WalletDatabase::new(WalletMemoryDatabase::new());
+
+        assert!(!runtime.block_on(is_recovery_in_progress(&db)).unwrap());
+        let (index, num_utxos, total_amount) = runtime.block_on(get_recovery_progress(&db)).unwrap();
+        assert_eq!(index, 0);
+        assert_eq!(num_utxos, 0);
+        assert_eq!(total_amount, MicroTari::from(0));
+
+        runtime
+            .block_on(set_recovery_progress(&db, 250, 3, MicroTari::from(12345)))
+            .unwrap();
+        assert!(runtime.block_on(is_recovery_in_progress(&db)).unwrap());
+        let (index, num_utxos, total_amount) = runtime.block_on(get_recovery_progress(&db)).unwrap();
+        assert_eq!(index, 250);
+        assert_eq!(num_utxos, 3);
+        assert_eq!(total_amount, MicroTari::from(12345));
+
+        runtime.block_on(clear_recovery_progress(&db)).unwrap();
+        assert!(!runtime.block_on(is_recovery_in_progress(&db)).unwrap());
+    }
+}
@@ -0,0 +1,346 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::{
+    base_node_service::handle::{BaseNodeEvent, BaseNodeEventReceiver},
+    error::WalletError,
+    storage::database::{WalletBackend, WalletDatabase},
+    watch_only::{WatchOnlyKeys, WatchedOutput},
+};
+use futures::{pin_mut, stream::Fuse, FutureExt, StreamExt};
+use log::*;
+use std::{cmp, collections::HashSet, convert::TryFrom};
+use tari_comms::{connectivity::ConnectivityRequester, peer_manager::NodeId};
+use tari_core::{
+    base_node::rpc::BaseNodeWalletRpcClient,
+    proto::base_node::FetchMatchingUtxos,
+    transactions::{transaction::TransactionOutput, types::CryptoFactories},
+};
+use tari_crypto::tari_utilities::{hash::Hashable, hex::Hex};
+use tari_shutdown::ShutdownSignal;
+
+const LOG_TARGET: &str = "wallet::watch_only_scanner";
+
+/// The maximum number of output hashes sent to the base node in a single spent output query
+const SPENT_QUERY_BATCH_SIZE: usize = 100;
+/// The number of blocks below the fork point candidate that are scanned again when the chain has been reorged
+const REORG_RESCAN_DEPTH: u64 = 10;
+
+/// Scans the blocks on the wallet's base node for outputs belonging to a watch-only wallet and keeps track of when
+/// they are spent.
+///
+/// An output belongs to the watch-only wallet if its value can be rewound with the wallet's public rewind keys or if
+/// its commitment was imported into the wallet. Every newly found output is logged as an incoming payment. After each
+/// scan the hashes of the unspent outputs are queried from the base node; outputs that are no longer in the UTXO set
+/// are marked as spent. Unlike the UTXO scanner, a watch-only wallet scans from the height provided when the keys were
+/// imported, which is the genesis block by default.
+///
+/// The height and header hash of the last scanned block are stored as wallet settings. If the base node no longer has
+/// that block in its main chain, the chain has been reorged and the last `REORG_RESCAN_DEPTH` blocks are scanned again,
+/// the same way the UTXO scanner does.
+pub struct WatchOnlyScannerTask<T>
+where T: WalletBackend + 'static
+{
+    db: WalletDatabase<T>,
+    connectivity: ConnectivityRequester,
+    base_node_events: Fuse<BaseNodeEventReceiver>,
+    factories: CryptoFactories,
+    keys: WatchOnlyKeys,
+    base_node: Option<NodeId>,
+    shutdown_signal: ShutdownSignal,
+}
+
+impl<T> WatchOnlyScannerTask<T>
+where T: WalletBackend + 'static
+{
+    pub fn new(
+        db: WalletDatabase<T>,
+        connectivity: ConnectivityRequester,
+        base_node_events: Fuse<BaseNodeEventReceiver>,
+        factories: CryptoFactories,
+        keys: WatchOnlyKeys,
+        shutdown_signal: ShutdownSignal,
+    ) -> Self
+    {
+        Self {
+            db,
+            connectivity,
+            base_node_events,
+            factories,
+            keys,
+            base_node: None,
+            shutdown_signal,
+        }
+    }
+
+    pub async fn run(mut self) {
+        let mut shutdown = self.shutdown_signal.clone();
+        info!(target: LOG_TARGET, "Watch-only scanner starting");
+        loop {
+            futures::select! {
+                event = self.base_node_events.select_next_some() => {
+                    match event {
+                        Ok(event) => self.handle_base_node_event(&*event).await,
+                        Err(e) => trace!(target: LOG_TARGET, "Lagging read on base node event stream: {:?}", e),
+                    }
+                },
+                _ = shutdown => {
+                    info!(target: LOG_TARGET, "Watch-only scanner shutting down because it received the shutdown signal");
+                    break;
+                },
+                complete => {
+                    info!(target: LOG_TARGET, "Watch-only scanner shutting down because the event stream ended");
+                    break;
+                },
+            }
+        }
+    }
+
+    async fn handle_base_node_event(&mut self, event: &BaseNodeEvent) {
+        match event {
+            BaseNodeEvent::BaseNodePeerSet(peer) => {
+                self.base_node = Some(peer.node_id.clone());
+            },
+            BaseNodeEvent::BaseNodeState(state) => {
+                let (tip_height, tip_hash) = match state.chain_metadata.as_ref() {
+                    Some(metadata) => (metadata.height_of_longest_chain(), metadata.best_block().clone()),
+                    None => return,
+                };
+                let peer = match self.base_node.clone() {
+                    Some(peer) => peer,
+                    None => return,
+                };
+                match self.scan_to_height(peer.clone(), tip_height, &tip_hash).await {
+                    Ok(()) | Err(WalletError::Shutdown) => {},
+                    Err(e) => warn!(
+                        target: LOG_TARGET,
+                        "Error scanning blocks from base node {} for watched outputs: {}", peer, e
+                    ),
+                }
+            },
+        }
+    }
+
+    /// Scan all the blocks after the last scanned block up to and including `tip_height`, then refresh the spent
+    /// status of the unspent watched outputs.
+    async fn scan_to_height(&mut self, peer: NodeId, tip_height: u64, tip_hash: &[u8]) -> Result<(), WalletError> {
+        let scanned_height = self.db.get_watch_only_scanned_height().await?;
+        let scanned_hash = self.db.get_watch_only_scanned_header_hash().await?;
+        if scanned_height == Some(tip_height) && scanned_hash.as_deref() == Some(tip_hash) {
+            return Ok(());
+        }
+
+        let mut connection = self.connectivity.dial_peer(peer).await?;
+        let mut client = connection.connect_rpc::<BaseNodeWalletRpcClient>().await?;
+
+        let start_height = match scanned_height {
+            Some(height) => {
+                // Compare the last scanned block to the base node's block at that height to detect a reorg. Without
+                // a stored header hash no block has been scanned since the keys were imported.
+                let is_on_main_chain = height <= tip_height &&
+                    match scanned_hash.as_ref() {
+                        Some(hash) => &client.get_block_outputs(height).await?.header_hash == hash,
+                        None => true,
+                    };
+                if is_on_main_chain {
+                    height + 1
+                } else {
+                    let rescan_height = cmp::min(height, tip_height).saturating_sub(REORG_RESCAN_DEPTH).max(1) - 1;
+                    warn!(
+                        target: LOG_TARGET,
+                        "Block {} at height {} is no longer in the main chain, scanning again from height {}",
+                        scanned_hash.map(|h| h.to_hex()).unwrap_or_default(),
+                        height,
+                        rescan_height + 1
+                    );
+                    let header_hash = client.get_block_outputs(rescan_height).await?.header_hash;
+                    self.rollback_to_height(rescan_height, header_hash).await?;
+                    rescan_height + 1
+                }
+            },
+            None => 0,
+        };
+
+        let mut shutdown = self.shutdown_signal.clone();
+        let mut watched_outputs = self.db.get_watched_outputs().await?;
+
+        for height in start_height..=tip_height {
+            let response = {
+                let request = client.get_block_outputs(height).fuse();
+                pin_mut!(request);
+                futures::select! {
+                    response = request => response?,
+                    _ = shutdown => return Err(WalletError::Shutdown),
+                }
+            };
+
+            let outputs = response
+                .outputs
+                .into_iter()
+                .filter_map(|o| TransactionOutput::try_from(o).ok());
+            for output in outputs {
+                if let Some(watched) = self.match_output(&mut watched_outputs, &output, height) {
+                    self.db.set_watched_output(watched).await?;
+                }
+            }
+            self.db.set_watch_only_scanned_height(Some(height)).await?;
+            self.db
+                .set_watch_only_scanned_header_hash(Some(response.header_hash))
+                .await?;
+        }
+        trace!(
+            target: LOG_TARGET,
+            "Scanned blocks {} to {} for watched outputs",
+            start_height,
+            tip_height
+        );
+
+        self.update_spent_outputs(&mut client, &mut watched_outputs).await
+    }
+
+    /// Returns the updated watched output if the output belongs to this wallet and has not been seen before
+    fn match_output(
+        &self,
+        watched_outputs: &mut Vec<WatchedOutput>,
+        output: &TransactionOutput,
+        height: u64,
+    ) -> Option<WatchedOutput>
+    {
+        let value = output
+            .rewind_range_proof_value_only(
+                &self.factories.range_proof,
+                &self.keys.rewind_public_key,
+                &self.keys.rewind_blinding_public_key,
+            )
+            .ok()
+            .map(|r| r.committed_value);
+
+        match watched_outputs.iter_mut().find(|o| o.commitment == output.commitment) {
+            Some(watched) => {
+                if watched.mined_height.is_some() {
+                    return None;
+                }
+                watched.hash = Some(output.hash());
+                watched.mined_height = Some(height);
+                watched.value = watched.value.or(value);
+                info!(
+                    target: LOG_TARGET,
+                    "Watched output {} mined in block {}",
+                    watched.commitment.to_hex(),
+                    height
+                );
+                Some(watched.clone())
+            },
+            None => {
+                let value = value?;
+                info!(
+                    target: LOG_TARGET,
+                    "Incoming payment of {} detected in block {}", value, height
+                );
+                let watched = WatchedOutput {
+                    commitment: output.commitment.clone(),
+                    hash: Some(output.hash()),
+                    value: Some(value),
+                    mined_height: Some(height),
+                    spent: false,
+                };
+                watched_outputs.push(watched.clone());
+                Some(watched)
+            },
+        }
+    }
+
+    /// Mark the unspent watched outputs that are no longer in the base node's UTXO set as spent
+    async fn update_spent_outputs(
+        &mut self,
+        client: &mut BaseNodeWalletRpcClient,
+        watched_outputs: &mut Vec<WatchedOutput>,
+    ) -> Result<(), WalletError>
+    {
+        let unspent_hashes = watched_outputs
+            .iter()
+            .filter(|o| o.is_unspent())
+            .filter_map(|o| o.hash.clone())
+            .collect::<Vec<_>>();
+        if unspent_hashes.is_empty() {
+            return Ok(());
+        }
+
+        let mut spent_hashes = HashSet::new();
+        for batch in unspent_hashes.chunks(SPENT_QUERY_BATCH_SIZE) {
+            let response = client
+                .fetch_matching_utxos(FetchMatchingUtxos {
+                    output_hashes: batch.to_vec(),
+                })
+                .await?;
+            if !response.is_synced {
+                debug!(
+                    target: LOG_TARGET,
+                    "Base node is not synced, not updating the spent status of watched outputs"
+                );
+                return Ok(());
+            }
+            let found = response
+                .outputs
+                .into_iter()
+                .filter_map(|o| TransactionOutput::try_from(o).ok())
+                .map(|o| o.hash())
+                .collect::<HashSet<_>>();
+            spent_hashes.extend(batch.iter().filter(|h| !found.contains(*h)).cloned());
+        }
+        if spent_hashes.is_empty() {
+            return Ok(());
+        }
+
+        for output in watched_outputs.iter_mut() {
+            if output.hash.as_ref().map(|h| spent_hashes.contains(h)).unwrap_or(false) {
+                output.spent = true;
+                self.db.set_watched_output(output.clone()).await?;
+                info!(
+                    target: LOG_TARGET,
+                    "Watched output {} has been spent",
+                    output.commitment.to_hex()
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// The chain has been reorged, scanning resumes after the block at `height`. Outputs mined above it are returned
+    /// to the unmined state, they will be matched again by commitment when they are mined on the new chain.
+    /// Spends may also have been reorged out so the spent status of every output is refreshed on the next scan.
+    async fn rollback_to_height(&mut self, height: u64, header_hash: Vec<u8>) -> Result<(), WalletError> {
+        for mut output in self.db.get_watched_outputs().await? {
+            if output.mined_height.map(|h| h > height).unwrap_or(false) {
+                output.hash = None;
+                output.mined_height = None;
+            } else if !output.spent {
+                continue;
+            }
+            output.spent = false;
+            self.db.set_watched_output(output).await?;
+        }
+        self.db.set_watch_only_scanned_height(Some(height)).await?;
+        self.db.set_watch_only_scanned_header_hash(Some(header_hash)).await?;
+        Ok(())
+    }
+}
@@ -159,6 +159,8 @@ pub async fn create_wallet(
         dns_seeds: Default::default(),
         dns_seeds_name_server: "1.1.1.1:53".parse().unwrap(),
         dns_seeds_use_dnssec: false,
+        max_bandwidth_bytes_per_sec: None,
+        max_peer_bandwidth_bytes_per_sec: None,
         peer_seeds: Default::default(),
     };
 
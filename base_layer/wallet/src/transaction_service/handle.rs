@@ -53,8 +53,10 @@ pub enum TransactionServiceRequest {
     GetAnyTransaction(TxId),
     SetBaseNodePublicKey(CommsPublicKey),
     SendTransaction((CommsPublicKey, MicroTari, MicroTari, String)),
+    SendOneSidedTransaction((CommsPublicKey, MicroTari, MicroTari, String)),
     CancelTransaction(TxId),
     ImportUtxo(MicroTari, CommsPublicKey, String),
+    ImportUtxoWithId(TxId, MicroTari, CommsPublicKey, String),
     SubmitTransaction((TxId, Transaction, MicroTari, MicroTari, String)),
     SetLowPowerMode,
     SetNormalPowerMode,
@@ -92,8 +94,14 @@ impl fmt::Display for TransactionServiceRequest {
             Self::SendTransaction((k, v, _, msg)) => {
                 f.write_str(&format!("SendTransaction (to {}, {}, {})", k, v, msg))
             },
+            Self::SendOneSidedTransaction((k, v, _, msg)) => {
+                f.write_str(&format!("SendOneSidedTransaction (to {}, {}, {})", k, v, msg))
+            },
             Self::CancelTransaction(t) => f.write_str(&format!("CancelTransaction ({})", t)),
             Self::ImportUtxo(v, k, msg) => f.write_str(&format!("ImportUtxo (from {}, {}, {})", k, v, msg)),
+            Self::ImportUtxoWithId(id, v, k, msg) => {
+                f.write_str(&format!("ImportUtxoWithId ({}, from {}, {}, {})", id, k, v, msg))
+            },
             Self::SubmitTransaction((id, _, _, _, _)) => f.write_str(&format!("SubmitTransaction ({})", id)),
             Self::SetLowPowerMode => f.write_str("SetLowPowerMode "),
             Self::SetNormalPowerMode => f.write_str("SetNormalPowerMode"),
@@ -167,10 +175,13 @@ pub enum TransactionEvent {
     ReceivedTransaction(TxId),
     ReceivedTransactionReply(TxId),
     ReceivedFinalizedTransaction(TxId),
+    TransactionImported(TxId),
     TransactionDiscoveryInProgress(TxId),
     TransactionDirectSendResult(TxId, bool),
     TransactionCompletedImmediately(TxId),
     TransactionStoreForwardSendResult(TxId, bool),
+    /// The recipient acknowledged receiving the transaction from a store and forward node
+    TransactionDeliveredViaStoreAndForward(TxId),
     TransactionCancelled(TxId),
     TransactionBroadcast(TxId),
     TransactionMined(TxId),
@@ -234,6 +245,31 @@ impl TransactionServiceHandle {
         }
     }
 
+    /// Send a one-sided transaction to `dest_pubkey`. The transaction is completed without any interaction with the
+    /// recipient, who will detect the output when it is mined.
+    pub async fn send_one_sided_transaction(
+        &mut self,
+        dest_pubkey: CommsPublicKey,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        message: String,
+    ) -> Result<TxId, TransactionServiceError>
+    {
+        match self
+            .handle
+            .call(TransactionServiceRequest::SendOneSidedTransaction((
+                dest_pubkey,
+                amount,
+                fee_per_gram,
+                message,
+            )))
+            .await??
+        {
+            TransactionServiceResponse::TransactionSent(tx_id) => Ok(tx_id),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn cancel_transaction(&mut self, tx_id: TxId) -> Result<(), TransactionServiceError> {
         match self
             .handle
@@ -389,6 +425,31 @@ impl TransactionServiceHandle {
         }
     }
 
+    /// Import a UTXO under the given transaction id. Importing the same id twice fails with a `DuplicateOutput`
+    /// storage error, so callers can use a deterministic id to import a UTXO at most once.
+    pub async fn import_utxo_with_id(
+        &mut self,
+        tx_id: TxId,
+        amount: MicroTari,
+        source_public_key: CommsPublicKey,
+        message: String,
+    ) -> Result<TxId, TransactionServiceError>
+    {
+        match self
+            .handle
+            .call(TransactionServiceRequest::ImportUtxoWithId(
+                tx_id,
+                amount,
+                source_public_key,
+                message,
+            ))
+            .await??
+        {
+            TransactionServiceResponse::UtxoImported(tx_id) => Ok(tx_id),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn submit_transaction(
         &mut self,
         tx_id: u64,
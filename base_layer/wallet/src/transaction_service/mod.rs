@@ -41,7 +41,7 @@ use futures::{future, Future, Stream, StreamExt};
 use log::*;
 use std::sync::Arc;
 use tari_comms::{connectivity::ConnectivityRequester, peer_manager::NodeIdentity};
-use tari_comms_dht::Dht;
+use tari_comms_dht::{outbound::OutboundPriority, Dht};
 use tari_core::{
     proto::base_node as base_node_proto,
     transactions::{transaction_protocol::proto, types::CryptoFactories},
@@ -190,7 +190,9 @@ where T: TransactionBackend + 'static
         let config = self.config.clone();
 
         context.spawn_when_ready(move |handles| async move {
-            let outbound_message_service = handles.expect_handle::<Dht>().outbound_requester();
+            let dht = handles.expect_handle::<Dht>();
+            // Transaction negotiation is latency sensitive, so these messages are sent ahead of other outbound messages
+            let outbound_message_service = dht.outbound_requester().with_default_priority(OutboundPriority::High);
             let output_manager_service = handles.expect_handle::<OutputManagerHandle>();
             let connectivity_manager = handles.expect_handle::<ConnectivityRequester>();
 
@@ -203,6 +205,7 @@ where T: TransactionBackend + 'static
                 transaction_finalized_stream,
                 base_node_response_stream,
                 transaction_cancelled_stream,
+                dht.subscribe_dht_events(),
                 output_manager_service,
                 outbound_message_service,
                 connectivity_manager,
@@ -33,7 +33,13 @@ use crate::transaction_service::{
     service::TransactionServiceResources,
     storage::{
         database::TransactionBackend,
-        models::{CompletedTransaction, OutboundTransaction, TransactionDirection, TransactionStatus},
+        models::{
+            CompletedTransaction,
+            OutboundSafMessage,
+            OutboundTransaction,
+            TransactionDirection,
+            TransactionStatus,
+        },
     },
     tasks::{
         send_finalized_transaction::send_finalized_transaction_message,
@@ -42,14 +48,13 @@ use crate::transaction_service::{
     },
 };
 use futures::channel::oneshot;
-use tari_comms::{peer_manager::NodeId, types::CommsPublicKey};
+use tari_comms::{message::MessageTag, peer_manager::NodeId, types::CommsPublicKey};
 use tari_comms_dht::{
     domain_message::OutboundDomainMessage,
-    outbound::{OutboundEncryption, SendMessageResponse},
+    outbound::{DhtOutboundError, MessageSendStates, OutboundEncryption, SendMessageResponse},
 };
 use tari_core::transactions::{
     tari_amount::MicroTari,
-    transaction::KernelFeatures,
     transaction_protocol::{proto, recipient::RecipientSignedMessage, sender::SingleRoundSenderData},
     SenderTransactionProtocol,
 };
@@ -400,9 +405,11 @@ where TBackend: TransactionBackend + 'static
             .add_single_recipient_info(recipient_reply, &self.resources.factories.range_proof)
             .map_err(|e| TransactionServiceProtocolError::new(self.id, TransactionServiceError::from(e)))?;
 
-        outbound_tx
-            .sender_protocol
-            .finalize(KernelFeatures::empty(), &self.resources.factories)
+        outbound_tx.sender_protocol = self
+            .resources
+            .output_manager_service
+            .finalize_transaction(outbound_tx.sender_protocol.clone())
+            .await
             .map_err(|e| {
                 error!(
                     target: LOG_TARGET,
@@ -628,22 +635,44 @@ where TBackend: TransactionBackend + 'static
         if self.resources.config.transaction_routing_mechanism == TransactionRoutingMechanism::DirectOnly {
             return Ok(false);
         }
-        let proto_message = proto::TransactionSenderMessage::single(msg.into());
-        match self
+        let previous_saf_messages = self
             .resources
-            .outbound_message_service
-            .closest_broadcast(
-                NodeId::from_public_key(&self.dest_pubkey),
-                OutboundEncryption::EncryptFor(Box::new(self.dest_pubkey.clone())),
-                vec![],
-                OutboundDomainMessage::new(TariMessageType::SenderPartialTransaction, proto_message),
-            )
+            .db
+            .fetch_outbound_saf_messages(self.id)
             .await
-        {
+            .map_err(|e| TransactionServiceProtocolError::new(self.id, TransactionServiceError::from(e)))?;
+        if previous_saf_messages.iter().any(OutboundSafMessage::is_delivered) {
+            debug!(
+                target: LOG_TARGET,
+                "Transaction (TxId: {}) was already delivered via Store and Forward, not resending", self.id
+            );
+            return Ok(true);
+        }
+
+        // The storers used previously have not delivered the transaction, so try different ones
+        let excluded_storers = previous_saf_messages
+            .into_iter()
+            .map(|m| m.storer_node_id)
+            .collect::<Vec<_>>();
+        let proto_message = proto::TransactionSenderMessage::single(msg.into());
+        let mut send_result = self
+            .send_to_storers(proto_message.clone(), excluded_storers.clone())
+            .await;
+        if !excluded_storers.is_empty() && matches!(&send_result, Ok(send_states) if send_states.is_empty()) {
+            debug!(
+                target: LOG_TARGET,
+                "No other Store and Forward storers available for TxId: {}, resending to previous storers", self.id
+            );
+            send_result = self.send_to_storers(proto_message, vec![]).await;
+        }
+
+        match send_result {
             Ok(send_states) if !send_states.is_empty() => {
+                let storers = send_states.to_tagged_node_ids();
                 let (successful_sends, failed_sends) = send_states
                     .wait_n_timeout(self.resources.config.broadcast_send_timeout, 1)
                     .await;
+                self.record_saf_messages(storers, &failed_sends).await;
                 if !successful_sends.is_empty() {
                     info!(
                         target: LOG_TARGET,
@@ -719,6 +748,42 @@ where TBackend: TransactionBackend + 'static
         }
     }
 
+    async fn send_to_storers(
+        &mut self,
+        proto_message: proto::TransactionSenderMessage,
+        excluded_storers: Vec<NodeId>,
+    ) -> Result<MessageSendStates, DhtOutboundError>
+    {
+        self.resources
+            .outbound_message_service
+            .closest_broadcast(
+                NodeId::from_public_key(&self.dest_pubkey),
+                OutboundEncryption::EncryptFor(Box::new(self.dest_pubkey.clone())),
+                excluded_storers,
+                OutboundDomainMessage::new(TariMessageType::SenderPartialTransaction, proto_message),
+            )
+            .await
+    }
+
+    /// Persist the message tags of the store and forward copies that were not known to have failed so that delivery
+    /// receipts from the recipient can be matched to this transaction
+    async fn record_saf_messages(&self, storers: Vec<(MessageTag, NodeId)>, failed_sends: &[MessageTag]) {
+        let messages = storers
+            .into_iter()
+            .filter(|(tag, _)| !failed_sends.contains(tag))
+            .map(|(tag, node_id)| OutboundSafMessage::new(tag.as_value(), self.id, self.dest_pubkey.clone(), node_id))
+            .collect::<Vec<_>>();
+        if messages.is_empty() {
+            return;
+        }
+        if let Err(e) = self.resources.db.add_outbound_saf_messages(messages).await {
+            warn!(
+                target: LOG_TARGET,
+                "Failed to record Store and Forward messages for TxId: {}: {:?}", self.id, e
+            );
+        }
+    }
+
     async fn timeout_transaction(&mut self) -> Result<(), TransactionServiceProtocolError> {
         info!(
             target: LOG_TARGET,
@@ -62,8 +62,16 @@ use std::{
     sync::Arc,
     time::Duration,
 };
-use tari_comms::{connectivity::ConnectivityRequester, peer_manager::NodeIdentity, types::CommsPublicKey};
-use tari_comms_dht::outbound::OutboundMessageRequester;
+use tari_comms::{
+    connectivity::ConnectivityRequester,
+    message::MessageTag,
+    peer_manager::NodeIdentity,
+    types::CommsPublicKey,
+};
+use tari_comms_dht::{
+    event::{DhtEvent, DhtEventReceiver},
+    outbound::OutboundMessageRequester,
+};
 #[cfg(feature = "test_harness")]
 use tari_core::transactions::{tari_amount::uT, types::BlindingFactor};
 use tari_core::{
@@ -114,6 +122,7 @@ pub struct TransactionService<
     transaction_finalized_stream: Option<TTxFinalizedStream>,
     base_node_response_stream: Option<BNResponseStream>,
     transaction_cancelled_stream: Option<TTxCancelledStream>,
+    dht_event_stream: Option<DhtEventReceiver>,
     request_stream: Option<
         reply_channel::Receiver<TransactionServiceRequest, Result<TransactionServiceResponse, TransactionServiceError>>,
     >,
@@ -156,6 +165,7 @@ where
         transaction_finalized_stream: TTxFinalizedStream,
         base_node_response_stream: BNResponseStream,
         transaction_cancelled_stream: TTxCancelledStream,
+        dht_event_stream: DhtEventReceiver,
         output_manager_service: OutputManagerHandle,
         outbound_message_service: OutboundMessageRequester,
         connectivity_manager: ConnectivityRequester,
@@ -191,6 +201,7 @@ where
             transaction_finalized_stream: Some(transaction_finalized_stream),
             base_node_response_stream: Some(base_node_response_stream),
             transaction_cancelled_stream: Some(transaction_cancelled_stream),
+            dht_event_stream: Some(dht_event_stream),
             request_stream: Some(request_stream),
             event_publisher,
             node_identity,
@@ -247,6 +258,11 @@ where
             .expect("Transaction Service initialized without transaction_cancelled_stream")
             .fuse();
         pin_mut!(transaction_cancelled_stream);
+        let mut dht_event_stream = self
+            .dht_event_stream
+            .take()
+            .expect("Transaction Service initialized without dht_event_stream")
+            .fuse();
 
         let mut shutdown = self.resources.shutdown_signal.clone();
 
@@ -380,6 +396,18 @@ where
                         warn!(target: LOG_TARGET, "Error handing Transaction Cancelled Message: {:?}", e);
                     }
                 }
+                event = dht_event_stream.select_next_some() => {
+                    match event {
+                        Ok(event) => {
+                            if let DhtEvent::StoreAndForwardDeliveryReceipt { recipient, message_tags } = &*event {
+                                if let Err(e) = self.handle_saf_delivery_receipt(recipient, message_tags).await {
+                                    warn!(target: LOG_TARGET, "Error handling store and forward delivery receipt: {:?}", e);
+                                }
+                            }
+                        },
+                        Err(e) => debug!(target: LOG_TARGET, "Lagging read on DHT event broadcast channel: {:?}", e),
+                    }
+                }
                 join_result = send_transaction_protocol_handles.select_next_some() => {
                     trace!(target: LOG_TARGET, "Send Protocol for Transaction has ended with result {:?}", join_result);
                     match join_result {
@@ -466,6 +494,16 @@ where
                 )
                 .await
                 .map(TransactionServiceResponse::TransactionSent),
+            TransactionServiceRequest::SendOneSidedTransaction((dest_pubkey, amount, fee_per_gram, message)) => self
+                .send_one_sided_transaction(
+                    dest_pubkey,
+                    amount,
+                    fee_per_gram,
+                    message,
+                    transaction_broadcast_join_handles,
+                )
+                .await
+                .map(TransactionServiceResponse::TransactionSent),
             TransactionServiceRequest::CancelTransaction(tx_id) => self
                 .cancel_transaction(tx_id)
                 .await
@@ -512,7 +550,11 @@ where
                 Ok(TransactionServiceResponse::BaseNodePublicKeySet)
             },
             TransactionServiceRequest::ImportUtxo(value, source_public_key, message) => self
-                .add_utxo_import_transaction(value, source_public_key, message)
+                .add_utxo_import_transaction(OsRng.next_u64(), value, source_public_key, message)
+                .await
+                .map(TransactionServiceResponse::UtxoImported),
+            TransactionServiceRequest::ImportUtxoWithId(tx_id, value, source_public_key, message) => self
+                .add_utxo_import_transaction(tx_id, value, source_public_key, message)
                 .await
                 .map(TransactionServiceResponse::UtxoImported),
             TransactionServiceRequest::SubmitTransaction((tx_id, tx, fee, amount, message)) => self
@@ -673,6 +715,57 @@ where
         Ok(tx_id)
     }
 
+    /// Sends a one-sided transaction to a recipient. The transaction is completed immediately and broadcast without
+    /// any interaction with the recipient.
+    /// # Arguments
+    /// 'dest_pubkey': The Comms pubkey of the recipient node
+    /// 'amount': The amount of Tari to send to the recipient
+    /// 'fee_per_gram': The amount of fee per transaction gram to be included in transaction
+    pub async fn send_one_sided_transaction(
+        &mut self,
+        dest_pubkey: CommsPublicKey,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        message: String,
+        transaction_broadcast_join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<u64, TransactionServiceProtocolError>>,
+        >,
+    ) -> Result<TxId, TransactionServiceError>
+    {
+        let (tx_id, fee, transaction) = self
+            .output_manager_service
+            .create_one_sided_transaction(dest_pubkey.clone(), amount, fee_per_gram, None, message.clone())
+            .await?;
+
+        self.db
+            .insert_completed_transaction(
+                tx_id,
+                CompletedTransaction::new(
+                    tx_id,
+                    self.node_identity.public_key().clone(),
+                    dest_pubkey,
+                    amount,
+                    fee,
+                    transaction,
+                    TransactionStatus::Completed,
+                    message,
+                    Utc::now().naive_utc(),
+                    TransactionDirection::Outbound,
+                    None,
+                ),
+            )
+            .await?;
+
+        let _ = self
+            .event_publisher
+            .send(Arc::new(TransactionEvent::TransactionCompletedImmediately(tx_id)));
+
+        self.complete_send_transaction_protocol(Ok(tx_id), transaction_broadcast_join_handles)
+            .await;
+
+        Ok(tx_id)
+    }
+
     /// Accept the public reply from a recipient and apply the reply to the relevant transaction protocol
     /// # Arguments
     /// 'recipient_reply' - The public response from a recipient with data required to complete the transaction
@@ -888,6 +981,42 @@ where
         Ok(())
     }
 
+    /// Handle a delivery receipt for store and forward messages sent by this node. Any outbound transaction that is
+    /// acknowledged for the first time is reported as delivered via store and forward.
+    async fn handle_saf_delivery_receipt(
+        &mut self,
+        recipient: &CommsPublicKey,
+        message_tags: &[MessageTag],
+    ) -> Result<(), TransactionServiceError>
+    {
+        let tx_ids = self
+            .db
+            .mark_saf_messages_delivered(recipient.clone(), message_tags.iter().map(|t| t.as_value()).collect())
+            .await?;
+
+        for tx_id in tx_ids {
+            info!(
+                target: LOG_TARGET,
+                "Transaction (TxId: {}) was delivered to {} via Store and Forward", tx_id, recipient
+            );
+            let _ = self
+                .event_publisher
+                .send(Arc::new(TransactionEvent::TransactionDeliveredViaStoreAndForward(
+                    tx_id,
+                )))
+                .map_err(|e| {
+                    trace!(
+                        target: LOG_TARGET,
+                        "Error sending event, usually because there are no subscribers: {:?}",
+                        e
+                    );
+                    e
+                });
+        }
+
+        Ok(())
+    }
+
     /// Handle a Transaction Cancelled message received from the Comms layer
     pub async fn handle_transaction_cancelled_message(
         &mut self,
@@ -1488,12 +1617,12 @@ where
     /// Add a completed transaction to the Transaction Manager to record directly importing a spendable UTXO.
     pub async fn add_utxo_import_transaction(
         &mut self,
+        tx_id: TxId,
         value: MicroTari,
         source_public_key: CommsPublicKey,
         message: String,
     ) -> Result<TxId, TransactionServiceError>
     {
-        let tx_id = OsRng.next_u64();
         self.db
             .add_utxo_import_transaction(
                 tx_id,
@@ -1503,6 +1632,17 @@ where
                 message,
             )
             .await?;
+        let _ = self
+            .event_publisher
+            .send(Arc::new(TransactionEvent::TransactionImported(tx_id)))
+            .map_err(|e| {
+                trace!(
+                    target: LOG_TARGET,
+                    "Error sending event, usually because there are no subscribers: {:?}",
+                    e
+                );
+                e
+            });
         Ok(tx_id)
     }
 
@@ -1796,7 +1936,7 @@ where
     /// the outputs
     #[cfg(feature = "test_harness")]
     pub async fn mine_transaction(&mut self, tx_id: TxId) -> Result<(), TransactionServiceError> {
-        use tari_core::transactions::transaction::OutputFeatures;
+        use tari_core::transactions::{transaction::TransactionOutput, types::RangeProof};
 
         let completed_txs = self.db.get_completed_transactions().await?;
         let _found_tx = completed_txs.get(&tx_id).ok_or_else(|| {
@@ -1808,24 +1948,24 @@ where
             TransactionServiceError::TestHarnessError("Could not find Pending TX to complete.".to_string())
         })?;
 
+        // Only the commitments of the pending outputs are checked when a transaction is confirmed
         self.output_manager_service
             .confirm_transaction(
                 tx_id,
                 pending_tx
                     .outputs_to_be_spent
                     .iter()
-                    .map(|o| {
-                        o.unblinded_output
-                            .as_transaction_input(&self.resources.factories.commitment, OutputFeatures::default())
-                    })
+                    .map(|o| o.as_transaction_input())
                     .collect(),
                 pending_tx
                     .outputs_to_be_received
                     .iter()
                     .map(|o| {
-                        o.unblinded_output
-                            .as_transaction_output(&self.resources.factories)
-                            .expect("Failed to convert to Transaction Output")
+                        TransactionOutput::new(
+                            o.unblinded_output.features.clone(),
+                            o.commitment.clone(),
+                            RangeProof::default(),
+                        )
                     })
                     .collect(),
             )
@@ -1900,6 +2040,7 @@ where
             shutdown_signal,
             basenode_service_handle,
             connectivity_manager,
+            None,
         )
         .await?;
 
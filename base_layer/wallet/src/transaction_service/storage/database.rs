@@ -27,6 +27,7 @@ use crate::{
         storage::models::{
             CompletedTransaction,
             InboundTransaction,
+            OutboundSafMessage,
             OutboundTransaction,
             TransactionDirection,
             TransactionStatus,
@@ -120,6 +121,17 @@ pub trait TransactionBackend: Send + Sync + Clone {
     fn remove_encryption(&self) -> Result<(), TransactionStorageError>;
     /// Increment the send counter and timestamp of a transaction
     fn increment_send_count(&self, tx_id: TxId) -> Result<(), TransactionStorageError>;
+    /// Record the copies of a transaction message that were sent to peers for store and forward
+    fn add_outbound_saf_messages(&self, messages: Vec<OutboundSafMessage>) -> Result<(), TransactionStorageError>;
+    /// Fetch the store and forward copies that were sent for a transaction
+    fn fetch_outbound_saf_messages(&self, tx_id: TxId) -> Result<Vec<OutboundSafMessage>, TransactionStorageError>;
+    /// Mark the store and forward copies sent to `recipient` with the given message tags as delivered. Returns the ids
+    /// of the transactions that were not previously marked as delivered.
+    fn mark_saf_messages_delivered(
+        &self,
+        recipient: CommsPublicKey,
+        message_tags: Vec<u64>,
+    ) -> Result<Vec<TxId>, TransactionStorageError>;
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -698,6 +710,47 @@ where T: TransactionBackend + 'static
         Ok(())
     }
 
+    pub async fn add_outbound_saf_messages(
+        &self,
+        messages: Vec<OutboundSafMessage>,
+    ) -> Result<(), TransactionStorageError>
+    {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || db_clone.add_outbound_saf_messages(messages))
+            .await
+            .map_err(|err| TransactionStorageError::BlockingTaskSpawnError(err.to_string()))??;
+        Ok(())
+    }
+
+    pub async fn fetch_outbound_saf_messages(
+        &self,
+        tx_id: TxId,
+    ) -> Result<Vec<OutboundSafMessage>, TransactionStorageError>
+    {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || db_clone.fetch_outbound_saf_messages(tx_id))
+            .await
+            .map_err(|err| TransactionStorageError::BlockingTaskSpawnError(err.to_string()))?
+    }
+
+    /// Returns true if the recipient has acknowledged receiving any of the store and forward copies of the transaction
+    pub async fn is_delivered_via_saf(&self, tx_id: TxId) -> Result<bool, TransactionStorageError> {
+        let messages = self.fetch_outbound_saf_messages(tx_id).await?;
+        Ok(messages.iter().any(OutboundSafMessage::is_delivered))
+    }
+
+    pub async fn mark_saf_messages_delivered(
+        &self,
+        recipient: CommsPublicKey,
+        message_tags: Vec<u64>,
+    ) -> Result<Vec<TxId>, TransactionStorageError>
+    {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || db_clone.mark_saf_messages_delivered(recipient, message_tags))
+            .await
+            .map_err(|err| TransactionStorageError::BlockingTaskSpawnError(err.to_string()))?
+    }
+
     pub async fn confirm_broadcast_or_coinbase_transaction(&self, tx_id: TxId) -> Result<(), TransactionStorageError> {
         let db_clone = self.db.clone();
         tokio::task::spawn_blocking(move || db_clone.confirm_broadcast_or_coinbase_transaction(tx_id))
@@ -21,13 +21,13 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use crate::{output_manager_service::TxId, transaction_service::error::TransactionStorageError};
-use chrono::NaiveDateTime;
+use chrono::{NaiveDateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::{
     convert::TryFrom,
     fmt::{Display, Error, Formatter},
 };
-use tari_comms::types::CommsPublicKey;
+use tari_comms::{peer_manager::NodeId, types::CommsPublicKey};
 use tari_core::transactions::{
     tari_amount::MicroTari,
     transaction::Transaction,
@@ -184,6 +184,36 @@ impl OutboundTransaction {
     }
 }
 
+/// A copy of an outbound transaction message that was sent to a peer to be stored and forwarded to the recipient
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutboundSafMessage {
+    /// The DHT message tag of the copy, which is returned in the recipient's delivery receipt
+    pub message_tag: u64,
+    pub tx_id: TxId,
+    pub destination_public_key: CommsPublicKey,
+    /// The peer that the copy was sent to for storage
+    pub storer_node_id: NodeId,
+    pub sent_at: NaiveDateTime,
+    pub delivered_at: Option<NaiveDateTime>,
+}
+
+impl OutboundSafMessage {
+    pub fn new(message_tag: u64, tx_id: TxId, destination_public_key: CommsPublicKey, storer_node_id: NodeId) -> Self {
+        Self {
+            message_tag,
+            tx_id,
+            destination_public_key,
+            storer_node_id,
+            sent_at: Utc::now().naive_utc(),
+            delivered_at: None,
+        }
+    }
+
+    pub fn is_delivered(&self) -> bool {
+        self.delivered_at.is_some()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct CompletedTransaction {
     pub tx_id: TxId,
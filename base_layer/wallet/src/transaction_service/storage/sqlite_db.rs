@@ -22,7 +22,7 @@
 
 use crate::{
     output_manager_service::TxId,
-    schema::{completed_transactions, inbound_transactions, outbound_transactions},
+    schema::{completed_transactions, inbound_transactions, outbound_saf_messages, outbound_transactions},
     storage::sqlite_utilities::WalletDbConnection,
     transaction_service::{
         error::TransactionStorageError,
@@ -31,6 +31,7 @@ use crate::{
             models::{
                 CompletedTransaction,
                 InboundTransaction,
+                OutboundSafMessage,
                 OutboundTransaction,
                 TransactionDirection,
                 TransactionStatus,
@@ -50,7 +51,7 @@ use std::{
     str::from_utf8,
     sync::{Arc, MutexGuard, RwLock},
 };
-use tari_comms::types::CommsPublicKey;
+use tari_comms::{peer_manager::NodeId, types::CommsPublicKey};
 use tari_core::transactions::{tari_amount::MicroTari, types::PublicKey};
 use tari_crypto::tari_utilities::{
     hex::{from_hex, Hex},
@@ -899,6 +900,134 @@ impl TransactionBackend for TransactionServiceSqliteDatabase {
         };
         Ok(())
     }
+
+    fn add_outbound_saf_messages(&self, messages: Vec<OutboundSafMessage>) -> Result<(), TransactionStorageError> {
+        let conn = self.database_connection.acquire_lock();
+        for message in messages {
+            OutboundSafMessageSql::from(message).commit(&conn)?;
+        }
+        Ok(())
+    }
+
+    fn fetch_outbound_saf_messages(&self, tx_id: u64) -> Result<Vec<OutboundSafMessage>, TransactionStorageError> {
+        let conn = self.database_connection.acquire_lock();
+        OutboundSafMessageSql::index_by_tx_id(tx_id, &conn)?
+            .into_iter()
+            .map(OutboundSafMessage::try_from)
+            .collect()
+    }
+
+    fn mark_saf_messages_delivered(
+        &self,
+        recipient: CommsPublicKey,
+        message_tags: Vec<u64>,
+    ) -> Result<Vec<u64>, TransactionStorageError>
+    {
+        let conn = self.database_connection.acquire_lock();
+        let tags = message_tags.into_iter().map(|t| t as i64).collect::<Vec<_>>();
+        // Only the intended recipient can acknowledge a message, the storers also know the message tags
+        let messages = OutboundSafMessageSql::find_undelivered(&recipient, &tags, &conn)?;
+        let mut tx_ids = Vec::new();
+        for message in messages {
+            let tx_id = message.tx_id as u64;
+            // Only report the first receipt for each transaction
+            if !tx_ids.contains(&tx_id) && !OutboundSafMessageSql::is_delivered(message.tx_id, &conn)? {
+                tx_ids.push(tx_id);
+            }
+            message.mark_delivered(&conn)?;
+        }
+        Ok(tx_ids)
+    }
+}
+
+/// A Sql compatible version of the OutboundSafMessage struct
+#[derive(Clone, Debug, Queryable, Insertable, PartialEq)]
+#[table_name = "outbound_saf_messages"]
+struct OutboundSafMessageSql {
+    message_tag: i64,
+    tx_id: i64,
+    destination_public_key: Vec<u8>,
+    storer_node_id: Vec<u8>,
+    sent_at: NaiveDateTime,
+    delivered_at: Option<NaiveDateTime>,
+}
+
+impl OutboundSafMessageSql {
+    pub fn commit(&self, conn: &SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::insert_into(outbound_saf_messages::table)
+            .values(self.clone())
+            .execute(conn)?;
+        Ok(())
+    }
+
+    pub fn index_by_tx_id(
+        tx_id: TxId,
+        conn: &SqliteConnection,
+    ) -> Result<Vec<OutboundSafMessageSql>, TransactionStorageError>
+    {
+        Ok(outbound_saf_messages::table
+            .filter(outbound_saf_messages::tx_id.eq(tx_id as i64))
+            .load::<OutboundSafMessageSql>(conn)?)
+    }
+
+    pub fn find_undelivered(
+        destination_public_key: &CommsPublicKey,
+        message_tags: &[i64],
+        conn: &SqliteConnection,
+    ) -> Result<Vec<OutboundSafMessageSql>, TransactionStorageError>
+    {
+        Ok(outbound_saf_messages::table
+            .filter(outbound_saf_messages::destination_public_key.eq(destination_public_key.to_vec()))
+            .filter(outbound_saf_messages::message_tag.eq_any(message_tags))
+            .filter(outbound_saf_messages::delivered_at.is_null())
+            .load::<OutboundSafMessageSql>(conn)?)
+    }
+
+    pub fn is_delivered(tx_id: i64, conn: &SqliteConnection) -> Result<bool, TransactionStorageError> {
+        let num_delivered = outbound_saf_messages::table
+            .filter(outbound_saf_messages::tx_id.eq(tx_id))
+            .filter(outbound_saf_messages::delivered_at.is_not_null())
+            .count()
+            .get_result::<i64>(conn)?;
+        Ok(num_delivered > 0)
+    }
+
+    pub fn mark_delivered(&self, conn: &SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::update(outbound_saf_messages::table.filter(outbound_saf_messages::message_tag.eq(self.message_tag)))
+            .set(outbound_saf_messages::delivered_at.eq(Some(Utc::now().naive_utc())))
+            .execute(conn)?;
+        Ok(())
+    }
+}
+
+impl From<OutboundSafMessage> for OutboundSafMessageSql {
+    fn from(message: OutboundSafMessage) -> Self {
+        Self {
+            message_tag: message.message_tag as i64,
+            tx_id: message.tx_id as i64,
+            destination_public_key: message.destination_public_key.to_vec(),
+            storer_node_id: message.storer_node_id.to_vec(),
+            sent_at: message.sent_at,
+            delivered_at: message.delivered_at,
+        }
+    }
+}
+
+impl TryFrom<OutboundSafMessageSql> for OutboundSafMessage {
+    type Error = TransactionStorageError;
+
+    fn try_from(message: OutboundSafMessageSql) -> Result<Self, Self::Error> {
+        Ok(Self {
+            message_tag: message.message_tag as u64,
+            tx_id: message.tx_id as u64,
+            destination_public_key: CommsPublicKey::from_bytes(&message.destination_public_key)
+                .map_err(|_| TransactionStorageError::ConversionError("Invalid destination PublicKey".to_string()))?,
+            storer_node_id: NodeId::from_bytes(&message.storer_node_id)
+                .map_err(|_| TransactionStorageError::ConversionError("Invalid storer NodeId".to_string()))?,
+            sent_at: message.sent_at,
+            delivered_at: message.delivered_at,
+        })
+    }
 }
 
 #[derive(Clone, Debug, Queryable, Insertable, PartialEq)]
@@ -1589,6 +1718,7 @@ mod test {
             models::{
                 CompletedTransaction,
                 InboundTransaction,
+                OutboundSafMessage,
                 OutboundTransaction,
                 TransactionDirection,
                 TransactionStatus,
@@ -1610,6 +1740,7 @@ mod test {
     use diesel::{Connection, SqliteConnection};
     use rand::rngs::OsRng;
     use std::convert::TryFrom;
+    use tari_comms::peer_manager::NodeId;
     use tari_core::transactions::{
         tari_amount::MicroTari,
         transaction::{OutputFeatures, Transaction, UnblindedOutput},
@@ -2167,4 +2298,59 @@ mod test {
         assert!(db3.fetch(&DbKey::PendingOutboundTransactions).is_ok());
         assert!(db3.fetch(&DbKey::CompletedTransactions).is_ok());
     }
+
+    #[test]
+    fn test_outbound_saf_messages() {
+        let db_name = format!("{}.sqlite3", string(8).as_str());
+        let temp_dir = tempdir().unwrap();
+        let db_folder = temp_dir.path().to_str().unwrap().to_string();
+        let db_path = format!("{}{}", db_folder, db_name);
+
+        embed_migrations!("./migrations");
+        let conn = SqliteConnection::establish(&db_path).unwrap_or_else(|_| panic!("Error connecting to {}", db_path));
+
+        embedded_migrations::run_with_output(&conn, &mut std::io::stdout()).expect("Migration failed");
+
+        let db = TransactionServiceSqliteDatabase::new(WalletDbConnection::new(conn, None), None);
+
+        let recipient = PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng));
+        let storer1 = NodeId::from_public_key(&PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)));
+        let storer2 = NodeId::from_public_key(&PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)));
+        db.add_outbound_saf_messages(vec![
+            OutboundSafMessage::new(1, 10, recipient.clone(), storer1.clone()),
+            OutboundSafMessage::new(2, 10, recipient.clone(), storer2.clone()),
+            OutboundSafMessage::new(3, 11, recipient.clone(), storer1.clone()),
+        ])
+        .unwrap();
+
+        let messages = db.fetch_outbound_saf_messages(10).unwrap();
+        assert_eq!(messages.len(), 2);
+        assert!(messages
+            .iter()
+            .any(|m| m.message_tag == 1 && m.storer_node_id == storer1));
+        assert!(messages
+            .iter()
+            .any(|m| m.message_tag == 2 && m.storer_node_id == storer2));
+        assert!(messages.iter().all(|m| !m.is_delivered()));
+
+        // Receipts from anyone other than the recipient are ignored
+        let other = PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng));
+        let tx_ids = db.mark_saf_messages_delivered(other, vec![1, 2, 3]).unwrap();
+        assert!(tx_ids.is_empty());
+
+        // Unknown tags are ignored and each transaction is only reported once
+        let tx_ids = db
+            .mark_saf_messages_delivered(recipient.clone(), vec![1, 2, 99])
+            .unwrap();
+        assert_eq!(tx_ids, vec![10]);
+        assert!(db
+            .fetch_outbound_saf_messages(10)
+            .unwrap()
+            .iter()
+            .all(OutboundSafMessage::is_delivered));
+        assert!(!db.fetch_outbound_saf_messages(11).unwrap()[0].is_delivered());
+
+        let tx_ids = db.mark_saf_messages_delivered(recipient, vec![1, 3]).unwrap();
+        assert_eq!(tx_ids, vec![11]);
+    }
 }
@@ -0,0 +1,329 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::util::emoji::EmojiId;
+use chrono::NaiveDateTime;
+use std::{
+    fmt::{Display, Error, Formatter},
+    str::FromStr,
+};
+use tari_core::transactions::{tari_amount::MicroTari, types::PublicKey};
+use tari_crypto::tari_utilities::hex::Hex;
+use thiserror::Error;
+
+/// The URI scheme used for payment requests
+pub const PAYMENT_REQUEST_SCHEME: &str = "tari";
+
+const AMOUNT_PARAM: &str = "amount";
+const MESSAGE_PARAM: &str = "message";
+const EXPIRY_PARAM: &str = "expiry";
+const FEE_PER_GRAM_PARAM: &str = "fee_per_gram";
+
+#[derive(Debug, Error, PartialEq)]
+pub enum PaymentRequestError {
+    #[error("Payment request destination is not a valid public key or emoji ID")]
+    InvalidDestination,
+    #[error("Payment request parameter `{0}` is invalid")]
+    InvalidParameter(String),
+}
+
+/// A request for payment that a wallet can hand to a payer, either as a URI or as an emoji ID with a query string.
+///
+/// The URI form is `tari:<public key hex>?amount=<uT>&message=<text>&expiry=<unix seconds>&fee_per_gram=<uT>` and
+/// the emoji form replaces `tari:<public key hex>` with the destination's emoji ID. Every parameter is optional, so a
+/// bare emoji ID or public key is also a valid payment request. The `tari://<network>/pubkey/<hex>` link shown as a
+/// QR code by the console wallet is accepted when parsing.
+///
+/// # Example
+///
+/// ```
+/// use tari_core::transactions::tari_amount::MicroTari;
+/// use tari_wallet::util::{emoji::EmojiId, payment_request::PaymentRequest};
+///
+/// let destination = EmojiId::from_hex("70350e09c474809209824c6e6888707b7dd09959aa227343b5106382b856f73a")
+///     .unwrap()
+///     .to_pubkey();
+/// let request = PaymentRequest::new(destination)
+///     .with_amount(MicroTari::from(5000))
+///     .with_message("Coffee & cake".to_string());
+/// let uri = request.to_uri();
+/// assert_eq!(
+///     uri,
+///     "tari:70350e09c474809209824c6e6888707b7dd09959aa227343b5106382b856f73a?amount=5000&message=Coffee%20%26%20cake"
+/// );
+/// assert_eq!(uri.parse::<PaymentRequest>().unwrap(), request);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct PaymentRequest {
+    pub destination: PublicKey,
+    pub amount: Option<MicroTari>,
+    pub message: String,
+    pub expiry: Option<NaiveDateTime>,
+    pub fee_per_gram: Option<MicroTari>,
+}
+
+impl PaymentRequest {
+    pub fn new(destination: PublicKey) -> Self {
+        Self {
+            destination,
+            amount: None,
+            message: String::new(),
+            expiry: None,
+            fee_per_gram: None,
+        }
+    }
+
+    pub fn with_amount(mut self, amount: MicroTari) -> Self {
+        self.amount = Some(amount);
+        self
+    }
+
+    pub fn with_message(mut self, message: String) -> Self {
+        self.message = message;
+        self
+    }
+
+    pub fn with_expiry(mut self, expiry: NaiveDateTime) -> Self {
+        self.expiry = Some(expiry);
+        self
+    }
+
+    pub fn with_fee_per_gram(mut self, fee_per_gram: MicroTari) -> Self {
+        self.fee_per_gram = Some(fee_per_gram);
+        self
+    }
+
+    /// Returns true if the request has an expiry that is at or before `now`
+    pub fn is_expired(&self, now: NaiveDateTime) -> bool {
+        self.expiry.map(|expiry| expiry <= now).unwrap_or(false)
+    }
+
+    /// Encode this request as a `tari:` URI
+    pub fn to_uri(&self) -> String {
+        format!(
+            "{}:{}{}",
+            PAYMENT_REQUEST_SCHEME,
+            self.destination.to_hex(),
+            self.query_string()
+        )
+    }
+
+    /// Encode this request as the destination's emoji ID followed by the request parameters
+    pub fn to_emoji_string(&self) -> String {
+        format!("{}{}", EmojiId::from_pubkey(&self.destination), self.query_string())
+    }
+
+    fn query_string(&self) -> String {
+        let mut params = Vec::new();
+        if let Some(amount) = self.amount {
+            params.push(format!("{}={}", AMOUNT_PARAM, u64::from(amount)));
+        }
+        if !self.message.is_empty() {
+            params.push(format!("{}={}", MESSAGE_PARAM, percent_encode(&self.message)));
+        }
+        if let Some(expiry) = self.expiry {
+            params.push(format!("{}={}", EXPIRY_PARAM, expiry.timestamp()));
+        }
+        if let Some(fee_per_gram) = self.fee_per_gram {
+            params.push(format!("{}={}", FEE_PER_GRAM_PARAM, u64::from(fee_per_gram)));
+        }
+        if params.is_empty() {
+            String::new()
+        } else {
+            format!("?{}", params.join("&"))
+        }
+    }
+
+    fn parse_destination(s: &str) -> Result<PublicKey, PaymentRequestError> {
+        // Accept the `//<network>/pubkey/<hex>` form used by the console wallet QR code
+        let s = match s.strip_prefix("//") {
+            Some(path) => path.rsplit('/').next().unwrap_or_default(),
+            None => s,
+        };
+        PublicKey::from_hex(s)
+            .or_else(|_| EmojiId::str_to_pubkey(s))
+            .map_err(|_| PaymentRequestError::InvalidDestination)
+    }
+}
+
+impl FromStr for PaymentRequest {
+    type Err = PaymentRequestError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let s = s
+            .strip_prefix(PAYMENT_REQUEST_SCHEME)
+            .and_then(|rest| rest.strip_prefix(':'))
+            .unwrap_or(s);
+        let (destination, query) = match s.find('?') {
+            Some(i) => (&s[..i], &s[i + 1..]),
+            None => (s, ""),
+        };
+
+        let mut request = PaymentRequest::new(PaymentRequest::parse_destination(destination)?);
+        for param in query.split('&').filter(|p| !p.is_empty()) {
+            let (key, value) = match param.find('=') {
+                Some(i) => (&param[..i], &param[i + 1..]),
+                None => (param, ""),
+            };
+            let invalid = || PaymentRequestError::InvalidParameter(key.to_string());
+            match key {
+                AMOUNT_PARAM => request.amount = Some(MicroTari::from(value.parse::<u64>().map_err(|_| invalid())?)),
+                MESSAGE_PARAM => request.message = percent_decode(value).ok_or_else(invalid)?,
+                EXPIRY_PARAM => {
+                    let timestamp = value.parse::<i64>().map_err(|_| invalid())?;
+                    request.expiry = Some(NaiveDateTime::from_timestamp_opt(timestamp, 0).ok_or_else(invalid)?);
+                },
+                FEE_PER_GRAM_PARAM => {
+                    request.fee_per_gram = Some(MicroTari::from(value.parse::<u64>().map_err(|_| invalid())?))
+                },
+                // Unknown parameters are ignored so that newer wallets can add fields
+                _ => (),
+            }
+        }
+        Ok(request)
+    }
+}
+
+impl Display for PaymentRequest {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), Error> {
+        fmt.write_str(&self.to_uri())
+    }
+}
+
+fn percent_encode(s: &str) -> String {
+    let mut encoded = String::with_capacity(s.len());
+    for b in s.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => encoded.push(b as char),
+            _ => encoded.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    encoded
+}
+
+fn percent_decode(s: &str) -> Option<String> {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => {
+                let hex = s.get(i + 1..i + 3)?;
+                decoded.push(u8::from_str_radix(hex, 16).ok()?);
+                i += 3;
+            },
+            b'+' => {
+                decoded.push(b' ');
+                i += 1;
+            },
+            b => {
+                decoded.push(b);
+                i += 1;
+            },
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::util::{
+        emoji::EmojiId,
+        payment_request::{PaymentRequest, PaymentRequestError},
+    };
+    use chrono::NaiveDateTime;
+    use tari_core::transactions::{tari_amount::MicroTari, types::PublicKey};
+    use tari_crypto::tari_utilities::hex::Hex;
+
+    const KEY_HEX: &str = "70350e09c474809209824c6e6888707b7dd09959aa227343b5106382b856f73a";
+
+    fn destination() -> PublicKey {
+        PublicKey::from_hex(KEY_HEX).unwrap()
+    }
+
+    #[test]
+    fn uri_round_trip() {
+        let request = PaymentRequest::new(destination())
+            .with_amount(MicroTari::from(1_000_000))
+            .with_message("Rent for March? 100% paid 🏠".to_string())
+            .with_expiry(NaiveDateTime::from_timestamp(1_614_556_800, 0))
+            .with_fee_per_gram(MicroTari::from(25));
+
+        let uri = request.to_uri();
+        assert!(uri.starts_with(&format!("tari:{}?amount=1000000&message=", KEY_HEX)));
+        assert!(uri.ends_with("&expiry=1614556800&fee_per_gram=25"));
+        assert_eq!(uri.parse::<PaymentRequest>().unwrap(), request);
+        assert_eq!(request.to_string(), uri);
+
+        let emoji_string = request.to_emoji_string();
+        assert!(emoji_string.starts_with(EmojiId::from_pubkey(&destination()).as_str()));
+        assert_eq!(emoji_string.parse::<PaymentRequest>().unwrap(), request);
+    }
+
+    #[test]
+    fn bare_destinations() {
+        let eid = EmojiId::from_pubkey(&destination());
+        let expected = PaymentRequest::new(destination());
+        assert_eq!(eid.as_str().parse::<PaymentRequest>().unwrap(), expected);
+        assert_eq!(KEY_HEX.parse::<PaymentRequest>().unwrap(), expected);
+        assert_eq!(
+            format!("tari://stibbons/pubkey/{}", KEY_HEX)
+                .parse::<PaymentRequest>()
+                .unwrap(),
+            expected
+        );
+        assert_eq!(
+            format!("tari:{}?message=hello+world&unknown=1", KEY_HEX)
+                .parse::<PaymentRequest>()
+                .unwrap()
+                .message,
+            "hello world"
+        );
+    }
+
+    #[test]
+    fn invalid_requests() {
+        assert_eq!(
+            "tari:notakey?amount=1".parse::<PaymentRequest>(),
+            Err(PaymentRequestError::InvalidDestination)
+        );
+        assert_eq!(
+            format!("tari:{}?amount=ten", KEY_HEX).parse::<PaymentRequest>(),
+            Err(PaymentRequestError::InvalidParameter("amount".to_string()))
+        );
+        assert_eq!(
+            format!("tari:{}?message=%E", KEY_HEX).parse::<PaymentRequest>(),
+            Err(PaymentRequestError::InvalidParameter("message".to_string()))
+        );
+    }
+
+    #[test]
+    fn expiry() {
+        let now = NaiveDateTime::from_timestamp(1_614_556_800, 0);
+        let request = PaymentRequest::new(destination());
+        assert!(!request.is_expired(now));
+        let request = request.with_expiry(now);
+        assert!(request.is_expired(now));
+        assert!(!request.is_expired(NaiveDateTime::from_timestamp(1_614_556_799, 0)));
+    }
+}
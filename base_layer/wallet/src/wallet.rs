@@ -24,20 +24,25 @@ use crate::{
     base_node_service::{config::BaseNodeServiceConfig, handle::BaseNodeServiceHandle, BaseNodeServiceInitializer},
     contacts_service::{handle::ContactsServiceHandle, storage::database::ContactsBackend, ContactsServiceInitializer},
     error::WalletError,
+    multisig_service::{handle::MultiSigServiceHandle, MultiSigServiceInitializer},
     output_manager_service::{
         config::OutputManagerServiceConfig,
         handle::OutputManagerHandle,
+        signer::TransactionSigner,
         storage::database::OutputManagerBackend,
         OutputManagerServiceInitializer,
         TxId,
     },
     storage::database::{WalletBackend, WalletDatabase},
+    tasks::{utxo_scanner::UtxoScannerTask, watch_only_scanner::WatchOnlyScannerTask},
     transaction_service::{
         config::TransactionServiceConfig,
         handle::TransactionServiceHandle,
         storage::database::TransactionBackend,
         TransactionServiceInitializer,
     },
+    watch_only,
+    watch_only::{WatchOnlyBalance, WatchOnlyKeys, WatchedOutput},
 };
 use aes_gcm::{
     aead::{generic_array::GenericArray, NewAead},
@@ -59,7 +64,7 @@ use tari_core::{
     transactions::{
         tari_amount::MicroTari,
         transaction::{OutputFeatures, UnblindedOutput},
-        types::{CryptoFactories, PrivateKey},
+        types::{Commitment, CryptoFactories, PrivateKey},
     },
 };
 use tari_crypto::{
@@ -89,6 +94,10 @@ pub struct WalletConfig {
     pub rate_limit: usize,
     pub network: Network,
     pub base_node_service_config: BaseNodeServiceConfig,
+    /// An optional external signer for the Output Manager Service, the keys are held in process if not provided
+    pub signer: Option<Arc<dyn TransactionSigner>>,
+    /// Public rewind keys of another wallet, importing them turns this wallet into a watch-only wallet
+    pub watch_only_keys: Option<WatchOnlyKeys>,
 }
 
 impl WalletConfig {
@@ -113,6 +122,8 @@ impl WalletConfig {
             rate_limit: rate_limit.unwrap_or_else(|| 50),
             network,
             base_node_service_config: base_node_service_config.unwrap_or_default(),
+            signer: None,
+            watch_only_keys: None,
         }
     }
 }
@@ -134,8 +145,11 @@ where
     pub transaction_service: TransactionServiceHandle,
     pub contacts_service: ContactsServiceHandle,
     pub base_node_service: BaseNodeServiceHandle,
+    pub multisig_service: MultiSigServiceHandle,
     pub db: WalletDatabase<T>,
     pub factories: CryptoFactories,
+    /// True if this wallet was set up with watch-only keys and can only track the outputs of another wallet
+    pub watch_only: bool,
     #[cfg(feature = "test_harness")]
     pub transaction_backend: U,
     _u: PhantomData<U>,
@@ -193,14 +207,36 @@ where
             config.buffer_size,
             config.rate_limit
         );
+        let watch_only_keys = match (watch_only::get_watch_only_keys(&db).await?, config.watch_only_keys) {
+            (Some(stored), Some(keys)) if stored != keys => {
+                return Err(WalletError::WatchOnlyError(
+                    "The wallet is already watching a different set of keys".to_string(),
+                ));
+            },
+            (None, Some(keys)) => {
+                watch_only::set_watch_only_keys(&db, &keys, 0).await?;
+                Some(keys)
+            },
+            (stored, _) => stored,
+        };
+        let mut output_manager_config = config.output_manager_service_config.unwrap_or_default();
+        if watch_only_keys.is_some() {
+            info!(target: LOG_TARGET, "Wallet is running in watch-only mode");
+            output_manager_config.watch_only = true;
+        }
+        let mut output_manager_initializer = OutputManagerServiceInitializer::new(
+            output_manager_config,
+            output_manager_backend,
+            factories.clone(),
+            config.network,
+        );
+        if let Some(signer) = config.signer {
+            output_manager_initializer = output_manager_initializer.with_signer(signer);
+        }
+
         let stack = StackBuilder::new(shutdown_signal)
             .add_initializer(P2pInitializer::new(config.comms_config, publisher))
-            .add_initializer(OutputManagerServiceInitializer::new(
-                config.output_manager_service_config.unwrap_or_default(),
-                output_manager_backend,
-                factories.clone(),
-                config.network,
-            ))
+            .add_initializer(output_manager_initializer)
             .add_initializer(TransactionServiceInitializer::new(
                 config.transaction_service_config.unwrap_or_default(),
                 peer_message_subscription_factory.clone(),
@@ -211,8 +247,14 @@ where
             .add_initializer(ContactsServiceInitializer::new(contacts_backend))
             .add_initializer(BaseNodeServiceInitializer::new(
                 config.base_node_service_config,
-                peer_message_subscription_factory,
+                peer_message_subscription_factory.clone(),
                 bn_service_db,
+            ))
+            .add_initializer(MultiSigServiceInitializer::new(
+                peer_message_subscription_factory,
+                db.clone(),
+                node_identity.clone(),
+                factories.clone(),
             ));
 
         let mut handles = stack.build().await?;
@@ -229,6 +271,35 @@ where
         let store_and_forward_requester = dht.store_and_forward_requester();
 
         let base_node_service_handle = handles.expect_handle::<BaseNodeServiceHandle>();
+        let multisig_handle = handles.expect_handle::<MultiSigServiceHandle>();
+
+        let watch_only = watch_only_keys.is_some();
+        match watch_only_keys {
+            Some(keys) => {
+                let watch_only_scanner = WatchOnlyScannerTask::new(
+                    db.clone(),
+                    comms.connectivity(),
+                    base_node_service_handle.get_event_stream_fused(),
+                    factories.clone(),
+                    keys,
+                    comms.shutdown_signal(),
+                );
+                tokio::spawn(watch_only_scanner.run());
+            },
+            None => {
+                let utxo_scanner = UtxoScannerTask::new(
+                    db.clone(),
+                    comms.connectivity(),
+                    output_manager_handle.clone(),
+                    transaction_service_handle.clone(),
+                    base_node_service_handle.get_event_stream_fused(),
+                    factories.clone(),
+                    comms.node_identity(),
+                    comms.shutdown_signal(),
+                );
+                tokio::spawn(utxo_scanner.run());
+            },
+        }
 
         Ok(Wallet {
             comms,
@@ -238,8 +309,10 @@ where
             transaction_service: transaction_service_handle,
             contacts_service: contacts_handle,
             base_node_service: base_node_service_handle,
+            multisig_service: multisig_handle,
             db,
             factories,
+            watch_only,
             #[cfg(feature = "test_harness")]
             transaction_backend: transaction_backend_handle,
             _u: PhantomData,
@@ -297,6 +370,34 @@ where
         Ok(())
     }
 
+    /// Returns the balance of a watch-only wallet, calculated from the outputs found by the watch-only scanner
+    pub async fn get_watch_only_balance(&self) -> Result<WatchOnlyBalance, WalletError> {
+        self.ensure_watch_only()?;
+        watch_only::get_watch_only_balance(&self.db).await
+    }
+
+    /// Returns the outputs tracked by a watch-only wallet, which includes every incoming payment that was detected
+    pub async fn get_watched_outputs(&self) -> Result<Vec<WatchedOutput>, WalletError> {
+        self.ensure_watch_only()?;
+        watch_only::get_watched_outputs(&self.db).await
+    }
+
+    /// Add known output commitments to a watch-only wallet. Returns the number of commitments that were not already
+    /// being tracked.
+    pub async fn add_watched_commitments(&self, commitments: Vec<Commitment>) -> Result<usize, WalletError> {
+        self.ensure_watch_only()?;
+        watch_only::add_watched_commitments(&self.db, commitments).await
+    }
+
+    fn ensure_watch_only(&self) -> Result<(), WalletError> {
+        if !self.watch_only {
+            return Err(WalletError::WatchOnlyError(
+                "This wallet is not a watch-only wallet".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
     /// Import an external spendable UTXO into the wallet. The output will be added to the Output Manager and made
     /// spendable. A faux incoming transaction will be created to provide a record of the event. The TxId of the
     /// generated transaction is returned.
@@ -0,0 +1,262 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Watch-only wallet support.
+//!
+//! A watch-only wallet holds no spending keys. It is configured with the public rewind keys of another wallet (see
+//! `OutputManagerHandle::get_rewind_public_keys`) and, optionally, a set of known output commitments. The
+//! [WatchOnlyScannerTask](crate::tasks::watch_only_scanner::WatchOnlyScannerTask) scans the chain for outputs whose
+//! value can be rewound with those keys or whose commitments were imported, and tracks when they are spent. The keys
+//! and the scanned height are stored as wallet settings and the tracked outputs in the `watched_outputs` table.
+
+use crate::{
+    error::WalletError,
+    output_manager_service::handle::PublicRewindKeys,
+    storage::database::{WalletBackend, WalletDatabase},
+};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use tari_core::transactions::{
+    tari_amount::MicroTari,
+    types::{Commitment, PublicKey},
+};
+
+/// The public rewind keys a watch-only wallet uses to detect outputs and read their values
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WatchOnlyKeys {
+    pub rewind_public_key: PublicKey,
+    pub rewind_blinding_public_key: PublicKey,
+}
+
+impl From<PublicRewindKeys> for WatchOnlyKeys {
+    fn from(keys: PublicRewindKeys) -> Self {
+        Self {
+            rewind_public_key: keys.rewind_public_key,
+            rewind_blinding_public_key: keys.rewind_blinding_public_key,
+        }
+    }
+}
+
+/// An output tracked by a watch-only wallet
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WatchedOutput {
+    pub commitment: Commitment,
+    /// The output hash, known once the output has been seen on chain
+    pub hash: Option<Vec<u8>>,
+    /// The value of the output, if it could be rewound with the watch-only keys
+    pub value: Option<MicroTari>,
+    /// The height of the block the output was mined in
+    pub mined_height: Option<u64>,
+    pub spent: bool,
+}
+
+impl WatchedOutput {
+    fn imported(commitment: Commitment) -> Self {
+        Self {
+            commitment,
+            hash: None,
+            value: None,
+            mined_height: None,
+            spent: false,
+        }
+    }
+
+    pub fn is_unspent(&self) -> bool {
+        self.mined_height.is_some() && !self.spent
+    }
+}
+
+/// The balance of a watch-only wallet. Only outputs whose value could be rewound contribute to the available balance.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WatchOnlyBalance {
+    pub available_balance: MicroTari,
+    pub unspent_outputs: usize,
+    pub unspent_outputs_with_unknown_value: usize,
+    pub scanned_height: Option<u64>,
+}
+
+impl fmt::Display for WatchOnlyBalance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Available balance: {}", self.available_balance)?;
+        writeln!(f, "Unspent outputs: {}", self.unspent_outputs)?;
+        writeln!(
+            f,
+            "Unspent outputs with unknown value: {}",
+            self.unspent_outputs_with_unknown_value
+        )?;
+        match self.scanned_height {
+            Some(height) => write!(f, "Scanned to height: {}", height),
+            None => write!(f, "Scanned to height: not scanned yet"),
+        }
+    }
+}
+
+/// Configure the wallet as a watch-only wallet. Scanning starts at `start_height`.
+pub async fn set_watch_only_keys<T: WalletBackend + 'static>(
+    db: &WalletDatabase<T>,
+    keys: &WatchOnlyKeys,
+    start_height: u64,
+) -> Result<(), WalletError>
+{
+    db.set_watch_only_keys(keys.clone()).await?;
+    db.set_watch_only_scanned_height(start_height.checked_sub(1)).await?;
+    db.set_watch_only_scanned_header_hash(None).await?;
+    Ok(())
+}
+
+/// Returns the watch-only keys if this wallet is a watch-only wallet
+pub async fn get_watch_only_keys<T: WalletBackend + 'static>(
+    db: &WalletDatabase<T>,
+) -> Result<Option<WatchOnlyKeys>, WalletError> {
+    Ok(db.get_watch_only_keys().await?)
+}
+
+/// Returns all the outputs tracked by the watch-only wallet
+pub async fn get_watched_outputs<T: WalletBackend + 'static>(
+    db: &WalletDatabase<T>,
+) -> Result<Vec<WatchedOutput>, WalletError> {
+    Ok(db.get_watched_outputs().await?)
+}
+
+/// Start tracking the given commitments. Commitments that are already tracked are ignored. Returns the number of
+/// commitments that were added.
+pub async fn add_watched_commitments<T: WalletBackend + 'static>(
+    db: &WalletDatabase<T>,
+    commitments: Vec<Commitment>,
+) -> Result<usize, WalletError>
+{
+    let mut outputs = db.get_watched_outputs().await?;
+    let mut added = 0;
+    for commitment in commitments {
+        if outputs.iter().all(|o| o.commitment != commitment) {
+            let output = WatchedOutput::imported(commitment);
+            db.set_watched_output(output.clone()).await?;
+            outputs.push(output);
+            added += 1;
+        }
+    }
+    Ok(added)
+}
+
+/// Calculate the balance of the watch-only wallet from the tracked outputs
+pub async fn get_watch_only_balance<T: WalletBackend + 'static>(
+    db: &WalletDatabase<T>,
+) -> Result<WatchOnlyBalance, WalletError> {
+    let mut balance = WatchOnlyBalance {
+        scanned_height: db.get_watch_only_scanned_height().await?,
+        ..Default::default()
+    };
+    for output in db.get_watched_outputs().await?.iter().filter(|o| o.is_unspent()) {
+        balance.unspent_outputs += 1;
+        match output.value {
+            Some(value) => balance.available_balance += value,
+            None => balance.unspent_outputs_with_unknown_value += 1,
+        }
+    }
+    Ok(balance)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::storage::{
+        memory_db::WalletMemoryDatabase,
+        sqlite_db::WalletSqliteDatabase,
+        sqlite_utilities::run_migration_and_create_sqlite_connection,
+    };
+    use tari_core::transactions::{tari_amount::uT, types::CryptoFactories};
+    use tari_crypto::{commitment::HomomorphicCommitmentFactory, keys::PublicKey as PublicKeyTrait};
+    use tari_test_utils::random::string;
+    use tempfile::tempdir;
+    use tokio::runtime::Runtime;
+
+    fn test_watch_only_storage_and_balance<T: WalletBackend + 'static>(backend: T) {
+        let mut runtime = Runtime::new().unwrap();
+        let db = WalletDatabase::new(backend);
+        let factories = CryptoFactories::default();
+        let mut rng = rand::rngs::OsRng;
+
+        assert_eq!(runtime.block_on(get_watch_only_keys(&db)).unwrap(), None);
+        let keys = WatchOnlyKeys {
+            rewind_public_key: PublicKey::random_keypair(&mut rng).1,
+            rewind_blinding_public_key: PublicKey::random_keypair(&mut rng).1,
+        };
+        runtime.block_on(set_watch_only_keys(&db, &keys, 0)).unwrap();
+        assert_eq!(runtime.block_on(get_watch_only_keys(&db)).unwrap(), Some(keys.clone()));
+        assert_eq!(runtime.block_on(db.get_watch_only_scanned_height()).unwrap(), None);
+        runtime.block_on(set_watch_only_keys(&db, &keys, 10)).unwrap();
+        assert_eq!(runtime.block_on(db.get_watch_only_scanned_height()).unwrap(), Some(9));
+        runtime.block_on(set_watch_only_keys(&db, &keys, 0)).unwrap();
+        assert_eq!(runtime.block_on(db.get_watch_only_scanned_height()).unwrap(), None);
+
+        let c1 = factories.commitment.commit_value(&Default::default(), 1);
+        let c2 = factories.commitment.commit_value(&Default::default(), 2);
+        let c3 = factories.commitment.commit_value(&Default::default(), 3);
+        assert_eq!(
+            runtime
+                .block_on(add_watched_commitments(&db, vec![c1.clone(), c2]))
+                .unwrap(),
+            2
+        );
+        assert_eq!(runtime.block_on(add_watched_commitments(&db, vec![c1])).unwrap(), 0);
+
+        let mut outputs = runtime.block_on(get_watched_outputs(&db)).unwrap();
+        assert_eq!(outputs.len(), 2);
+        outputs[0].mined_height = Some(5);
+        outputs[0].value = Some(100 * uT);
+        outputs[1].mined_height = Some(6);
+        outputs.push(WatchedOutput {
+            commitment: c3,
+            hash: None,
+            value: Some(50 * uT),
+            mined_height: Some(7),
+            spent: true,
+        });
+        for output in outputs {
+            runtime.block_on(db.set_watched_output(output)).unwrap();
+        }
+        assert_eq!(runtime.block_on(get_watched_outputs(&db)).unwrap().len(), 3);
+        runtime.block_on(db.set_watch_only_scanned_height(Some(7))).unwrap();
+
+        let balance = runtime.block_on(get_watch_only_balance(&db)).unwrap();
+        assert_eq!(balance, WatchOnlyBalance {
+            available_balance: 100 * uT,
+            unspent_outputs: 2,
+            unspent_outputs_with_unknown_value: 1,
+            scanned_height: Some(7),
+        });
+    }
+
+    #[test]
+    fn test_watch_only_storage_and_balance_memory_db() {
+        test_watch_only_storage_and_balance(WalletMemoryDatabase::new());
+    }
+
+    #[test]
+    fn test_watch_only_storage_and_balance_sqlite_db() {
+        let db_name = format!("{}.sqlite3", string(8).as_str());
+        let db_folder = tempdir().unwrap().path().to_str().unwrap().to_string();
+        let connection = run_migration_and_create_sqlite_connection(&format!("{}{}", db_folder, db_name)).unwrap();
+
+        test_watch_only_storage_and_balance(WalletSqliteDatabase::new(connection, None).unwrap());
+    }
+}
@@ -170,6 +170,52 @@ pub fn test_contacts_service<T: ContactsBackend + 'static>(backend: T) {
     assert_eq!(new_contact.alias, updated_contact.alias);
 }
 
+pub fn test_contacts_import_export<T: ContactsBackend + 'static>(backend: T, other_backend: T) {
+    let mut runtime = Runtime::new().unwrap();
+    let (mut contacts_service, _shutdown) = setup_contacts_service(&mut runtime, backend);
+    let (mut other_contacts_service, _other_shutdown) = setup_contacts_service(&mut runtime, other_backend);
+
+    let mut contacts = (0..3)
+        .map(|_| Contact {
+            alias: random_string(8),
+            public_key: PublicKey::random_keypair(&mut OsRng).1,
+        })
+        .collect::<Vec<_>>();
+    for contact in &contacts {
+        runtime
+            .block_on(contacts_service.upsert_contact(contact.clone()))
+            .unwrap();
+    }
+
+    let json = runtime.block_on(contacts_service.export_contacts()).unwrap();
+
+    let mut renamed = contacts[0].clone();
+    renamed.alias = "Fred".to_string();
+    runtime
+        .block_on(other_contacts_service.upsert_contact(renamed))
+        .unwrap();
+
+    assert!(matches!(
+        runtime.block_on(other_contacts_service.import_contacts("[{\"alias\": \"Bob\"}]")),
+        Err(ContactsServiceError::SerializationError(_))
+    ));
+    assert_eq!(
+        runtime.block_on(other_contacts_service.get_contacts()).unwrap().len(),
+        1
+    );
+
+    let num_imported = runtime.block_on(other_contacts_service.import_contacts(&json)).unwrap();
+    assert_eq!(num_imported, 3);
+    // Importing the same contacts again changes nothing
+    let num_imported = runtime.block_on(other_contacts_service.import_contacts(&json)).unwrap();
+    assert_eq!(num_imported, 0);
+
+    let mut got_contacts = runtime.block_on(other_contacts_service.get_contacts()).unwrap();
+    got_contacts.sort_by(|a, b| a.alias.cmp(&b.alias));
+    contacts.sort_by(|a, b| a.alias.cmp(&b.alias));
+    assert_eq!(got_contacts, contacts);
+}
+
 #[test]
 fn contacts_service_memory_db() {
     test_contacts_service(ContactsServiceMemoryDatabase::new());
@@ -184,3 +230,25 @@ fn contacts_service_sqlite_db() {
     let connection = run_migration_and_create_sqlite_connection(&db_path).unwrap();
     test_contacts_service(ContactsServiceSqliteDatabase::new(connection));
 }
+
+#[test]
+fn contacts_import_export_memory_db() {
+    test_contacts_import_export(
+        ContactsServiceMemoryDatabase::new(),
+        ContactsServiceMemoryDatabase::new(),
+    );
+}
+
+#[test]
+fn contacts_import_export_sqlite_db() {
+    let temp_dir = tempdir().unwrap();
+    let db_folder = temp_dir.path().to_str().unwrap().to_string();
+    let connection =
+        run_migration_and_create_sqlite_connection(&format!("{}/{}.sqlite3", db_folder, random_string(8))).unwrap();
+    let other_connection =
+        run_migration_and_create_sqlite_connection(&format!("{}/{}.sqlite3", db_folder, random_string(8))).unwrap();
+    test_contacts_import_export(
+        ContactsServiceSqliteDatabase::new(connection),
+        ContactsServiceSqliteDatabase::new(other_connection),
+    );
+}
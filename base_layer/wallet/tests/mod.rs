@@ -22,8 +22,10 @@
 
 #![feature(type_alias_impl_trait)]
 
+pub mod multisig_service;
 pub mod output_manager_service;
 pub mod support;
+pub mod tasks;
 // pub mod text_message_service;
 pub mod contacts_service;
 pub mod transaction_service;
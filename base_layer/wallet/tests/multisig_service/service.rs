@@ -0,0 +1,362 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::{
+    output_manager_service::service::setup_output_manager_service,
+    support::{
+        comms_and_services::create_dummy_message,
+        rpc::{publish_base_node_state, BaseNodeWalletRpcMockNode},
+        utils::{make_input, wait_until},
+    },
+};
+use futures::{channel::mpsc, StreamExt};
+use prost::Message;
+use rand::rngs::OsRng;
+use std::{
+    convert::TryFrom,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tari_comms::{
+    message::EnvelopeBody,
+    peer_manager::{NodeIdentity, PeerFeatures},
+    test_utils::node_identity::build_node_identity,
+    types::CommsPublicKey,
+};
+use tari_comms_dht::outbound::mock::{create_outbound_service_mock, OutboundServiceMockState};
+use tari_core::{
+    base_node::proto::wallet_rpc::{TxLocation, TxQueryResponse},
+    transactions::{
+        tari_amount::{uT, MicroTari},
+        transaction::Transaction,
+        transaction_protocol::{multisig::MultiSigMessage, proto},
+        types::{Commitment, CryptoFactories},
+    },
+};
+use tari_p2p::domain_message::DomainMessage;
+use tari_service_framework::reply_channel::{self, Receiver};
+use tari_shutdown::Shutdown;
+use tari_wallet::{
+    base_node_service::handle::BaseNodeEventSender,
+    multisig_service::{
+        handle::{MultiSigEvent, MultiSigServiceHandle},
+        service::MultiSigService,
+    },
+    output_manager_service::{handle::OutputManagerHandle, storage::memory_db::OutputManagerMemoryDatabase, TxId},
+    storage::{database::WalletDatabase, memory_db::WalletMemoryDatabase},
+    transaction_service::{
+        error::TransactionServiceError,
+        handle::{TransactionServiceHandle, TransactionServiceRequest, TransactionServiceResponse},
+    },
+};
+use tokio::{runtime::Runtime, sync::broadcast};
+
+/// Simple task that records the transactions submitted on this channel
+async fn ts_reply_channel_task(
+    mut receiver: Receiver<TransactionServiceRequest, Result<TransactionServiceResponse, TransactionServiceError>>,
+    submitted: Arc<Mutex<Vec<(TxId, Transaction)>>>,
+)
+{
+    while let Some(request_context) = receiver.next().await {
+        let (request, reply_tx) = request_context.split();
+        let response = match request {
+            TransactionServiceRequest::SubmitTransaction((tx_id, tx, _, _, _)) => {
+                submitted.lock().unwrap().push((tx_id, tx));
+                Ok(TransactionServiceResponse::TransactionSubmitted)
+            },
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        };
+
+        let _ = reply_tx.send(response);
+    }
+}
+
+/// A wallet running the MultiSig service against mock outbound messaging, a mock transaction service and a mock base
+/// node
+struct Party {
+    identity: Arc<NodeIdentity>,
+    handle: MultiSigServiceHandle,
+    oms: OutputManagerHandle,
+    outbound: OutboundServiceMockState,
+    messages: mpsc::UnboundedSender<DomainMessage<proto::MultiSigMessage>>,
+    submitted: Arc<Mutex<Vec<(TxId, Transaction)>>>,
+    base_node: BaseNodeWalletRpcMockNode,
+    events: BaseNodeEventSender,
+    _shutdown: Shutdown,
+    _oms_shutdown: Shutdown,
+}
+
+impl Party {
+    fn public_key(&self) -> CommsPublicKey {
+        self.identity.public_key().clone()
+    }
+
+    /// Deliver a message to this party as if it was sent by `source`
+    fn receive(&self, message: MultiSigMessage, source: &CommsPublicKey) {
+        self.messages
+            .unbounded_send(create_dummy_message(proto::MultiSigMessage::from(message), source))
+            .unwrap();
+    }
+
+    /// Deliver all messages sent by this party so far to `destination`, returns the number of messages delivered
+    fn send_to(&self, destination: &Party) -> usize {
+        let calls = self.outbound.take_calls();
+        for (_, body) in &calls {
+            let envelope_body = EnvelopeBody::decode(body.to_vec().as_slice()).unwrap();
+            let message = envelope_body.decode_part::<proto::MultiSigMessage>(1).unwrap().unwrap();
+            destination.receive(MultiSigMessage::try_from(message).unwrap(), &self.public_key());
+        }
+        calls.len()
+    }
+
+    /// Publish the mock base node as this party's base node followed by a state update, which makes the service check
+    /// the spends of its joint outputs
+    fn publish_base_node_state(&self) {
+        self.base_node.publish_peer(&self.events);
+        publish_base_node_state(&self.events, None);
+    }
+
+    fn submitted(&self) -> Vec<(TxId, Transaction)> {
+        self.submitted.lock().unwrap().clone()
+    }
+
+    fn joint_output_count(&self, runtime: &mut Runtime) -> usize {
+        let mut handle = self.handle.clone();
+        runtime.block_on(handle.get_joint_outputs()).unwrap().len()
+    }
+}
+
+/// Keep delivering the messages of both parties to each other until `condition` holds
+fn exchange_until<F: FnMut(&mut Runtime) -> bool>(runtime: &mut Runtime, alice: &Party, bob: &Party, mut condition: F) {
+    wait_until(runtime, |rt| {
+        alice.send_to(bob);
+        bob.send_to(alice);
+        condition(rt)
+    });
+}
+
+/// Spawn a MultiSig service for a wallet holding `funds`
+fn setup_party(runtime: &mut Runtime, funds: Option<MicroTari>) -> Party {
+    let factories = CryptoFactories::default();
+    let shutdown = Shutdown::new();
+
+    let (mut oms, oms_shutdown, _, _, _, _, _) =
+        setup_output_manager_service(runtime, OutputManagerMemoryDatabase::new(), false);
+    if let Some(funds) = funds {
+        let (_, uo) = make_input(&mut OsRng, funds, &factories.commitment);
+        runtime.block_on(oms.add_output(uo)).unwrap();
+    }
+
+    let (ts_request_sender, ts_request_receiver) = reply_channel::unbounded();
+    let (ts_event_publisher, _) = broadcast::channel(100);
+    let submitted = Arc::new(Mutex::new(Vec::new()));
+    runtime.spawn(ts_reply_channel_task(ts_request_receiver, submitted.clone()));
+    let ts_handle = TransactionServiceHandle::new(ts_request_sender, ts_event_publisher);
+
+    let (outbound_message_requester, mock_outbound_service) = create_outbound_service_mock(100);
+    let outbound = mock_outbound_service.get_state();
+    runtime.spawn(mock_outbound_service.run());
+
+    let (base_node, connectivity) = BaseNodeWalletRpcMockNode::spawn(runtime);
+
+    let identity = build_node_identity(PeerFeatures::COMMUNICATION_CLIENT);
+    let (request_sender, request_receiver) = reply_channel::unbounded();
+    let (event_publisher, _) = broadcast::channel(100);
+    let (messages, message_stream) = mpsc::unbounded();
+    let (events, event_receiver) = broadcast::channel(100);
+    let service = MultiSigService::new(
+        request_receiver,
+        message_stream,
+        WalletDatabase::new(WalletMemoryDatabase::new()),
+        oms.clone(),
+        ts_handle,
+        outbound_message_requester,
+        event_publisher.clone(),
+        identity.clone(),
+        factories,
+        connectivity,
+        event_receiver.fuse(),
+        shutdown.to_signal(),
+    );
+    runtime.spawn(service.start());
+
+    Party {
+        identity,
+        handle: MultiSigServiceHandle::new(request_sender, event_publisher),
+        oms,
+        outbound,
+        messages,
+        submitted,
+        base_node,
+        events,
+        _shutdown: shutdown,
+        _oms_shutdown: oms_shutdown,
+    }
+}
+
+/// Create a joint output funded by Alice and owned by Alice and Bob, and have Alice propose to sweep it. Returns the
+/// commitment of the joint output and the signing session id once Bob has received the proposal.
+fn setup_spend_proposal(runtime: &mut Runtime, alice: &Party, bob: &Party) -> (Commitment, u64) {
+    let mut alice_handle = alice.handle.clone();
+    let (_, commitment) = runtime
+        .block_on(alice_handle.create_joint_output(
+            vec![bob.public_key()],
+            10_000 * uT,
+            MicroTari::from(20),
+            "joint".to_string(),
+        ))
+        .unwrap();
+    assert_eq!(alice.submitted().len(), 1);
+    exchange_until(runtime, alice, bob, |rt| bob.joint_output_count(rt) == 1);
+
+    let session_id = propose_spend(runtime, alice, bob, &commitment);
+    (commitment, session_id)
+}
+
+/// Have Alice propose to sweep the joint output, returns the signing session id once Bob has received the proposal
+fn propose_spend(runtime: &mut Runtime, alice: &Party, bob: &Party, commitment: &Commitment) -> u64 {
+    let mut alice_handle = alice.handle.clone();
+    let session_id = runtime
+        .block_on(alice_handle.propose_spend(commitment.clone(), MicroTari::from(20)))
+        .unwrap();
+    let mut bob_handle = bob.handle.clone();
+    wait_until(runtime, |rt| {
+        alice.send_to(bob);
+        rt.block_on(bob_handle.get_spend_proposals())
+            .unwrap()
+            .iter()
+            .any(|p| p.session_id == session_id)
+    });
+    session_id
+}
+
+#[test]
+fn joint_output_shares_are_kept_until_the_spend_is_confirmed() {
+    let mut runtime = Runtime::new().unwrap();
+    let alice = setup_party(&mut runtime, Some(100_000 * uT));
+    let bob = setup_party(&mut runtime, None);
+
+    let (_, session_id) = setup_spend_proposal(&mut runtime, &alice, &bob);
+    let mut bob_handle = bob.handle.clone();
+    runtime.block_on(bob_handle.approve_spend(session_id)).unwrap();
+    exchange_until(&mut runtime, &alice, &bob, |_| alice.submitted().len() == 2);
+
+    // The coordinator broadcasts the spend and only holds the swept output as a pending incoming output of the spend
+    let (spend_tx_id, spend_tx) = alice.submitted()[1].clone();
+    let mut alice_oms = alice.oms.clone();
+    let pending = runtime.block_on(alice_oms.get_pending_transactions()).unwrap();
+    let pending_spend = pending.get(&spend_tx_id).unwrap();
+    assert_eq!(pending_spend.outputs_to_be_received.len(), 1);
+    assert!(pending_spend.outputs_to_be_spent.is_empty());
+    assert_eq!(
+        pending_spend.outputs_to_be_received[0].commitment,
+        spend_tx.body.outputs()[0].commitment
+    );
+
+    // Both owners keep their share until the spend is confirmed
+    assert_eq!(alice.joint_output_count(&mut runtime), 1);
+    assert_eq!(bob.joint_output_count(&mut runtime), 1);
+
+    // A co-owner submits the spend again if the base node does not know about it
+    bob.base_node.rpc_state.set_transaction_query_response(TxQueryResponse {
+        location: TxLocation::NotStored,
+        block_hash: None,
+        confirmations: 0,
+        is_synced: true,
+        height_of_longest_chain: 10,
+    });
+    bob.publish_base_node_state();
+    let resubmitted = runtime
+        .block_on(
+            bob.base_node
+                .rpc_state
+                .wait_pop_submit_transaction_calls(1, Duration::from_secs(20)),
+        )
+        .unwrap();
+    assert_eq!(resubmitted[0].body.kernels(), spend_tx.body.kernels());
+    assert_eq!(bob.joint_output_count(&mut runtime), 1);
+
+    // Not enough confirmations yet
+    bob.base_node.rpc_state.take_transaction_query_calls();
+    bob.base_node.rpc_state.set_transaction_query_response(TxQueryResponse {
+        location: TxLocation::Mined,
+        block_hash: None,
+        confirmations: 1,
+        is_synced: true,
+        height_of_longest_chain: 11,
+    });
+    bob.publish_base_node_state();
+    runtime
+        .block_on(
+            bob.base_node
+                .rpc_state
+                .wait_pop_transaction_query_calls(1, Duration::from_secs(20)),
+        )
+        .unwrap();
+    assert_eq!(bob.joint_output_count(&mut runtime), 1);
+
+    let mut bob_events = bob.handle.get_event_stream_fused();
+    bob.base_node.rpc_state.set_transaction_query_response(TxQueryResponse {
+        location: TxLocation::Mined,
+        block_hash: None,
+        confirmations: 3,
+        is_synced: true,
+        height_of_longest_chain: 13,
+    });
+    bob.publish_base_node_state();
+    wait_until(&mut runtime, |rt| bob.joint_output_count(rt) == 0);
+    assert!(bob.base_node.rpc_state.take_submit_transaction_calls().is_empty());
+    let event = runtime.block_on(bob_events.next()).unwrap().unwrap();
+    assert!(matches!(*event, MultiSigEvent::SpendConfirmed(_)));
+}
+
+#[test]
+fn early_session_messages_are_bounded_and_only_kept_from_owners() {
+    let mut runtime = Runtime::new().unwrap();
+    let alice = setup_party(&mut runtime, Some(100_000 * uT));
+    let bob = setup_party(&mut runtime, None);
+
+    let (commitment, session_id) = setup_spend_proposal(&mut runtime, &alice, &bob);
+
+    // Alice's nonce commitment was delivered with her proposal, flood Bob with more messages for the session before he
+    // approves it
+    let stranger = build_node_identity(PeerFeatures::COMMUNICATION_CLIENT);
+    for i in 0..200u8 {
+        let message = MultiSigMessage::NonceCommitment {
+            session_id,
+            party_index: 0,
+            commitment: vec![i; 32],
+        };
+        bob.receive(message.clone(), stranger.public_key());
+        bob.receive(message, &alice.public_key());
+    }
+    // Messages are handled in order, so the flood has been handled once Bob has received a second proposal
+    propose_spend(&mut runtime, &alice, &bob, &commitment);
+
+    let mut bob_handle = bob.handle.clone();
+    runtime.block_on(bob_handle.approve_spend(session_id)).unwrap();
+    exchange_until(&mut runtime, &alice, &bob, |_| alice.submitted().len() == 2);
+    let (_, spend_tx) = alice.submitted()[1].clone();
+    spend_tx
+        .validate_internal_consistency(&CryptoFactories::default(), None)
+        .unwrap();
+}
@@ -42,17 +42,22 @@ use tari_core::{
     transactions::{
         fee::Fee,
         tari_amount::{uT, MicroTari},
-        transaction::{KernelFeatures, OutputFeatures, Transaction, UnblindedOutput},
+        transaction::{OutputFeatures, Transaction, UnblindedOutput},
         transaction_protocol::{
+            one_sided::OneSidedPaymentKeys,
             recipient::RecipientState,
             sender::TransactionSenderMessage,
             single_receiver::SingleReceiverTransactionProtocol,
         },
-        types::{CryptoFactories, PrivateKey},
+        types::{CryptoFactories, PrivateKey, PublicKey},
         SenderTransactionProtocol,
     },
 };
-use tari_crypto::{hash::blake2::Blake256, keys::SecretKey};
+use tari_crypto::{
+    commitment::HomomorphicCommitmentFactory,
+    hash::blake2::Blake256,
+    keys::{PublicKey as PublicKeyTrait, SecretKey},
+};
 use tari_service_framework::reply_channel;
 use tari_shutdown::Shutdown;
 use tari_wallet::{
@@ -66,7 +71,7 @@ use tari_wallet::{
         storage::{
             database::{DbKey, DbKeyValuePair, DbValue, OutputManagerBackend, OutputManagerDatabase, WriteOperation},
             memory_db::OutputManagerMemoryDatabase,
-            models::DbUnblindedOutput,
+            models::{DbUnblindedOutput, DEFAULT_ACCOUNT_NAME},
             sqlite_db::OutputManagerSqliteDatabase,
         },
         TxId,
@@ -159,6 +164,7 @@ pub fn setup_output_manager_service<T: OutputManagerBackend + 'static>(
             shutdown.to_signal(),
             basenode_service_handle,
             connectivity_manager,
+            None,
         ))
         .unwrap();
     let output_manager_service_handle = OutputManagerHandle::new(oms_request_sender, oms_event_publisher);
@@ -204,7 +210,7 @@ async fn complete_transaction(mut stp: SenderTransactionProtocol, mut oms: Outpu
     .unwrap();
     stp.add_single_recipient_info(recv_info, &factories.range_proof)
         .unwrap();
-    stp.finalize(KernelFeatures::empty(), &factories).unwrap();
+    let stp = oms.finalize_transaction(stp).await.unwrap();
     stp.get_transaction().unwrap().clone()
 }
 
@@ -309,7 +315,7 @@ fn fee_estimate<T: Clone + OutputManagerBackend + 'static>(backend: T) {
     let (mut oms, _shutdown, _, _, _, _, _) = setup_output_manager_service(&mut runtime, backend, true);
 
     let (_, uo) = make_input(&mut OsRng.clone(), MicroTari::from(3000), &factories.commitment);
-    runtime.block_on(oms.add_output(uo)).unwrap();
+    runtime.block_on(oms.add_output(uo.clone())).unwrap();
 
     // minimum fee
     let fee_per_gram = MicroTari::from(1);
@@ -326,6 +332,12 @@ fn fee_estimate<T: Clone + OutputManagerBackend + 'static>(backend: T) {
         assert_eq!(fee, Fee::calculate(fee_per_gram, 1, 1, outputs as usize));
     }
 
+    let (fee, selected) = runtime
+        .block_on(oms.fee_estimate_with_selection(MicroTari::from(100), fee_per_gram, 1, 1))
+        .unwrap();
+    assert_eq!(fee, Fee::calculate(fee_per_gram, 1, 1, 1));
+    assert_eq!(selected, vec![uo]);
+
     // not enough funds
     let err = runtime
         .block_on(oms.fee_estimate(MicroTari::from(2750), fee_per_gram, 1, 1))
@@ -348,6 +360,26 @@ pub fn setup_oms_with_bn_state<T: OutputManagerBackend + 'static>(
     TransactionServiceHandle,
     BaseNodeServiceHandle,
 )
+{
+    setup_oms_with_bn_state_and_config(runtime, backend, height, OutputManagerServiceConfig {
+        base_node_query_timeout: Duration::from_secs(10),
+        max_utxo_query_size: 2,
+        peer_dial_retry_timeout: Duration::from_secs(5),
+        ..Default::default()
+    })
+}
+
+pub fn setup_oms_with_bn_state_and_config<T: OutputManagerBackend + 'static>(
+    runtime: &mut Runtime,
+    backend: T,
+    height: Option<u64>,
+    config: OutputManagerServiceConfig,
+) -> (
+    OutputManagerHandle,
+    Shutdown,
+    TransactionServiceHandle,
+    BaseNodeServiceHandle,
+)
 {
     let shutdown = Shutdown::new();
     let factories = CryptoFactories::default();
@@ -375,12 +407,7 @@ pub fn setup_oms_with_bn_state<T: OutputManagerBackend + 'static>(
 
     let output_manager_service = runtime
         .block_on(OutputManagerService::new(
-            OutputManagerServiceConfig {
-                base_node_query_timeout: Duration::from_secs(10),
-                max_utxo_query_size: 2,
-                peer_dial_retry_timeout: Duration::from_secs(5),
-                ..Default::default()
-            },
+            config,
             ts_handle.clone(),
             oms_request_receiver,
             OutputManagerDatabase::new(backend),
@@ -390,6 +417,7 @@ pub fn setup_oms_with_bn_state<T: OutputManagerBackend + 'static>(
             shutdown.to_signal(),
             base_node_service_handle.clone(),
             connectivity_manager,
+            None,
         ))
         .unwrap();
     let output_manager_service_handle = OutputManagerHandle::new(oms_request_sender, oms_event_publisher);
@@ -635,6 +663,126 @@ fn send_not_enough_funds_sqlite_db() {
     send_not_enough_funds(OutputManagerSqliteDatabase::new(connection, None));
 }
 
+fn watch_only_wallet_refuses_transactions<T: OutputManagerBackend + 'static>(backend: T) {
+    let factories = CryptoFactories::default();
+    let mut runtime = Runtime::new().unwrap();
+
+    let (mut oms, _shutdown, _, _) =
+        setup_oms_with_bn_state_and_config(&mut runtime, backend, None, OutputManagerServiceConfig {
+            watch_only: true,
+            ..Default::default()
+        });
+    let (_ti, uo) = make_input(&mut OsRng.clone(), MicroTari::from(10_000), &factories.commitment);
+    runtime.block_on(oms.add_output(uo)).unwrap();
+
+    match runtime.block_on(oms.prepare_transaction_to_send(
+        MicroTari::from(1000),
+        MicroTari::from(20),
+        None,
+        "".to_string(),
+    )) {
+        Err(OutputManagerError::WatchOnlyWallet) => {},
+        _ => panic!("A watch-only wallet should not build transactions"),
+    }
+    match runtime.block_on(oms.create_coin_split(MicroTari::from(1000), 3, MicroTari::from(20), None)) {
+        Err(OutputManagerError::WatchOnlyWallet) => {},
+        _ => panic!("A watch-only wallet should not build transactions"),
+    }
+    let (_tx_id, sender_message) = generate_sender_transaction_message(MicroTari::from(1000));
+    match runtime.block_on(oms.get_recipient_transaction(sender_message)) {
+        Err(OutputManagerError::WatchOnlyWallet) => {},
+        _ => panic!("A watch-only wallet should not receive transactions"),
+    }
+    assert_eq!(
+        runtime.block_on(oms.get_balance()).unwrap().available_balance,
+        MicroTari::from(10_000)
+    );
+}
+
+#[test]
+fn watch_only_wallet_refuses_transactions_memory_db() {
+    watch_only_wallet_refuses_transactions(OutputManagerMemoryDatabase::new());
+}
+
+fn accounts_have_separate_balances<T: Clone + OutputManagerBackend + 'static>(backend: T) {
+    let factories = CryptoFactories::default();
+    let mut runtime = Runtime::new().unwrap();
+
+    let (mut oms, _shutdown, _, _, _, _, _) = setup_output_manager_service(&mut runtime, backend.clone(), true);
+    let (_ti, uo) = make_input(&mut OsRng.clone(), MicroTari::from(10_000), &factories.commitment);
+    runtime.block_on(oms.add_output(uo)).unwrap();
+
+    let account = runtime.block_on(oms.create_account("savings".to_string())).unwrap();
+    match runtime.block_on(oms.create_account("savings".to_string())) {
+        Err(OutputManagerError::AccountAlreadyExists(_)) => {},
+        _ => panic!("Account names should be unique"),
+    }
+    let accounts = runtime.block_on(oms.get_accounts()).unwrap();
+    assert_eq!(accounts.len(), 2);
+    assert!(accounts[0].is_default());
+
+    runtime.block_on(oms.set_active_account(account.name.clone())).unwrap();
+    assert_eq!(runtime.block_on(oms.get_active_account()).unwrap().id, account.id);
+    assert_eq!(
+        runtime.block_on(oms.get_balance()).unwrap().available_balance,
+        MicroTari::from(0)
+    );
+    match runtime.block_on(oms.prepare_transaction_to_send(
+        MicroTari::from(1000),
+        MicroTari::from(20),
+        None,
+        "".to_string(),
+    )) {
+        Err(OutputManagerError::NotEnoughFunds) => {},
+        _ => panic!("Outputs of another account should not be spent"),
+    }
+
+    let (tx_id, sender_message) = generate_sender_transaction_message(MicroTari::from(1000));
+    runtime.block_on(oms.get_recipient_transaction(sender_message)).unwrap();
+    assert_eq!(
+        runtime.block_on(oms.get_balance()).unwrap().pending_incoming_balance,
+        MicroTari::from(1000)
+    );
+    let account_txs = runtime.block_on(oms.get_account_transactions()).unwrap();
+    assert_eq!(account_txs.get(&tx_id), Some(&account.id));
+
+    // The active account and the account of the pending receive survive a restart
+    drop(oms);
+    let (mut oms, _shutdown, _, _, _, _, _) = setup_output_manager_service(&mut runtime, backend, true);
+    assert_eq!(runtime.block_on(oms.get_active_account()).unwrap().id, account.id);
+    assert_eq!(
+        runtime.block_on(oms.get_balance()).unwrap().pending_incoming_balance,
+        MicroTari::from(1000)
+    );
+
+    runtime
+        .block_on(oms.set_active_account(DEFAULT_ACCOUNT_NAME.to_string()))
+        .unwrap();
+    let balance = runtime.block_on(oms.get_balance()).unwrap();
+    assert_eq!(balance.available_balance, MicroTari::from(10_000));
+    assert_eq!(balance.pending_incoming_balance, MicroTari::from(0));
+
+    match runtime.block_on(oms.set_active_account("missing".to_string())) {
+        Err(OutputManagerError::AccountNotFound(_)) => {},
+        _ => panic!("Unknown accounts should not be selectable"),
+    }
+}
+
+#[test]
+fn accounts_have_separate_balances_memory_db() {
+    accounts_have_separate_balances(OutputManagerMemoryDatabase::new());
+}
+
+#[test]
+fn accounts_have_separate_balances_sqlite_db() {
+    let db_name = format!("{}.sqlite3", random_string(8).as_str());
+    let temp_dir = tempdir().unwrap();
+    let db_folder = temp_dir.path().to_str().unwrap().to_string();
+    let connection = run_migration_and_create_sqlite_connection(&format!("{}/{}", db_folder, db_name)).unwrap();
+
+    accounts_have_separate_balances(OutputManagerSqliteDatabase::new(connection, None));
+}
+
 fn send_no_change<T: OutputManagerBackend + 'static>(backend: T) {
     let factories = CryptoFactories::default();
 
@@ -685,7 +833,7 @@ fn send_no_change<T: OutputManagerBackend + 'static>(backend: T) {
     stp.add_single_recipient_info(recv_info, &factories.range_proof)
         .unwrap();
 
-    stp.finalize(KernelFeatures::empty(), &factories).unwrap();
+    let stp = runtime.block_on(oms.finalize_transaction(stp)).unwrap();
 
     let tx = stp.get_transaction().unwrap();
 
@@ -1012,6 +1160,11 @@ fn test_confirming_received_output<T: OutputManagerBackend + 'static>(backend: T
         )
         .unwrap();
     assert_eq!(rewind_result.committed_value, value);
+
+    // Recovering an output that the wallet already holds skips it instead of failing the recovery
+    let recovered = runtime.block_on(oms.recover_outputs(vec![output])).unwrap();
+    assert!(recovered.is_empty());
+    assert_eq!(runtime.block_on(oms.get_balance()).unwrap().available_balance, value);
 }
 
 #[test]
@@ -1192,6 +1345,169 @@ fn coin_split_no_change_sqlite_db() {
     coin_split_no_change(OutputManagerSqliteDatabase::new(connection, None));
 }
 
+fn one_sided_transaction<T: Clone + OutputManagerBackend + 'static>(backend: T) {
+    let factories = CryptoFactories::default();
+    let mut runtime = Runtime::new().unwrap();
+    let (mut oms, _shutdown, _, _, _, _, _) = setup_output_manager_service(&mut runtime, backend, true);
+
+    let (_ti, uo) = make_input(&mut OsRng.clone(), 10_000 * uT, &factories.commitment);
+    assert!(runtime.block_on(oms.add_output(uo)).is_ok());
+
+    let (recipient_key, recipient_public_key) = PublicKey::random_keypair(&mut OsRng);
+    let amount = 3_000 * uT;
+    let fee_per_gram = MicroTari::from(25);
+    let (_tx_id, fee, tx) = runtime
+        .block_on(oms.create_one_sided_transaction(
+            recipient_public_key,
+            amount,
+            fee_per_gram,
+            None,
+            "one-sided".to_string(),
+        ))
+        .unwrap();
+    assert_eq!(tx.body.inputs().len(), 1);
+    assert_eq!(tx.body.outputs().len(), 2);
+    assert_eq!(fee, Fee::calculate(fee_per_gram, 1, 1, 2));
+
+    // Only the change is pending incoming to the sender
+    let balance = runtime.block_on(oms.get_balance()).unwrap();
+    assert_eq!(balance.available_balance, MicroTari::from(0));
+    assert_eq!(balance.pending_incoming_balance, 10_000 * uT - amount - fee);
+
+    // The recipient finds its output using the public nonce of the kernel
+    let keys =
+        OneSidedPaymentKeys::for_recipient(&recipient_key, tx.body.kernels()[0].excess_sig.get_public_nonce()).unwrap();
+    let rewound = tx
+        .body
+        .outputs()
+        .iter()
+        .filter_map(|o| {
+            o.full_rewind_range_proof(
+                &factories.range_proof,
+                &keys.rewind_data.rewind_key,
+                &keys.rewind_data.rewind_blinding_key,
+            )
+            .ok()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(rewound.len(), 1);
+    assert_eq!(rewound[0].committed_value, amount);
+    assert_eq!(rewound[0].blinding_factor, keys.spending_key);
+}
+
+#[test]
+fn one_sided_transaction_memory_db() {
+    one_sided_transaction(OutputManagerMemoryDatabase::new());
+}
+
+fn one_sided_transaction_no_change<T: Clone + OutputManagerBackend + 'static>(backend: T) {
+    let factories = CryptoFactories::default();
+    let mut runtime = Runtime::new().unwrap();
+    let (mut oms, _shutdown, _, _, _, _, _) = setup_output_manager_service(&mut runtime, backend, true);
+
+    // The remainder after the fee covers the fee of the recipient's output, but not that of a change output as well
+    let amount = 3_000 * uT;
+    let fee_per_gram = MicroTari::from(25);
+    let (_ti, uo) = make_input(
+        &mut OsRng.clone(),
+        amount + Fee::calculate(fee_per_gram, 1, 1, 2),
+        &factories.commitment,
+    );
+    assert!(runtime.block_on(oms.add_output(uo)).is_ok());
+
+    let (_, recipient_public_key) = PublicKey::random_keypair(&mut OsRng);
+    let (tx_id, fee, tx) = runtime
+        .block_on(oms.create_one_sided_transaction(
+            recipient_public_key,
+            amount,
+            fee_per_gram,
+            None,
+            "one-sided".to_string(),
+        ))
+        .unwrap();
+    assert_eq!(tx.body.outputs().len(), 1);
+    assert_eq!(fee, Fee::calculate(fee_per_gram, 1, 1, 2));
+
+    // No zero value change output is expected by the sender
+    let balance = runtime.block_on(oms.get_balance()).unwrap();
+    assert_eq!(balance.pending_incoming_balance, MicroTari::from(0));
+    assert!(runtime.block_on(oms.get_pending_transactions()).unwrap()[&tx_id]
+        .outputs_to_be_received
+        .is_empty());
+}
+
+#[test]
+fn one_sided_transaction_no_change_memory_db() {
+    one_sided_transaction_no_change(OutputManagerMemoryDatabase::new());
+}
+
+fn claim_one_sided_payment<T: Clone + OutputManagerBackend + 'static>(backend: T) {
+    let factories = CryptoFactories::default();
+    let mut runtime = Runtime::new().unwrap();
+    let (mut oms, _shutdown, _, _, _, _, _) = setup_output_manager_service(&mut runtime, backend, true);
+
+    // A one-sided payment found by the recipient, whose commitment key is also known to the sender
+    let (sender_nonce, _) = PublicKey::random_keypair(&mut OsRng);
+    let (_, recipient_public_key) = PublicKey::random_keypair(&mut OsRng);
+    let keys = OneSidedPaymentKeys::for_sender(&sender_nonce, &recipient_public_key).unwrap();
+    let amount = 5_000 * uT;
+    let payment = UnblindedOutput::new(amount, keys.spending_key.clone(), None);
+    runtime.block_on(oms.add_output(payment.clone())).unwrap();
+
+    let fee_per_gram = MicroTari::from(25);
+    let (tx_id, fee, tx) = runtime
+        .block_on(oms.create_claim_transaction(payment.clone(), fee_per_gram))
+        .unwrap();
+    assert_eq!(fee, Fee::calculate(fee_per_gram, 1, 1, 1));
+    assert_eq!(tx.body.inputs().len(), 1);
+    assert_eq!(tx.body.outputs().len(), 1);
+    tx.validate_internal_consistency(&factories, None).unwrap();
+
+    // The claimed output is keyed by a new key held by the signer, not by the key the sender knows
+    assert_ne!(
+        tx.body.outputs()[0].commitment,
+        factories
+            .commitment
+            .commit_value(&keys.spending_key, u64::from(amount - fee))
+    );
+    let pending_tx = runtime
+        .block_on(oms.get_pending_transactions())
+        .unwrap()
+        .remove(&tx_id)
+        .unwrap();
+    assert!(pending_tx.outputs_to_be_received[0].key_id.is_some());
+    assert_eq!(
+        pending_tx.outputs_to_be_received[0].commitment,
+        tx.body.outputs()[0].commitment
+    );
+
+    let balance = runtime.block_on(oms.get_balance()).unwrap();
+    assert_eq!(balance.available_balance, MicroTari::from(0));
+    assert_eq!(balance.pending_incoming_balance, amount - fee);
+
+    // A payment can only be claimed once
+    match runtime.block_on(oms.create_claim_transaction(payment, fee_per_gram)) {
+        Err(OutputManagerError::OutputManagerStorageError(OutputManagerStorageError::ValuesNotFound)) => {},
+        r => panic!("Unexpected result: {:?}", r.map(|(tx_id, _, _)| tx_id)),
+    }
+}
+
+#[test]
+fn claim_one_sided_payment_memory_db() {
+    claim_one_sided_payment(OutputManagerMemoryDatabase::new());
+}
+
+#[test]
+fn claim_one_sided_payment_sqlite_db() {
+    let db_name = format!("{}.sqlite3", random_string(8).as_str());
+    let db_tempdir = tempdir().unwrap();
+    let db_folder = db_tempdir.path().to_str().unwrap().to_string();
+    let db_path = format!("{}/{}", db_folder, db_name);
+    let connection = run_migration_and_create_sqlite_connection(&db_path).unwrap();
+
+    claim_one_sided_payment(OutputManagerSqliteDatabase::new(connection, None));
+}
+
 fn handle_coinbase<T: Clone + OutputManagerBackend + 'static>(backend: T) {
     let mut runtime = Runtime::new().unwrap();
     let factories = CryptoFactories::default();
@@ -1296,7 +1612,7 @@ fn test_utxo_stxo_invalid_txo_validation() {
 
     backend
         .write(WriteOperation::Insert(DbKeyValuePair::UnspentOutput(
-            invalid_output.spending_key.clone(),
+            invalid_tx_output.commitment.clone(),
             Box::new(DbUnblindedOutput::from_unblinded_output(invalid_output.clone(), &factories).unwrap()),
         )))
         .unwrap();
@@ -1313,7 +1629,7 @@ fn test_utxo_stxo_invalid_txo_validation() {
 
     backend
         .write(WriteOperation::Insert(DbKeyValuePair::SpentOutput(
-            spent_output1.spending_key.clone(),
+            spent_tx_output1.commitment.clone(),
             Box::new(DbUnblindedOutput::from_unblinded_output(spent_output1.clone(), &factories).unwrap()),
         )))
         .unwrap();
@@ -1324,7 +1640,7 @@ fn test_utxo_stxo_invalid_txo_validation() {
 
     backend
         .write(WriteOperation::Insert(DbKeyValuePair::SpentOutput(
-            spent_output2.spending_key.clone(),
+            spent_output2.as_transaction_output(&factories).unwrap().commitment,
             Box::new(DbUnblindedOutput::from_unblinded_output(spent_output2, &factories).unwrap()),
         )))
         .unwrap();
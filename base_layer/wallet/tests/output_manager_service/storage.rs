@@ -31,7 +31,6 @@ use rand::{rngs::OsRng, RngCore};
 use std::time::Duration;
 use tari_core::transactions::{
     tari_amount::MicroTari,
-    transaction::OutputFeatures,
     types::{CryptoFactories, PrivateKey},
 };
 use tari_crypto::{commitment::HomomorphicCommitmentFactory, keys::SecretKey};
@@ -42,7 +41,7 @@ use tari_wallet::{
         storage::{
             database::{KeyManagerState, OutputManagerBackend, OutputManagerDatabase, PendingTransactionOutputs},
             memory_db::OutputManagerMemoryDatabase,
-            models::DbUnblindedOutput,
+            models::{account_of, Account, DbUnblindedOutput, DEFAULT_ACCOUNT_ID},
             sqlite_db::OutputManagerSqliteDatabase,
         },
     },
@@ -89,6 +88,7 @@ pub fn test_db_backend<T: OutputManagerBackend + 'static>(backend: T) {
             timestamp: Utc::now().naive_utc() -
                 ChronoDuration::from_std(Duration::from_millis(120_000_000 * i)).unwrap(),
             coinbase_block_height: None,
+            account_id: DEFAULT_ACCOUNT_ID,
         };
         for _ in 0..(OsRng.next_u64() % 5 + 1) {
             let (_ti, uo) = make_input(
@@ -212,7 +212,7 @@ pub fn test_db_backend<T: OutputManagerBackend + 'static>(backend: T) {
     let outputs_to_encumber = vec![outputs[0].clone(), outputs[1].clone()];
     let total_encumbered = outputs[0].clone().unblinded_output.value + outputs[1].clone().unblinded_output.value;
     runtime
-        .block_on(db.encumber_outputs(2, outputs_to_encumber, vec![uo_change.clone()]))
+        .block_on(db.encumber_outputs(2, DEFAULT_ACCOUNT_ID, outputs_to_encumber, vec![uo_change.clone()]))
         .unwrap();
     runtime.block_on(db.confirm_encumbered_outputs(2)).unwrap();
 
@@ -236,10 +236,8 @@ pub fn test_db_backend<T: OutputManagerBackend + 'static>(backend: T) {
     runtime
         .block_on(db.accept_incoming_pending_transaction(
             5,
-            uo_incoming.value,
-            uo_incoming.spending_key.clone(),
-            OutputFeatures::default(),
-            &factories,
+            DbUnblindedOutput::from_unblinded_output(uo_incoming.clone(), &factories).unwrap(),
+            DEFAULT_ACCOUNT_ID,
             None,
         ))
         .unwrap();
@@ -452,6 +450,92 @@ pub fn test_key_manager_crud_sqlite_db() {
     test_key_manager_crud(OutputManagerSqliteDatabase::new(connection, None));
 }
 
+pub fn test_accounts_crud<T: OutputManagerBackend + 'static>(backend: T) {
+    let mut runtime = Runtime::new().unwrap();
+    let factories = CryptoFactories::default();
+
+    let db = OutputManagerDatabase::new(backend);
+
+    assert!(runtime.block_on(db.get_accounts()).unwrap().is_empty());
+
+    let mut savings = Account::new(1, "savings".to_string());
+    let spending = Account::new(2, "spending".to_string());
+    runtime.block_on(db.set_account(savings.clone())).unwrap();
+    runtime.block_on(db.set_account(spending.clone())).unwrap();
+
+    savings.key_index = 5;
+    runtime.block_on(db.set_account(savings.clone())).unwrap();
+    assert_eq!(runtime.block_on(db.get_accounts()).unwrap(), vec![
+        savings.clone(),
+        spending.clone()
+    ]);
+
+    runtime.block_on(db.set_account_transaction(10, savings.id)).unwrap();
+    runtime.block_on(db.set_account_transaction(11, spending.id)).unwrap();
+    let account_txs = runtime.block_on(db.get_account_transactions()).unwrap();
+    assert_eq!(account_of(&account_txs, 10), savings.id);
+    assert_eq!(account_of(&account_txs, 11), spending.id);
+    assert_eq!(account_of(&account_txs, 12), DEFAULT_ACCOUNT_ID);
+
+    let (_ti, uo) = make_input(&mut OsRng, MicroTari::from(1000), &factories.commitment);
+    let uo = DbUnblindedOutput::from_unblinded_output(uo, &factories).unwrap();
+    runtime.block_on(db.add_unspent_output(uo)).unwrap();
+    let (_ti, uo) = make_input(&mut OsRng, MicroTari::from(2000), &factories.commitment);
+    let uo = DbUnblindedOutput::from_unblinded_output(uo, &factories)
+        .unwrap()
+        .with_account(savings.id);
+    runtime.block_on(db.add_unspent_output(uo)).unwrap();
+
+    let unspent = runtime.block_on(db.get_unspent_outputs()).unwrap();
+    assert_eq!(unspent.iter().filter(|o| o.account_id == savings.id).count(), 1);
+
+    let balance = runtime
+        .block_on(db.get_account_balance(None, DEFAULT_ACCOUNT_ID))
+        .unwrap();
+    assert_eq!(balance.available_balance, MicroTari::from(1000));
+    let balance = runtime.block_on(db.get_account_balance(None, savings.id)).unwrap();
+    assert_eq!(balance.available_balance, MicroTari::from(2000));
+    let balance = runtime.block_on(db.get_account_balance(None, spending.id)).unwrap();
+    assert_eq!(balance.available_balance, MicroTari::from(0));
+    let balance = runtime.block_on(db.get_balance(None)).unwrap();
+    assert_eq!(balance.available_balance, MicroTari::from(3000));
+
+    // A pending receive is credited to the account recorded with it, not to the account of the output
+    let (_ti, uo) = make_input(&mut OsRng, MicroTari::from(4000), &factories.commitment);
+    let uo = DbUnblindedOutput::from_unblinded_output(uo, &factories).unwrap();
+    runtime
+        .block_on(db.accept_incoming_pending_transaction(20, uo, spending.id, None))
+        .unwrap();
+    let balance = runtime.block_on(db.get_account_balance(None, spending.id)).unwrap();
+    assert_eq!(balance.pending_incoming_balance, MicroTari::from(4000));
+    runtime.block_on(db.confirm_pending_transaction_outputs(20)).unwrap();
+    let balance = runtime.block_on(db.get_account_balance(None, spending.id)).unwrap();
+    assert_eq!(balance.available_balance, MicroTari::from(4000));
+    assert_eq!(balance.pending_incoming_balance, MicroTari::from(0));
+
+    assert_eq!(runtime.block_on(db.get_active_account()).unwrap(), None);
+    runtime.block_on(db.set_active_account(spending.id)).unwrap();
+    runtime.block_on(db.set_active_account(savings.id)).unwrap();
+    savings.key_index = 6;
+    runtime.block_on(db.set_account(savings.clone())).unwrap();
+    assert_eq!(runtime.block_on(db.get_active_account()).unwrap(), Some(savings.id));
+}
+
+#[test]
+pub fn test_accounts_crud_memory_db() {
+    test_accounts_crud(OutputManagerMemoryDatabase::new());
+}
+
+#[test]
+pub fn test_accounts_crud_sqlite_db() {
+    let db_name = format!("{}.sqlite3", random_string(8).as_str());
+    let temp_dir = tempdir().unwrap();
+    let db_folder = temp_dir.path().to_str().unwrap().to_string();
+    let connection = run_migration_and_create_sqlite_connection(&format!("{}/{}", db_folder, db_name)).unwrap();
+
+    test_accounts_crud(OutputManagerSqliteDatabase::new(connection, None));
+}
+
 pub async fn test_short_term_encumberance<T: OutputManagerBackend + 'static>(backend: T) {
     let factories = CryptoFactories::default();
 
@@ -465,6 +549,7 @@ pub async fn test_short_term_encumberance<T: OutputManagerBackend + 'static>(bac
         outputs_to_be_received: vec![],
         timestamp: Utc::now().naive_utc() - ChronoDuration::from_std(Duration::from_millis(120_000_000)).unwrap(),
         coinbase_block_height: None,
+        account_id: DEFAULT_ACCOUNT_ID,
     };
     for i in 1..4 {
         let (_ti, uo) = make_input(&mut OsRng, MicroTari::from(1000 * i), &factories.commitment);
@@ -478,9 +563,12 @@ pub async fn test_short_term_encumberance<T: OutputManagerBackend + 'static>(bac
     let uo = DbUnblindedOutput::from_unblinded_output(uo, &factories).unwrap();
     pending_tx.outputs_to_be_received.push(uo);
 
-    db.encumber_outputs(pending_tx.tx_id, pending_tx.outputs_to_be_spent.clone(), vec![
-        pending_tx.outputs_to_be_received[0].clone(),
-    ])
+    db.encumber_outputs(
+        pending_tx.tx_id,
+        DEFAULT_ACCOUNT_ID,
+        pending_tx.outputs_to_be_spent.clone(),
+        vec![pending_tx.outputs_to_be_received[0].clone()],
+    )
     .await
     .unwrap();
 
@@ -497,9 +585,12 @@ pub async fn test_short_term_encumberance<T: OutputManagerBackend + 'static>(bac
     let uo = DbUnblindedOutput::from_unblinded_output(uo, &factories).unwrap();
     pending_tx.outputs_to_be_received.push(uo);
 
-    db.encumber_outputs(pending_tx.tx_id, pending_tx.outputs_to_be_spent.clone(), vec![
-        pending_tx.outputs_to_be_received[0].clone(),
-    ])
+    db.encumber_outputs(
+        pending_tx.tx_id,
+        DEFAULT_ACCOUNT_ID,
+        pending_tx.outputs_to_be_spent.clone(),
+        vec![pending_tx.outputs_to_be_received[0].clone()],
+    )
     .await
     .unwrap();
 
@@ -516,9 +607,12 @@ pub async fn test_short_term_encumberance<T: OutputManagerBackend + 'static>(bac
 
     db.cancel_pending_transaction_outputs(pending_tx.tx_id).await.unwrap();
 
-    db.encumber_outputs(pending_tx.tx_id, pending_tx.outputs_to_be_spent.clone(), vec![
-        pending_tx.outputs_to_be_received[0].clone(),
-    ])
+    db.encumber_outputs(
+        pending_tx.tx_id,
+        DEFAULT_ACCOUNT_ID,
+        pending_tx.outputs_to_be_spent.clone(),
+        vec![pending_tx.outputs_to_be_received[0].clone()],
+    )
     .await
     .unwrap();
 
@@ -588,6 +682,7 @@ pub async fn test_no_duplicate_outputs<T: OutputManagerBackend + 'static>(backen
         outputs_to_be_received: vec![uo],
         timestamp: Utc::now().naive_utc() - ChronoDuration::from_std(Duration::from_millis(120_000_000)).unwrap(),
         coinbase_block_height: None,
+        account_id: DEFAULT_ACCOUNT_ID,
     };
 
     match db.add_pending_transaction_outputs(pending_tx.clone()).await {
@@ -20,40 +20,67 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+use futures::channel::mpsc;
 use std::{
     convert::TryFrom,
     sync::{Arc, Mutex},
     time::{Duration, Instant},
 };
-use tari_comms::protocol::rpc::{Request, Response, RpcStatus};
+use tari_common_types::chain_metadata::ChainMetadata;
+use tari_comms::{
+    connectivity::ConnectivityRequester,
+    peer_manager::{NodeIdentity, PeerFeatures},
+    protocol::rpc::{mock::MockRpcServer, NamedProtocolService, Request, Response, RpcStatus, Streaming},
+    test_utils::{mocks::create_connectivity_mock, node_identity::build_node_identity},
+    Substream,
+};
 use tari_core::{
     base_node::{
         proto::wallet_rpc::{TxLocation, TxQueryResponse, TxSubmissionRejectionReason, TxSubmissionResponse},
-        rpc::BaseNodeWalletService,
+        rpc::{BaseNodeWalletRpcServer, BaseNodeWalletService},
+        sync::rpc::BaseNodeSyncService,
     },
     proto::{
         base_node::{
+            BlockBodyResponse,
+            BlockOutputsResponse,
+            ChainMetadata as ChainMetadataProto,
             FetchMatchingUtxos,
             FetchUtxosResponse,
+            FindChainSplitRequest,
+            FindChainSplitResponse,
             Signatures as SignaturesProto,
+            SyncBlocksRequest,
+            SyncHeadersRequest,
+            SyncKernelsRequest,
+            SyncUtxo,
+            SyncUtxosRequest,
+            SyncUtxosResponse,
+            TipInfoResponse,
             TxQueryBatchResponse as TxQueryBatchResponseProto,
             TxQueryBatchResponses as TxQueryBatchResponsesProto,
             TxQueryResponse as TxQueryResponseProto,
             TxSubmissionResponse as TxSubmissionResponseProto,
         },
+        core::BlockHeader as BlockHeaderProto,
         types::{
             Signature as SignatureProto,
             Transaction as TransactionProto,
+            TransactionKernel as TransactionKernelProto,
             TransactionOutput as TransactionOutputProto,
         },
     },
     tari_utilities::Hashable,
     transactions::{
-        transaction::{Transaction, TransactionOutput},
+        transaction::{Transaction, TransactionKernel, TransactionOutput},
         types::Signature,
     },
 };
-use tokio::time::delay_for;
+use tari_wallet::base_node_service::{
+    handle::{BaseNodeEvent, BaseNodeEventSender},
+    service::{BaseNodeState, OnlineState},
+};
+use tokio::{runtime::Runtime, time::delay_for};
 
 /// This macro unlocks a Mutex or RwLock. If the lock is
 /// poisoned (i.e. panic while unlocked) the last value
@@ -85,6 +112,15 @@ pub struct BaseNodeWalletRpcMockState {
     rpc_status_error: Arc<Mutex<Option<RpcStatus>>>,
     synced: Arc<Mutex<bool>>,
     utxos: Arc<Mutex<Vec<TransactionOutput>>>,
+    blocks: Arc<Mutex<Vec<MockBlock>>>,
+}
+
+/// A block served by the `BaseNodeWalletRpcMockService`. The tip is the last block.
+#[derive(Clone, Debug, Default)]
+pub struct MockBlock {
+    pub header_hash: Vec<u8>,
+    pub outputs: Vec<TransactionOutput>,
+    pub kernels: Vec<TransactionKernel>,
 }
 
 #[allow(clippy::mutex_atomic)]
@@ -111,6 +147,7 @@ impl BaseNodeWalletRpcMockState {
             rpc_status_error: Arc::new(Mutex::new(None)),
             synced: Arc::new(Mutex::new(true)),
             utxos: Arc::new(Mutex::new(Vec::new())),
+            blocks: Arc::new(Mutex::new(Vec::new())),
         }
     }
 
@@ -145,6 +182,27 @@ impl BaseNodeWalletRpcMockState {
         *lock = utxos;
     }
 
+    /// This method sets the outputs contained in each block, indexed by block height. The tip is the last block.
+    pub fn set_block_outputs(&self, blocks: Vec<Vec<TransactionOutput>>) {
+        self.set_blocks(
+            blocks
+                .into_iter()
+                .enumerate()
+                .map(|(height, outputs)| MockBlock {
+                    header_hash: mock_block_hash(height as u64),
+                    outputs,
+                    kernels: Vec::new(),
+                })
+                .collect(),
+        );
+    }
+
+    /// This method sets the blocks of the main chain, indexed by block height
+    pub fn set_blocks(&self, blocks: Vec<MockBlock>) {
+        let mut lock = acquire_lock!(self.blocks);
+        *lock = blocks;
+    }
+
     pub fn take_submit_transaction_calls(&self) -> Vec<Transaction> {
         acquire_lock!(self.submit_transaction_calls).drain(..).collect()
     }
@@ -426,6 +484,263 @@ impl BaseNodeWalletService for BaseNodeWalletRpcMockService {
             is_synced: *sync_lock,
         }))
     }
+
+    async fn get_tip_info(&self, _request: Request<()>) -> Result<Response<TipInfoResponse>, RpcStatus> {
+        let status_lock = acquire_lock!(self.state.rpc_status_error);
+        if let Some(status) = (*status_lock).clone() {
+            return Err(status);
+        }
+
+        let blocks_lock = acquire_lock!(self.state.blocks);
+        let tip = blocks_lock.last();
+        let sync_lock = acquire_lock!(self.state.synced);
+        Ok(Response::new(TipInfoResponse {
+            metadata: Some(ChainMetadataProto {
+                height_of_longest_chain: (blocks_lock.len() as u64).checked_sub(1),
+                best_block: tip.map(|b| b.header_hash.clone()),
+                pruning_horizon: 0,
+                accumulated_difficulty: Vec::new(),
+                effective_pruned_height: 0,
+            }),
+            is_synced: *sync_lock,
+        }))
+    }
+
+    async fn get_block_outputs(&self, request: Request<u64>) -> Result<Response<BlockOutputsResponse>, RpcStatus> {
+        let status_lock = acquire_lock!(self.state.rpc_status_error);
+        if let Some(status) = (*status_lock).clone() {
+            return Err(status);
+        }
+
+        let height = request.into_message();
+        let blocks_lock = acquire_lock!(self.state.blocks);
+        let block = blocks_lock
+            .get(height as usize)
+            .ok_or_else(|| RpcStatus::not_found(format!("Block not found at height {}", height)))?;
+
+        let sync_lock = acquire_lock!(self.state.synced);
+        Ok(Response::new(BlockOutputsResponse {
+            height,
+            header_hash: block.header_hash.clone(),
+            outputs: block
+                .outputs
+                .iter()
+                .cloned()
+                .map(TransactionOutputProto::from)
+                .collect(),
+            is_synced: *sync_lock,
+            kernels: block
+                .kernels
+                .iter()
+                .cloned()
+                .map(TransactionKernelProto::from)
+                .collect(),
+        }))
+    }
+}
+
+fn mock_block_hash(height: u64) -> Vec<u8> {
+    height.to_le_bytes().to_vec()
+}
+
+/// A mock base node serving the `BaseNodeWalletRpcMockService`. A connection to it is held by the mock connectivity
+/// manager returned from `spawn`.
+pub struct BaseNodeWalletRpcMockNode {
+    pub identity: Arc<NodeIdentity>,
+    pub rpc_state: BaseNodeWalletRpcMockState,
+    _mock_server: MockRpcServer<BaseNodeWalletRpcServer<BaseNodeWalletRpcMockService>, Substream>,
+}
+
+impl BaseNodeWalletRpcMockNode {
+    pub fn spawn(runtime: &mut Runtime) -> (Self, ConnectivityRequester) {
+        let (connectivity, connectivity_mock) = create_connectivity_mock();
+        let connectivity_mock_state = connectivity_mock.get_shared_state();
+        runtime.spawn(connectivity_mock.run());
+
+        let service = BaseNodeWalletRpcMockService::new();
+        let rpc_state = service.get_state();
+        let server = BaseNodeWalletRpcServer::new(service);
+        let protocol_name = server.as_protocol_name();
+        let identity = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+        let mut mock_server = runtime.handle().enter(|| MockRpcServer::new(server, identity.clone()));
+        runtime.handle().enter(|| mock_server.serve());
+        let connection = runtime.block_on(async {
+            mock_server
+                .create_connection(identity.to_peer(), protocol_name.into())
+                .await
+        });
+        runtime.block_on(connectivity_mock_state.add_active_connection(connection));
+
+        let node = Self {
+            identity,
+            rpc_state,
+            _mock_server: mock_server,
+        };
+        (node, connectivity)
+    }
+
+    /// Publish this node as the wallet's base node
+    pub fn publish_peer(&self, events: &BaseNodeEventSender) {
+        events
+            .send(Arc::new(BaseNodeEvent::BaseNodePeerSet(Box::new(
+                self.identity.to_peer(),
+            ))))
+            .unwrap();
+    }
+}
+
+/// Publish a state update from an online and synced base node
+pub fn publish_base_node_state(events: &BaseNodeEventSender, chain_metadata: Option<ChainMetadata>) {
+    let state = BaseNodeState {
+        chain_metadata,
+        is_synced: Some(true),
+        updated: None,
+        latency: None,
+        online: OnlineState::Online,
+    };
+    events.send(Arc::new(BaseNodeEvent::BaseNodeState(state))).unwrap();
+}
+
+/// The number of outputs returned in each `SyncUtxosResponse` streamed by the `BaseNodeSyncRpcMockService`
+pub const MOCK_SYNC_UTXOS_BATCH_SIZE: usize = 2;
+
+#[derive(Clone, Debug)]
+pub struct BaseNodeSyncRpcMockState {
+    outputs: Arc<Mutex<Vec<TransactionOutput>>>,
+    sync_utxos_calls: Arc<Mutex<Vec<u64>>>,
+}
+
+impl BaseNodeSyncRpcMockState {
+    pub fn new() -> Self {
+        Self {
+            outputs: Arc::new(Mutex::new(Vec::new())),
+            sync_utxos_calls: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// This method sets the outputs in the output MMR of the mock chain, which consists of a single block
+    pub fn set_outputs(&self, outputs: Vec<TransactionOutput>) {
+        let mut lock = acquire_lock!(self.outputs);
+        *lock = outputs;
+    }
+
+    /// Returns the `start` index of every `sync_utxos` request received so far
+    pub fn take_sync_utxos_calls(&self) -> Vec<u64> {
+        acquire_lock!(self.sync_utxos_calls).drain(..).collect()
+    }
+}
+
+impl Default for BaseNodeSyncRpcMockState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct BaseNodeSyncRpcMockService {
+    state: BaseNodeSyncRpcMockState,
+}
+
+impl BaseNodeSyncRpcMockService {
+    pub fn new() -> Self {
+        Self {
+            state: BaseNodeSyncRpcMockState::new(),
+        }
+    }
+
+    pub fn get_state(&self) -> BaseNodeSyncRpcMockState {
+        self.state.clone()
+    }
+}
+
+impl Default for BaseNodeSyncRpcMockService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[tari_comms::async_trait]
+impl BaseNodeSyncService for BaseNodeSyncRpcMockService {
+    async fn sync_blocks(
+        &self,
+        _request: Request<SyncBlocksRequest>,
+    ) -> Result<Streaming<BlockBodyResponse>, RpcStatus>
+    {
+        Err(RpcStatus::not_implemented("Not implemented by the mock"))
+    }
+
+    async fn sync_headers(
+        &self,
+        _request: Request<SyncHeadersRequest>,
+    ) -> Result<Streaming<BlockHeaderProto>, RpcStatus>
+    {
+        Err(RpcStatus::not_implemented("Not implemented by the mock"))
+    }
+
+    async fn get_header_by_height(&self, request: Request<u64>) -> Result<Response<BlockHeaderProto>, RpcStatus> {
+        let height = request.into_message();
+        if height != 0 {
+            return Err(RpcStatus::not_found(format!("Header not found at height {}", height)));
+        }
+        Ok(Response::new(BlockHeaderProto {
+            output_mmr_size: acquire_lock!(self.state.outputs).len() as u64,
+            ..Default::default()
+        }))
+    }
+
+    async fn find_chain_split(
+        &self,
+        _request: Request<FindChainSplitRequest>,
+    ) -> Result<Response<FindChainSplitResponse>, RpcStatus>
+    {
+        Err(RpcStatus::not_implemented("Not implemented by the mock"))
+    }
+
+    async fn get_chain_metadata(&self, _request: Request<()>) -> Result<Response<ChainMetadataProto>, RpcStatus> {
+        Ok(Response::new(ChainMetadataProto {
+            height_of_longest_chain: Some(0),
+            best_block: Some(mock_block_hash(0)),
+            pruning_horizon: 0,
+            accumulated_difficulty: Vec::new(),
+            effective_pruned_height: 0,
+        }))
+    }
+
+    async fn sync_kernels(
+        &self,
+        _request: Request<SyncKernelsRequest>,
+    ) -> Result<Streaming<TransactionKernelProto>, RpcStatus>
+    {
+        Err(RpcStatus::not_implemented("Not implemented by the mock"))
+    }
+
+    async fn sync_utxos(&self, request: Request<SyncUtxosRequest>) -> Result<Streaming<SyncUtxosResponse>, RpcStatus> {
+        let start = request.into_message().start;
+        acquire_lock!(self.state.sync_utxos_calls).push(start);
+
+        let outputs = acquire_lock!(self.state.outputs)
+            .iter()
+            .skip(start as usize)
+            .cloned()
+            .collect::<Vec<_>>();
+        let batches = outputs.chunks(MOCK_SYNC_UTXOS_BATCH_SIZE);
+        let (mut tx, rx) = mpsc::channel(batches.len());
+        for batch in batches {
+            let response = SyncUtxosResponse {
+                utxos: batch
+                    .iter()
+                    .map(|output| SyncUtxo {
+                        output: Some(output.clone().into()),
+                        hash: Vec::new(),
+                        rangeproof_hash: Vec::new(),
+                    })
+                    .collect(),
+                deleted_bitmaps: Vec::new(),
+            };
+            tx.try_send(Ok(response))
+                .map_err(|_| RpcStatus::general("Mock stream buffer is full"))?;
+        }
+        Ok(Streaming::new(rx))
+    }
 }
 
 #[cfg(test)]
@@ -21,7 +21,12 @@
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use rand::{distributions::Alphanumeric, rngs::OsRng, CryptoRng, Rng};
-use std::{fmt::Debug, iter, thread, time::Duration};
+use std::{
+    fmt::Debug,
+    iter,
+    thread,
+    time::{Duration, Instant},
+};
 use tari_core::transactions::{
     tari_amount::MicroTari,
     transaction::{OutputFeatures, TransactionInput, UnblindedOutput},
@@ -31,6 +36,7 @@ use tari_crypto::{
     commitment::HomomorphicCommitmentFactory,
     keys::{PublicKey as PublicKeyTrait, SecretKey as SecretKeyTrait},
 };
+use tokio::{runtime::Runtime, time::delay_for};
 
 pub fn assert_change<F, T>(mut func: F, to: T, poll_count: usize)
 where
@@ -58,6 +64,18 @@ where
     }
 }
 
+/// Wait until `condition` holds while running `runtime`, failing the test if it does not within 20 seconds
+pub fn wait_until<F: FnMut(&mut Runtime) -> bool>(runtime: &mut Runtime, mut condition: F) {
+    let start = Instant::now();
+    while !condition(runtime) {
+        assert!(
+            start.elapsed() < Duration::from_secs(20),
+            "Condition did not hold within 20 seconds"
+        );
+        runtime.block_on(delay_for(Duration::from_millis(50)));
+    }
+}
+
 pub struct TestParams {
     pub spend_key: PrivateKey,
     pub change_key: PrivateKey,
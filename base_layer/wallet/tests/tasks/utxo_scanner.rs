@@ -0,0 +1,300 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::{
+    output_manager_service::service::setup_output_manager_service,
+    support::{
+        rpc::{publish_base_node_state, BaseNodeWalletRpcMockNode, MockBlock},
+        utils::{make_input, wait_until},
+    },
+};
+use futures::StreamExt;
+use rand::rngs::OsRng;
+use std::sync::{Arc, Mutex};
+use tari_comms::{
+    peer_manager::{NodeIdentity, PeerFeatures},
+    test_utils::node_identity::build_node_identity,
+};
+use tari_core::transactions::{
+    tari_amount::{uT, MicroTari},
+    transaction::Transaction,
+    transaction_protocol::one_sided::OneSidedPaymentKeys,
+    types::CryptoFactories,
+};
+use tari_crypto::commitment::HomomorphicCommitmentFactory;
+use tari_service_framework::reply_channel::{self, Receiver};
+use tari_shutdown::Shutdown;
+use tari_wallet::{
+    base_node_service::handle::BaseNodeEventSender,
+    output_manager_service::{handle::OutputManagerHandle, storage::memory_db::OutputManagerMemoryDatabase},
+    storage::{database::WalletDatabase, memory_db::WalletMemoryDatabase},
+    tasks::utxo_scanner::{UtxoScannerTask, UTXO_SCANNER_HEADER_HASH_KEY, UTXO_SCANNER_HEIGHT_KEY},
+    transaction_service::{
+        error::TransactionServiceError,
+        handle::{TransactionServiceHandle, TransactionServiceRequest, TransactionServiceResponse},
+    },
+};
+use tokio::{runtime::Runtime, sync::broadcast};
+
+/// The requests made to the mock transaction service by the UTXO scanner
+#[derive(Clone, Default)]
+struct TransactionServiceCalls {
+    imported: Arc<Mutex<Vec<MicroTari>>>,
+    submitted: Arc<Mutex<Vec<Transaction>>>,
+}
+
+/// Simple task that records the UTXO imports and transaction submissions made on this channel
+async fn ts_reply_channel_task(
+    mut receiver: Receiver<TransactionServiceRequest, Result<TransactionServiceResponse, TransactionServiceError>>,
+    calls: TransactionServiceCalls,
+)
+{
+    let mut next_tx_id = 0;
+    while let Some(request_context) = receiver.next().await {
+        let (request, reply_tx) = request_context.split();
+        let response = match request {
+            TransactionServiceRequest::ImportUtxo(value, _, _) => {
+                calls.imported.lock().unwrap().push(value);
+                next_tx_id += 1;
+                Ok(TransactionServiceResponse::UtxoImported(next_tx_id))
+            },
+            TransactionServiceRequest::SubmitTransaction((_, tx, _, _, _)) => {
+                calls.submitted.lock().unwrap().push(tx);
+                Ok(TransactionServiceResponse::TransactionSubmitted)
+            },
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        };
+
+        let _ = reply_tx.send(response);
+    }
+}
+
+struct ScannerTestContext {
+    wallet_identity: Arc<NodeIdentity>,
+    wallet_db: WalletDatabase<WalletMemoryDatabase>,
+    events: BaseNodeEventSender,
+    calls: TransactionServiceCalls,
+    sender_oms: OutputManagerHandle,
+    base_node: BaseNodeWalletRpcMockNode,
+    _shutdown: Shutdown,
+    _oms_shutdown: Shutdown,
+    _sender_oms_shutdown: Shutdown,
+}
+
+impl ScannerTestContext {
+    /// Publish a base node state update, which makes the scanner scan up to the mock base node's tip
+    fn publish_state(&self) {
+        publish_base_node_state(&self.events, None);
+    }
+
+    /// Create a block containing a one-sided payment of `amount` to the scanner's wallet
+    fn payment_block(&self, runtime: &mut Runtime, header_hash: u8, amount: MicroTari) -> MockBlock {
+        let mut sender_oms = self.sender_oms.clone();
+        let (_, _, tx) = runtime
+            .block_on(sender_oms.create_one_sided_transaction(
+                self.wallet_identity.public_key().clone(),
+                amount,
+                MicroTari::from(25),
+                None,
+                "one-sided".to_string(),
+            ))
+            .unwrap();
+        MockBlock {
+            header_hash: vec![header_hash; 32],
+            outputs: tx.body.outputs().clone(),
+            kernels: tx.body.kernels().clone(),
+        }
+    }
+
+    fn scanned_block(&self, runtime: &mut Runtime) -> Option<(String, String)> {
+        let height = runtime
+            .block_on(self.wallet_db.get_client_key_value(UTXO_SCANNER_HEIGHT_KEY.to_string()))
+            .unwrap()?;
+        let header_hash = runtime
+            .block_on(
+                self.wallet_db
+                    .get_client_key_value(UTXO_SCANNER_HEADER_HASH_KEY.to_string()),
+            )
+            .unwrap()?;
+        Some((height, header_hash))
+    }
+
+    fn imported(&self) -> Vec<MicroTari> {
+        self.calls.imported.lock().unwrap().clone()
+    }
+}
+
+fn empty_block(header_hash: u8) -> MockBlock {
+    MockBlock {
+        header_hash: vec![header_hash; 32],
+        ..Default::default()
+    }
+}
+
+/// Spawn a UTXO scanner for a wallet with an empty database, connected to a mock base node
+fn setup_scanner(runtime: &mut Runtime) -> ScannerTestContext {
+    setup_scanner_with_db(runtime, WalletDatabase::new(WalletMemoryDatabase::new()))
+}
+
+fn setup_scanner_with_db(runtime: &mut Runtime, wallet_db: WalletDatabase<WalletMemoryDatabase>) -> ScannerTestContext {
+    let factories = CryptoFactories::default();
+    let shutdown = Shutdown::new();
+
+    let (oms, oms_shutdown, _, _, _, _, _) =
+        setup_output_manager_service(runtime, OutputManagerMemoryDatabase::new(), false);
+    let (mut sender_oms, sender_oms_shutdown, _, _, _, _, _) =
+        setup_output_manager_service(runtime, OutputManagerMemoryDatabase::new(), false);
+    for _ in 0..5 {
+        let (_, uo) = make_input(&mut OsRng, 100_000 * uT, &factories.commitment);
+        runtime.block_on(sender_oms.add_output(uo)).unwrap();
+    }
+
+    let (ts_request_sender, ts_request_receiver) = reply_channel::unbounded();
+    let (ts_event_publisher, _) = broadcast::channel(100);
+    let calls = TransactionServiceCalls::default();
+    runtime.spawn(ts_reply_channel_task(ts_request_receiver, calls.clone()));
+    let ts_handle = TransactionServiceHandle::new(ts_request_sender, ts_event_publisher);
+
+    let (base_node, connectivity) = BaseNodeWalletRpcMockNode::spawn(runtime);
+
+    let wallet_identity = build_node_identity(PeerFeatures::COMMUNICATION_CLIENT);
+    let (events, event_receiver) = broadcast::channel(100);
+    let scanner = UtxoScannerTask::new(
+        wallet_db.clone(),
+        connectivity,
+        oms,
+        ts_handle,
+        event_receiver.fuse(),
+        factories,
+        wallet_identity.clone(),
+        shutdown.to_signal(),
+    );
+    runtime.spawn(scanner.run());
+    base_node.publish_peer(&events);
+
+    ScannerTestContext {
+        wallet_identity,
+        wallet_db,
+        events,
+        calls,
+        sender_oms,
+        base_node,
+        _shutdown: shutdown,
+        _oms_shutdown: oms_shutdown,
+        _sender_oms_shutdown: sender_oms_shutdown,
+    }
+}
+
+#[test]
+fn scanner_finds_and_claims_one_sided_payments() {
+    let mut runtime = Runtime::new().unwrap();
+    let factories = CryptoFactories::default();
+    let context = setup_scanner(&mut runtime);
+
+    // The first run starts at the tip
+    context.base_node.rpc_state.set_blocks(vec![empty_block(0)]);
+    context.publish_state();
+    wait_until(&mut runtime, |rt| context.scanned_block(rt).is_some());
+    assert_eq!(
+        context.scanned_block(&mut runtime),
+        Some(("0".to_string(), "00".repeat(32)))
+    );
+
+    let amount = 5_000 * uT;
+    let block = context.payment_block(&mut runtime, 1, amount);
+    let public_nonce = block.kernels[0].excess_sig.get_public_nonce().clone();
+    context.base_node.rpc_state.set_blocks(vec![empty_block(0), block]);
+    context.publish_state();
+    wait_until(&mut runtime, |_| !context.calls.submitted.lock().unwrap().is_empty());
+    assert_eq!(context.imported(), vec![amount]);
+
+    // The payment is claimed into a new key held by the signer, not the key the sender knows
+    let keys = OneSidedPaymentKeys::for_recipient(context.wallet_identity.secret_key(), &public_nonce).unwrap();
+    let claim_tx = context.calls.submitted.lock().unwrap()[0].clone();
+    assert_eq!(claim_tx.body.outputs().len(), 1);
+    claim_tx.validate_internal_consistency(&factories, None).unwrap();
+    let claim_value = amount - claim_tx.body.get_total_fee();
+    assert_ne!(
+        claim_tx.body.outputs()[0].commitment,
+        factories
+            .commitment
+            .commit_value(&keys.spending_key, claim_value.into())
+    );
+    wait_until(&mut runtime, |rt| {
+        context.scanned_block(rt) == Some(("1".to_string(), "01".repeat(32)))
+    });
+}
+
+#[test]
+fn scanner_resumes_from_the_last_scanned_block() {
+    let mut runtime = Runtime::new().unwrap();
+    let wallet_db = WalletDatabase::new(WalletMemoryDatabase::new());
+    runtime
+        .block_on(wallet_db.set_client_key_value(UTXO_SCANNER_HEIGHT_KEY.to_string(), "1".to_string()))
+        .unwrap();
+    runtime
+        .block_on(wallet_db.set_client_key_value(UTXO_SCANNER_HEADER_HASH_KEY.to_string(), "01".repeat(32)))
+        .unwrap();
+    let context = setup_scanner_with_db(&mut runtime, wallet_db);
+
+    // The payment in block 1 was scanned by an earlier run
+    let block1 = context.payment_block(&mut runtime, 1, 1_000 * uT);
+    let block2 = context.payment_block(&mut runtime, 2, 2_000 * uT);
+    context
+        .base_node
+        .rpc_state
+        .set_blocks(vec![empty_block(0), block1, block2]);
+    context.publish_state();
+
+    wait_until(&mut runtime, |rt| {
+        context.scanned_block(rt) == Some(("2".to_string(), "02".repeat(32)))
+    });
+    assert_eq!(context.imported(), vec![2_000 * uT]);
+}
+
+#[test]
+fn scanner_rescans_after_a_reorg() {
+    let mut runtime = Runtime::new().unwrap();
+    let context = setup_scanner(&mut runtime);
+
+    context
+        .base_node
+        .rpc_state
+        .set_blocks(vec![empty_block(0), empty_block(1)]);
+    context.publish_state();
+    wait_until(&mut runtime, |rt| {
+        context.scanned_block(rt) == Some(("1".to_string(), "01".repeat(32)))
+    });
+
+    // Block 1 is replaced by a block containing a payment, which is found even though the scanned height is unchanged
+    let amount = 3_000 * uT;
+    let block = context.payment_block(&mut runtime, 0xaa, amount);
+    context
+        .base_node
+        .rpc_state
+        .set_blocks(vec![empty_block(0), block, empty_block(2)]);
+    context.publish_state();
+    wait_until(&mut runtime, |rt| {
+        context.scanned_block(rt) == Some(("2".to_string(), "02".repeat(32)))
+    });
+    assert_eq!(context.imported(), vec![amount]);
+}
@@ -0,0 +1,150 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::support::{
+    rpc::{publish_base_node_state, BaseNodeWalletRpcMockNode, MockBlock},
+    utils::{make_input, wait_until},
+};
+use futures::StreamExt;
+use rand::rngs::OsRng;
+use tari_common_types::chain_metadata::ChainMetadata;
+use tari_core::transactions::{
+    tari_amount::uT,
+    transaction::TransactionOutput,
+    types::{CryptoFactories, PublicKey},
+};
+use tari_crypto::keys::PublicKey as PublicKeyTrait;
+use tari_shutdown::Shutdown;
+use tari_wallet::{
+    base_node_service::handle::BaseNodeEventSender,
+    storage::{database::WalletDatabase, memory_db::WalletMemoryDatabase},
+    tasks::watch_only_scanner::WatchOnlyScannerTask,
+    watch_only::{add_watched_commitments, get_watched_outputs, set_watch_only_keys, WatchOnlyKeys},
+};
+use tokio::{runtime::Runtime, sync::broadcast};
+
+struct ScannerTestContext {
+    base_node: BaseNodeWalletRpcMockNode,
+    wallet_db: WalletDatabase<WalletMemoryDatabase>,
+    events: BaseNodeEventSender,
+    _shutdown: Shutdown,
+}
+
+impl ScannerTestContext {
+    /// Serve `blocks` from the mock base node and publish its tip, which makes the scanner scan up to it
+    fn set_chain(&self, blocks: Vec<MockBlock>) {
+        let tip = blocks.last().unwrap();
+        let chain_metadata = ChainMetadata::new((blocks.len() - 1) as u64, tip.header_hash.clone(), 0, 0, 0);
+        self.base_node.rpc_state.set_blocks(blocks);
+        publish_base_node_state(&self.events, Some(chain_metadata));
+    }
+
+    fn scanned_block(&self, runtime: &mut Runtime) -> (Option<u64>, Option<Vec<u8>>) {
+        let height = runtime
+            .block_on(self.wallet_db.get_watch_only_scanned_height())
+            .unwrap();
+        let header_hash = runtime
+            .block_on(self.wallet_db.get_watch_only_scanned_header_hash())
+            .unwrap();
+        (height, header_hash)
+    }
+
+    fn mined_height(&self, runtime: &mut Runtime) -> Option<u64> {
+        runtime.block_on(get_watched_outputs(&self.wallet_db)).unwrap()[0].mined_height
+    }
+}
+
+fn block(header_hash: u8, outputs: Vec<TransactionOutput>) -> MockBlock {
+    MockBlock {
+        header_hash: vec![header_hash; 32],
+        outputs,
+        ..Default::default()
+    }
+}
+
+/// Spawn a watch-only scanner watching `output`, connected to a mock base node
+fn setup_scanner(runtime: &mut Runtime, output: &TransactionOutput) -> ScannerTestContext {
+    let factories = CryptoFactories::default();
+    let shutdown = Shutdown::new();
+
+    let wallet_db = WalletDatabase::new(WalletMemoryDatabase::new());
+    let keys = WatchOnlyKeys {
+        rewind_public_key: PublicKey::random_keypair(&mut OsRng).1,
+        rewind_blinding_public_key: PublicKey::random_keypair(&mut OsRng).1,
+    };
+    runtime.block_on(set_watch_only_keys(&wallet_db, &keys, 0)).unwrap();
+    runtime
+        .block_on(add_watched_commitments(&wallet_db, vec![output.commitment.clone()]))
+        .unwrap();
+
+    let (base_node, connectivity) = BaseNodeWalletRpcMockNode::spawn(runtime);
+    // The watched output stays in the UTXO set
+    base_node.rpc_state.set_utxos(vec![output.clone()]);
+
+    let (events, event_receiver) = broadcast::channel(100);
+    let scanner = WatchOnlyScannerTask::new(
+        wallet_db.clone(),
+        connectivity,
+        event_receiver.fuse(),
+        factories,
+        keys,
+        shutdown.to_signal(),
+    );
+    runtime.spawn(scanner.run());
+    base_node.publish_peer(&events);
+
+    ScannerTestContext {
+        base_node,
+        wallet_db,
+        events,
+        _shutdown: shutdown,
+    }
+}
+
+#[test]
+fn watch_only_scanner_rescans_after_a_reorg() {
+    let mut runtime = Runtime::new().unwrap();
+    let factories = CryptoFactories::default();
+    let (_, uo) = make_input(&mut OsRng, 1_000 * uT, &factories.commitment);
+    let output = uo.as_transaction_output(&factories).unwrap();
+    let context = setup_scanner(&mut runtime, &output);
+
+    context.set_chain(vec![block(0, vec![]), block(1, vec![output.clone()])]);
+    wait_until(&mut runtime, |rt| {
+        context.scanned_block(rt) == (Some(1), Some(vec![1; 32]))
+    });
+    assert_eq!(context.mined_height(&mut runtime), Some(1));
+
+    // Block 1 is replaced by a block at the same height that does not contain the output
+    context.set_chain(vec![block(0, vec![]), block(2, vec![])]);
+    wait_until(&mut runtime, |rt| {
+        context.scanned_block(rt) == (Some(1), Some(vec![2; 32]))
+    });
+    assert_eq!(context.mined_height(&mut runtime), None);
+
+    // The output is mined again on the new chain
+    context.set_chain(vec![block(0, vec![]), block(2, vec![]), block(3, vec![output])]);
+    wait_until(&mut runtime, |rt| {
+        context.scanned_block(rt) == (Some(2), Some(vec![3; 32]))
+    });
+    assert_eq!(context.mined_height(&mut runtime), Some(2));
+}
@@ -69,9 +69,8 @@ use tari_core::{
     consensus::{ConsensusConstantsBuilder, Network},
     proto::base_node as base_node_proto,
     transactions::{
-        fee::Fee,
         tari_amount::*,
-        transaction::{KernelBuilder, KernelFeatures, OutputFeatures, Transaction, UnblindedOutput},
+        transaction::{KernelBuilder, OutputFeatures, Transaction, UnblindedOutput},
         transaction_protocol::{proto, recipient::RecipientSignedMessage, sender::TransactionSenderMessage},
         types::{CryptoFactories, PrivateKey, PublicKey, Signature},
         ReceiverTransactionProtocol,
@@ -80,7 +79,6 @@ use tari_core::{
 };
 use tari_crypto::{
     commitment::HomomorphicCommitmentFactory,
-    common::Blake256,
     keys::{PublicKey as PK, SecretKey as SK},
 };
 use tari_p2p::{comms_connector::pubsub_connector, domain_message::DomainMessage};
@@ -334,6 +332,7 @@ pub fn setup_transaction_service_no_comms_and_oms_backend<
             shutdown.to_signal(),
             basenode_service_handle,
             connectivity_manager.clone(),
+            None,
         ))
         .unwrap();
 
@@ -352,6 +351,8 @@ pub fn setup_transaction_service_no_comms_and_oms_backend<
         ..Default::default()
     });
 
+    let (_, dht_event_receiver) = broadcast::channel(10);
+
     let ts_service = TransactionService::new(
         test_config,
         TransactionDatabase::new(tx_backend),
@@ -361,6 +362,7 @@ pub fn setup_transaction_service_no_comms_and_oms_backend<
         tx_finalized_receiver,
         base_node_response_receiver,
         tx_cancelled_receiver,
+        dht_event_receiver,
         output_manager_service_handle.clone(),
         outbound_message_requester,
         connectivity_manager,
@@ -1018,7 +1020,7 @@ fn test_accepting_unknown_tx_id_and_malformed_reply() {
             futures::select! {
                 event = alice_event_stream.select_next_some() => {
                     if let TransactionEvent::Error(s) = &*event.unwrap() {
-                        if s == &"TransactionProtocolError(TransactionBuildError(InvalidSignatureError))".to_string()
+                        if s == &"OutputManagerError(TransactionProtocolError(TransactionBuildError(InvalidSignatureError)))".to_string()
 {                             errors+=1;
                         }
                         if errors >= 2 {
@@ -1123,7 +1125,7 @@ fn finalize_tx_with_incorrect_pubkey() {
 
     stp.add_single_recipient_info(recipient_reply.clone(), &factories.range_proof)
         .unwrap();
-    stp.finalize(KernelFeatures::empty(), &factories).unwrap();
+    let stp = runtime.block_on(bob_output_manager.finalize_transaction(stp)).unwrap();
     let tx = stp.get_transaction().unwrap();
 
     let finalized_transaction_message = proto::TransactionFinalizedMessage {
@@ -1247,7 +1249,7 @@ fn finalize_tx_with_missing_output() {
 
     stp.add_single_recipient_info(recipient_reply.clone(), &factories.range_proof)
         .unwrap();
-    stp.finalize(KernelFeatures::empty(), &factories).unwrap();
+    runtime.block_on(bob_output_manager.finalize_transaction(stp)).unwrap();
 
     let finalized_transaction_message = proto::TransactionFinalizedMessage {
         tx_id: recipient_reply.tx_id,
@@ -2329,22 +2331,30 @@ fn test_restarting_transaction_protocols() {
         NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE).unwrap(),
     );
 
-    // Bob is going to send a transaction to Alice
+    // Bob is going to send a transaction to Alice. Bob's transaction is built by his Output Manager, whose state is
+    // kept for his restarted node so that it can finalize the transaction.
     let alice = TestParams::new(&mut OsRng);
-    let bob = TestParams::new(&mut OsRng);
-    let (utxo, input) = make_input(&mut OsRng, MicroTari(2000), &factories.commitment);
-    let mut builder = SenderTransactionProtocol::builder(1);
-    let fee = Fee::calculate(MicroTari(20), 1, 1, 1);
-    builder
-        .with_lock_height(0)
-        .with_fee_per_gram(MicroTari(20))
-        .with_offset(bob.offset.clone())
-        .with_private_nonce(bob.nonce)
-        .with_input(utxo, input)
-        .with_amount(0, MicroTari(2000) - fee - MicroTari(10));
-    let mut bob_stp = builder.build::<Blake256>(&factories).unwrap();
+    let bob_oms_backend = OutputManagerMemoryDatabase::new();
+    let (bob_first_backend, _temp_dir3) = make_transaction_database(None);
+    let (_, mut bob_oms, _, _, _, _, _, _, _, _bob_first_shutdown, _, _, _) =
+        setup_transaction_service_no_comms_and_oms_backend(
+            &mut runtime,
+            factories.clone(),
+            bob_first_backend,
+            bob_oms_backend.clone(),
+            None,
+        );
+    let (_utxo, input) = make_input(&mut OsRng, MicroTari(2000), &factories.commitment);
+    runtime.block_on(bob_oms.add_output(input)).unwrap();
+    let mut bob_stp = runtime
+        .block_on(bob_oms.prepare_transaction_to_send(MicroTari(1000), MicroTari(20), None, "".to_string()))
+        .unwrap();
+    let fee = bob_stp.get_fee_amount().unwrap();
     let msg = bob_stp.build_single_round_message().unwrap();
     let bob_pre_finalize = bob_stp.clone();
+    runtime
+        .block_on(bob_oms.confirm_pending_transaction(msg.tx_id))
+        .unwrap();
 
     let tx_id = msg.tx_id;
 
@@ -2363,10 +2373,7 @@ fn test_restarting_transaction_protocols() {
         .add_single_recipient_info(alice_reply.clone(), &factories.range_proof)
         .unwrap();
 
-    match bob_stp.finalize(KernelFeatures::empty(), &factories) {
-        Ok(_) => (),
-        _ => panic!("Should be able to finalize tx"),
-    };
+    let bob_stp = runtime.block_on(bob_oms.finalize_transaction(bob_stp)).unwrap();
     let tx = bob_stp.get_transaction().unwrap().clone();
 
     let inbound_tx = InboundTransaction {
@@ -2413,7 +2420,13 @@ fn test_restarting_transaction_protocols() {
 
     // Test that Bob's node restarts the send protocol
     let (mut bob_ts, _bob_oms, _bob_outbound_service, _, _, mut bob_tx_reply, _, _, _, _shutdown, _, _, _) =
-        setup_transaction_service_no_comms(&mut runtime, factories.clone(), bob_backend, None);
+        setup_transaction_service_no_comms_and_oms_backend(
+            &mut runtime,
+            factories.clone(),
+            bob_backend,
+            bob_oms_backend,
+            None,
+        );
     let mut bob_event_stream = bob_ts.get_event_stream_fused();
 
     runtime
@@ -20,39 +20,67 @@
 //  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use crate::support::utils::{make_input, random_string};
+use crate::support::{
+    rpc::BaseNodeSyncRpcMockService,
+    utils::{make_input, random_string},
+};
 use rand::rngs::OsRng;
 use std::{panic, sync::Arc, time::Duration};
 use tari_comms::{
     multiaddr::Multiaddr,
     peer_manager::{NodeId, NodeIdentity, Peer, PeerFeatures, PeerFlags},
+    protocol::rpc::RpcServer,
+    transports::MemoryTransport,
     types::CommsPublicKey,
+    CommsBuilder,
+    CommsNode,
 };
 use tari_comms_dht::DhtConfig;
 use tari_core::transactions::{tari_amount::MicroTari, types::CryptoFactories};
-use tari_crypto::keys::PublicKey;
+use tari_crypto::keys::{PublicKey, SecretKey};
 use tari_p2p::initialization::CommsConfig;
 use tari_shutdown::{Shutdown, ShutdownSignal};
+use tari_storage::{
+    lmdb_store::{LMDBBuilder, LMDBConfig},
+    LMDBWrapper,
+};
 
 use crate::support::comms_and_services::get_next_memory_address;
 use aes_gcm::{
     aead::{generic_array::GenericArray, NewAead},
     Aes256Gcm,
 };
+use diesel::{Connection, RunQueryDsl, SqliteConnection};
 use digest::Digest;
 use futures::{FutureExt, StreamExt};
 use std::path::Path;
 use tari_common_types::chain_metadata::ChainMetadata;
 use tari_core::{
+    base_node::sync::rpc::BaseNodeSyncRpcServer,
     consensus::Network,
-    transactions::{tari_amount::uT, transaction::UnblindedOutput, types::PrivateKey},
+    transactions::{
+        tari_amount::uT,
+        transaction::{OutputFeatures, UnblindedOutput},
+        types::PrivateKey,
+    },
 };
-use tari_crypto::common::Blake256;
+use tari_crypto::{common::Blake256, tari_utilities::Hashable};
 use tari_p2p::{transport::TransportType, DEFAULT_DNS_SEED_RESOLVER};
 use tari_wallet::{
     contacts_service::storage::{database::Contact, memory_db::ContactsServiceMemoryDatabase},
     error::{WalletError, WalletStorageError},
-    output_manager_service::storage::memory_db::OutputManagerMemoryDatabase,
+    output_manager_service::{
+        signer::{in_process::InProcessSigner, KeyBranch, KeyId, MemoryKernelSigningStore, TransactionSigner},
+        storage::{
+            database::{
+                DbKeyValuePair as OmsDbKeyValuePair,
+                KeyManagerState,
+                OutputManagerBackend,
+                WriteOperation as OmsWriteOperation,
+            },
+            memory_db::OutputManagerMemoryDatabase,
+        },
+    },
     storage::{
         database::{DbKeyValuePair, WalletBackend, WalletDatabase, WriteOperation},
         memory_db::WalletMemoryDatabase,
@@ -63,6 +91,14 @@ use tari_wallet::{
             run_migration_and_create_sqlite_connection,
         },
     },
+    tasks::wallet_recovery::{
+        is_recovery_in_progress,
+        WalletRecoveryEvent,
+        WalletRecoveryTask,
+        RECOVERY_INDEX_KEY,
+        RECOVERY_NUM_UTXOS_KEY,
+        RECOVERY_TOTAL_AMOUNT_KEY,
+    },
     test_utils::make_transaction_database,
     transaction_service::{config::TransactionServiceConfig, handle::TransactionEvent},
     wallet::WalletConfig,
@@ -70,7 +106,7 @@ use tari_wallet::{
     WalletSqlite,
 };
 use tempfile::tempdir;
-use tokio::{runtime::Runtime, time::delay_for};
+use tokio::{runtime::Runtime, sync::broadcast, time::delay_for};
 
 fn create_peer(public_key: CommsPublicKey, net_address: Multiaddr) -> Peer {
     Peer::new(
@@ -116,6 +152,8 @@ async fn create_wallet(
         peer_seeds: Default::default(),
         dns_seeds: Default::default(),
         dns_seeds_use_dnssec: false,
+        max_bandwidth_bytes_per_sec: None,
+        max_peer_bandwidth_bytes_per_sec: None,
     };
 
     let sql_database_path = comms_config
@@ -597,6 +635,8 @@ async fn test_import_utxo() {
         peer_seeds: Default::default(),
         dns_seeds: Default::default(),
         dns_seeds_use_dnssec: false,
+        max_bandwidth_bytes_per_sec: None,
+        max_peer_bandwidth_bytes_per_sec: None,
     };
     let config = WalletConfig::new(
         comms_config,
@@ -679,6 +719,8 @@ async fn test_data_generation() {
         peer_seeds: Default::default(),
         dns_seeds: Default::default(),
         dns_seeds_use_dnssec: false,
+        max_bandwidth_bytes_per_sec: None,
+        max_peer_bandwidth_bytes_per_sec: None,
     };
 
     let config = WalletConfig::new(comms_config, factories, None, None, Network::Stibbons, None, None, None);
@@ -743,3 +785,302 @@ fn test_db_file_locking() {
 
     assert!(run_migration_and_create_sqlite_connection(&wallet_path).is_ok());
 }
+
+/// Spawns a node that only serves the base node sync RPC service, backed by the given mock
+async fn spawn_sync_rpc_base_node(
+    service: BaseNodeSyncRpcMockService,
+    data_path: &Path,
+    shutdown_signal: ShutdownSignal,
+) -> CommsNode
+{
+    let datastore = LMDBBuilder::new()
+        .set_path(data_path.to_str().unwrap())
+        .set_env_config(LMDBConfig::default())
+        .set_max_number_of_databases(1)
+        .add_database("peerdb", lmdb_zero::db::CREATE)
+        .build()
+        .unwrap();
+    let peer_database = LMDBWrapper::new(Arc::new(datastore.get_handle("peerdb").unwrap()));
+    let node_identity =
+        NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE).unwrap();
+    CommsBuilder::new()
+        .allow_test_addresses()
+        .with_listener_address(node_identity.public_address())
+        .with_node_identity(Arc::new(node_identity))
+        .with_peer_storage(peer_database, None)
+        .with_shutdown_signal(shutdown_signal)
+        .build()
+        .unwrap()
+        .add_rpc_server(RpcServer::new().add_service(BaseNodeSyncRpcServer::new(service)))
+        .spawn_with_transport(MemoryTransport)
+        .await
+        .unwrap()
+}
+
+fn drain_recovery_events(events: &mut broadcast::Receiver<WalletRecoveryEvent>) -> Vec<WalletRecoveryEvent> {
+    let mut drained = Vec::new();
+    while let Ok(event) = events.try_recv() {
+        drained.push(event);
+    }
+    drained
+}
+
+#[tokio_macros::test]
+async fn test_wallet_recovery_failure_keeps_checkpoint() {
+    let mut shutdown = Shutdown::new();
+    let factories = CryptoFactories::default();
+    let db_tempdir = tempdir().unwrap();
+    let wallet_identity =
+        NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE).unwrap();
+    let wallet = create_wallet(
+        wallet_identity,
+        &db_tempdir.path(),
+        "recovery_db",
+        factories,
+        shutdown.to_signal(),
+        None,
+    )
+    .await;
+    let db = wallet.db.clone();
+
+    // A base node that is not listening
+    let base_node_identity =
+        NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE).unwrap();
+    wallet
+        .comms
+        .peer_manager()
+        .add_peer(base_node_identity.to_peer())
+        .await
+        .unwrap();
+
+    let recovery_task =
+        WalletRecoveryTask::new(wallet.clone(), vec![base_node_identity.public_key().clone()]).with_retry_limit(1);
+    let mut events = recovery_task.get_event_receiver();
+    assert!(recovery_task.run().await.is_err());
+
+    let events = drain_recovery_events(&mut events);
+    assert!(matches!(events[0], WalletRecoveryEvent::ConnectingToBaseNode(_)));
+    assert!(matches!(
+        events[1],
+        WalletRecoveryEvent::ConnectionFailedToBaseNode(_, 1, 1, _)
+    ));
+    assert!(matches!(events[2], WalletRecoveryEvent::RecoveryFailed(_)));
+
+    // The checkpoint written before the first connection attempt marks the recovery as unfinished
+    assert!(is_recovery_in_progress(&db).await.unwrap());
+    assert_eq!(
+        db.get_client_key_value(RECOVERY_INDEX_KEY.to_string()).await.unwrap(),
+        Some("0".to_string())
+    );
+
+    shutdown.trigger().unwrap();
+    wallet.wait_until_shutdown().await;
+}
+
+#[tokio_macros::test]
+async fn test_wallet_recovery_resumes_from_checkpoint() {
+    let mut shutdown = Shutdown::new();
+    let factories = CryptoFactories::default();
+    let db_tempdir = tempdir().unwrap();
+    let wallet_identity =
+        NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE).unwrap();
+    let wallet = create_wallet(
+        wallet_identity,
+        &db_tempdir.path(),
+        "recovery_db",
+        factories.clone(),
+        shutdown.to_signal(),
+        None,
+    )
+    .await;
+    let db = wallet.db.clone();
+
+    // Outputs that do not belong to the wallet
+    let outputs = (0..5)
+        .map(|_| {
+            make_input(&mut OsRng, MicroTari::from(1000), &factories.commitment)
+                .1
+                .as_transaction_output(&factories)
+                .unwrap()
+        })
+        .collect();
+    let service = BaseNodeSyncRpcMockService::new();
+    let service_state = service.get_state();
+    service_state.set_outputs(outputs);
+    let base_node_tempdir = tempdir().unwrap();
+    let base_node = spawn_sync_rpc_base_node(service, base_node_tempdir.path(), shutdown.to_signal()).await;
+    wallet
+        .comms
+        .peer_manager()
+        .add_peer(base_node.node_identity().to_peer())
+        .await
+        .unwrap();
+
+    // An earlier run scanned the first two outputs and recovered one of them
+    db.set_client_key_value(RECOVERY_INDEX_KEY.to_string(), "2".to_string())
+        .await
+        .unwrap();
+    db.set_client_key_value(RECOVERY_NUM_UTXOS_KEY.to_string(), "1".to_string())
+        .await
+        .unwrap();
+    db.set_client_key_value(RECOVERY_TOTAL_AMOUNT_KEY.to_string(), "5000".to_string())
+        .await
+        .unwrap();
+
+    let recovery_task = WalletRecoveryTask::new(wallet.clone(), vec![base_node.node_identity().public_key().clone()]);
+    let mut events = recovery_task.get_event_receiver();
+    recovery_task.run().await.unwrap();
+
+    assert_eq!(service_state.take_sync_utxos_calls(), vec![2]);
+    let events = drain_recovery_events(&mut events);
+    let progress = events
+        .iter()
+        .filter_map(|e| match e {
+            WalletRecoveryEvent::Progress(scanned, total) => Some((*scanned, *total)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(progress, vec![(2, 5), (4, 5), (5, 5)]);
+    assert_eq!(
+        events.last().unwrap(),
+        &WalletRecoveryEvent::Completed(1, MicroTari::from(5000))
+    );
+    assert!(!is_recovery_in_progress(&db).await.unwrap());
+
+    shutdown.trigger().unwrap();
+    wallet.wait_until_shutdown().await;
+}
+
+#[tokio_macros::test]
+async fn test_wallet_recovery_retries_failed_imports() {
+    let mut shutdown = Shutdown::new();
+    let factories = CryptoFactories::default();
+    let db_tempdir = tempdir().unwrap();
+    let db_path = db_tempdir.path().join("recovery_db").with_extension("sqlite3");
+
+    // Start the wallet from a known master key so that outputs that belong to it can be created outside the wallet
+    let master_key = PrivateKey::random(&mut OsRng);
+    {
+        let (_, _, output_manager_backend, _) = initialize_sqlite_database_backends(db_path.clone(), None).unwrap();
+        output_manager_backend
+            .write(OmsWriteOperation::Insert(OmsDbKeyValuePair::KeyManagerState(
+                KeyManagerState {
+                    master_key: master_key.clone(),
+                    branch_seed: "".to_string(),
+                    primary_key_index: 0,
+                },
+            )))
+            .unwrap();
+    }
+    let signer = InProcessSigner::new(
+        master_key,
+        "".to_string(),
+        factories.clone(),
+        Arc::new(MemoryKernelSigningStore::new()),
+    )
+    .unwrap();
+    let mut outputs = Vec::new();
+    for i in 0..3 {
+        let output = signer
+            .create_output(
+                KeyId::new(KeyBranch::Spend, i),
+                MicroTari::from(1000 * (i + 1)),
+                OutputFeatures::default(),
+            )
+            .await
+            .unwrap();
+        outputs.push(output);
+    }
+
+    let wallet_identity =
+        NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE).unwrap();
+    let mut wallet = create_wallet(
+        wallet_identity,
+        &db_tempdir.path(),
+        "recovery_db",
+        factories,
+        shutdown.to_signal(),
+        None,
+    )
+    .await;
+    let db = wallet.db.clone();
+
+    let service = BaseNodeSyncRpcMockService::new();
+    let service_state = service.get_state();
+    service_state.set_outputs(outputs.clone());
+    let base_node_tempdir = tempdir().unwrap();
+    let base_node = spawn_sync_rpc_base_node(service, base_node_tempdir.path(), shutdown.to_signal()).await;
+    wallet
+        .comms
+        .peer_manager()
+        .add_peer(base_node.node_identity().to_peer())
+        .await
+        .unwrap();
+
+    // Fail the import of the second output of the first batch
+    let failing_tx_id = {
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&outputs[1].hash()[..8]);
+        u64::from_le_bytes(bytes) as i64
+    };
+    let conn = SqliteConnection::establish(db_path.to_str().unwrap()).unwrap();
+    diesel::sql_query(format!(
+        "CREATE TRIGGER fail_import BEFORE INSERT ON completed_transactions WHEN NEW.tx_id = {} BEGIN SELECT \
+         RAISE(ABORT, 'import failed'); END;",
+        failing_tx_id
+    ))
+    .execute(&conn)
+    .unwrap();
+
+    let recovery_task = WalletRecoveryTask::new(wallet.clone(), vec![base_node.node_identity().public_key().clone()])
+        .with_retry_limit(1);
+    assert!(recovery_task.run().await.is_err());
+
+    // The first output was recovered, but the batch was not checkpointed
+    assert_eq!(
+        db.get_client_key_value(RECOVERY_INDEX_KEY.to_string()).await.unwrap(),
+        Some("0".to_string())
+    );
+    assert_eq!(
+        db.get_client_key_value(RECOVERY_NUM_UTXOS_KEY.to_string())
+            .await
+            .unwrap(),
+        Some("1".to_string())
+    );
+
+    diesel::sql_query("DROP TRIGGER fail_import").execute(&conn).unwrap();
+
+    let recovery_task = WalletRecoveryTask::new(wallet.clone(), vec![base_node.node_identity().public_key().clone()]);
+    let mut events = recovery_task.get_event_receiver();
+    recovery_task.run().await.unwrap();
+
+    assert_eq!(service_state.take_sync_utxos_calls(), vec![0, 0]);
+    let events = drain_recovery_events(&mut events);
+    assert_eq!(
+        events.last().unwrap(),
+        &WalletRecoveryEvent::Completed(3, MicroTari::from(6000))
+    );
+
+    // Every recovered output has exactly one transaction record
+    let completed_transactions = wallet.transaction_service.get_completed_transactions().await.unwrap();
+    assert_eq!(completed_transactions.len(), 3);
+    let mut amounts = completed_transactions.values().map(|tx| tx.amount).collect::<Vec<_>>();
+    amounts.sort();
+    assert_eq!(amounts, vec![
+        MicroTari::from(1000),
+        MicroTari::from(2000),
+        MicroTari::from(3000)
+    ]);
+    assert_eq!(
+        wallet
+            .output_manager_service
+            .get_balance()
+            .await
+            .unwrap()
+            .available_balance,
+        MicroTari::from(6000)
+    );
+
+    shutdown.trigger().unwrap();
+    wallet.wait_until_shutdown().await;
+}
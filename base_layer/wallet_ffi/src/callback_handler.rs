@@ -47,13 +47,20 @@
 //! `callback_base_node_sync_complete` - This is called when a Base Node Sync process is completed or times out. The
 //! request_key is used to identify which request this callback references and a result of true means it was successful
 //! and false that the process timed out and new one will be started
+//!
+//! `callback_base_node_state` - This is called whenever the wallet receives updated chain state from its Base Node.
+//! The chain tip height is provided (0 if it is not yet known) along with whether the Base Node reports that it is
+//! synced. It is optional and is registered after the wallet has been created, so it is held in a shared slot that can
+//! be filled in while the handler is running
 
 use futures::{stream::Fuse, StreamExt};
 use log::*;
+use std::sync::{Arc, Mutex};
 use tari_comms::types::CommsPublicKey;
 use tari_comms_dht::event::{DhtEvent, DhtEventReceiver};
 use tari_shutdown::ShutdownSignal;
 use tari_wallet::{
+    base_node_service::handle::{BaseNodeEvent, BaseNodeEventReceiver},
     output_manager_service::{
         handle::{OutputManagerEvent, OutputManagerEventReceiver},
         protocols::txo_validation_protocol::TxoValidationType,
@@ -70,6 +77,9 @@ use tari_wallet::{
 
 const LOG_TARGET: &str = "wallet::transaction_service::callback_handler";
 
+/// A shared slot for the optional Base Node state callback
+pub type BaseNodeStateCallback = Arc<Mutex<Option<unsafe extern "C" fn(u64, bool)>>>;
+
 #[derive(Clone, Copy)]
 enum CallbackValidationResults {
     Success,           // 0
@@ -95,10 +105,12 @@ where TBackend: TransactionBackend + 'static
     callback_invalid_txo_validation_complete: unsafe extern "C" fn(u64, u8),
     callback_transaction_validation_complete: unsafe extern "C" fn(u64, u8),
     callback_saf_messages_received: unsafe extern "C" fn(),
+    callback_base_node_state: BaseNodeStateCallback,
     db: TransactionDatabase<TBackend>,
     transaction_service_event_stream: Fuse<TransactionEventReceiver>,
     output_manager_service_event_stream: Fuse<OutputManagerEventReceiver>,
     dht_event_stream: Fuse<DhtEventReceiver>,
+    base_node_event_stream: Fuse<BaseNodeEventReceiver>,
     shutdown_signal: Option<ShutdownSignal>,
     comms_public_key: CommsPublicKey,
 }
@@ -112,6 +124,7 @@ where TBackend: TransactionBackend + 'static
         transaction_service_event_stream: Fuse<TransactionEventReceiver>,
         output_manager_service_event_stream: Fuse<OutputManagerEventReceiver>,
         dht_event_stream: Fuse<DhtEventReceiver>,
+        base_node_event_stream: Fuse<BaseNodeEventReceiver>,
         shutdown_signal: ShutdownSignal,
         comms_public_key: CommsPublicKey,
         callback_received_transaction: unsafe extern "C" fn(*mut InboundTransaction),
@@ -128,6 +141,7 @@ where TBackend: TransactionBackend + 'static
         callback_invalid_txo_validation_complete: unsafe extern "C" fn(TxId, u8),
         callback_transaction_validation_complete: unsafe extern "C" fn(TxId, u8),
         callback_saf_messages_received: unsafe extern "C" fn(),
+        callback_base_node_state: BaseNodeStateCallback,
     ) -> Self
     {
         info!(
@@ -202,10 +216,12 @@ where TBackend: TransactionBackend + 'static
             callback_invalid_txo_validation_complete,
             callback_transaction_validation_complete,
             callback_saf_messages_received,
+            callback_base_node_state,
             db,
             transaction_service_event_stream,
             output_manager_service_event_stream,
             dht_event_stream,
+            base_node_event_stream,
             shutdown_signal: Some(shutdown_signal),
             comms_public_key,
         }
@@ -232,7 +248,8 @@ where TBackend: TransactionBackend + 'static
                                 TransactionEvent::ReceivedTransactionReply(tx_id) => {
                                     self.receive_transaction_reply_event(tx_id).await;
                                 },
-                                TransactionEvent::ReceivedFinalizedTransaction(tx_id) => {
+                                TransactionEvent::ReceivedFinalizedTransaction(tx_id) |
+                                TransactionEvent::TransactionImported(tx_id) => {
                                     self.receive_finalized_transaction_event(tx_id).await;
                                 },
                                 TransactionEvent::TransactionDirectSendResult(tx_id, result) => {
@@ -306,6 +323,18 @@ where TBackend: TransactionBackend + 'static
                         },
                         Err(_e) => error!(target: LOG_TARGET, "Error reading from DHT event broadcast channel"),
                     }
+                },
+                result = self.base_node_event_stream.select_next_some() => {
+                    match result {
+                        Ok(msg) => {
+                            trace!(target: LOG_TARGET, "Base Node Service Callback Handler event {:?}", msg);
+                            if let BaseNodeEvent::BaseNodeState(state) = (*msg).clone() {
+                                let height = state.chain_metadata.map(|m| m.height_of_longest_chain()).unwrap_or(0);
+                                self.base_node_state_event(height, state.is_synced.unwrap_or(false));
+                            }
+                        },
+                        Err(_e) => error!(target: LOG_TARGET, "Error reading from Base Node Service event broadcast channel"),
+                    }
                 }
                 complete => {
                     info!(target: LOG_TARGET, "Callback Handler is exiting because all tasks have completed");
@@ -581,11 +610,27 @@ where TBackend: TransactionBackend + 'static
             (self.callback_saf_messages_received)();
         }
     }
+
+    fn base_node_state_event(&mut self, height: u64, is_synced: bool) {
+        let callback = match self.callback_base_node_state.lock() {
+            Ok(lock) => *lock,
+            Err(poisoned) => *poisoned.into_inner(),
+        };
+        if let Some(callback) = callback {
+            debug!(
+                target: LOG_TARGET,
+                "Calling Base Node State callback function with height {} and synced {}", height, is_synced
+            );
+            unsafe {
+                callback(height, is_synced);
+            }
+        }
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::callback_handler::CallbackHandler;
+    use crate::callback_handler::{BaseNodeStateCallback, CallbackHandler};
     use chrono::Utc;
     use futures::StreamExt;
     use rand::rngs::OsRng;
@@ -594,6 +639,7 @@ mod test {
         thread,
         time::Duration,
     };
+    use tari_common_types::chain_metadata::ChainMetadata;
     use tari_comms_dht::event::DhtEvent;
     use tari_core::transactions::{
         tari_amount::{uT, MicroTari},
@@ -605,6 +651,7 @@ mod test {
     use tari_crypto::keys::{PublicKey as PublicKeyTrait, SecretKey};
     use tari_shutdown::Shutdown;
     use tari_wallet::{
+        base_node_service::{handle::BaseNodeEvent, service::BaseNodeState},
         output_manager_service::{handle::OutputManagerEvent, protocols::txo_validation_protocol::TxoValidationType},
         test_utils::make_transaction_database,
         transaction_service::{
@@ -640,6 +687,8 @@ mod test {
         pub callback_invalid_txo_validation_complete: u32,
         pub callback_transaction_validation_complete: u32,
         pub saf_messages_received: bool,
+        pub base_node_state_height: u64,
+        pub base_node_state_synced: bool,
     }
 
     impl CallbackState {
@@ -661,6 +710,8 @@ mod test {
                 tx_cancellation_callback_called_inbound: false,
                 tx_cancellation_callback_called_outbound: false,
                 saf_messages_received: false,
+                base_node_state_height: 0,
+                base_node_state_synced: false,
             }
         }
     }
@@ -729,6 +780,13 @@ mod test {
         drop(lock);
     }
 
+    unsafe extern "C" fn base_node_state_callback(height: u64, is_synced: bool) {
+        let mut lock = CALLBACK_STATE.lock().unwrap();
+        lock.base_node_state_height = height;
+        lock.base_node_state_synced = is_synced;
+        drop(lock);
+    }
+
     unsafe extern "C" fn tx_cancellation_callback(tx: *mut CompletedTransaction) {
         let mut lock = CALLBACK_STATE.lock().unwrap();
         match (*tx).tx_id {
@@ -846,13 +904,16 @@ mod test {
         let (tx_sender, tx_receiver) = broadcast::channel(20);
         let (oms_sender, oms_receiver) = broadcast::channel(20);
         let (dht_sender, dht_receiver) = broadcast::channel(20);
+        let (base_node_sender, base_node_receiver) = broadcast::channel(20);
 
+        let callback_base_node_state: BaseNodeStateCallback = Arc::new(Mutex::new(None));
         let shutdown_signal = Shutdown::new();
         let callback_handler = CallbackHandler::new(
             db,
             tx_receiver.fuse(),
             oms_receiver.fuse(),
             dht_receiver.fuse(),
+            base_node_receiver.fuse(),
             shutdown_signal.to_signal(),
             PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
             received_tx_callback,
@@ -869,6 +930,7 @@ mod test {
             invalid_txo_validation_complete_callback,
             transaction_validation_complete_callback,
             saf_messages_received_callback,
+            callback_base_node_state.clone(),
         );
 
         runtime.spawn(callback_handler.start());
@@ -1015,6 +1077,26 @@ mod test {
             .send(Arc::new(DhtEvent::StoreAndForwardMessagesReceived))
             .unwrap();
 
+        // State received before the callback is registered is not reported
+        base_node_sender
+            .send(Arc::new(BaseNodeEvent::BaseNodeState(BaseNodeState {
+                chain_metadata: Some(ChainMetadata::new(21, Vec::new(), 0, 0, 0)),
+                is_synced: Some(false),
+                ..Default::default()
+            })))
+            .unwrap();
+        thread::sleep(Duration::from_secs(2));
+        assert_eq!(CALLBACK_STATE.lock().unwrap().base_node_state_height, 0);
+
+        *callback_base_node_state.lock().unwrap() = Some(base_node_state_callback);
+        base_node_sender
+            .send(Arc::new(BaseNodeEvent::BaseNodeState(BaseNodeState {
+                chain_metadata: Some(ChainMetadata::new(42, Vec::new(), 0, 0, 0)),
+                is_synced: Some(true),
+                ..Default::default()
+            })))
+            .unwrap();
+
         thread::sleep(Duration::from_secs(10));
 
         let lock = CALLBACK_STATE.lock().unwrap();
@@ -1030,6 +1112,8 @@ mod test {
         assert!(lock.tx_cancellation_callback_called_completed);
         assert!(lock.tx_cancellation_callback_called_outbound);
         assert!(lock.saf_messages_received);
+        assert_eq!(lock.base_node_state_height, 42);
+        assert!(lock.base_node_state_synced);
 
         assert_eq!(lock.callback_utxo_validation_complete, 6);
         assert_eq!(lock.callback_stxo_validation_complete, 6);
@@ -53,6 +53,10 @@ pub enum InterfaceError {
     InvalidEmojiId,
     #[error("Comms Private Key is not present while Db appears to be encrypted which should not happen")]
     MissingCommsPrivateKey,
+    #[error("An error has occurred when serializing the requested data: `{0}`")]
+    SerializationError(String),
+    #[error("Payment request is invalid: `{0}`")]
+    InvalidPaymentRequest(String),
 }
 
 /// This struct is meant to hold an error for use by FFI client applications. The error has an integer code and string
@@ -91,6 +95,14 @@ impl From<InterfaceError> for LibWalletError {
                 code: 7,
                 message: format!("{:?}", v),
             },
+            InterfaceError::SerializationError(_) => Self {
+                code: 8,
+                message: format!("{:?}", v),
+            },
+            InterfaceError::InvalidPaymentRequest(_) => Self {
+                code: 9,
+                message: format!("{:?}", v),
+            },
         }
     }
 }
@@ -234,6 +246,10 @@ impl From<WalletError> for LibWalletError {
                 code: 404,
                 message: format!("{:?}", w),
             },
+            WalletError::ContactsServiceError(ContactsServiceError::SerializationError(_)) => Self {
+                code: 405,
+                message: format!("{:?}", w),
+            },
             // Wallet Encryption Errors
             WalletError::WalletStorageError(WalletStorageError::InvalidEncryptionCipher) => Self {
                 code: 420,
@@ -263,6 +279,11 @@ impl From<WalletError> for LibWalletError {
                 code: 426,
                 message: format!("{:?}", w),
             },
+            // Wallet Recovery Errors
+            WalletError::WalletRecoveryError(_) => Self {
+                code: 430,
+                message: format!("{:?}", w),
+            },
             // This is the catch all error code. Any error that is not explicitly mapped above will be given this code
             _ => Self {
                 code: 999,
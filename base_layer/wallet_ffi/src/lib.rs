@@ -120,11 +120,14 @@
 extern crate lazy_static;
 mod callback_handler;
 mod error;
+mod tasks;
 
 use crate::{
-    callback_handler::CallbackHandler,
+    callback_handler::{BaseNodeStateCallback, CallbackHandler},
     error::{InterfaceError, TransactionError},
+    tasks::recovery_event_monitoring,
 };
+use chrono::{NaiveDateTime, Utc};
 use core::ptr;
 use error::LibWalletError;
 use libc::{c_char, c_int, c_longlong, c_uchar, c_uint, c_ulonglong, c_ushort};
@@ -137,10 +140,11 @@ use log4rs::{
 use rand::rngs::OsRng;
 use std::{
     boxed::Box,
+    collections::HashMap,
     ffi::{CStr, CString},
     path::PathBuf,
     slice,
-    sync::Arc,
+    sync::{Arc, Mutex},
     time::Duration,
 };
 use tari_comms::{
@@ -162,6 +166,11 @@ use tari_utilities::{hex, hex::Hex};
 use tari_wallet::{
     contacts_service::storage::database::Contact,
     error::WalletError,
+    output_manager_service::{
+        error::OutputManagerError,
+        storage::models::{account_of, Account, AccountId},
+        TxId,
+    },
     storage::{
         database::WalletDatabase,
         sqlite_db::WalletSqliteDatabase,
@@ -171,6 +180,7 @@ use tari_wallet::{
             run_migration_and_create_sqlite_connection,
         },
     },
+    tasks::wallet_recovery::{is_recovery_in_progress, WalletRecoveryTask},
     testnet_utils::{
         broadcast_transaction,
         complete_sent_transaction,
@@ -231,6 +241,7 @@ pub type TariExcessSignature = tari_crypto::ristretto::RistrettoSecretKey;
 pub struct TariContacts(Vec<TariContact>);
 
 pub type TariContact = tari_wallet::contacts_service::storage::database::Contact;
+pub type TariPaymentRequest = tari_wallet::util::payment_request::PaymentRequest;
 pub type TariCompletedTransaction = tari_wallet::transaction_service::storage::models::CompletedTransaction;
 
 pub struct TariCompletedTransactions(Vec<TariCompletedTransaction>);
@@ -242,6 +253,12 @@ pub struct TariPendingInboundTransactions(Vec<TariPendingInboundTransaction>);
 
 pub struct TariPendingOutboundTransactions(Vec<TariPendingOutboundTransaction>);
 
+pub type TariUnblindedOutput = tari_core::transactions::transaction::UnblindedOutput;
+
+pub struct TariUnblindedOutputs(Vec<TariUnblindedOutput>);
+
+pub struct TariAccounts(Vec<Account>);
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct ByteVector(Vec<c_uchar>); // declared like this so that it can be exposed to external header
 
@@ -254,6 +271,7 @@ pub struct TariWallet {
     wallet: WalletSqlite,
     runtime: Runtime,
     shutdown: Shutdown,
+    callback_base_node_state: BaseNodeStateCallback,
 }
 
 /// -------------------------------- Strings ------------------------------------------------ ///
@@ -887,6 +905,349 @@ pub unsafe extern "C" fn seed_words_destroy(seed_words: *mut TariSeedWords) {
     }
 }
 
+/// ----------------------------------- PaymentRequest ------------------------------------------///
+
+/// Creates a TariPaymentRequest for a payment to the given public key
+///
+/// ## Arguments
+/// `public_key` - The pointer to the TariPublicKey that should be paid
+/// `amount` - The requested amount in MicroTari, zero if the payer should choose the amount
+/// `message` - The pointer to a char array containing the message for the payer
+/// `expiry` - The unix timestamp in seconds after which the request should no longer be paid, zero if it does not
+/// expire
+/// `fee_per_gram` - The suggested fee per gram in MicroTari, zero if the payer should choose the fee
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `*mut TariPaymentRequest` - Returns a pointer to a TariPaymentRequest. Note that it returns ptr::null_mut() if
+/// public_key or message is null
+///
+/// # Safety
+/// The ```payment_request_destroy``` method must be called when finished with a TariPaymentRequest to prevent a
+/// memory leak
+#[no_mangle]
+pub unsafe extern "C" fn payment_request_create(
+    public_key: *mut TariPublicKey,
+    amount: c_ulonglong,
+    message: *const c_char,
+    expiry: c_ulonglong,
+    fee_per_gram: c_ulonglong,
+    error_out: *mut c_int,
+) -> *mut TariPaymentRequest
+{
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if public_key.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("public_key".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    }
+    if message.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("message".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    }
+    let message_string = CStr::from_ptr(message).to_str().unwrap().to_owned();
+
+    let mut request = TariPaymentRequest::new((*public_key).clone()).with_message(message_string);
+    if amount > 0 {
+        request = request.with_amount(MicroTari::from(amount));
+    }
+    if expiry > 0 {
+        request = request.with_expiry(NaiveDateTime::from_timestamp(expiry as i64, 0));
+    }
+    if fee_per_gram > 0 {
+        request = request.with_fee_per_gram(MicroTari::from(fee_per_gram));
+    }
+    Box::into_raw(Box::new(request))
+}
+
+/// Parses a TariPaymentRequest from a payment request URI, an emoji ID with request parameters, or a bare emoji ID or
+/// public key hex string
+///
+/// ## Arguments
+/// `request` - The pointer to a char array containing the payment request
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `*mut TariPaymentRequest` - Returns a pointer to a TariPaymentRequest. Note that it returns ptr::null_mut() if
+/// request is null or is not a valid payment request
+///
+/// # Safety
+/// The ```payment_request_destroy``` method must be called when finished with a TariPaymentRequest to prevent a
+/// memory leak
+#[no_mangle]
+pub unsafe extern "C" fn payment_request_from_string(
+    request: *const c_char,
+    error_out: *mut c_int,
+) -> *mut TariPaymentRequest
+{
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if request.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("request".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    }
+
+    match CStr::from_ptr(request).to_str().unwrap().parse::<TariPaymentRequest>() {
+        Ok(r) => Box::into_raw(Box::new(r)),
+        Err(e) => {
+            error = LibWalletError::from(InterfaceError::InvalidPaymentRequest(e.to_string())).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            ptr::null_mut()
+        },
+    }
+}
+
+/// Encodes a TariPaymentRequest as a `tari:` URI
+///
+/// ## Arguments
+/// `request` - The pointer to a TariPaymentRequest
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `*mut c_char` - Returns a pointer to a char array. Note that it returns an empty char array if request is null
+///
+/// # Safety
+/// The ```string_destroy``` method must be called when finished with a string from rust to prevent a memory leak
+#[no_mangle]
+pub unsafe extern "C" fn payment_request_to_uri(
+    request: *mut TariPaymentRequest,
+    error_out: *mut c_int,
+) -> *mut c_char
+{
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    let mut result = CString::new("").unwrap();
+    if request.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("request".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+    } else {
+        result = CString::new((*request).to_uri()).unwrap();
+    }
+    CString::into_raw(result)
+}
+
+/// Encodes a TariPaymentRequest as the destination emoji ID followed by the request parameters
+///
+/// ## Arguments
+/// `request` - The pointer to a TariPaymentRequest
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `*mut c_char` - Returns a pointer to a char array. Note that it returns an empty char array if request is null
+///
+/// # Safety
+/// The ```string_destroy``` method must be called when finished with a string from rust to prevent a memory leak
+#[no_mangle]
+pub unsafe extern "C" fn payment_request_to_emoji_string(
+    request: *mut TariPaymentRequest,
+    error_out: *mut c_int,
+) -> *mut c_char
+{
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    let mut result = CString::new("").unwrap();
+    if request.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("request".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+    } else {
+        result = CString::new((*request).to_emoji_string()).unwrap();
+    }
+    CString::into_raw(result)
+}
+
+/// Gets the TariPublicKey that a TariPaymentRequest asks to be paid
+///
+/// ## Arguments
+/// `request` - The pointer to a TariPaymentRequest
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `*mut TariPublicKey` - Returns a pointer to a TariPublicKey. Note that it returns ptr::null_mut() if request is
+/// null
+///
+/// # Safety
+/// The ```public_key_destroy``` method must be called when finished with a TariPublicKey to prevent a memory leak
+#[no_mangle]
+pub unsafe extern "C" fn payment_request_get_public_key(
+    request: *mut TariPaymentRequest,
+    error_out: *mut c_int,
+) -> *mut TariPublicKey
+{
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if request.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("request".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    }
+    Box::into_raw(Box::new((*request).destination.clone()))
+}
+
+/// Gets the requested amount of a TariPaymentRequest
+///
+/// ## Arguments
+/// `request` - The pointer to a TariPaymentRequest
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `c_ulonglong` - Returns the amount in MicroTari, zero if request is null or does not specify an amount
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn payment_request_get_amount(
+    request: *mut TariPaymentRequest,
+    error_out: *mut c_int,
+) -> c_ulonglong
+{
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if request.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("request".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return 0;
+    }
+    (*request).amount.map(u64::from).unwrap_or(0) as c_ulonglong
+}
+
+/// Gets the message of a TariPaymentRequest
+///
+/// ## Arguments
+/// `request` - The pointer to a TariPaymentRequest
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `*mut c_char` - Returns a pointer to a char array. Note that it returns an empty char array if request is null
+///
+/// # Safety
+/// The ```string_destroy``` method must be called when finished with a string from rust to prevent a memory leak
+#[no_mangle]
+pub unsafe extern "C" fn payment_request_get_message(
+    request: *mut TariPaymentRequest,
+    error_out: *mut c_int,
+) -> *mut c_char
+{
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    let mut result = CString::new("").unwrap();
+    if request.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("request".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+    } else {
+        result = CString::new((*request).message.clone()).unwrap();
+    }
+    CString::into_raw(result)
+}
+
+/// Gets the expiry of a TariPaymentRequest
+///
+/// ## Arguments
+/// `request` - The pointer to a TariPaymentRequest
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `c_ulonglong` - Returns the unix timestamp in seconds, zero if request is null or does not expire
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn payment_request_get_expiry(
+    request: *mut TariPaymentRequest,
+    error_out: *mut c_int,
+) -> c_ulonglong
+{
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if request.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("request".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return 0;
+    }
+    (*request).expiry.map(|e| e.timestamp().max(0) as u64).unwrap_or(0) as c_ulonglong
+}
+
+/// Gets the suggested fee per gram of a TariPaymentRequest
+///
+/// ## Arguments
+/// `request` - The pointer to a TariPaymentRequest
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `c_ulonglong` - Returns the fee per gram in MicroTari, zero if request is null or does not suggest a fee
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn payment_request_get_fee_per_gram(
+    request: *mut TariPaymentRequest,
+    error_out: *mut c_int,
+) -> c_ulonglong
+{
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if request.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("request".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return 0;
+    }
+    (*request).fee_per_gram.map(u64::from).unwrap_or(0) as c_ulonglong
+}
+
+/// Checks whether a TariPaymentRequest has expired
+///
+/// ## Arguments
+/// `request` - The pointer to a TariPaymentRequest
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `bool` - Returns true if the request has expired, false if it has not or if request is null
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn payment_request_is_expired(request: *mut TariPaymentRequest, error_out: *mut c_int) -> bool {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if request.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("request".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return false;
+    }
+    (*request).is_expired(Utc::now().naive_utc())
+}
+
+/// Frees memory for a TariPaymentRequest
+///
+/// ## Arguments
+/// `request` - The pointer to a TariPaymentRequest
+///
+/// ## Returns
+/// `()` - Does not return a value, equivalent to void in C
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn payment_request_destroy(request: *mut TariPaymentRequest) {
+    if !request.is_null() {
+        Box::from_raw(request);
+    }
+}
+
+/// -------------------------------------------------------------------------------------------- ///
+
 /// ----------------------------------- Contact -------------------------------------------------///
 
 /// Creates a TariContact
@@ -1091,54 +1452,374 @@ pub unsafe extern "C" fn contacts_destroy(contacts: *mut TariContacts) {
 
 /// -------------------------------------------------------------------------------------------- ///
 
-/// ----------------------------------- CompletedTransactions ----------------------------------- ///
+/// ----------------------------------- UnblindedOutputs ----------------------------------------///
 
-/// Gets the length of a TariCompletedTransactions
+/// Gets the length of TariUnblindedOutputs
 ///
 /// ## Arguments
-/// `transactions` - The pointer to a TariCompletedTransactions
+/// `outputs` - The pointer to a TariUnblindedOutputs
 /// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
 /// as an out parameter.
 ///
 /// ## Returns
-/// `c_uint` - Returns the number of elements in a TariCompletedTransactions, note that it will be
-/// zero if transactions is null
+/// `c_uint` - Returns number of elements in outputs, zero if outputs is null
 ///
 /// # Safety
 /// None
 #[no_mangle]
-pub unsafe extern "C" fn completed_transactions_get_length(
-    transactions: *mut TariCompletedTransactions,
+pub unsafe extern "C" fn unblinded_outputs_get_length(
+    outputs: *mut TariUnblindedOutputs,
     error_out: *mut c_int,
 ) -> c_uint
 {
     let mut error = 0;
     ptr::swap(error_out, &mut error as *mut c_int);
     let mut len = 0;
-    if transactions.is_null() {
-        error = LibWalletError::from(InterfaceError::NullError("transaction".to_string())).code;
+    if outputs.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("outputs".to_string())).code;
         ptr::swap(error_out, &mut error as *mut c_int);
     } else {
-        len = (*transactions).0.len();
+        len = (*outputs).0.len();
     }
     len as c_uint
 }
 
-/// Gets a TariCompletedTransaction from a TariCompletedTransactions at position
+/// Gets a TariUnblindedOutput from TariUnblindedOutputs at position
 ///
 /// ## Arguments
-/// `transactions` - The pointer to a TariCompletedTransactions
+/// `outputs` - The pointer to a TariUnblindedOutputs
 /// `position` - The integer position
 /// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
 /// as an out parameter.
 ///
 /// ## Returns
-/// `*mut TariCompletedTransaction` - Returns a pointer to a TariCompletedTransaction,
-/// note that ptr::null_mut() is returned if transactions is null or position is invalid
+/// `*mut TariUnblindedOutput` - Returns a TariUnblindedOutput, note that it returns ptr::null_mut() if outputs is
+/// null or position is invalid
 ///
 /// # Safety
-/// The ```completed_transaction_destroy``` method must be called when finished with a TariCompletedTransaction to
-/// prevent a memory leak
+/// The ```unblinded_output_destroy``` method must be called when finished with a TariUnblindedOutput to prevent a
+/// memory leak
+#[no_mangle]
+pub unsafe extern "C" fn unblinded_outputs_get_at(
+    outputs: *mut TariUnblindedOutputs,
+    position: c_uint,
+    error_out: *mut c_int,
+) -> *mut TariUnblindedOutput
+{
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if outputs.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("outputs".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    }
+    let len = unblinded_outputs_get_length(outputs, error_out) as c_int - 1;
+    if len < 0 || position > len as c_uint {
+        error = LibWalletError::from(InterfaceError::PositionInvalidError).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    }
+    Box::into_raw(Box::new((*outputs).0[position as usize].clone()))
+}
+
+/// Frees memory for a TariUnblindedOutputs
+///
+/// ## Arguments
+/// `outputs` - The pointer to a TariUnblindedOutputs
+///
+/// ## Returns
+/// `()` - Does not return a value, equivalent to void in C
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn unblinded_outputs_destroy(outputs: *mut TariUnblindedOutputs) {
+    if !outputs.is_null() {
+        Box::from_raw(outputs);
+    }
+}
+
+/// -------------------------------------------------------------------------------------------- ///
+
+/// ----------------------------------- Accounts ------------------------------------------------///
+
+/// Gets the length of TariAccounts
+///
+/// ## Arguments
+/// `accounts` - The pointer to a TariAccounts
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `c_uint` - Returns number of elements in accounts, zero if accounts is null
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn accounts_get_length(accounts: *mut TariAccounts, error_out: *mut c_int) -> c_uint {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    let mut len = 0;
+    if accounts.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("accounts".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+    } else {
+        len = (*accounts).0.len();
+    }
+    len as c_uint
+}
+
+/// Gets the name of the account in TariAccounts at position
+///
+/// ## Arguments
+/// `accounts` - The pointer to a TariAccounts
+/// `position` - The integer position
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `*mut c_char` - Returns a pointer to a char array. Note that it returns an empty char array if accounts is null or
+/// position is invalid
+///
+/// # Safety
+/// The ```string_destroy``` method must be called when finished with a string from rust to prevent a memory leak
+#[no_mangle]
+pub unsafe extern "C" fn accounts_get_name_at(
+    accounts: *mut TariAccounts,
+    position: c_uint,
+    error_out: *mut c_int,
+) -> *mut c_char
+{
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    let mut name = CString::new("").unwrap();
+    if accounts.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("accounts".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+    } else {
+        let len = (*accounts).0.len();
+        if position as usize >= len {
+            error = LibWalletError::from(InterfaceError::PositionInvalidError).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+        } else {
+            name = CString::new((*accounts).0[position as usize].name.clone()).unwrap()
+        }
+    }
+    CString::into_raw(name)
+}
+
+/// Gets the id of the account in TariAccounts at position
+///
+/// ## Arguments
+/// `accounts` - The pointer to a TariAccounts
+/// `position` - The integer position
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `c_ulonglong` - Returns the account id, zero if accounts is null or position is invalid
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn accounts_get_id_at(
+    accounts: *mut TariAccounts,
+    position: c_uint,
+    error_out: *mut c_int,
+) -> c_ulonglong
+{
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if accounts.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("accounts".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return 0;
+    }
+    if position as usize >= (*accounts).0.len() {
+        error = LibWalletError::from(InterfaceError::PositionInvalidError).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return 0;
+    }
+    (*accounts).0[position as usize].id as c_ulonglong
+}
+
+/// Frees memory for a TariAccounts
+///
+/// ## Arguments
+/// `accounts` - The pointer to a TariAccounts
+///
+/// ## Returns
+/// `()` - Does not return a value, equivalent to void in C
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn accounts_destroy(accounts: *mut TariAccounts) {
+    if !accounts.is_null() {
+        Box::from_raw(accounts);
+    }
+}
+
+/// -------------------------------------------------------------------------------------------- ///
+
+/// ----------------------------------- UnblindedOutput -----------------------------------------///
+
+/// Gets the value of a TariUnblindedOutput
+///
+/// ## Arguments
+/// `output` - The pointer to a TariUnblindedOutput
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `c_ulonglong` - Returns the value in MicroTari, zero if output is null
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn unblinded_output_get_value(
+    output: *mut TariUnblindedOutput,
+    error_out: *mut c_int,
+) -> c_ulonglong
+{
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if output.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("output".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return 0;
+    }
+    c_ulonglong::from((*output).value)
+}
+
+/// Gets the maturity of a TariUnblindedOutput, i.e. the block height from which it can be spent
+///
+/// ## Arguments
+/// `output` - The pointer to a TariUnblindedOutput
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `c_ulonglong` - Returns the maturity block height, zero if output is null
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn unblinded_output_get_maturity(
+    output: *mut TariUnblindedOutput,
+    error_out: *mut c_int,
+) -> c_ulonglong
+{
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if output.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("output".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return 0;
+    }
+    (*output).features.maturity
+}
+
+/// Gets the commitment of a TariUnblindedOutput as it appears on the blockchain
+///
+/// ## Arguments
+/// `output` - The pointer to a TariUnblindedOutput
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `*mut ByteVector` - Returns a pointer to a ByteVector containing the commitment bytes. Note that it returns
+/// ptr::null_mut() if output is null
+///
+/// # Safety
+/// The ```byte_vector_destroy``` function must be called when finished with the ByteVector to prevent a memory leak.
+#[no_mangle]
+pub unsafe extern "C" fn unblinded_output_get_commitment(
+    output: *mut TariUnblindedOutput,
+    error_out: *mut c_int,
+) -> *mut ByteVector
+{
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if output.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("output".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    }
+    let factories = CryptoFactories::default();
+    let commitment = (*output)
+        .as_transaction_input(&factories.commitment, (*output).features.clone())
+        .commitment;
+    Box::into_raw(Box::new(ByteVector(commitment.as_bytes().to_vec())))
+}
+
+/// Frees memory for a TariUnblindedOutput
+///
+/// ## Arguments
+/// `output` - The pointer to a TariUnblindedOutput
+///
+/// ## Returns
+/// `()` - Does not return a value, equivalent to void in C
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn unblinded_output_destroy(output: *mut TariUnblindedOutput) {
+    if !output.is_null() {
+        Box::from_raw(output);
+    }
+}
+
+/// -------------------------------------------------------------------------------------------- ///
+
+/// ----------------------------------- CompletedTransactions ----------------------------------- ///
+
+/// Gets the length of a TariCompletedTransactions
+///
+/// ## Arguments
+/// `transactions` - The pointer to a TariCompletedTransactions
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `c_uint` - Returns the number of elements in a TariCompletedTransactions, note that it will be
+/// zero if transactions is null
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn completed_transactions_get_length(
+    transactions: *mut TariCompletedTransactions,
+    error_out: *mut c_int,
+) -> c_uint
+{
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    let mut len = 0;
+    if transactions.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("transaction".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+    } else {
+        len = (*transactions).0.len();
+    }
+    len as c_uint
+}
+
+/// Gets a TariCompletedTransaction from a TariCompletedTransactions at position
+///
+/// ## Arguments
+/// `transactions` - The pointer to a TariCompletedTransactions
+/// `position` - The integer position
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `*mut TariCompletedTransaction` - Returns a pointer to a TariCompletedTransaction,
+/// note that ptr::null_mut() is returned if transactions is null or position is invalid
+///
+/// # Safety
+/// The ```completed_transaction_destroy``` method must be called when finished with a TariCompletedTransaction to
+/// prevent a memory leak
 #[no_mangle]
 pub unsafe extern "C" fn completed_transactions_get_at(
     transactions: *mut TariCompletedTransactions,
@@ -2417,6 +3098,8 @@ pub unsafe extern "C" fn transport_tor_create(
         port_mapping: tor::PortMapping::new(tor_port, "127.0.0.1:0".parse().unwrap()),
         socks_address_override: None,
         socks_auth: authentication,
+        additional_services: Vec::new(),
+        key_rotation_interval: None,
     };
     let transport = TariTransportType::Tor(tor_config);
 
@@ -2657,6 +3340,8 @@ pub unsafe extern "C" fn comms_config_create(
                         peer_seeds: Default::default(),
                         dns_seeds: Default::default(),
                         dns_seeds_use_dnssec: true,
+                        max_bandwidth_bytes_per_sec: None,
+                        max_peer_bandwidth_bytes_per_sec: None,
                     };
 
                     Box::into_raw(Box::new(config))
@@ -2976,16 +3661,18 @@ pub unsafe extern "C" fn wallet_create(
                 Ok(mut w) => {
                     // lets ensure the wallet tor_id is saved
                     if let Some(hs) = w.comms.hidden_service() {
-                        if let Err(e) = runtime.block_on(w.db.set_tor_identity(hs.tor_identity().clone())) {
+                        if let Err(e) = runtime.block_on(w.db.set_tor_identity(hs.tor_identity())) {
                             warn!(target: LOG_TARGET, "Could not save tor identity to db: {}", e);
                         }
                     }
                     // Start Callback Handler
+                    let callback_base_node_state: BaseNodeStateCallback = Arc::new(Mutex::new(None));
                     let callback_handler = CallbackHandler::new(
                         TransactionDatabase::new(transaction_backend),
                         w.transaction_service.get_event_stream_fused(),
                         w.output_manager_service.get_event_stream_fused(),
                         w.dht_service.subscribe_dht_events().fuse(),
+                        w.base_node_service.get_event_stream_fused(),
                         w.comms.shutdown_signal(),
                         w.comms.node_identity().public_key().clone(),
                         callback_received_transaction,
@@ -3002,6 +3689,7 @@ pub unsafe extern "C" fn wallet_create(
                         callback_invalid_txo_validation_complete,
                         callback_transaction_validation_complete,
                         callback_saf_messages_received,
+                        callback_base_node_state.clone(),
                     );
 
                     runtime.spawn(callback_handler.start());
@@ -3017,6 +3705,7 @@ pub unsafe extern "C" fn wallet_create(
                         wallet: w,
                         runtime,
                         shutdown,
+                        callback_base_node_state,
                     };
 
                     Box::into_raw(Box::new(tari_wallet))
@@ -3823,32 +4512,434 @@ pub unsafe extern "C" fn wallet_get_fee_estimate(
     }
 }
 
-/// Gets the number of mining confirmations required
+/// Gets a fee estimate for an amount along with the unspent outputs that would currently be selected to fund it. The
+/// selected outputs are not reserved, so a subsequent send may select differently if the wallet state changes.
 ///
 /// ## Arguments
 /// `wallet` - The TariWallet pointer
+/// `amount` - The amount
+/// `fee_per_gram` - The fee per gram
+/// `num_kernels` - The number of transaction kernels
+/// `num_outputs` - The number of outputs
+/// `fee_out` - Pointer to an unsigned long long which will be modified to the fee estimate in MicroTari, may not be
+/// null. Functions as an out parameter.
 /// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
 /// as an out parameter.
 ///
 /// ## Returns
-/// `unsigned long long` - Returns the number of confirmations required
+/// `*mut TariUnblindedOutputs` - Returns the selected outputs, note that it returns ptr::null_mut() if wallet is null
+/// or an error is encountered
 ///
 /// # Safety
-/// None
+/// The ```unblinded_outputs_destroy``` method must be called when finished with a TariUnblindedOutputs to prevent a
+/// memory leak
 #[no_mangle]
-pub unsafe extern "C" fn wallet_get_num_confirmations_required(
+pub unsafe extern "C" fn wallet_get_fee_estimate_with_selection(
     wallet: *mut TariWallet,
+    amount: c_ulonglong,
+    fee_per_gram: c_ulonglong,
+    num_kernels: c_ulonglong,
+    num_outputs: c_ulonglong,
+    fee_out: *mut c_ulonglong,
     error_out: *mut c_int,
-) -> c_ulonglong
+) -> *mut TariUnblindedOutputs
 {
     let mut error = 0;
     ptr::swap(error_out, &mut error as *mut c_int);
     if wallet.is_null() {
         error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
         ptr::swap(error_out, &mut error as *mut c_int);
-        return 0;
+        return ptr::null_mut();
     }
-
+    if fee_out.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("fee_out".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    }
+
+    match (*wallet)
+        .runtime
+        .block_on((*wallet).wallet.output_manager_service.fee_estimate_with_selection(
+            MicroTari::from(amount),
+            MicroTari::from(fee_per_gram),
+            num_kernels,
+            num_outputs,
+        )) {
+        Ok((fee, outputs)) => {
+            *fee_out = fee.into();
+            Box::into_raw(Box::new(TariUnblindedOutputs(outputs)))
+        },
+        Err(e) => {
+            error = LibWalletError::from(WalletError::OutputManagerError(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            ptr::null_mut()
+        },
+    }
+}
+
+/// Gets the unspent outputs of a TariWallet. The maturity of each output can be queried to determine whether it is
+/// currently spendable.
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `*mut TariUnblindedOutputs` - Returns the unspent outputs, note that it returns ptr::null_mut() if wallet is null
+/// or an error is encountered
+///
+/// # Safety
+/// The ```unblinded_outputs_destroy``` method must be called when finished with a TariUnblindedOutputs to prevent a
+/// memory leak
+#[no_mangle]
+pub unsafe extern "C" fn wallet_get_unspent_outputs(
+    wallet: *mut TariWallet,
+    error_out: *mut c_int,
+) -> *mut TariUnblindedOutputs
+{
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    }
+
+    match (*wallet)
+        .runtime
+        .block_on((*wallet).wallet.output_manager_service.get_unspent_outputs())
+    {
+        Ok(outputs) => Box::into_raw(Box::new(TariUnblindedOutputs(outputs))),
+        Err(e) => {
+            error = LibWalletError::from(WalletError::OutputManagerError(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            ptr::null_mut()
+        },
+    }
+}
+
+/// Creates a new named account in a TariWallet. Each account derives its keys from its own key branch and keeps its
+/// own balance, outputs and transaction history.
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer
+/// `name` - The pointer to a char array containing the unique account name
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `c_ulonglong` - Returns the id of the new account, zero if an error occurred
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn wallet_create_account(
+    wallet: *mut TariWallet,
+    name: *const c_char,
+    error_out: *mut c_int,
+) -> c_ulonglong
+{
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return 0;
+    }
+    if name.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("name".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return 0;
+    }
+    let name_string = CStr::from_ptr(name).to_str().unwrap().to_owned();
+
+    match (*wallet)
+        .runtime
+        .block_on((*wallet).wallet.output_manager_service.create_account(name_string))
+    {
+        Ok(account) => account.id as c_ulonglong,
+        Err(e) => {
+            error = LibWalletError::from(WalletError::OutputManagerError(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            0
+        },
+    }
+}
+
+/// Gets the accounts of a TariWallet, the default account is always first
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `*mut TariAccounts` - Returns the accounts, note that it returns ptr::null_mut() if wallet is null or an error is
+/// encountered
+///
+/// # Safety
+/// The ```accounts_destroy``` method must be called when finished with a TariAccounts to prevent a memory leak
+#[no_mangle]
+pub unsafe extern "C" fn wallet_get_accounts(wallet: *mut TariWallet, error_out: *mut c_int) -> *mut TariAccounts {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    }
+
+    match (*wallet)
+        .runtime
+        .block_on((*wallet).wallet.output_manager_service.get_accounts())
+    {
+        Ok(accounts) => Box::into_raw(Box::new(TariAccounts(accounts))),
+        Err(e) => {
+            error = LibWalletError::from(WalletError::OutputManagerError(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            ptr::null_mut()
+        },
+    }
+}
+
+/// Selects the active account of a TariWallet. Balances, outputs, transaction lists and new transactions all apply
+/// to the active account.
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer
+/// `name` - The pointer to a char array containing the account name
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `bool` - Returns if the account was selected
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn wallet_set_active_account(
+    wallet: *mut TariWallet,
+    name: *const c_char,
+    error_out: *mut c_int,
+) -> bool
+{
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return false;
+    }
+    if name.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("name".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return false;
+    }
+    let name_string = CStr::from_ptr(name).to_str().unwrap().to_owned();
+
+    match (*wallet)
+        .runtime
+        .block_on((*wallet).wallet.output_manager_service.set_active_account(name_string))
+    {
+        Ok(_) => true,
+        Err(e) => {
+            error = LibWalletError::from(WalletError::OutputManagerError(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            false
+        },
+    }
+}
+
+/// Gets the name of the active account of a TariWallet
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `*mut c_char` - Returns a pointer to a char array. Note that it returns ptr::null_mut() if wallet is null or an
+/// error is encountered
+///
+/// # Safety
+/// The ```string_destroy``` method must be called when finished with a string from rust to prevent a memory leak
+#[no_mangle]
+pub unsafe extern "C" fn wallet_get_active_account_name(wallet: *mut TariWallet, error_out: *mut c_int) -> *mut c_char {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    }
+
+    match (*wallet)
+        .runtime
+        .block_on((*wallet).wallet.output_manager_service.get_active_account())
+    {
+        Ok(account) => CString::into_raw(CString::new(account.name).unwrap()),
+        Err(e) => {
+            error = LibWalletError::from(WalletError::OutputManagerError(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            ptr::null_mut()
+        },
+    }
+}
+
+/// Fetches the active account id and the transaction to account mapping so that transaction lists can be restricted
+/// to the active account
+unsafe fn active_account_transactions(
+    wallet: *mut TariWallet,
+) -> Result<(AccountId, HashMap<TxId, AccountId>), OutputManagerError> {
+    let active = (*wallet)
+        .runtime
+        .block_on((*wallet).wallet.output_manager_service.get_active_account())?;
+    let account_transactions = (*wallet)
+        .runtime
+        .block_on((*wallet).wallet.output_manager_service.get_account_transactions())?;
+    Ok((active.id, account_transactions))
+}
+
+/// Gets the chain tip height last reported by the wallet's Base Node
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `unsigned long long` - Returns the chain tip height, 0 if it is not yet known or an error occurred
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn wallet_get_chain_tip_height(wallet: *mut TariWallet, error_out: *mut c_int) -> c_ulonglong {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return 0;
+    }
+
+    match (*wallet)
+        .runtime
+        .block_on((*wallet).wallet.base_node_service.get_chain_metadata())
+    {
+        Ok(metadata) => metadata.map(|m| m.height_of_longest_chain()).unwrap_or(0),
+        Err(e) => {
+            error = LibWalletError::from(WalletError::BaseNodeServiceError(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            0
+        },
+    }
+}
+
+/// Gets whether the wallet's Base Node last reported that it is synced
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `bool` - Returns true if the Base Node is synced, false if it is not, its state is not yet known or an error
+/// occurred
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn wallet_is_base_node_synced(wallet: *mut TariWallet, error_out: *mut c_int) -> bool {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return false;
+    }
+
+    match (*wallet)
+        .runtime
+        .block_on((*wallet).wallet.base_node_service.get_base_node_state())
+    {
+        Ok(state) => state.is_synced.unwrap_or(false),
+        Err(e) => {
+            error = LibWalletError::from(WalletError::BaseNodeServiceError(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            false
+        },
+    }
+}
+
+/// Registers a callback that will be called whenever updated chain state is received from the wallet's Base Node.
+/// Registering a callback replaces any previously registered one.
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer
+/// `callback_base_node_state` - The callback function pointer. The first parameter is the chain tip height (0 if
+/// unknown) and the second is whether the Base Node reports that it is synced.
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `bool` - Returns true if the callback was registered, false if an error occurred
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn wallet_set_base_node_state_callback(
+    wallet: *mut TariWallet,
+    callback_base_node_state: unsafe extern "C" fn(c_ulonglong, bool),
+    error_out: *mut c_int,
+) -> bool
+{
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return false;
+    }
+
+    info!(
+        target: LOG_TARGET,
+        "BaseNodeStateCallback -> Assigning Fn:  {:?}", callback_base_node_state
+    );
+    let mut lock = match (*wallet).callback_base_node_state.lock() {
+        Ok(lock) => lock,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    *lock = Some(callback_base_node_state);
+    true
+}
+
+/// Gets the number of mining confirmations required
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `unsigned long long` - Returns the number of confirmations required
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn wallet_get_num_confirmations_required(
+    wallet: *mut TariWallet,
+    error_out: *mut c_int,
+) -> c_ulonglong
+{
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return 0;
+    }
+
     match (*wallet)
         .runtime
         .block_on((*wallet).wallet.transaction_service.get_num_confirmations_required())
@@ -3876,67 +4967,151 @@ pub unsafe extern "C" fn wallet_get_num_confirmations_required(
 /// # Safety
 /// None
 #[no_mangle]
-pub unsafe extern "C" fn wallet_set_num_confirmations_required(
-    wallet: *mut TariWallet,
-    num: c_ulonglong,
-    error_out: *mut c_int,
-)
-{
+pub unsafe extern "C" fn wallet_set_num_confirmations_required(
+    wallet: *mut TariWallet,
+    num: c_ulonglong,
+    error_out: *mut c_int,
+)
+{
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int)
+    }
+
+    match (*wallet)
+        .runtime
+        .block_on((*wallet).wallet.transaction_service.set_num_confirmations_required(num))
+    {
+        Ok(()) => (),
+        Err(e) => {
+            error = LibWalletError::from(WalletError::TransactionServiceError(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int)
+        },
+    }
+}
+
+/// Get the TariContacts from a TariWallet
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `*mut TariContacts` - returns the contacts, note that it returns ptr::null_mut() if
+/// wallet is null
+///
+/// # Safety
+/// The ```contacts_destroy``` method must be called when finished with a TariContacts to prevent a memory leak
+#[no_mangle]
+pub unsafe extern "C" fn wallet_get_contacts(wallet: *mut TariWallet, error_out: *mut c_int) -> *mut TariContacts {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    let mut contacts = Vec::new();
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    }
+
+    let retrieved_contacts = (*wallet)
+        .runtime
+        .block_on((*wallet).wallet.contacts_service.get_contacts());
+    match retrieved_contacts {
+        Ok(mut retrieved_contacts) => {
+            contacts.append(&mut retrieved_contacts);
+            Box::into_raw(Box::new(TariContacts(contacts)))
+        },
+        Err(e) => {
+            error = LibWalletError::from(WalletError::ContactsServiceError(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            ptr::null_mut()
+        },
+    }
+}
+
+/// Exports the contacts of a TariWallet as a JSON array
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `*mut c_char` - Returns a pointer to a char array of the JSON export. Note that it returns ptr::null_mut() if wallet
+/// is null or an error is encountered
+///
+/// # Safety
+/// The ```string_destroy``` method must be called when finished with a string from rust to prevent a memory leak
+#[no_mangle]
+pub unsafe extern "C" fn wallet_export_contacts(wallet: *mut TariWallet, error_out: *mut c_int) -> *mut c_char {
     let mut error = 0;
     ptr::swap(error_out, &mut error as *mut c_int);
     if wallet.is_null() {
         error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
-        ptr::swap(error_out, &mut error as *mut c_int)
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
     }
 
     match (*wallet)
         .runtime
-        .block_on((*wallet).wallet.transaction_service.set_num_confirmations_required(num))
+        .block_on((*wallet).wallet.contacts_service.export_contacts())
     {
-        Ok(()) => (),
+        Ok(json) => CString::into_raw(CString::new(json).unwrap()),
         Err(e) => {
-            error = LibWalletError::from(WalletError::TransactionServiceError(e)).code;
-            ptr::swap(error_out, &mut error as *mut c_int)
+            error = LibWalletError::from(WalletError::ContactsServiceError(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            ptr::null_mut()
         },
     }
 }
 
-/// Get the TariContacts from a TariWallet
+/// Imports contacts into a TariWallet from JSON produced by `wallet_export_contacts`. Contacts that already exist have
+/// their alias updated and nothing is imported if the JSON is invalid.
 ///
 /// ## Arguments
 /// `wallet` - The TariWallet pointer
+/// `contacts_json` - The pointer to a char array containing the JSON contacts
 /// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
 /// as an out parameter.
 ///
 /// ## Returns
-/// `*mut TariContacts` - returns the contacts, note that it returns ptr::null_mut() if
-/// wallet is null
+/// `c_uint` - Returns the number of contacts that were added or had their alias changed, zero if an error occurred
 ///
 /// # Safety
-/// The ```contacts_destroy``` method must be called when finished with a TariContacts to prevent a memory leak
+/// None
 #[no_mangle]
-pub unsafe extern "C" fn wallet_get_contacts(wallet: *mut TariWallet, error_out: *mut c_int) -> *mut TariContacts {
+pub unsafe extern "C" fn wallet_import_contacts(
+    wallet: *mut TariWallet,
+    contacts_json: *const c_char,
+    error_out: *mut c_int,
+) -> c_uint
+{
     let mut error = 0;
     ptr::swap(error_out, &mut error as *mut c_int);
-    let mut contacts = Vec::new();
     if wallet.is_null() {
         error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
         ptr::swap(error_out, &mut error as *mut c_int);
-        return ptr::null_mut();
+        return 0;
+    }
+    if contacts_json.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("contacts_json".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return 0;
     }
+    let json = CStr::from_ptr(contacts_json).to_str().unwrap();
 
-    let retrieved_contacts = (*wallet)
+    match (*wallet)
         .runtime
-        .block_on((*wallet).wallet.contacts_service.get_contacts());
-    match retrieved_contacts {
-        Ok(mut retrieved_contacts) => {
-            contacts.append(&mut retrieved_contacts);
-            Box::into_raw(Box::new(TariContacts(contacts)))
-        },
+        .block_on((*wallet).wallet.contacts_service.import_contacts(json))
+    {
+        Ok(imported) => imported as c_uint,
         Err(e) => {
             error = LibWalletError::from(WalletError::ContactsServiceError(e)).code;
             ptr::swap(error_out, &mut error as *mut c_int);
-            ptr::null_mut()
+            0
         },
     }
 }
@@ -3970,6 +5145,16 @@ pub unsafe extern "C" fn wallet_get_completed_transactions(
         return ptr::null_mut();
     }
 
+    let (active_account, account_transactions) = match active_account_transactions(wallet) {
+        Ok(v) => v,
+        Err(e) => {
+            error = LibWalletError::from(WalletError::OutputManagerError(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            return ptr::null_mut();
+        },
+    };
+    let in_account = |tx_id: TxId| account_of(&account_transactions, tx_id) == active_account;
+
     let completed_transactions = (*wallet)
         .runtime
         .block_on((*wallet).wallet.transaction_service.get_completed_transactions());
@@ -3983,6 +5168,7 @@ pub unsafe extern "C" fn wallet_get_completed_transactions(
                 .values()
                 .filter(|ct| ct.status != TransactionStatus::Completed)
                 .filter(|ct| ct.status != TransactionStatus::Broadcast)
+                .filter(|ct| in_account(ct.tx_id))
             {
                 completed.push(tx.clone());
             }
@@ -3996,6 +5182,77 @@ pub unsafe extern "C" fn wallet_get_completed_transactions(
     }
 }
 
+/// Exports all completed transactions of a TariWallet, including cancelled ones, as a JSON array
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `*mut c_char` - Returns a pointer to a char array of the JSON export. Note that it returns ptr::null_mut() if wallet
+/// is null or an error is encountered
+///
+/// # Safety
+/// The ```string_destroy``` method must be called when finished with a string from rust to prevent a memory leak
+#[no_mangle]
+pub unsafe extern "C" fn wallet_export_completed_transactions(
+    wallet: *mut TariWallet,
+    error_out: *mut c_int,
+) -> *mut c_char
+{
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    }
+
+    let mut transactions = Vec::new();
+    match (*wallet)
+        .runtime
+        .block_on((*wallet).wallet.transaction_service.get_completed_transactions())
+    {
+        Ok(completed) => transactions.extend(completed.into_iter().map(|(_, tx)| tx)),
+        Err(e) => {
+            error = LibWalletError::from(WalletError::TransactionServiceError(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            return ptr::null_mut();
+        },
+    }
+    match (*wallet).runtime.block_on(
+        (*wallet)
+            .wallet
+            .transaction_service
+            .get_cancelled_completed_transactions(),
+    ) {
+        Ok(cancelled) => transactions.extend(cancelled.into_iter().map(|(_, tx)| tx)),
+        Err(e) => {
+            error = LibWalletError::from(WalletError::TransactionServiceError(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            return ptr::null_mut();
+        },
+    }
+    transactions.sort_by_key(|tx| tx.timestamp);
+
+    match serde_json::to_string(&transactions) {
+        Ok(json) => match CString::new(json) {
+            Ok(v) => CString::into_raw(v),
+            Err(e) => {
+                error = LibWalletError::from(InterfaceError::SerializationError(e.to_string())).code;
+                ptr::swap(error_out, &mut error as *mut c_int);
+                ptr::null_mut()
+            },
+        },
+        Err(e) => {
+            error = LibWalletError::from(InterfaceError::SerializationError(e.to_string())).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            ptr::null_mut()
+        },
+    }
+}
+
 /// Get the TariPendingInboundTransactions from a TariWallet
 ///
 /// Currently a CompletedTransaction with the Status of Completed and Broadcast is considered Pending by the frontend
@@ -4027,13 +5284,23 @@ pub unsafe extern "C" fn wallet_get_pending_inbound_transactions(
         return ptr::null_mut();
     }
 
+    let (active_account, account_transactions) = match active_account_transactions(wallet) {
+        Ok(v) => v,
+        Err(e) => {
+            error = LibWalletError::from(WalletError::OutputManagerError(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            return ptr::null_mut();
+        },
+    };
+    let in_account = |tx_id: TxId| account_of(&account_transactions, tx_id) == active_account;
+
     let pending_transactions = (*wallet)
         .runtime
         .block_on((*wallet).wallet.transaction_service.get_pending_inbound_transactions());
 
     match pending_transactions {
         Ok(pending_transactions) => {
-            for tx in pending_transactions.values() {
+            for tx in pending_transactions.values().filter(|tx| in_account(tx.tx_id)) {
                 pending.push(tx.clone());
             }
 
@@ -4049,6 +5316,7 @@ pub unsafe extern "C" fn wallet_get_pending_inbound_transactions(
                     .values()
                     .filter(|ct| ct.status == TransactionStatus::Completed || ct.status == TransactionStatus::Broadcast)
                     .filter(|ct| ct.direction == TransactionDirection::Inbound)
+                    .filter(|ct| in_account(ct.tx_id))
                 {
                     pending.push(InboundTransaction::from(ct.clone()));
                 }
@@ -4095,12 +5363,22 @@ pub unsafe extern "C" fn wallet_get_pending_outbound_transactions(
         return ptr::null_mut();
     }
 
+    let (active_account, account_transactions) = match active_account_transactions(wallet) {
+        Ok(v) => v,
+        Err(e) => {
+            error = LibWalletError::from(WalletError::OutputManagerError(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            return ptr::null_mut();
+        },
+    };
+    let in_account = |tx_id: TxId| account_of(&account_transactions, tx_id) == active_account;
+
     let pending_transactions = (*wallet)
         .runtime
         .block_on((*wallet).wallet.transaction_service.get_pending_outbound_transactions());
     match pending_transactions {
         Ok(pending_transactions) => {
-            for tx in pending_transactions.values() {
+            for tx in pending_transactions.values().filter(|tx| in_account(tx.tx_id)) {
                 pending.push(tx.clone());
             }
             if let Ok(completed_txs) = (*wallet)
@@ -4115,6 +5393,7 @@ pub unsafe extern "C" fn wallet_get_pending_outbound_transactions(
                     .values()
                     .filter(|ct| ct.status == TransactionStatus::Completed || ct.status == TransactionStatus::Broadcast)
                     .filter(|ct| ct.direction == TransactionDirection::Outbound)
+                    .filter(|ct| in_account(ct.tx_id))
                 {
                     pending.push(OutboundTransaction::from(ct.clone()));
                 }
@@ -4199,16 +5478,26 @@ pub unsafe extern "C" fn wallet_get_cancelled_transactions(
         },
     };
 
+    let (active_account, account_transactions) = match active_account_transactions(wallet) {
+        Ok(v) => v,
+        Err(e) => {
+            error = LibWalletError::from(WalletError::OutputManagerError(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            return ptr::null_mut();
+        },
+    };
+    let in_account = |tx_id: TxId| account_of(&account_transactions, tx_id) == active_account;
+
     let mut completed = Vec::new();
-    for tx in completed_transactions.values() {
+    for tx in completed_transactions.values().filter(|tx| in_account(tx.tx_id)) {
         completed.push(tx.clone());
     }
-    for tx in inbound_transactions.values() {
+    for tx in inbound_transactions.values().filter(|tx| in_account(tx.tx_id)) {
         let mut inbound_tx = CompletedTransaction::from(tx.clone());
         inbound_tx.destination_public_key = (*wallet).wallet.comms.node_identity().public_key().clone();
         completed.push(inbound_tx);
     }
-    for tx in outbound_transactions.values() {
+    for tx in outbound_transactions.values().filter(|tx| in_account(tx.tx_id)) {
         let mut outbound_tx = CompletedTransaction::from(tx.clone());
         outbound_tx.source_public_key = (*wallet).wallet.comms.node_identity().public_key().clone();
         completed.push(outbound_tx);
@@ -5309,6 +6598,106 @@ pub unsafe extern "C" fn wallet_clear_value(
     }
 }
 
+/// Check if a Wallet has the data of an In Progress Recovery in its database.
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer.
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `bool` - Return a boolean value indicating whether there is an in progress recovery or not. An error will also
+/// result in a false result.
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn wallet_is_recovery_in_progress(wallet: *mut TariWallet, error_out: *mut c_int) -> bool {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return false;
+    }
+
+    match (*wallet)
+        .runtime
+        .block_on(is_recovery_in_progress(&(*wallet).wallet.db))
+    {
+        Ok(result) => result,
+        Err(e) => {
+            error = LibWalletError::from(e).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            false
+        },
+    }
+}
+
+/// Starts the Wallet recovery process. The outputs stored on the blockchain are streamed from the provided base node
+/// and any that can be rewound with this wallet's keys are imported. If a previous recovery was interrupted it will be
+/// resumed from where it stopped.
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer.
+/// `base_node_public_key` - The TariPublicKey pointer of the Base Node the recovery process will use
+/// `recovery_progress_callback` - The callback function pointer that will be used to asynchronously communicate
+/// progress to the client. The first argument is the event type and the meaning of the two values that follow depend
+/// on it:
+///     - ConnectingToBaseNode, 0, 0
+///     - ConnectedToBaseNode, 0, latency in milliseconds
+///     - ConnectionToBaseNodeFailed, number of attempts, retry limit
+///     - Progress, current output position, total outputs
+///     - Completed, number of recovered outputs, total value of the recovered outputs in MicroTari
+///     - RecoveryFailed, 0, 0
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `bool` - Return a boolean value indicating whether the process started successfully or not, the process will
+/// continue to run asynchronously and communicate it progress via the callback. An error will also produce a false
+/// result.
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn wallet_start_recovery(
+    wallet: *mut TariWallet,
+    base_node_public_key: *mut TariPublicKey,
+    recovery_progress_callback: unsafe extern "C" fn(u8, u64, u64),
+    error_out: *mut c_int,
+) -> bool
+{
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return false;
+    }
+
+    if base_node_public_key.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("base_node_public_key".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return false;
+    }
+
+    let recovery_task = WalletRecoveryTask::new((*wallet).wallet.clone(), vec![(*base_node_public_key).clone()]);
+    let event_stream = recovery_task.get_event_receiver();
+    let recovery_join_handle = (*wallet).runtime.spawn(recovery_task.run());
+
+    // Spawn a task to monitor the recovery process events and call the callback appropriately
+    (*wallet).runtime.spawn(recovery_event_monitoring(
+        event_stream,
+        recovery_join_handle,
+        recovery_progress_callback,
+    ));
+
+    true
+}
+
 /// This function will produce a partial backup of the specified wallet database file. This backup will be written to
 /// the provided file (full path must include the filename and extension) and will include the full wallet db but will
 /// clear the sensitive Comms Private Key
@@ -5620,7 +7009,11 @@ mod test {
             type_of((*tx).clone()),
             std::any::type_name::<TariCompletedTransaction>()
         );
-        assert_eq!((*tx).status, TransactionStatus::Completed);
+        // Outputs found on chain are reported as imported transactions
+        assert!(matches!(
+            (*tx).status,
+            TransactionStatus::Completed | TransactionStatus::Imported
+        ));
         let mut lock = CALLBACK_STATE_FFI.lock().unwrap();
         lock.received_finalized_tx_callback_called = true;
         drop(lock);
@@ -5703,6 +7096,10 @@ mod test {
         // assert!(true); //optimized out by compiler
     }
 
+    unsafe extern "C" fn base_node_state_callback(_height: c_ulonglong, _is_synced: bool) {
+        // assert!(true); //optimized out by compiler
+    }
+
     unsafe extern "C" fn received_tx_callback_bob(tx: *mut TariPendingInboundTransaction) {
         assert_eq!(tx.is_null(), false);
         assert_eq!(
@@ -6012,6 +7409,58 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_payment_request() {
+        unsafe {
+            let mut error = 0;
+            let error_ptr = &mut error as *mut c_int;
+            let private_key = private_key_generate();
+            let public_key = public_key_from_private_key(private_key, error_ptr);
+            let message_str = CString::new("Lunch & drinks").unwrap();
+            let message: *const c_char = CString::into_raw(message_str) as *const c_char;
+
+            let request = payment_request_create(public_key, 5000, message, 1_614_556_800, 0, error_ptr);
+            assert_eq!(error, 0);
+            assert!(payment_request_is_expired(request, error_ptr));
+
+            let uri = payment_request_to_uri(request, error_ptr);
+            let parsed = payment_request_from_string(uri, error_ptr);
+            assert_eq!(error, 0);
+            assert_eq!(*parsed, *request);
+            assert_eq!(payment_request_get_amount(parsed, error_ptr), 5000);
+            assert_eq!(payment_request_get_expiry(parsed, error_ptr), 1_614_556_800);
+            assert_eq!(payment_request_get_fee_per_gram(parsed, error_ptr), 0);
+            let parsed_message = payment_request_get_message(parsed, error_ptr);
+            assert_eq!(CStr::from_ptr(parsed_message).to_str().unwrap(), "Lunch & drinks");
+            let parsed_key = payment_request_get_public_key(parsed, error_ptr);
+            assert_eq!(*parsed_key, *public_key);
+
+            let emoji_request = payment_request_to_emoji_string(request, error_ptr);
+            let parsed_emoji = payment_request_from_string(emoji_request, error_ptr);
+            assert_eq!(*parsed_emoji, *request);
+
+            let invalid = CString::into_raw(CString::new("tari:invalid?amount=1").unwrap());
+            let invalid_request = payment_request_from_string(invalid, error_ptr);
+            assert!(invalid_request.is_null());
+            assert_eq!(
+                error,
+                LibWalletError::from(InterfaceError::InvalidPaymentRequest(String::new())).code
+            );
+
+            payment_request_destroy(request);
+            payment_request_destroy(parsed);
+            payment_request_destroy(parsed_emoji);
+            public_key_destroy(parsed_key);
+            public_key_destroy(public_key);
+            private_key_destroy(private_key);
+            string_destroy(message as *mut c_char);
+            string_destroy(uri);
+            string_destroy(emoji_request);
+            string_destroy(parsed_message);
+            string_destroy(invalid);
+        }
+    }
+
     #[test]
     fn test_contact_dont_panic() {
         unsafe {
@@ -6238,6 +7687,135 @@ mod test {
             assert_eq!(fee, 0);
             assert_eq!(error, 101);
 
+            // unspent outputs
+            let balance = (*alice_wallet)
+                .runtime
+                .block_on((*alice_wallet).wallet.output_manager_service.get_balance())
+                .unwrap();
+            let unspent_outputs = wallet_get_unspent_outputs(alice_wallet, error_ptr);
+            assert_eq!(error, 0);
+            let num_unspent = unblinded_outputs_get_length(unspent_outputs, error_ptr);
+            assert!(num_unspent > 0);
+            let mut unspent_total = 0;
+            for i in 0..num_unspent {
+                let output = unblinded_outputs_get_at(unspent_outputs, i, error_ptr);
+                assert_eq!(error, 0);
+                let value = unblinded_output_get_value(output, error_ptr);
+                assert_eq!(error, 0);
+                assert_eq!(value, u64::from((*output).value));
+                unspent_total += value;
+                let maturity = unblinded_output_get_maturity(output, error_ptr);
+                assert_eq!(error, 0);
+                assert_eq!(maturity, (*output).features.maturity);
+                let commitment = unblinded_output_get_commitment(output, error_ptr);
+                assert_eq!(error, 0);
+                let factories = CryptoFactories::default();
+                let expected = (*output).as_transaction_input(&factories.commitment, (*output).features.clone());
+                assert_eq!((*commitment).0, expected.commitment.as_bytes().to_vec());
+                byte_vector_destroy(commitment);
+                unblinded_output_destroy(output);
+            }
+            assert_eq!(MicroTari::from(unspent_total), balance.available_balance);
+            assert!(unblinded_outputs_get_at(unspent_outputs, num_unspent, error_ptr).is_null());
+            assert_eq!(error, LibWalletError::from(InterfaceError::PositionInvalidError).code);
+            unblinded_outputs_destroy(unspent_outputs);
+
+            assert_eq!(unblinded_outputs_get_length(ptr::null_mut(), error_ptr), 0);
+            assert_ne!(error, 0);
+            assert!(unblinded_outputs_get_at(ptr::null_mut(), 0, error_ptr).is_null());
+            assert_ne!(error, 0);
+            assert_eq!(unblinded_output_get_value(ptr::null_mut(), error_ptr), 0);
+            assert_ne!(error, 0);
+            assert_eq!(unblinded_output_get_maturity(ptr::null_mut(), error_ptr), 0);
+            assert_ne!(error, 0);
+            assert!(unblinded_output_get_commitment(ptr::null_mut(), error_ptr).is_null());
+            assert_ne!(error, 0);
+
+            // fee estimate with the selected outputs
+            let mut selection_fee: c_ulonglong = 0;
+            let selection_fee_ptr = &mut selection_fee as *mut c_ulonglong;
+            let selected =
+                wallet_get_fee_estimate_with_selection(alice_wallet, 100, 25, 1, 2, selection_fee_ptr, error_ptr);
+            assert_eq!(error, 0);
+            assert_eq!(
+                selection_fee,
+                wallet_get_fee_estimate(alice_wallet, 100, 25, 1, 2, error_ptr)
+            );
+            let num_selected = unblinded_outputs_get_length(selected, error_ptr);
+            assert!(num_selected > 0);
+            let mut selected_total = 0;
+            for i in 0..num_selected {
+                let output = unblinded_outputs_get_at(selected, i, error_ptr);
+                selected_total += unblinded_output_get_value(output, error_ptr);
+                unblinded_output_destroy(output);
+            }
+            assert!(selected_total >= 100 + selection_fee);
+            unblinded_outputs_destroy(selected);
+
+            let selected = wallet_get_fee_estimate_with_selection(
+                alice_wallet,
+                1_000_000_000,
+                2_500,
+                1,
+                1,
+                selection_fee_ptr,
+                error_ptr,
+            );
+            assert!(selected.is_null());
+            assert_eq!(error, 101);
+            let selected =
+                wallet_get_fee_estimate_with_selection(alice_wallet, 100, 25, 1, 2, ptr::null_mut(), error_ptr);
+            assert!(selected.is_null());
+            assert_ne!(error, 0);
+
+            // base node state, no base node has been set so nothing has been reported yet
+            assert_eq!(wallet_get_chain_tip_height(alice_wallet, error_ptr), 0);
+            assert_eq!(error, 0);
+            assert_eq!(wallet_is_base_node_synced(alice_wallet, error_ptr), false);
+            assert_eq!(error, 0);
+            assert_eq!(
+                wallet_set_base_node_state_callback(alice_wallet, base_node_state_callback, error_ptr),
+                true
+            );
+            assert_eq!(error, 0);
+            assert!((*alice_wallet).callback_base_node_state.lock().unwrap().is_some());
+            assert_eq!(wallet_get_chain_tip_height(ptr::null_mut(), error_ptr), 0);
+            assert_ne!(error, 0);
+            assert_eq!(wallet_is_base_node_synced(ptr::null_mut(), error_ptr), false);
+            assert_ne!(error, 0);
+            assert_eq!(
+                wallet_set_base_node_state_callback(ptr::null_mut(), base_node_state_callback, error_ptr),
+                false
+            );
+            assert_ne!(error, 0);
+
+            // export of the completed and cancelled transactions
+            let completed = (*alice_wallet)
+                .runtime
+                .block_on((*alice_wallet).wallet.transaction_service.get_completed_transactions())
+                .unwrap();
+            let cancelled = (*alice_wallet)
+                .runtime
+                .block_on(
+                    (*alice_wallet)
+                        .wallet
+                        .transaction_service
+                        .get_cancelled_completed_transactions(),
+                )
+                .unwrap();
+            let export_str = wallet_export_completed_transactions(alice_wallet, error_ptr);
+            assert_eq!(error, 0);
+            let export: Vec<tari_wallet::transaction_service::storage::models::CompletedTransaction> =
+                serde_json::from_str(CStr::from_ptr(export_str).to_str().unwrap()).unwrap();
+            assert_eq!(export.len(), completed.len() + cancelled.len());
+            assert!(export.windows(2).all(|w| w[0].timestamp <= w[1].timestamp));
+            assert!(export
+                .iter()
+                .all(|tx| completed.contains_key(&tx.tx_id) || cancelled.contains_key(&tx.tx_id)));
+            string_destroy(export_str);
+            assert!(wallet_export_completed_transactions(ptr::null_mut(), error_ptr).is_null());
+            assert_ne!(error, 0);
+
             assert_eq!(
                 (wallet_get_completed_transactions(&mut (*alice_wallet), error_ptr)).is_null(),
                 false
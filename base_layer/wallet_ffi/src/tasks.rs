@@ -0,0 +1,110 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use futures::StreamExt;
+use log::*;
+use tari_wallet::{error::WalletError, tasks::wallet_recovery::WalletRecoveryEvent};
+use tokio::{sync::broadcast, task::JoinHandle};
+
+const LOG_TARGET: &str = "wallet_ffi";
+
+const CONNECTING_TO_BASE_NODE: u8 = 0;
+const CONNECTED_TO_BASE_NODE: u8 = 1;
+const CONNECTION_TO_BASE_NODE_FAILED: u8 = 2;
+const PROGRESS: u8 = 3;
+const COMPLETED: u8 = 4;
+const RECOVERY_FAILED: u8 = 5;
+
+/// Forwards the events of a running wallet recovery to the `recovery_progress_callback` until the recovery task exits.
+/// The first argument of the callback identifies the event and the meaning of the two values that follow depends on
+/// it, see `wallet_start_recovery` for the details.
+pub async fn recovery_event_monitoring(
+    mut event_stream: broadcast::Receiver<WalletRecoveryEvent>,
+    recovery_join_handle: JoinHandle<Result<(), WalletError>>,
+    recovery_progress_callback: unsafe extern "C" fn(u8, u64, u64),
+)
+{
+    while let Some(event) = event_stream.next().await {
+        match event {
+            Ok(WalletRecoveryEvent::ConnectingToBaseNode(peer)) => {
+                unsafe {
+                    (recovery_progress_callback)(CONNECTING_TO_BASE_NODE, 0u64, 0u64);
+                }
+                info!(target: LOG_TARGET, "Attempting connection to base node {}", peer);
+            },
+            Ok(WalletRecoveryEvent::ConnectedToBaseNode(peer, latency)) => {
+                unsafe {
+                    (recovery_progress_callback)(CONNECTED_TO_BASE_NODE, 0u64, latency.as_millis() as u64);
+                }
+                info!(
+                    target: LOG_TARGET,
+                    "Connected to base node {} with a latency of {} ms",
+                    peer,
+                    latency.as_millis()
+                );
+            },
+            Ok(WalletRecoveryEvent::ConnectionFailedToBaseNode(peer, attempt, retry_limit, error)) => {
+                unsafe {
+                    (recovery_progress_callback)(CONNECTION_TO_BASE_NODE_FAILED, attempt as u64, retry_limit as u64);
+                }
+                warn!(
+                    target: LOG_TARGET,
+                    "Failed to connect to base node {} (attempt {} of {}): {}", peer, attempt, retry_limit, error
+                );
+            },
+            Ok(WalletRecoveryEvent::Progress(current, total)) => {
+                unsafe {
+                    (recovery_progress_callback)(PROGRESS, current, total);
+                }
+                debug!(target: LOG_TARGET, "Recovery progress: {} of {}", current, total);
+            },
+            Ok(WalletRecoveryEvent::Completed(num_utxos, total_amount)) => {
+                unsafe {
+                    (recovery_progress_callback)(COMPLETED, num_utxos, u64::from(total_amount));
+                }
+                info!(
+                    target: LOG_TARGET,
+                    "Recovery complete: {} outputs recovered with a total value of {}", num_utxos, total_amount
+                );
+            },
+            Ok(WalletRecoveryEvent::RecoveryFailed(error)) => {
+                unsafe {
+                    (recovery_progress_callback)(RECOVERY_FAILED, 0u64, 0u64);
+                }
+                warn!(target: LOG_TARGET, "Wallet recovery failed: {}", error);
+            },
+            Err(e) => {
+                trace!(
+                    target: LOG_TARGET,
+                    "Error reading from wallet recovery event stream: {}",
+                    e
+                );
+            },
+        }
+    }
+
+    match recovery_join_handle.await {
+        Ok(Ok(_)) => {},
+        Ok(Err(e)) => error!(target: LOG_TARGET, "Wallet recovery exited with an error: {}", e),
+        Err(e) => error!(target: LOG_TARGET, "Wallet recovery task could not be joined: {}", e),
+    }
+}
@@ -57,6 +57,8 @@ struct TariContacts;
 
 struct TariContact;
 
+struct TariPaymentRequest;
+
 struct TariCompletedTransactions;
 
 struct TariCompletedTransaction;
@@ -81,6 +83,12 @@ struct TariExcessPublicNonce;
 
 struct TariExcessSignature;
 
+struct TariUnblindedOutputs;
+
+struct TariUnblindedOutput;
+
+struct TariAccounts;
+
 /// -------------------------------- Transport Types ----------------------------------------------- ///
 
 // Creates a memory transport type
@@ -173,6 +181,41 @@ char *seed_words_get_at(struct TariSeedWords *seed_words, unsigned int position,
 // Frees the memory for a TariSeedWords collection
 void seed_words_destroy(struct TariSeedWords *seed_words);
 
+/// -------------------------------- PaymentRequest ------------------------------------------------------ ///
+
+// Creates a TariPaymentRequest, zero amount, expiry (unix seconds) or fee_per_gram leave that field unspecified
+struct TariPaymentRequest *payment_request_create(struct TariPublicKey *public_key, unsigned long long amount, const char *message, unsigned long long expiry, unsigned long long fee_per_gram, int* error_out);
+
+// Parses a TariPaymentRequest from a URI, an emoji ID with request parameters, or a bare emoji ID or public key
+struct TariPaymentRequest *payment_request_from_string(const char *request, int* error_out);
+
+// Encodes a TariPaymentRequest as a tari: URI
+char *payment_request_to_uri(struct TariPaymentRequest *request, int* error_out);
+
+// Encodes a TariPaymentRequest as an emoji ID followed by the request parameters
+char *payment_request_to_emoji_string(struct TariPaymentRequest *request, int* error_out);
+
+// Gets the TariPublicKey that a TariPaymentRequest asks to be paid
+struct TariPublicKey *payment_request_get_public_key(struct TariPaymentRequest *request, int* error_out);
+
+// Gets the requested amount in MicroTari, 0 if not specified
+unsigned long long payment_request_get_amount(struct TariPaymentRequest *request, int* error_out);
+
+// Gets the message of a TariPaymentRequest
+char *payment_request_get_message(struct TariPaymentRequest *request, int* error_out);
+
+// Gets the expiry of a TariPaymentRequest as a unix timestamp in seconds, 0 if it does not expire
+unsigned long long payment_request_get_expiry(struct TariPaymentRequest *request, int* error_out);
+
+// Gets the suggested fee per gram in MicroTari, 0 if not specified
+unsigned long long payment_request_get_fee_per_gram(struct TariPaymentRequest *request, int* error_out);
+
+// Checks whether a TariPaymentRequest has expired
+bool payment_request_is_expired(struct TariPaymentRequest *request, int* error_out);
+
+// Frees memory for a TariPaymentRequest
+void payment_request_destroy(struct TariPaymentRequest *request);
+
 /// -------------------------------- Contact ------------------------------------------------------ ///
 
 // Creates a TariContact
@@ -198,6 +241,45 @@ struct TariContact *contacts_get_at(struct TariContacts *contacts, unsigned int
 // Frees memory for TariContacts
 void contacts_destroy(struct TariContacts *contacts);
 
+/// -------------------------------- UnblindedOutputs ------------------------------------------------------ ///
+
+// Gets number of elements in TariUnblindedOutputs
+unsigned int unblinded_outputs_get_length(struct TariUnblindedOutputs *outputs, int* error_out);
+
+// Gets a TariUnblindedOutput from TariUnblindedOutputs at position
+struct TariUnblindedOutput *unblinded_outputs_get_at(struct TariUnblindedOutputs *outputs, unsigned int position, int* error_out);
+
+// Frees memory for TariUnblindedOutputs
+void unblinded_outputs_destroy(struct TariUnblindedOutputs *outputs);
+
+/// -------------------------------- Accounts ------------------------------------------------------ ///
+
+// Gets number of elements in TariAccounts
+unsigned int accounts_get_length(struct TariAccounts *accounts, int* error_out);
+
+// Gets the name of the account in TariAccounts at position
+char *accounts_get_name_at(struct TariAccounts *accounts, unsigned int position, int* error_out);
+
+// Gets the id of the account in TariAccounts at position
+unsigned long long accounts_get_id_at(struct TariAccounts *accounts, unsigned int position, int* error_out);
+
+// Frees memory for TariAccounts
+void accounts_destroy(struct TariAccounts *accounts);
+
+/// -------------------------------- UnblindedOutput ------------------------------------------------------ ///
+
+// Gets the value of a TariUnblindedOutput in MicroTari
+unsigned long long unblinded_output_get_value(struct TariUnblindedOutput *output, int* error_out);
+
+// Gets the block height from which a TariUnblindedOutput can be spent
+unsigned long long unblinded_output_get_maturity(struct TariUnblindedOutput *output, int* error_out);
+
+// Gets the commitment of a TariUnblindedOutput as a ByteVector
+struct ByteVector *unblinded_output_get_commitment(struct TariUnblindedOutput *output, int* error_out);
+
+// Frees memory for a TariUnblindedOutput
+void unblinded_output_destroy(struct TariUnblindedOutput *output);
+
 /// -------------------------------- CompletedTransaction ------------------------------------------------------ ///
 
 // Gets the destination TariPublicKey of a TariCompletedTransaction
@@ -486,6 +568,35 @@ unsigned long long wallet_get_pending_outgoing_balance(struct TariWallet *wallet
 // Get a fee estimate from a TariWallet for a given amount
 unsigned long long wallet_get_fee_estimate(struct TariWallet *wallet, unsigned long long amount, unsigned long long fee_per_gram, unsigned long long num_kernels, unsigned long long num_outputs, int* error_out);
 
+// Get a fee estimate from a TariWallet along with the unspent outputs that would be selected to fund it, the fee is
+// written to fee_out
+struct TariUnblindedOutputs *wallet_get_fee_estimate_with_selection(struct TariWallet *wallet, unsigned long long amount, unsigned long long fee_per_gram, unsigned long long num_kernels, unsigned long long num_outputs, unsigned long long* fee_out, int* error_out);
+
+// Get the unspent outputs of a TariWallet
+struct TariUnblindedOutputs *wallet_get_unspent_outputs(struct TariWallet *wallet, int* error_out);
+
+// Creates a new named account in a TariWallet and returns its id
+unsigned long long wallet_create_account(struct TariWallet *wallet, const char *name, int* error_out);
+
+// Get the accounts of a TariWallet, the default account is always first
+struct TariAccounts *wallet_get_accounts(struct TariWallet *wallet, int* error_out);
+
+// Selects the account that balances, outputs and transaction lists of a TariWallet apply to
+bool wallet_set_active_account(struct TariWallet *wallet, const char *name, int* error_out);
+
+// Get the name of the active account of a TariWallet
+char *wallet_get_active_account_name(struct TariWallet *wallet, int* error_out);
+
+// Get the chain tip height last reported by the wallet's base node, 0 if not yet known
+unsigned long long wallet_get_chain_tip_height(struct TariWallet *wallet, int* error_out);
+
+// Get whether the wallet's base node last reported that it is synced, false if not yet known
+bool wallet_is_base_node_synced(struct TariWallet *wallet, int* error_out);
+
+// Register a callback that is called with the chain tip height and synced state whenever updated chain state is
+// received from the wallet's base node, replacing any previously registered callback
+bool wallet_set_base_node_state_callback(struct TariWallet *wallet, void (*callback_base_node_state)(unsigned long long, bool), int* error_out);
+
 // Get the number of mining confirmations by the wallet transaction service
 unsigned long long wallet_get_num_confirmations_required(struct TariWallet *wallet, int* error_out);
 
@@ -499,9 +610,18 @@ unsigned long long wallet_send_transaction(struct TariWallet *wallet, struct Tar
 // Get the TariContacts from a TariWallet
 struct TariContacts *wallet_get_contacts(struct TariWallet *wallet,int* error_out);
 
+// Export the TariContacts of a TariWallet as JSON
+char *wallet_export_contacts(struct TariWallet *wallet, int* error_out);
+
+// Import contacts into a TariWallet from JSON produced by wallet_export_contacts, returns the number added or changed
+unsigned int wallet_import_contacts(struct TariWallet *wallet, const char *contacts_json, int* error_out);
+
 // Get the TariCompletedTransactions from a TariWallet
 struct TariCompletedTransactions *wallet_get_completed_transactions(struct TariWallet *wallet,int* error_out);
 
+// Export all completed and cancelled transactions from a TariWallet as a JSON array
+char *wallet_export_completed_transactions(struct TariWallet *wallet, int* error_out);
+
 // Get the TariPendingOutboundTransactions from a TariWallet
 struct TariPendingOutboundTransactions *wallet_get_pending_outbound_transactions(struct TariWallet *wallet,int* error_out);
 
@@ -634,6 +754,53 @@ const char *wallet_get_value(struct TariWallet *wallet, const char* key, int* er
 bool wallet_clear_value(struct TariWallet *wallet, const char* key, int* error_out);
 
 
+/// Check if a Wallet has the data of an In Progress Recovery in its database.
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer.
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `bool` - Return a boolean value indicating whether there is an in progress recovery or not. An error will also
+/// result in a false result.
+bool wallet_is_recovery_in_progress(struct TariWallet *wallet, int* error_out);
+
+/// Starts the Wallet recovery process. The outputs stored on the blockchain are streamed from the provided base node
+/// and any that can be rewound with this wallet's keys are imported. If a previous recovery was interrupted it will be
+/// resumed from where it stopped.
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer.
+/// `base_node_public_key` - The TariPublicKey pointer of the Base Node the recovery process will use
+/// `recovery_progress_callback` - The callback function pointer that will be used to asynchronously communicate
+/// progress to the client. The first argument is the event type and the meaning of the two values that follow depend
+/// on it:
+///     - ConnectingToBaseNode, 0, 0
+///     - ConnectedToBaseNode, 0, latency in milliseconds
+///     - ConnectionToBaseNodeFailed, number of attempts, retry limit
+///     - Progress, current output position, total outputs
+///     - Completed, number of recovered outputs, total value of the recovered outputs in MicroTari
+///     - RecoveryFailed, 0, 0
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `bool` - Return a boolean value indicating whether the process started successfully or not, the process will
+/// continue to run asynchronously and communicate it progress via the callback. An error will also produce a false
+/// result.
+///
+/// The event types are:
+/// enum RecoveryEvent {
+///        ConnectingToBaseNode,       // 0
+///        ConnectedToBaseNode,        // 1
+///        ConnectionToBaseNodeFailed, // 2
+///        Progress,                   // 3
+///        Completed,                  // 4
+///        RecoveryFailed,             // 5
+///    }
+bool wallet_start_recovery(struct TariWallet *wallet, struct TariPublicKey *base_node_public_key, void (*recovery_progress_callback)(unsigned char, unsigned long long, unsigned long long), int* error_out);
+
 // Frees memory for a TariWallet
 void wallet_destroy(struct TariWallet *wallet);
 
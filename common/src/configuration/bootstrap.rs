@@ -105,6 +105,12 @@ pub struct ConfigBootstrap {
     /// This will rebuild the db, adding block for block in
     #[structopt(long, alias("rebuild_db"))]
     pub rebuild_db: bool,
+    /// Verify the integrity of the blockchain database at startup
+    #[structopt(long, alias("verify_db"))]
+    pub verify_db: bool,
+    /// Used with `--verify-db`. Rewinds the blockchain to the last consistent block if an inconsistency is found
+    #[structopt(long, alias("repair_db"))]
+    pub repair_db: bool,
     /// Path to input file of commands
     #[structopt(short, long, alias("input"), alias("script"), parse(from_os_str))]
     pub input_file: Option<PathBuf>,
@@ -126,6 +132,14 @@ pub struct ConfigBootstrap {
     /// Wallet notify script
     #[structopt(long, alias("notify"))]
     pub wallet_notify: Option<PathBuf>,
+    /// Create a watch-only wallet from the public rewind keys of another wallet, given as
+    /// `<rewind public key>,<rewind blinding public key>` in hex
+    #[structopt(long)]
+    pub watch_only: Option<String>,
+    /// Run the base node as a self-contained developer chain on LocalNet, with trivial difficulty, no seed peers and
+    /// blocks produced on demand
+    #[structopt(long)]
+    pub regtest: bool,
 }
 
 impl Default for ConfigBootstrap {
@@ -138,6 +152,8 @@ impl Default for ConfigBootstrap {
             create_id: false,
             daemon_mode: false,
             rebuild_db: false,
+            verify_db: false,
+            repair_db: false,
             input_file: None,
             command: None,
             clean_orphans_db: false,
@@ -145,6 +161,8 @@ impl Default for ConfigBootstrap {
             change_password: false,
             recovery: false,
             wallet_notify: None,
+            watch_only: None,
+            regtest: false,
         }
     }
 }
@@ -289,6 +307,7 @@ pub enum ApplicationType {
 mod test {
     use crate::{
         configuration::bootstrap::ApplicationType,
+        default_config,
         dir_utils,
         dir_utils::default_subdir,
         load_configuration,
@@ -441,4 +460,26 @@ mod test {
         assert!(log_base_layer_file_exists);
         assert!(log_other_file_exists);
     }
+
+    #[test]
+    fn test_regtest_selects_localnet() {
+        let bootstrap = ConfigBootstrap::from_iter_safe(vec!["", "--regtest"]).expect("failed to process arguments");
+        assert!(bootstrap.regtest);
+        let cfg = default_config(&bootstrap);
+        assert_eq!(cfg.get_str("base_node.network").unwrap(), "localnet");
+
+        // The override survives a config file that selects another network
+        let temp_dir = tempdir().unwrap();
+        let config_path = temp_dir.path().join("config.toml");
+        std::fs::write(&config_path, "[base_node]\nnetwork = \"stibbons\"\n").unwrap();
+        let mut cfg = default_config(&bootstrap);
+        cfg.merge(config::File::from(config_path)).unwrap();
+        assert_eq!(cfg.get_str("base_node.network").unwrap(), "localnet");
+
+        let bootstrap = ConfigBootstrap::from_iter_safe(vec![""]).expect("failed to process arguments");
+        assert_eq!(
+            default_config(&bootstrap).get_str("base_node.network").unwrap(),
+            "mainnet"
+        );
+    }
 }
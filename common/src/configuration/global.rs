@@ -96,6 +96,7 @@ pub struct GlobalConfig {
     pub transaction_routing_mechanism: String,
     pub transaction_num_confirmations_required: u64,
     pub console_wallet_password: Option<String>,
+    pub console_wallet_signer: Option<String>,
     pub wallet_command_send_wait_stage: String,
     pub wallet_command_send_wait_timeout: u64,
     pub wallet_base_node_service_peers: Vec<String>,
@@ -115,6 +116,10 @@ pub struct GlobalConfig {
     pub auto_ping_interval: u64,
     pub blocks_behind_before_considered_lagging: u64,
     pub flood_ban_max_msg_count: usize,
+    pub consensus_file: Option<PathBuf>,
+    pub genesis_block_file: Option<PathBuf>,
+    pub max_bandwidth_bytes_per_sec: Option<u64>,
+    pub max_peer_bandwidth_bytes_per_sec: Option<u64>,
 }
 
 impl GlobalConfig {
@@ -150,6 +155,7 @@ fn convert_node_config(network: Network, cfg: Config) -> Result<GlobalConfig, Co
     let db_type = match db_type.as_str() {
         "memory" => Ok(DatabaseType::Memory),
         "lmdb" => Ok(DatabaseType::LMDB(data_dir.join("db"))),
+        "sqlite" => Ok(DatabaseType::Sqlite(data_dir.join("db_sqlite"))),
         invalid_opt => Err(ConfigurationError::new(
             "base_node.db_type",
             &format!("Invalid option: {}", invalid_opt),
@@ -392,6 +398,25 @@ fn convert_node_config(network: Network, cfg: Config) -> Result<GlobalConfig, Co
     let key = config_string("base_node", &net_str, "blocks_behind_before_considered_lagging");
     let blocks_behind_before_considered_lagging = optional(cfg.get_int(&key))?.unwrap_or(0) as u64;
 
+    // Custom network consensus constants and genesis block, only used on localnet
+    let key = config_string("base_node", &net_str, "consensus_file");
+    let consensus_file = optional(cfg.get_str(&key))?.map(PathBuf::from);
+    let key = config_string("base_node", &net_str, "genesis_block_file");
+    let genesis_block_file = optional(cfg.get_str(&key))?.map(PathBuf::from);
+
+    if network != Network::LocalNet && (consensus_file.is_some() || genesis_block_file.is_some()) {
+        return Err(ConfigurationError::new(
+            &key,
+            "Custom consensus and genesis block files can only be used on the localnet network",
+        ));
+    }
+
+    // Bandwidth caps for nodes on metered links
+    let key = config_string("base_node", &net_str, "max_bandwidth_bytes_per_sec");
+    let max_bandwidth_bytes_per_sec = optional(cfg.get_int(&key).map(|n| n as u64))?;
+    let key = config_string("base_node", &net_str, "max_peer_bandwidth_bytes_per_sec");
+    let max_peer_bandwidth_bytes_per_sec = optional(cfg.get_int(&key).map(|n| n as u64))?;
+
     // set wallet_db_file
     let key = "wallet.wallet_db_file".to_string();
     let wallet_db_file = cfg
@@ -467,6 +492,9 @@ fn convert_node_config(network: Network, cfg: Config) -> Result<GlobalConfig, Co
     let key = "wallet.password";
     let console_wallet_password = optional(cfg.get_str(key))?;
 
+    let key = "wallet.signer";
+    let console_wallet_signer = optional(cfg.get_str(key))?;
+
     let key = "wallet.notify";
     let console_wallet_notify_file = optional(cfg.get_str(key))?.map(PathBuf::from);
 
@@ -621,6 +649,7 @@ fn convert_node_config(network: Network, cfg: Config) -> Result<GlobalConfig, Co
         transaction_routing_mechanism,
         transaction_num_confirmations_required,
         console_wallet_password,
+        console_wallet_signer,
         wallet_command_send_wait_stage,
         wallet_command_send_wait_timeout,
         wallet_base_node_service_peers,
@@ -640,6 +669,10 @@ fn convert_node_config(network: Network, cfg: Config) -> Result<GlobalConfig, Co
         auto_ping_interval,
         blocks_behind_before_considered_lagging,
         flood_ban_max_msg_count,
+        consensus_file,
+        genesis_block_file,
+        max_bandwidth_bytes_per_sec,
+        max_peer_bandwidth_bytes_per_sec,
     })
 }
 
@@ -709,12 +742,32 @@ fn network_transport_config(cfg: &Config, network: &str) -> Result<CommsTranspor
                 None => None,
             };
 
+            let key = config_string("base_node", network, "tor_additional_onion_services");
+            let additional_onion_services = optional(cfg.get_array(&key))
+                .map_err(|err| ConfigurationError::new(&key, &err.to_string()))?
+                .unwrap_or_default()
+                .into_iter()
+                .map(|v| {
+                    v.into_str()
+                        .map_err(|err| err.to_string())
+                        .and_then(|s| s.parse::<TorOnionService>())
+                        .map_err(|err| ConfigurationError::new(&key, &err))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let key = config_string("base_node", network, "tor_key_rotation_interval");
+            let key_rotation_interval = optional(cfg.get_int(&key))
+                .map_err(|err| ConfigurationError::new(&key, &err.to_string()))?
+                .map(|secs| Duration::from_secs(secs as u64));
+
             Ok(CommsTransport::TorHiddenService {
                 control_server_address,
                 auth,
                 socks_address_override,
                 forward_address,
                 onion_port,
+                additional_onion_services,
+                key_rotation_interval,
             })
         },
         "socks5" => {
@@ -736,6 +789,12 @@ fn network_transport_config(cfg: &Config, network: &str) -> Result<CommsTranspor
                 auth,
             })
         },
+        "websocket" => {
+            let key = config_string("base_node", network, "websocket_listener_address");
+            let listener_address = get_conf_multiaddr(&key)?;
+
+            Ok(CommsTransport::WebSocket { listener_address })
+        },
         t => Err(ConfigurationError::new(
             &transport_key,
             &format!("Invalid transport type '{}'", t),
@@ -792,6 +851,7 @@ impl Display for Network {
 #[derive(Debug, Clone)]
 pub enum DatabaseType {
     LMDB(PathBuf),
+    Sqlite(PathBuf),
     Memory,
 }
 
@@ -833,6 +893,42 @@ impl FromStr for TorControlAuthentication {
     }
 }
 
+/// An additional onion service configured in the format `name=onion_port:forward_address`
+#[derive(Debug, Clone)]
+pub struct TorOnionService {
+    pub name: String,
+    pub onion_port: NonZeroU16,
+    /// The address to which traffic on this onion service will be forwarded
+    pub forward_address: Multiaddr,
+}
+
+impl FromStr for TorOnionService {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || {
+            format!(
+                "Invalid format for onion service '{}'. It should be in the format 'name=onion_port:forward_address'.",
+                s
+            )
+        };
+        let (name, maybe_value) = parse_key_value(s, '=');
+        let (onion_port, forward_address) = maybe_value
+            .map(|value| parse_key_value(value, ':'))
+            .and_then(|(port, addr)| addr.map(|addr| (port, addr)))
+            .ok_or_else(err)?;
+        if name.is_empty() {
+            return Err(err());
+        }
+
+        Ok(TorOnionService {
+            name,
+            onion_port: onion_port.parse().map_err(|_| err())?,
+            forward_address: forward_address.parse().map_err(|_| err())?,
+        })
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum SocksAuthentication {
     None,
@@ -885,6 +981,10 @@ pub enum CommsTransport {
         forward_address: Multiaddr,
         auth: TorControlAuthentication,
         onion_port: NonZeroU16,
+        /// Additional onion services to run alongside the node's public onion service
+        additional_onion_services: Vec<TorOnionService>,
+        /// If set, the onion key of the node's public onion service is rotated at this interval
+        key_rotation_interval: Option<Duration>,
     },
     /// Use a SOCKS5 proxy transport. This transport recognises any addresses supported by the proxy.
     Socks5 {
@@ -892,4 +992,7 @@ pub enum CommsTransport {
         auth: SocksAuthentication,
         listener_address: Multiaddr,
     },
+    /// Listen for WebSocket connections. Peers with `/ws` addresses are dialed over WebSockets and all other TCP/IP
+    /// addresses over plain TCP. This allows nodes behind restrictive firewalls to reach this node.
+    WebSocket { listener_address: Multiaddr },
 }
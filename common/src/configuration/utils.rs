@@ -22,6 +22,9 @@ pub fn load_configuration(bootstrap: &ConfigBootstrap) -> Result<Config, String>
     match cfg.merge(config_file) {
         Ok(_) => {
             info!(target: LOG_TARGET, "Configuration file loaded.");
+            if bootstrap.regtest {
+                info!(target: LOG_TARGET, "Regtest mode selected, using the localnet network.");
+            }
             Ok(cfg)
         },
         Err(e) => Err(format!(
@@ -103,6 +106,10 @@ pub fn default_config(bootstrap: &ConfigBootstrap) -> Config {
     //---------------------------------- Mainnet Defaults --------------------------------------------//
 
     cfg.set_default("base_node.network", "mainnet").unwrap();
+    // `--regtest` is an override rather than a default so that it also wins over the network in the config file
+    if bootstrap.regtest {
+        cfg.set("base_node.network", "localnet").unwrap();
+    }
 
     // Mainnet base node defaults
     cfg.set_default("base_node.mainnet.db_type", "lmdb").unwrap();
@@ -223,6 +230,68 @@ pub fn default_config(bootstrap: &ConfigBootstrap) -> Config {
         .unwrap();
     cfg.set_default("base_node.stibbons.auto_ping_interval", 30).unwrap();
 
+    //---------------------------------- LocalNet Defaults --------------------------------------------//
+
+    // LocalNet is the self-contained developer network used by the base node's `--regtest` mode, so it has no seed
+    // peers and keeps its data separate from the public networks.
+    cfg.set_default("base_node.localnet.db_type", "lmdb").unwrap();
+    cfg.set_default("base_node.localnet.orphan_storage_capacity", 720)
+        .unwrap();
+    cfg.set_default("base_node.localnet.orphan_db_clean_out_threshold", 0)
+        .unwrap();
+    cfg.set_default("base_node.localnet.pruning_horizon", 0).unwrap();
+    cfg.set_default("base_node.localnet.pruned_mode_cleanup_interval", 50)
+        .unwrap();
+    cfg.set_default("base_node.localnet.flood_ban_max_msg_count", 1000)
+        .unwrap();
+    cfg.set_default("base_node.localnet.peer_seeds", Vec::<String>::new())
+        .unwrap();
+    cfg.set_default("base_node.localnet.dns_seeds", Vec::<String>::new())
+        .unwrap();
+    cfg.set_default(
+        "base_node.localnet.data_dir",
+        default_subdir("localnet/", Some(&bootstrap.base_path)),
+    )
+    .unwrap();
+    cfg.set_default(
+        "base_node.localnet.base_node_tor_identity_file",
+        default_subdir("config/base_node_tor.json", Some(&bootstrap.base_path)),
+    )
+    .unwrap();
+    cfg.set_default(
+        "base_node.localnet.console_wallet_identity_file",
+        default_subdir("config/console_wallet_id.json", Some(&bootstrap.base_path)),
+    )
+    .unwrap();
+    cfg.set_default(
+        "base_node.localnet.console_wallet_tor_identity_file",
+        default_subdir("config/console_wallet_tor.json", Some(&bootstrap.base_path)),
+    )
+    .unwrap();
+    cfg.set_default(
+        "base_node.localnet.base_node_identity_file",
+        default_subdir("config/base_node_id.json", Some(&bootstrap.base_path)),
+    )
+    .unwrap();
+    cfg.set_default("base_node.localnet.public_address", "/ip4/127.0.0.1/tcp/18289")
+        .unwrap();
+
+    cfg.set_default("base_node.localnet.allow_test_addresses", true)
+        .unwrap();
+    cfg.set_default("base_node.localnet.grpc_enabled", false).unwrap();
+    cfg.set_default("base_node.localnet.grpc_base_node_address", "127.0.0.1:18142")
+        .unwrap();
+    cfg.set_default("base_node.localnet.grpc_console_wallet_address", "127.0.0.1:18143")
+        .unwrap();
+    cfg.set_default("base_node.localnet.enable_wallet", true).unwrap();
+    cfg.set_default("base_node.localnet.num_mining_threads", 1).unwrap();
+
+    cfg.set_default("base_node.localnet.dns_seeds_name_server", "1.1.1.1:53")
+        .unwrap();
+    cfg.set_default("base_node.localnet.dns_seeds_use_dnssec", false)
+        .unwrap();
+    cfg.set_default("base_node.localnet.auto_ping_interval", 30).unwrap();
+
     cfg.set_default("wallet.base_node_service_peers", Vec::<String>::new())
         .unwrap();
 
@@ -260,6 +329,21 @@ fn set_merge_mining_defaults(cfg: &mut Config) {
         .unwrap();
     cfg.set_default("merge_mining_proxy.stibbons.wait_for_initial_sync_at_startup", true)
         .unwrap();
+
+    cfg.set_default("merge_mining_proxy.localnet.monerod_url", "http://127.0.0.1:18081")
+        .unwrap();
+    cfg.set_default("merge_mining_proxy.localnet.proxy_host_address", "127.0.0.1:7878")
+        .unwrap();
+    cfg.set_default("merge_mining_proxy.localnet.proxy_submit_to_origin", true)
+        .unwrap();
+    cfg.set_default("merge_mining_proxy.localnet.monerod_use_auth", "false")
+        .unwrap();
+    cfg.set_default("merge_mining_proxy.localnet.monerod_username", "")
+        .unwrap();
+    cfg.set_default("merge_mining_proxy.localnet.monerod_password", "")
+        .unwrap();
+    cfg.set_default("merge_mining_proxy.localnet.wait_for_initial_sync_at_startup", false)
+        .unwrap();
 }
 
 fn set_transport_defaults(cfg: &mut Config) {
@@ -282,6 +366,12 @@ fn set_transport_defaults(cfg: &mut Config) {
         .unwrap();
     cfg.set_default("base_node.mainnet.socks5_auth", "none").unwrap();
 
+    cfg.set_default(
+        "base_node.mainnet.websocket_listener_address",
+        "/ip4/0.0.0.0/tcp/18090/ws",
+    )
+    .unwrap();
+
     // stibbons
     // Default transport for stibbons is tcp
     cfg.set_default("base_node.stibbons.transport", "tcp").unwrap();
@@ -300,6 +390,23 @@ fn set_transport_defaults(cfg: &mut Config) {
     cfg.set_default("base_node.stibbons.socks5_listener_address", "/ip4/0.0.0.0/tcp/18199")
         .unwrap();
     cfg.set_default("base_node.stibbons.socks5_auth", "none").unwrap();
+
+    cfg.set_default(
+        "base_node.stibbons.websocket_listener_address",
+        "/ip4/0.0.0.0/tcp/18190/ws",
+    )
+    .unwrap();
+
+    // localnet
+    // LocalNet only listens on the loopback interface
+    cfg.set_default("base_node.localnet.transport", "tcp").unwrap();
+    cfg.set_default("base_node.localnet.tcp_listener_address", "/ip4/127.0.0.1/tcp/18289")
+        .unwrap();
+    cfg.set_default(
+        "base_node.localnet.websocket_listener_address",
+        "/ip4/127.0.0.1/tcp/18290/ws",
+    )
+    .unwrap();
 }
 
 fn get_local_ip() -> Option<Multiaddr> {
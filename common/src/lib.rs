@@ -86,7 +86,15 @@ pub use configuration::error::ConfigError;
 pub mod dir_utils;
 pub use configuration::{
     bootstrap::{install_configuration, ConfigBootstrap},
-    global::{CommsTransport, DatabaseType, GlobalConfig, Network, SocksAuthentication, TorControlAuthentication},
+    global::{
+        CommsTransport,
+        DatabaseType,
+        GlobalConfig,
+        Network,
+        SocksAuthentication,
+        TorControlAuthentication,
+        TorOnionService,
+    },
     loader::{ConfigLoader, ConfigPath, ConfigurationError, DefaultConfigLoader, NetworkConfigPath},
     utils::{default_config, install_default_config_file, load_configuration},
 };
@@ -883,7 +883,7 @@ async fn setup_comms_dht(
 ) -> (CommsNode, Dht, MessagingEventSender)
 {
     // Create inbound and outbound channels
-    let (outbound_tx, outbound_rx) = mpsc::channel(10);
+    let (outbound_tx, outbound_rx) = mpsc::channel(0);
 
     let comms = CommsBuilder::new()
         .allow_test_addresses()
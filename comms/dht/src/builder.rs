@@ -39,6 +39,9 @@ pub struct DhtBuilder {
 }
 
 impl DhtBuilder {
+    /// Create a new DhtBuilder. Outbound requests are sent to `outbound_tx` in priority order as the outbound
+    /// pipeline takes them. Requests already buffered in the channel are no longer prioritised, so it should be created
+    /// with a buffer size of zero.
     pub fn new(
         node_identity: Arc<NodeIdentity>,
         peer_manager: Arc<PeerManager>,
@@ -20,16 +20,21 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use crate::{envelope::Network, network_discovery::NetworkDiscoveryConfig, storage::DbConnectionUrl};
+use crate::{
+    envelope::Network,
+    network_discovery::NetworkDiscoveryConfig,
+    outbound::OutboundQueueConfig,
+    storage::DbConnectionUrl,
+};
 use std::time::Duration;
 
 #[derive(Debug, Clone)]
 pub struct DhtConfig {
     /// The `DbConnectionUrl` for the Dht database. Default: In-memory database
     pub database_url: DbConnectionUrl,
-    /// The size of the buffer (channel) which holds pending outbound message requests.
-    /// Default: 20
-    pub outbound_buffer_size: usize,
+    /// Buffer sizes and scheduling weights for each outbound message priority class.
+    /// Default: [OutboundQueueConfig::default](crate::outbound::OutboundQueueConfig)
+    pub outbound_queues: OutboundQueueConfig,
     /// The maximum number of peer nodes that a message has to be closer to, to be considered a neighbour
     /// Default: [DEFAULT_NUM_NEIGHBOURING_NODES](self::DEFAULT_NUM_NEIGHBOURING_NODES)
     pub num_neighbouring_nodes: usize,
@@ -71,6 +76,9 @@ pub struct DhtConfig {
     /// time, so `minimum_request_period` can be used so that messages aren't missed.
     /// Default: 3 days
     pub saf_minimum_request_period: Duration,
+    /// When true, a signed delivery receipt is sent back to the origin of encrypted stored messages received by this
+    /// node (Default: true)
+    pub saf_delivery_receipts: bool,
     /// The max capacity of the message hash cache
     /// Default: 100,000
     pub msg_hash_cache_capacity: usize,
@@ -80,6 +88,12 @@ pub struct DhtConfig {
     /// The duration to wait for a peer discovery to complete before giving up.
     /// Default: 2 minutes
     pub discovery_request_timeout: Duration,
+    /// The number of peers that are queried in parallel in each round of an iterative peer lookup.
+    /// Default: 3
+    pub peer_lookup_alpha: usize,
+    /// The maximum number of query rounds an iterative peer lookup will make before giving up.
+    /// Default: 10
+    pub peer_lookup_max_rounds: usize,
     /// Set to true to automatically broadcast a join message when ready, otherwise false. Default: false
     pub auto_join: bool,
     /// The minimum time between sending a Join message to the network. Joins are only sent when the node establishes
@@ -152,7 +166,7 @@ impl Default for DhtConfig {
             num_random_nodes: 4,
             propagation_factor: 4,
             broadcast_factor: 8,
-            outbound_buffer_size: 20,
+            outbound_queues: Default::default(),
             saf_num_closest_nodes: 10,
             saf_max_returned_messages: 50,
             saf_msg_storage_capacity: 100_000,
@@ -161,10 +175,13 @@ impl Default for DhtConfig {
             saf_auto_request: true,
             saf_max_message_size: 512 * 1024,
             saf_minimum_request_period: Duration::from_secs(3 * 24 * 60 * 60), // 3 days
+            saf_delivery_receipts: true,
             msg_hash_cache_capacity: 100_000,
             msg_hash_cache_ttl: Duration::from_secs(5 * 60),
             database_url: DbConnectionUrl::Memory,
             discovery_request_timeout: Duration::from_secs(2 * 60),
+            peer_lookup_alpha: 3,
+            peer_lookup_max_rounds: 10,
             connectivity_update_interval: Duration::from_secs(2 * 60),
             connectivity_random_pool_refresh: Duration::from_secs(2 * 60 * 60),
             auto_join: false,
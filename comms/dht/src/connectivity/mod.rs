@@ -579,7 +579,7 @@ impl DhtConnectivity {
 
                 true
             })
-            .sort_by(PeerQuerySortBy::DistanceFrom(&node_id))
+            .sort_by(PeerQuerySortBy::ReputationThenDistanceFrom(&node_id))
             .limit(n);
 
         let peers = peer_manager.perform_query(query).await?;
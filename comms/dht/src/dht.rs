@@ -31,7 +31,7 @@ use crate::{
     logging_middleware::MessageLoggingLayer,
     network_discovery::DhtNetworkDiscovery,
     outbound,
-    outbound::DhtOutboundRequest,
+    outbound::{DhtOutboundRequest, OutboundPriorityQueue, OutboundQueueMetrics, OutboundQueueSender},
     proto::envelope::DhtMessageType,
     rpc,
     storage::{DbConnection, StorageError},
@@ -42,7 +42,7 @@ use crate::{
     DhtActorError,
     DhtConfig,
 };
-use futures::{channel::mpsc, future, Future};
+use futures::{channel::mpsc, future, Future, StreamExt};
 use log::*;
 use std::sync::Arc;
 use tari_comms::{
@@ -53,7 +53,7 @@ use tari_comms::{
 };
 use tari_shutdown::ShutdownSignal;
 use thiserror::Error;
-use tokio::sync::broadcast;
+use tokio::{sync::broadcast, task};
 use tower::{layer::Layer, Service, ServiceBuilder};
 
 const LOG_TARGET: &str = "comms::dht";
@@ -84,7 +84,9 @@ pub struct Dht {
     /// Dht configuration
     config: DhtConfig,
     /// Used to create a OutboundMessageRequester.
-    outbound_tx: mpsc::Sender<DhtOutboundRequest>,
+    outbound_tx: OutboundQueueSender,
+    /// Per priority class metrics for the outbound queue
+    outbound_queue_metrics: OutboundQueueMetrics,
     /// Sender for DHT requests
     dht_sender: mpsc::Sender<DhtRequest>,
     /// Sender for SAF requests
@@ -118,13 +120,17 @@ impl Dht {
         let (event_publisher, _) = broadcast::channel(DHT_EVENT_BROADCAST_CHANNEL_SIZE);
 
         let metrics_collector = MetricsCollector::spawn();
+        let (outbound_queue_tx, outbound_queue) = outbound::outbound_priority_queue(&config.outbound_queues);
+        let outbound_queue_metrics = outbound_queue.metrics();
+        Self::spawn_outbound_queue(outbound_queue, outbound_tx);
 
         let dht = Self {
             node_identity,
             peer_manager,
             metrics_collector,
             config,
-            outbound_tx,
+            outbound_tx: outbound_queue_tx,
+            outbound_queue_metrics,
             dht_sender,
             saf_sender,
             saf_response_signal_sender,
@@ -154,6 +160,28 @@ impl Dht {
         Ok(dht)
     }
 
+    /// Forward outbound requests from the priority queue to the outbound middleware. A request is only taken from the
+    /// queue once the outbound channel has capacity for it, so requests wait in the priority queue rather than in the
+    /// channel. The outbound channel should therefore have a buffer size of zero (see `DhtBuilder::new`).
+    fn spawn_outbound_queue(mut queue: OutboundPriorityQueue, mut outbound_tx: mpsc::Sender<DhtOutboundRequest>) {
+        task::spawn(async move {
+            loop {
+                if let Err(err) = future::poll_fn(|cx| outbound_tx.poll_ready(cx)).await {
+                    debug!(target: LOG_TARGET, "Outbound priority queue stopped because '{}'", err);
+                    break;
+                }
+                let request = match queue.next().await {
+                    Some(request) => request,
+                    None => break,
+                };
+                if let Err(err) = outbound_tx.start_send(request) {
+                    debug!(target: LOG_TARGET, "Outbound priority queue stopped because '{}'", err);
+                    break;
+                }
+            }
+        });
+    }
+
     /// Create a DHT RPC service
     pub fn rpc_service(&self) -> rpc::DhtService<rpc::DhtRpcServiceImpl> {
         rpc::DhtService::new(rpc::DhtRpcServiceImpl::new(self.peer_manager.clone()))
@@ -190,6 +218,7 @@ impl Dht {
             self.config.clone(),
             Arc::clone(&self.node_identity),
             Arc::clone(&self.peer_manager),
+            self.connectivity.clone(),
             self.outbound_requester(),
             request_receiver,
             shutdown_signal,
@@ -248,6 +277,11 @@ impl Dht {
         OutboundMessageRequester::new(self.outbound_tx.clone())
     }
 
+    /// Returns the per priority class metrics for the outbound message queue
+    pub fn outbound_queue_metrics(&self) -> OutboundQueueMetrics {
+        self.outbound_queue_metrics.clone()
+    }
+
     /// Returns a requester for the DhtActor associated with this instance
     pub fn dht_requester(&self) -> DhtRequester {
         DhtRequester::new(self.dht_sender.clone())
@@ -404,7 +438,7 @@ mod test {
     use crate::{
         crypt,
         envelope::DhtMessageFlags,
-        outbound::mock::create_outbound_service_mock,
+        outbound::{mock::create_outbound_service_mock, DhtOutboundRequest, OutboundPriority, SendMessageParams},
         proto::envelope::DhtMessageType,
         test_utils::{
             build_peer_manager,
@@ -415,7 +449,10 @@ mod test {
         },
         DhtBuilder,
     };
-    use futures::{channel::mpsc, StreamExt};
+    use futures::{
+        channel::{mpsc, oneshot},
+        StreamExt,
+    };
     use std::{sync::Arc, time::Duration};
     use tari_comms::{
         message::{MessageExt, MessageTag},
@@ -633,4 +670,49 @@ mod test {
             "receiver channel is empty"
         );
     }
+
+    #[tokio_macros::test_basic]
+    async fn outbound_requests_wait_in_the_priority_queue() {
+        let node_identity = make_node_identity();
+        let peer_manager = build_peer_manager();
+        let (connectivity, _) = create_connectivity_mock();
+        let (out_tx, mut out_rx) = mpsc::channel(0);
+
+        let shutdown = Shutdown::new();
+        let dht = DhtBuilder::new(node_identity, peer_manager, out_tx, connectivity, shutdown.to_signal())
+            .local_test()
+            .build()
+            .await
+            .unwrap();
+
+        let create_request = |body: u8| {
+            let (reply_tx, _) = oneshot::channel();
+            DhtOutboundRequest::SendMessage(Box::new(SendMessageParams::new().finish()), vec![body].into(), reply_tx)
+        };
+        let mut sender = dht.outbound_tx.clone();
+        for _ in 0..5 {
+            sender.send(OutboundPriority::Low, create_request(2)).await.unwrap();
+        }
+        // Let the outbound queue task take what it can before the high priority request arrives
+        time::delay_for(Duration::from_millis(50)).await;
+        sender.send(OutboundPriority::High, create_request(0)).await.unwrap();
+
+        let mut order = Vec::new();
+        while order.len() < 6 {
+            let request = time::timeout(Duration::from_secs(10), out_rx.next())
+                .await
+                .unwrap()
+                .unwrap();
+            match request {
+                DhtOutboundRequest::SendMessage(_, body, _) => order.push(body[0]),
+            }
+        }
+        // At most one low priority request was waiting in the outbound channel
+        let high_position = order.iter().position(|b| *b == 0).unwrap();
+        assert!(
+            high_position <= 1,
+            "High priority request was sent at position {}",
+            high_position
+        );
+    }
 }
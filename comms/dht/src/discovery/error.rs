@@ -22,7 +22,12 @@
 
 use crate::outbound::{message::SendFailure, DhtOutboundError};
 use futures::channel::mpsc::SendError;
-use tari_comms::{connection_manager::ConnectionManagerError, peer_manager::PeerManagerError};
+use tari_comms::{
+    connection_manager::ConnectionManagerError,
+    connectivity::ConnectivityError,
+    peer_manager::PeerManagerError,
+    protocol::rpc::RpcError,
+};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -49,6 +54,12 @@ pub enum DhtDiscoveryError {
     InvalidPeerMultiaddr(String),
     #[error("ConnectionManagerError: {0}")]
     ConnectionManagerError(#[from] ConnectionManagerError),
+    #[error("ConnectivityError: {0}")]
+    ConnectivityError(#[from] ConnectivityError),
+    #[error("RpcError: {0}")]
+    RpcError(#[from] RpcError),
+    #[error("The peer lookup did not find the requested peer")]
+    PeerNotFound,
 }
 
 impl DhtDiscoveryError {
@@ -0,0 +1,346 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+use crate::{discovery::DhtDiscoveryError, proto::rpc::GetCloserPeersRequest, rpc, DhtConfig};
+use futures::{future, StreamExt};
+use log::*;
+use std::{collections::HashSet, convert::TryInto, sync::Arc};
+use tari_comms::{
+    connectivity::ConnectivityRequester,
+    peer_manager::{NodeId, NodeIdentity, Peer, PeerFeatures, PeerFlags, PeerManager},
+    protocol::rpc::RpcError,
+    types::CommsPublicKey,
+    validate_peer_addresses,
+};
+use tari_utilities::ByteArray;
+
+const LOG_TARGET: &str = "comms::dht::discovery::lookup";
+
+/// An iterative (Kademlia-style) lookup for a peer. Each round, the `alpha` closest peers to the target that have not
+/// yet been queried are asked for their closest peers to the target using the `get_closer_peers` RPC. When a round
+/// does not get any closer to the target, all of the closest peers that have not been queried yet are queried in the
+/// next round. The lookup ends when a peer returns the target, or when all of the closest peers have been queried.
+/// Unlike a discovery message, the target does not have to be online for the lookup to succeed.
+pub struct PeerLookup {
+    config: DhtConfig,
+    node_identity: Arc<NodeIdentity>,
+    peer_manager: Arc<PeerManager>,
+    connectivity: ConnectivityRequester,
+}
+
+impl PeerLookup {
+    pub fn new(
+        config: DhtConfig,
+        node_identity: Arc<NodeIdentity>,
+        peer_manager: Arc<PeerManager>,
+        connectivity: ConnectivityRequester,
+    ) -> Self
+    {
+        Self {
+            config,
+            node_identity,
+            peer_manager,
+            connectivity,
+        }
+    }
+
+    /// Search the network for the peer with the given public key
+    pub async fn lookup(&self, public_key: &CommsPublicKey) -> Result<Peer, DhtDiscoveryError> {
+        let target = NodeId::from_public_key(public_key);
+        let num_closest = self.config.num_neighbouring_nodes;
+        let mut shortlist = self
+            .peer_manager
+            .closest_peers(
+                &target,
+                num_closest,
+                &[self.node_identity.node_id().clone()],
+                Some(PeerFeatures::COMMUNICATION_NODE),
+            )
+            .await?;
+        let mut queried = HashSet::new();
+        let mut num_candidates = self.config.peer_lookup_alpha;
+
+        for round in 1..=self.config.peer_lookup_max_rounds {
+            let candidates = shortlist
+                .iter()
+                .filter(|peer| !queried.contains(&peer.node_id))
+                .take(num_candidates)
+                .map(|peer| peer.node_id.clone())
+                .collect::<Vec<_>>();
+
+            if candidates.is_empty() {
+                debug!(
+                    target: LOG_TARGET,
+                    "Peer lookup for `{}` queried all of the closest peers after {} round(s) without finding the peer",
+                    target.short_str(),
+                    round - 1
+                );
+                break;
+            }
+
+            trace!(
+                target: LOG_TARGET,
+                "Peer lookup round {} for `{}` querying {} peer(s)",
+                round,
+                target.short_str(),
+                candidates.len()
+            );
+            queried.extend(candidates.iter().cloned());
+            let closest_distance = shortlist.first().map(|peer| peer.node_id.distance(&target));
+
+            let responses = future::join_all(candidates.into_iter().map(|node_id| self.query_peer(node_id, &target)))
+                .await
+                .into_iter()
+                .flatten();
+
+            for peer in responses {
+                if peer.public_key == *public_key {
+                    debug!(
+                        target: LOG_TARGET,
+                        "Peer lookup for `{}` completed after {} round(s)",
+                        target.short_str(),
+                        round
+                    );
+                    return self.add_peer(peer).await;
+                }
+
+                if peer.node_id == *self.node_identity.node_id() || shortlist.iter().any(|p| p.node_id == peer.node_id)
+                {
+                    continue;
+                }
+
+                if let Err(err) = validate_peer_addresses(peer.addresses.iter(), self.config.allow_test_addresses) {
+                    debug!(
+                        target: LOG_TARGET,
+                        "Peer `{}` returned in peer lookup has invalid addresses: {}", peer.node_id, err
+                    );
+                    continue;
+                }
+
+                // The peer has to be known to the peer manager so that it can be dialed in the next round
+                if !self.peer_manager.exists_node_id(&peer.node_id).await {
+                    self.peer_manager.add_peer(peer.clone()).await?;
+                }
+                shortlist.push(peer);
+            }
+
+            shortlist.sort_by(|a, b| a.node_id.distance(&target).cmp(&b.node_id.distance(&target)));
+            shortlist.truncate(num_closest);
+
+            let new_closest_distance = shortlist.first().map(|peer| peer.node_id.distance(&target));
+            num_candidates = if new_closest_distance >= closest_distance {
+                trace!(
+                    target: LOG_TARGET,
+                    "Peer lookup round {} for `{}` got no closer to the target. Querying the remaining closest peers.",
+                    round,
+                    target.short_str()
+                );
+                num_closest
+            } else {
+                self.config.peer_lookup_alpha
+            };
+        }
+
+        Err(DhtDiscoveryError::PeerNotFound)
+    }
+
+    async fn query_peer(&self, node_id: NodeId, target: &NodeId) -> Vec<Peer> {
+        match self.request_closer_peers(&node_id, target).await {
+            Ok(peers) => peers,
+            Err(err) => {
+                debug!(
+                    target: LOG_TARGET,
+                    "Failed to request closer peers from `{}`: {}", node_id, err
+                );
+                Vec::new()
+            },
+        }
+    }
+
+    async fn request_closer_peers(&self, node_id: &NodeId, target: &NodeId) -> Result<Vec<Peer>, DhtDiscoveryError> {
+        let mut conn = self.connectivity.clone().dial_peer(node_id.clone()).await?;
+        let mut client = conn.connect_rpc::<rpc::DhtClient>().await?;
+        let mut stream = client
+            .get_closer_peers(GetCloserPeersRequest {
+                n: self.config.num_neighbouring_nodes as u32,
+                excluded: vec![self.node_identity.node_id().to_vec()],
+                closer_to: target.to_vec(),
+                include_clients: true,
+            })
+            .await?;
+
+        let mut peers = Vec::new();
+        while let Some(resp) = stream.next().await {
+            match resp.map_err(RpcError::from)?.peer.and_then(|peer| peer.try_into().ok()) {
+                Some(peer) => peers.push(peer),
+                None => {
+                    debug!(target: LOG_TARGET, "Invalid peer in response from `{}`", node_id);
+                },
+            }
+        }
+
+        Ok(peers)
+    }
+
+    /// The addresses of the target were not signed by the target, so they are only added to the addresses that are
+    /// tried when dialing it. Neither the existing addresses nor the online status of the target are changed.
+    async fn add_peer(&self, peer: Peer) -> Result<Peer, DhtDiscoveryError> {
+        let addresses = peer.addresses.iter().cloned().collect::<Vec<_>>();
+        validate_peer_addresses(&addresses, self.config.allow_test_addresses)
+            .map_err(|err| DhtDiscoveryError::InvalidPeerMultiaddr(err.to_string()))?;
+
+        if self.peer_manager.exists_node_id(&peer.node_id).await {
+            for address in &addresses {
+                self.peer_manager.add_net_address(&peer.node_id, address).await?;
+            }
+        } else {
+            self.peer_manager
+                .add_peer(Peer::new(
+                    peer.public_key.clone(),
+                    peer.node_id.clone(),
+                    addresses.into(),
+                    PeerFlags::NONE,
+                    peer.features,
+                    Default::default(),
+                    Default::default(),
+                ))
+                .await?;
+        }
+
+        let peer = self.peer_manager.find_by_node_id(&peer.node_id).await?;
+        Ok(peer)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        proto::rpc::GetPeersResponse,
+        rpc::DhtRpcServiceMock,
+        test_utils::{build_peer_manager, make_node_identity},
+    };
+    use tari_comms::{
+        multiaddr::Multiaddr,
+        protocol::rpc::{mock::MockRpcServer, NamedProtocolService},
+        test_utils::{mocks::create_connectivity_mock, node_identity::build_node_identity},
+    };
+
+    async fn setup(
+        responses: Vec<GetPeersResponse>,
+        num_peers: usize,
+        config: DhtConfig,
+    ) -> (PeerLookup, DhtRpcServiceMock, Arc<PeerManager>)
+    {
+        let node_identity = make_node_identity();
+        let peer_manager = build_peer_manager();
+        let (connectivity, mock) = create_connectivity_mock();
+        let connectivity_state = mock.get_shared_state();
+        mock.spawn();
+
+        let rpc_mock = DhtRpcServiceMock::new();
+        rpc_mock.get_closer_peers.set_response(Ok(responses)).await;
+        let service = rpc::DhtService::new(rpc_mock.clone());
+        let protocol_name = service.as_protocol_name();
+        let mut mock_server = MockRpcServer::new(service, node_identity.clone());
+        mock_server.serve();
+
+        // The only peers we know about are the ones serving the mock RPC
+        for _ in 0..num_peers {
+            let peer_node_identity = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+            peer_manager.add_peer(peer_node_identity.to_peer()).await.unwrap();
+            let connection = mock_server
+                .create_connection(peer_node_identity.to_peer(), protocol_name.into())
+                .await;
+            connectivity_state.add_active_connection(connection).await;
+        }
+
+        let lookup = PeerLookup::new(config, node_identity, peer_manager.clone(), connectivity);
+        (lookup, rpc_mock, peer_manager)
+    }
+
+    #[tokio_macros::test_basic]
+    async fn it_finds_the_peer() {
+        let target = make_node_identity();
+        let (lookup, rpc_mock, peer_manager) = setup(
+            vec![GetPeersResponse {
+                peer: Some(target.to_peer().into()),
+            }],
+            1,
+            DhtConfig::default_local_test(),
+        )
+        .await;
+
+        let peer = lookup.lookup(target.public_key()).await.unwrap();
+        assert_eq!(peer.node_id, *target.node_id());
+        assert_eq!(rpc_mock.get_closer_peers.request_count().await, 1);
+        assert!(peer_manager.exists(target.public_key()).await);
+        // The addresses were not provided by the target itself, so it is not marked as online
+        assert!(peer.connection_stats.last_connected_at.is_none());
+    }
+
+    #[tokio_macros::test_basic]
+    async fn it_does_not_replace_known_addresses() {
+        let target = make_node_identity();
+        let mut impostor = target.to_peer();
+        impostor.addresses = "/ip4/127.0.0.1/tcp/9999".parse::<Multiaddr>().unwrap().into();
+        let (lookup, _, peer_manager) = setup(
+            vec![GetPeersResponse {
+                peer: Some(impostor.into()),
+            }],
+            1,
+            DhtConfig::default_local_test(),
+        )
+        .await;
+        peer_manager.add_peer(target.to_peer()).await.unwrap();
+
+        let peer = lookup.lookup(target.public_key()).await.unwrap();
+        // The address returned by the queried peer is added, but the address that is already known is kept
+        assert_eq!(peer.addresses.len(), 2);
+        assert!(peer.addresses.iter().any(|addr| *addr == target.public_address()));
+    }
+
+    #[tokio_macros::test_basic]
+    async fn it_stops_when_no_closer_peers_are_returned() {
+        let target = make_node_identity();
+        let (lookup, rpc_mock, peer_manager) = setup(vec![], 1, DhtConfig::default_local_test()).await;
+
+        let err = lookup.lookup(target.public_key()).await.unwrap_err();
+        assert!(matches!(err, DhtDiscoveryError::PeerNotFound));
+        assert_eq!(rpc_mock.get_closer_peers.request_count().await, 1);
+        assert!(!peer_manager.exists(target.public_key()).await);
+    }
+
+    #[tokio_macros::test_basic]
+    async fn it_queries_the_remaining_closest_peers_when_a_round_gets_no_closer() {
+        let target = make_node_identity();
+        let config = DhtConfig {
+            peer_lookup_alpha: 1,
+            ..DhtConfig::default_local_test()
+        };
+        let (lookup, rpc_mock, _) = setup(vec![], 3, config).await;
+
+        let err = lookup.lookup(target.public_key()).await.unwrap_err();
+        assert!(matches!(err, DhtDiscoveryError::PeerNotFound));
+        // The first round queries a single peer and, having got no closer, the second round queries the other two
+        assert_eq!(rpc_mock.get_closer_peers.request_count().await, 3);
+    }
+}
@@ -21,9 +21,15 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 mod error;
+mod lookup;
 mod requester;
 mod service;
 
 pub(crate) use self::requester::DhtDiscoveryRequest;
 
-pub use self::{error::DhtDiscoveryError, requester::DhtDiscoveryRequester, service::DhtDiscoveryService};
+pub use self::{
+    error::DhtDiscoveryError,
+    lookup::PeerLookup,
+    requester::DhtDiscoveryRequester,
+    service::DhtDiscoveryService,
+};
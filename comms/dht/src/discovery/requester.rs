@@ -40,6 +40,7 @@ pub enum DhtDiscoveryRequest {
         oneshot::Sender<Result<Peer, DhtDiscoveryError>>,
     ),
     NotifyDiscoveryResponseReceived(Box<DiscoveryResponseMessage>),
+    LookupPeer(Box<CommsPublicKey>, oneshot::Sender<Result<Peer, DhtDiscoveryError>>),
 }
 
 impl Display for DhtDiscoveryRequest {
@@ -50,6 +51,7 @@ impl Display for DhtDiscoveryRequest {
             NotifyDiscoveryResponseReceived(discovery_resp) => {
                 write!(f, "NotifyDiscoveryResponseReceived({:#?})", discovery_resp)
             },
+            LookupPeer(public_key, _) => write!(f, "LookupPeer({})", public_key),
         }
     }
 }
@@ -109,6 +111,22 @@ impl DhtDiscoveryRequester {
             .map_err(|_| DhtDiscoveryError::ReplyCanceled)?
     }
 
+    /// Find a peer by iteratively querying the closest known peers to the public key's `NodeId` for peers that are
+    /// closer, until the peer is found or the search stops getting closer. This does not require the peer to be
+    /// online, but does require that a peer close to it in the network knows about it.
+    pub async fn lookup_peer(&mut self, public_key: Box<CommsPublicKey>) -> Result<Peer, DhtDiscoveryError> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+
+        self.sender
+            .send(DhtDiscoveryRequest::LookupPeer(public_key, reply_tx))
+            .await?;
+
+        time::timeout(self.discovery_timeout, reply_rx)
+            .await
+            .map_err(|_| DhtDiscoveryError::DiscoveryTimeout)?
+            .map_err(|_| DhtDiscoveryError::ReplyCanceled)?
+    }
+
     pub async fn notify_discovery_response_received(
         &mut self,
         response: DiscoveryResponseMessage,
@@ -21,7 +21,7 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use crate::{
-    discovery::{requester::DhtDiscoveryRequest, DhtDiscoveryError},
+    discovery::{requester::DhtDiscoveryRequest, DhtDiscoveryError, PeerLookup},
     envelope::{DhtMessageType, NodeDestination},
     outbound::{OutboundEncryption, OutboundMessageRequester, SendMessageParams},
     proto::dht::{DiscoveryMessage, DiscoveryResponseMessage},
@@ -40,6 +40,7 @@ use std::{
     time::{Duration, Instant},
 };
 use tari_comms::{
+    connectivity::ConnectivityRequester,
     log_if_error,
     peer_manager::{NodeId, NodeIdentity, Peer, PeerFeatures, PeerManager},
     types::CommsPublicKey,
@@ -72,6 +73,7 @@ pub struct DhtDiscoveryService {
     node_identity: Arc<NodeIdentity>,
     outbound_requester: OutboundMessageRequester,
     peer_manager: Arc<PeerManager>,
+    connectivity: ConnectivityRequester,
     request_rx: Option<mpsc::Receiver<DhtDiscoveryRequest>>,
     shutdown_signal: Option<ShutdownSignal>,
     inflight_discoveries: HashMap<u64, DiscoveryRequestState>,
@@ -82,6 +84,7 @@ impl DhtDiscoveryService {
         config: DhtConfig,
         node_identity: Arc<NodeIdentity>,
         peer_manager: Arc<PeerManager>,
+        connectivity: ConnectivityRequester,
         outbound_requester: OutboundMessageRequester,
         request_rx: mpsc::Receiver<DhtDiscoveryRequest>,
         shutdown_signal: ShutdownSignal,
@@ -92,6 +95,7 @@ impl DhtDiscoveryService {
             outbound_requester,
             node_identity,
             peer_manager,
+            connectivity,
             shutdown_signal: Some(shutdown_signal),
             request_rx: Some(request_rx),
             inflight_discoveries: HashMap::new(),
@@ -146,9 +150,38 @@ impl DhtDiscoveryService {
             },
 
             NotifyDiscoveryResponseReceived(discovery_msg) => self.handle_discovery_response(discovery_msg).await,
+
+            LookupPeer(public_key, reply_tx) => self.spawn_peer_lookup(public_key, reply_tx),
         }
     }
 
+    fn spawn_peer_lookup(
+        &self,
+        public_key: Box<CommsPublicKey>,
+        reply_tx: oneshot::Sender<Result<Peer, DhtDiscoveryError>>,
+    )
+    {
+        let lookup = PeerLookup::new(
+            self.config.clone(),
+            Arc::clone(&self.node_identity),
+            Arc::clone(&self.peer_manager),
+            self.connectivity.clone(),
+        );
+        // Lookups make many requests to other peers, so they are run outside of the service loop
+        task::spawn(async move {
+            let start = Instant::now();
+            let result = lookup.lookup(&public_key).await;
+            debug!(
+                target: LOG_TARGET,
+                "Peer lookup for '{}' completed in {:.2?} (success = {})",
+                public_key,
+                start.elapsed(),
+                result.is_ok()
+            );
+            let _ = reply_tx.send(result);
+        });
+    }
+
     fn collect_all_discovery_requests(&mut self, public_key: &CommsPublicKey) -> Vec<DiscoveryRequestState> {
         let mut requests = Vec::new();
         let mut remaining_requests = HashMap::new();
@@ -398,6 +431,7 @@ mod test {
         test_utils::{build_peer_manager, make_node_identity},
     };
     use std::time::Duration;
+    use tari_comms::test_utils::mocks::create_connectivity_mock;
     use tari_shutdown::Shutdown;
 
     #[tokio_macros::test_basic]
@@ -412,11 +446,13 @@ mod test {
         // Requester which timeout instantly
         let mut requester = DhtDiscoveryRequester::new(sender, Duration::from_millis(1));
         let shutdown = Shutdown::new();
+        let (connectivity, _) = create_connectivity_mock();
 
         DhtDiscoveryService::new(
             DhtConfig::default(),
             node_identity,
             peer_manager,
+            connectivity,
             outbound_requester,
             receiver,
             shutdown.to_signal(),
@@ -22,6 +22,7 @@
 
 use crate::network_discovery::DhtNetworkDiscoveryRoundInfo;
 use std::sync::Arc;
+use tari_comms::{message::MessageTag, types::CommsPublicKey};
 use tokio::sync::broadcast;
 
 pub type DhtEventSender = broadcast::Sender<Arc<DhtEvent>>;
@@ -33,6 +34,13 @@ pub enum DhtEvent {
     /// Emitted by the store and forward service upon receipt of a sufficient number of store and forward messages
     StoreAndForwardMessagesReceived,
 
+    /// Emitted by the store and forward service when a recipient acknowledges that it received messages that this
+    /// node sent. The message tags are those of the DHT header of each original outbound message.
+    StoreAndForwardDeliveryReceipt {
+        recipient: Box<CommsPublicKey>,
+        message_tags: Vec<MessageTag>,
+    },
+
     /// Emitted by the NetworkDiscovery actor once a round of peer syncing has completed.
     NetworkDiscoveryPeersAdded(DhtNetworkDiscoveryRoundInfo),
 }
@@ -34,7 +34,7 @@ use std::{sync::Arc, task::Poll, time::Duration};
 use tari_comms::{
     connectivity::ConnectivityRequester,
     message::EnvelopeBody,
-    peer_manager::NodeIdentity,
+    peer_manager::{NodeId, NodeIdentity, ReputationEvent},
     pipeline::PipelineError,
     types::CommsPublicKey,
     utils::signature,
@@ -60,9 +60,11 @@ enum DecryptionError {
     #[error("Ephemeral public key not provided for encrypted message")]
     EphemeralKeyNotProvided,
     #[error("Message rejected because this node could not decrypt a message that was addressed to it")]
-    MessageRejectDecryptionFailed,
+    MessageRejectDecryptionFailed { authenticated_origin: CommsPublicKey },
     #[error("Failed to decode envelope body")]
-    EnvelopeBodyDecodeFailed,
+    EnvelopeBodyDecodeFailed {
+        authenticated_origin: Option<CommsPublicKey>,
+    },
     #[error("Failed to decrypt message body")]
     MessageBodyDecryptionFailed,
 }
@@ -174,7 +176,7 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
                     .await?;
                 Err(err.into())
             },
-            Err(EnvelopeBodyDecodeFailed) => {
+            Err(EnvelopeBodyDecodeFailed { authenticated_origin }) => {
                 debug!(
                     target: LOG_TARGET,
                     "Failed to decode message body ({}, peer={}, trace={}). Message discarded",
@@ -182,8 +184,28 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
                     source.node_id,
                     trace_id
                 );
+                // A relaying peer cannot check the body, only the origin that signed it is responsible for it
+                if let Some(origin) = authenticated_origin {
+                    connectivity
+                        .report_peer(NodeId::from_public_key(&origin), ReputationEvent::DhtValidationFailure)
+                        .await?;
+                }
                 Ok(())
             },
+            Err(MessageRejectDecryptionFailed { authenticated_origin }) => {
+                // The origin MAC covers the encrypted body, so the authenticated origin sent a body that cannot be
+                // decrypted. The peer that relayed it cannot tell.
+                connectivity
+                    .report_peer(
+                        NodeId::from_public_key(&authenticated_origin),
+                        ReputationEvent::DhtValidationFailure,
+                    )
+                    .await?;
+                Err(MessageRejectDecryptionFailed { authenticated_origin }.into())
+            },
+            // The origin of these messages could not be authenticated and relaying peers cannot check them, so the
+            // message is discarded without penalising anyone
+            Err(err @ OriginMacInvalidPublicKey) | Err(err @ OriginMacClearTextDecodeFailed) => Err(err.into()),
             Err(err) => Err(err.into()),
         }
     }
@@ -278,7 +300,7 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
                         message.tag,
                         message.dht_header.message_tag
                     );
-                    return Err(DecryptionError::MessageRejectDecryptionFailed);
+                    return Err(DecryptionError::MessageRejectDecryptionFailed { authenticated_origin });
                 }
 
                 Ok(DecryptedDhtMessage::failed(message))
@@ -387,7 +409,9 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
                     err,
                     message.dht_header.message_tag
                 );
-                Err(DecryptionError::EnvelopeBodyDecodeFailed)
+                Err(DecryptionError::EnvelopeBodyDecodeFailed {
+                    authenticated_origin: authenticated_pk,
+                })
             },
         }
     }
@@ -487,7 +511,43 @@ mod test {
 
         let err = service.call(inbound_msg).await.unwrap_err();
         let err = err.downcast::<DecryptionError>().unwrap();
-        unpack_enum!(DecryptionError::MessageRejectDecryptionFailed = err);
+        unpack_enum!(DecryptionError::MessageRejectDecryptionFailed { authenticated_origin } = err);
+        assert_eq!(&authenticated_origin, node_identity.public_key());
         assert!(result.lock().unwrap().is_none());
     }
+
+    #[tokio_macros::test_basic]
+    async fn decode_failure_only_penalises_authenticated_origin() {
+        let (mut connectivity, mock) = create_connectivity_mock();
+        let mock_state = mock.get_shared_state();
+        mock.spawn();
+        let service = service_fn(|_: DecryptedDhtMessage| future::ready(Result::<(), PipelineError>::Ok(())));
+        let node_identity = make_node_identity();
+        let relay = make_node_identity();
+        let mut service =
+            DecryptionService::new(Default::default(), node_identity.clone(), connectivity.clone(), service);
+
+        // Without an origin MAC nobody is responsible for the body, the relaying peer could not check it
+        let mut inbound_msg = make_dht_inbound_message(&node_identity, vec![0xff; 16], DhtMessageFlags::NONE, false);
+        inbound_msg.source_peer = Arc::new(relay.to_peer());
+        service.call(inbound_msg).await.unwrap();
+        // The mock handles requests in order, so the report (if any) has been handled once this returns
+        connectivity.get_connectivity_status().await.unwrap();
+        let calls = mock_state.take_calls().await;
+        assert!(calls.iter().all(|c| !c.starts_with("ReportPeer")));
+
+        let mut inbound_msg = make_dht_inbound_message(&node_identity, vec![0xff; 16], DhtMessageFlags::NONE, true);
+        inbound_msg.source_peer = Arc::new(relay.to_peer());
+        service.call(inbound_msg).await.unwrap();
+        connectivity.get_connectivity_status().await.unwrap();
+        let reports = mock_state
+            .take_calls()
+            .await
+            .into_iter()
+            .filter(|c| c.starts_with("ReportPeer"))
+            .collect::<Vec<_>>();
+        assert_eq!(reports.len(), 1);
+        assert!(reports[0].contains(&format!("{:?}", node_identity.node_id())));
+        assert!(!reports[0].contains(&format!("{:?}", relay.node_id())));
+    }
 }
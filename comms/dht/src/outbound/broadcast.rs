@@ -263,6 +263,7 @@ where S: Service<DhtOutboundMessage, Response = (), Error = PipelineError>
             is_discovery_enabled,
             force_origin,
             dht_header,
+            ..
         } = params;
 
         match self.select_peers(broadcast_strategy.clone()).await {
@@ -438,7 +439,7 @@ where S: Service<DhtOutboundMessage, Response = (), Error = PipelineError>
         let messages = selected_peers.into_iter().map(|node_id| {
             let (reply_tx, reply_rx) = oneshot::channel();
             let tag = MessageTag::new();
-            let send_state = MessageSendState::new(tag, reply_rx);
+            let send_state = MessageSendState::new(tag, reply_rx).with_node_id(node_id.clone());
             (
                 DhtOutboundMessage {
                     tag,
@@ -593,7 +594,7 @@ mod test {
             Network::LocalTest,
             chrono::Duration::seconds(10800),
         );
-        let (reply_tx, _reply_rx) = oneshot::channel();
+        let (reply_tx, reply_rx) = oneshot::channel();
 
         service
             .call(DhtOutboundRequest::SendMessage(
@@ -610,6 +611,14 @@ mod test {
             .iter()
             .any(|msg| msg.destination_node_id == example_peer.node_id));
         assert!(requests.iter().any(|msg| msg.destination_node_id == other_peer.node_id));
+
+        let send_message_response = reply_rx.await.unwrap();
+        unpack_enum!(SendMessageResponse::Queued(send_states) = send_message_response);
+        let tagged_node_ids = send_states.to_tagged_node_ids();
+        assert_eq!(tagged_node_ids.len(), 2);
+        assert!(requests
+            .iter()
+            .all(|msg| tagged_node_ids.contains(&(msg.tag, msg.destination_node_id.clone()))));
     }
 
     #[tokio_macros::test_basic]
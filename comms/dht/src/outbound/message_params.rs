@@ -23,7 +23,7 @@
 use crate::{
     broadcast_strategy::{BroadcastClosestRequest, BroadcastStrategy},
     envelope::{DhtMessageFlags, DhtMessageHeader, NodeDestination},
-    outbound::OutboundEncryption,
+    outbound::{OutboundEncryption, OutboundPriority},
     proto::envelope::DhtMessageType,
 };
 use std::{fmt, fmt::Display};
@@ -66,6 +66,7 @@ pub struct FinalSendMessageParams {
     pub dht_message_type: DhtMessageType,
     pub dht_message_flags: DhtMessageFlags,
     pub dht_header: Option<DhtMessageHeader>,
+    pub priority: Option<OutboundPriority>,
 }
 
 impl Default for FinalSendMessageParams {
@@ -79,6 +80,31 @@ impl Default for FinalSendMessageParams {
             force_origin: false,
             is_discovery_enabled: false,
             dht_header: None,
+            priority: None,
+        }
+    }
+}
+
+impl FinalSendMessageParams {
+    /// Returns the priority class of the message. If no priority has been set, DHT discovery and join messages are
+    /// `Background`, store and forward messages are `Low` and all other messages are `Normal`.
+    pub fn priority(&self) -> OutboundPriority {
+        if let Some(priority) = self.priority {
+            return priority;
+        }
+        let message_type = self
+            .dht_header
+            .as_ref()
+            .map(|header| header.message_type)
+            .unwrap_or(self.dht_message_type);
+        match message_type {
+            DhtMessageType::Join | DhtMessageType::Discovery | DhtMessageType::DiscoveryResponse => {
+                OutboundPriority::Background
+            },
+            DhtMessageType::SafRequestMessages |
+            DhtMessageType::SafStoredMessages |
+            DhtMessageType::SafDeliveryReceipt => OutboundPriority::Low,
+            DhtMessageType::None => OutboundPriority::Normal,
         }
     }
 }
@@ -195,6 +221,12 @@ impl SendMessageParams {
         self
     }
 
+    /// Set the priority class of the message. If not set, the priority is determined by the DHT message type.
+    pub fn with_priority(&mut self, priority: OutboundPriority) -> &mut Self {
+        self.params_mut().priority = Some(priority);
+        self
+    }
+
     /// Force the message origin to be included in the message. The origin is usually not included in messages without
     /// encryption, however this setting will force the message origin and signature to be included.
     pub fn force_origin(&mut self) -> &mut Self {
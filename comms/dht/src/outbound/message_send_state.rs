@@ -27,6 +27,7 @@ use std::{
 };
 use tari_comms::{
     message::{MessageTag, MessagingReplyRx},
+    peer_manager::NodeId,
     protocol::messaging::SendFailReason,
 };
 use tokio::time;
@@ -39,11 +40,22 @@ pub enum TimeoutResult<T> {
 #[derive(Debug)]
 pub struct MessageSendState {
     pub tag: MessageTag,
+    /// The peer that this message is being sent to, if known
+    pub node_id: Option<NodeId>,
     reply_rx: MessagingReplyRx,
 }
 impl MessageSendState {
     pub fn new(tag: MessageTag, reply_rx: MessagingReplyRx) -> Self {
-        Self { tag, reply_rx }
+        Self {
+            tag,
+            node_id: None,
+            reply_rx,
+        }
+    }
+
+    pub fn with_node_id(mut self, node_id: NodeId) -> Self {
+        self.node_id = Some(node_id);
+        self
     }
 
     pub fn wait_for_result(self) -> MessagingReplyRx {
@@ -231,6 +243,14 @@ impl MessageSendStates {
     pub fn to_tags(&self) -> Vec<MessageTag> {
         self.inner.iter().map(|s| s.tag).collect()
     }
+
+    /// Returns the message tag and destination peer of each send state for which the peer is known
+    pub fn to_tagged_node_ids(&self) -> Vec<(MessageTag, NodeId)> {
+        self.inner
+            .iter()
+            .filter_map(|s| s.node_id.clone().map(|node_id| (s.tag, node_id)))
+            .collect()
+    }
 }
 
 impl From<Vec<MessageSendState>> for MessageSendStates {
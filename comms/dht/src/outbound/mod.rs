@@ -35,6 +35,18 @@ pub use message_params::SendMessageParams;
 mod message_send_state;
 pub use message_send_state::{MessageSendState, MessageSendStates};
 
+mod priority_queue;
+pub use priority_queue::{
+    outbound_priority_queue,
+    OutboundPriority,
+    OutboundPriorityQueue,
+    OutboundQueueConfig,
+    OutboundQueueMetrics,
+    OutboundQueueSender,
+    OutboundQueueStats,
+    NUM_OUTBOUND_PRIORITIES,
+};
+
 mod requester;
 pub use requester::OutboundMessageRequester;
 
@@ -0,0 +1,376 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use super::message::DhtOutboundRequest;
+use futures::{
+    channel::mpsc,
+    stream::FusedStream,
+    task::{Context, Poll},
+    SinkExt,
+    Stream,
+    StreamExt,
+};
+use std::{
+    cmp,
+    fmt,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+/// The number of outbound priority classes
+pub const NUM_OUTBOUND_PRIORITIES: usize = 4;
+
+/// The priority class of an outbound message. Messages in a higher priority class are sent before those in a lower
+/// class, however each class is given a share of the outbound capacity so that lower classes are never starved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum OutboundPriority {
+    /// Latency sensitive messages e.g. transaction negotiation
+    High = 0,
+    /// The default for domain messages e.g. block propagation
+    Normal = 1,
+    /// Store and forward requests and responses
+    Low = 2,
+    /// Peer discovery and join messages
+    Background = 3,
+}
+
+impl OutboundPriority {
+    pub const ALL: [OutboundPriority; NUM_OUTBOUND_PRIORITIES] = [
+        OutboundPriority::High,
+        OutboundPriority::Normal,
+        OutboundPriority::Low,
+        OutboundPriority::Background,
+    ];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+impl Default for OutboundPriority {
+    fn default() -> Self {
+        OutboundPriority::Normal
+    }
+}
+
+impl fmt::Display for OutboundPriority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct OutboundQueueConfig {
+    /// The size of the buffer (channel) which holds pending outbound message requests for each priority class,
+    /// indexed by `OutboundPriority`.
+    /// Default: [20, 20, 50, 20]
+    pub buffer_sizes: [usize; NUM_OUTBOUND_PRIORITIES],
+    /// The maximum number of messages taken from a priority class before moving on to the next class that has
+    /// pending messages, indexed by `OutboundPriority`. A weight of zero is treated as one.
+    /// Default: [8, 4, 2, 1]
+    pub weights: [usize; NUM_OUTBOUND_PRIORITIES],
+}
+
+impl Default for OutboundQueueConfig {
+    fn default() -> Self {
+        Self {
+            buffer_sizes: [20, 20, 50, 20],
+            weights: [8, 4, 2, 1],
+        }
+    }
+}
+
+/// Create the priority queue sender and the weighted round-robin queue that yields its requests
+pub fn outbound_priority_queue(config: &OutboundQueueConfig) -> (OutboundQueueSender, OutboundPriorityQueue) {
+    let metrics = OutboundQueueMetrics::default();
+    let (senders, receivers) = config.buffer_sizes.iter().map(|size| mpsc::channel(*size)).unzip();
+    let sender = OutboundQueueSender {
+        senders,
+        metrics: Some(metrics.clone()),
+    };
+    let queue = OutboundPriorityQueue::new(
+        receivers,
+        config.weights.iter().map(|w| cmp::max(*w, 1)).collect(),
+        metrics,
+    );
+    (sender, queue)
+}
+
+/// Sends outbound requests to the queue for their priority class.
+///
+/// A sender created from a single `mpsc::Sender` sends requests of all priorities to that channel.
+#[derive(Clone)]
+pub struct OutboundQueueSender {
+    senders: Vec<mpsc::Sender<DhtOutboundRequest>>,
+    metrics: Option<OutboundQueueMetrics>,
+}
+
+impl OutboundQueueSender {
+    pub async fn send(
+        &mut self,
+        priority: OutboundPriority,
+        request: DhtOutboundRequest,
+    ) -> Result<(), mpsc::SendError>
+    {
+        let index = cmp::min(priority.index(), self.senders.len() - 1);
+        // Count the request before sending it so that it cannot be dequeued before it is counted as enqueued
+        if let Some(metrics) = self.metrics.as_ref() {
+            metrics.inc_enqueued(priority);
+        }
+        self.senders[index].send(request).await
+    }
+
+    #[cfg(test)]
+    pub(crate) fn get_mpsc_sender(&self, priority: OutboundPriority) -> mpsc::Sender<DhtOutboundRequest> {
+        self.senders[cmp::min(priority.index(), self.senders.len() - 1)].clone()
+    }
+}
+
+impl From<mpsc::Sender<DhtOutboundRequest>> for OutboundQueueSender {
+    fn from(sender: mpsc::Sender<DhtOutboundRequest>) -> Self {
+        Self {
+            senders: vec![sender],
+            metrics: None,
+        }
+    }
+}
+
+/// A stream of outbound requests taken from each priority class in turn. Up to `weight` requests are taken from a
+/// class before moving to the next class, so that higher priority classes get a larger share of the outbound capacity
+/// when all classes are busy. Idle classes give up their turn.
+pub struct OutboundPriorityQueue {
+    receivers: Vec<mpsc::Receiver<DhtOutboundRequest>>,
+    weights: Vec<usize>,
+    current: usize,
+    remaining: usize,
+    metrics: OutboundQueueMetrics,
+}
+
+impl OutboundPriorityQueue {
+    fn new(
+        receivers: Vec<mpsc::Receiver<DhtOutboundRequest>>,
+        weights: Vec<usize>,
+        metrics: OutboundQueueMetrics,
+    ) -> Self
+    {
+        Self {
+            current: receivers.len() - 1,
+            remaining: 0,
+            receivers,
+            weights,
+            metrics,
+        }
+    }
+
+    /// Returns the per priority class metrics for this queue
+    pub fn metrics(&self) -> OutboundQueueMetrics {
+        self.metrics.clone()
+    }
+}
+
+impl Stream for OutboundPriorityQueue {
+    type Item = DhtOutboundRequest;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        let num_classes = this.receivers.len();
+        // Poll every class at least once so that a waker is registered for each of them
+        for _ in 0..=num_classes {
+            if this.remaining == 0 {
+                this.current = (this.current + 1) % num_classes;
+                this.remaining = this.weights[this.current];
+            }
+
+            let receiver = &mut this.receivers[this.current];
+            if !receiver.is_terminated() {
+                if let Poll::Ready(Some(request)) = receiver.poll_next_unpin(cx) {
+                    this.remaining -= 1;
+                    this.metrics.inc_dequeued(OutboundPriority::ALL[this.current]);
+                    return Poll::Ready(Some(request));
+                }
+            }
+            this.remaining = 0;
+        }
+
+        if this.receivers.iter().all(|r| r.is_terminated()) {
+            Poll::Ready(None)
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+#[derive(Default)]
+struct ClassCounters {
+    enqueued: AtomicU64,
+    dequeued: AtomicU64,
+}
+
+/// Per priority class counters for the outbound queue
+#[derive(Clone, Default)]
+pub struct OutboundQueueMetrics {
+    counters: Arc<[ClassCounters; NUM_OUTBOUND_PRIORITIES]>,
+}
+
+impl OutboundQueueMetrics {
+    fn inc_enqueued(&self, priority: OutboundPriority) {
+        self.counters[priority.index()].enqueued.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn inc_dequeued(&self, priority: OutboundPriority) {
+        self.counters[priority.index()].dequeued.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Returns the current stats for each priority class
+    pub fn get_stats(&self) -> Vec<OutboundQueueStats> {
+        OutboundPriority::ALL
+            .iter()
+            .map(|priority| {
+                let counters = &self.counters[priority.index()];
+                OutboundQueueStats {
+                    priority: *priority,
+                    total_enqueued: counters.enqueued.load(Ordering::Relaxed),
+                    total_sent: counters.dequeued.load(Ordering::Relaxed),
+                }
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutboundQueueStats {
+    pub priority: OutboundPriority,
+    /// The total number of requests added to this class
+    pub total_enqueued: u64,
+    /// The total number of requests taken from this class for sending
+    pub total_sent: u64,
+}
+
+impl OutboundQueueStats {
+    /// The number of requests waiting in this class
+    pub fn num_pending(&self) -> u64 {
+        self.total_enqueued.saturating_sub(self.total_sent)
+    }
+}
+
+impl fmt::Display for OutboundQueueStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: enqueued = {}, sent = {}, pending = {}",
+            self.priority,
+            self.total_enqueued,
+            self.total_sent,
+            self.num_pending()
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::outbound::SendMessageParams;
+    use futures::channel::oneshot;
+
+    fn create_request(body: &[u8]) -> DhtOutboundRequest {
+        let (reply_tx, _) = oneshot::channel();
+        DhtOutboundRequest::SendMessage(
+            Box::new(SendMessageParams::new().finish()),
+            body.to_vec().into(),
+            reply_tx,
+        )
+    }
+
+    fn body_of(request: DhtOutboundRequest) -> u8 {
+        match request {
+            DhtOutboundRequest::SendMessage(_, body, _) => body[0],
+        }
+    }
+
+    #[tokio_macros::test_basic]
+    async fn weighted_round_robin() {
+        let config = OutboundQueueConfig {
+            buffer_sizes: [20; NUM_OUTBOUND_PRIORITIES],
+            weights: [3, 2, 0, 1],
+        };
+        let (mut sender, queue) = outbound_priority_queue(&config);
+        for priority in OutboundPriority::ALL.iter() {
+            for _ in 0..4 {
+                sender
+                    .send(*priority, create_request(&[priority.index() as u8]))
+                    .await
+                    .unwrap();
+            }
+        }
+        drop(sender);
+
+        let order = queue.map(body_of).collect::<Vec<_>>().await;
+        assert_eq!(order, vec![0, 0, 0, 1, 1, 2, 3, 0, 1, 1, 2, 3, 2, 3, 2, 3]);
+    }
+
+    #[tokio_macros::test_basic]
+    async fn idle_classes_give_up_their_turn() {
+        let (mut sender, mut queue) = outbound_priority_queue(&Default::default());
+        sender
+            .send(OutboundPriority::Background, create_request(&[3]))
+            .await
+            .unwrap();
+        assert_eq!(body_of(queue.next().await.unwrap()), 3);
+        sender.send(OutboundPriority::Low, create_request(&[2])).await.unwrap();
+        sender.send(OutboundPriority::High, create_request(&[0])).await.unwrap();
+        assert_eq!(body_of(queue.next().await.unwrap()), 0);
+        assert_eq!(body_of(queue.next().await.unwrap()), 2);
+    }
+
+    #[tokio_macros::test_basic]
+    async fn metrics() {
+        let (mut sender, mut queue) = outbound_priority_queue(&Default::default());
+        sender.send(OutboundPriority::High, create_request(&[0])).await.unwrap();
+        sender.send(OutboundPriority::Low, create_request(&[2])).await.unwrap();
+        sender.send(OutboundPriority::Low, create_request(&[2])).await.unwrap();
+        queue.next().await.unwrap();
+        queue.next().await.unwrap();
+
+        let stats = queue.metrics.get_stats();
+        assert_eq!(stats[OutboundPriority::High.index()].total_sent, 1);
+        assert_eq!(stats[OutboundPriority::High.index()].num_pending(), 0);
+        assert_eq!(stats[OutboundPriority::Low.index()].total_enqueued, 2);
+        assert_eq!(stats[OutboundPriority::Low.index()].num_pending(), 1);
+        assert_eq!(stats[OutboundPriority::Normal.index()].total_enqueued, 0);
+    }
+
+    #[tokio_macros::test_basic]
+    async fn single_channel_sender() {
+        let (tx, mut rx) = mpsc::channel(2);
+        let mut sender = OutboundQueueSender::from(tx);
+        sender
+            .send(OutboundPriority::Background, create_request(&[3]))
+            .await
+            .unwrap();
+        sender.send(OutboundPriority::High, create_request(&[0])).await.unwrap();
+        assert_eq!(body_of(rx.next().await.unwrap()), 3);
+        assert_eq!(body_of(rx.next().await.unwrap()), 0);
+    }
+}
@@ -30,12 +30,13 @@ use crate::{
         message_send_state::MessageSendState,
         DhtOutboundError,
         MessageSendStates,
+        OutboundPriority,
+        OutboundQueueSender,
     },
 };
-use futures::{
-    channel::{mpsc, oneshot},
-    SinkExt,
-};
+#[cfg(test)]
+use futures::channel::mpsc;
+use futures::channel::oneshot;
 use log::*;
 use tari_comms::{message::MessageExt, peer_manager::NodeId, types::CommsPublicKey, wrap_in_envelope_body};
 
@@ -43,12 +44,22 @@ const LOG_TARGET: &str = "comms::dht::requests::outbound";
 
 #[derive(Clone)]
 pub struct OutboundMessageRequester {
-    sender: mpsc::Sender<DhtOutboundRequest>,
+    sender: OutboundQueueSender,
+    default_priority: Option<OutboundPriority>,
 }
 
 impl OutboundMessageRequester {
-    pub fn new(sender: mpsc::Sender<DhtOutboundRequest>) -> Self {
-        Self { sender }
+    pub fn new<T: Into<OutboundQueueSender>>(sender: T) -> Self {
+        Self {
+            sender: sender.into(),
+            default_priority: None,
+        }
+    }
+
+    /// Send messages from this requester with the given priority, unless a priority is set in the message params
+    pub fn with_default_priority(mut self, priority: OutboundPriority) -> Self {
+        self.default_priority = Some(priority);
+        self
     }
 
     /// Send directly to a peer. If the peer does not exist in the peer list, a discovery will be initiated.
@@ -274,13 +285,20 @@ impl OutboundMessageRequester {
     /// Send a raw message
     pub async fn send_raw(
         &mut self,
-        params: FinalSendMessageParams,
+        mut params: FinalSendMessageParams,
         body: Vec<u8>,
     ) -> Result<SendMessageResponse, DhtOutboundError>
     {
         let (reply_tx, reply_rx) = oneshot::channel();
+        if params.priority.is_none() {
+            params.priority = self.default_priority;
+        }
+        let priority = params.priority();
         self.sender
-            .send(DhtOutboundRequest::SendMessage(Box::new(params), body.into(), reply_tx))
+            .send(
+                priority,
+                DhtOutboundRequest::SendMessage(Box::new(params), body.into(), reply_tx),
+            )
             .await?;
 
         reply_rx
@@ -290,6 +308,6 @@ impl OutboundMessageRequester {
 
     #[cfg(test)]
     pub fn get_mpsc_sender(&self) -> mpsc::Sender<DhtOutboundRequest> {
-        self.sender.clone()
+        self.sender.get_mpsc_sender(Default::default())
     }
 }
@@ -26,11 +26,12 @@ use crate::{
     crypt,
     envelope::{timestamp_to_datetime, DhtMessageFlags, DhtMessageHeader, NodeDestination},
     inbound::{DecryptedDhtMessage, DhtInboundMessage},
-    outbound::{OutboundMessageRequester, SendMessageParams},
+    outbound::{OutboundEncryption, OutboundMessageRequester, SendMessageParams},
     proto::{
         envelope::{DhtMessageType, OriginMac},
         store_forward::{
             stored_messages_response::SafResponseType,
+            SafDeliveryReceipt,
             StoredMessage as ProtoStoredMessage,
             StoredMessagesRequest,
             StoredMessagesResponse,
@@ -42,10 +43,10 @@ use digest::Digest;
 use futures::{channel::mpsc, future, stream, Future, SinkExt, StreamExt};
 use log::*;
 use prost::Message;
-use std::{convert::TryInto, sync::Arc};
+use std::{collections::HashMap, convert::TryInto, sync::Arc};
 use tari_comms::{
     message::{EnvelopeBody, MessageTag},
-    peer_manager::{NodeIdentity, Peer, PeerFeatures, PeerManager, PeerManagerError},
+    peer_manager::{NodeId, NodeIdentity, Peer, PeerFeatures, PeerManager, PeerManagerError},
     pipeline::PipelineError,
     types::{Challenge, CommsPublicKey},
     utils::signature,
@@ -133,6 +134,9 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
             },
 
             DhtMessageType::SafStoredMessages => self.handle_stored_messages(message).await?,
+            DhtMessageType::SafDeliveryReceipt if message.decryption_succeeded() => {
+                self.handle_delivery_receipt(message).await?
+            },
             // Not a SAF message, call downstream middleware
             _ => {
                 trace!(
@@ -333,6 +337,20 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
             .filter(Result::is_ok)
             .map(Result::unwrap);
 
+        // Delivery receipts for messages this node sent are handled here and not passed on
+        let (receipts, successful_msgs): (Vec<_>, Vec<_>) =
+            successful_msgs_iter.partition(|msg| msg.dht_header.message_type == DhtMessageType::SafDeliveryReceipt);
+
+        for receipt in receipts {
+            if let Err(err) = self.handle_delivery_receipt(receipt).await {
+                debug!(target: LOG_TARGET, "Discarding stored delivery receipt: {}", err);
+            }
+        }
+
+        if self.config.saf_delivery_receipts {
+            self.send_delivery_receipts(&successful_msgs).await;
+        }
+
         // Let the SAF Service know we got a SAF response.
         let _ = self
             .saf_response_signal_sender
@@ -341,7 +359,7 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
             .map_err(|e| warn!(target: LOG_TARGET, "Error sending SAF response signal; {:?}", e));
 
         self.next_service
-            .call_all(stream::iter(successful_msgs_iter))
+            .call_all(stream::iter(successful_msgs))
             .unordered()
             .for_each(|service_result| {
                 if let Err(err) = service_result {
@@ -354,6 +372,75 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
         Ok(())
     }
 
+    async fn handle_delivery_receipt(&mut self, message: DecryptedDhtMessage) -> Result<(), StoreAndForwardError> {
+        // Only the recipient of the original message can acknowledge it, so the receipt must be signed
+        let recipient = message
+            .authenticated_origin()
+            .cloned()
+            .ok_or_else(|| StoreAndForwardError::InvalidOriginMac)?;
+        let receipt = message
+            .success()
+            .expect("already checked that this message decrypted successfully")
+            .decode_part::<SafDeliveryReceipt>(0)?
+            .ok_or_else(|| StoreAndForwardError::InvalidEnvelopeBody)?;
+
+        debug!(
+            target: LOG_TARGET,
+            "Received delivery receipt for {} stored message(s) from '{}' (Trace: {})",
+            receipt.message_tags.len(),
+            recipient,
+            message.dht_header.message_tag
+        );
+        let message_tags = receipt.message_tags.into_iter().map(MessageTag::from).collect();
+        self.saf_requester
+            .delivery_receipt_received(recipient, message_tags)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Send a signed delivery receipt to the origin of each encrypted stored message. The receipt is encrypted for
+    /// and propagated towards the origin, so it will itself be stored if the origin is offline.
+    async fn send_delivery_receipts(&mut self, messages: &[DecryptedDhtMessage]) {
+        let mut tags_by_origin = HashMap::<CommsPublicKey, Vec<u64>>::new();
+        for msg in messages.iter().filter(|msg| msg.is_encrypted()) {
+            if let Some(origin) = msg.authenticated_origin() {
+                tags_by_origin
+                    .entry(origin.clone())
+                    .or_default()
+                    .push(msg.dht_header.message_tag.as_value());
+            }
+        }
+
+        for (origin, message_tags) in tags_by_origin {
+            let node_id = NodeId::from_public_key(&origin);
+            trace!(
+                target: LOG_TARGET,
+                "Sending delivery receipt for {} stored message(s) to '{}'",
+                message_tags.len(),
+                origin
+            );
+            let result = self
+                .outbound_service
+                .send_message_no_header(
+                    SendMessageParams::new()
+                        .closest(node_id.clone(), vec![])
+                        .with_destination(node_id.into())
+                        .with_encryption(OutboundEncryption::EncryptFor(Box::new(origin)))
+                        .with_dht_message_type(DhtMessageType::SafDeliveryReceipt)
+                        .finish(),
+                    SafDeliveryReceipt { message_tags },
+                )
+                .await;
+            if let Err(err) = result {
+                warn!(
+                    target: LOG_TARGET,
+                    "Failed to send store and forward delivery receipt: {}", err
+                );
+            }
+        }
+    }
+
     fn process_incoming_stored_message(
         &self,
         source_peer: Arc<Peer>,
@@ -749,4 +836,86 @@ mod test {
         );
         assert_eq!(signals.len(), 1);
     }
+
+    #[tokio_macros::test_basic]
+    async fn stored_message_delivery_receipts() {
+        let rt_handle = Handle::current();
+        let spy = service_spy();
+        let (requester, mock_state) = create_store_and_forward_mock();
+
+        let peer_manager = build_peer_manager();
+        let (oms_tx, mut oms_rx) = mpsc::channel(1);
+
+        let node_identity = make_node_identity();
+
+        let msg_a = wrap_in_envelope_body!(&b"A".to_vec()).to_encoded_bytes();
+        let inbound_msg_a = make_dht_inbound_message(&node_identity, msg_a, DhtMessageFlags::ENCRYPTED, true);
+        peer_manager
+            .add_peer(Clone::clone(&*inbound_msg_a.source_peer))
+            .await
+            .unwrap();
+        let msg_a_tag = inbound_msg_a.dht_header.message_tag;
+
+        // A receipt for a message this node previously sent
+        let receipt = wrap_in_envelope_body!(SafDeliveryReceipt {
+            message_tags: vec![123]
+        })
+        .to_encoded_bytes();
+        let mut inbound_receipt = make_dht_inbound_message(&node_identity, receipt, DhtMessageFlags::ENCRYPTED, true);
+        inbound_receipt.dht_header.message_type = DhtMessageType::SafDeliveryReceipt;
+
+        let msg1 = ProtoStoredMessage::new(0, inbound_msg_a.dht_header, inbound_msg_a.body);
+        let msg2 = ProtoStoredMessage::new(0, inbound_receipt.dht_header, inbound_receipt.body);
+        let mut message = DecryptedDhtMessage::succeeded(
+            wrap_in_envelope_body!(StoredMessagesResponse {
+                messages: vec![msg1, msg2],
+                request_id: 123,
+                response_type: 0
+            }),
+            None,
+            make_dht_inbound_message(
+                &node_identity,
+                b"Stored message".to_vec(),
+                DhtMessageFlags::ENCRYPTED,
+                true,
+            ),
+        );
+        message.dht_header.message_type = DhtMessageType::SafStoredMessages;
+
+        let (dht_requester, mock) = create_dht_actor_mock(1);
+        rt_handle.spawn(mock.run());
+        let (saf_response_signal_sender, _saf_response_signal_receiver) = mpsc::channel(20);
+
+        let task = MessageHandlerTask::new(
+            Default::default(),
+            spy.to_service::<PipelineError>(),
+            requester,
+            dht_requester,
+            peer_manager,
+            OutboundMessageRequester::new(oms_tx),
+            node_identity.clone(),
+            message,
+            saf_response_signal_sender,
+        );
+
+        let task = rt_handle.spawn(task.run());
+
+        let (params, body) = unwrap_oms_send_msg!(oms_rx.next().await.unwrap());
+        task.await.unwrap().unwrap();
+        assert_eq!(params.dht_message_type, DhtMessageType::SafDeliveryReceipt);
+        assert_eq!(
+            params.destination,
+            NodeDestination::NodeId(Box::new(node_identity.node_id().clone()))
+        );
+        let body = EnvelopeBody::decode(body.to_vec().as_slice()).unwrap();
+        let receipt = body.decode_part::<SafDeliveryReceipt>(0).unwrap().unwrap();
+        assert_eq!(receipt.message_tags, vec![msg_a_tag.as_value()]);
+
+        // Only the stored message is passed on, the receipt is handled by this layer
+        assert_eq!(spy.call_count(), 1);
+        let calls = mock_state.take_calls().await;
+        assert_eq!(calls.len(), 1);
+        assert!(calls[0].contains("DeliveryReceiptReceived"));
+        assert!(calls[0].contains("123"));
+    }
 }
@@ -46,6 +46,7 @@ use log::*;
 use std::{cmp, convert::TryFrom, sync::Arc, time::Duration};
 use tari_comms::{
     connectivity::{ConnectivityEvent, ConnectivityEventRx, ConnectivityRequester},
+    message::MessageTag,
     peer_manager::{NodeId, PeerFeatures},
     types::CommsPublicKey,
     PeerManager,
@@ -94,6 +95,7 @@ pub enum StoreAndForwardRequest {
     RemoveMessages(Vec<i32>),
     SendStoreForwardRequestToPeer(Box<NodeId>),
     SendStoreForwardRequestNeighbours,
+    DeliveryReceiptReceived(Box<CommsPublicKey>, Vec<MessageTag>),
 }
 
 #[derive(Clone)]
@@ -147,6 +149,23 @@ impl StoreAndForwardRequester {
             .map_err(|_| StoreAndForwardError::RequesterChannelClosed)?;
         Ok(())
     }
+
+    /// Notify the service that `recipient` has acknowledged receipt of the messages with the given message tags
+    pub async fn delivery_receipt_received(
+        &mut self,
+        recipient: CommsPublicKey,
+        message_tags: Vec<MessageTag>,
+    ) -> SafResult<()>
+    {
+        self.sender
+            .send(StoreAndForwardRequest::DeliveryReceiptReceived(
+                Box::new(recipient),
+                message_tags,
+            ))
+            .await
+            .map_err(|_| StoreAndForwardError::RequesterChannelClosed)?;
+        Ok(())
+    }
 }
 
 pub struct StoreAndForwardService {
@@ -298,6 +317,18 @@ impl StoreAndForwardService {
                     );
                 }
             },
+            DeliveryReceiptReceived(recipient, message_tags) => {
+                debug!(
+                    target: LOG_TARGET,
+                    "Peer '{}' acknowledged delivery of {} stored message(s)",
+                    recipient,
+                    message_tags.len()
+                );
+                self.publish_event(DhtEvent::StoreAndForwardDeliveryReceipt {
+                    recipient,
+                    message_tags,
+                });
+            },
         }
     }
 
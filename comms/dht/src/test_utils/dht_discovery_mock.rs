@@ -107,6 +107,10 @@ impl DhtDiscoveryMock {
                 reply_tx.send(Ok(lock.clone())).unwrap();
             },
             NotifyDiscoveryResponseReceived(_) => {},
+            LookupPeer(_, reply_tx) => {
+                let lock = self.state.discover_peer.read().unwrap();
+                reply_tx.send(Ok(lock.clone())).unwrap();
+            },
         }
     }
 }
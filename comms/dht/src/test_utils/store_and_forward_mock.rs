@@ -143,6 +143,7 @@ impl StoreAndForwardMock {
             },
             SendStoreForwardRequestToPeer(_) => {},
             SendStoreForwardRequestNeighbours => {},
+            DeliveryReceiptReceived(_, _) => {},
         }
     }
 }
@@ -94,7 +94,7 @@ async fn run() -> Result<(), Error> {
     }
     if !is_tcp {
         if let Some(tor_identity_path) = tor_identity_path.as_ref() {
-            save_json(comms_node.hidden_service().unwrap().tor_identity(), tor_identity_path)?;
+            save_json(&comms_node.hidden_service().unwrap().tor_identity(), tor_identity_path)?;
         }
     }
 
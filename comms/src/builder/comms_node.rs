@@ -40,6 +40,7 @@ use crate::{
         ProtocolNotificationTx,
         Protocols,
     },
+    runtime,
     tor,
     transports::Transport,
     CommsBuilder,
@@ -136,6 +137,42 @@ impl UnspawnedCommsNode {
         }
     }
 
+    /// Keeps the node's public address in sync with the hidden service onion address when the onion key is rotated
+    fn spawn_public_address_updater(hidden_service: &tor::HiddenService, node_identity: Arc<NodeIdentity>) {
+        let mut events = hidden_service.subscribe_events();
+        runtime::task::spawn(async move {
+            loop {
+                match events.recv().await {
+                    Ok(tor::HiddenServiceEvent::IdentityRotated(identity)) => match identity.try_get_onion_address() {
+                        Ok(address) => {
+                            info!(
+                                target: LOG_TARGET,
+                                "Onion key rotated. Your node's public address is now '{}'", address
+                            );
+                            node_identity.set_public_address(address);
+                        },
+                        Err(err) => {
+                            error!(
+                                target: LOG_TARGET,
+                                "Onion key rotated but the new identity does not have a valid onion address ({}). \
+                                 Your node's public address was not changed.",
+                                err
+                            );
+                        },
+                    },
+                    Ok(_) => {},
+                    Err(broadcast::RecvError::Lagged(n)) => {
+                        warn!(
+                            target: LOG_TARGET,
+                            "Hidden service event subscriber lagged by {} events", n
+                        );
+                    },
+                    Err(broadcast::RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+
     pub async fn spawn_with_transport<TTransport>(self, transport: TTransport) -> Result<CommsNode, CommsBuilderError>
     where
         TTransport: Transport + Unpin + Send + Sync + Clone + 'static,
@@ -230,6 +267,7 @@ impl UnspawnedCommsNode {
             ctl.set_proxied_addr(listening_addr.clone());
             let hs = ctl.create_hidden_service().await?;
             node_identity.set_public_address(hs.get_onion_address());
+            Self::spawn_public_address_updater(&hs, node_identity.clone());
             hidden_service = Some(hs);
         }
         info!(
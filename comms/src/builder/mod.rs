@@ -149,6 +149,19 @@ impl CommsBuilder {
         self
     }
 
+    /// Caps the bytes per second sent and received across all peer connections and on any single peer connection.
+    /// None means unlimited.
+    pub fn with_bandwidth_limits(
+        mut self,
+        max_bytes_per_sec: Option<u64>,
+        max_peer_bytes_per_sec: Option<u64>,
+    ) -> Self
+    {
+        self.connection_manager_config.max_bandwidth_bytes_per_sec = max_bytes_per_sec;
+        self.connection_manager_config.max_peer_bandwidth_bytes_per_sec = max_peer_bytes_per_sec;
+        self
+    }
+
     /// Sets the minimum required connectivity as a percentage of peers added to the connectivity manager peer set.
     pub fn with_min_connectivity(mut self, min_connectivity: f32) -> Self {
         self.connectivity_config.min_connectivity = min_connectivity;
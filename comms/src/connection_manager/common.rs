@@ -24,15 +24,17 @@ use super::types::ConnectionDirection;
 use crate::{
     connection_manager::error::ConnectionManagerError,
     multiaddr::{Multiaddr, Protocol},
-    multiplexing::Yamux,
+    multiplexing::Multiplexer,
+    noise::NoiseConfig,
     peer_manager::{NodeId, NodeIdentity, Peer, PeerFeatures, PeerFlags},
     proto::identity::PeerIdentityMsg,
     protocol,
     protocol::ProtocolId,
+    transports::QuicConnection,
     types::CommsPublicKey,
     PeerManager,
 };
-use futures::StreamExt;
+use futures::{AsyncWriteExt, StreamExt};
 use log::*;
 use tari_crypto::tari_utilities::ByteArray;
 
@@ -41,15 +43,15 @@ const LOG_TARGET: &str = "comms::connection_manager::common";
 /// The maximum size of the peer's user agent string. If the peer sends a longer string it is truncated.
 const MAX_USER_AGENT_LEN: usize = 100;
 
-pub async fn perform_identity_exchange<'p, P: IntoIterator<Item = &'p ProtocolId>>(
-    muxer: &mut Yamux,
+pub async fn perform_identity_exchange<'p, M: Multiplexer, P: IntoIterator<Item = &'p ProtocolId>>(
+    muxer: &mut M,
     node_identity: &NodeIdentity,
     direction: ConnectionDirection,
     our_supported_protocols: P,
     user_agent: String,
 ) -> Result<PeerIdentityMsg, ConnectionManagerError>
 {
-    let mut control = muxer.get_yamux_control();
+    let mut control = muxer.get_control();
     let stream = match direction {
         ConnectionDirection::Inbound => muxer
             .incoming_mut()
@@ -70,6 +72,36 @@ pub async fn perform_identity_exchange<'p, P: IntoIterator<Item = &'p ProtocolId
     Ok(peer_identity)
 }
 
+/// Authenticate the peer on a QUIC connection by performing the noise handshake on the first stream of the connection.
+/// The listener's TLS certificate is used as the noise prologue, binding the noise session to the QUIC connection.
+/// The authenticated public key of the peer is returned.
+pub async fn authenticate_quic_connection(
+    connection: &mut QuicConnection,
+    noise_config: &NoiseConfig,
+    direction: ConnectionDirection,
+) -> Result<CommsPublicKey, ConnectionManagerError>
+{
+    let stream = match direction {
+        ConnectionDirection::Inbound => connection.accept_stream().await,
+        ConnectionDirection::Outbound => connection.open_stream().await,
+    }
+    .map_err(|err| ConnectionManagerError::QuicConnectionError(err.to_string()))?;
+
+    let prologue = connection.channel_binding().to_vec();
+    let mut noise_socket = noise_config
+        .upgrade_socket_with_prologue(stream, direction, &prologue)
+        .await?;
+
+    let authenticated_public_key = noise_socket
+        .get_remote_public_key()
+        .ok_or_else(|| ConnectionManagerError::InvalidStaticPublicKey)?;
+
+    // The handshake stream is not used again
+    let _ = noise_socket.close().await;
+
+    Ok(authenticated_public_key)
+}
+
 /// Validate the node id against the given public key. Returns true if this is a valid base node
 /// node id, otherwise false.
 pub fn is_valid_base_node_node_id(node_id: &NodeId, public_key: &CommsPublicKey) -> bool {
@@ -213,6 +245,16 @@ fn validate_address(addr: &Multiaddr, allow_test_addrs: bool) -> Result<(), Conn
         None => Ok(()),
     };
 
+    // A TCP address may be followed by a WebSocket component
+    let expect_end_of_tcp_address = |mut iter: multiaddr::Iter<'_>| match iter.next() {
+        Some(Protocol::Ws(_)) => expect_end_of_address(iter),
+        Some(p) => Err(ConnectionManagerError::InvalidMultiaddr(format!(
+            "Unexpected multiaddress component '{}'",
+            p
+        ))),
+        None => Ok(()),
+    };
+
     match proto {
         Protocol::Dns4(_) | Protocol::Dns6(_) | Protocol::Dnsaddr(_) => {
             let tcp = addr_iter.next().ok_or_else(|| {
@@ -221,7 +263,7 @@ fn validate_address(addr: &Multiaddr, allow_test_addrs: bool) -> Result<(), Conn
 
             validate_tcp_port(tcp)?;
 
-            expect_end_of_address(addr_iter)
+            expect_end_of_tcp_address(addr_iter)
         },
 
         Protocol::Ip4(addr)
@@ -239,12 +281,26 @@ fn validate_address(addr: &Multiaddr, allow_test_addrs: bool) -> Result<(), Conn
             ))
         },
         Protocol::Ip4(_) | Protocol::Ip6(_) => {
-            let tcp = addr_iter.next().ok_or_else(|| {
+            let transport = addr_iter.next().ok_or_else(|| {
                 ConnectionManagerError::InvalidMultiaddr("Address does not include a TCP port".to_string())
             })?;
 
-            validate_tcp_port(tcp)?;
-            expect_end_of_address(addr_iter)
+            match transport {
+                Protocol::Udp(0) => Err(ConnectionManagerError::InvalidMultiaddr(
+                    "Cannot connect to a zero UDP port".to_string(),
+                )),
+                // UDP is only supported for QUIC
+                Protocol::Udp(_) => match addr_iter.next() {
+                    Some(Protocol::Quic) => expect_end_of_address(addr_iter),
+                    _ => Err(ConnectionManagerError::InvalidMultiaddr(
+                        "UDP addresses must be QUIC addresses".to_string(),
+                    )),
+                },
+                tcp => {
+                    validate_tcp_port(tcp)?;
+                    expect_end_of_tcp_address(addr_iter)
+                },
+            }
         },
         Protocol::Memory(0) => Err(ConnectionManagerError::InvalidMultiaddr(
             "Cannot connect to a zero memory port".to_string(),
@@ -296,10 +352,17 @@ mod test {
                 .parse()
                 .unwrap(),
             multiaddr!(Dnsaddr("mike-magic-nodes.com"), Tcp(1u16)),
+            "/ip4/172.0.0.1/tcp/1/ws".parse().unwrap(),
+            "/ip4/172.0.0.1/udp/1/quic".parse().unwrap(),
         ];
 
         let invalid = &[
             multiaddr!(Ip4([127, 0, 0, 1]), Tcp(1u16)),
+            "/ip4/172.0.0.1/ws".parse().unwrap(),
+            "/ip4/172.0.0.1/tcp/1/ws/http".parse().unwrap(),
+            "/ip4/172.0.0.1/udp/1".parse().unwrap(),
+            "/ip4/172.0.0.1/udp/0/quic".parse().unwrap(),
+            "/ip4/127.0.0.1/udp/1/quic".parse().unwrap(),
             multiaddr!(Ip4([169, 254, 0, 1]), Tcp(1u16)),
             multiaddr!(Ip4([172, 0, 0, 1])),
             "/onion/aaimaq4ygg2iegci:1234/http".parse().unwrap(),
@@ -31,11 +31,11 @@ use crate::{
         wire_mode::WireMode,
     },
     multiaddr::Multiaddr,
-    multiplexing::Yamux,
+    multiplexing::{BandwidthLimits, Multiplexer, Quic, Yamux},
     noise::{NoiseConfig, NoiseSocket},
     peer_manager::{NodeId, NodeIdentity, Peer, PeerFeatures, PeerManager},
     protocol::ProtocolId,
-    transports::Transport,
+    transports::{QuicConnection, QuicTransport, Transport},
     types::CommsPublicKey,
 };
 use futures::{
@@ -59,10 +59,18 @@ use tokio::time;
 
 const LOG_TARGET: &str = "comms::connection_manager::dialer";
 
-type DialResult<TSocket> = Result<(NoiseSocket<TSocket>, Multiaddr), ConnectionManagerError>;
+type DialResult<TSocket> = Result<(DialedConnection<TSocket>, Multiaddr), ConnectionManagerError>;
 type DialFuturesUnordered =
     FuturesUnordered<BoxFuture<'static, (DialState, Result<PeerConnection, ConnectionManagerError>)>>;
 
+/// An authenticated connection to a peer which has not yet been multiplexed
+enum DialedConnection<TSocket> {
+    /// A transport socket upgraded to use noise
+    Stream(NoiseSocket<TSocket>),
+    /// A QUIC connection and the public key that the peer authenticated with
+    Quic(Box<QuicConnection>, CommsPublicKey),
+}
+
 #[derive(Debug)]
 pub(crate) enum DialerRequest {
     Dial(
@@ -77,6 +85,7 @@ pub struct Dialer<TTransport, TBackoff> {
     peer_manager: Arc<PeerManager>,
     node_identity: Arc<NodeIdentity>,
     transport: TTransport,
+    quic_transport: QuicTransport,
     noise_config: NoiseConfig,
     backoff: Arc<TBackoff>,
     request_rx: Fuse<mpsc::Receiver<DialerRequest>>,
@@ -85,6 +94,7 @@ pub struct Dialer<TTransport, TBackoff> {
     shutdown: Option<ShutdownSignal>,
     pending_dial_requests: HashMap<NodeId, Vec<oneshot::Sender<Result<PeerConnection, ConnectionManagerError>>>>,
     our_supported_protocols: Vec<ProtocolId>,
+    bandwidth_limits: BandwidthLimits,
 }
 
 impl<TTransport, TBackoff> Dialer<TTransport, TBackoff>
@@ -111,6 +121,7 @@ where
             node_identity,
             peer_manager,
             transport,
+            quic_transport: QuicTransport::new(),
             noise_config,
             backoff: Arc::new(backoff),
             request_rx: request_rx.fuse(),
@@ -119,6 +130,7 @@ where
             shutdown: Some(shutdown),
             pending_dial_requests: Default::default(),
             our_supported_protocols: Vec::new(),
+            bandwidth_limits: BandwidthLimits::unlimited(),
         }
     }
 
@@ -128,6 +140,12 @@ where
         self
     }
 
+    /// Set the bandwidth limits applied to all connections
+    pub fn set_bandwidth_limits(&mut self, bandwidth_limits: BandwidthLimits) -> &mut Self {
+        self.bandwidth_limits = bandwidth_limits;
+        self
+    }
+
     pub async fn run(mut self) {
         let mut pending_dials = FuturesUnordered::new();
         let mut shutdown = self
@@ -268,6 +286,7 @@ where
         }
 
         let transport = self.transport.clone();
+        let quic_transport = self.quic_transport.clone();
         let dial_cancel = Shutdown::new();
         let cancel_signal = dial_cancel.to_signal();
         self.cancel_signals.insert(peer.node_id.clone(), dial_cancel);
@@ -280,20 +299,28 @@ where
         let peer_manager = self.peer_manager.clone();
         let conn_man_notifier = self.conn_man_notifier.clone();
         let supported_protocols = self.our_supported_protocols.clone();
+        let bandwidth_limits = self.bandwidth_limits.clone();
         let user_agent = self.config.user_agent.clone();
         let noise_config = self.noise_config.clone();
         let allow_test_addresses = self.config.allow_test_addresses;
 
         let dial_fut = async move {
-            let (dial_state, dial_result) =
-                Self::dial_peer_with_retry(dial_state, noise_config, transport, backoff, max_attempts).await;
+            let (dial_state, dial_result) = Self::dial_peer_with_retry(
+                dial_state,
+                noise_config,
+                transport,
+                quic_transport,
+                backoff,
+                max_attempts,
+            )
+            .await;
 
             let cancel_signal = dial_state.get_cancel_signal();
 
             match dial_result {
-                Ok((socket, addr)) => {
+                Ok((connection, addr)) => {
                     let authenticated_public_key =
-                        match Self::check_authenticated_public_key(&socket, &dial_state.peer.public_key) {
+                        match Self::check_authenticated_public_key(&connection, &dial_state.peer.public_key) {
                             Ok(pk) => pk,
                             Err(err) => {
                                 return (dial_state, Err(err));
@@ -303,11 +330,12 @@ where
                     let result = Self::perform_socket_upgrade_procedure(
                         peer_manager,
                         node_identity,
-                        socket,
+                        connection,
                         addr,
                         authenticated_public_key,
                         conn_man_notifier,
                         supported_protocols,
+                        bandwidth_limits,
                         user_agent,
                         allow_test_addresses,
                         cancel_signal,
@@ -324,13 +352,16 @@ where
     }
 
     fn check_authenticated_public_key(
-        socket: &NoiseSocket<TTransport::Output>,
+        connection: &DialedConnection<TTransport::Output>,
         expected_public_key: &CommsPublicKey,
     ) -> Result<CommsPublicKey, ConnectionManagerError>
     {
-        let authenticated_public_key = socket
-            .get_remote_public_key()
-            .ok_or_else(|| ConnectionManagerError::InvalidStaticPublicKey)?;
+        let authenticated_public_key = match connection {
+            DialedConnection::Stream(socket) => socket
+                .get_remote_public_key()
+                .ok_or_else(|| ConnectionManagerError::InvalidStaticPublicKey)?,
+            DialedConnection::Quic(_, public_key) => public_key.clone(),
+        };
 
         if &authenticated_public_key != expected_public_key {
             return Err(ConnectionManagerError::DialedPublicKeyMismatch);
@@ -343,21 +374,75 @@ where
     async fn perform_socket_upgrade_procedure(
         peer_manager: Arc<PeerManager>,
         node_identity: Arc<NodeIdentity>,
-        socket: NoiseSocket<TTransport::Output>,
+        connection: DialedConnection<TTransport::Output>,
         dialed_addr: Multiaddr,
         authenticated_public_key: CommsPublicKey,
         conn_man_notifier: mpsc::Sender<ConnectionManagerEvent>,
         our_supported_protocols: Vec<ProtocolId>,
+        bandwidth_limits: BandwidthLimits,
         user_agent: String,
         allow_test_addresses: bool,
         cancel_signal: ShutdownSignal,
     ) -> Result<PeerConnection, ConnectionManagerError>
     {
-        static CONNECTION_DIRECTION: ConnectionDirection = ConnectionDirection::Outbound;
+        match connection {
+            DialedConnection::Stream(socket) => {
+                let muxer =
+                    Yamux::upgrade_connection_with_limits(socket, ConnectionDirection::Outbound, bandwidth_limits)
+                        .await
+                        .map_err(|err| ConnectionManagerError::YamuxUpgradeFailure(err.to_string()))?;
+
+                Self::establish_peer_connection(
+                    muxer,
+                    peer_manager,
+                    node_identity,
+                    dialed_addr,
+                    authenticated_public_key,
+                    conn_man_notifier,
+                    our_supported_protocols,
+                    user_agent,
+                    allow_test_addresses,
+                    cancel_signal,
+                )
+                .await
+            },
+            DialedConnection::Quic(connection, _) => {
+                let muxer = Quic::upgrade_connection(*connection, bandwidth_limits);
+
+                Self::establish_peer_connection(
+                    muxer,
+                    peer_manager,
+                    node_identity,
+                    dialed_addr,
+                    authenticated_public_key,
+                    conn_man_notifier,
+                    our_supported_protocols,
+                    user_agent,
+                    allow_test_addresses,
+                    cancel_signal,
+                )
+                .await
+            },
+        }
+    }
 
-        let mut muxer = Yamux::upgrade_connection(socket, CONNECTION_DIRECTION)
-            .await
-            .map_err(|err| ConnectionManagerError::YamuxUpgradeFailure(err.to_string()))?;
+    /// Performs the identity exchange on an authenticated, multiplexed outbound connection and creates the
+    /// PeerConnection
+    #[allow(clippy::too_many_arguments)]
+    async fn establish_peer_connection<M: Multiplexer>(
+        mut muxer: M,
+        peer_manager: Arc<PeerManager>,
+        node_identity: Arc<NodeIdentity>,
+        dialed_addr: Multiaddr,
+        authenticated_public_key: CommsPublicKey,
+        conn_man_notifier: mpsc::Sender<ConnectionManagerEvent>,
+        our_supported_protocols: Vec<ProtocolId>,
+        user_agent: String,
+        allow_test_addresses: bool,
+        cancel_signal: ShutdownSignal,
+    ) -> Result<PeerConnection, ConnectionManagerError>
+    {
+        static CONNECTION_DIRECTION: ConnectionDirection = ConnectionDirection::Outbound;
 
         debug!(
             target: LOG_TARGET,
@@ -376,7 +461,7 @@ where
         )
         .await?;
         if cancel_signal.is_terminated() {
-            muxer.get_yamux_control().close().await?;
+            muxer.get_control().close().await?;
             return Err(ConnectionManagerError::DialCancelled);
         }
 
@@ -403,7 +488,7 @@ where
         .await?;
 
         if cancel_signal.is_terminated() {
-            muxer.get_yamux_control().close().await?;
+            muxer.get_control().close().await?;
             return Err(ConnectionManagerError::DialCancelled);
         }
 
@@ -430,6 +515,7 @@ where
         dial_state: DialState,
         noise_config: NoiseConfig,
         transport: TTransport,
+        quic_transport: QuicTransport,
         backoff: Arc<TBackoff>,
         max_attempts: usize,
     ) -> (DialState, DialResult<TTransport::Output>)
@@ -455,10 +541,10 @@ where
             futures::select! {
                 _ = delay => {
                     debug!(target: LOG_TARGET, "[Attempt {}] Connecting to peer '{}'", current_state.num_attempts(), current_state.peer.node_id.short_str());
-                    match Self::dial_peer(current_state, &noise_config, &current_transport).await {
-                        (state, Ok((socket, addr))) => {
+                    match Self::dial_peer(current_state, &noise_config, &current_transport, &quic_transport).await {
+                        (state, Ok((connection, addr))) => {
                             debug!(target: LOG_TARGET, "Dial succeeded for peer '{}' after {} attempt(s)", state.peer.node_id.short_str(), state.num_attempts());
-                            break (state, Ok((socket, addr)));
+                            break (state, Ok((connection, addr)));
                         },
                         // Inflight dial was cancelled
                         (state, Err(ConnectionManagerError::DialCancelled)) => break (state, Err(ConnectionManagerError::DialCancelled)),
@@ -489,10 +575,8 @@ where
         dial_state: DialState,
         noise_config: &NoiseConfig,
         transport: &TTransport,
-    ) -> (
-        DialState,
-        Result<(NoiseSocket<TTransport::Output>, Multiaddr), ConnectionManagerError>,
-    )
+        quic_transport: &QuicTransport,
+    ) -> (DialState, DialResult<TTransport::Output>)
     {
         let mut addr_iter = dial_state.peer.addresses.iter();
         let cancel_signal = dial_state.get_cancel_signal();
@@ -507,6 +591,29 @@ where
                     );
 
                     let dial_fut = async move {
+                        if QuicTransport::is_quic_address(address) {
+                            let mut connection = quic_transport
+                                .dial(address.clone())
+                                .await
+                                .map_err(|err| ConnectionManagerError::TransportError(err.to_string()))?;
+                            debug!(
+                                target: LOG_TARGET,
+                                "QUIC connection established on '{}'. Performing noise authentication", address
+                            );
+
+                            let authenticated_public_key = time::timeout(
+                                Duration::from_secs(30),
+                                common::authenticate_quic_connection(
+                                    &mut connection,
+                                    noise_config,
+                                    ConnectionDirection::Outbound,
+                                ),
+                            )
+                            .await
+                            .map_err(|_| ConnectionManagerError::NoiseProtocolTimeout)??;
+                            return Ok(DialedConnection::Quic(Box::new(connection), authenticated_public_key));
+                        }
+
                         let mut socket = transport
                             .dial(address.clone())
                             .map_err(|err| ConnectionManagerError::TransportError(err.to_string()))?
@@ -528,13 +635,13 @@ where
                         )
                         .await
                         .map_err(|_| ConnectionManagerError::NoiseProtocolTimeout)??;
-                        Result::<_, ConnectionManagerError>::Ok(noise_socket)
+                        Result::<_, ConnectionManagerError>::Ok(DialedConnection::Stream(noise_socket))
                     };
 
                     pin_mut!(dial_fut);
                     let either = future::select(dial_fut, cancel_signal.clone()).await;
                     match either {
-                        Either::Left((Ok(noise_socket), _)) => Ok((noise_socket, address.clone())),
+                        Either::Left((Ok(connection), _)) => Ok((connection, address.clone())),
                         Either::Left((Err(err), _)) => {
                             debug!(
                                 target: LOG_TARGET,
@@ -80,6 +80,8 @@ pub enum ConnectionManagerError {
     WireFormatSendFailed,
     #[error("Noise protocol handshake timed out")]
     NoiseProtocolTimeout,
+    #[error("QUIC connection error: {0}")]
+    QuicConnectionError(String),
 }
 
 impl From<yamux::ConnectionError> for ConnectionManagerError {
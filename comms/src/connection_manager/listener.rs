@@ -32,19 +32,31 @@ use crate::{
     bounded_executor::BoundedExecutor,
     connection_manager::{liveness::LivenessSession, wire_mode::WireMode},
     multiaddr::Multiaddr,
-    multiplexing::Yamux,
+    multiplexing::{BandwidthLimits, Multiplexer, Quic, Yamux},
     noise::NoiseConfig,
-    peer_manager::{NodeIdentity, PeerFeatures},
+    peer_manager::{NodeIdentity, Peer, PeerFeatures},
     protocol::ProtocolId,
     runtime,
-    transports::Transport,
+    transports::{QuicConnection, QuicListener, QuicTransport, Transport},
+    types::CommsPublicKey,
     utils::multiaddr::multiaddr_to_socketaddr,
     PeerManager,
 };
-use futures::{channel::mpsc, future, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, SinkExt, StreamExt};
+use futures::{
+    channel::mpsc,
+    future,
+    future::BoxFuture,
+    AsyncRead,
+    AsyncReadExt,
+    AsyncWrite,
+    AsyncWriteExt,
+    SinkExt,
+    StreamExt,
+};
 use log::*;
 use std::{
     convert::TryInto,
+    io,
     mem,
     sync::{
         atomic::{AtomicUsize, Ordering},
@@ -64,11 +76,13 @@ pub struct PeerListener<TTransport> {
     conn_man_notifier: mpsc::Sender<ConnectionManagerEvent>,
     shutdown_signal: ShutdownSignal,
     transport: TTransport,
+    quic_transport: QuicTransport,
     noise_config: NoiseConfig,
     peer_manager: Arc<PeerManager>,
     node_identity: Arc<NodeIdentity>,
     listening_address: Option<Multiaddr>,
     our_supported_protocols: Vec<ProtocolId>,
+    bandwidth_limits: BandwidthLimits,
     liveness_session_count: Arc<AtomicUsize>,
 }
 
@@ -90,6 +104,7 @@ where
     {
         Self {
             transport,
+            quic_transport: QuicTransport::new(),
             noise_config,
             conn_man_notifier,
             peer_manager,
@@ -97,6 +112,7 @@ where
             shutdown_signal,
             listening_address: None,
             our_supported_protocols: Vec::new(),
+            bandwidth_limits: BandwidthLimits::unlimited(),
             bounded_executor: BoundedExecutor::from_current(config.max_simultaneous_inbound_connects),
             liveness_session_count: Arc::new(AtomicUsize::new(config.liveness_max_sessions)),
             config,
@@ -109,7 +125,18 @@ where
         self
     }
 
+    /// Set the bandwidth limits applied to all connections
+    pub fn set_bandwidth_limits(&mut self, bandwidth_limits: BandwidthLimits) -> &mut Self {
+        self.bandwidth_limits = bandwidth_limits;
+        self
+    }
+
     pub async fn run(mut self) {
+        if QuicTransport::is_quic_address(&self.config.listener_address) {
+            self.run_quic().await;
+            return;
+        }
+
         let mut shutdown_signal = self.shutdown_signal.clone();
 
         match self.listen().await {
@@ -145,6 +172,43 @@ where
         }
     }
 
+    /// Listen for QUIC connections. The wire format byte is not used on QUIC connections, so only comms connections
+    /// are accepted.
+    async fn run_quic(mut self) {
+        let mut shutdown_signal = self.shutdown_signal.clone();
+
+        match self.listen_quic().await {
+            Ok((inbound, address)) => {
+                let inbound = inbound.fuse();
+                futures::pin_mut!(inbound);
+
+                info!(
+                    target: LOG_TARGET,
+                    "Listening for QUIC peer connections on '{}'", address
+                );
+                self.listening_address = Some(address.clone());
+
+                self.send_event(ConnectionManagerEvent::Listening(address)).await;
+
+                loop {
+                    futures::select! {
+                        (connecting, peer_addr) = inbound.select_next_some() => {
+                            self.spawn_quic_listen_task(connecting, peer_addr).await;
+                        },
+                        _ = shutdown_signal => {
+                            info!(target: LOG_TARGET, "PeerListener is shutting down because the shutdown signal was triggered");
+                            break;
+                        },
+                    }
+                }
+            },
+            Err(err) => {
+                warn!(target: LOG_TARGET, "PeerListener was unable to start because '{}'", err);
+                self.send_event(ConnectionManagerEvent::ListenFailed(err)).await;
+            },
+        }
+    }
+
     async fn read_wire_format(socket: &mut TTransport::Output, time_to_first_byte: Duration) -> Option<WireMode> {
         let mut buf = [0u8; 1];
         match time::timeout(time_to_first_byte, socket.read_exact(&mut buf))
@@ -196,10 +260,11 @@ where
     async fn spawn_listen_task(&self, mut socket: TTransport::Output, peer_addr: Multiaddr) {
         let node_identity = self.node_identity.clone();
         let peer_manager = self.peer_manager.clone();
-        let mut conn_man_notifier = self.conn_man_notifier.clone();
+        let conn_man_notifier = self.conn_man_notifier.clone();
         let noise_config = self.noise_config.clone();
         let config = self.config.clone();
         let our_supported_protocols = self.our_supported_protocols.clone();
+        let bandwidth_limits = self.bandwidth_limits.clone();
         let allow_test_addresses = self.config.allow_test_addresses;
         let liveness_session_count = self.liveness_session_count.clone();
         let user_agent = self.config.user_agent.clone();
@@ -217,37 +282,13 @@ where
                         socket,
                         peer_addr,
                         our_supported_protocols,
+                        bandwidth_limits,
                         user_agent,
                         allow_test_addresses,
                     )
                     .await;
 
-                    match result {
-                        Ok(peer_conn) => {
-                            log_if_error!(
-                                target: LOG_TARGET,
-                                conn_man_notifier
-                                    .send(ConnectionManagerEvent::PeerConnected(peer_conn))
-                                    .await,
-                                "Failed to publish event because '{error}'",
-                            );
-                        },
-                        Err(err) => {
-                            debug!(
-                                target: LOG_TARGET,
-                                "[ThisNode={}] Peer connection upgrade failed for peer because '{:?}'",
-                                this_node_id_str,
-                                err
-                            );
-                            log_if_error!(
-                                target: LOG_TARGET,
-                                conn_man_notifier
-                                    .send(ConnectionManagerEvent::PeerInboundConnectFailed(err))
-                                    .await,
-                                "Failed to publish event because '{error}'",
-                            );
-                        },
-                    }
+                    Self::notify_upgrade_result(conn_man_notifier, this_node_id_str, result).await;
                 },
                 Some(WireMode::Liveness) => {
                     if liveness_session_count.load(Ordering::SeqCst) > 0 &&
@@ -281,6 +322,80 @@ where
         self.bounded_executor.spawn(inbound_fut).await;
     }
 
+    async fn spawn_quic_listen_task(
+        &self,
+        connecting: BoxFuture<'static, io::Result<QuicConnection>>,
+        peer_addr: Multiaddr,
+    )
+    {
+        let node_identity = self.node_identity.clone();
+        let peer_manager = self.peer_manager.clone();
+        let conn_man_notifier = self.conn_man_notifier.clone();
+        let noise_config = self.noise_config.clone();
+        let our_supported_protocols = self.our_supported_protocols.clone();
+        let bandwidth_limits = self.bandwidth_limits.clone();
+        let allow_test_addresses = self.config.allow_test_addresses;
+        let user_agent = self.config.user_agent.clone();
+
+        let inbound_fut = async move {
+            let this_node_id_str = node_identity.node_id().short_str();
+            let result = match connecting.await {
+                Ok(connection) => {
+                    Self::perform_quic_upgrade_procedure(
+                        node_identity,
+                        peer_manager,
+                        noise_config,
+                        conn_man_notifier.clone(),
+                        connection,
+                        peer_addr,
+                        our_supported_protocols,
+                        bandwidth_limits,
+                        user_agent,
+                        allow_test_addresses,
+                    )
+                    .await
+                },
+                Err(err) => Err(ConnectionManagerError::QuicConnectionError(err.to_string())),
+            };
+
+            Self::notify_upgrade_result(conn_man_notifier, this_node_id_str, result).await;
+        };
+
+        self.bounded_executor.spawn(inbound_fut).await;
+    }
+
+    async fn notify_upgrade_result(
+        mut conn_man_notifier: mpsc::Sender<ConnectionManagerEvent>,
+        this_node_id_str: String,
+        result: Result<PeerConnection, ConnectionManagerError>,
+    )
+    {
+        match result {
+            Ok(peer_conn) => {
+                log_if_error!(
+                    target: LOG_TARGET,
+                    conn_man_notifier
+                        .send(ConnectionManagerEvent::PeerConnected(peer_conn))
+                        .await,
+                    "Failed to publish event because '{error}'",
+                );
+            },
+            Err(err) => {
+                debug!(
+                    target: LOG_TARGET,
+                    "[ThisNode={}] Peer connection upgrade failed for peer because '{:?}'", this_node_id_str, err
+                );
+                log_if_error!(
+                    target: LOG_TARGET,
+                    conn_man_notifier
+                        .send(ConnectionManagerEvent::PeerInboundConnectFailed(err))
+                        .await,
+                    "Failed to publish event because '{error}'",
+                );
+            },
+        }
+    }
+
     async fn send_event(&mut self, event: ConnectionManagerEvent) {
         log_if_error_fmt!(
             target: LOG_TARGET,
@@ -298,6 +413,7 @@ where
         socket: TTransport::Output,
         peer_addr: Multiaddr,
         our_supported_protocols: Vec<ProtocolId>,
+        bandwidth_limits: BandwidthLimits,
         user_agent: String,
         allow_test_addresses: bool,
     ) -> Result<PeerConnection, ConnectionManagerError>
@@ -322,10 +438,95 @@ where
         // Check if we know the peer and if it is banned
         let known_peer = common::find_unbanned_peer(&peer_manager, &authenticated_public_key).await?;
 
-        let mut muxer = Yamux::upgrade_connection(noise_socket, CONNECTION_DIRECTION)
+        let muxer = Yamux::upgrade_connection_with_limits(noise_socket, CONNECTION_DIRECTION, bandwidth_limits)
             .await
             .map_err(|err| ConnectionManagerError::YamuxUpgradeFailure(err.to_string()))?;
 
+        Self::establish_peer_connection(
+            muxer,
+            node_identity,
+            peer_manager,
+            conn_man_notifier,
+            known_peer,
+            authenticated_public_key,
+            peer_addr,
+            our_supported_protocols,
+            user_agent,
+            allow_test_addresses,
+        )
+        .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn perform_quic_upgrade_procedure(
+        node_identity: Arc<NodeIdentity>,
+        peer_manager: Arc<PeerManager>,
+        noise_config: NoiseConfig,
+        conn_man_notifier: mpsc::Sender<ConnectionManagerEvent>,
+        mut connection: QuicConnection,
+        peer_addr: Multiaddr,
+        our_supported_protocols: Vec<ProtocolId>,
+        bandwidth_limits: BandwidthLimits,
+        user_agent: String,
+        allow_test_addresses: bool,
+    ) -> Result<PeerConnection, ConnectionManagerError>
+    {
+        debug!(
+            target: LOG_TARGET,
+            "Starting noise authentication for QUIC peer at address '{}'", peer_addr
+        );
+
+        let authenticated_public_key = time::timeout(
+            Duration::from_secs(30),
+            common::authenticate_quic_connection(&mut connection, &noise_config, ConnectionDirection::Inbound),
+        )
+        .await
+        .map_err(|_| ConnectionManagerError::NoiseProtocolTimeout)??;
+
+        // Check if we know the peer and if it is banned
+        let known_peer = match common::find_unbanned_peer(&peer_manager, &authenticated_public_key).await {
+            Ok(known_peer) => known_peer,
+            Err(err) => {
+                connection.close();
+                return Err(err);
+            },
+        };
+
+        let muxer = Quic::upgrade_connection(connection, bandwidth_limits);
+
+        Self::establish_peer_connection(
+            muxer,
+            node_identity,
+            peer_manager,
+            conn_man_notifier,
+            known_peer,
+            authenticated_public_key,
+            peer_addr,
+            our_supported_protocols,
+            user_agent,
+            allow_test_addresses,
+        )
+        .await
+    }
+
+    /// Performs the identity exchange on an authenticated, multiplexed inbound connection and creates the
+    /// PeerConnection
+    #[allow(clippy::too_many_arguments)]
+    async fn establish_peer_connection<M: Multiplexer>(
+        mut muxer: M,
+        node_identity: Arc<NodeIdentity>,
+        peer_manager: Arc<PeerManager>,
+        conn_man_notifier: mpsc::Sender<ConnectionManagerEvent>,
+        known_peer: Option<Peer>,
+        authenticated_public_key: CommsPublicKey,
+        peer_addr: Multiaddr,
+        our_supported_protocols: Vec<ProtocolId>,
+        user_agent: String,
+        allow_test_addresses: bool,
+    ) -> Result<PeerConnection, ConnectionManagerError>
+    {
+        static CONNECTION_DIRECTION: ConnectionDirection = ConnectionDirection::Inbound;
+
         trace!(
             target: LOG_TARGET,
             "Starting peer identity exchange for peer with public key '{}'",
@@ -379,6 +580,18 @@ where
         )
     }
 
+    async fn listen_quic(&mut self) -> Result<(QuicListener, Multiaddr), ConnectionManagerError> {
+        let listener_address = mem::replace(&mut self.config.listener_address, Multiaddr::empty());
+        debug!(
+            target: LOG_TARGET,
+            "Attempting to listen for QUIC connections on {}", listener_address
+        );
+        self.quic_transport
+            .listen(listener_address)
+            .await
+            .map_err(|err| ConnectionManagerError::TransportError(err.to_string()))
+    }
+
     async fn listen(&mut self) -> Result<(TTransport::Listener, Multiaddr), ConnectionManagerError> {
         let listener_address = mem::replace(&mut self.config.listener_address, Multiaddr::empty());
         debug!(target: LOG_TARGET, "Attempting to listen on {}", listener_address);
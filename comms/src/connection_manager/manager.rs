@@ -29,7 +29,7 @@ use super::{
 };
 use crate::{
     backoff::Backoff,
-    multiplexing::Substream,
+    multiplexing::{BandwidthLimits, Substream},
     noise::NoiseConfig,
     peer_manager::{NodeId, NodeIdentity},
     protocol::{ProtocolEvent, ProtocolId, Protocols},
@@ -96,7 +96,8 @@ impl fmt::Display for ConnectionManagerEvent {
 
 #[derive(Debug, Clone)]
 pub struct ConnectionManagerConfig {
-    /// The address to listen on for incoming connections. This address must be supported by the transport.
+    /// The address to listen on for incoming connections. This address must be supported by the transport, or be a
+    /// QUIC address (`/ip4/.../udp/.../quic`) in which case the built-in QUIC transport is used.
     /// Default: DEFAULT_LISTENER_ADDRESS constant
     pub listener_address: Multiaddr,
     /// The number of dial attempts to make before giving up. Default: 3
@@ -115,6 +116,12 @@ pub struct ConnectionManagerConfig {
     pub liveness_cidr_allowlist: Vec<cidr::AnyIpCidr>,
     /// The user agent string for this node
     pub user_agent: String,
+    /// The maximum number of bytes per second that may be sent and received across all peer connections. Default: None
+    /// (unlimited)
+    pub max_bandwidth_bytes_per_sec: Option<u64>,
+    /// The maximum number of bytes per second that may be sent and received on a single peer connection. Default: None
+    /// (unlimited)
+    pub max_peer_bandwidth_bytes_per_sec: Option<u64>,
 }
 
 impl Default for ConnectionManagerConfig {
@@ -134,6 +141,8 @@ impl Default for ConnectionManagerConfig {
             time_to_first_byte: Duration::from_secs(7),
             liveness_cidr_allowlist: vec![cidr::AnyIpCidr::V4("127.0.0.1/32".parse().unwrap())],
             user_agent: Default::default(),
+            max_bandwidth_bytes_per_sec: None,
+            max_peer_bandwidth_bytes_per_sec: None,
         }
     }
 }
@@ -176,7 +185,13 @@ where
 
         let (dialer_tx, dialer_rx) = mpsc::channel(DIALER_REQUEST_CHANNEL_SIZE);
 
-        let listener = PeerListener::new(
+        // The global limit is shared between the listener and dialer
+        let bandwidth_limits = BandwidthLimits::new(
+            config.max_bandwidth_bytes_per_sec,
+            config.max_peer_bandwidth_bytes_per_sec,
+        );
+
+        let mut listener = PeerListener::new(
             config.clone(),
             transport.clone(),
             noise_config.clone(),
@@ -185,8 +200,9 @@ where
             Arc::clone(&node_identity),
             shutdown_signal.clone(),
         );
+        listener.set_bandwidth_limits(bandwidth_limits.clone());
 
-        let dialer = Dialer::new(
+        let mut dialer = Dialer::new(
             config,
             node_identity,
             peer_manager.clone(),
@@ -197,6 +213,7 @@ where
             internal_event_tx,
             shutdown_signal.clone(),
         );
+        dialer.set_bandwidth_limits(bandwidth_limits);
 
         Self {
             shutdown_signal: Some(shutdown_signal),
@@ -31,7 +31,15 @@ use super::{
 use crate::{
     framing,
     framing::CanonicalFraming,
-    multiplexing::{Control, IncomingSubstreams, Substream, SubstreamCounter, Yamux},
+    multiplexing::{
+        BandwidthMeter,
+        BandwidthStats,
+        Control,
+        IncomingSubstreams,
+        Multiplexer,
+        Substream,
+        SubstreamCounter,
+    },
     peer_manager::{NodeId, PeerFeatures},
     protocol::{ProtocolId, ProtocolNegotiation},
     runtime,
@@ -58,8 +66,8 @@ const PEER_REQUEST_BUFFER_SIZE: usize = 64;
 static ID_COUNTER: AtomicUsize = AtomicUsize::new(0);
 
 #[allow(clippy::too_many_arguments)]
-pub fn create(
-    connection: Yamux,
+pub fn create<M: Multiplexer>(
+    connection: M,
     peer_addr: Multiaddr,
     peer_node_id: NodeId,
     peer_features: PeerFeatures,
@@ -77,6 +85,7 @@ pub fn create(
     let (peer_tx, peer_rx) = mpsc::channel(PEER_REQUEST_BUFFER_SIZE);
     let id = ID_COUNTER.fetch_add(1, Ordering::Relaxed); // Monotonic
     let substream_counter = connection.substream_counter();
+    let bandwidth_meter = connection.bandwidth_meter();
     let peer_conn = PeerConnection::new(
         id,
         peer_tx,
@@ -85,6 +94,7 @@ pub fn create(
         peer_addr,
         direction,
         substream_counter,
+        bandwidth_meter,
     );
     let peer_actor = PeerConnectionActor::new(
         id,
@@ -125,9 +135,11 @@ pub struct PeerConnection {
     direction: ConnectionDirection,
     started_at: Instant,
     substream_counter: SubstreamCounter,
+    bandwidth_meter: BandwidthMeter,
 }
 
 impl PeerConnection {
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         id: ConnectionId,
         request_tx: mpsc::Sender<PeerConnectionRequest>,
@@ -136,6 +148,7 @@ impl PeerConnection {
         address: Multiaddr,
         direction: ConnectionDirection,
         substream_counter: SubstreamCounter,
+        bandwidth_meter: BandwidthMeter,
     ) -> Self
     {
         Self {
@@ -147,6 +160,7 @@ impl PeerConnection {
             direction,
             started_at: Instant::now(),
             substream_counter,
+            bandwidth_meter,
         }
     }
 
@@ -182,6 +196,16 @@ impl PeerConnection {
         self.substream_counter.get()
     }
 
+    /// Returns the total bytes sent and received on this connection
+    pub fn bandwidth_stats(&self) -> BandwidthStats {
+        self.bandwidth_meter.total()
+    }
+
+    /// Returns the bytes sent and received on this connection for each negotiated protocol
+    pub fn protocol_bandwidth_stats(&self) -> Vec<(ProtocolId, BandwidthStats)> {
+        self.bandwidth_meter.protocols()
+    }
+
     pub async fn open_substream(
         &mut self,
         protocol_id: &ProtocolId,
@@ -286,11 +310,11 @@ struct PeerConnectionActor {
 
 impl PeerConnectionActor {
     #[allow(clippy::too_many_arguments)]
-    fn new(
+    fn new<M: Multiplexer>(
         id: ConnectionId,
         peer_node_id: NodeId,
         direction: ConnectionDirection,
-        connection: Yamux,
+        connection: M,
         request_rx: mpsc::Receiver<PeerConnectionRequest>,
         event_notifier: mpsc::Sender<ConnectionManagerEvent>,
         our_supported_protocols: Vec<ProtocolId>,
@@ -301,8 +325,8 @@ impl PeerConnectionActor {
             id,
             peer_node_id,
             direction,
-            control: connection.get_yamux_control(),
-            incoming_substreams: connection.incoming().fuse(),
+            control: connection.get_control(),
+            incoming_substreams: connection.into_incoming().fuse(),
             substream_shutdown: None,
             request_rx: request_rx.fuse(),
             event_notifier,
@@ -373,6 +397,7 @@ impl PeerConnectionActor {
         let selected_protocol = ProtocolNegotiation::new(&mut stream)
             .negotiate_protocol_inbound(&self.our_supported_protocols)
             .await?;
+        stream.set_protocol(&selected_protocol);
 
         self.notify_event(ConnectionManagerEvent::NewInboundSubstream(
             Box::new(self.peer_node_id.clone()),
@@ -405,6 +430,7 @@ impl PeerConnectionActor {
         } else {
             negotiation.negotiate_protocol_outbound(&[protocol]).await?
         };
+        stream.set_protocol(&selected_protocol);
 
         Ok(NegotiatedSubstream::new(selected_protocol, stream))
     }
@@ -189,6 +189,113 @@ async fn smoke() {
     timeout(Duration::from_secs(5), dialer_fut).await.unwrap().unwrap();
 }
 
+#[runtime::test_basic]
+async fn quic_smoke() {
+    let rt_handle = runtime::current();
+    // Same as the smoke test, except that the listener listens on a QUIC address over localhost UDP and the dialer
+    // dials it. Substreams are QUIC streams instead of yamux substreams.
+    let (event_tx, mut event_rx) = mpsc::channel(10);
+    let mut shutdown = Shutdown::new();
+
+    let node_identity1 = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+    let noise_config1 = NoiseConfig::new(node_identity1.clone());
+    let expected_proto = ProtocolId::from_static(b"/tari/test-proto");
+    let supported_protocols = vec![expected_proto.clone()];
+    let peer_manager1 = build_peer_manager();
+    let mut listener = PeerListener::new(
+        ConnectionManagerConfig {
+            listener_address: "/ip4/127.0.0.1/udp/0/quic".parse().unwrap(),
+            ..Default::default()
+        },
+        MemoryTransport,
+        noise_config1,
+        event_tx.clone(),
+        peer_manager1.clone(),
+        node_identity1.clone(),
+        shutdown.to_signal(),
+    );
+    listener.set_supported_protocols(supported_protocols.clone());
+
+    let listener_fut = rt_handle.spawn(listener.run());
+
+    let node_identity2 = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+    let noise_config2 = NoiseConfig::new(node_identity2.clone());
+    let (mut request_tx, request_rx) = mpsc::channel(1);
+    let peer_manager2 = build_peer_manager();
+    let mut dialer = Dialer::new(
+        ConnectionManagerConfig::default(),
+        node_identity2.clone(),
+        peer_manager2.clone(),
+        MemoryTransport,
+        noise_config2,
+        ConstantBackoff::new(Duration::from_millis(100)),
+        request_rx,
+        event_tx,
+        shutdown.to_signal(),
+    );
+    dialer.set_supported_protocols(supported_protocols.clone());
+
+    let dialer_fut = rt_handle.spawn(dialer.run());
+
+    // Get the listening address of the peer
+    let listen_event = event_rx.next().await.unwrap();
+    unpack_enum!(ConnectionManagerEvent::Listening(address) = listen_event);
+    assert_eq!(address.iter().last(), Some(Protocol::Quic));
+
+    let mut peer = node_identity1.to_peer();
+    peer.addresses = vec![address].into();
+    peer.set_id_for_test(1);
+
+    let (reply_tx, reply_rx) = oneshot::channel();
+    request_tx
+        .send(DialerRequest::Dial(Box::new(peer), reply_tx))
+        .await
+        .unwrap();
+
+    let mut outbound_peer_conn = reply_rx.await.unwrap().unwrap();
+
+    // Open a substream
+    {
+        let mut out_stream = outbound_peer_conn
+            .open_substream(&ProtocolId::from_static(b"/tari/test-proto"))
+            .await
+            .unwrap();
+        out_stream.stream.write_all(b"HELLO").await.unwrap();
+        out_stream.stream.flush().await.unwrap();
+    }
+
+    // Read PeerConnected events - we don't know which connection is which
+    unpack_enum!(ConnectionManagerEvent::PeerConnected(conn1) = event_rx.next().await.unwrap());
+    unpack_enum!(ConnectionManagerEvent::PeerConnected(_conn2) = event_rx.next().await.unwrap());
+
+    // Next event should be a NewInboundSubstream has been received
+    let listen_event = event_rx.next().await.unwrap();
+    {
+        unpack_enum!(ConnectionManagerEvent::NewInboundSubstream(node_id, proto, in_stream) = listen_event);
+        assert_eq!(&*node_id, node_identity2.node_id());
+        assert_eq!(proto, expected_proto);
+
+        let mut buf = [0u8; 5];
+        in_stream.read_exact(&mut buf).await.unwrap();
+        assert_eq!(buf, *b"HELLO");
+    }
+
+    assert!(outbound_peer_conn.bandwidth_stats().bytes_out > 0);
+
+    conn1.disconnect().await.unwrap();
+
+    shutdown.trigger().unwrap();
+
+    let peer2 = peer_manager1.find_by_node_id(node_identity2.node_id()).await.unwrap();
+    let peer1 = peer_manager2.find_by_node_id(node_identity1.node_id()).await.unwrap();
+
+    assert_eq!(&peer1.public_key, node_identity1.public_key());
+    assert_eq!(&peer2.public_key, node_identity2.public_key());
+
+    timeout(Duration::from_secs(5), listener_fut).await.unwrap().unwrap();
+    timeout(Duration::from_secs(5), dialer_fut).await.unwrap().unwrap();
+}
+
 #[runtime::test_basic]
 async fn banned() {
     let rt_handle = runtime::current();
@@ -42,6 +42,9 @@ pub struct ConnectivityConfig {
     /// The length of time to wait before disconnecting a connection that failed tie breaking.
     /// Default: 1s
     pub connection_tie_break_linger: Duration,
+    /// The length of time to ban a peer once its reputation falls below the ban threshold.
+    /// Default: 6 hours
+    pub reputation_ban_duration: Duration,
 }
 
 impl Default for ConnectivityConfig {
@@ -53,6 +56,7 @@ impl Default for ConnectivityConfig {
             is_connection_reaping_enabled: true,
             max_failures_mark_offline: 1,
             connection_tie_break_linger: Duration::from_secs(2),
+            reputation_ban_duration: Duration::from_secs(6 * 60 * 60),
         }
     }
 }
@@ -29,7 +29,7 @@ use super::{
 };
 use crate::{
     connection_manager::{ConnectionDirection, ConnectionManagerError, ConnectionManagerRequester},
-    peer_manager::NodeId,
+    peer_manager::{NodeId, ReputationEvent, ReputationScore},
     runtime::task,
     utils::datetime::format_duration,
     ConnectionManagerEvent,
@@ -84,6 +84,7 @@ impl ConnectivityManager {
             peer_manager: self.peer_manager.clone(),
             event_tx: self.event_tx,
             connection_stats: HashMap::new(),
+            peer_reputations: HashMap::new(),
             node_identity: self.node_identity,
 
             managed_peers: Vec::new(),
@@ -136,6 +137,7 @@ pub struct ConnectivityManagerActor {
     peer_manager: Arc<PeerManager>,
     event_tx: broadcast::Sender<Arc<ConnectivityEvent>>,
     connection_stats: HashMap<NodeId, PeerConnectionStats>,
+    peer_reputations: HashMap<NodeId, ReputationScore>,
 
     managed_peers: Vec<NodeId>,
     pool: ConnectionPool,
@@ -264,6 +266,11 @@ impl ConnectivityManagerActor {
                     error!(target: LOG_TARGET, "Error when banning peer: {:?}", err);
                 }
             },
+            ReportPeer(node_id, event) => {
+                if let Err(err) = self.report_peer(&node_id, event).await {
+                    error!(target: LOG_TARGET, "Error when reporting peer: {:?}", err);
+                }
+            },
             GetActiveConnections(reply) => {
                 let _ = reply.send(
                     self.pool
@@ -273,6 +280,15 @@ impl ConnectivityManagerActor {
                         .collect(),
                 );
             },
+            GetBandwidthStats(reply) => {
+                let _ = reply.send(
+                    self.pool
+                        .filter_connection_states(|s| s.is_connected())
+                        .into_iter()
+                        .map(Into::into)
+                        .collect(),
+                );
+            },
         }
     }
 
@@ -420,7 +436,7 @@ impl ConnectivityManagerActor {
             self.pool.count_connected_nodes()
         );
 
-        let conns = selection.select(&self.pool);
+        let conns = selection.select(&self.pool, &self.peer_reputations);
         debug!(target: LOG_TARGET, "Selected {} connections(s)", conns.len());
 
         Ok(conns.into_iter().cloned().collect())
@@ -606,6 +622,7 @@ impl ConnectivityManagerActor {
         let (node_id, mut new_status, connection) = match event {
             PeerDisconnected(node_id) => {
                 self.connection_stats.remove(&node_id);
+                self.peer_reputations.remove(&node_id);
                 (&**node_id, ConnectionStatus::Disconnected, None)
             },
             PeerConnected(conn) => (conn.peer_node_id(), ConnectionStatus::Connected, Some(conn.clone())),
@@ -646,6 +663,7 @@ impl ConnectivityManagerActor {
         match (old_status, new_status) {
             (_, Connected) => {
                 self.mark_peer_succeeded(node_id.clone());
+                self.load_peer_reputation(&node_id).await;
                 match self.pool.get_connection(&node_id).cloned() {
                     Some(conn) => {
                         self.publish_event(ConnectivityEvent::PeerConnected(conn));
@@ -802,6 +820,48 @@ impl ConnectivityManagerActor {
         let _ = self.event_tx.send(Arc::new(event));
     }
 
+    /// Caches the reputation of a newly connected peer so that it can be used for connection selection
+    async fn load_peer_reputation(&mut self, node_id: &NodeId) {
+        match self.peer_manager.find_by_node_id(node_id).await {
+            Ok(peer) => {
+                self.peer_reputations.insert(node_id.clone(), peer.reputation().score());
+            },
+            Err(err) => {
+                debug!(
+                    target: LOG_TARGET,
+                    "Unable to load reputation for peer '{}' because '{:?}'", node_id, err
+                );
+            },
+        }
+    }
+
+    async fn report_peer(&mut self, node_id: &NodeId, event: ReputationEvent) -> Result<(), ConnectivityError> {
+        let reputation = self.peer_manager.apply_reputation_event(node_id, event).await?;
+        debug!(
+            target: LOG_TARGET,
+            "Peer '{}' reported for {}. Reputation is now {}",
+            node_id.short_str(),
+            event,
+            reputation
+        );
+
+        if reputation.should_ban() {
+            self.ban_peer(
+                node_id,
+                self.config.reputation_ban_duration,
+                format!("Reputation fell to {} after {}", reputation, event),
+            )
+            .await?;
+            return Ok(());
+        }
+
+        if self.pool.get(node_id).is_some() {
+            self.peer_reputations.insert(node_id.clone(), reputation.score());
+        }
+
+        Ok(())
+    }
+
     async fn ban_peer(
         &mut self,
         node_id: &NodeId,
@@ -823,6 +883,7 @@ impl ConnectivityManagerActor {
         }
 
         self.peer_manager.ban_peer_by_node_id(node_id, duration, reason).await?;
+        self.peer_reputations.remove(node_id);
 
         self.publish_event(ConnectivityEvent::PeerBanned(node_id.clone()));
 
@@ -36,7 +36,13 @@ pub use manager::ConnectivityStatus;
 
 mod requester;
 pub(crate) use requester::ConnectivityRequest;
-pub use requester::{ConnectivityEvent, ConnectivityEventRx, ConnectivityEventTx, ConnectivityRequester};
+pub use requester::{
+    ConnectivityEvent,
+    ConnectivityEventRx,
+    ConnectivityEventTx,
+    ConnectivityRequester,
+    PeerBandwidthStats,
+};
 
 mod selection;
 pub use selection::ConnectivitySelection;
@@ -28,7 +28,9 @@ use super::{
 };
 use crate::{
     connection_manager::{ConnectionDirection, ConnectionManagerError},
-    peer_manager::NodeId,
+    multiplexing::BandwidthStats,
+    peer_manager::{NodeId, ReputationEvent},
+    protocol::ProtocolId,
     PeerConnection,
 };
 use futures::{
@@ -87,6 +89,24 @@ impl fmt::Display for ConnectivityEvent {
     }
 }
 
+/// Bytes sent and received on an active peer connection
+#[derive(Debug, Clone)]
+pub struct PeerBandwidthStats {
+    pub node_id: NodeId,
+    pub total: BandwidthStats,
+    pub protocols: Vec<(ProtocolId, BandwidthStats)>,
+}
+
+impl From<&PeerConnection> for PeerBandwidthStats {
+    fn from(conn: &PeerConnection) -> Self {
+        Self {
+            node_id: conn.peer_node_id().clone(),
+            total: conn.bandwidth_stats(),
+            protocols: conn.protocol_bandwidth_stats(),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum ConnectivityRequest {
     WaitStarted(oneshot::Sender<()>),
@@ -101,7 +121,9 @@ pub enum ConnectivityRequest {
     GetConnection(NodeId, oneshot::Sender<Option<PeerConnection>>),
     GetAllConnectionStates(oneshot::Sender<Vec<PeerConnectionState>>),
     GetActiveConnections(oneshot::Sender<Vec<PeerConnection>>),
+    GetBandwidthStats(oneshot::Sender<Vec<PeerBandwidthStats>>),
     BanPeer(NodeId, Duration, String),
+    ReportPeer(NodeId, ReputationEvent),
 }
 
 #[derive(Debug, Clone)]
@@ -214,6 +236,16 @@ impl ConnectivityRequester {
         reply_rx.await.map_err(|_| ConnectivityError::ActorResponseCancelled)
     }
 
+    /// Returns the bytes sent and received, in total and per protocol, for each active peer connection
+    pub async fn get_bandwidth_stats(&mut self) -> Result<Vec<PeerBandwidthStats>, ConnectivityError> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.sender
+            .send(ConnectivityRequest::GetBandwidthStats(reply_tx))
+            .await
+            .map_err(|_| ConnectivityError::ActorDisconnected)?;
+        reply_rx.await.map_err(|_| ConnectivityError::ActorResponseCancelled)
+    }
+
     pub async fn ban_peer_until(
         &mut self,
         node_id: NodeId,
@@ -233,6 +265,16 @@ impl ConnectivityRequester {
             .await
     }
 
+    /// Report a peer's behaviour. The peer's reputation is adjusted according to the event and the peer is banned if
+    /// its reputation falls below the ban threshold.
+    pub async fn report_peer(&mut self, node_id: NodeId, event: ReputationEvent) -> Result<(), ConnectivityError> {
+        self.sender
+            .send(ConnectivityRequest::ReportPeer(node_id, event))
+            .await
+            .map_err(|_| ConnectivityError::ActorDisconnected)?;
+        Ok(())
+    }
+
     pub async fn wait_started(&mut self) -> Result<(), ConnectivityError> {
         let (reply_tx, reply_rx) = oneshot::channel();
         self.sender
@@ -21,9 +21,17 @@
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use super::connection_pool::ConnectionPool;
-use crate::{connectivity::connection_pool::ConnectionStatus, peer_manager::NodeId, PeerConnection};
+use crate::{
+    connectivity::connection_pool::ConnectionStatus,
+    peer_manager::{is_poor_score, NodeId, ReputationScore},
+    PeerConnection,
+};
 use rand::{rngs::OsRng, seq::SliceRandom};
-use std::{fmt, fmt::Display};
+use std::{collections::HashMap, fmt, fmt::Display};
+
+/// Reputation scores of connected peers, keyed by NodeId. Peers that are not present are treated as having the
+/// initial reputation.
+pub type PeerReputations = HashMap<NodeId, ReputationScore>;
 
 #[derive(Debug, Clone)]
 pub struct ConnectivitySelection {
@@ -61,14 +69,19 @@ impl ConnectivitySelection {
         }
     }
 
-    /// Select peers from the pool according to the ConnectivitySelection
-    pub fn select<'a>(&self, pool: &'a ConnectionPool) -> Vec<&'a PeerConnection> {
+    /// Select peers from the pool according to the ConnectivitySelection. Peers with a poor reputation are ordered
+    /// after all other peers, and so are only selected if there are not enough other peers to satisfy the selection.
+    pub fn select<'a>(&self, pool: &'a ConnectionPool, reputations: &PeerReputations) -> Vec<&'a PeerConnection> {
         use SelectionMode::*;
         match &self.selection_mode {
-            AllNodes => select_connected_nodes(pool, &self.excluded_peers),
-            RandomNodes(n) => select_random_nodes(pool, *n, &self.excluded_peers),
+            AllNodes => {
+                let mut connections = select_connected_nodes(pool, &self.excluded_peers);
+                connections.sort_by_key(|conn| is_poor_peer(reputations, conn.peer_node_id()));
+                connections
+            },
+            RandomNodes(n) => select_random_nodes(pool, *n, &self.excluded_peers, reputations),
             ClosestTo(dest_node_id, n) => {
-                let mut connections = select_closest(pool, dest_node_id, &self.excluded_peers);
+                let mut connections = select_closest(pool, dest_node_id, &self.excluded_peers, reputations);
                 connections.truncate(*n);
                 connections.to_vec()
             },
@@ -88,21 +101,46 @@ pub fn select_connected_nodes<'a>(pool: &'a ConnectionPool, exclude: &[NodeId])
     })
 }
 
-pub fn select_closest<'a>(pool: &'a ConnectionPool, node_id: &NodeId, exclude: &[NodeId]) -> Vec<&'a PeerConnection> {
+pub fn select_closest<'a>(
+    pool: &'a ConnectionPool,
+    node_id: &NodeId,
+    exclude: &[NodeId],
+    reputations: &PeerReputations,
+) -> Vec<&'a PeerConnection>
+{
     let mut nodes = select_connected_nodes(pool, exclude);
 
     nodes.sort_by(|a, b| {
         let dist_a = a.peer_node_id().distance(node_id);
         let dist_b = b.peer_node_id().distance(node_id);
-        dist_a.cmp(&dist_b)
+        is_poor_peer(reputations, a.peer_node_id())
+            .cmp(&is_poor_peer(reputations, b.peer_node_id()))
+            .then_with(|| dist_a.cmp(&dist_b))
     });
 
     nodes
 }
 
-pub fn select_random_nodes<'a>(pool: &'a ConnectionPool, n: usize, exclude: &[NodeId]) -> Vec<&'a PeerConnection> {
-    let nodes = select_connected_nodes(pool, exclude);
-    nodes.choose_multiple(&mut OsRng, n).cloned().collect()
+pub fn select_random_nodes<'a>(
+    pool: &'a ConnectionPool,
+    n: usize,
+    exclude: &[NodeId],
+    reputations: &PeerReputations,
+) -> Vec<&'a PeerConnection>
+{
+    let mut nodes = select_connected_nodes(pool, exclude);
+    nodes.shuffle(&mut OsRng);
+    // Stable sort keeps the shuffled order while preferring peers that do not have a poor reputation
+    nodes.sort_by_key(|conn| is_poor_peer(reputations, conn.peer_node_id()));
+    nodes.truncate(n);
+    nodes
+}
+
+fn is_poor_peer(reputations: &PeerReputations, node_id: &NodeId) -> bool {
+    reputations
+        .get(node_id)
+        .map(|score| is_poor_score(*score))
+        .unwrap_or(false)
 }
 
 impl Display for ConnectivitySelection {
@@ -154,11 +192,11 @@ mod test {
     #[test]
     fn select_random() {
         let (pool, _receivers) = create_pool_with_connections(10);
-        let conns = select_random_nodes(&pool, 500, &[]);
+        let conns = select_random_nodes(&pool, 500, &[], &Default::default());
         assert_eq!(conns.len(), 10);
 
         let first_node = conns.first().unwrap().peer_node_id().clone();
-        let conns = select_random_nodes(&pool, 10, &[first_node.clone()]);
+        let conns = select_random_nodes(&pool, 10, &[first_node.clone()], &Default::default());
         assert_eq!(conns.len(), 9);
         assert!(conns.iter().all(|c| c.peer_node_id() != &first_node));
     }
@@ -167,7 +205,7 @@ mod test {
     fn select_closest_ordering() {
         let (pool, _receivers) = create_pool_with_connections(10);
         let subject_node_identity = build_node_identity(Default::default());
-        let conns = select_closest(&pool, subject_node_identity.node_id(), &[]);
+        let conns = select_closest(&pool, subject_node_identity.node_id(), &[], &Default::default());
         assert_eq!(conns.len(), 10);
 
         let mut last_dist = NodeDistance::zero();
@@ -182,7 +220,25 @@ mod test {
     fn select_closest_empty() {
         let pool = ConnectionPool::new();
         let node_identity = build_node_identity(Default::default());
-        let conns = select_closest(&pool, node_identity.node_id(), &[]);
+        let conns = select_closest(&pool, node_identity.node_id(), &[], &Default::default());
         assert!(conns.is_empty());
     }
+
+    #[test]
+    fn select_prefers_reputable_peers() {
+        let (pool, _receivers) = create_pool_with_connections(10);
+        let subject_node_identity = build_node_identity(Default::default());
+        let conns = select_closest(&pool, subject_node_identity.node_id(), &[], &Default::default());
+        let poor_node_id = conns[0].peer_node_id().clone();
+        let mut reputations = PeerReputations::new();
+        reputations.insert(poor_node_id.clone(), -10);
+
+        let conns = select_closest(&pool, subject_node_identity.node_id(), &[], &reputations);
+        assert_eq!(conns.len(), 10);
+        assert_eq!(conns.last().unwrap().peer_node_id(), &poor_node_id);
+
+        let conns = select_random_nodes(&pool, 9, &[], &reputations);
+        assert_eq!(conns.len(), 9);
+        assert!(conns.iter().all(|c| c.peer_node_id() != &poor_node_id));
+    }
 }
@@ -28,7 +28,7 @@ use super::{
 };
 use crate::{
     connection_manager::ConnectionManagerError,
-    peer_manager::{Peer, PeerFeatures},
+    peer_manager::{Peer, PeerFeatures, ReputationEvent},
     runtime,
     runtime::task,
     test_utils::{
@@ -348,6 +348,41 @@ async fn ban_peer() {
     assert!(conn.is_none());
 }
 
+#[runtime::test_basic]
+async fn report_peer_bans_peer_below_threshold() {
+    let (mut connectivity, mut event_stream, _node_identity, peer_manager, _cm_mock_state, _shutdown) =
+        setup_connectivity_manager(Default::default());
+    let peer = add_test_peers(&peer_manager, 1).await.pop().unwrap();
+
+    let mut events = collect_stream!(event_stream, take = 1, timeout = Duration::from_secs(10));
+    unpack_enum!(ConnectivityEvent::ConnectivityStateInitialized = &*events.remove(0).unwrap());
+
+    connectivity
+        .report_peer(peer.node_id.clone(), ReputationEvent::InvalidTransaction)
+        .await
+        .unwrap();
+    connectivity.wait_started().await.unwrap();
+    let reported = peer_manager.find_by_node_id(&peer.node_id).await.unwrap();
+    assert!(reported.reputation().is_poor());
+    assert!(!reported.is_banned());
+
+    connectivity
+        .report_peer(peer.node_id.clone(), ReputationEvent::InvalidBlock)
+        .await
+        .unwrap();
+
+    let event = collect_stream!(event_stream, take = 1, timeout = Duration::from_secs(10))
+        .pop()
+        .unwrap()
+        .unwrap();
+    unpack_enum!(ConnectivityEvent::PeerBanned(node_id) = &*event);
+    assert_eq!(node_id, &peer.node_id);
+
+    let peer = peer_manager.find_by_node_id(&peer.node_id).await.unwrap();
+    assert!(peer.is_banned());
+    assert!(!peer.reputation().is_poor());
+}
+
 #[runtime::test_basic]
 async fn peer_selection() {
     let config = ConnectivityConfig {
@@ -44,9 +44,9 @@ pub use common::rate_limit;
 mod consts;
 
 mod multiplexing;
-pub use multiplexing::Substream;
+pub use multiplexing::{BandwidthLimits, BandwidthMeter, BandwidthStats, Substream};
 
-mod noise;
+pub mod noise;
 mod proto;
 mod runtime;
 
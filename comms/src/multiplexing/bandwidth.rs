@@ -0,0 +1,291 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Byte-level bandwidth accounting and limiting for multiplexed peer connections.
+//!
+//! Every [Substream](super::Substream) opened on a connection records the bytes it reads and writes in the
+//! connection's [BandwidthMeter], both in total and against the protocol negotiated for the substream. A
+//! [BandwidthLimits] may be given when upgrading a connection to throttle substreams once a global or per-peer cap is
+//! exceeded.
+
+use crate::protocol::ProtocolId;
+use std::{
+    collections::HashMap,
+    fmt,
+    ops::Add,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+        Mutex,
+        RwLock,
+    },
+    time::{Duration, Instant},
+};
+
+/// A snapshot of the number of bytes sent and received
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BandwidthStats {
+    pub bytes_in: u64,
+    pub bytes_out: u64,
+}
+
+impl BandwidthStats {
+    pub fn new(bytes_in: u64, bytes_out: u64) -> Self {
+        Self { bytes_in, bytes_out }
+    }
+
+    /// The total number of bytes sent and received
+    pub fn total(&self) -> u64 {
+        self.bytes_in.saturating_add(self.bytes_out)
+    }
+}
+
+impl Add for BandwidthStats {
+    type Output = BandwidthStats;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self {
+            bytes_in: self.bytes_in.saturating_add(rhs.bytes_in),
+            bytes_out: self.bytes_out.saturating_add(rhs.bytes_out),
+        }
+    }
+}
+
+impl fmt::Display for BandwidthStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "in: {} bytes, out: {} bytes", self.bytes_in, self.bytes_out)
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct BandwidthCounters {
+    bytes_in: AtomicU64,
+    bytes_out: AtomicU64,
+}
+
+impl BandwidthCounters {
+    pub fn record_in(&self, num_bytes: usize) {
+        self.bytes_in.fetch_add(num_bytes as u64, Ordering::Relaxed);
+    }
+
+    pub fn record_out(&self, num_bytes: usize) {
+        self.bytes_out.fetch_add(num_bytes as u64, Ordering::Relaxed);
+    }
+
+    pub fn stats(&self) -> BandwidthStats {
+        BandwidthStats::new(
+            self.bytes_in.load(Ordering::Relaxed),
+            self.bytes_out.load(Ordering::Relaxed),
+        )
+    }
+}
+
+/// Tracks the bytes sent and received on a connection, in total and per protocol. Cloning this meter is cheap and all
+/// clones share the same counters.
+#[derive(Debug, Clone, Default)]
+pub struct BandwidthMeter {
+    total: Arc<BandwidthCounters>,
+    protocols: Arc<RwLock<HashMap<ProtocolId, Arc<BandwidthCounters>>>>,
+}
+
+impl BandwidthMeter {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Returns the total bytes sent and received on the connection
+    pub fn total(&self) -> BandwidthStats {
+        self.total.stats()
+    }
+
+    /// Returns the bytes sent and received on substreams negotiated for the given protocol
+    pub fn protocol(&self, protocol: &ProtocolId) -> BandwidthStats {
+        self.protocols
+            .read()
+            .unwrap()
+            .get(protocol)
+            .map(|c| c.stats())
+            .unwrap_or_default()
+    }
+
+    /// Returns the bytes sent and received for every protocol that has been negotiated on the connection
+    pub fn protocols(&self) -> Vec<(ProtocolId, BandwidthStats)> {
+        self.protocols
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(p, c)| (p.clone(), c.stats()))
+            .collect()
+    }
+
+    pub(crate) fn total_counters(&self) -> Arc<BandwidthCounters> {
+        self.total.clone()
+    }
+
+    pub(crate) fn protocol_counters(&self, protocol: &ProtocolId) -> Arc<BandwidthCounters> {
+        if let Some(counters) = self.protocols.read().unwrap().get(protocol) {
+            return counters.clone();
+        }
+        self.protocols
+            .write()
+            .unwrap()
+            .entry(protocol.clone())
+            .or_insert_with(Default::default)
+            .clone()
+    }
+}
+
+/// A token bucket that allows `bytes_per_sec` bytes through on average with bursts of up to one second's worth of
+/// bytes. Consumption is never refused; instead the bucket goes into debt and the caller is told how long to wait
+/// before the debt is repaid. Cloning this limiter is cheap and all clones share the same bucket.
+#[derive(Debug, Clone)]
+pub struct BandwidthLimiter {
+    bytes_per_sec: u64,
+    bucket: Arc<Mutex<TokenBucket>>,
+}
+
+#[derive(Debug)]
+struct TokenBucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl BandwidthLimiter {
+    pub fn new(bytes_per_sec: u64) -> Self {
+        assert!(bytes_per_sec > 0, "BandwidthLimiter rate must be greater than zero");
+        Self {
+            bytes_per_sec,
+            bucket: Arc::new(Mutex::new(TokenBucket {
+                tokens: bytes_per_sec as f64,
+                last_refill: Instant::now(),
+            })),
+        }
+    }
+
+    /// Consume `num_bytes` from the bucket, returning the time to wait before any further bytes should be transferred
+    /// or None if the limit has not been exceeded.
+    pub fn consume(&self, num_bytes: usize) -> Option<Duration> {
+        let rate = self.bytes_per_sec as f64;
+        let mut bucket = self.bucket.lock().unwrap();
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+        bucket.last_refill = now;
+        bucket.tokens = (bucket.tokens + elapsed * rate).min(rate) - num_bytes as f64;
+        if bucket.tokens >= 0.0 {
+            return None;
+        }
+        Some(Duration::from_secs_f64(-bucket.tokens / rate))
+    }
+}
+
+/// Global and per-peer bandwidth caps. The global limiter is shared by every connection created with these limits.
+#[derive(Debug, Clone, Default)]
+pub struct BandwidthLimits {
+    global: Option<BandwidthLimiter>,
+    per_peer_bytes_per_sec: Option<u64>,
+}
+
+impl BandwidthLimits {
+    pub fn new(global_bytes_per_sec: Option<u64>, per_peer_bytes_per_sec: Option<u64>) -> Self {
+        Self {
+            global: global_bytes_per_sec.filter(|r| *r > 0).map(BandwidthLimiter::new),
+            per_peer_bytes_per_sec: per_peer_bytes_per_sec.filter(|r| *r > 0),
+        }
+    }
+
+    /// No bandwidth limits
+    pub fn unlimited() -> Self {
+        Default::default()
+    }
+
+    pub fn is_unlimited(&self) -> bool {
+        self.global.is_none() && self.per_peer_bytes_per_sec.is_none()
+    }
+
+    /// Returns a limiter for a single connection. The global limit is shared with all other connections while the
+    /// per-peer limit is specific to the returned limiter.
+    pub(crate) fn connection_limiter(&self) -> ConnectionLimiter {
+        ConnectionLimiter {
+            global: self.global.clone(),
+            peer: self.per_peer_bytes_per_sec.map(BandwidthLimiter::new),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ConnectionLimiter {
+    global: Option<BandwidthLimiter>,
+    peer: Option<BandwidthLimiter>,
+}
+
+impl ConnectionLimiter {
+    /// Consume `num_bytes` from the global and per-peer limits, returning the longest wait required by either
+    pub fn consume(&self, num_bytes: usize) -> Option<Duration> {
+        let global = self.global.as_ref().and_then(|l| l.consume(num_bytes));
+        let peer = self.peer.as_ref().and_then(|l| l.consume(num_bytes));
+        global.into_iter().chain(peer).max()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn meter_per_protocol() {
+        let meter = BandwidthMeter::new();
+        let proto = ProtocolId::from_static(b"/test/1");
+        meter.total_counters().record_in(10);
+        meter.total_counters().record_out(5);
+        meter.protocol_counters(&proto).record_in(10);
+
+        assert_eq!(meter.total(), BandwidthStats::new(10, 5));
+        assert_eq!(meter.protocol(&proto), BandwidthStats::new(10, 0));
+        assert_eq!(
+            meter.protocol(&ProtocolId::from_static(b"/other")),
+            BandwidthStats::default()
+        );
+        assert_eq!(meter.protocols().len(), 1);
+    }
+
+    #[test]
+    fn limiter_goes_into_debt() {
+        let limiter = BandwidthLimiter::new(1000);
+        assert!(limiter.consume(1000).is_none());
+        let wait = limiter.consume(500).unwrap();
+        assert!(wait <= Duration::from_millis(500));
+        assert!(wait > Duration::from_millis(400));
+    }
+
+    #[test]
+    fn connection_limiter_shares_global() {
+        let limits = BandwidthLimits::new(Some(1000), None);
+        let conn1 = limits.connection_limiter();
+        let conn2 = limits.connection_limiter();
+        assert!(conn1.consume(1000).is_none());
+        assert!(conn2.consume(100).is_some());
+        assert!(BandwidthLimits::unlimited()
+            .connection_limiter()
+            .consume(1_000_000)
+            .is_none());
+    }
+}
@@ -20,5 +20,25 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+mod bandwidth;
+pub use bandwidth::{BandwidthLimiter, BandwidthLimits, BandwidthMeter, BandwidthStats};
+
+mod quic;
+pub use self::quic::Quic;
+
 mod yamux;
 pub use self::yamux::{ConnectionError, Control, IncomingSubstreams, Substream, SubstreamCounter, Yamux};
+
+/// A multiplexed connection over which substreams are opened and accepted
+pub trait Multiplexer {
+    /// Returns a control which is used to open substreams and close the connection
+    fn get_control(&self) -> Control;
+    /// Returns a mutable reference to a `Stream` that emits substreams initiated by the remote
+    fn incoming_mut(&mut self) -> &mut IncomingSubstreams;
+    /// Consumes the multiplexer and returns a `Stream` that emits substreams initiated by the remote
+    fn into_incoming(self) -> IncomingSubstreams;
+    /// Returns the counter of active substreams on this connection
+    fn substream_counter(&self) -> SubstreamCounter;
+    /// Returns the bandwidth meter for this connection
+    fn bandwidth_meter(&self) -> BandwidthMeter;
+}
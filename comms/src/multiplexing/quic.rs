@@ -0,0 +1,89 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use super::{
+    bandwidth::{BandwidthLimits, BandwidthMeter},
+    yamux::{spawn_incoming_stream_worker, StreamInner, SubstreamMetering},
+    Control,
+    IncomingSubstreams,
+    Multiplexer,
+    SubstreamCounter,
+};
+use crate::transports::{QuicConnection, QuicStream};
+use futures::{StreamExt, TryStreamExt};
+
+/// Multiplexer for QUIC connections. QUIC streams are natively multiplexed, so this simply exposes the streams of the
+/// connection as substreams.
+pub struct Quic {
+    control: Control,
+    incoming: IncomingSubstreams,
+    substream_counter: SubstreamCounter,
+    bandwidth_meter: BandwidthMeter,
+}
+
+impl Quic {
+    /// Use the given QUIC connection for substreams. Substreams on this connection are throttled once the given
+    /// bandwidth limits are exceeded.
+    pub fn upgrade_connection(connection: QuicConnection, limits: BandwidthLimits) -> Self {
+        let (connection, incoming) = connection.into_parts();
+        let substream_counter = SubstreamCounter::new();
+        let bandwidth_meter = BandwidthMeter::new();
+        let metering = SubstreamMetering::new(bandwidth_meter.clone(), limits.connection_limiter());
+        let control = Control::new_quic(connection, substream_counter.clone(), metering.clone());
+        let incoming = spawn_incoming_stream_worker(
+            incoming
+                .map_ok(|(send, recv)| StreamInner::Quic(QuicStream::new(send, recv)))
+                .boxed(),
+            substream_counter.clone(),
+            metering,
+        );
+
+        Self {
+            control,
+            incoming,
+            substream_counter,
+            bandwidth_meter,
+        }
+    }
+}
+
+impl Multiplexer for Quic {
+    fn get_control(&self) -> Control {
+        self.control.clone()
+    }
+
+    fn incoming_mut(&mut self) -> &mut IncomingSubstreams {
+        &mut self.incoming
+    }
+
+    fn into_incoming(self) -> IncomingSubstreams {
+        self.incoming
+    }
+
+    fn substream_counter(&self) -> SubstreamCounter {
+        self.substream_counter.clone()
+    }
+
+    fn bandwidth_meter(&self) -> BandwidthMeter {
+        self.bandwidth_meter.clone()
+    }
+}
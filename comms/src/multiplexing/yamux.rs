@@ -20,7 +20,16 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use crate::{connection_manager::ConnectionDirection, runtime};
+use crate::{
+    connection_manager::ConnectionDirection,
+    multiplexing::{
+        bandwidth::{BandwidthCounters, BandwidthLimits, BandwidthMeter, BandwidthStats, ConnectionLimiter},
+        Multiplexer,
+    },
+    protocol::ProtocolId,
+    runtime,
+    transports::QuicStream,
+};
 use futures::{
     channel::mpsc,
     future,
@@ -31,14 +40,16 @@ use futures::{
     SinkExt,
     Stream,
     StreamExt,
+    TryStreamExt,
 };
 use log::*;
-use std::{future::Future, io, pin::Pin, sync::Arc, task::Poll};
+use std::{fmt, future::Future, io, pin::Pin, sync::Arc, task::Poll, time::Duration};
 use tari_shutdown::{Shutdown, ShutdownSignal};
+use tokio::time;
 use yamux::Mode;
 
-type IncomingRx = mpsc::Receiver<yamux::Stream>;
-type IncomingTx = mpsc::Sender<yamux::Stream>;
+type IncomingRx = mpsc::Receiver<StreamInner>;
+type IncomingTx = mpsc::Sender<StreamInner>;
 
 // Reexport
 pub use yamux::ConnectionError;
@@ -49,6 +60,7 @@ pub struct Yamux {
     control: Control,
     incoming: IncomingSubstreams,
     substream_counter: SubstreamCounter,
+    bandwidth_meter: BandwidthMeter,
 }
 
 const MAX_BUFFER_SIZE: u32 = 8 * 1024 * 1024; // 8MB
@@ -58,6 +70,19 @@ impl Yamux {
     /// Upgrade the underlying socket to use yamux
     pub async fn upgrade_connection<TSocket>(socket: TSocket, direction: ConnectionDirection) -> io::Result<Self>
     where TSocket: AsyncRead + AsyncWrite + Send + Unpin + 'static {
+        Self::upgrade_connection_with_limits(socket, direction, BandwidthLimits::unlimited()).await
+    }
+
+    /// Upgrade the underlying socket to use yamux. Substreams on this connection are throttled once the given
+    /// bandwidth limits are exceeded.
+    pub async fn upgrade_connection_with_limits<TSocket>(
+        socket: TSocket,
+        direction: ConnectionDirection,
+        limits: BandwidthLimits,
+    ) -> io::Result<Self>
+    where
+        TSocket: AsyncRead + AsyncWrite + Send + Unpin + 'static,
+    {
         let mode = match direction {
             ConnectionDirection::Inbound => Mode::Server,
             ConnectionDirection::Outbound => Mode::Client,
@@ -75,34 +100,24 @@ impl Yamux {
         config.set_receive_window(RECEIVE_WINDOW);
 
         let substream_counter = SubstreamCounter::new();
+        let bandwidth_meter = BandwidthMeter::new();
+        let metering = SubstreamMetering::new(bandwidth_meter.clone(), limits.connection_limiter());
         let connection = yamux::Connection::new(socket, config, mode);
-        let control = Control::new(connection.control(), substream_counter.clone());
-        let incoming = Self::spawn_incoming_stream_worker(connection, substream_counter.clone());
+        let control = Control::new(connection.control(), substream_counter.clone(), metering.clone());
+        let incoming = spawn_incoming_stream_worker(
+            yamux::into_stream(connection).map_ok(StreamInner::Yamux).boxed(),
+            substream_counter.clone(),
+            metering,
+        );
 
         Ok(Self {
             control,
             incoming,
             substream_counter,
+            bandwidth_meter,
         })
     }
 
-    // yamux@0.4 requires the incoming substream stream be polled in order to make progress on requests from it's
-    // Control api. Here we spawn off a worker which will do this job
-    fn spawn_incoming_stream_worker<TSocket>(
-        connection: yamux::Connection<TSocket>,
-        counter: SubstreamCounter,
-    ) -> IncomingSubstreams
-    where
-        TSocket: AsyncRead + AsyncWrite + Unpin + Send + 'static,
-    {
-        let shutdown = Shutdown::new();
-        let (incoming_tx, incoming_rx) = mpsc::channel(10);
-        let stream = yamux::into_stream(connection).boxed();
-        let incoming = IncomingWorker::new(stream, incoming_tx, shutdown.to_signal());
-        runtime::current().spawn(incoming.run());
-        IncomingSubstreams::new(incoming_rx, counter, shutdown)
-    }
-
     /// Get the yamux control struct
     pub fn get_yamux_control(&self) -> Control {
         self.control.clone()
@@ -128,37 +143,119 @@ impl Yamux {
         self.substream_counter.clone()
     }
 
+    /// Return the BandwidthMeter for this connection
+    pub fn bandwidth_meter(&self) -> BandwidthMeter {
+        self.bandwidth_meter.clone()
+    }
+
     pub fn is_terminated(&self) -> bool {
         self.incoming.is_terminated()
     }
 }
 
+impl Multiplexer for Yamux {
+    fn get_control(&self) -> Control {
+        self.get_yamux_control()
+    }
+
+    fn incoming_mut(&mut self) -> &mut IncomingSubstreams {
+        self.incoming_mut()
+    }
+
+    fn into_incoming(self) -> IncomingSubstreams {
+        self.incoming()
+    }
+
+    fn substream_counter(&self) -> SubstreamCounter {
+        self.substream_counter()
+    }
+
+    fn bandwidth_meter(&self) -> BandwidthMeter {
+        self.bandwidth_meter()
+    }
+}
+
+// yamux@0.4 requires the incoming substream stream be polled in order to make progress on requests from it's
+// Control api. Here we spawn off a worker which will do this job. The same worker forwards incoming QUIC streams.
+pub(super) fn spawn_incoming_stream_worker<S, E>(
+    stream: S,
+    counter: SubstreamCounter,
+    metering: SubstreamMetering,
+) -> IncomingSubstreams
+where
+    S: Stream<Item = Result<StreamInner, E>> + Unpin + Send + 'static,
+    E: fmt::Display + Send + 'static,
+{
+    let shutdown = Shutdown::new();
+    let (incoming_tx, incoming_rx) = mpsc::channel(10);
+    let incoming = IncomingWorker::new(stream, incoming_tx, shutdown.to_signal());
+    runtime::current().spawn(incoming.run());
+    IncomingSubstreams::new(incoming_rx, counter, metering, shutdown)
+}
+
+#[derive(Clone)]
+enum ControlInner {
+    Yamux(yamux::Control),
+    Quic(quinn::Connection),
+}
+
 #[derive(Clone)]
 pub struct Control {
-    inner: yamux::Control,
+    inner: ControlInner,
     substream_counter: SubstreamCounter,
+    metering: SubstreamMetering,
 }
 
 impl Control {
-    pub fn new(inner: yamux::Control, substream_counter: SubstreamCounter) -> Self {
+    pub(crate) fn new(inner: yamux::Control, substream_counter: SubstreamCounter, metering: SubstreamMetering) -> Self {
         Self {
-            inner,
+            inner: ControlInner::Yamux(inner),
             substream_counter,
+            metering,
+        }
+    }
+
+    pub(super) fn new_quic(
+        connection: quinn::Connection,
+        substream_counter: SubstreamCounter,
+        metering: SubstreamMetering,
+    ) -> Self
+    {
+        Self {
+            inner: ControlInner::Quic(connection),
+            substream_counter,
+            metering,
         }
     }
 
     /// Open a new stream to the remote.
     pub async fn open_stream(&mut self) -> Result<Substream, ConnectionError> {
-        let stream = self.inner.open_stream().await?;
-        Ok(Substream {
+        let stream = match &mut self.inner {
+            ControlInner::Yamux(control) => StreamInner::Yamux(control.open_stream().await?),
+            ControlInner::Quic(connection) => {
+                let (send, recv) = connection
+                    .open_bi()
+                    .await
+                    .map_err(|err| ConnectionError::Io(io::Error::new(io::ErrorKind::Other, err)))?;
+                StreamInner::Quic(QuicStream::new(send, recv))
+            },
+        };
+        Ok(Substream::new(
             stream,
-            counter_guard: self.substream_counter.new_guard(),
-        })
+            self.substream_counter.new_guard(),
+            self.metering.clone(),
+        ))
     }
 
     /// Close the connection.
-    pub fn close(&mut self) -> impl Future<Output = Result<(), ConnectionError>> + '_ {
-        self.inner.close()
+    pub async fn close(&mut self) -> Result<(), ConnectionError> {
+        match &mut self.inner {
+            ControlInner::Yamux(control) => control.close().await,
+            ControlInner::Quic(connection) => {
+                connection.close(quinn::VarInt::from_u32(0), b"");
+                Ok(())
+            },
+        }
     }
 
     pub fn substream_count(&self) -> usize {
@@ -168,19 +265,31 @@ impl Control {
     pub(crate) fn substream_counter(&self) -> SubstreamCounter {
         self.substream_counter.clone()
     }
+
+    pub fn bandwidth_meter(&self) -> BandwidthMeter {
+        self.metering.meter.clone()
+    }
 }
 
 pub struct IncomingSubstreams {
     inner: IncomingRx,
     substream_counter: SubstreamCounter,
+    metering: SubstreamMetering,
     shutdown: Shutdown,
 }
 
 impl IncomingSubstreams {
-    pub fn new(inner: IncomingRx, substream_counter: SubstreamCounter, shutdown: Shutdown) -> Self {
+    pub(super) fn new(
+        inner: IncomingRx,
+        substream_counter: SubstreamCounter,
+        metering: SubstreamMetering,
+        shutdown: Shutdown,
+    ) -> Self
+    {
         Self {
             inner,
             substream_counter,
+            metering,
             shutdown,
         }
     }
@@ -201,10 +310,11 @@ impl Stream for IncomingSubstreams {
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
         match futures::ready!(Pin::new(&mut self.inner).poll_next(cx)) {
-            Some(stream) => Poll::Ready(Some(Substream {
+            Some(stream) => Poll::Ready(Some(Substream::new(
                 stream,
-                counter_guard: self.substream_counter.new_guard(),
-            })),
+                self.substream_counter.new_guard(),
+                self.metering.clone(),
+            ))),
             None => Poll::Ready(None),
         }
     }
@@ -216,21 +326,138 @@ impl Drop for IncomingSubstreams {
     }
 }
 
+/// The bandwidth meter and limiter shared by all substreams on a connection
+#[derive(Debug, Clone)]
+pub(crate) struct SubstreamMetering {
+    meter: BandwidthMeter,
+    limiter: ConnectionLimiter,
+}
+
+impl SubstreamMetering {
+    pub fn new(meter: BandwidthMeter, limiter: ConnectionLimiter) -> Self {
+        Self { meter, limiter }
+    }
+}
+
+/// The underlying stream of a substream, which is either a yamux stream or a QUIC stream
+#[derive(Debug)]
+pub(super) enum StreamInner {
+    Yamux(yamux::Stream),
+    Quic(QuicStream),
+}
+
 #[derive(Debug)]
 pub struct Substream {
-    stream: yamux::Stream,
+    stream: StreamInner,
     counter_guard: CounterGuard,
+    metering: SubstreamMetering,
+    total_counters: Arc<BandwidthCounters>,
+    protocol_counters: Option<Arc<BandwidthCounters>>,
+    read_delay: Option<Pin<Box<time::Delay>>>,
+    write_delay: Option<Pin<Box<time::Delay>>>,
+}
+
+impl Substream {
+    fn new(stream: StreamInner, counter_guard: CounterGuard, metering: SubstreamMetering) -> Self {
+        Self {
+            stream,
+            counter_guard,
+            total_counters: metering.meter.total_counters(),
+            metering,
+            protocol_counters: None,
+            read_delay: None,
+            write_delay: None,
+        }
+    }
+
+    /// Attribute all further bytes sent and received on this substream to the given protocol
+    pub(crate) fn set_protocol(&mut self, protocol: &ProtocolId) {
+        self.protocol_counters = Some(self.metering.meter.protocol_counters(protocol));
+    }
+
+    /// Returns the bytes sent and received on this connection for the protocol negotiated on this substream, or the
+    /// connection total if no protocol has been negotiated.
+    pub fn bandwidth_stats(&self) -> BandwidthStats {
+        self.protocol_counters.as_ref().unwrap_or(&self.total_counters).stats()
+    }
+
+    fn record_in(&mut self, num_bytes: usize) {
+        self.total_counters.record_in(num_bytes);
+        if let Some(counters) = self.protocol_counters.as_ref() {
+            counters.record_in(num_bytes);
+        }
+        self.read_delay = self.metering.limiter.consume(num_bytes).map(Self::new_delay);
+    }
+
+    fn record_out(&mut self, num_bytes: usize) {
+        self.total_counters.record_out(num_bytes);
+        if let Some(counters) = self.protocol_counters.as_ref() {
+            counters.record_out(num_bytes);
+        }
+        self.write_delay = self.metering.limiter.consume(num_bytes).map(Self::new_delay);
+    }
+
+    fn new_delay(duration: Duration) -> Pin<Box<time::Delay>> {
+        Box::pin(time::delay_for(duration))
+    }
+
+    /// Polls the given delay (if any) to completion, clearing it once it has elapsed
+    fn poll_delay(delay: &mut Option<Pin<Box<time::Delay>>>, cx: &mut Context<'_>) -> Poll<()> {
+        if let Some(d) = delay.as_mut() {
+            futures::ready!(d.as_mut().poll(cx));
+            *delay = None;
+        }
+        Poll::Ready(())
+    }
+}
+
+impl AsyncRead for StreamInner {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            StreamInner::Yamux(stream) => Pin::new(stream).poll_read(cx, buf),
+            StreamInner::Quic(stream) => Pin::new(stream).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for StreamInner {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            StreamInner::Yamux(stream) => Pin::new(stream).poll_write(cx, buf),
+            StreamInner::Quic(stream) => Pin::new(stream).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            StreamInner::Yamux(stream) => Pin::new(stream).poll_flush(cx),
+            StreamInner::Quic(stream) => Pin::new(stream).poll_flush(cx),
+        }
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            StreamInner::Yamux(stream) => Pin::new(stream).poll_close(cx),
+            StreamInner::Quic(stream) => Pin::new(stream).poll_close(cx),
+        }
+    }
 }
 
 impl AsyncRead for Substream {
     fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
-        Pin::new(&mut self.stream).poll_read(cx, buf)
+        futures::ready!(Self::poll_delay(&mut self.read_delay, cx));
+        let num_bytes = futures::ready!(Pin::new(&mut self.stream).poll_read(cx, buf))?;
+        self.record_in(num_bytes);
+        Poll::Ready(Ok(num_bytes))
     }
 }
 
 impl AsyncWrite for Substream {
     fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
-        Pin::new(&mut self.stream).poll_write(cx, buf)
+        futures::ready!(Self::poll_delay(&mut self.write_delay, cx));
+        let num_bytes = futures::ready!(Pin::new(&mut self.stream).poll_write(cx, buf))?;
+        self.record_out(num_bytes);
+        Poll::Ready(Ok(num_bytes))
     }
 
     fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
@@ -244,12 +471,14 @@ impl AsyncWrite for Substream {
 
 struct IncomingWorker<S> {
     inner: S,
-    sender: mpsc::Sender<yamux::Stream>,
+    sender: IncomingTx,
     shutdown_signal: Option<ShutdownSignal>,
 }
 
-impl<S> IncomingWorker<S>
-where S: Stream<Item = Result<yamux::Stream, yamux::ConnectionError>> + Unpin
+impl<S, E> IncomingWorker<S>
+where
+    S: Stream<Item = Result<StreamInner, E>> + Unpin,
+    E: fmt::Display,
 {
     pub fn new(stream: S, sender: IncomingTx, shutdown_signal: ShutdownSignal) -> Self {
         Self {
@@ -335,7 +564,8 @@ mod test {
     use crate::{
         connection_manager::ConnectionDirection,
         memsocket::MemorySocket,
-        multiplexing::yamux::Yamux,
+        multiplexing::{bandwidth::BandwidthLimits, yamux::Yamux, BandwidthStats},
+        protocol::ProtocolId,
         runtime,
         runtime::task,
     };
@@ -344,7 +574,10 @@ mod test {
         io::{AsyncReadExt, AsyncWriteExt},
         StreamExt,
     };
-    use std::{io, time::Duration};
+    use std::{
+        io,
+        time::{Duration, Instant},
+    };
     use tari_test_utils::collect_stream;
 
     #[runtime::test_basic]
@@ -501,4 +734,65 @@ mod test {
 
         Ok(())
     }
+
+    #[runtime::test_basic]
+    async fn bandwidth_metering() -> io::Result<()> {
+        let (dialer, listener) = MemorySocket::new_pair();
+        let msg = b"Oathbringer";
+        let protocol = ProtocolId::from_static(b"/test/metering");
+
+        let dialer = Yamux::upgrade_connection(dialer, ConnectionDirection::Outbound).await?;
+        let mut dialer_control = dialer.get_yamux_control();
+        let dialer_meter = dialer.bandwidth_meter();
+
+        let mut listener = Yamux::upgrade_connection(listener, ConnectionDirection::Inbound).await?;
+        let listener_meter = listener.bandwidth_meter();
+
+        let mut substream = dialer_control.open_stream().await.unwrap();
+        substream.set_protocol(&protocol);
+        substream.write_all(msg).await?;
+        substream.flush().await?;
+
+        let mut incoming = listener.incoming_mut().next().await.unwrap();
+        let mut buf = vec![0u8; msg.len()];
+        incoming.read_exact(&mut buf).await?;
+
+        let expected = msg.len() as u64;
+        assert_eq!(dialer_meter.total(), BandwidthStats::new(0, expected));
+        assert_eq!(dialer_meter.protocol(&protocol), BandwidthStats::new(0, expected));
+        assert_eq!(substream.bandwidth_stats(), BandwidthStats::new(0, expected));
+        assert_eq!(listener_meter.total(), BandwidthStats::new(expected, 0));
+        assert!(listener_meter.protocols().is_empty());
+
+        Ok(())
+    }
+
+    #[runtime::test_basic]
+    async fn bandwidth_limited() -> io::Result<()> {
+        const RATE: u64 = 1024;
+        let (dialer, listener) = MemorySocket::new_pair();
+
+        let limits = BandwidthLimits::new(None, Some(RATE));
+        let dialer = Yamux::upgrade_connection_with_limits(dialer, ConnectionDirection::Outbound, limits).await?;
+        let mut dialer_control = dialer.get_yamux_control();
+
+        let mut incoming = Yamux::upgrade_connection(listener, ConnectionDirection::Inbound)
+            .await?
+            .incoming();
+        task::spawn(async move {
+            let mut substream = incoming.next().await.unwrap();
+            let mut buf = Vec::new();
+            let _ = substream.read_to_end(&mut buf).await;
+        });
+
+        let mut substream = dialer_control.open_stream().await.unwrap();
+        let timer = Instant::now();
+        // The first RATE bytes are allowed through immediately, the next RATE / 2 bytes incur a delay
+        substream.write_all(&[0u8; RATE as usize]).await?;
+        substream.write_all(&[0u8; RATE as usize / 2]).await?;
+        substream.write_all(&[0u8; 1]).await?;
+        assert!(timer.elapsed() >= Duration::from_millis(400));
+
+        Ok(())
+    }
 }
@@ -65,13 +65,29 @@ impl NoiseConfig {
         socket: TSocket,
         direction: ConnectionDirection,
     ) -> Result<NoiseSocket<TSocket>, NoiseError>
+    where
+        TSocket: AsyncWrite + AsyncRead + Unpin,
+    {
+        self.upgrade_socket_with_prologue(socket, direction, &[]).await
+    }
+
+    /// Upgrades the given socket to using the noise protocol, mixing the given prologue into the handshake. Both
+    /// sides must use the same prologue for the handshake to succeed, which allows the noise session to be bound to
+    /// the underlying channel.
+    pub async fn upgrade_socket_with_prologue<TSocket>(
+        &self,
+        socket: TSocket,
+        direction: ConnectionDirection,
+        prologue: &[u8],
+    ) -> Result<NoiseSocket<TSocket>, NoiseError>
     where
         TSocket: AsyncWrite + AsyncRead + Unpin,
     {
         let handshake_state = {
             let builder =
                 snow::Builder::with_resolver(self.parameters.clone(), Box::new(TariCryptoResolver::default()))
-                    .local_private_key(self.node_identity.secret_key().as_bytes());
+                    .local_private_key(self.node_identity.secret_key().as_bytes())
+                    .prologue(prologue);
 
             match direction {
                 ConnectionDirection::Outbound => {
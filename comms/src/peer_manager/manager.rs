@@ -31,6 +31,8 @@ use crate::{
         PeerFeatures,
         PeerManagerError,
         PeerQuery,
+        PeerReputation,
+        ReputationEvent,
     },
     types::{CommsDatabase, CommsPublicKey},
 };
@@ -302,6 +304,17 @@ impl PeerManager {
     {
         self.peer_storage.write().await.set_peer_metadata(node_id, key, data)
     }
+
+    /// Applies a reputation event to the peer and returns the peer's new reputation. It is up to the caller to take
+    /// action (e.g. ban the peer) if the reputation falls below the ban threshold.
+    pub async fn apply_reputation_event(
+        &self,
+        node_id: &NodeId,
+        event: ReputationEvent,
+    ) -> Result<PeerReputation, PeerManagerError>
+    {
+        self.peer_storage.write().await.apply_reputation_event(node_id, event)
+    }
 }
 
 impl fmt::Debug for PeerManager {
@@ -560,6 +573,37 @@ mod test {
         }
     }
 
+    #[runtime::test_basic]
+    async fn closest_peers_prefers_reputable_peers() {
+        let peer_manager = PeerManager::new(HashmapDatabase::new(), None).unwrap();
+        let region_node_id = create_test_peer(false, Default::default()).node_id;
+        let test_peers = (0..10)
+            .map(|_| create_test_peer(false, PeerFeatures::COMMUNICATION_NODE))
+            .collect::<Vec<_>>();
+        for p in &test_peers {
+            peer_manager.add_peer(p.clone()).await.unwrap();
+        }
+
+        let closest = peer_manager.closest_peers(&region_node_id, 1, &[], None).await.unwrap();
+        let closest_node_id = closest[0].node_id.clone();
+
+        let reputation = peer_manager
+            .apply_reputation_event(&closest_node_id, ReputationEvent::DhtValidationFailure)
+            .await
+            .unwrap();
+        assert!(reputation.is_poor());
+        let peer = peer_manager.find_by_node_id(&closest_node_id).await.unwrap();
+        assert_eq!(peer.reputation().score(), reputation.score());
+
+        let closest = peer_manager
+            .closest_peers(&region_node_id, 10, &[], None)
+            .await
+            .unwrap();
+        assert_eq!(closest.len(), 10);
+        assert_ne!(closest[0].node_id, closest_node_id);
+        assert_eq!(closest[9].node_id, closest_node_id);
+    }
+
     #[runtime::test_basic]
     async fn add_or_update_online_peer() {
         let peer_manager = PeerManager::new(HashmapDatabase::new(), None).unwrap();
@@ -99,6 +99,17 @@ pub use peer_query::{PeerQuery, PeerQuerySortBy};
 mod peer_storage;
 pub use peer_storage::PeerStorage;
 
+mod reputation;
+pub use reputation::{
+    is_poor_score,
+    PeerReputation,
+    ReputationEvent,
+    ReputationScore,
+    SuccessfulExchangeDebouncer,
+    REPUTATION_BAN_THRESHOLD,
+    REPUTATION_POOR_THRESHOLD,
+};
+
 mod migrations;
 
 mod wrapper;
@@ -24,6 +24,7 @@ use super::{
     connection_stats::PeerConnectionStats,
     node_id::{deserialize_node_id_from_hex, NodeId},
     peer_id::PeerId,
+    reputation::{PeerReputation, ReputationEvent},
     PeerFeatures,
 };
 use crate::{
@@ -92,6 +93,8 @@ pub struct Peer {
     /// Metadata field. This field is for use by upstream clients to record extra info about a peer.
     /// We use a hashmap here so that we can use more than one "info set"
     pub metadata: HashMap<u8, Vec<u8>>,
+    /// Reputation of the peer, adjusted according to the peer's behaviour
+    pub reputation: PeerReputation,
 }
 
 impl Peer {
@@ -121,6 +124,7 @@ impl Peer {
             supported_protocols,
             user_agent,
             metadata: HashMap::new(),
+            reputation: PeerReputation::new(),
         }
     }
 
@@ -238,11 +242,13 @@ impl Peer {
         &self.banned_reason
     }
 
-    /// Bans the peer for a specified duration
+    /// Bans the peer for a specified duration. The peer's reputation is reset so that it starts afresh once the ban
+    /// expires.
     pub fn ban_for(&mut self, duration: Duration, reason: String) {
         let dt = safe_future_datetime_from_duration(duration);
         self.banned_until = Some(dt.naive_utc());
         self.banned_reason = reason;
+        self.reputation.reset();
     }
 
     /// Unban the peer
@@ -255,6 +261,17 @@ impl Peer {
         self.banned_until.as_ref().filter(|dt| *dt > &Utc::now().naive_utc())
     }
 
+    /// Returns the reputation of the peer
+    pub fn reputation(&self) -> &PeerReputation {
+        &self.reputation
+    }
+
+    /// Applies a reputation event to the peer, returning the new reputation
+    pub fn apply_reputation_event(&mut self, event: ReputationEvent) -> PeerReputation {
+        self.reputation.apply(event);
+        self.reputation
+    }
+
     /// Marks the peer as offline
     pub fn set_offline(&mut self, is_offline: bool) {
         if is_offline {
@@ -317,7 +334,7 @@ impl Display for Peer {
         };
 
         f.write_str(&format!(
-            "{}[{}] PK={} ({}) - {}. Type: {}. User agent: {}. {}.",
+            "{}[{}] PK={} ({}) - {}. Type: {}. User agent: {}. Reputation: {}. {}.",
             flags_str,
             self.node_id.short_str(),
             self.public_key,
@@ -334,6 +351,7 @@ impl Display for Peer {
                 f => format!("{:?}", f),
             },
             user_agent,
+            self.reputation,
             self.connection_stats,
         ))
     }
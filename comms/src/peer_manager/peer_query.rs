@@ -33,6 +33,8 @@ pub enum PeerQuerySortBy<'a> {
     None,
     /// Sort by distance from a given node id
     DistanceFrom(&'a NodeId),
+    /// Sort by distance from a given node id, placing peers with a poor reputation after all other peers
+    ReputationThenDistanceFrom(&'a NodeId),
 }
 
 impl Default for PeerQuerySortBy<'_> {
@@ -128,18 +130,25 @@ where DS: KeyValueStore<PeerId, Peer>
     pub fn get_results(&mut self) -> Result<Vec<Peer>, PeerManagerError> {
         match self.query.sort_by {
             PeerQuerySortBy::None => self.get_query_results(),
-            PeerQuerySortBy::DistanceFrom(node_id) => self.get_distance_sorted_results(node_id),
+            PeerQuerySortBy::DistanceFrom(node_id) => self.get_distance_sorted_results(node_id, false),
+            PeerQuerySortBy::ReputationThenDistanceFrom(node_id) => self.get_distance_sorted_results(node_id, true),
         }
     }
 
-    pub fn get_distance_sorted_results(&mut self, node_id: &NodeId) -> Result<Vec<Peer>, PeerManagerError> {
+    pub fn get_distance_sorted_results(
+        &mut self,
+        node_id: &NodeId,
+        prefer_reputable: bool,
+    ) -> Result<Vec<Peer>, PeerManagerError>
+    {
         let mut peer_keys = Vec::new();
         let mut distances = Vec::new();
         self.store
             .for_each_ok(|(peer_key, peer)| {
                 if self.query.is_selected(&peer) {
                     peer_keys.push(peer_key);
-                    distances.push(node_id.distance(&peer.node_id));
+                    let is_poor = prefer_reputable && peer.reputation().is_poor();
+                    distances.push((is_poor, node_id.distance(&peer.node_id)));
                 }
 
                 IterationResult::Continue
@@ -214,6 +223,7 @@ mod test {
             node_id::NodeId,
             peer::{Peer, PeerFlags},
             PeerFeatures,
+            ReputationEvent,
         },
     };
     use multiaddr::Multiaddr;
@@ -413,4 +423,28 @@ mod test {
         })
         .unwrap();
     }
+
+    #[test]
+    fn sort_by_reputation_query() {
+        let db = HashmapDatabase::new();
+        let node_id = NodeId::default();
+
+        let mut peers = repeat_with(|| create_test_peer(false)).take(5).collect::<Vec<_>>();
+        peers.sort_by(|a, b| a.node_id.distance(&node_id).cmp(&b.node_id.distance(&node_id)));
+        // Closest peer has a poor reputation
+        peers[0].apply_reputation_event(ReputationEvent::DhtValidationFailure);
+        let poor_node_id = peers[0].node_id.clone();
+        for (i, peer) in peers.into_iter().enumerate() {
+            db.insert(i as u64, peer).unwrap();
+        }
+
+        let peers = PeerQuery::new()
+            .sort_by(PeerQuerySortBy::ReputationThenDistanceFrom(&node_id))
+            .executor(&db)
+            .get_results()
+            .unwrap();
+
+        assert_eq!(peers.len(), 5);
+        assert_eq!(peers.last().unwrap().node_id, poor_node_id);
+    }
 }
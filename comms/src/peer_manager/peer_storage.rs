@@ -29,6 +29,8 @@ use crate::{
         PeerFeatures,
         PeerManagerError,
         PeerQuery,
+        PeerReputation,
+        ReputationEvent,
     },
     protocol::ProtocolId,
     types::{CommsDatabase, CommsPublicKey},
@@ -325,7 +327,14 @@ where DS: KeyValueStore<PeerId, Peer>
             })
             .map_err(PeerManagerError::DatabaseError)?;
 
-        distances.sort_by(|(_, dist_a), (_, dist_b)| dist_a.cmp(dist_b));
+        // Peers with a poor reputation are only selected if there are not enough reputable peers
+        distances.sort_by(|(peer_a, dist_a), (peer_b, dist_b)| {
+            peer_a
+                .reputation()
+                .is_poor()
+                .cmp(&peer_b.reputation().is_poor())
+                .then_with(|| dist_a.cmp(dist_b))
+        });
         distances.truncate(n);
 
         Ok(distances.into_iter().map(|(peer, _)| peer).collect())
@@ -352,6 +361,8 @@ where DS: KeyValueStore<PeerId, Peer>
             return Ok(Vec::new());
         }
         peers.shuffle(&mut OsRng);
+        // Stable sort keeps the shuffled order while preferring peers that do not have a poor reputation
+        peers.sort_by_key(|peer| peer.reputation().is_poor());
         peers.truncate(n);
 
         Ok(peers)
@@ -536,6 +547,29 @@ where DS: KeyValueStore<PeerId, Peer>
             .map_err(PeerManagerError::DatabaseError)?;
         Ok(result)
     }
+
+    /// Applies a reputation event to the peer and returns the peer's new reputation
+    pub fn apply_reputation_event(
+        &mut self,
+        node_id: &NodeId,
+        event: ReputationEvent,
+    ) -> Result<PeerReputation, PeerManagerError>
+    {
+        let peer_key = *self
+            .node_id_index
+            .get(&node_id)
+            .ok_or_else(|| PeerManagerError::PeerNotFoundError)?;
+        let mut peer: Peer = self
+            .peer_db
+            .get(&peer_key)
+            .map_err(PeerManagerError::DatabaseError)?
+            .expect("node_id_index is out of sync with peer db");
+        let reputation = peer.apply_reputation_event(event);
+        self.peer_db
+            .insert(peer_key, peer)
+            .map_err(PeerManagerError::DatabaseError)?;
+        Ok(reputation)
+    }
 }
 
 impl Into<CommsDatabase> for PeerStorage<CommsDatabase> {
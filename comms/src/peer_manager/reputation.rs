@@ -0,0 +1,227 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use super::NodeId;
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp,
+    collections::HashMap,
+    fmt,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// The reputation score of a peer
+pub type ReputationScore = i32;
+
+/// The score given to a peer that has not yet been seen to behave well or badly
+pub const INITIAL_REPUTATION: ReputationScore = 0;
+/// The upper bound of a reputation score. A ceiling prevents a long-lived peer from accumulating enough credit to
+/// misbehave for an extended period without being banned.
+pub const MAX_REPUTATION: ReputationScore = 100;
+/// The lower bound of a reputation score
+pub const MIN_REPUTATION: ReputationScore = -100;
+/// Peers with a score below this threshold are banned
+pub const REPUTATION_BAN_THRESHOLD: ReputationScore = -50;
+/// Peers with a score below this threshold are deprioritised when selecting peers
+pub const REPUTATION_POOR_THRESHOLD: ReputationScore = 0;
+
+/// An event that affects the reputation of a peer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReputationEvent {
+    /// A request/response or message exchange with the peer completed successfully
+    SuccessfulExchange,
+    /// The peer sent a DHT message that failed validation
+    DhtValidationFailure,
+    /// An RPC session with the peer failed due to a protocol or peer error
+    RpcError,
+    /// The peer sent a block that failed validation
+    InvalidBlock,
+    /// The peer sent a transaction that failed validation
+    InvalidTransaction,
+}
+
+impl ReputationEvent {
+    /// The amount that the event adjusts a peer's reputation score
+    pub fn score_delta(self) -> ReputationScore {
+        use ReputationEvent::*;
+        match self {
+            SuccessfulExchange => 1,
+            DhtValidationFailure => -10,
+            RpcError => -5,
+            InvalidBlock => -40,
+            InvalidTransaction => -15,
+        }
+    }
+}
+
+impl fmt::Display for ReputationEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// Tracks the reputation of a peer. The score is adjusted by [ReputationEvent](self::ReputationEvent)s and is always
+/// within the range [MIN_REPUTATION, MAX_REPUTATION].
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+pub struct PeerReputation {
+    score: ReputationScore,
+}
+
+impl PeerReputation {
+    pub fn new() -> Self {
+        Self {
+            score: INITIAL_REPUTATION,
+        }
+    }
+
+    /// Returns the current reputation score
+    pub fn score(&self) -> ReputationScore {
+        self.score
+    }
+
+    /// Applies the event to the reputation score and returns the new score
+    pub fn apply(&mut self, event: ReputationEvent) -> ReputationScore {
+        self.score = cmp::min(
+            MAX_REPUTATION,
+            cmp::max(MIN_REPUTATION, self.score.saturating_add(event.score_delta())),
+        );
+        self.score
+    }
+
+    /// Returns true if the score has fallen below the ban threshold
+    pub fn should_ban(&self) -> bool {
+        self.score < REPUTATION_BAN_THRESHOLD
+    }
+
+    /// Returns true if the score has fallen below the threshold at which the peer is deprioritised
+    pub fn is_poor(&self) -> bool {
+        is_poor_score(self.score)
+    }
+
+    /// Resets the reputation to the initial score. This is done once a peer has been banned so that it is given a
+    /// clean slate when the ban expires.
+    pub fn reset(&mut self) {
+        self.score = INITIAL_REPUTATION;
+    }
+}
+
+impl Default for PeerReputation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Display for PeerReputation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.score)
+    }
+}
+
+/// Returns true if the given score is below the threshold at which the peer is deprioritised
+pub fn is_poor_score(score: ReputationScore) -> bool {
+    score < REPUTATION_POOR_THRESHOLD
+}
+
+/// Limits how often each peer is credited with a [SuccessfulExchange](self::ReputationEvent::SuccessfulExchange).
+/// Every reputation event is written to the peer database, and a peer should not be able to earn back reputation by
+/// sending many cheap valid messages, so a peer is credited at most once per interval.
+#[derive(Debug, Clone)]
+pub struct SuccessfulExchangeDebouncer {
+    interval: Duration,
+    last_reported: Arc<Mutex<HashMap<NodeId, Instant>>>,
+}
+
+impl SuccessfulExchangeDebouncer {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            last_reported: Default::default(),
+        }
+    }
+
+    /// Returns true if the peer has not been credited with a successful exchange within the interval, and records
+    /// that it is being credited now.
+    pub fn should_report(&self, node_id: &NodeId) -> bool {
+        let mut last_reported = self.last_reported.lock().unwrap_or_else(|err| err.into_inner());
+        let now = Instant::now();
+        let interval = self.interval;
+        last_reported.retain(|_, reported_at| now.duration_since(*reported_at) < interval);
+        if last_reported.contains_key(node_id) {
+            return false;
+        }
+        last_reported.insert(node_id.clone(), now);
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::types::CommsPublicKey;
+    use rand::rngs::OsRng;
+    use tari_crypto::keys::PublicKey;
+
+    #[test]
+    fn apply() {
+        let mut reputation = PeerReputation::new();
+        assert_eq!(reputation.score(), INITIAL_REPUTATION);
+        assert_eq!(reputation.apply(ReputationEvent::SuccessfulExchange), 1);
+        assert_eq!(reputation.apply(ReputationEvent::RpcError), -4);
+        assert!(reputation.is_poor());
+        assert!(!reputation.should_ban());
+        reputation.apply(ReputationEvent::InvalidBlock);
+        assert!(reputation.should_ban());
+        reputation.reset();
+        assert_eq!(reputation.score(), INITIAL_REPUTATION);
+    }
+
+    #[test]
+    fn apply_is_bounded() {
+        let mut reputation = PeerReputation::new();
+        for _ in 0..200 {
+            reputation.apply(ReputationEvent::SuccessfulExchange);
+        }
+        assert_eq!(reputation.score(), MAX_REPUTATION);
+
+        for _ in 0..10 {
+            reputation.apply(ReputationEvent::InvalidBlock);
+        }
+        assert_eq!(reputation.score(), MIN_REPUTATION);
+    }
+
+    #[test]
+    fn successful_exchanges_are_reported_once_per_interval() {
+        let node_id = NodeId::from_public_key(&CommsPublicKey::random_keypair(&mut OsRng).1);
+        let other_node_id = NodeId::from_public_key(&CommsPublicKey::random_keypair(&mut OsRng).1);
+
+        let debouncer = SuccessfulExchangeDebouncer::new(Duration::from_secs(60));
+        assert!(debouncer.should_report(&node_id));
+        assert!(!debouncer.should_report(&node_id));
+        assert!(debouncer.clone().should_report(&other_node_id));
+        assert!(!debouncer.should_report(&other_node_id));
+
+        let debouncer = SuccessfulExchangeDebouncer::new(Duration::from_secs(0));
+        assert!(debouncer.should_report(&node_id));
+        assert!(debouncer.should_report(&node_id));
+    }
+}
@@ -178,11 +178,17 @@ impl ConnectivityManagerMock {
             },
             GetAllConnectionStates(_) => unimplemented!(),
             BanPeer(_, _, _) => {},
+            ReportPeer(_, _) => {},
             GetActiveConnections(reply) => {
                 reply
                     .send(self.state.active_conns.lock().await.values().cloned().collect())
                     .unwrap();
             },
+            GetBandwidthStats(reply) => {
+                reply
+                    .send(self.state.active_conns.lock().await.values().map(Into::into).collect())
+                    .unwrap();
+            },
             WaitStarted(reply) => reply.send(()).unwrap(),
         }
     }
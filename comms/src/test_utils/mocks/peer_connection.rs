@@ -30,7 +30,7 @@ use crate::{
     },
     multiaddr::Multiaddr,
     multiplexing,
-    multiplexing::{IncomingSubstreams, Substream, SubstreamCounter, Yamux},
+    multiplexing::{BandwidthMeter, IncomingSubstreams, Substream, SubstreamCounter, Yamux},
     peer_manager::{NodeId, Peer, PeerFeatures},
     test_utils::transport,
 };
@@ -52,6 +52,7 @@ pub fn create_dummy_peer_connection(node_id: NodeId) -> (PeerConnection, mpsc::R
             Multiaddr::empty(),
             ConnectionDirection::Inbound,
             SubstreamCounter::new(),
+            BandwidthMeter::new(),
         ),
         rx,
     )
@@ -90,6 +91,7 @@ pub async fn create_peer_connection_mock_pair(
             listen_addr.clone(),
             ConnectionDirection::Inbound,
             mock_state_in.substream_counter(),
+            mock_state_in.bandwidth_meter(),
         ),
         mock_state_in,
         PeerConnection::new(
@@ -100,6 +102,7 @@ pub async fn create_peer_connection_mock_pair(
             listen_addr,
             ConnectionDirection::Outbound,
             mock_state_out.substream_counter(),
+            mock_state_out.bandwidth_meter(),
         ),
         mock_state_out,
     )
@@ -111,17 +114,20 @@ pub struct PeerConnectionMockState {
     mux_control: Arc<Mutex<multiplexing::Control>>,
     mux_incoming: Arc<Mutex<IncomingSubstreams>>,
     substream_counter: SubstreamCounter,
+    bandwidth_meter: BandwidthMeter,
 }
 
 impl PeerConnectionMockState {
     pub fn new(muxer: Yamux) -> Self {
         let control = muxer.get_yamux_control();
         let substream_counter = control.substream_counter();
+        let bandwidth_meter = control.bandwidth_meter();
         Self {
             call_count: Arc::new(AtomicUsize::new(0)),
             mux_control: Arc::new(Mutex::new(control)),
             mux_incoming: Arc::new(Mutex::new(muxer.incoming())),
             substream_counter,
+            bandwidth_meter,
         }
     }
 
@@ -141,6 +147,10 @@ impl PeerConnectionMockState {
         self.substream_counter.clone()
     }
 
+    pub fn bandwidth_meter(&self) -> BandwidthMeter {
+        self.bandwidth_meter.clone()
+    }
+
     pub async fn next_incoming_substream(&self) -> Option<Substream> {
         self.mux_incoming.lock().await.next().await
     }
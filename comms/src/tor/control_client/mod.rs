@@ -39,6 +39,6 @@ mod types;
 pub use types::{KeyBlob, KeyType, PortMapping, PrivateKey};
 
 #[cfg(test)]
-mod test_server;
+pub(in crate::tor) mod test_server;
 
 const LOG_TARGET: &str = "comms::tor::control_client";
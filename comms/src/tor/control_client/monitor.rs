@@ -52,6 +52,8 @@ where
                             target: LOG_TARGET,
                             "Error when sending to Tor control server: {:?}. Monitor is shutting down.", err
                         );
+                        cmd_rx.close();
+                        let _ = event_tx.send(TorControlEvent::TorControlDisconnected);
                         break;
                     }
                 },
@@ -100,6 +102,8 @@ where
                         target: LOG_TARGET,
                         "Line framing error when reading from tor control server: '{:?}'. Monitor is exiting.", err
                     );
+                    cmd_rx.close();
+                    let _ = event_tx.send(TorControlEvent::TorControlDisconnected);
                     break;
                 },
                 // The control server disconnected
@@ -149,3 +153,32 @@ fn log_server_response_error<E: fmt::Debug>(err: E) {
         "Error processing response from tor control server: '{:?}'", err
     );
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{runtime, tor::control_client::test_server};
+    use std::time::Duration;
+    use tari_test_utils::unpack_enum;
+    use tokio::time;
+
+    #[runtime::test_basic]
+    async fn disconnect_event() {
+        let (_, state, socket) = test_server::spawn().await;
+        let (mut cmd_tx, cmd_rx) = mpsc::channel(1);
+        let (event_tx, mut event_rx) = broadcast::channel(1);
+        let mut responses_rx = spawn_monitor(cmd_rx, socket, event_tx);
+
+        cmd_tx.send("GETINFO version".to_string()).await.unwrap();
+        let line = responses_rx.next().await.unwrap();
+        assert!(line.is_ok());
+
+        state.hang_up().await;
+        let event = time::timeout(Duration::from_secs(5), event_rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        unpack_enum!(TorControlEvent::TorControlDisconnected = event);
+        assert!(cmd_tx.is_closed());
+    }
+}
@@ -26,9 +26,13 @@ use crate::{
     multiaddr::Multiaddr,
     runtime,
     test_utils::transport::build_connected_sockets,
+    transports::{TcpTransport, Transport},
+};
+use futures::{channel::mpsc, future, future::Either, lock::Mutex, stream, AsyncRead, AsyncWrite, SinkExt, StreamExt};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
 };
-use futures::{lock::Mutex, stream, SinkExt, StreamExt};
-use std::sync::Arc;
 use tokio_util::codec::{Framed, LinesCodec};
 
 pub async fn spawn() -> (Multiaddr, State, MemorySocket) {
@@ -41,17 +45,54 @@ pub async fn spawn() -> (Multiaddr, State, MemorySocket) {
     (addr, state, socket_out)
 }
 
+/// Spawns a test server listening on a local TCP port. Connections are served one after the other, so a client may
+/// reconnect after the server hangs up.
+pub async fn spawn_tcp() -> (Multiaddr, State) {
+    let (mut listener, addr) = TcpTransport::new()
+        .listen("/ip4/127.0.0.1/tcp/0".parse().unwrap())
+        .unwrap()
+        .await
+        .unwrap();
+
+    let state = State::new();
+    runtime::current().spawn({
+        let state = state.clone();
+        async move {
+            while let Some(Ok((inbound, _))) = listener.next().await {
+                if let Ok(socket) = inbound.await {
+                    state.num_connections.fetch_add(1, Ordering::SeqCst);
+                    serve(socket, state.clone()).await;
+                }
+            }
+        }
+    });
+
+    (addr, state)
+}
+
+/// A command prefix and the lines sent in response to it
+type CommandResponse = (String, Vec<String>);
+
 #[derive(Clone)]
 pub struct State {
     request_lines: Arc<Mutex<Vec<String>>>,
     canned_response: Arc<Mutex<Vec<String>>>,
+    command_responses: Arc<Mutex<Vec<CommandResponse>>>,
+    hang_up_tx: mpsc::UnboundedSender<()>,
+    hang_up_rx: Arc<Mutex<mpsc::UnboundedReceiver<()>>>,
+    num_connections: Arc<AtomicUsize>,
 }
 
 impl State {
     pub fn new() -> Self {
+        let (hang_up_tx, hang_up_rx) = mpsc::unbounded();
         Self {
             request_lines: Arc::new(Mutex::new(Vec::new())),
             canned_response: Arc::new(Mutex::new(all_to_owned(canned_responses::OK))),
+            command_responses: Arc::new(Mutex::new(Vec::new())),
+            hang_up_tx,
+            hang_up_rx: Arc::new(Mutex::new(hang_up_rx)),
+            num_connections: Arc::new(AtomicUsize::new(0)),
         }
     }
 
@@ -59,6 +100,35 @@ impl State {
         *self.canned_response.lock().await = all_to_owned(lines);
     }
 
+    /// Respond with the given lines to requests starting with `command` instead of the canned response
+    pub async fn set_command_response<'a, T: AsRef<[&'a str]>>(&self, command: &str, lines: T) {
+        self.command_responses
+            .lock()
+            .await
+            .push((command.to_string(), all_to_owned(lines)));
+    }
+
+    /// Close the current connection
+    pub async fn hang_up(&self) {
+        self.hang_up_tx.unbounded_send(()).unwrap();
+    }
+
+    /// The number of connections accepted by a TCP test server
+    pub fn num_connections(&self) -> usize {
+        self.num_connections.load(Ordering::SeqCst)
+    }
+
+    async fn response_for(&self, request: &str) -> Vec<String> {
+        let command_responses = self.command_responses.lock().await;
+        match command_responses
+            .iter()
+            .find(|(cmd, _)| request.starts_with(cmd.as_str()))
+        {
+            Some((_, lines)) => lines.clone(),
+            None => self.canned_response.lock().await.clone(),
+        }
+    }
+
     pub async fn take_requests(&self) -> Vec<String> {
         self.request_lines.lock().await.drain(..).collect()
     }
@@ -82,12 +152,20 @@ impl TorControlPortTestServer {
     }
 
     pub async fn run(self) {
-        let mut framed = Framed::new(IoCompat::new(self.socket), LinesCodec::new());
-        let state = self.state;
-        while let Some(msg) = framed.next().await {
-            state.request_lines.lock().await.push(msg.unwrap());
-            let mut responses = stream::iter(state.canned_response.lock().await.clone()).map(Ok);
-            framed.send_all(&mut responses).await.unwrap();
+        serve(self.socket, self.state).await;
+    }
+}
+
+async fn serve<TSocket>(socket: TSocket, state: State)
+where TSocket: AsyncRead + AsyncWrite + Unpin {
+    let mut framed = Framed::new(IoCompat::new(socket), LinesCodec::new());
+    let mut hang_up_rx = state.hang_up_rx.lock().await;
+    while let Either::Left((Some(Ok(msg)), _)) = future::select(framed.next(), hang_up_rx.next()).await {
+        let response = state.response_for(&msg).await;
+        state.request_lines.lock().await.push(msg);
+        let mut responses = stream::iter(response).map(Ok);
+        if framed.send_all(&mut responses).await.is_err() {
+            break;
         }
     }
 }
@@ -24,10 +24,16 @@ use super::controller::HiddenServiceControllerError;
 use crate::{
     multiaddr::Multiaddr,
     socks,
-    tor::{hidden_service::controller::HiddenServiceController, Authentication, PortMapping, TorIdentity},
+    tor::{
+        hidden_service::{controller::HiddenServiceController, OnionServiceConfig},
+        Authentication,
+        PortMapping,
+        TorIdentity,
+    },
 };
 use bitflags::bitflags;
 use log::*;
+use std::time::Duration;
 use tari_shutdown::{OptionalShutdownSignal, ShutdownSignal};
 use thiserror::Error;
 
@@ -57,6 +63,8 @@ bitflags! {
 #[derive(Default)]
 pub struct HiddenServiceBuilder {
     identity: Option<TorIdentity>,
+    additional_services: Vec<OnionServiceConfig>,
+    key_rotation_interval: Option<Duration>,
     port_mapping: Option<PortMapping>,
     socks_addr_override: Option<Multiaddr>,
     control_server_addr: Option<Multiaddr>,
@@ -91,6 +99,18 @@ impl HiddenServiceBuilder {
     #[doc("Configuration flags for the hidden service")]
     setter!(with_hs_flags, hs_flags, HsFlags);
 
+    #[doc(
+        "Onion services to create in addition to the primary hidden service. These services are recreated along with \
+         the primary service if the control port connection is reestablished, but are never rotated."
+    )]
+    setter!(with_additional_services, additional_services, Vec<OnionServiceConfig>);
+
+    #[doc(
+        "If set, the primary hidden service is moved to a newly generated key at this interval. The previous service \
+         remains reachable until the following rotation."
+    )]
+    setter!(with_key_rotation_interval, key_rotation_interval, Option<Duration>);
+
     /// The address of the SOCKS5 server. If an address is None, the hidden service builder will use the SOCKS
     /// listener address as given by the tor control port.
     pub fn with_shutdown_signal(mut self, shutdown_signal: ShutdownSignal) -> Self {
@@ -137,7 +157,9 @@ impl HiddenServiceBuilder {
             self.socks_addr_override,
             self.socks_auth,
             self.identity,
+            self.additional_services,
             self.hs_flags,
+            self.key_rotation_interval,
             self.shutdown_signal,
         );
 
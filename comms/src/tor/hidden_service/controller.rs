@@ -19,7 +19,6 @@
 // SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
-
 use crate::{
     multiaddr::Multiaddr,
     runtime::task,
@@ -29,8 +28,10 @@ use crate::{
             commands::{AddOnionFlag, AddOnionResponse},
             TorControlEvent,
         },
+        hidden_service::OnionServiceConfig,
         Authentication,
         HiddenService,
+        HiddenServiceEvent,
         HsFlags,
         PortMapping,
         TorClientError,
@@ -40,15 +41,26 @@ use crate::{
     transports::{SocksConfig, SocksTransport},
     utils::multiaddr::{multiaddr_to_socketaddr, socketaddr_to_multiaddr},
 };
-use futures::{future, future::Either, pin_mut, StreamExt};
+use futures::{future, future::Either, pin_mut, stream, StreamExt};
 use log::*;
-use std::{net::SocketAddr, time::Duration};
+use std::{
+    cmp,
+    mem,
+    net::SocketAddr,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
 use tari_shutdown::OptionalShutdownSignal;
 use thiserror::Error;
 use tokio::{sync::broadcast, time};
 
 const LOG_TARGET: &str = "comms::tor::hidden_service_controller";
 
+/// The initial delay between attempts to reconnect to the tor control server. The delay doubles on each failed
+/// attempt up to `MAX_RECONNECT_DELAY`.
+const MIN_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(60);
+
 #[derive(Debug, Error)]
 pub enum HiddenServiceControllerError {
     #[error("Tor client is not connected")]
@@ -73,9 +85,14 @@ pub struct HiddenServiceController {
     socks_address_override: Option<Multiaddr>,
     socks_auth: socks::Authentication,
     identity: Option<TorIdentity>,
+    shared_identity: Option<Arc<RwLock<TorIdentity>>>,
+    additional_services: Vec<OnionServiceConfig>,
     hs_flags: HsFlags,
+    key_rotation_interval: Option<Duration>,
+    retired_service_ids: Vec<String>,
     is_authenticated: bool,
     shutdown_signal: OptionalShutdownSignal,
+    event_tx: broadcast::Sender<HiddenServiceEvent>,
 }
 
 impl HiddenServiceController {
@@ -87,10 +104,13 @@ impl HiddenServiceController {
         socks_address_override: Option<Multiaddr>,
         socks_auth: socks::Authentication,
         identity: Option<TorIdentity>,
+        additional_services: Vec<OnionServiceConfig>,
         hs_flags: HsFlags,
+        key_rotation_interval: Option<Duration>,
         shutdown_signal: OptionalShutdownSignal,
     ) -> Self
     {
+        let (event_tx, _) = broadcast::channel(10);
         Self {
             client: None,
             control_server_addr,
@@ -100,8 +120,13 @@ impl HiddenServiceController {
             socks_auth,
             hs_flags,
             identity,
+            shared_identity: None,
+            additional_services,
+            key_rotation_interval,
+            retired_service_ids: Vec::new(),
             is_authenticated: false,
             shutdown_signal,
+            event_tx,
         }
     }
 
@@ -123,48 +148,67 @@ impl HiddenServiceController {
     /// Connects, authenticates to the Tor control port and creates a hidden service using the tor identity if provided,
     /// otherwise a new tor identity will be created. The creation of a hidden service is idempotent i.e. if the
     /// hidden service exists, the
+    ///
+    /// Once created, the controller monitors the control port connection, reconnecting and recreating all onion
+    /// services if it drops, and rotates the primary onion service key if a key rotation interval is set.
     pub async fn create_hidden_service(mut self) -> Result<HiddenService, HiddenServiceControllerError> {
         self.connect_and_auth().await?;
         self.set_events().await?;
 
         let hidden_service = self.create_hidden_service_from_identity().await?;
         let mut shutdown_signal = hidden_service.shutdown_signal.clone();
-        let mut event_stream = self.client.as_ref().unwrap().get_event_stream();
+        let mut event_stream = self.client.as_ref().unwrap().get_event_stream().fuse();
+        let mut rotation_ticker = match self.key_rotation_interval {
+            Some(interval) => time::interval_at(time::Instant::now() + interval, interval)
+                .map(|_| ())
+                .boxed(),
+            None => stream::pending().boxed(),
+        }
+        .fuse();
 
         task::spawn({
             async move {
                 loop {
-                    let either = future::select(&mut shutdown_signal, event_stream.next()).await;
-                    match either {
-                        Either::Left(_) => {
+                    futures::select! {
+                        _ = shutdown_signal => {
                             debug!(
                                 target: LOG_TARGET,
                                 "Tor controller shut down because the shutdown signal was received"
                             );
                             break;
                         },
-                        Either::Right((Some(Ok(TorControlEvent::TorControlDisconnected)), shutdown_signal)) => {
-                            let event_tx = self
-                                .client
-                                .as_ref()
-                                .map(|c| c.event_sender().clone())
-                                .expect("HiddenServiceController::client was None");
-                            warn!(
-                                target: LOG_TARGET,
-                                "Tor control server disconnected. Attempting to reestablish connection..."
-                            );
-                            if let Err(err) = self.reestablish_hidden_service(event_tx, shutdown_signal).await {
+                        event = event_stream.next() => match event {
+                            Some(Ok(TorControlEvent::TorControlDisconnected)) => {
+                                let event_tx = self
+                                    .client
+                                    .as_ref()
+                                    .map(|c| c.event_sender().clone())
+                                    .expect("HiddenServiceController::client was None");
+                                warn!(
+                                    target: LOG_TARGET,
+                                    "Tor control server disconnected. Attempting to reestablish connection..."
+                                );
+                                if let Err(err) = self.reestablish_hidden_service(event_tx, &mut shutdown_signal).await {
+                                    error!(
+                                        target: LOG_TARGET,
+                                        "Failed to reestablish connection to tor control server because '{:?}'", err
+                                    );
+                                    break;
+                                }
+                            },
+                            Some(Ok(evt)) => {
+                                trace!(target: LOG_TARGET, "Tor control event: {:?}", evt);
+                            },
+                            _ => {},
+                        },
+                        _ = rotation_ticker.next() => {
+                            if let Err(err) = self.rotate_identity().await {
                                 error!(
                                     target: LOG_TARGET,
-                                    "Failed to reestablish connection to tor control server because '{:?}'", err
+                                    "Failed to rotate hidden service identity because '{:?}'", err
                                 );
-                                break;
                             }
                         },
-                        Either::Right((Some(Ok(evt)), _)) => {
-                            trace!(target: LOG_TARGET, "Tor control event: {:?}", evt);
-                        },
-                        _ => {},
                     }
                 }
             }
@@ -182,46 +226,99 @@ impl HiddenServiceController {
         Ok(())
     }
 
+    /// Reconnects to the tor control server and recreates all onion services. Reconnection is retried with an
+    /// increasing delay until it succeeds or the shutdown signal is triggered.
     async fn reestablish_hidden_service(
         &mut self,
         event_tx: broadcast::Sender<TorControlEvent>,
         shutdown_signal: &mut OptionalShutdownSignal,
     ) -> Result<(), HiddenServiceControllerError>
     {
-        let mut signal = Some(shutdown_signal);
+        let mut delay = MIN_RECONNECT_DELAY;
         loop {
             warn!(
                 target: LOG_TARGET,
                 "Attempting to reestablish control port connection at '{}'", self.control_server_addr
             );
-            let connect_fut = TorControlPortClient::connect(self.control_server_addr.clone(), event_tx.clone());
-            pin_mut!(connect_fut);
-            let either = future::select(connect_fut, signal.take().expect("signal was None")).await;
-            match either {
-                Either::Left((Ok(client), _)) => {
-                    self.client = Some(client);
-                    self.authenticate().await?;
-                    self.set_events().await?;
-                    let _ = self.create_hidden_service_from_identity().await;
+            let result = {
+                let reconnect_fut = self.reconnect(event_tx.clone());
+                pin_mut!(reconnect_fut);
+                match future::select(reconnect_fut, &mut *shutdown_signal).await {
+                    Either::Left((result, _)) => Some(result),
+                    Either::Right(_) => None,
+                }
+            };
+
+            match result {
+                Some(Ok(_)) => {
+                    info!(
+                        target: LOG_TARGET,
+                        "Connection to tor control server reestablished and onion services recreated"
+                    );
+                    let _ = self.event_tx.send(HiddenServiceEvent::Reestablished);
                     break Ok(());
                 },
-                Either::Left((Err(err), shutdown_signal)) => {
-                    signal = Some(shutdown_signal);
+                Some(Err(err)) => {
                     warn!(
                         target: LOG_TARGET,
                         "Failed to reestablish connection with tor control server because '{:?}'", err
                     );
-                    warn!(target: LOG_TARGET, "Will attempt again in 5 seconds...");
-                    time::delay_for(Duration::from_secs(5)).await;
+                    warn!(target: LOG_TARGET, "Will attempt again in {:.0?}...", delay);
+                    let delay_fut = time::delay_for(delay);
+                    if let Either::Right(_) = future::select(delay_fut, &mut *shutdown_signal).await {
+                        break Err(HiddenServiceControllerError::ShutdownSignalInterrupt);
+                    }
+                    delay = cmp::min(delay * 2, MAX_RECONNECT_DELAY);
                 },
-
-                Either::Right(_) => {
+                None => {
                     break Err(HiddenServiceControllerError::ShutdownSignalInterrupt);
                 },
             }
         }
     }
 
+    async fn reconnect(
+        &mut self,
+        event_tx: broadcast::Sender<TorControlEvent>,
+    ) -> Result<(), HiddenServiceControllerError>
+    {
+        let client = TorControlPortClient::connect(self.control_server_addr.clone(), event_tx).await?;
+        self.client = Some(client);
+        self.authenticate().await?;
+        self.set_events().await?;
+        self.create_onion_services().await?;
+        Ok(())
+    }
+
+    /// Replaces the primary onion service with one using a newly generated key. The previous service remains
+    /// reachable until the next rotation, giving peers time to learn the new address, after which it is removed.
+    async fn rotate_identity(&mut self) -> Result<(), HiddenServiceControllerError> {
+        for service_id in mem::replace(&mut self.retired_service_ids, Vec::new()) {
+            debug!(target: LOG_TARGET, "Removing retired onion service '{}'", service_id);
+            if let Err(err) = self.client_mut()?.del_onion(&service_id).await {
+                warn!(
+                    target: LOG_TARGET,
+                    "Failed to remove retired onion service '{}' because '{:?}'", service_id, err
+                );
+            }
+        }
+
+        let identity = self.add_new_onion(self.proxied_port_mapping).await?;
+        info!(
+            target: LOG_TARGET,
+            "Rotated hidden service identity. New service id is '{}'", identity.service_id
+        );
+        if let Some(old_identity) = self.identity.replace(identity.clone()) {
+            self.retired_service_ids.push(old_identity.service_id);
+        }
+        if let Some(shared_identity) = self.shared_identity.as_ref() {
+            *acquire_write_lock!(shared_identity) = identity.clone();
+        }
+
+        let _ = self.event_tx.send(HiddenServiceEvent::IdentityRotated(identity));
+        Ok(())
+    }
+
     fn client_mut(&mut self) -> Result<&mut TorControlPortClient, HiddenServiceControllerError> {
         self.client
             .as_mut()
@@ -287,46 +384,81 @@ impl HiddenServiceController {
         let socks_addr = self.get_socks_address().await?;
         debug!(target: LOG_TARGET, "Tor SOCKS address is '{}'", socks_addr);
 
+        let identity = self.create_onion_services().await?;
+        let shared_identity = self
+            .shared_identity
+            .get_or_insert_with(|| Arc::new(RwLock::new(identity)))
+            .clone();
+
+        let proxied_addr = socketaddr_to_multiaddr(self.proxied_port_mapping.proxied_address());
+        let additional_services = self
+            .additional_services
+            .iter()
+            .filter_map(|service| {
+                service
+                    .identity
+                    .clone()
+                    .map(|identity| (service.name.clone(), identity))
+            })
+            .collect();
+
+        Ok(HiddenService {
+            socks_addr,
+            socks_auth: self.socks_auth.clone(),
+            identity: shared_identity,
+            additional_services,
+            proxied_addr,
+            shutdown_signal: self.shutdown_signal.clone(),
+            event_tx: self.event_tx.clone(),
+        })
+    }
+
+    /// Creates the primary onion service followed by any additional onion services. Services are created from their
+    /// identities if they have one, otherwise a new identity is created for them. The primary identity is returned.
+    async fn create_onion_services(&mut self) -> Result<TorIdentity, HiddenServiceControllerError> {
         // Initialize a onion hidden service - either from the given private key or by creating a new one
-        match self.identity.take() {
+        let port_mapping = self.proxied_port_mapping;
+        let identity = match self.identity.clone() {
             Some(identity) => {
-                let resp = self.create_or_reuse_onion(&identity).await?;
-                self.identity = Some(TorIdentity {
+                let resp = self.create_or_reuse_onion(&identity, port_mapping).await?;
+                TorIdentity {
                     onion_port: resp.onion_port,
                     ..identity
-                });
-            },
-            None => {
-                let port_mapping = self.proxied_port_mapping;
-                let resp = self.client_mut()?.add_onion(vec![], port_mapping, None).await?;
-                let private_key = resp
-                    .private_key
-                    .clone()
-                    .expect("Tor server MUST return private key according to spec");
-
-                self.identity = Some(TorIdentity {
-                    private_key,
-                    service_id: resp.service_id,
-                    onion_port: resp.onion_port,
-                });
+                }
             },
+            None => self.add_new_onion(port_mapping).await?,
         };
-
-        let identity = self.identity.as_ref().map(Clone::clone).expect("already checked");
         debug!(
             target: LOG_TARGET,
             "Added hidden service with service id '{}' on port '{}'", identity.service_id, identity.onion_port
         );
+        self.identity = Some(identity.clone());
+
+        for i in 0..self.additional_services.len() {
+            let service = self.additional_services[i].clone();
+            let service_identity = match service.identity {
+                Some(service_identity) => {
+                    let resp = self
+                        .create_or_reuse_onion(&service_identity, service.port_mapping)
+                        .await?;
+                    TorIdentity {
+                        onion_port: resp.onion_port,
+                        ..service_identity
+                    }
+                },
+                None => self.add_new_onion(service.port_mapping).await?,
+            };
+            debug!(
+                target: LOG_TARGET,
+                "Added '{}' hidden service with service id '{}' on port '{}'",
+                service.name,
+                service_identity.service_id,
+                service_identity.onion_port
+            );
+            self.additional_services[i].identity = Some(service_identity);
+        }
 
-        let proxied_addr = socketaddr_to_multiaddr(self.proxied_port_mapping.proxied_address());
-
-        Ok(HiddenService {
-            socks_addr,
-            socks_auth: self.socks_auth.clone(),
-            identity,
-            proxied_addr,
-            shutdown_signal: self.shutdown_signal.clone(),
-        })
+        Ok(identity)
     }
 
     pub fn set_proxied_addr(&mut self, addr: Multiaddr) {
@@ -335,9 +467,24 @@ impl HiddenServiceController {
         )
     }
 
+    /// Creates an onion service using a new key
+    async fn add_new_onion(&mut self, port_mapping: PortMapping) -> Result<TorIdentity, HiddenServiceControllerError> {
+        let resp = self.client_mut()?.add_onion(vec![], port_mapping, None).await?;
+        let private_key = resp
+            .private_key
+            .expect("Tor server MUST return private key according to spec");
+
+        Ok(TorIdentity {
+            private_key,
+            service_id: resp.service_id,
+            onion_port: resp.onion_port,
+        })
+    }
+
     async fn create_or_reuse_onion(
         &mut self,
         identity: &TorIdentity,
+        port_mapping: PortMapping,
     ) -> Result<AddOnionResponse, HiddenServiceControllerError>
     {
         let mut flags = Vec::new();
@@ -345,8 +492,6 @@ impl HiddenServiceController {
             flags.push(AddOnionFlag::Detach);
         }
 
-        let port_mapping = self.proxied_port_mapping;
-
         let client = self.client_mut()?;
 
         loop {
@@ -382,3 +527,114 @@ impl HiddenServiceController {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        runtime,
+        tor::{
+            control_client::test_server::{self, canned_responses},
+            HiddenServiceBuilder,
+            PrivateKey,
+        },
+    };
+    use tari_test_utils::unpack_enum;
+
+    const CANNED_SERVICE_ID: &str = "qigbgbs4ue3ghbupsotgh73cmmkjrin2aprlyxsrnrvpmcmzy3g4wbid";
+
+    fn builder(control_addr: Multiaddr) -> HiddenServiceBuilder {
+        HiddenServiceBuilder::new()
+            .with_control_server_address(control_addr)
+            .with_socks_address_override(Some("/ip4/127.0.0.1/tcp/9050".parse().unwrap()))
+            .with_port_mapping((18141u16, "127.0.0.1:18141".parse::<SocketAddr>().unwrap()))
+    }
+
+    async fn next_event(events: &mut broadcast::Receiver<HiddenServiceEvent>) -> HiddenServiceEvent {
+        time::timeout(Duration::from_secs(10), events.recv())
+            .await
+            .unwrap()
+            .unwrap()
+    }
+
+    #[runtime::test_basic]
+    async fn reestablish_after_disconnect() {
+        let (addr, state) = test_server::spawn_tcp().await;
+        state
+            .set_command_response("ADD_ONION", canned_responses::ADD_ONION_OK)
+            .await;
+
+        let hidden_service = builder(addr)
+            .with_additional_services(vec![OnionServiceConfig {
+                name: "wallet".to_string(),
+                port_mapping: (18142u16, "127.0.0.1:18143".parse::<SocketAddr>().unwrap()).into(),
+                identity: None,
+            }])
+            .build()
+            .await
+            .unwrap()
+            .create_hidden_service()
+            .await
+            .unwrap();
+
+        assert_eq!(hidden_service.service_id(), CANNED_SERVICE_ID);
+        assert_eq!(hidden_service.additional_services().len(), 1);
+        assert_eq!(hidden_service.additional_services()[0].0, "wallet");
+        let requests = state.take_requests().await;
+        assert_eq!(requests.iter().filter(|r| r.starts_with("ADD_ONION")).count(), 2);
+        assert!(requests.iter().any(|r| r.contains("Port=18142,127.0.0.1:18143")));
+
+        let mut events = hidden_service.subscribe_events();
+        state.hang_up().await;
+        let event = next_event(&mut events).await;
+        assert!(matches!(event, HiddenServiceEvent::Reestablished));
+
+        assert_eq!(state.num_connections(), 2);
+        let requests = state.take_requests().await;
+        assert_eq!(requests[0], "AUTHENTICATE");
+        // Both services are recreated from their identities
+        assert_eq!(
+            requests
+                .iter()
+                .filter(|r| r.starts_with("ADD_ONION ED25519-V3:"))
+                .count(),
+            2
+        );
+    }
+
+    #[runtime::test_basic]
+    async fn rotate_identity() {
+        let (addr, state) = test_server::spawn_tcp().await;
+        state
+            .set_command_response("ADD_ONION", canned_responses::ADD_ONION_OK)
+            .await;
+        let initial_service_id = "mochz2xppfziim5olr5f6q27poc4vfob2xxxxxxxxxxxxxxxxxxxxxxx";
+
+        let hidden_service = builder(addr)
+            .with_tor_identity(TorIdentity {
+                private_key: PrivateKey::Ed25519V3("dummy".to_string()),
+                service_id: initial_service_id.to_string(),
+                onion_port: 18141,
+            })
+            .with_key_rotation_interval(Duration::from_millis(100))
+            .build()
+            .await
+            .unwrap()
+            .create_hidden_service()
+            .await
+            .unwrap();
+        assert_eq!(hidden_service.service_id(), initial_service_id);
+        let mut events = hidden_service.subscribe_events();
+
+        unpack_enum!(HiddenServiceEvent::IdentityRotated(identity) = next_event(&mut events).await);
+        assert_eq!(identity.service_id, CANNED_SERVICE_ID);
+        assert_eq!(hidden_service.service_id(), CANNED_SERVICE_ID);
+        let requests = state.take_requests().await;
+        assert!(requests.iter().all(|r| !r.starts_with("DEL_ONION")));
+
+        // The previous service is removed on the following rotation
+        unpack_enum!(HiddenServiceEvent::IdentityRotated(_identity) = next_event(&mut events).await);
+        let requests = state.take_requests().await;
+        assert!(requests.contains(&format!("DEL_ONION {}", initial_service_id)));
+    }
+}
@@ -27,20 +27,26 @@ mod controller;
 use crate::{
     multiaddr::Multiaddr,
     socks,
-    tor::{PrivateKey, TorClientError},
+    tor::{PortMapping, PrivateKey, TorClientError},
     transports::{SocksConfig, SocksTransport},
 };
 pub use controller::{HiddenServiceController, HiddenServiceControllerError};
 use serde_derive::{Deserialize, Serialize};
-use std::fmt;
+use std::{
+    fmt,
+    sync::{Arc, RwLock},
+};
 use tari_shutdown::OptionalShutdownSignal;
+use tokio::sync::broadcast;
 
 /// Handle for a Tor Hidden Service. This handle keeps the session to the Tor control port alive.
 /// Once this is dropped, the hidden service will cease to be accessible.
 #[derive(Clone)]
 pub struct HiddenService {
-    /// The identity of the hidden service
-    pub(super) identity: TorIdentity,
+    /// The identity of the hidden service. This changes if the identity is rotated.
+    pub(super) identity: Arc<RwLock<TorIdentity>>,
+    /// The names and identities of the additional onion services
+    pub(super) additional_services: Vec<(String, TorIdentity)>,
     /// The SOCKS5 address obtained by querying the Tor control port and used to configure the `SocksTransport`.
     pub(super) socks_addr: Multiaddr,
     /// SOCKS5 authentication details used to configure the `SocksTransport`.
@@ -49,17 +55,20 @@ pub struct HiddenService {
     pub(super) proxied_addr: Multiaddr,
     /// Shutdown signal for hidden service
     pub(super) shutdown_signal: OptionalShutdownSignal,
+    /// Publishes identity rotation and reconnection events
+    pub(super) event_tx: broadcast::Sender<HiddenServiceEvent>,
 }
 
 impl HiddenService {
     pub fn get_onion_address(&self) -> Multiaddr {
         // service_id should always come from the tor control server, so the length can be relied on
-        multiaddr_from_service_id_and_port(self.service_id(), self.identity.onion_port)
+        self.tor_identity()
+            .try_get_onion_address()
             .expect("failed to create onion address from HiddenService service_id and onion_port")
     }
 
-    pub fn service_id(&self) -> &str {
-        &self.identity.service_id
+    pub fn service_id(&self) -> String {
+        acquire_read_lock!(self.identity).service_id.clone()
     }
 
     pub fn proxied_address(&self) -> &Multiaddr {
@@ -73,11 +82,45 @@ impl HiddenService {
         })
     }
 
-    pub fn tor_identity(&self) -> &TorIdentity {
-        &self.identity
+    /// Returns the current identity of the primary hidden service
+    pub fn tor_identity(&self) -> TorIdentity {
+        acquire_read_lock!(self.identity).clone()
+    }
+
+    /// Returns the name and identity of each additional onion service
+    pub fn additional_services(&self) -> &[(String, TorIdentity)] {
+        &self.additional_services
+    }
+
+    /// Subscribe to hidden service events. An `IdentityRotated` event is published when the primary onion service is
+    /// moved to a new address, which should then be advertised to the network.
+    pub fn subscribe_events(&self) -> broadcast::Receiver<HiddenServiceEvent> {
+        self.event_tx.subscribe()
     }
 }
 
+/// Events published by the hidden service controller
+#[derive(Debug, Clone)]
+pub enum HiddenServiceEvent {
+    /// The key of the primary onion service was rotated. The service is now reachable at the onion address of the
+    /// given identity.
+    IdentityRotated(TorIdentity),
+    /// The connection to the tor control server was lost, then reestablished and all onion services recreated
+    Reestablished,
+}
+
+/// An onion service created in addition to the primary comms onion service, for example to expose a wallet-only
+/// service on a separate onion address.
+#[derive(Debug, Clone)]
+pub struct OnionServiceConfig {
+    /// A name used to identify this service
+    pub name: String,
+    /// Maps the onion port to the (usually local) address that traffic is forwarded to
+    pub port_mapping: PortMapping,
+    /// The identity of the service. A new identity is created if this is `None`.
+    pub identity: Option<TorIdentity>,
+}
+
 fn multiaddr_from_service_id_and_port(service_id: &str, onion_port: u16) -> Result<Multiaddr, TorClientError> {
     const ONION_V2_LEN: usize = 16;
     const ONION_V3_LEN: usize = 56;
@@ -48,6 +48,8 @@ pub use hidden_service::{
     HiddenServiceBuilderError,
     HiddenServiceController,
     HiddenServiceControllerError,
+    HiddenServiceEvent,
     HsFlags,
+    OnionServiceConfig,
     TorIdentity,
 };
@@ -32,6 +32,9 @@ mod dns;
 mod memory;
 pub use memory::MemoryTransport;
 
+mod quic;
+pub use quic::{QuicConnection, QuicListener, QuicStream, QuicTransport};
+
 mod socks;
 pub use socks::{SocksConfig, SocksTransport};
 
@@ -41,6 +44,9 @@ pub use tcp::{TcpSocket, TcpTransport};
 mod tcp_with_tor;
 pub use tcp_with_tor::TcpWithTorTransport;
 
+mod websocket;
+pub use websocket::{WebSocketStream, WebSocketTransport, WsOrTcpSocket};
+
 pub trait Transport {
     /// The output of the transport after a connection is established
     type Output;
@@ -0,0 +1,424 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! QUIC transport. Listens on and dials `/ip4/.../udp/.../quic` and `/ip6/.../udp/.../quic` addresses. Unlike the other
+//! transports, a QUIC connection is natively multiplexed, so it does not implement [Transport](super::Transport) and is
+//! not upgraded with Yamux. Instead, peers authenticate using a Noise handshake on the first stream of the connection
+//! and all further substreams are QUIC streams.
+//!
+//! The TLS layer of QUIC uses a self-signed certificate generated when the listener is started. Certificates are not
+//! validated by the dialer; peer authentication is left to the Noise handshake which is bound to the TLS session
+//! using the listener's certificate (see [QuicConnection::channel_binding]).
+
+use crate::multiaddr::{Multiaddr, Protocol};
+use futures::{future::BoxFuture, ready, AsyncRead, AsyncWrite, FutureExt, Stream, StreamExt};
+use log::*;
+use std::{
+    fmt,
+    io,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::Duration,
+};
+
+const LOG_TARGET: &str = "comms::transports::quic";
+
+/// The ALPN protocol negotiated on all tari QUIC connections
+const ALPN_PROTOCOL: &[u8] = b"/tari/comms/quic/1";
+/// The server name used by the dialer. Certificates are self-signed so this value is only used to satisfy the TLS
+/// handshake.
+const SERVER_NAME: &str = "tari";
+/// Keep-alive packets are sent at this interval so that idle peer connections are not timed out
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Transport implementation for QUIC over UDP
+#[derive(Clone, Default)]
+pub struct QuicTransport {
+    client_endpoint_v4: Arc<Mutex<Option<quinn::Endpoint>>>,
+    client_endpoint_v6: Arc<Mutex<Option<quinn::Endpoint>>>,
+}
+
+impl QuicTransport {
+    /// Create a new QuicTransport
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Returns true if the given address is a QUIC address i.e. `/ip4/.../udp/.../quic` or `/ip6/.../udp/.../quic`
+    pub fn is_quic_address(addr: &Multiaddr) -> bool {
+        quic_multiaddr_to_socketaddr(addr).is_ok()
+    }
+
+    /// Listen for QUIC connections on the given address. The listener and the address it is bound to are returned.
+    pub async fn listen(&self, addr: Multiaddr) -> io::Result<(QuicListener, Multiaddr)> {
+        let socket_addr = quic_multiaddr_to_socketaddr(&addr)?;
+
+        let cert = rcgen::generate_simple_self_signed(vec![SERVER_NAME.to_string()]).map_err(to_io_error)?;
+        let cert_der = cert.serialize_der().map_err(to_io_error)?;
+        let key_der = cert.serialize_private_key_der();
+
+        let mut server_config = quinn::ServerConfig::default();
+        server_config.transport = new_transport_config();
+        let mut server_config = quinn::ServerConfigBuilder::new(server_config);
+        server_config
+            .protocols(&[ALPN_PROTOCOL])
+            .certificate(
+                quinn::CertificateChain::from_certs(Some(
+                    quinn::Certificate::from_der(&cert_der).map_err(to_io_error)?,
+                )),
+                quinn::PrivateKey::from_der(&key_der).map_err(to_io_error)?,
+            )
+            .map_err(to_io_error)?;
+
+        let mut builder = quinn::Endpoint::builder();
+        builder.listen(server_config.build());
+        let (endpoint, incoming) = builder.bind(&socket_addr).map_err(to_io_error)?;
+        let local_addr = socketaddr_to_quic_multiaddr(&endpoint.local_addr()?);
+        debug!(target: LOG_TARGET, "QUIC listener bound to '{}'", local_addr);
+
+        Ok((
+            QuicListener {
+                endpoint,
+                incoming,
+                channel_binding: Arc::new(cert_der),
+            },
+            local_addr,
+        ))
+    }
+
+    /// Connect to the QUIC listener at the given address
+    pub async fn dial(&self, addr: Multiaddr) -> io::Result<QuicConnection> {
+        let socket_addr = quic_multiaddr_to_socketaddr(&addr)?;
+        let endpoint = self.client_endpoint(&socket_addr)?;
+
+        let verifier = Arc::new(CapturingCertVerifier::default());
+        let mut client_config = quinn::ClientConfigBuilder::default();
+        client_config.protocols(&[ALPN_PROTOCOL]);
+        let mut client_config = client_config.build();
+        client_config.transport = new_transport_config();
+        Arc::make_mut(&mut client_config.crypto)
+            .dangerous()
+            .set_certificate_verifier(verifier.clone());
+
+        let new_connection = endpoint
+            .connect_with(client_config, &socket_addr, SERVER_NAME)
+            .map_err(to_io_error)?
+            .await
+            .map_err(to_io_error)?;
+
+        let channel_binding = verifier.take_certificate().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::ConnectionRefused,
+                "QUIC listener did not present a certificate",
+            )
+        })?;
+
+        Ok(QuicConnection::new(new_connection, channel_binding, addr))
+    }
+
+    /// Returns the endpoint used for outbound connections to the address family of `remote_addr`, binding it on first
+    /// use
+    fn client_endpoint(&self, remote_addr: &SocketAddr) -> io::Result<quinn::Endpoint> {
+        let (client_endpoint, unspecified) = match remote_addr {
+            SocketAddr::V4(_) => (&self.client_endpoint_v4, IpAddr::V4(Ipv4Addr::UNSPECIFIED)),
+            SocketAddr::V6(_) => (&self.client_endpoint_v6, IpAddr::V6(Ipv6Addr::UNSPECIFIED)),
+        };
+        let mut lock = client_endpoint
+            .lock()
+            .map_err(|_| io::Error::new(io::ErrorKind::Other, "QUIC client endpoint lock poisoned"))?;
+        match lock.as_ref() {
+            Some(endpoint) => Ok(endpoint.clone()),
+            None => {
+                let (endpoint, _) = quinn::Endpoint::builder()
+                    .bind(&SocketAddr::new(unspecified, 0))
+                    .map_err(to_io_error)?;
+                *lock = Some(endpoint.clone());
+                Ok(endpoint)
+            },
+        }
+    }
+}
+
+/// A stream of inbound QUIC connections. Each item is a future that resolves once the QUIC handshake has completed,
+/// along with the address of the remote peer.
+pub struct QuicListener {
+    // Held so that the endpoint remains open for as long as the listener
+    #[allow(dead_code)]
+    endpoint: quinn::Endpoint,
+    incoming: quinn::Incoming,
+    channel_binding: Arc<Vec<u8>>,
+}
+
+impl Stream for QuicListener {
+    type Item = (BoxFuture<'static, io::Result<QuicConnection>>, Multiaddr);
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        match ready!(self.incoming.poll_next_unpin(cx)) {
+            Some(connecting) => {
+                let peer_addr = socketaddr_to_quic_multiaddr(&connecting.remote_address());
+                let channel_binding = (*self.channel_binding).clone();
+                let addr = peer_addr.clone();
+                let fut = async move {
+                    let new_connection = connecting.await.map_err(to_io_error)?;
+                    Ok(QuicConnection::new(new_connection, channel_binding, addr))
+                }
+                .boxed();
+                Poll::Ready(Some((fut, peer_addr)))
+            },
+            None => Poll::Ready(None),
+        }
+    }
+}
+
+/// An established QUIC connection
+pub struct QuicConnection {
+    connection: quinn::Connection,
+    incoming: quinn::IncomingBiStreams,
+    channel_binding: Vec<u8>,
+    remote_address: Multiaddr,
+}
+
+impl QuicConnection {
+    fn new(new_connection: quinn::NewConnection, channel_binding: Vec<u8>, remote_address: Multiaddr) -> Self {
+        let quinn::NewConnection {
+            connection, bi_streams, ..
+        } = new_connection;
+        Self {
+            connection,
+            incoming: bi_streams,
+            channel_binding,
+            remote_address,
+        }
+    }
+
+    /// The address of the remote peer
+    pub fn remote_address(&self) -> &Multiaddr {
+        &self.remote_address
+    }
+
+    /// The DER-encoded certificate presented by the listening side of this connection. Both sides of the connection
+    /// agree on this value, so a handshake that includes it cannot be relayed to a different QUIC connection.
+    pub fn channel_binding(&self) -> &[u8] {
+        &self.channel_binding
+    }
+
+    /// Open a new bidirectional stream to the remote
+    pub async fn open_stream(&mut self) -> io::Result<QuicStream> {
+        let (send, recv) = self.connection.open_bi().await.map_err(to_io_error)?;
+        Ok(QuicStream::new(send, recv))
+    }
+
+    /// Wait for the remote to open a bidirectional stream
+    pub async fn accept_stream(&mut self) -> io::Result<QuicStream> {
+        match self.incoming.next().await {
+            Some(Ok((send, recv))) => Ok(QuicStream::new(send, recv)),
+            Some(Err(err)) => Err(to_io_error(err)),
+            None => Err(io::ErrorKind::ConnectionAborted.into()),
+        }
+    }
+
+    /// Close the connection immediately
+    pub fn close(&self) {
+        self.connection.close(quinn::VarInt::from_u32(0), b"");
+    }
+
+    pub(crate) fn into_parts(self) -> (quinn::Connection, quinn::IncomingBiStreams) {
+        (self.connection, self.incoming)
+    }
+}
+
+/// A bidirectional QUIC stream. Closing the stream finishes the sending side.
+pub struct QuicStream {
+    send: quinn::SendStream,
+    recv: quinn::RecvStream,
+}
+
+impl fmt::Debug for QuicStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("QuicStream").field("id", &self.send.id()).finish()
+    }
+}
+
+impl QuicStream {
+    pub(crate) fn new(send: quinn::SendStream, recv: quinn::RecvStream) -> Self {
+        Self { send, recv }
+    }
+}
+
+impl AsyncRead for QuicStream {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.recv).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for QuicStream {
+    fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.send).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.send).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.send).poll_close(cx)
+    }
+}
+
+/// Accepts any certificate presented by the listener and keeps a copy of it for use as the channel binding
+#[derive(Default)]
+struct CapturingCertVerifier {
+    certificate: Mutex<Option<Vec<u8>>>,
+}
+
+impl CapturingCertVerifier {
+    fn take_certificate(&self) -> Option<Vec<u8>> {
+        self.certificate.lock().ok().and_then(|mut cert| cert.take())
+    }
+}
+
+impl rustls::ServerCertVerifier for CapturingCertVerifier {
+    fn verify_server_cert(
+        &self,
+        _roots: &rustls::RootCertStore,
+        presented_certs: &[rustls::Certificate],
+        _dns_name: webpki::DNSNameRef<'_>,
+        _ocsp_response: &[u8],
+    ) -> Result<rustls::ServerCertVerified, rustls::TLSError>
+    {
+        let cert = presented_certs
+            .first()
+            .ok_or(rustls::TLSError::NoCertificatesPresented)?;
+        if let Ok(mut lock) = self.certificate.lock() {
+            *lock = Some(cert.0.clone());
+        }
+        Ok(rustls::ServerCertVerified::assertion())
+    }
+}
+
+fn new_transport_config() -> Arc<quinn::TransportConfig> {
+    let mut config = quinn::TransportConfig::default();
+    config.keep_alive_interval(Some(KEEP_ALIVE_INTERVAL));
+    Arc::new(config)
+}
+
+fn to_io_error<E>(err: E) -> io::Error
+where E: std::error::Error + Send + Sync + 'static {
+    io::Error::new(io::ErrorKind::Other, err)
+}
+
+/// Convert a `/ip4/.../udp/.../quic` or `/ip6/.../udp/.../quic` multiaddr to a socket address
+fn quic_multiaddr_to_socketaddr(addr: &Multiaddr) -> io::Result<SocketAddr> {
+    let invalid = || io::Error::new(io::ErrorKind::InvalidInput, format!("'{}' is not a QUIC address", addr));
+    let mut iter = addr.iter();
+    let ip: IpAddr = match iter.next() {
+        Some(Protocol::Ip4(ip)) => ip.into(),
+        Some(Protocol::Ip6(ip)) => ip.into(),
+        _ => return Err(invalid()),
+    };
+    let port = match iter.next() {
+        Some(Protocol::Udp(port)) => port,
+        _ => return Err(invalid()),
+    };
+    match (iter.next(), iter.next()) {
+        (Some(Protocol::Quic), None) => Ok(SocketAddr::new(ip, port)),
+        _ => Err(invalid()),
+    }
+}
+
+fn socketaddr_to_quic_multiaddr(socket_addr: &SocketAddr) -> Multiaddr {
+    let mut addr: Multiaddr = match socket_addr.ip() {
+        IpAddr::V4(addr) => Protocol::Ip4(addr).into(),
+        IpAddr::V6(addr) => Protocol::Ip6(addr).into(),
+    };
+    addr.push(Protocol::Udp(socket_addr.port()));
+    addr.push(Protocol::Quic);
+    addr
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::runtime;
+    use futures::{future, AsyncReadExt, AsyncWriteExt};
+
+    #[test]
+    fn is_quic_address() {
+        assert!(QuicTransport::is_quic_address(
+            &"/ip4/127.0.0.1/udp/1234/quic".parse().unwrap()
+        ));
+        assert!(QuicTransport::is_quic_address(
+            &"/ip6/::1/udp/1234/quic".parse().unwrap()
+        ));
+        assert!(!QuicTransport::is_quic_address(
+            &"/ip4/127.0.0.1/udp/1234".parse().unwrap()
+        ));
+        assert!(!QuicTransport::is_quic_address(
+            &"/ip4/127.0.0.1/tcp/1234".parse().unwrap()
+        ));
+        assert!(!QuicTransport::is_quic_address(
+            &"/ip4/127.0.0.1/udp/1234/quic/ws".parse().unwrap()
+        ));
+    }
+
+    async fn dial_and_listen_on(transport: &QuicTransport, listen_addr: &str) {
+        let (mut listener, addr) = transport.listen(listen_addr.parse().unwrap()).await.unwrap();
+
+        let (inbound, outbound) = future::join(
+            async move {
+                let (inbound, _) = listener.next().await.unwrap();
+                let mut conn = inbound.await.unwrap();
+                let mut stream = conn.accept_stream().await.unwrap();
+                let mut buf = [0u8; 5];
+                stream.read_exact(&mut buf).await.unwrap();
+                assert_eq!(&buf, b"HELLO");
+                conn
+            },
+            async move {
+                let mut conn = transport.dial(addr).await.unwrap();
+                let mut stream = conn.open_stream().await.unwrap();
+                stream.write_all(b"HELLO").await.unwrap();
+                stream.close().await.unwrap();
+                conn
+            },
+        )
+        .await;
+
+        assert!(!inbound.channel_binding().is_empty());
+        assert_eq!(inbound.channel_binding(), outbound.channel_binding());
+    }
+
+    #[runtime::test_basic]
+    async fn dial_and_listen() {
+        dial_and_listen_on(&QuicTransport::new(), "/ip4/127.0.0.1/udp/0/quic").await;
+    }
+
+    #[runtime::test_basic]
+    async fn dial_and_listen_ip6() {
+        // The same transport dials both address families
+        let transport = QuicTransport::new();
+        dial_and_listen_on(&transport, "/ip4/127.0.0.1/udp/0/quic").await;
+        dial_and_listen_on(&transport, "/ip6/::1/udp/0/quic").await;
+    }
+}
@@ -0,0 +1,506 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! WebSocket transport. Listens on and dials `/ip4/.../tcp/.../ws` addresses, framing the byte stream in binary
+//! WebSocket messages so that the Noise and Yamux upgrades work unchanged on top of it.
+
+use super::{tcp::TcpInbound, TcpSocket, TcpTransport, Transport};
+use futures::{
+    future::BoxFuture,
+    ready,
+    stream,
+    stream::{BoxStream, IntoAsyncRead},
+    AsyncRead,
+    AsyncWrite,
+    Future,
+    FutureExt,
+    Stream,
+    StreamExt,
+    TryStreamExt,
+};
+use multiaddr::{Multiaddr, Protocol};
+use soketto::{connection, handshake};
+use std::{
+    borrow::Cow,
+    fmt,
+    io,
+    mem,
+    pin::Pin,
+    sync::{Mutex, PoisonError},
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::time;
+
+const DEFAULT_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+/// The maximum size of a WebSocket message. Received messages that are larger close the connection and writes are
+/// split into messages of at most this size.
+const MAX_MESSAGE_SIZE: usize = 1024 * 1024;
+
+/// Transport implementation for WebSockets over TCP. Addresses that end in `/ws` are dialed as WebSockets, any other
+/// TCP/IP or DNS address is dialed as a plain TCP connection so that peers that do not use WebSockets remain
+/// contactable. The listener only accepts WebSocket connections.
+#[derive(Clone)]
+pub struct WebSocketTransport {
+    tcp_transport: TcpTransport,
+    handshake_timeout: Duration,
+}
+
+impl WebSocketTransport {
+    #[doc("Sets the maximum time allowed to complete the WebSocket handshake.")]
+    setter_mut!(set_handshake_timeout, handshake_timeout, Duration);
+
+    /// Create a new WebSocketTransport
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn tcp_transport_mut(&mut self) -> &mut TcpTransport {
+        &mut self.tcp_transport
+    }
+}
+
+impl Default for WebSocketTransport {
+    fn default() -> Self {
+        Self {
+            tcp_transport: TcpTransport::new(),
+            handshake_timeout: DEFAULT_HANDSHAKE_TIMEOUT,
+        }
+    }
+}
+
+impl Transport for WebSocketTransport {
+    type DialFuture = BoxFuture<'static, io::Result<Self::Output>>;
+    type Error = io::Error;
+    type Inbound = BoxFuture<'static, io::Result<Self::Output>>;
+    type Listener = WebSocketInbound;
+    type Output = WsOrTcpSocket;
+
+    type ListenFuture = impl Future<Output = io::Result<(Self::Listener, Multiaddr)>>;
+
+    fn listen(&self, addr: Multiaddr) -> Result<Self::ListenFuture, Self::Error> {
+        let (tcp_addr, path) = split_ws_address(&addr).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("'{}' is not a WebSocket address", addr),
+            )
+        })?;
+        let listen_fut = self.tcp_transport.listen(tcp_addr)?;
+        let handshake_timeout = self.handshake_timeout;
+
+        Ok(Box::pin(async move {
+            let (inbound, local_addr) = listen_fut.await?;
+            Ok((
+                WebSocketInbound::new(inbound, handshake_timeout),
+                local_addr.with(Protocol::Ws(Cow::Owned(path))),
+            ))
+        }))
+    }
+
+    fn dial(&self, addr: Multiaddr) -> Result<Self::DialFuture, Self::Error> {
+        match split_ws_address(&addr) {
+            Some((tcp_addr, path)) => {
+                let host = host_header(&tcp_addr).ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("'{}' is not a TCP/IP or DNS WebSocket address", addr),
+                    )
+                })?;
+                let dial_fut = self.tcp_transport.dial(tcp_addr)?;
+                let handshake_timeout = self.handshake_timeout;
+                Ok(async move {
+                    let socket = dial_fut.await?;
+                    let stream = time::timeout(handshake_timeout, client_handshake(socket, host, path))
+                        .await
+                        .map_err(|_| handshake_timed_out())??;
+                    Ok(WsOrTcpSocket::WebSocket(stream))
+                }
+                .boxed())
+            },
+            None => {
+                let dial_fut = self.tcp_transport.dial(addr)?;
+                Ok(async move { Ok(WsOrTcpSocket::Tcp(dial_fut.await?)) }.boxed())
+            },
+        }
+    }
+}
+
+/// Wraps the TCP listener stream, performing the server WebSocket handshake on each inbound connection
+pub struct WebSocketInbound {
+    inner: TcpInbound,
+    handshake_timeout: Duration,
+}
+
+impl WebSocketInbound {
+    pub fn new(inner: TcpInbound, handshake_timeout: Duration) -> Self {
+        Self {
+            inner,
+            handshake_timeout,
+        }
+    }
+}
+
+impl Stream for WebSocketInbound {
+    type Item = io::Result<(BoxFuture<'static, io::Result<WsOrTcpSocket>>, Multiaddr)>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        match ready!(Pin::new(&mut self.inner).poll_next(cx)) {
+            Some(Ok((socket_fut, peer_addr))) => {
+                let handshake_timeout = self.handshake_timeout;
+                let fut = async move {
+                    let socket = socket_fut.await?;
+                    let stream = time::timeout(handshake_timeout, server_handshake(socket))
+                        .await
+                        .map_err(|_| handshake_timed_out())??;
+                    Ok(WsOrTcpSocket::WebSocket(stream))
+                };
+                Poll::Ready(Some(Ok((
+                    fut.boxed(),
+                    peer_addr.with(Protocol::Ws(Cow::Borrowed("/"))),
+                ))))
+            },
+            Some(Err(err)) => Poll::Ready(Some(Err(err))),
+            None => Poll::Ready(None),
+        }
+    }
+}
+
+/// The output of the WebSocketTransport. This is a WebSocket stream when connected to a `/ws` address or a plain TCP
+/// socket otherwise.
+pub enum WsOrTcpSocket {
+    WebSocket(WebSocketStream<TcpSocket>),
+    Tcp(TcpSocket),
+}
+
+impl AsyncRead for WsOrTcpSocket {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            WsOrTcpSocket::WebSocket(stream) => Pin::new(stream).poll_read(cx, buf),
+            WsOrTcpSocket::Tcp(socket) => Pin::new(socket).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for WsOrTcpSocket {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            WsOrTcpSocket::WebSocket(stream) => Pin::new(stream).poll_write(cx, buf),
+            WsOrTcpSocket::Tcp(socket) => Pin::new(socket).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            WsOrTcpSocket::WebSocket(stream) => Pin::new(stream).poll_flush(cx),
+            WsOrTcpSocket::Tcp(socket) => Pin::new(socket).poll_flush(cx),
+        }
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            WsOrTcpSocket::WebSocket(stream) => Pin::new(stream).poll_close(cx),
+            WsOrTcpSocket::Tcp(socket) => Pin::new(socket).poll_close(cx),
+        }
+    }
+}
+
+type WriteFuture<T> = BoxFuture<'static, (connection::Sender<T>, io::Result<()>)>;
+
+enum WriteState<T> {
+    Ready(connection::Sender<T>),
+    Pending(WriteFuture<T>),
+    Closing(BoxFuture<'static, io::Result<()>>),
+    Closed,
+}
+
+struct WebSocketStreamInner<T> {
+    reader: IntoAsyncRead<BoxStream<'static, io::Result<Vec<u8>>>>,
+    writer: WriteState<T>,
+}
+
+/// A byte stream over a WebSocket connection. Each write is sent as a binary WebSocket message and the payloads of
+/// received messages are read back as a contiguous byte stream.
+pub struct WebSocketStream<T> {
+    // The futures held by the stream are Send but not Sync. The Mutex is never locked, it only makes the stream Sync
+    // as required by the comms stack. All access is through `Mutex::get_mut`.
+    inner: Mutex<WebSocketStreamInner<T>>,
+}
+
+impl<T> WebSocketStream<T>
+where T: AsyncRead + AsyncWrite + Unpin + Send + 'static
+{
+    fn new(builder: connection::Builder<T>) -> Self {
+        let (sender, mut receiver) = builder.finish();
+        receiver.set_max_message_size(MAX_MESSAGE_SIZE);
+        Self {
+            inner: Mutex::new(WebSocketStreamInner {
+                reader: Self::receiver_byte_stream(receiver).into_async_read(),
+                writer: WriteState::Ready(sender),
+            }),
+        }
+    }
+
+    fn receiver_byte_stream(receiver: connection::Receiver<T>) -> BoxStream<'static, io::Result<Vec<u8>>> {
+        stream::unfold(Some(receiver), |receiver| async move {
+            let mut receiver = receiver?;
+            loop {
+                let mut buf = Vec::new();
+                match receiver.receive_data(&mut buf).await {
+                    // An empty chunk would be read as EOF
+                    Ok(_) if buf.is_empty() => continue,
+                    Ok(_) => return Some((Ok(buf), Some(receiver))),
+                    Err(connection::Error::Closed) => return None,
+                    Err(err) => return Some((Err(to_io_error(err)), None)),
+                }
+            }
+        })
+        .boxed()
+    }
+
+    fn inner_mut(&mut self) -> &mut WebSocketStreamInner<T> {
+        self.inner.get_mut().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+// The stream is never pinned structurally, all inner state is boxed or owned
+impl<T> Unpin for WebSocketStream<T> {}
+
+impl<T> WebSocketStreamInner<T>
+where T: AsyncRead + AsyncWrite + Unpin + Send + 'static
+{
+    /// Drives any pending write to completion
+    fn poll_writer_ready(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match &mut self.writer {
+            WriteState::Ready(_) => Poll::Ready(Ok(())),
+            WriteState::Pending(fut) => {
+                let (sender, result) = ready!(fut.as_mut().poll(cx));
+                self.writer = WriteState::Ready(sender);
+                Poll::Ready(result)
+            },
+            WriteState::Closing(_) | WriteState::Closed => Poll::Ready(Err(io::ErrorKind::WriteZero.into())),
+        }
+    }
+
+    /// Starts a new write on the sender. `poll_writer_ready` must have returned Ready(Ok) before this is called.
+    fn start_write<F>(&mut self, f: F)
+    where F: FnOnce(connection::Sender<T>) -> WriteFuture<T> {
+        if let WriteState::Ready(sender) = mem::replace(&mut self.writer, WriteState::Closed) {
+            self.writer = WriteState::Pending(f(sender));
+        }
+    }
+}
+
+impl<T> AsyncRead for WebSocketStream<T>
+where T: AsyncRead + AsyncWrite + Unpin + Send + 'static
+{
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.get_mut().inner_mut().reader).poll_read(cx, buf)
+    }
+}
+
+impl<T> AsyncWrite for WebSocketStream<T>
+where T: AsyncRead + AsyncWrite + Unpin + Send + 'static
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+        let inner = self.get_mut().inner_mut();
+        ready!(inner.poll_writer_ready(cx))?;
+        let data = buf[..buf.len().min(MAX_MESSAGE_SIZE)].to_vec();
+        let len = data.len();
+        inner.start_write(move |mut sender| {
+            async move {
+                let result = sender.send_binary_mut(data).await.map_err(to_io_error);
+                (sender, result)
+            }
+            .boxed()
+        });
+        Poll::Ready(Ok(len))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let inner = self.get_mut().inner_mut();
+        ready!(inner.poll_writer_ready(cx))?;
+        inner.start_write(|mut sender| {
+            async move {
+                let result = sender.flush().await.map_err(to_io_error);
+                (sender, result)
+            }
+            .boxed()
+        });
+        inner.poll_writer_ready(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let inner = self.get_mut().inner_mut();
+        loop {
+            match &mut inner.writer {
+                WriteState::Ready(_) => {
+                    if let WriteState::Ready(mut sender) = mem::replace(&mut inner.writer, WriteState::Closed) {
+                        inner.writer =
+                            WriteState::Closing(async move { sender.close().await.map_err(to_io_error) }.boxed());
+                    }
+                },
+                WriteState::Pending(_) => {
+                    ready!(inner.poll_writer_ready(cx))?;
+                },
+                WriteState::Closing(fut) => {
+                    let result = ready!(fut.as_mut().poll(cx));
+                    inner.writer = WriteState::Closed;
+                    return Poll::Ready(result);
+                },
+                WriteState::Closed => return Poll::Ready(Ok(())),
+            }
+        }
+    }
+}
+
+async fn client_handshake(socket: TcpSocket, host: String, path: String) -> io::Result<WebSocketStream<TcpSocket>> {
+    let mut client = handshake::Client::new(socket, &host, &path);
+    match client.handshake().await.map_err(to_io_error)? {
+        handshake::ServerResponse::Accepted { .. } => Ok(WebSocketStream::new(client.into_builder())),
+        handshake::ServerResponse::Redirect { status_code, location } => Err(io::Error::new(
+            io::ErrorKind::ConnectionRefused,
+            format!(
+                "WebSocket server redirected the connection ({}) to '{}'",
+                status_code, location
+            ),
+        )),
+        handshake::ServerResponse::Rejected { status_code } => Err(io::Error::new(
+            io::ErrorKind::ConnectionRefused,
+            format!("WebSocket server rejected the connection ({})", status_code),
+        )),
+    }
+}
+
+async fn server_handshake(socket: TcpSocket) -> io::Result<WebSocketStream<TcpSocket>> {
+    let mut server = handshake::Server::new(socket);
+    let key = {
+        let request = server.receive_request().await.map_err(to_io_error)?;
+        request.into_key()
+    };
+    let accept = handshake::server::Response::Accept {
+        key: &key,
+        protocol: None,
+    };
+    server.send_response(&accept).await.map_err(to_io_error)?;
+    Ok(WebSocketStream::new(server.into_builder()))
+}
+
+/// Splits a `.../tcp/<port>/ws` address into the TCP address and the WebSocket path. None is returned if the address
+/// is not a WebSocket address.
+fn split_ws_address(addr: &Multiaddr) -> Option<(Multiaddr, String)> {
+    let mut tcp_addr = addr.clone();
+    match tcp_addr.pop()? {
+        Protocol::Ws(path) => Some((tcp_addr, path.into_owned())),
+        _ => None,
+    }
+}
+
+/// Returns the value of the HTTP Host header for the given TCP/IP or DNS address
+fn host_header(addr: &Multiaddr) -> Option<String> {
+    let mut iter = addr.iter();
+    let host = match iter.next()? {
+        Protocol::Ip4(ip) => ip.to_string(),
+        Protocol::Ip6(ip) => format!("[{}]", ip),
+        Protocol::Dns4(name) | Protocol::Dns6(name) | Protocol::Dnsaddr(name) => name.into_owned(),
+        _ => return None,
+    };
+    match iter.next()? {
+        Protocol::Tcp(port) => Some(format!("{}:{}", host, port)),
+        _ => None,
+    }
+}
+
+fn handshake_timed_out() -> io::Error {
+    io::Error::new(io::ErrorKind::TimedOut, "WebSocket handshake timed out")
+}
+
+fn to_io_error<E: fmt::Display>(err: E) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, err.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::runtime;
+    use futures::{AsyncReadExt, AsyncWriteExt};
+
+    #[test]
+    fn split_ws_address_and_host() {
+        let addr = "/ip4/127.0.0.1/tcp/1234/ws".parse::<Multiaddr>().unwrap();
+        let (tcp_addr, path) = split_ws_address(&addr).unwrap();
+        assert_eq!(tcp_addr, "/ip4/127.0.0.1/tcp/1234".parse::<Multiaddr>().unwrap());
+        assert_eq!(path, "/");
+        assert_eq!(host_header(&tcp_addr).unwrap(), "127.0.0.1:1234");
+
+        let addr = "/dns4/tari.com/tcp/80".parse::<Multiaddr>().unwrap();
+        assert!(split_ws_address(&addr).is_none());
+        assert_eq!(host_header(&addr).unwrap(), "tari.com:80");
+    }
+
+    #[runtime::test_basic]
+    async fn listen_and_dial() {
+        let transport = WebSocketTransport::new();
+        let (mut listener, addr) = transport
+            .listen("/ip4/127.0.0.1/tcp/0/ws".parse().unwrap())
+            .unwrap()
+            .await
+            .unwrap();
+        assert!(split_ws_address(&addr).is_some());
+
+        let dial_fut = transport.dial(addr).unwrap();
+        let (dialed, accepted) = futures::join!(dial_fut, async move {
+            let (inbound_fut, _) = listener.next().await.unwrap().unwrap();
+            inbound_fut.await
+        });
+        let mut dialed = dialed.unwrap();
+        let mut accepted = accepted.unwrap();
+
+        dialed.write_all(b"Edgedancer").await.unwrap();
+        dialed.flush().await.unwrap();
+        let mut buf = [0u8; 10];
+        accepted.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"Edgedancer");
+
+        // Writes larger than the maximum message size are split into multiple messages
+        let data = vec![7u8; MAX_MESSAGE_SIZE + 10];
+        let mut buf = vec![0u8; data.len()];
+        let (write_result, read_result) = futures::join!(
+            async {
+                dialed.write_all(&data).await?;
+                dialed.flush().await
+            },
+            accepted.read_exact(&mut buf)
+        );
+        write_result.unwrap();
+        read_result.unwrap();
+        assert_eq!(buf, data);
+
+        accepted.write_all(b"Dawnshard").await.unwrap();
+        accepted.close().await.unwrap();
+        let mut buf = Vec::new();
+        dialed.read_to_end(&mut buf).await.unwrap();
+        assert_eq!(buf, b"Dawnshard");
+    }
+}
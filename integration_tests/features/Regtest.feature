@@ -0,0 +1,14 @@
+@regtest
+Feature: Regtest block generation
+
+  @critical
+  Scenario: Blocks are generated on demand without a miner
+    Given I have a base node NODE unconnected
+    When I generate 5 blocks on NODE
+    Then node NODE is at height 5
+
+  Scenario: Generated blocks propagate to connected nodes
+    Given I have a seed node SEED
+    And I have a base node NODE connected to all seed nodes
+    When I generate 3 blocks on SEED
+    Then all nodes are at height 3
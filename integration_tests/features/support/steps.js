@@ -295,6 +295,19 @@ When(/I mine (\d+) blocks using wallet (.*) on (.*)/, {timeout: 600*1000}, async
     }
 });
 
+When(/I generate (\d+) blocks on (.*)/, {timeout: 600*1000}, async function (numBlocks, name) {
+    let hashes = await this.getClient(name).generateBlocks(parseInt(numBlocks));
+    expect(hashes.length).to.equal(parseInt(numBlocks));
+    this.tipHeight += parseInt(numBlocks);
+});
+
+When(/I generate (\d+) blocks paying wallet (.*) on (.*)/, {timeout: 600*1000}, async function (numBlocks, walletName, nodeName) {
+    let walletInfo = await this.getWallet(walletName).getClient().identify();
+    let hashes = await this.getClient(nodeName).generateBlocks(parseInt(numBlocks), walletInfo.public_key);
+    expect(hashes.length).to.equal(parseInt(numBlocks));
+    this.tipHeight += parseInt(numBlocks);
+});
+
 When(/I merge mine (.*) blocks via (.*)/, {timeout: 600*1000}, async function (numBlocks, mmProxy) {
     for(let i=0;i<numBlocks;i++) {
         await this.mergeMineBlock(mmProxy,0);
@@ -177,6 +177,14 @@ class BaseNodeClient {
             });
     }
 
+    async generateBlocks(numBlocks, walletPublicKey) {
+        let result = await this.client.generateBlocks().sendMessage({
+            num_blocks: numBlocks,
+            wallet_public_key: walletPublicKey ? Buffer.from(walletPublicKey, 'hex') : Buffer.alloc(0),
+        });
+        return result.block_hashes;
+    }
+
     async getMinedCandidateBlock(weight,existingBlockTemplate) {
         let builder = new TransactionBuilder();
         let blockTemplate = existingBlockTemplate || await this.getBlockTemplate(weight);